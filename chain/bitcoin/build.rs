@@ -0,0 +1,7 @@
+fn main() {
+    println!("cargo:rerun-if-changed=proto");
+    tonic_build::configure()
+        .out_dir("src/protobuf")
+        .compile(&["proto/bitcoin.proto"], &["proto"])
+        .expect("Failed to compile Firehose Bitcoin proto(s)");
+}
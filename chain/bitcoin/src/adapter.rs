@@ -0,0 +1,188 @@
+use crate::{codec, data_source::DataSource, Chain};
+use graph::blockchain as bc;
+use graph::prelude::*;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Default)]
+pub struct TriggerFilter {
+    pub(crate) block_filter: BitcoinBlockFilter,
+    pub(crate) transaction_filter: BitcoinTransactionFilter,
+}
+
+impl bc::TriggerFilter<Chain> for TriggerFilter {
+    fn extend<'a>(&mut self, data_sources: impl Iterator<Item = &'a DataSource> + Clone) {
+        let TriggerFilter {
+            block_filter,
+            transaction_filter,
+        } = self;
+
+        block_filter.extend(BitcoinBlockFilter::from_data_sources(data_sources.clone()));
+        transaction_filter.extend(BitcoinTransactionFilter::from_data_sources(data_sources));
+    }
+
+    fn node_capabilities(&self) -> bc::EmptyNodeCapabilities<Chain> {
+        bc::EmptyNodeCapabilities::default()
+    }
+
+    fn extend_with_template(
+        &mut self,
+        _data_source: impl Iterator<Item = <Chain as bc::Blockchain>::DataSourceTemplate>,
+    ) {
+    }
+
+    fn to_firehose_filter(self) -> Vec<prost_types::Any> {
+        vec![]
+    }
+}
+
+/// BitcoinTransactionFilter matches the transactions with an output that
+/// pays to one of the addresses or is locked by one of the scripts of the
+/// transaction handlers. A transaction handler without an address or script
+/// makes it match every transaction
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BitcoinTransactionFilter {
+    every_transaction: bool,
+    addresses: HashSet<String>,
+    scripts: HashSet<Vec<u8>>,
+}
+
+impl BitcoinTransactionFilter {
+    pub fn matches(&self, tx: &codec::Transaction) -> bool {
+        self.every_transaction
+            || tx.vout.iter().any(|vout| {
+                vout.address()
+                    .map_or(false, |address| self.addresses.contains(address))
+                    || self.scripts.contains(&vout.script())
+            })
+    }
+
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        let mut filter = Self::default();
+        for handler in iter
+            .into_iter()
+            .flat_map(|data_source| data_source.mapping.transaction_handlers.iter())
+        {
+            match (&handler.address, &handler.script) {
+                (Some(address), _) => {
+                    filter.addresses.insert(address.clone());
+                }
+                (None, Some(script)) => {
+                    filter.scripts.insert(script.as_ref().to_vec());
+                }
+                (None, None) => filter.every_transaction = true,
+            }
+        }
+        filter
+    }
+
+    pub fn extend(&mut self, other: BitcoinTransactionFilter) {
+        self.every_transaction = self.every_transaction || other.every_transaction;
+        self.addresses.extend(other.addresses);
+        self.scripts.extend(other.scripts);
+    }
+}
+
+/// BitcoinBlockFilter will match every block if any data source has a block
+/// handler
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BitcoinBlockFilter {
+    pub trigger_every_block: bool,
+}
+
+impl BitcoinBlockFilter {
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            trigger_every_block: iter
+                .into_iter()
+                .any(|data_source| !data_source.mapping.block_handlers.is_empty()),
+        }
+    }
+
+    pub fn extend(&mut self, other: BitcoinBlockFilter) {
+        self.trigger_every_block = self.trigger_every_block || other.trigger_every_block;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use graph::{prelude::Link, semver::Version};
+
+    use crate::codec;
+    use crate::data_source::{DataSource, Mapping, Source, TransactionHandler};
+
+    use super::BitcoinTransactionFilter;
+
+    const ADDRESS: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+    const SCRIPT: &str = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+    const OTHER_SCRIPT: &str = "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac";
+
+    #[test]
+    fn transaction_filter_matches_addresses_and_scripts() {
+        let dss = vec![
+            new_datasource(Some(ADDRESS), None),
+            new_datasource(None, Some(OTHER_SCRIPT)),
+        ];
+
+        let filter = BitcoinTransactionFilter::from_data_sources(dss.iter());
+        assert!(filter.matches(&transaction(Some(ADDRESS), SCRIPT)));
+        assert!(filter.matches(&transaction(None, OTHER_SCRIPT)));
+        assert!(!filter.matches(&transaction(None, SCRIPT)));
+    }
+
+    #[test]
+    fn transaction_filter_without_address_matches_everything() {
+        let mut filter = BitcoinTransactionFilter::from_data_sources(
+            vec![new_datasource(Some(ADDRESS), None)].iter(),
+        );
+        assert!(!filter.matches(&transaction(None, SCRIPT)));
+
+        filter.extend(BitcoinTransactionFilter::from_data_sources(
+            vec![new_datasource(None, None)].iter(),
+        ));
+        assert!(filter.matches(&transaction(None, SCRIPT)));
+    }
+
+    fn transaction(address: Option<&str>, script: &str) -> codec::Transaction {
+        codec::Transaction {
+            vout: vec![codec::Vout {
+                value: 0.5,
+                n: 0,
+                script_pub_key: Some(codec::ScriptPubKey {
+                    hex: script.to_string(),
+                    address: address.unwrap_or_default().to_string(),
+                    ..Default::default()
+                }),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn new_datasource(address: Option<&str>, script: Option<&str>) -> DataSource {
+        DataSource {
+            kind: "bitcoin".into(),
+            network: None,
+            name: "".into(),
+            source: Source {
+                start_block: 10,
+                end_block: None,
+            },
+            mapping: Mapping {
+                api_version: Version::new(1, 2, 3),
+                language: "".into(),
+                entities: vec![],
+                block_handlers: vec![],
+                transaction_handlers: vec![TransactionHandler {
+                    handler: "handleTransaction".into(),
+                    address: address.map(str::to_string),
+                    script: script.map(|script| script.parse().unwrap()),
+                }],
+                runtime: Arc::new(vec![]),
+                link: Link { link: "".into() },
+            },
+            context: Arc::new(None),
+            creation_block: None,
+        }
+    }
+}
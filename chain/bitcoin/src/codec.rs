@@ -0,0 +1,139 @@
+#[rustfmt::skip]
+#[path = "protobuf/sf.bitcoin.r#type.v1.rs"]
+mod pbcodec;
+
+use graph::{
+    blockchain::Block as BlockchainBlock,
+    blockchain::{BlockPtr, BlockTime},
+    prelude::BlockNumber,
+};
+
+pub use pbcodec::*;
+
+/// How many satoshis one bitcoin has
+const SATOSHIS_PER_BITCOIN: f64 = 100_000_000.0;
+
+/// The outpoint that the input of a coinbase transaction refers to
+const COINBASE_VOUT: u32 = u32::MAX;
+
+/// Hashes, transaction ids and scripts are hex encoded
+fn decode_hex(hex: &str) -> Vec<u8> {
+    hex::decode(hex).expect("Firehose sends hex encoded hashes and scripts")
+}
+
+impl Block {
+    pub fn hash(&self) -> Vec<u8> {
+        decode_hex(&self.hash)
+    }
+
+    pub fn previous_hash(&self) -> Vec<u8> {
+        decode_hex(&self.previous_hash)
+    }
+
+    pub fn merkle_root(&self) -> Vec<u8> {
+        decode_hex(&self.merkle_root)
+    }
+}
+
+impl BlockchainBlock for Block {
+    fn number(&self) -> i32 {
+        BlockNumber::try_from(self.height).unwrap()
+    }
+
+    fn ptr(&self) -> BlockPtr {
+        BlockPtr {
+            hash: self.hash().into(),
+            number: self.number(),
+        }
+    }
+
+    fn parent_ptr(&self) -> Option<BlockPtr> {
+        if self.height == 0 {
+            return None;
+        }
+
+        Some(BlockPtr {
+            hash: self.previous_hash().into(),
+            number: self.number() - 1,
+        })
+    }
+
+    fn timestamp(&self) -> BlockTime {
+        BlockTime::since_epoch(self.time, 0)
+    }
+}
+
+impl Transaction {
+    pub fn txid(&self) -> Vec<u8> {
+        decode_hex(&self.txid)
+    }
+
+    /// The hash of the transaction including its witness data, which is the
+    /// same as the txid for transactions without witnesses
+    pub fn wtxid(&self) -> Vec<u8> {
+        decode_hex(&self.hash)
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.vin.first().map_or(false, |vin| vin.is_coinbase())
+    }
+}
+
+impl Vin {
+    pub fn is_coinbase(&self) -> bool {
+        !self.coinbase.is_empty()
+    }
+
+    /// The txid and output index of the output that the input spends. The
+    /// input of a coinbase transaction spends the null outpoint
+    pub fn outpoint(&self) -> (Vec<u8>, u32) {
+        if self.is_coinbase() {
+            return (vec![0; 32], COINBASE_VOUT);
+        }
+        (decode_hex(&self.txid), self.vout)
+    }
+
+    /// The unlocking script of the input, or the data that the miner put
+    /// into the input of a coinbase transaction
+    pub fn script_sig(&self) -> Vec<u8> {
+        if self.is_coinbase() {
+            return decode_hex(&self.coinbase);
+        }
+        self.script_sig
+            .as_ref()
+            .map(|script| decode_hex(&script.hex))
+            .unwrap_or_default()
+    }
+
+    pub fn witness(&self) -> Vec<Vec<u8>> {
+        self.txinwitness
+            .iter()
+            .map(|item| decode_hex(item))
+            .collect()
+    }
+}
+
+impl Vout {
+    /// Bitcoin Core reports values in bitcoin; every value is a whole
+    /// number of satoshis
+    pub fn satoshis(&self) -> u64 {
+        (self.value * SATOSHIS_PER_BITCOIN).round() as u64
+    }
+
+    /// The locking script of the output
+    pub fn script(&self) -> Vec<u8> {
+        self.script_pub_key
+            .as_ref()
+            .map(|script| decode_hex(&script.hex))
+            .unwrap_or_default()
+    }
+
+    /// The address that the output pays to. Only outputs with a standard
+    /// script have one
+    pub fn address(&self) -> Option<&str> {
+        self.script_pub_key
+            .as_ref()
+            .map(|script| script.address.as_str())
+            .filter(|address| !address.is_empty())
+    }
+}
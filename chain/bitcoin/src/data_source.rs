@@ -0,0 +1,433 @@
+use graph::anyhow::Context;
+use graph::blockchain::{Block, TriggerWithHandler};
+use graph::components::store::StoredDynamicDataSource;
+use graph::components::subgraph::InstanceDSTemplateInfo;
+use graph::data::subgraph::DataSourceContext;
+use graph::{
+    anyhow::{anyhow, Error},
+    blockchain::{self, Blockchain},
+    prelude::{async_trait, BlockNumber, CheapClone, Deserialize, Link, LinkResolver, Logger},
+    semver,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::chain::Chain;
+use crate::codec;
+use crate::script::Script;
+use crate::trigger::BitcoinTrigger;
+
+pub const BITCOIN_KIND: &str = "bitcoin";
+const BLOCK_HANDLER_KIND: &str = "block";
+const TRANSACTION_HANDLER_KIND: &str = "transaction";
+
+/// Runtime representation of a data source.
+#[derive(Clone, Debug)]
+pub struct DataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: Mapping,
+    pub context: Arc<Option<DataSourceContext>>,
+    pub creation_block: Option<BlockNumber>,
+}
+
+impl blockchain::DataSource<Chain> for DataSource {
+    fn from_template_info(
+        _info: InstanceDSTemplateInfo,
+        _template: &graph::data_source::DataSourceTemplate<Chain>,
+    ) -> Result<Self, Error> {
+        Err(anyhow!("Bitcoin subgraphs do not support templates"))
+    }
+
+    fn address(&self) -> Option<&[u8]> {
+        None
+    }
+
+    fn start_block(&self) -> BlockNumber {
+        self.source.start_block
+    }
+
+    fn handler_kinds(&self) -> HashSet<&str> {
+        let mut kinds = HashSet::new();
+
+        if self.handler_for_block().is_some() {
+            kinds.insert(BLOCK_HANDLER_KIND);
+        }
+
+        if !self.mapping.transaction_handlers.is_empty() {
+            kinds.insert(TRANSACTION_HANDLER_KIND);
+        }
+
+        kinds
+    }
+
+    fn end_block(&self) -> Option<BlockNumber> {
+        self.source.end_block
+    }
+
+    fn match_and_decode(
+        &self,
+        trigger: &<Chain as Blockchain>::TriggerData,
+        block: &Arc<<Chain as Blockchain>::Block>,
+        _logger: &Logger,
+    ) -> Result<Option<TriggerWithHandler<Chain>>, Error> {
+        if self.source.start_block > block.number() {
+            return Ok(None);
+        }
+
+        let handler = match trigger {
+            // A block trigger matches if a block handler is present.
+            BitcoinTrigger::Block(_) => match self.handler_for_block() {
+                Some(handler) => &handler.handler,
+                None => return Ok(None),
+            },
+            // A transaction trigger matches the first transaction handler
+            // whose address or script one of the outputs pays to.
+            BitcoinTrigger::Transaction(tx) => {
+                match self.handler_for_transaction(&tx.transaction) {
+                    Some(handler) => &handler.handler,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        Ok(Some(TriggerWithHandler::<Chain>::new(
+            trigger.cheap_clone(),
+            handler.clone(),
+            block.ptr(),
+            block.timestamp(),
+        )))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    fn context(&self) -> Arc<Option<DataSourceContext>> {
+        self.context.cheap_clone()
+    }
+
+    fn creation_block(&self) -> Option<BlockNumber> {
+        self.creation_block
+    }
+
+    fn is_duplicate_of(&self, other: &Self) -> bool {
+        let DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+
+            // The creation block is ignored for detection duplicate data sources.
+            creation_block: _,
+        } = self;
+
+        kind == &other.kind
+            && network == &other.network
+            && name == &other.name
+            && source == &other.source
+            && mapping.block_handlers == other.mapping.block_handlers
+            && mapping.transaction_handlers == other.mapping.transaction_handlers
+            && context == &other.context
+    }
+
+    fn as_stored_dynamic_data_source(&self) -> StoredDynamicDataSource {
+        unreachable!("Bitcoin subgraphs do not support templates")
+    }
+
+    fn from_stored_dynamic_data_source(
+        _template: &DataSourceTemplate,
+        _stored: StoredDynamicDataSource,
+    ) -> Result<Self, Error> {
+        Err(anyhow!("Bitcoin subgraphs do not support templates"))
+    }
+
+    fn validate(&self, _: &semver::Version) -> Vec<Error> {
+        let mut errors = Vec::new();
+
+        if self.kind != BITCOIN_KIND {
+            errors.push(anyhow!(
+                "data source has invalid `kind`, expected {} but found {}",
+                BITCOIN_KIND,
+                self.kind
+            ))
+        }
+
+        let Mapping {
+            block_handlers,
+            transaction_handlers,
+            ..
+        } = &self.mapping;
+
+        if block_handlers.is_empty() && transaction_handlers.is_empty() {
+            errors.push(anyhow!("data source does not define any handler"));
+        }
+
+        // Validate that there is no more than one block handler
+        if block_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated block handlers"));
+        }
+
+        for handler in transaction_handlers {
+            if handler.address.is_some() && handler.script.is_some() {
+                errors.push(anyhow!(
+                    "transaction handler {} can not filter by both `address` and `script`",
+                    handler.handler
+                ));
+            }
+        }
+
+        errors
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+}
+
+impl DataSource {
+    fn from_manifest(
+        kind: String,
+        network: Option<String>,
+        name: String,
+        source: Source,
+        mapping: Mapping,
+        context: Option<DataSourceContext>,
+    ) -> Result<Self, Error> {
+        // Data sources in the manifest are created "before genesis" so they have no creation block.
+        let creation_block = None;
+
+        Ok(DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context: Arc::new(context),
+            creation_block,
+        })
+    }
+
+    fn handler_for_block(&self) -> Option<&MappingBlockHandler> {
+        self.mapping.block_handlers.first()
+    }
+
+    fn handler_for_transaction(&self, tx: &codec::Transaction) -> Option<&TransactionHandler> {
+        self.mapping
+            .transaction_handlers
+            .iter()
+            .find(|handler| handler.matches(tx))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct UnresolvedDataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: UnresolvedMapping,
+    pub context: Option<DataSourceContext>,
+}
+
+#[async_trait]
+impl blockchain::UnresolvedDataSource<Chain> for UnresolvedDataSource {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSource, Error> {
+        let UnresolvedDataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+        } = self;
+
+        let mapping = mapping.resolve(resolver, logger).await.with_context(|| {
+            format!(
+                "failed to resolve data source {} with start_block {}",
+                name, source.start_block
+            )
+        })?;
+
+        DataSource::from_manifest(kind, network, name, source, mapping, context)
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+pub struct BaseDataSourceTemplate<M> {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub mapping: M,
+}
+
+pub type UnresolvedDataSourceTemplate = BaseDataSourceTemplate<UnresolvedMapping>;
+pub type DataSourceTemplate = BaseDataSourceTemplate<Mapping>;
+
+#[async_trait]
+impl blockchain::UnresolvedDataSourceTemplate<Chain> for UnresolvedDataSourceTemplate {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSourceTemplate, Error> {
+        let UnresolvedDataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        } = self;
+
+        let mapping = mapping
+            .resolve(resolver, logger)
+            .await
+            .with_context(|| format!("failed to resolve data source template {}", name))?;
+
+        Ok(DataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        })
+    }
+}
+
+impl blockchain::DataSourceTemplate<Chain> for DataSourceTemplate {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+
+    fn manifest_idx(&self) -> u32 {
+        unreachable!("bitcoin does not support dynamic data sources")
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedMapping {
+    pub api_version: String,
+    pub language: String,
+    pub entities: Vec<String>,
+    #[serde(default)]
+    pub block_handlers: Vec<MappingBlockHandler>,
+    #[serde(default)]
+    pub transaction_handlers: Vec<TransactionHandler>,
+    pub file: Link,
+}
+
+impl UnresolvedMapping {
+    pub async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+    ) -> Result<Mapping, Error> {
+        let UnresolvedMapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            transaction_handlers,
+            file: link,
+        } = self;
+
+        let api_version = semver::Version::parse(&api_version)?;
+
+        let module_bytes = resolver
+            .cat(logger, &link)
+            .await
+            .with_context(|| format!("failed to resolve mapping {}", link.link))?;
+
+        Ok(Mapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            transaction_handlers,
+            runtime: Arc::new(module_bytes),
+            link,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Mapping {
+    pub api_version: semver::Version,
+    pub language: String,
+    pub entities: Vec<String>,
+    pub block_handlers: Vec<MappingBlockHandler>,
+    pub transaction_handlers: Vec<TransactionHandler>,
+    pub runtime: Arc<Vec<u8>>,
+    pub link: Link,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingBlockHandler {
+    pub handler: String,
+}
+
+/// A handler for the transactions with an output that pays to `address`
+/// or is locked by `script`. A handler without either is called for every
+/// transaction
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct TransactionHandler {
+    pub handler: String,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub script: Option<Script>,
+}
+
+impl TransactionHandler {
+    pub fn matches(&self, tx: &codec::Transaction) -> bool {
+        match (&self.address, &self.script) {
+            (Some(address), _) => tx
+                .vout
+                .iter()
+                .any(|vout| vout.address() == Some(address.as_str())),
+            (None, Some(script)) => tx.vout.iter().any(|vout| vout.script() == script.as_ref()),
+            (None, None) => true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Source {
+    #[serde(default)]
+    pub(crate) start_block: BlockNumber,
+    pub(crate) end_block: Option<BlockNumber>,
+}
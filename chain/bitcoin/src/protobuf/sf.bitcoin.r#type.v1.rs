@@ -0,0 +1,87 @@
+// This file is @generated by prost-build.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Block {
+    #[prost(string, tag = "1")]
+    pub hash: ::prost::alloc::string::String,
+    #[prost(int64, tag = "6")]
+    pub height: i64,
+    #[prost(int32, tag = "7")]
+    pub version: i32,
+    #[prost(string, tag = "9")]
+    pub merkle_root: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "10")]
+    pub tx: ::prost::alloc::vec::Vec<Transaction>,
+    #[prost(int64, tag = "11")]
+    pub time: i64,
+    #[prost(int64, tag = "12")]
+    pub mediantime: i64,
+    #[prost(uint32, tag = "13")]
+    pub nonce: u32,
+    #[prost(string, tag = "14")]
+    pub bits: ::prost::alloc::string::String,
+    #[prost(string, tag = "18")]
+    pub previous_hash: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Transaction {
+    #[prost(string, tag = "2")]
+    pub txid: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "7")]
+    pub version: u32,
+    #[prost(uint32, tag = "8")]
+    pub locktime: u32,
+    #[prost(message, repeated, tag = "9")]
+    pub vin: ::prost::alloc::vec::Vec<Vin>,
+    #[prost(message, repeated, tag = "10")]
+    pub vout: ::prost::alloc::vec::Vec<Vout>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Vin {
+    #[prost(string, tag = "1")]
+    pub txid: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub vout: u32,
+    #[prost(message, optional, tag = "3")]
+    pub script_sig: ::core::option::Option<ScriptSig>,
+    #[prost(uint32, tag = "4")]
+    pub sequence: u32,
+    #[prost(string, repeated, tag = "5")]
+    pub txinwitness: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "6")]
+    pub coinbase: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Vout {
+    #[prost(double, tag = "1")]
+    pub value: f64,
+    #[prost(uint32, tag = "2")]
+    pub n: u32,
+    #[prost(message, optional, tag = "3")]
+    pub script_pub_key: ::core::option::Option<ScriptPubKey>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScriptSig {
+    #[prost(string, tag = "1")]
+    pub asm: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub hex: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScriptPubKey {
+    #[prost(string, tag = "1")]
+    pub asm: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub hex: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub address: ::prost::alloc::string::String,
+}
@@ -0,0 +1,137 @@
+use crate::codec;
+use crate::trigger::TransactionWithBlock;
+use graph::runtime::gas::GasCounter;
+use graph::runtime::{asc_new, AscHeap, AscPtr, HostExportError, ToAscObj};
+use graph_runtime_wasm::asc_abi::class::{Array, Uint8Array};
+
+pub(crate) use super::generated::*;
+
+impl ToAscObj<AscBytesArray> for Vec<Vec<u8>> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBytesArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x.as_slice(), gas))
+            .collect::<Result<Vec<AscPtr<Uint8Array>>, _>>()?;
+        Ok(AscBytesArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscInputArray> for Vec<codec::Vin> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscInputArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscInputArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscOutputArray> for Vec<codec::Vout> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscOutputArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscOutputArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscBlock> for codec::Block {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBlock, HostExportError> {
+        Ok(AscBlock {
+            height: self.height,
+            timestamp: self.time,
+            median_time: self.mediantime,
+            version: self.version,
+            nonce: self.nonce,
+            hash: asc_new(heap, self.hash().as_slice(), gas)?,
+            previous_hash: asc_new(heap, self.previous_hash().as_slice(), gas)?,
+            merkle_root: asc_new(heap, self.merkle_root().as_slice(), gas)?,
+            bits: asc_new(heap, self.bits.as_str(), gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscInput> for codec::Vin {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscInput, HostExportError> {
+        let (txid, vout) = self.outpoint();
+        let outpoint = AscOutPoint {
+            txid: asc_new(heap, txid.as_slice(), gas)?,
+            vout,
+        };
+
+        Ok(AscInput {
+            outpoint: AscPtr::alloc_obj(outpoint, heap, gas)?,
+            script_sig: asc_new(heap, self.script_sig().as_slice(), gas)?,
+            witness: asc_new(heap, &self.witness(), gas)?,
+            sequence: self.sequence,
+            is_coinbase: self.is_coinbase(),
+        })
+    }
+}
+
+impl ToAscObj<AscOutput> for codec::Vout {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscOutput, HostExportError> {
+        let script_pub_key = self.script_pub_key.clone().unwrap_or_default();
+        let script = AscScript {
+            bytes: asc_new(heap, self.script().as_slice(), gas)?,
+            asm: asc_new(heap, script_pub_key.asm.as_str(), gas)?,
+            r#type: asc_new(heap, script_pub_key.r#type.as_str(), gas)?,
+            address: match self.address() {
+                Some(address) => asc_new(heap, address, gas)?,
+                None => AscPtr::null(),
+            },
+        };
+
+        Ok(AscOutput {
+            value: self.satoshis(),
+            index: self.n,
+            script: AscPtr::alloc_obj(script, heap, gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscTransaction> for TransactionWithBlock {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscTransaction, HostExportError> {
+        let tx = &self.transaction;
+
+        Ok(AscTransaction {
+            version: tx.version,
+            lock_time: tx.locktime,
+            index: self.index,
+            txid: asc_new(heap, tx.txid().as_slice(), gas)?,
+            hash: asc_new(heap, tx.wtxid().as_slice(), gas)?,
+            inputs: asc_new(heap, &tx.vin, gas)?,
+            outputs: asc_new(heap, &tx.vout, gas)?,
+            block: asc_new(heap, self.block.as_ref(), gas)?,
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use graph::runtime::{AscIndexId, AscPtr, AscType, DeterministicHostError, IndexForAscTypeId};
+use graph::semver::Version;
+use graph_runtime_derive::AscType;
+use graph_runtime_wasm::asc_abi::class::{Array, AscString, Uint8Array};
+
+pub struct AscBytesArray(pub(crate) Array<AscPtr<Uint8Array>>);
+
+impl AscType for AscBytesArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscBytesArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinArrayBytes;
+}
+
+pub struct AscInputArray(pub(crate) Array<AscPtr<AscInput>>);
+
+impl AscType for AscInputArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscInputArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinArrayInput;
+}
+
+pub struct AscOutputArray(pub(crate) Array<AscPtr<AscOutput>>);
+
+impl AscType for AscOutputArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscOutputArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinArrayOutput;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscBlock {
+    pub height: i64,
+    pub timestamp: i64,
+    pub median_time: i64,
+    pub version: i32,
+    pub nonce: u32,
+    pub hash: AscPtr<Uint8Array>,
+    pub previous_hash: AscPtr<Uint8Array>,
+    pub merkle_root: AscPtr<Uint8Array>,
+    pub bits: AscPtr<AscString>,
+}
+
+impl AscIndexId for AscBlock {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinBlock;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscOutPoint {
+    pub txid: AscPtr<Uint8Array>,
+    pub vout: u32,
+}
+
+impl AscIndexId for AscOutPoint {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinOutPoint;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscScript {
+    pub bytes: AscPtr<Uint8Array>,
+    pub asm: AscPtr<AscString>,
+    pub r#type: AscPtr<AscString>,
+    pub address: AscPtr<AscString>,
+}
+
+impl AscIndexId for AscScript {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinScript;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscInput {
+    pub outpoint: AscPtr<AscOutPoint>,
+    pub script_sig: AscPtr<Uint8Array>,
+    pub witness: AscPtr<AscBytesArray>,
+    pub sequence: u32,
+    pub is_coinbase: bool,
+}
+
+impl AscIndexId for AscInput {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinInput;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscOutput {
+    pub value: u64,
+    pub index: u32,
+    pub script: AscPtr<AscScript>,
+}
+
+impl AscIndexId for AscOutput {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinOutput;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscTransaction {
+    pub version: u32,
+    pub lock_time: u32,
+    pub index: u32,
+    pub txid: AscPtr<Uint8Array>,
+    pub hash: AscPtr<Uint8Array>,
+    pub inputs: AscPtr<AscInputArray>,
+    pub outputs: AscPtr<AscOutputArray>,
+    pub block: AscPtr<AscBlock>,
+}
+
+impl AscIndexId for AscTransaction {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinTransaction;
+}
@@ -0,0 +1,3 @@
+pub mod abi;
+
+mod generated;
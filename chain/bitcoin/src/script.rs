@@ -0,0 +1,86 @@
+use std::{
+    fmt::{Debug, Display, Formatter},
+    str::FromStr,
+};
+
+use graph::anyhow;
+use serde::{de::Visitor, Deserialize};
+
+/// The locking script of an output, written as a hex string in manifests.
+/// The `0x` prefix is optional
+#[derive(Clone, Hash, PartialEq, Eq)]
+pub struct Script(Vec<u8>);
+
+struct ScriptVisitor;
+
+impl Debug for Script {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Display for Script {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{}", hex::encode(&self.0))
+    }
+}
+
+impl AsRef<[u8]> for Script {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl FromStr for Script {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s.trim_start_matches("0x"))?;
+        if bytes.is_empty() {
+            anyhow::bail!("script must not be empty");
+        }
+        Ok(Script(bytes))
+    }
+}
+
+impl<'de> Deserialize<'de> for Script {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ScriptVisitor)
+    }
+}
+
+impl<'de> Visitor<'de> for ScriptVisitor {
+    type Value = Script;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Script::from_str(v).map_err(|_| {
+            serde::de::Error::invalid_value(serde::de::Unexpected::Str(v), &"hex encoded script")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_script() {
+        let p2wpkh = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+        let script: Script = p2wpkh.parse().unwrap();
+        assert_eq!(format!("0x{}", p2wpkh), script.to_string());
+        assert_eq!(script, format!("0x{}", p2wpkh).parse().unwrap());
+
+        assert!("not hex".parse::<Script>().is_err());
+        assert!("0x".parse::<Script>().is_err());
+    }
+}
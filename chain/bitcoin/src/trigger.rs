@@ -0,0 +1,143 @@
+use graph::blockchain::Block;
+use graph::blockchain::MappingTriggerTrait;
+use graph::blockchain::TriggerData;
+use graph::derive::CheapClone;
+use graph::prelude::BlockNumber;
+use graph::runtime::asc_new;
+use graph::runtime::gas::GasCounter;
+use graph::runtime::AscHeap;
+use graph::runtime::AscPtr;
+use graph::runtime::HostExportError;
+use graph_runtime_wasm::module::ToAscPtr;
+use std::{cmp::Ordering, sync::Arc};
+
+use crate::codec;
+
+// Logging the block is too verbose, so this strips the block from the trigger for Debug.
+impl std::fmt::Debug for BitcoinTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[allow(unused)]
+        #[derive(Debug)]
+        pub enum MappingTriggerWithoutBlock {
+            Block,
+            Transaction { txid: String },
+        }
+
+        let trigger_without_block = match self {
+            BitcoinTrigger::Block(_) => MappingTriggerWithoutBlock::Block,
+            BitcoinTrigger::Transaction(tx) => MappingTriggerWithoutBlock::Transaction {
+                txid: tx.transaction.txid.clone(),
+            },
+        };
+
+        write!(f, "{:?}", trigger_without_block)
+    }
+}
+
+impl ToAscPtr for BitcoinTrigger {
+    fn to_asc_ptr<H: AscHeap>(
+        self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<()>, HostExportError> {
+        Ok(match self {
+            BitcoinTrigger::Block(block) => asc_new(heap, block.as_ref(), gas)?.erase(),
+            BitcoinTrigger::Transaction(tx) => asc_new(heap, tx.as_ref(), gas)?.erase(),
+        })
+    }
+}
+
+#[derive(Clone, CheapClone)]
+pub enum BitcoinTrigger {
+    Block(Arc<codec::Block>),
+    Transaction(Arc<TransactionWithBlock>),
+}
+
+impl PartialEq for BitcoinTrigger {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Block(a), Self::Block(b)) => a == b,
+            (Self::Transaction(a), Self::Transaction(b)) => {
+                a.block.hash == b.block.hash && a.index == b.index
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for BitcoinTrigger {}
+
+impl BitcoinTrigger {
+    fn block(&self) -> &codec::Block {
+        match self {
+            BitcoinTrigger::Block(block) => block,
+            BitcoinTrigger::Transaction(tx) => &tx.block,
+        }
+    }
+
+    pub fn block_number(&self) -> BlockNumber {
+        self.block().number()
+    }
+
+    fn error_context(&self) -> std::string::String {
+        let block = self.block();
+        match self {
+            BitcoinTrigger::Block(..) => {
+                format!("Block #{} ({})", block.number(), block.hash)
+            }
+            BitcoinTrigger::Transaction(tx) => {
+                format!(
+                    "Transaction {}, block #{} ({})",
+                    tx.transaction.txid,
+                    block.number(),
+                    block.hash
+                )
+            }
+        }
+    }
+}
+
+impl Ord for BitcoinTrigger {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            // Keep the order when comparing two block triggers
+            (Self::Block(..), Self::Block(..)) => Ordering::Equal,
+
+            // Block triggers always come last
+            (Self::Block(..), _) => Ordering::Greater,
+            (_, Self::Block(..)) => Ordering::Less,
+
+            // Transaction triggers are ordered by their position in the block
+            (Self::Transaction(a), Self::Transaction(b)) => a.index.cmp(&b.index),
+        }
+    }
+}
+
+impl PartialOrd for BitcoinTrigger {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl TriggerData for BitcoinTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+
+    fn address_match(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+impl MappingTriggerTrait for BitcoinTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+}
+
+/// A transaction together with its position in its block
+pub struct TransactionWithBlock {
+    pub transaction: codec::Transaction,
+    pub index: u32,
+    pub block: Arc<codec::Block>,
+}
@@ -3,7 +3,7 @@ use std::collections::HashSet;
 use prost::Message;
 use prost_types::Any;
 
-use crate::{data_source::DataSource, Chain};
+use crate::{codec, data_source::DataSource, Chain};
 use graph::blockchain as bc;
 use graph::firehose::EventTypeFilter;
 use graph::prelude::*;
@@ -14,6 +14,7 @@ const EVENT_TYPE_FILTER_TYPE_URL: &str =
 #[derive(Clone, Debug, Default)]
 pub struct TriggerFilter {
     pub(crate) event_type_filter: CosmosEventTypeFilter,
+    pub(crate) wasm_event_filter: CosmosWasmEventFilter,
     pub(crate) block_filter: CosmosBlockFilter,
 }
 
@@ -21,6 +22,8 @@ impl bc::TriggerFilter<Chain> for TriggerFilter {
     fn extend<'a>(&mut self, data_sources: impl Iterator<Item = &'a DataSource> + Clone) {
         self.event_type_filter
             .extend_from_data_sources(data_sources.clone());
+        self.wasm_event_filter
+            .extend_from_data_sources(data_sources.clone());
         self.block_filter.extend_from_data_sources(data_sources);
     }
 
@@ -39,12 +42,15 @@ impl bc::TriggerFilter<Chain> for TriggerFilter {
             return vec![];
         }
 
-        if self.event_type_filter.event_types.is_empty() {
+        let mut event_types = self.event_type_filter.event_types;
+        event_types.extend(self.wasm_event_filter.event_types());
+
+        if event_types.is_empty() {
             return vec![];
         }
 
         let filter = EventTypeFilter {
-            event_types: Vec::from_iter(self.event_type_filter.event_types),
+            event_types: Vec::from_iter(event_types),
         };
 
         vec![Any {
@@ -73,6 +79,53 @@ impl CosmosEventTypeFilter {
     }
 }
 
+/// The wasm events that data sources handle, either for any contract or
+/// only for the contract of the data source
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CosmosWasmEventFilter {
+    pub any_contract: HashSet<EventType>,
+    pub contracts: HashSet<(String, EventType)>,
+}
+
+impl CosmosWasmEventFilter {
+    /// Returns the address of the contract that emitted the event if a data
+    /// source handles it
+    pub(crate) fn matches<'e>(&self, event: &'e codec::Event) -> Option<&'e str> {
+        let address = event.wasm_contract_address()?;
+
+        if self.any_contract.contains(&event.event_type)
+            || self
+                .contracts
+                .contains(&(address.to_string(), event.event_type.clone()))
+        {
+            Some(address)
+        } else {
+            None
+        }
+    }
+
+    fn event_types(&self) -> impl Iterator<Item = EventType> + '_ {
+        self.any_contract
+            .iter()
+            .chain(self.contracts.iter().map(|(_, event_type)| event_type))
+            .cloned()
+    }
+
+    fn extend_from_data_sources<'a>(&mut self, data_sources: impl Iterator<Item = &'a DataSource>) {
+        for (event_type, address) in data_sources.flat_map(DataSource::wasm_events) {
+            match address {
+                Some(address) => {
+                    self.contracts
+                        .insert((address.to_string(), event_type.to_string()));
+                }
+                None => {
+                    self.any_contract.insert(event_type.to_string());
+                }
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub(crate) struct CosmosBlockFilter {
     pub trigger_every_block: bool,
@@ -125,12 +178,57 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_wasm_event_filter() {
+        let event = |event_type: &str, address: Option<&str>| codec::Event {
+            event_type: event_type.to_string(),
+            attributes: address
+                .map(|address| codec::EventAttribute {
+                    key: "_contract_address".to_string(),
+                    value: address.to_string(),
+                    index: false,
+                })
+                .into_iter()
+                .collect(),
+        };
+
+        let filter = CosmosWasmEventFilter {
+            any_contract: HashSet::from_iter(["wasm".to_string()]),
+            contracts: HashSet::from_iter([("contract_1".to_string(), "wasm-swap".to_string())]),
+        };
+
+        assert_eq!(
+            Some("contract_2"),
+            filter.matches(&event("wasm", Some("contract_2")))
+        );
+        assert_eq!(
+            Some("contract_1"),
+            filter.matches(&event("wasm-swap", Some("contract_1")))
+        );
+        assert_eq!(
+            None,
+            filter.matches(&event("wasm-swap", Some("contract_2")))
+        );
+        // Events without a contract address are not wasm events
+        assert_eq!(None, filter.matches(&event("wasm", None)));
+
+        let mut trigger_filter = TriggerFilter::test_new(false, &["transfer"]);
+        trigger_filter.wasm_event_filter = filter;
+        let mut decoded_filter = decode_filter(trigger_filter.to_firehose_filter()).unwrap();
+        decoded_filter.event_types.sort();
+        assert_eq!(
+            event_type_filter_with(&["transfer", "wasm", "wasm-swap"]),
+            decoded_filter
+        );
+    }
+
     impl TriggerFilter {
         pub(crate) fn test_new(trigger_every_block: bool, event_types: &[&str]) -> TriggerFilter {
             TriggerFilter {
                 event_type_filter: CosmosEventTypeFilter {
                     event_types: event_types.iter().map(ToString::to_string).collect(),
                 },
+                wasm_event_filter: CosmosWasmEventFilter::default(),
                 block_filter: CosmosBlockFilter {
                     trigger_every_block,
                 },
@@ -1,5 +1,5 @@
 use graph::blockchain::firehose_block_ingestor::FirehoseBlockIngestor;
-use graph::blockchain::{BlockIngestor, NoopDecoderHook};
+use graph::blockchain::{BlockIngestor, IngestionMode, NoopDecoderHook};
 use graph::components::adapter::ChainId;
 use graph::env::EnvVars;
 use graph::prelude::MetricsRegistry;
@@ -115,6 +115,7 @@ impl Blockchain for Chain {
         start_blocks: Vec<BlockNumber>,
         filter: Arc<Self::TriggerFilter>,
         unified_api_version: UnifiedMappingApiVersion,
+        _ingestion: IngestionMode,
     ) -> Result<Box<dyn BlockStream<Self>>, Error> {
         let adapter = self
             .triggers_adapter(
@@ -233,14 +234,21 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
                     .unwrap()
                     .events
                     .iter()
-                    .filter_map(|e| {
-                        filter_event_trigger(
+                    .flat_map(|e| {
+                        let event_trigger = filter_event_trigger(
                             filter,
                             e.clone(),
                             &header_only_block,
                             Some(build_tx_context(tx)),
                             EventOrigin::DeliverTx,
-                        )
+                        );
+                        let wasm_event_trigger = filter_wasm_event_trigger(
+                            filter,
+                            e,
+                            &header_only_block,
+                            build_tx_context(tx),
+                        );
+                        event_trigger.into_iter().chain(wasm_event_trigger)
                     })
                     .collect::<Vec<_>>()
             }))
@@ -321,6 +329,24 @@ fn filter_event_trigger(
     }
 }
 
+/// Returns a new wasm event trigger only if the given event was emitted by a
+/// CosmWasm contract and matches the wasm event filter.
+fn filter_wasm_event_trigger(
+    filter: &TriggerFilter,
+    event: &codec::Event,
+    block: &codec::HeaderOnlyBlock,
+    tx_context: codec::TransactionContext,
+) -> Option<CosmosTrigger> {
+    let contract_address = filter.wasm_event_filter.matches(event)?;
+
+    Some(CosmosTrigger::with_wasm_event(
+        event.clone(),
+        contract_address.to_string(),
+        block.clone(),
+        tx_context,
+    ))
+}
+
 fn build_tx_context(tx: &codec::TxResult) -> codec::TransactionContext {
     codec::TransactionContext {
         hash: tx.hash.clone(),
@@ -8,6 +8,14 @@ use graph::{
 
 use std::convert::TryFrom;
 
+/// The type of the event with the attributes that a CosmWasm contract adds
+/// to its response
+pub const WASM_EVENT_TYPE: &str = "wasm";
+/// The prefix of the types of the custom events of CosmWasm contracts
+pub const WASM_CUSTOM_EVENT_TYPE_PREFIX: &str = "wasm-";
+/// The attribute that CosmWasm adds to all events of a contract
+const WASM_CONTRACT_ADDRESS_ATTRIBUTE: &str = "_contract_address";
+
 impl Block {
     pub fn header(&self) -> Result<&Header, Error> {
         self.header
@@ -162,6 +170,41 @@ impl EventData {
     }
 }
 
+impl Event {
+    /// The value of the first attribute with the given key
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.key == key)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /// The address of the CosmWasm contract that emitted the event, for
+    /// events of type `wasm` and `wasm-<custom type>`
+    pub fn wasm_contract_address(&self) -> Option<&str> {
+        if self.event_type != WASM_EVENT_TYPE
+            && !self.event_type.starts_with(WASM_CUSTOM_EVENT_TYPE_PREFIX)
+        {
+            return None;
+        }
+        self.attribute(WASM_CONTRACT_ADDRESS_ATTRIBUTE)
+    }
+}
+
+impl WasmEventData {
+    pub fn event(&self) -> Result<&Event, Error> {
+        self.event
+            .as_ref()
+            .ok_or_else(|| anyhow!("wasm event data missing event field"))
+    }
+
+    pub fn block(&self) -> Result<&HeaderOnlyBlock, Error> {
+        self.block
+            .as_ref()
+            .ok_or_else(|| anyhow!("wasm event data missing block field"))
+    }
+}
+
 impl TransactionData {
     pub fn tx_result(&self) -> Result<&TxResult, Error> {
         self.tx
@@ -23,6 +23,7 @@ const BLOCK_HANDLER_KIND: &str = "block";
 const EVENT_HANDLER_KIND: &str = "event";
 const TRANSACTION_HANDLER_KIND: &str = "transaction";
 const MESSAGE_HANDLER_KIND: &str = "message";
+const WASM_EVENT_HANDLER_KIND: &str = "wasm_event";
 
 const DYNAMIC_DATA_SOURCE_ERROR: &str = "Cosmos subgraphs do not support dynamic data sources";
 const TEMPLATE_ERROR: &str = "Cosmos subgraphs do not support templates";
@@ -65,6 +66,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             event_handlers,
             transaction_handlers,
             message_handlers,
+            wasm_event_handlers,
             ..
         } = &self.mapping;
 
@@ -84,6 +86,10 @@ impl blockchain::DataSource<Chain> for DataSource {
             kinds.insert(MESSAGE_HANDLER_KIND);
         }
 
+        if !wasm_event_handlers.is_empty() {
+            kinds.insert(WASM_EVENT_HANDLER_KIND);
+        }
+
         kinds
     }
 
@@ -125,6 +131,13 @@ impl blockchain::DataSource<Chain> for DataSource {
                     None => return Ok(None),
                 }
             }
+
+            CosmosTrigger::WasmEvent(wasm_event_data) => match self
+                .handler_for_wasm_event(wasm_event_data.event()?, &wasm_event_data.contract_address)
+            {
+                Some(handler) => handler.handler,
+                None => return Ok(None),
+            },
         };
 
         Ok(Some(TriggerWithHandler::<Chain>::new(
@@ -180,6 +193,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             && mapping.event_handlers == other.mapping.event_handlers
             && mapping.transaction_handlers == other.mapping.transaction_handlers
             && mapping.message_handlers == other.mapping.message_handlers
+            && mapping.wasm_event_handlers == other.mapping.wasm_event_handlers
             && context == &other.context
     }
 
@@ -252,6 +266,24 @@ impl blockchain::DataSource<Chain> for DataSource {
             }
         }
 
+        // Ensure wasm event handlers are for wasm events and that each combination of event
+        // type and attributes has only one handler
+        let mut wasm_events = HashSet::with_capacity(self.mapping.wasm_event_handlers.len());
+        for wasm_event_handler in self.mapping.wasm_event_handlers.iter() {
+            let event_type = &wasm_event_handler.event;
+            if event_type != codec::WASM_EVENT_TYPE
+                && !event_type.starts_with(codec::WASM_CUSTOM_EVENT_TYPE_PREFIX)
+            {
+                errors.push(invalid_wasm_event_type(event_type));
+            }
+
+            let mut attributes = wasm_event_handler.attributes.clone();
+            attributes.sort();
+            if !wasm_events.insert((event_type, attributes)) {
+                errors.push(duplicate_wasm_event(event_type));
+            }
+        }
+
         errors
     }
 
@@ -323,6 +355,32 @@ impl DataSource {
             .cloned()
     }
 
+    /// Returns the first wasm event handler for the event type whose attribute keys the event
+    /// has, if the event was emitted by the contract of the data source
+    fn handler_for_wasm_event(
+        &self,
+        event: &codec::Event,
+        contract_address: &str,
+    ) -> Option<MappingWasmEventHandler> {
+        if let Some(address) = &self.source.address {
+            if address != contract_address {
+                return None;
+            }
+        }
+
+        self.mapping
+            .wasm_event_handlers
+            .iter()
+            .find(|handler| {
+                event.event_type == handler.event
+                    && handler
+                        .attributes
+                        .iter()
+                        .all(|key| event.attribute(key).is_some())
+            })
+            .cloned()
+    }
+
     pub(crate) fn has_block_handler(&self) -> bool {
         !self.mapping.block_handlers.is_empty()
     }
@@ -334,6 +392,15 @@ impl DataSource {
             .iter()
             .map(|handler| handler.event.as_str())
     }
+
+    /// Return an iterator over the event types of wasm event handlers, together with the
+    /// contract address of the data source.
+    pub(crate) fn wasm_events(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.mapping
+            .wasm_event_handlers
+            .iter()
+            .map(|handler| (handler.event.as_str(), self.source.address.as_deref()))
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
@@ -433,6 +500,8 @@ pub struct UnresolvedMapping {
     pub transaction_handlers: Vec<MappingTransactionHandler>,
     #[serde(default)]
     pub message_handlers: Vec<MappingMessageHandler>,
+    #[serde(default)]
+    pub wasm_event_handlers: Vec<MappingWasmEventHandler>,
     pub file: Link,
 }
 
@@ -450,6 +519,7 @@ impl UnresolvedMapping {
             event_handlers,
             transaction_handlers,
             message_handlers,
+            wasm_event_handlers,
             file: link,
         } = self;
 
@@ -468,6 +538,7 @@ impl UnresolvedMapping {
             event_handlers: event_handlers.clone(),
             transaction_handlers: transaction_handlers.clone(),
             message_handlers: message_handlers.clone(),
+            wasm_event_handlers,
             runtime: Arc::new(module_bytes),
             link,
         })
@@ -483,6 +554,7 @@ pub struct Mapping {
     pub event_handlers: Vec<MappingEventHandler>,
     pub transaction_handlers: Vec<MappingTransactionHandler>,
     pub message_handlers: Vec<MappingMessageHandler>,
+    pub wasm_event_handlers: Vec<MappingWasmEventHandler>,
     pub runtime: Arc<Vec<u8>>,
     pub link: Link,
 }
@@ -510,9 +582,28 @@ pub struct MappingMessageHandler {
     pub handler: String,
 }
 
+/// A handler for the events of CosmWasm contracts with the given type that
+/// have all of the given attribute keys
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingWasmEventHandler {
+    #[serde(default = "default_wasm_event_type")]
+    pub event: String,
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    pub handler: String,
+}
+
+fn default_wasm_event_type() -> String {
+    codec::WASM_EVENT_TYPE.to_string()
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Source {
+    /// The address of the CosmWasm contract whose events trigger wasm event
+    /// handlers. Without it, the events of all contracts do
+    #[serde(default)]
+    pub address: Option<String>,
     #[serde(default)]
     pub start_block: BlockNumber,
     pub(crate) end_block: Option<BlockNumber>,
@@ -545,6 +636,22 @@ fn combined_origins_err(event_type: &str) -> Error {
     )
 }
 
+fn invalid_wasm_event_type(event_type: &str) -> Error {
+    anyhow!(
+        "data source has a wasm event handler for event type {}, but wasm event types are `{}` or start with `{}`",
+        event_type,
+        codec::WASM_EVENT_TYPE,
+        codec::WASM_CUSTOM_EVENT_TYPE_PREFIX,
+    )
+}
+
+fn duplicate_wasm_event(event_type: &str) -> Error {
+    anyhow!(
+        "data source has more than one wasm event handler for event type {} with the same attributes",
+        event_type
+    )
+}
+
 fn duplicate_url_type(message: &str) -> Error {
     anyhow!(
         "data source has more than one message handler for message {} ",
@@ -662,6 +769,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wasm_event_handlers() {
+        let handler = |event: &str, attributes: &[&str]| MappingWasmEventHandler {
+            event: event.to_string(),
+            attributes: attributes.iter().map(|key| key.to_string()).collect(),
+            handler: "handler".to_string(),
+        };
+
+        let cases = [
+            (
+                DataSource::with_wasm_event_handlers(vec![
+                    handler("wasm", &["action"]),
+                    handler("wasm", &["action", "amount"]),
+                    handler("wasm-transfer", &[]),
+                ]),
+                vec![],
+            ),
+            (
+                DataSource::with_wasm_event_handlers(vec![
+                    handler("wasm", &["action", "amount"]),
+                    handler("wasm", &["amount", "action"]),
+                    handler("transfer", &[]),
+                ]),
+                vec![
+                    duplicate_wasm_event("wasm"),
+                    invalid_wasm_event_type("transfer"),
+                ],
+            ),
+        ];
+
+        for (data_source, errors) in &cases {
+            let validation_errors = data_source.validate(&LATEST_VERSION);
+
+            assert_eq!(errors.len(), validation_errors.len());
+
+            for error in errors.iter() {
+                assert!(
+                    validation_errors
+                        .iter()
+                        .any(|validation_error| validation_error.to_string() == error.to_string()),
+                    r#"expected "{}" to be in validation errors, but it wasn't"#,
+                    error
+                );
+            }
+        }
+    }
+
     impl DataSource {
         fn with_event_handlers(event_handlers: Vec<MappingEventHandler>) -> DataSource {
             DataSource {
@@ -669,6 +823,7 @@ mod tests {
                 network: None,
                 name: "Test".to_string(),
                 source: Source {
+                    address: None,
                     start_block: 1,
                     end_block: None,
                 },
@@ -680,6 +835,7 @@ mod tests {
                     event_handlers,
                     transaction_handlers: vec![],
                     message_handlers: vec![],
+                    wasm_event_handlers: vec![],
                     runtime: Arc::new(vec![]),
                     link: "test".to_string().into(),
                 },
@@ -694,6 +850,7 @@ mod tests {
                 network: None,
                 name: "Test".to_string(),
                 source: Source {
+                    address: None,
                     start_block: 1,
                     end_block: None,
                 },
@@ -705,6 +862,7 @@ mod tests {
                     event_handlers: vec![],
                     transaction_handlers: vec![],
                     message_handlers,
+                    wasm_event_handlers: vec![],
                     runtime: Arc::new(vec![]),
                     link: "test".to_string().into(),
                 },
@@ -712,6 +870,14 @@ mod tests {
                 creation_block: None,
             }
         }
+
+        fn with_wasm_event_handlers(
+            wasm_event_handlers: Vec<MappingWasmEventHandler>,
+        ) -> DataSource {
+            let mut data_source = DataSource::with_event_handlers(vec![]);
+            data_source.mapping.wasm_event_handlers = wasm_event_handlers;
+            data_source
+        }
     }
 
     impl MappingEventHandler {
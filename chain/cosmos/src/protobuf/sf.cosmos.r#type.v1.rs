@@ -100,6 +100,31 @@ pub struct MessageData {
     #[prost(message, optional, tag = "3")]
     pub tx: ::core::option::Option<TransactionContext>,
 }
+/// WasmEventData is an event that a CosmWasm contract emitted, together with
+/// the address of that contract
+#[graph_runtime_derive::generate_asc_type(
+    __required__{event:Event,
+    block:HeaderOnlyBlock,
+    tx:TransactionContext}
+)]
+#[graph_runtime_derive::generate_network_type_id(Cosmos)]
+#[graph_runtime_derive::generate_from_rust_type(
+    __required__{event:Event,
+    block:HeaderOnlyBlock,
+    tx:TransactionContext}
+)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WasmEventData {
+    #[prost(string, tag = "1")]
+    pub contract_address: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub event: ::core::option::Option<Event>,
+    #[prost(message, optional, tag = "3")]
+    pub block: ::core::option::Option<HeaderOnlyBlock>,
+    #[prost(message, optional, tag = "4")]
+    pub tx: ::core::option::Option<TransactionContext>,
+}
 #[graph_runtime_derive::generate_asc_type()]
 #[graph_runtime_derive::generate_network_type_id(Cosmos)]
 #[graph_runtime_derive::generate_from_rust_type()]
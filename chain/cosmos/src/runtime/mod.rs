@@ -75,6 +75,13 @@ mod test {
             tx: new_asc_ptr(),
         });
 
+        assert_asc_bytes!(AscWasmEventData {
+            contract_address: new_asc_ptr(),
+            event: new_asc_ptr(),
+            block: new_asc_ptr(),
+            tx: new_asc_ptr(),
+        });
+
         assert_asc_bytes!(AscTransactionContext {
             hash: new_asc_ptr(),
             index: 20,
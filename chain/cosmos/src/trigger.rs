@@ -23,6 +23,10 @@ impl std::fmt::Debug for CosmosTrigger {
             },
             Transaction,
             Message,
+            WasmEvent {
+                event_type: &'e str,
+                contract_address: &'e str,
+            },
         }
 
         let trigger_without_block = match self {
@@ -33,6 +37,13 @@ impl std::fmt::Debug for CosmosTrigger {
             },
             CosmosTrigger::Transaction(_) => MappingTriggerWithoutBlock::Transaction,
             CosmosTrigger::Message(_) => MappingTriggerWithoutBlock::Message,
+            CosmosTrigger::WasmEvent(wasm_event_data) => MappingTriggerWithoutBlock::WasmEvent {
+                event_type: &wasm_event_data
+                    .event()
+                    .map_err(|_| std::fmt::Error)?
+                    .event_type,
+                contract_address: &wasm_event_data.contract_address,
+            },
         };
 
         write!(f, "{:?}", trigger_without_block)
@@ -56,6 +67,9 @@ impl ToAscPtr for CosmosTrigger {
             CosmosTrigger::Message(message_data) => {
                 asc_new(heap, message_data.as_ref(), gas)?.erase()
             }
+            CosmosTrigger::WasmEvent(wasm_event_data) => {
+                asc_new(heap, wasm_event_data.as_ref(), gas)?.erase()
+            }
         })
     }
 }
@@ -69,6 +83,7 @@ pub enum CosmosTrigger {
     },
     Transaction(Arc<codec::TransactionData>),
     Message(Arc<codec::MessageData>),
+    WasmEvent(Arc<codec::WasmEventData>),
 }
 
 impl PartialEq for CosmosTrigger {
@@ -101,6 +116,7 @@ impl PartialEq for CosmosTrigger {
             }
             (Self::Transaction(a_ptr), Self::Transaction(b_ptr)) => a_ptr == b_ptr,
             (Self::Message(a_ptr), Self::Message(b_ptr)) => a_ptr == b_ptr,
+            (Self::WasmEvent(a_ptr), Self::WasmEvent(b_ptr)) => a_ptr == b_ptr,
             _ => false,
         }
     }
@@ -147,6 +163,20 @@ impl CosmosTrigger {
         }))
     }
 
+    pub(crate) fn with_wasm_event(
+        event: codec::Event,
+        contract_address: String,
+        block: codec::HeaderOnlyBlock,
+        tx_context: codec::TransactionContext,
+    ) -> CosmosTrigger {
+        CosmosTrigger::WasmEvent(Arc::new(codec::WasmEventData {
+            contract_address,
+            event: Some(event),
+            block: Some(block),
+            tx: Some(tx_context),
+        }))
+    }
+
     pub fn block_number(&self) -> Result<BlockNumber, Error> {
         match self {
             CosmosTrigger::Block(block) => Ok(block.number()),
@@ -155,6 +185,9 @@ impl CosmosTrigger {
                 transaction_data.block().map(|b| b.number())
             }
             CosmosTrigger::Message(message_data) => message_data.block().map(|b| b.number()),
+            CosmosTrigger::WasmEvent(wasm_event_data) => {
+                wasm_event_data.block().map(|b| b.number())
+            }
         }
     }
 
@@ -166,6 +199,7 @@ impl CosmosTrigger {
                 transaction_data.block().map(|b| b.hash())
             }
             CosmosTrigger::Message(message_data) => message_data.block().map(|b| b.hash()),
+            CosmosTrigger::WasmEvent(wasm_event_data) => wasm_event_data.block().map(|b| b.hash()),
         }
     }
 
@@ -219,6 +253,20 @@ impl CosmosTrigger {
                     "message".to_string()
                 }
             }
+            CosmosTrigger::WasmEvent(wasm_event_data) => {
+                if let (Ok(event), Ok(block_number), Ok(block_hash)) = (
+                    wasm_event_data.event(),
+                    self.block_number(),
+                    self.block_hash(),
+                ) {
+                    format!(
+                        "wasm event type {}, contract {}, block #{block_number}, hash {block_hash}",
+                        event.event_type, wasm_event_data.contract_address,
+                    )
+                } else {
+                    "wasm event".to_string()
+                }
+            }
         }
     }
 }
@@ -228,7 +276,10 @@ impl Ord for CosmosTrigger {
         match (self, other) {
             // Events have no intrinsic ordering information, so we keep the order in
             // which they are included in the `events` field
-            (Self::Event { .. }, Self::Event { .. }) => Ordering::Equal,
+            (
+                Self::Event { .. } | Self::WasmEvent(..),
+                Self::Event { .. } | Self::WasmEvent(..),
+            ) => Ordering::Equal,
 
             // Keep the order when comparing two message triggers
             (Self::Message(..), Self::Message(..)) => Ordering::Equal,
@@ -246,8 +297,8 @@ impl Ord for CosmosTrigger {
             (Self::Block(..), Self::Block(..)) => Ordering::Equal,
 
             // Event triggers always come first
-            (Self::Event { .. }, _) => Ordering::Greater,
-            (_, Self::Event { .. }) => Ordering::Less,
+            (Self::Event { .. } | Self::WasmEvent(..), _) => Ordering::Greater,
+            (_, Self::Event { .. } | Self::WasmEvent(..)) => Ordering::Less,
 
             // Block triggers always come last
             (Self::Block(..), _) => Ordering::Less,
@@ -301,6 +352,10 @@ mod tests {
             }),
             origin: EventOrigin::BeginBlock,
         };
+        let wasm_event_trigger =
+            CosmosTrigger::WasmEvent(Arc::<codec::WasmEventData>::new(codec::WasmEventData {
+                ..Default::default()
+            }));
         let message_trigger =
             CosmosTrigger::Message(Arc::<codec::MessageData>::new(codec::MessageData {
                 ..Default::default()
@@ -338,6 +393,11 @@ mod tests {
         assert_eq!(event_trigger.cmp(&transaction_trigger), Ordering::Greater);
         assert_eq!(event_trigger.cmp(&message_trigger), Ordering::Greater);
         assert_eq!(event_trigger.cmp(&other_event_trigger), Ordering::Equal);
+        assert_eq!(event_trigger.cmp(&wasm_event_trigger), Ordering::Equal);
+
+        assert_eq!(wasm_event_trigger.cmp(&block_trigger), Ordering::Greater);
+        assert_eq!(wasm_event_trigger.cmp(&message_trigger), Ordering::Greater);
+        assert_eq!(wasm_event_trigger.cmp(&event_trigger), Ordering::Equal);
 
         assert_eq!(message_trigger.cmp(&block_trigger), Ordering::Greater);
         assert_eq!(message_trigger.cmp(&transaction_trigger), Ordering::Greater);
@@ -3,6 +3,7 @@ use ethabi::{Error as ABIError, Function, ParamType, Token};
 use graph::blockchain::ChainIdentifier;
 use graph::components::subgraph::MappingError;
 use graph::data::store::ethereum::call;
+use graph::endpoint::RequestClass;
 use graph::firehose::CallToFilter;
 use graph::firehose::CombinedFilter;
 use graph::firehose::LogFilter;
@@ -248,6 +249,25 @@ impl TriggerFilter {
         !self.call.is_empty() || self.block.requires_traces()
     }
 
+    /// Whether blocks need complete transaction receipts for this filter,
+    /// either because handlers asked for receipts or because the receipts
+    /// tell which calls succeeded
+    pub(crate) fn requires_receipts(&self) -> bool {
+        self.requires_traces() || self.log.requires_any_transaction_receipt()
+    }
+
+    /// The kind of requests that dominate scanning blocks for triggers of
+    /// this filter, and that the provider for the scan should be good at
+    pub(crate) fn request_class(&self) -> RequestClass {
+        if self.requires_traces() {
+            RequestClass::Traces
+        } else if !self.log.is_empty() {
+            RequestClass::Logs
+        } else {
+            RequestClass::Blocks
+        }
+    }
+
     #[cfg(debug_assertions)]
     pub fn log(&self) -> &EthereumLogFilter {
         &self.log
@@ -274,6 +294,11 @@ impl bc::TriggerFilter<Chain> for TriggerFilter {
             .extend(EthereumBlockFilter::from_data_sources(data_sources));
     }
 
+    fn extend_with_every_block(&mut self) -> bool {
+        self.block.trigger_every_block = true;
+        true
+    }
+
     fn node_capabilities(&self) -> NodeCapabilities {
         NodeCapabilities {
             archive: false,
@@ -346,6 +371,10 @@ pub struct EthereumLogFilter {
     /// Events with any of the topic filters set
     /// Maps to a boolean representing if a trigger requires a transaction receipt.
     events_with_topic_filters: HashMap<EventSignatureWithTopics, bool>,
+    /// Contracts for which all logs match, including those without topics, because their data
+    /// source has a fallback event handler.
+    /// Maps to a boolean representing if a trigger requires a transaction receipt.
+    contracts_with_all_events: HashMap<Address, bool>,
 }
 
 impl From<EthereumLogFilter> for Vec<LogFilter> {
@@ -374,6 +403,10 @@ impl From<EthereumLogFilter> for Vec<LogFilter> {
 impl EthereumLogFilter {
     /// Check if this filter matches the specified `Log`.
     pub fn matches(&self, log: &Log) -> bool {
+        if self.contracts_with_all_events.contains_key(&log.address) {
+            return true;
+        }
+
         // First topic should be event sig
         match log.topics.first() {
             None => false,
@@ -403,6 +436,12 @@ impl EthereumLogFilter {
         contract_address: Option<&Address>,
         topics: &Vec<H256>,
     ) -> bool {
+        if let Some(address) = contract_address {
+            if self.all_events_require_transaction_receipt(address) {
+                return true;
+            }
+        }
+
         // Check for wildcard events first.
         if self.wildcard_events.get(event_signature) == Some(&true) {
             return true;
@@ -439,9 +478,32 @@ impl EthereumLogFilter {
         false
     }
 
+    /// Whether all logs of `contract_address` require a transaction receipt, which also covers
+    /// logs without topics
+    pub fn all_events_require_transaction_receipt(&self, contract_address: &Address) -> bool {
+        self.contracts_with_all_events.get(contract_address) == Some(&true)
+    }
+
+    /// Whether any trigger of this filter requires a transaction receipt
+    pub fn requires_any_transaction_receipt(&self) -> bool {
+        self.contracts_and_events_graph
+            .all_edges()
+            .any(|(_, _, &requires_receipt)| requires_receipt)
+            || self.wildcard_events.values().any(|&r| r)
+            || self.events_with_topic_filters.values().any(|&r| r)
+            || self.contracts_with_all_events.values().any(|&r| r)
+    }
+
     pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
         let mut this = EthereumLogFilter::default();
         for ds in iter {
+            if let (Some(contract), Some(fallback)) =
+                (ds.address, &ds.mapping.fallback_event_handler)
+            {
+                let requires_receipt = this.contracts_with_all_events.entry(contract).or_default();
+                *requires_receipt = *requires_receipt || fallback.receipt;
+            }
+
             for event_handler in ds.mapping.event_handlers.iter() {
                 let event_sig = event_handler.topic0();
                 match ds.address {
@@ -488,6 +550,8 @@ impl EthereumLogFilter {
         this
     }
 
+    /// The filter for the data sources that will be created from a template with `mapping`.
+    /// Since their addresses are not known yet, this can not cover fallback event handlers
     pub fn from_mapping(mapping: &Mapping) -> Self {
         let mut this = EthereumLogFilter::default();
         for event_handler in &mapping.event_handlers {
@@ -509,6 +573,7 @@ impl EthereumLogFilter {
             contracts_and_events_graph,
             wildcard_events,
             events_with_topic_filters,
+            contracts_with_all_events,
         } = other;
         for (s, t, e) in contracts_and_events_graph.all_edges() {
             self.contracts_and_events_graph.add_edge(s, t, *e);
@@ -516,6 +581,10 @@ impl EthereumLogFilter {
         self.wildcard_events.extend(wildcard_events);
         self.events_with_topic_filters
             .extend(events_with_topic_filters);
+        for (contract, requires_receipt) in contracts_with_all_events {
+            let entry = self.contracts_with_all_events.entry(contract).or_default();
+            *entry = *entry || requires_receipt;
+        }
     }
 
     /// An empty filter is one that never matches.
@@ -525,10 +594,12 @@ impl EthereumLogFilter {
             contracts_and_events_graph,
             wildcard_events,
             events_with_topic_filters,
+            contracts_with_all_events,
         } = self;
         contracts_and_events_graph.edge_count() == 0
             && wildcard_events.is_empty()
             && events_with_topic_filters.is_empty()
+            && contracts_with_all_events.is_empty()
     }
 
     /// Filters for `eth_getLogs` calls. The filters will not return false positives. This attempts
@@ -553,6 +624,14 @@ impl EthereumLogFilter {
                 }),
         );
 
+        // Contracts with all events get one filter each, which makes the events of these
+        // contracts in the graph redundant
+        let mut g = self.contracts_and_events_graph;
+        for contract in self.contracts_with_all_events.into_keys() {
+            g.remove_node(LogFilterNode::Contract(contract));
+            filters.push(EthGetLogsFilter::from_contract(contract));
+        }
+
         // The current algorithm is to repeatedly find the maximum cardinality vertex and turn all
         // of its edges into a filter. This is nice because it is neutral between filtering by
         // contract or by events, if there are many events that appear on only one data source
@@ -566,7 +645,6 @@ impl EthereumLogFilter {
         // single node. For example if a subgraph has two data sources, each with the same two
         // events, we could cover that with a single filter and no false positives. However that
         // might cause the filter to become too broad, so at the moment it seems excessive.
-        let mut g = self.contracts_and_events_graph;
         while g.edge_count() > 0 {
             let mut push_filter = |filter: EthGetLogsFilter| {
                 // Sanity checks:
@@ -1313,6 +1391,7 @@ mod tests {
                 contracts_and_events_graph: GraphMap::new(),
                 wildcard_events: HashMap::new(),
                 events_with_topic_filters: HashMap::new(),
+                contracts_with_all_events: HashMap::new(),
             },
             call: EthereumCallFilter {
                 contract_addresses_function_signatures: HashMap::from_iter(vec![
@@ -1439,6 +1518,7 @@ mod tests {
                 contracts_and_events_graph: GraphMap::new(),
                 wildcard_events: HashMap::new(),
                 events_with_topic_filters: HashMap::new(),
+                contracts_with_all_events: HashMap::new(),
             },
             call: EthereumCallFilter {
                 contract_addresses_function_signatures: HashMap::new(),
@@ -1831,6 +1911,7 @@ fn complete_log_filter() {
                 contracts_and_events_graph,
                 wildcard_events: HashMap::new(),
                 events_with_topic_filters: HashMap::new(),
+                contracts_with_all_events: HashMap::new(),
             }
             .eth_get_logs_filters()
             .collect();
@@ -1926,6 +2007,7 @@ fn log_filter_require_transacion_receipt_method() {
         contracts_and_events_graph,
         wildcard_events,
         events_with_topic_filters,
+        contracts_with_all_events: HashMap::new(),
     };
 
     let empty_vec: Vec<H256> = vec![];
@@ -2012,4 +2094,57 @@ fn log_filter_require_transacion_receipt_method() {
         Some(&alien_contract_address),
         &empty_vec
     ));
+    assert!(filter.requires_any_transaction_receipt());
+    assert!(!EthereumLogFilter::default().requires_any_transaction_receipt());
+}
+
+#[test]
+fn log_filter_with_all_events_of_contracts() {
+    let event_a = H256::from_low_u64_be(1);
+    let event_b = H256::from_low_u64_be(2);
+    let contract_a = Address::from_low_u64_be(3);
+    let contract_b = Address::from_low_u64_be(4);
+
+    let mut contracts_and_events_graph = GraphMap::new();
+    contracts_and_events_graph.add_edge(
+        LogFilterNode::Contract(contract_a),
+        LogFilterNode::Event(event_a),
+        false,
+    );
+    contracts_and_events_graph.add_edge(
+        LogFilterNode::Contract(contract_b),
+        LogFilterNode::Event(event_b),
+        false,
+    );
+
+    let filter = EthereumLogFilter {
+        contracts_and_events_graph,
+        wildcard_events: HashMap::new(),
+        events_with_topic_filters: HashMap::new(),
+        contracts_with_all_events: HashMap::from_iter([(contract_a, true)]),
+    };
+
+    // Logs of `contract_a` match and require a receipt even without topics
+    let log = |address, topics| Log {
+        address,
+        topics,
+        ..Default::default()
+    };
+    assert!(filter.matches(&log(contract_a, vec![])));
+    assert!(filter.matches(&log(contract_a, vec![event_b])));
+    assert!(!filter.matches(&log(contract_b, vec![])));
+    assert!(!filter.matches(&log(contract_b, vec![event_a])));
+    assert!(filter.all_events_require_transaction_receipt(&contract_a));
+    assert!(filter.requires_transaction_receipt(&event_b, Some(&contract_a), &vec![]));
+    assert!(!filter.requires_transaction_receipt(&event_b, Some(&contract_b), &vec![]));
+
+    // `contract_a` gets a filter without events; its edge in the graph is redundant
+    let filters = filter.eth_get_logs_filters().collect_vec();
+    assert_eq!(2, filters.len());
+    assert!(filters
+        .iter()
+        .any(|f| f.contracts == vec![contract_a] && f.event_signatures.is_empty()));
+    assert!(filters
+        .iter()
+        .any(|f| f.contracts == vec![contract_b] && f.event_signatures == vec![event_b]));
 }
@@ -0,0 +1,306 @@
+//! Blob-carrying transactions (EIP-4844) and the contents of their blobs.
+//! Blobs are not part of the execution chain; their contents have to come
+//! from a beacon node or an archive like Blobscan, and beacon nodes only
+//! keep them for about 18 days
+use std::sync::Mutex;
+
+use graph::prelude::{
+    anyhow::{anyhow, Context},
+    reqwest, serde_json,
+    web3::types::{Bytes, H160, H256, U128, U256, U64},
+    Error,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Where to get the contents of blobs from
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BlobSource {
+    /// The beacon API of a consensus client, which serves blob sidecars
+    /// by slot
+    Beacon { url: String },
+    /// An API that serves blobs by versioned hash at `/blobs/<hash>` as
+    /// JSON with `commitment` and `data`, like Blobscan
+    Blobscan { url: String },
+}
+
+/// A transaction that carries blobs
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlobTransaction {
+    pub hash: H256,
+    pub index: U128,
+    pub from: H160,
+    pub to: Option<H160>,
+    pub max_fee_per_blob_gas: U256,
+    pub blob_versioned_hashes: Vec<H256>,
+}
+
+/// The blob-carrying transactions of a block
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockBlobs {
+    pub hash: H256,
+    pub timestamp: u64,
+    pub transactions: Vec<BlobTransaction>,
+}
+
+impl BlockBlobs {
+    /// Extract the blob-carrying transactions from the result of
+    /// `eth_getBlockByHash` with full transactions. We can not use the
+    /// block type from `web3` for that since it drops the blob fields
+    pub fn from_json(block: serde_json::Value) -> Result<Self, Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct RawTransaction {
+            hash: H256,
+            transaction_index: U64,
+            from: H160,
+            to: Option<H160>,
+            #[serde(default)]
+            max_fee_per_blob_gas: Option<U256>,
+            #[serde(default)]
+            blob_versioned_hashes: Vec<H256>,
+        }
+
+        #[derive(Deserialize)]
+        struct RawBlock {
+            hash: H256,
+            timestamp: U256,
+            transactions: Vec<RawTransaction>,
+        }
+
+        let block: RawBlock = serde_json::from_value(block).context("invalid block")?;
+        let transactions = block
+            .transactions
+            .into_iter()
+            .filter(|tx| !tx.blob_versioned_hashes.is_empty())
+            .map(|tx| BlobTransaction {
+                hash: tx.hash,
+                index: U128::from(tx.transaction_index.as_u64()),
+                from: tx.from,
+                to: tx.to,
+                max_fee_per_blob_gas: tx.max_fee_per_blob_gas.unwrap_or_default(),
+                blob_versioned_hashes: tx.blob_versioned_hashes,
+            })
+            .collect();
+        Ok(BlockBlobs {
+            hash: block.hash,
+            timestamp: block.timestamp.as_u64(),
+            transactions,
+        })
+    }
+
+    pub fn contains(&self, versioned_hash: &H256) -> bool {
+        self.transactions
+            .iter()
+            .any(|tx| tx.blob_versioned_hashes.contains(versioned_hash))
+    }
+}
+
+/// The versioned hash of the blob with the KZG `commitment`
+pub fn versioned_hash(commitment: &[u8]) -> H256 {
+    const VERSION_KZG: u8 = 0x01;
+
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSION_KZG;
+    H256(hash)
+}
+
+#[derive(Deserialize)]
+struct BeaconResponse<T> {
+    data: T,
+}
+
+/// Fetches the contents of blobs from a `BlobSource`
+pub struct BlobClient {
+    source: BlobSource,
+    http: reqwest::Client,
+    /// The genesis time and the seconds per slot of the beacon chain
+    beacon_clock: Mutex<Option<(u64, u64)>>,
+}
+
+impl BlobClient {
+    pub fn new(source: BlobSource) -> Self {
+        Self {
+            source,
+            http: reqwest::Client::new(),
+            beacon_clock: Mutex::new(None),
+        }
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, url: String) -> Result<T, Error> {
+        self.http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .with_context(|| format!("failed to get `{}`", url))?
+            .json()
+            .await
+            .with_context(|| format!("invalid response from `{}`", url))
+    }
+
+    /// The slot of the beacon block for the execution block with the given
+    /// timestamp
+    async fn slot(&self, url: &str, timestamp: u64) -> Result<u64, Error> {
+        #[derive(Deserialize)]
+        struct Genesis {
+            genesis_time: String,
+        }
+
+        #[derive(Deserialize)]
+        struct Spec {
+            #[serde(rename = "SECONDS_PER_SLOT")]
+            seconds_per_slot: String,
+        }
+
+        let clock = *self.beacon_clock.lock().unwrap();
+        let (genesis_time, seconds_per_slot) = match clock {
+            Some(clock) => clock,
+            None => {
+                let genesis: BeaconResponse<Genesis> =
+                    self.get(format!("{}/eth/v1/beacon/genesis", url)).await?;
+                let spec: BeaconResponse<Spec> =
+                    self.get(format!("{}/eth/v1/config/spec", url)).await?;
+                let clock = (
+                    genesis.data.genesis_time.parse()?,
+                    spec.data.seconds_per_slot.parse()?,
+                );
+                *self.beacon_clock.lock().unwrap() = Some(clock);
+                clock
+            }
+        };
+
+        if timestamp < genesis_time || seconds_per_slot == 0 {
+            return Err(anyhow!(
+                "block with timestamp {} is not on the beacon chain",
+                timestamp
+            ));
+        }
+        Ok((timestamp - genesis_time) / seconds_per_slot)
+    }
+
+    /// Get the contents of the blob with `versioned_hash`, which must be a
+    /// blob of `block`
+    pub async fn blob(&self, block: &BlockBlobs, versioned_hash: &H256) -> Result<Vec<u8>, Error> {
+        #[derive(Deserialize)]
+        struct Sidecar {
+            blob: Bytes,
+            kzg_commitment: Bytes,
+        }
+
+        #[derive(Deserialize)]
+        struct Blob {
+            commitment: Bytes,
+            data: Bytes,
+        }
+
+        match &self.source {
+            BlobSource::Beacon { url } => {
+                let url = url.trim_end_matches('/');
+                let slot = self.slot(url, block.timestamp).await?;
+                let sidecars: BeaconResponse<Vec<Sidecar>> = self
+                    .get(format!("{}/eth/v1/beacon/blob_sidecars/{}", url, slot))
+                    .await?;
+                sidecars
+                    .data
+                    .into_iter()
+                    .find(|sidecar| {
+                        self::versioned_hash(&sidecar.kzg_commitment.0) == *versioned_hash
+                    })
+                    .map(|sidecar| sidecar.blob.0)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "the beacon node has no blob {:?} for block {:?} in slot {}, \
+                             it might have been pruned",
+                            versioned_hash,
+                            block.hash,
+                            slot
+                        )
+                    })
+            }
+            BlobSource::Blobscan { url } => {
+                let url = url.trim_end_matches('/');
+                let blob: Blob = self
+                    .get(format!("{}/blobs/{:?}", url, versioned_hash))
+                    .await?;
+                if self::versioned_hash(&blob.commitment.0) != *versioned_hash {
+                    return Err(anyhow!(
+                        "`{}` returned a blob with the wrong commitment for {:?}",
+                        url,
+                        versioned_hash
+                    ));
+                }
+                Ok(blob.data.0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::prelude::serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn blob_transactions_from_block() {
+        let block = json!({
+            "hash": "0x0000000000000000000000000000000000000000000000000000000000000007",
+            "number": "0x10",
+            "timestamp": "0x65f6a2c0",
+            "transactions": [
+                {
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    "transactionIndex": "0x0",
+                    "type": "0x2",
+                    "from": "0x0000000000000000000000000000000000000001",
+                    "to": "0x0000000000000000000000000000000000000002"
+                },
+                {
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                    "transactionIndex": "0x1",
+                    "type": "0x3",
+                    "from": "0x0000000000000000000000000000000000000003",
+                    "to": "0x0000000000000000000000000000000000000004",
+                    "maxFeePerBlobGas": "0x3b9aca00",
+                    "blobVersionedHashes": [
+                        "0x010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014"
+                    ]
+                }
+            ]
+        });
+
+        let blobs = BlockBlobs::from_json(block).unwrap();
+        assert_eq!(0x65f6a2c0, blobs.timestamp);
+        assert_eq!(1, blobs.transactions.len());
+        let tx = &blobs.transactions[0];
+        assert_eq!(U128::from(1), tx.index);
+        assert_eq!(U256::from(1_000_000_000), tx.max_fee_per_blob_gas);
+
+        // The commitment of the blob that is all zeros
+        let mut commitment = vec![0u8; 48];
+        commitment[0] = 0xc0;
+        let hash = versioned_hash(&commitment);
+        assert_eq!(tx.blob_versioned_hashes, vec![hash]);
+        assert!(blobs.contains(&hash));
+        assert!(!blobs.contains(&H256::zero()));
+    }
+
+    #[test]
+    fn blob_source_from_toml() {
+        let source: BlobSource = graph::prelude::toml::from_str(
+            r#"
+            type = "beacon"
+            url = "http://localhost:5052"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            BlobSource::Beacon {
+                url: "http://localhost:5052".to_string()
+            },
+            source
+        );
+    }
+}
@@ -67,3 +67,15 @@ impl graph::blockchain::NodeCapabilities<crate::Chain> for NodeCapabilities {
         }
     }
 }
+
+/// What sets an EVM chain apart from Ethereum mainnet. These come from the
+/// chain registry in the configuration so that chains with these quirks can
+/// be added without code changes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChainCapabilities {
+    /// Blocks are final as soon as they are produced, so polling block
+    /// streams do not have to handle reorgs
+    pub final_blocks: bool,
+    /// `eth_call` fails for calls that set a gas limit
+    pub no_eth_call_gas: bool,
+}
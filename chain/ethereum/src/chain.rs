@@ -2,13 +2,16 @@ use anyhow::{anyhow, bail, Result};
 use anyhow::{Context, Error};
 use graph::blockchain::client::ChainClient;
 use graph::blockchain::firehose_block_ingestor::{FirehoseBlockIngestor, Transforms};
+use graph::blockchain::hybrid_block_stream::{BuildBlockStream, HybridBlockStream};
 use graph::blockchain::{
-    BlockIngestor, BlockTime, BlockchainKind, ChainIdentifier, TriggersAdapterSelector,
+    BlockIngestor, BlockTime, BlockchainKind, ChainIdentifier, IngestionMode,
+    TriggersAdapterSelector,
 };
 use graph::components::adapter::ChainId;
 use graph::components::store::DeploymentCursorTracker;
 use graph::data::subgraph::UnifiedMappingApiVersion;
-use graph::firehose::{FirehoseEndpoint, ForkStep};
+use graph::endpoint::RequestClass;
+use graph::firehose::{FirehoseEndpoint, FirehoseEndpoints, ForkStep};
 use graph::futures03::compat::Future01CompatExt;
 use graph::prelude::{
     BlockHash, ComponentLoggerConfig, ElasticComponentLoggerConfig, EthereumBlock,
@@ -41,10 +44,11 @@ use std::iter::FromIterator;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::blobs::BlobClient;
 use crate::codec::HeaderOnlyBlock;
 use crate::data_source::DataSourceTemplate;
 use crate::data_source::UnresolvedDataSourceTemplate;
-use crate::ingestor::PollingBlockIngestor;
+use crate::ingestor::{HeadBlock, PollingBlockIngestor};
 use crate::network::EthereumNetworkAdapters;
 use crate::runtime::runtime_adapter::eth_call_gas;
 use crate::{
@@ -52,12 +56,12 @@ use crate::{
     codec,
     data_source::{DataSource, UnresolvedDataSource},
     ethereum_adapter::{
-        blocks_with_triggers, get_calls, parse_block_triggers, parse_call_triggers,
+        blocks_with_triggers, get_calls, get_receipts, parse_block_triggers, parse_call_triggers,
         parse_log_triggers,
     },
     SubgraphEthRpcMetrics, TriggerFilter, ENV_VARS,
 };
-use crate::{BufferedCallCache, NodeCapabilities};
+use crate::{BufferedCallCache, ChainCapabilities, NodeCapabilities};
 use crate::{EthereumAdapter, RuntimeAdapter};
 use graph::blockchain::block_stream::{
     BlockStream, BlockStreamBuilder, BlockStreamError, BlockStreamMapper, FirehoseCursor,
@@ -149,25 +153,7 @@ impl BlockStreamBuilder<Chain> for EthereumStreamBuilder {
             .chain_head_update_listener
             .subscribe(chain.name.to_string(), logger.clone());
 
-        // Special case: Detect Celo and set the threshold to 0, so that eth_getLogs is always used.
-        // This is ok because Celo blocks are always final. And we _need_ to do this because
-        // some events appear only in eth_getLogs but not in transaction receipts.
-        // See also ca0edc58-0ec5-4c89-a7dd-2241797f5e50.
-        let chain_id = match chain.chain_client().as_ref() {
-            ChainClient::Rpc(adapter) => {
-                adapter
-                    .cheapest()
-                    .await
-                    .ok_or(anyhow!("unable to get eth adapter for chan_id call"))?
-                    .chain_id()
-                    .await?
-            }
-            _ => panic!("expected rpc when using polling blockstream"),
-        };
-        let reorg_threshold = match CELO_CHAIN_IDS.contains(&chain_id) {
-            false => chain.reorg_threshold,
-            true => 0,
-        };
+        let reorg_threshold = chain.polling_reorg_threshold().await?;
 
         Ok(Box::new(PollingBlockStream::new(
             chain_store,
@@ -269,7 +255,13 @@ pub trait RuntimeAdapterBuilder: Send + Sync + 'static {
     ) -> Arc<dyn RuntimeAdapterTrait<Chain>>;
 }
 
-pub struct EthereumRuntimeAdapterBuilder {}
+#[derive(Default)]
+pub struct EthereumRuntimeAdapterBuilder {
+    /// Where `ethereum.getBlob` gets blobs from, if the chain has a source
+    /// for them configured
+    pub blobs: Option<Arc<BlobClient>>,
+    pub capabilities: ChainCapabilities,
+}
 
 impl RuntimeAdapterBuilder for EthereumRuntimeAdapterBuilder {
     fn build(
@@ -282,6 +274,8 @@ impl RuntimeAdapterBuilder for EthereumRuntimeAdapterBuilder {
             eth_adapters,
             call_cache,
             chain_identifier,
+            blobs: self.blobs.clone(),
+            capabilities: self.capabilities,
         })
     }
 }
@@ -297,12 +291,17 @@ pub struct Chain {
     chain_head_update_listener: Arc<dyn ChainHeadUpdateListener>,
     reorg_threshold: BlockNumber,
     polling_ingestor_interval: Duration,
+    head: HeadBlock,
     pub is_ingestible: bool,
     block_stream_builder: Arc<dyn BlockStreamBuilder<Self>>,
     block_refetcher: Arc<dyn BlockRefetcher<Self>>,
     adapter_selector: Arc<dyn TriggersAdapterSelector<Self>>,
     runtime_adapter_builder: Arc<dyn RuntimeAdapterBuilder>,
     eth_adapters: Arc<EthereumNetworkAdapters>,
+    /// Where deployments with `IngestionMode::Hybrid` get their blocks
+    /// while they are far behind the chain head
+    firehose_backfill: Option<FirehoseEndpoints>,
+    capabilities: ChainCapabilities,
 }
 
 impl std::fmt::Debug for Chain {
@@ -329,6 +328,7 @@ impl Chain {
         eth_adapters: Arc<EthereumNetworkAdapters>,
         reorg_threshold: BlockNumber,
         polling_ingestor_interval: Duration,
+        head: HeadBlock,
         is_ingestible: bool,
     ) -> Self {
         Chain {
@@ -348,6 +348,9 @@ impl Chain {
             reorg_threshold,
             is_ingestible,
             polling_ingestor_interval,
+            head,
+            firehose_backfill: None,
+            capabilities: ChainCapabilities::default(),
         }
     }
 
@@ -366,6 +369,135 @@ impl Chain {
         };
         adapters.cheapest().await.unwrap()
     }
+
+    /// Backfill deployments with `IngestionMode::Hybrid` from these
+    /// Firehose endpoints. Only has an effect if the chain itself uses
+    /// JSON-RPC
+    pub fn with_firehose_backfill(mut self, endpoints: FirehoseEndpoints) -> Self {
+        self.firehose_backfill = Some(endpoints);
+        self
+    }
+
+    /// Set the capabilities of the chain from the chain registry
+    pub fn with_capabilities(mut self, capabilities: ChainCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// The reorg threshold for polling block streams
+    async fn polling_reorg_threshold(&self) -> Result<BlockNumber> {
+        if self.capabilities.final_blocks {
+            return Ok(0);
+        }
+
+        // Special case: Detect Celo and set the threshold to 0, so that eth_getLogs is always used.
+        // This is ok because Celo blocks are always final. And we _need_ to do this because
+        // some events appear only in eth_getLogs but not in transaction receipts.
+        // See also ca0edc58-0ec5-4c89-a7dd-2241797f5e50.
+        let chain_id = match self.chain_client().as_ref() {
+            ChainClient::Rpc(adapter) => {
+                adapter
+                    .cheapest()
+                    .await
+                    .ok_or(anyhow!("unable to get eth adapter for chan_id call"))?
+                    .chain_id()
+                    .await?
+            }
+            _ => panic!("expected rpc when using polling blockstream"),
+        };
+        Ok(match CELO_CHAIN_IDS.contains(&chain_id) {
+            false => self.reorg_threshold,
+            true => 0,
+        })
+    }
+
+    /// A block stream that backfills over the Firehose `endpoints` and
+    /// polls JSON-RPC near the chain head
+    async fn hybrid_block_stream(
+        &self,
+        endpoints: &FirehoseEndpoints,
+        deployment: DeploymentLocator,
+        cursor: FirehoseCursor,
+        start_blocks: Vec<BlockNumber>,
+        subgraph_current_block: Option<BlockPtr>,
+        filter: Arc<TriggerFilter>,
+        unified_api_version: UnifiedMappingApiVersion,
+    ) -> Result<Box<dyn BlockStream<Self>>, Error> {
+        let requirements = filter.node_capabilities();
+        let adapter =
+            self.triggers_adapter(&deployment, &requirements, unified_api_version.clone())?;
+        let logger = self.logger_factory.subgraph_logger(&deployment);
+
+        let backfill: BuildBlockStream<Self> = {
+            let client = Arc::new(ChainClient::new_firehose(endpoints.clone()));
+            let mapper = Arc::new(FirehoseMapper {
+                adapter: adapter.cheap_clone(),
+                filter: filter.cheap_clone(),
+            });
+            let deployment = deployment.hash.clone();
+            let start_blocks = start_blocks.clone();
+            let logger = logger.new(o!("component" => "FirehoseBlockStream"));
+            let registry = self.registry.cheap_clone();
+            Box::new(
+                move |current: Option<BlockPtr>,
+                      cursor: FirehoseCursor|
+                      -> Box<dyn BlockStream<Self>> {
+                    Box::new(FirehoseBlockStream::new(
+                        deployment.clone(),
+                        client.cheap_clone(),
+                        current,
+                        cursor,
+                        mapper.cheap_clone(),
+                        start_blocks.clone(),
+                        logger.clone(),
+                        registry.cheap_clone(),
+                    ))
+                },
+            )
+        };
+
+        let head: BuildBlockStream<Self> = {
+            let chain_store = self.chain_store.cheap_clone();
+            let chain_head_update_listener = self.chain_head_update_listener.cheap_clone();
+            let name = self.name.to_string();
+            let node_id = self.node_id.clone();
+            let deployment = deployment.hash.clone();
+            let filter = filter.cheap_clone();
+            let start_blocks = start_blocks.clone();
+            let reorg_threshold = self.polling_reorg_threshold().await?;
+            let logger = logger.new(o!("component" => "BlockStream"));
+            Box::new(
+                move |current: Option<BlockPtr>, _: FirehoseCursor| -> Box<dyn BlockStream<Self>> {
+                    Box::new(PollingBlockStream::new(
+                        chain_store.cheap_clone(),
+                        chain_head_update_listener.subscribe(name.clone(), logger.clone()),
+                        adapter.cheap_clone(),
+                        node_id.clone(),
+                        deployment.clone(),
+                        filter.cheap_clone(),
+                        start_blocks.clone(),
+                        reorg_threshold,
+                        logger.clone(),
+                        ENV_VARS.max_block_range_size,
+                        ENV_VARS.target_triggers_per_block_range,
+                        unified_api_version.clone(),
+                        current,
+                    ))
+                },
+            )
+        };
+
+        Ok(Box::new(HybridBlockStream::new(
+            self.chain_store.cheap_clone(),
+            subgraph_current_block,
+            cursor,
+            start_blocks,
+            ENV_VARS.hybrid_switch_distance,
+            backfill,
+            head,
+            logger.new(o!("component" => "HybridBlockStream")),
+        )))
+    }
 }
 
 #[async_trait]
@@ -411,9 +543,38 @@ impl Blockchain for Chain {
         start_blocks: Vec<BlockNumber>,
         filter: Arc<Self::TriggerFilter>,
         unified_api_version: UnifiedMappingApiVersion,
+        ingestion: IngestionMode,
     ) -> Result<Box<dyn BlockStream<Self>>, Error> {
         let current_ptr = store.block_ptr();
         match self.chain_client().as_ref() {
+            ChainClient::Rpc(_) if ingestion == IngestionMode::Hybrid => {
+                match &self.firehose_backfill {
+                    Some(endpoints) => {
+                        self.hybrid_block_stream(
+                            endpoints,
+                            deployment,
+                            store.firehose_cursor(),
+                            start_blocks,
+                            current_ptr,
+                            filter,
+                            unified_api_version,
+                        )
+                        .await
+                    }
+                    None => {
+                        self.block_stream_builder
+                            .build_polling(
+                                self,
+                                deployment,
+                                start_blocks,
+                                current_ptr,
+                                filter,
+                                unified_api_version,
+                            )
+                            .await
+                    }
+                }
+            }
             ChainClient::Rpc(_) => {
                 self.block_stream_builder
                     .build_polling(
@@ -494,7 +655,7 @@ impl Blockchain for Chain {
             call_cache.cheap_clone(),
             Arc::new(chain_ident.clone()),
         );
-        let eth_call_gas = eth_call_gas(&chain_ident);
+        let eth_call_gas = eth_call_gas(&chain_ident, &self.capabilities);
 
         let decoder_hook = crate::data_source::DecoderHook::new(
             self.eth_adapters.cheap_clone(),
@@ -552,6 +713,7 @@ impl Blockchain for Chain {
                     self.chain_client(),
                     self.chain_store().cheap_clone(),
                     self.polling_ingestor_interval,
+                    self.head,
                     self.name.clone(),
                 )?)
             }
@@ -637,6 +799,7 @@ impl Block for BlockFinality {
                 let eth_block = EthereumBlock {
                     block: block.clone(),
                     transaction_receipts: vec![],
+                    slim: false,
                 };
                 json::to_value(eth_block)
             }
@@ -676,7 +839,7 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
         blocks_with_triggers(
             self.chain_client
                 .rpc()?
-                .cheapest_with(&self.capabilities)
+                .cheapest_with(&self.capabilities, filter.request_class())
                 .await?,
             self.logger.clone(),
             self.chain_store.clone(),
@@ -695,6 +858,15 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
         block: BlockFinality,
         filter: &TriggerFilter,
     ) -> Result<BlockWithTriggers<Chain>, Error> {
+        let block = get_receipts(
+            &self.chain_client,
+            logger,
+            self.chain_store.cheap_clone(),
+            &self.capabilities,
+            filter.requires_receipts(),
+            block,
+        )
+        .await?;
         let block = get_calls(
             &self.chain_client,
             logger.clone(),
@@ -710,7 +882,7 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
                 let adapter = self
                     .chain_client
                     .rpc()?
-                    .cheapest_with(&self.capabilities)
+                    .cheapest_with(&self.capabilities, filter.request_class())
                     .await?;
                 let block_number = block.number() as BlockNumber;
                 let (blocks, _) = blocks_with_triggers(
@@ -783,7 +955,7 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
             }),
             ChainClient::Rpc(adapters) => {
                 let blocks = adapters
-                    .cheapest_with(&self.capabilities)
+                    .cheapest_with(&self.capabilities, RequestClass::Blocks)
                     .await?
                     .load_blocks(
                         self.logger.cheap_clone(),
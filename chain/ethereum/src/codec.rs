@@ -3,13 +3,14 @@
 mod pbcodec;
 
 use anyhow::format_err;
+use graph::components::ethereum::PostMergeFields;
 use graph::{
     blockchain::{
         self, Block as BlockchainBlock, BlockPtr, BlockTime, ChainStoreBlock, ChainStoreData,
     },
     prelude::{
         web3,
-        web3::types::{Bytes, H160, H2048, H256, H64, U256, U64},
+        web3::types::{AccessList, AccessListItem, Bytes, H160, H2048, H256, H64, U256, U64},
         BlockNumber, Error, EthereumBlock, EthereumBlockWithCalls, EthereumCall,
         LightEthereumBlock,
     },
@@ -201,10 +202,14 @@ impl<'a> TryInto<web3::types::Transaction> for TransactionTraceAt<'a> {
             r: None,
             s: None,
             raw: None,
-            access_list: None,
-            max_fee_per_gas: None,
-            max_priority_fee_per_gas: None,
-            transaction_type: None,
+            access_list: get_access_list(self.trace)?,
+            max_fee_per_gas: self.trace.max_fee_per_gas.as_ref().map(|x| x.into()),
+            max_priority_fee_per_gas: self
+                .trace
+                .max_priority_fee_per_gas
+                .as_ref()
+                .map(|x| x.into()),
+            transaction_type: Some(U64::from(self.trace.r#type as u64)),
         })
     }
 }
@@ -228,58 +233,76 @@ impl TryInto<EthereumBlockWithCalls> for &Block {
         let block = EthereumBlockWithCalls {
             ethereum_block: EthereumBlock {
                 block: Arc::new(LightEthereumBlock {
-                    hash: Some(self.hash.try_decode_proto("block hash")?),
-                    number: Some(U64::from(self.number)),
-                    author: header.coinbase.try_decode_proto("author / coinbase")?,
-                    parent_hash: header.parent_hash.try_decode_proto("parent hash")?,
-                    uncles_hash: header.uncle_hash.try_decode_proto("uncle hash")?,
-                    state_root: header.state_root.try_decode_proto("state root")?,
-                    transactions_root: header
-                        .transactions_root
-                        .try_decode_proto("transactions root")?,
-                    receipts_root: header.receipt_root.try_decode_proto("receipt root")?,
-                    gas_used: U256::from(header.gas_used),
-                    gas_limit: U256::from(header.gas_limit),
-                    base_fee_per_gas: Some(
-                        header
-                            .base_fee_per_gas
+                    inner: web3::types::Block {
+                        hash: Some(self.hash.try_decode_proto("block hash")?),
+                        number: Some(U64::from(self.number)),
+                        author: header.coinbase.try_decode_proto("author / coinbase")?,
+                        parent_hash: header.parent_hash.try_decode_proto("parent hash")?,
+                        uncles_hash: header.uncle_hash.try_decode_proto("uncle hash")?,
+                        state_root: header.state_root.try_decode_proto("state root")?,
+                        transactions_root: header
+                            .transactions_root
+                            .try_decode_proto("transactions root")?,
+                        receipts_root: header.receipt_root.try_decode_proto("receipt root")?,
+                        gas_used: U256::from(header.gas_used),
+                        gas_limit: U256::from(header.gas_limit),
+                        base_fee_per_gas: Some(
+                            header
+                                .base_fee_per_gas
+                                .as_ref()
+                                .map_or_else(U256::default, |v| v.into()),
+                        ),
+                        extra_data: Bytes::from(header.extra_data.clone()),
+                        logs_bloom: match &header.logs_bloom.len() {
+                            0 => None,
+                            _ => Some(header.logs_bloom.try_decode_proto("logs bloom")?),
+                        },
+                        timestamp: header
+                            .timestamp
                             .as_ref()
-                            .map_or_else(U256::default, |v| v.into()),
-                    ),
-                    extra_data: Bytes::from(header.extra_data.clone()),
-                    logs_bloom: match &header.logs_bloom.len() {
-                        0 => None,
-                        _ => Some(header.logs_bloom.try_decode_proto("logs bloom")?),
-                    },
-                    timestamp: header
-                        .timestamp
-                        .as_ref()
-                        .map_or_else(U256::default, |v| U256::from(v.seconds)),
-                    difficulty: header
-                        .difficulty
-                        .as_ref()
-                        .map_or_else(U256::default, |v| v.into()),
-                    total_difficulty: Some(
-                        header
-                            .total_difficulty
+                            .map_or_else(U256::default, |v| U256::from(v.seconds)),
+                        difficulty: header
+                            .difficulty
                             .as_ref()
                             .map_or_else(U256::default, |v| v.into()),
-                    ),
-                    // FIXME (SF): Firehose does not have seal fields, are they really used? Might be required for POA chains only also, I've seen that stuff on xDai (is this important?)
-                    seal_fields: vec![],
-                    uncles: self
-                        .uncles
-                        .iter()
-                        .map(|u| u.hash.try_decode_proto("uncle hash"))
-                        .collect::<Result<Vec<H256>, _>>()?,
-                    transactions: self
-                        .transaction_traces
-                        .iter()
-                        .map(|t| TransactionTraceAt::new(t, self).try_into())
-                        .collect::<Result<Vec<web3::types::Transaction>, Error>>()?,
-                    size: Some(U256::from(self.size)),
-                    mix_hash: Some(header.mix_hash.try_decode_proto("mix hash")?),
-                    nonce: Some(H64::from_low_u64_be(header.nonce)),
+                        total_difficulty: Some(
+                            header
+                                .total_difficulty
+                                .as_ref()
+                                .map_or_else(U256::default, |v| v.into()),
+                        ),
+                        // FIXME (SF): Firehose does not have seal fields, are they really used? Might be required for POA chains only also, I've seen that stuff on xDai (is this important?)
+                        seal_fields: vec![],
+                        uncles: self
+                            .uncles
+                            .iter()
+                            .map(|u| u.hash.try_decode_proto("uncle hash"))
+                            .collect::<Result<Vec<H256>, _>>()?,
+                        transactions: self
+                            .transaction_traces
+                            .iter()
+                            .map(|t| TransactionTraceAt::new(t, self).try_into())
+                            .collect::<Result<Vec<web3::types::Transaction>, Error>>()?,
+                        size: Some(U256::from(self.size)),
+                        mix_hash: Some(header.mix_hash.try_decode_proto("mix hash")?),
+                        nonce: Some(H64::from_low_u64_be(header.nonce)),
+                    },
+                    // Firehose only has the balance changes of withdrawals, not
+                    // the withdrawals themselves
+                    post_merge: PostMergeFields {
+                        withdrawals: None,
+                        parent_beacon_block_root: match header.parent_beacon_root.len() {
+                            0 => None,
+                            _ => Some(
+                                header
+                                    .parent_beacon_root
+                                    .try_decode_proto("parent beacon root")?,
+                            ),
+                        },
+                        blob_gas_used: header.blob_gas_used.map(U64::from),
+                        excess_blob_gas: header.excess_blob_gas.map(U64::from),
+                    },
+                    l2_transactions: Default::default(),
                 }),
                 transaction_receipts: self
                     .transaction_traces
@@ -343,8 +366,10 @@ impl TryInto<EthereumBlockWithCalls> for &Block {
                                     .try_decode_proto("transaction logs bloom")?,
                                 from: t.from.try_decode_proto("transaction from")?,
                                 to: get_to_address(t)?,
-                                transaction_type: None,
-                                effective_gas_price: None,
+                                transaction_type: Some(U64::from(t.r#type as u64)),
+                                // For dynamic fee transactions, Firehose already
+                                // reports the effective gas price as the gas price
+                                effective_gas_price: t.gas_price.as_ref().map(|x| x.into()),
                             })
                         })
                     })
@@ -352,8 +377,9 @@ impl TryInto<EthereumBlockWithCalls> for &Block {
                     .into_iter()
                     // Transaction receipts will be shared along the code, so we put them into an
                     // Arc here to avoid excessive cloning.
-                    .map(Arc::new)
+                    .map(|receipt| Arc::new(receipt.into()))
                     .collect(),
+                slim: false,
             },
             // Comment (437a9f17-67cc-478f-80a3-804fe554b227): This Some() will avoid calls in the triggers_in_block
             // TODO: Refactor in a way that this is no longer needed.
@@ -506,7 +532,7 @@ mod test {
 
     use crate::codec::BlockHeader;
 
-    use super::Block;
+    use super::*;
 
     #[test]
     fn ensure_block_serialization() {
@@ -528,6 +554,46 @@ mod test {
             format!(r#"{{"block":{{"data":null,"timestamp":"{}"}}}}"#, now)
         );
     }
+
+    #[test]
+    fn transaction_with_access_list() {
+        let block = Block {
+            hash: vec![1; 32],
+            number: 10,
+            ..Default::default()
+        };
+        let mut trace = TransactionTrace {
+            hash: vec![2; 32],
+            from: vec![3; 20],
+            to: vec![4; 20],
+            r#type: transaction_trace::Type::TrxTypeDynamicFee as i32,
+            access_list: vec![AccessTuple {
+                address: vec![5; 20],
+                storage_keys: vec![vec![6; 32], vec![7; 32]],
+            }],
+            max_fee_per_gas: Some(BigInt { bytes: vec![100] }),
+            ..Default::default()
+        };
+
+        let tx: web3::types::Transaction =
+            TransactionTraceAt::new(&trace, &block).try_into().unwrap();
+        assert_eq!(Some(U64::from(2)), tx.transaction_type);
+        assert_eq!(Some(U256::from(100)), tx.max_fee_per_gas);
+        let access_list = tx.access_list.unwrap();
+        assert_eq!(1, access_list.len());
+        assert_eq!(H160::repeat_byte(5), access_list[0].address);
+        assert_eq!(
+            vec![H256::repeat_byte(6), H256::repeat_byte(7)],
+            access_list[0].storage_keys
+        );
+
+        trace.r#type = transaction_trace::Type::TrxTypeLegacy as i32;
+        trace.access_list = vec![];
+        let tx: web3::types::Transaction =
+            TransactionTraceAt::new(&trace, &block).try_into().unwrap();
+        assert_eq!(Some(U64::from(0)), tx.transaction_type);
+        assert_eq!(None, tx.access_list);
+    }
 }
 
 fn get_to_address(trace: &TransactionTrace) -> Result<Option<H160>, Error> {
@@ -544,3 +610,27 @@ fn get_to_address(trace: &TransactionTrace) -> Result<Option<H160>, Error> {
         Ok(Some(trace.to.try_decode_proto("transaction to address")?))
     }
 }
+
+fn get_access_list(trace: &TransactionTrace) -> Result<Option<AccessList>, Error> {
+    // Legacy transactions have no access list, but later transaction
+    // types always have one, even if it is empty
+    if trace.r#type == transaction_trace::Type::TrxTypeLegacy as i32 {
+        return Ok(None);
+    }
+
+    trace
+        .access_list
+        .iter()
+        .map(|tuple| {
+            Ok(AccessListItem {
+                address: tuple.address.try_decode_proto("access list address")?,
+                storage_keys: tuple
+                    .storage_keys
+                    .iter()
+                    .map(|key| key.try_decode_proto("access list storage key"))
+                    .collect::<Result<Vec<H256>, Error>>()?,
+            })
+        })
+        .collect::<Result<AccessList, Error>>()
+        .map(Some)
+}
@@ -42,8 +42,8 @@ use graph::{
 };
 
 use graph::data::subgraph::{
-    calls_host_fn, DataSourceContext, Source, MIN_SPEC_VERSION, SPEC_VERSION_0_0_8,
-    SPEC_VERSION_1_2_0,
+    calls_host_fn, DataSourceContext, Source, API_VERSION_0_0_11, MIN_SPEC_VERSION,
+    SPEC_VERSION_0_0_8, SPEC_VERSION_1_2_0,
 };
 
 use crate::adapter::EthereumAdapter as _;
@@ -148,12 +148,13 @@ impl blockchain::DataSource<Chain> for DataSource {
 
         let Mapping {
             event_handlers,
+            fallback_event_handler,
             call_handlers,
             block_handlers,
             ..
         } = &self.mapping;
 
-        if !event_handlers.is_empty() {
+        if !event_handlers.is_empty() || fallback_event_handler.is_some() {
             kinds.insert(EVENT_HANDLER_KIND);
         }
         if !call_handlers.is_empty() {
@@ -231,6 +232,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             && address == &other.address
             && mapping.abis == other.mapping.abis
             && mapping.event_handlers == other.mapping.event_handlers
+            && mapping.fallback_event_handler == other.mapping.fallback_event_handler
             && mapping.call_handlers == other.mapping.call_handlers
             && mapping.block_handlers == other.mapping.block_handlers
             && context == &other.context
@@ -306,7 +308,10 @@ impl blockchain::DataSource<Chain> for DataSource {
         let no_source_address = self.address().is_none();
         let has_call_handlers = !self.mapping.call_handlers.is_empty();
         let has_block_handlers = !self.mapping.block_handlers.is_empty();
-        if no_source_address && (has_call_handlers || has_block_handlers) {
+        let has_fallback_event_handler = self.mapping.fallback_event_handler.is_some();
+        if no_source_address
+            && (has_call_handlers || has_block_handlers || has_fallback_event_handler)
+        {
             errors.push(SubgraphManifestValidationError::SourceAddressRequired.into());
         };
 
@@ -371,6 +376,13 @@ impl blockchain::DataSource<Chain> for DataSource {
             }
         }
 
+        if has_fallback_event_handler && api_version < API_VERSION_0_0_11 {
+            errors.push(anyhow!(
+                "data source has a fallback event handler, but this is only supported for \
+                 apiVersion >= 0.0.11"
+            ));
+        }
+
         if spec_version < &SPEC_VERSION_1_2_0 {
             for handler in &self.mapping.event_handlers {
                 if !handler.calls.decls.is_empty() {
@@ -756,20 +768,32 @@ impl DataSource {
                     })
                     .collect::<Vec<_>>();
 
-                if matching_handlers.is_empty() {
-                    return Ok(None);
-                }
-
-                // Process the event with the matching handler
-                let (event_handler, params) = matching_handlers.pop().unwrap();
-
-                ensure!(
-                    matching_handlers.is_empty(),
-                    format!(
-                        "Multiple handlers defined for event `{}`, only one is supported",
-                        &event_handler.event
-                    )
-                );
+                let (signature, handler, params, calls) = match matching_handlers.pop() {
+                    Some((event_handler, params)) => {
+                        ensure!(
+                            matching_handlers.is_empty(),
+                            format!(
+                                "Multiple handlers defined for event `{}`, only one is supported",
+                                &event_handler.event
+                            )
+                        );
+                        let calls =
+                            DeclaredCall::new(&self.mapping, &event_handler, &log, &params)?;
+                        (event_handler.event, event_handler.handler, params, calls)
+                    }
+                    // Logs that no event handler decoded go to the fallback handler
+                    // undecoded
+                    None => match &self.mapping.fallback_event_handler {
+                        Some(fallback) => {
+                            let signature = match log.topics.first() {
+                                Some(topic0) => format!("{:?}", topic0),
+                                None => "anonymous".to_string(),
+                            };
+                            (signature, fallback.handler.clone(), vec![], vec![])
+                        }
+                        None => return Ok(None),
+                    },
+                };
 
                 // Special case: In Celo, there are Epoch Rewards events, which do not have an
                 // associated transaction and instead have `transaction_hash == block.hash`,
@@ -797,12 +821,10 @@ impl DataSource {
                 };
 
                 let logging_extras = Arc::new(o! {
-                    "signature" => event_handler.event.to_string(),
+                    "signature" => signature,
                     "address" => format!("{}", &log.address),
                     "transaction" => format!("{}", &transaction.hash),
                 });
-                let handler = event_handler.handler.clone();
-                let calls = DeclaredCall::new(&self.mapping, &event_handler, &log, &params)?;
                 Ok(Some(TriggerWithHandler::<Chain>::new_with_logging_extras(
                     MappingTrigger::Log {
                         block: block.cheap_clone(),
@@ -1214,6 +1236,52 @@ impl blockchain::DecoderHook<Chain> for DecoderHook {
 
         Ok(runnables)
     }
+
+    async fn warm_up(
+        &self,
+        logger: &Logger,
+        block_ptr: &BlockPtr,
+        triggers: Vec<MappingTrigger>,
+    ) -> Result<(), Error> {
+        if ENV_VARS.mappings.disable_declared_calls {
+            return Ok(());
+        }
+
+        let mut calls: Vec<DeclaredCall> = Vec::new();
+        for trigger in triggers {
+            if let MappingTrigger::Log {
+                calls: declared, ..
+            } = trigger
+            {
+                for call in declared {
+                    if !calls.contains(&call) {
+                        calls.push(call);
+                    }
+                }
+            }
+        }
+        if calls.is_empty() {
+            return Ok(());
+        }
+
+        // The results end up in the call cache, which is all we need them for
+        let calls: Vec<_> = calls
+            .into_iter()
+            .map(|call| call.as_eth_call(block_ptr.clone(), self.eth_call_gas).0)
+            .collect();
+        let eth_adapter = self.eth_adapters.call_or_cheapest(Some(&NodeCapabilities {
+            archive: true,
+            traces: false,
+        }))?;
+        eth_adapter
+            .contract_calls(
+                logger,
+                &calls.iter().collect::<Vec<_>>(),
+                self.call_cache.cheap_clone(),
+            )
+            .await?;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
@@ -1341,6 +1409,8 @@ pub struct UnresolvedMapping {
     pub call_handlers: Vec<MappingCallHandler>,
     #[serde(default)]
     pub event_handlers: Vec<MappingEventHandler>,
+    #[serde(default)]
+    pub fallback_event_handler: Option<MappingFallbackEventHandler>,
     pub file: Link,
 }
 
@@ -1354,6 +1424,7 @@ pub struct Mapping {
     pub block_handlers: Vec<MappingBlockHandler>,
     pub call_handlers: Vec<MappingCallHandler>,
     pub event_handlers: Vec<MappingEventHandler>,
+    pub fallback_event_handler: Option<MappingFallbackEventHandler>,
     pub runtime: Arc<Vec<u8>>,
     pub link: Link,
 }
@@ -1398,6 +1469,7 @@ impl UnresolvedMapping {
             block_handlers,
             call_handlers,
             event_handlers,
+            fallback_event_handler,
             file: link,
         } = self;
 
@@ -1430,6 +1502,7 @@ impl UnresolvedMapping {
             block_handlers: block_handlers.clone(),
             call_handlers: call_handlers.clone(),
             event_handlers: event_handlers.clone(),
+            fallback_event_handler,
             runtime,
             link,
         })
@@ -1626,6 +1699,16 @@ impl MappingEventHandler {
     }
 }
 
+/// The handler for logs of the data source's contract that no event handler
+/// decoded, for example logs of anonymous events or of events that are not
+/// in the ABI. It receives the topics and the data of the log undecoded
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingFallbackEventHandler {
+    pub handler: String,
+    #[serde(default)]
+    pub receipt: bool,
+}
+
 /// Hashes a string to a H256 hash.
 fn string_to_h256(s: &str) -> H256 {
     let mut result = [0u8; 32];
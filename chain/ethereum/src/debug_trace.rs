@@ -0,0 +1,219 @@
+//! Call triggers from `debug_traceBlockByHash` with the `callTracer` that
+//! Geth and Erigon have built in, for providers that do not support the
+//! OpenEthereum-style `trace_filter`
+use graph::prelude::{
+    anyhow::{anyhow, Context},
+    serde_json::{self, json},
+    web3::types::{Bytes, H160, H256, U256},
+    BlockNumber, Error, EthereumCall,
+};
+use serde::Deserialize;
+
+/// The options for `debug_traceBlockByHash` that select the `callTracer`
+pub(crate) fn call_tracer_options() -> serde_json::Value {
+    json!({ "tracer": "callTracer" })
+}
+
+#[derive(Debug, Deserialize)]
+struct TransactionTrace {
+    result: CallFrame,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallFrame {
+    #[serde(rename = "type")]
+    kind: String,
+    from: H160,
+    #[serde(default)]
+    to: Option<H160>,
+    #[serde(default)]
+    value: Option<U256>,
+    #[serde(default)]
+    gas_used: U256,
+    #[serde(default)]
+    input: Bytes,
+    #[serde(default)]
+    output: Option<Bytes>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    fn is_call(&self) -> bool {
+        matches!(
+            self.kind.as_str(),
+            "CALL" | "CALLCODE" | "DELEGATECALL" | "STATICCALL"
+        )
+    }
+}
+
+/// Turn the result of `debug_traceBlockByHash` for a block into the calls
+/// that call handlers can trigger on. `transactions` are the hashes of the
+/// transactions of the block, in the same order as the traces.
+///
+/// The calls are the same that `trace_filter` produces through
+/// `EthereumCall::try_from_trace` so that a subgraph gets the same triggers
+/// from either kind of provider: calls that failed themselves are left out,
+/// but calls that succeeded inside of a call that failed later are not
+pub(crate) fn calls_from_traces(
+    traces: serde_json::Value,
+    block_number: BlockNumber,
+    block_hash: H256,
+    transactions: &[H256],
+) -> Result<Vec<EthereumCall>, Error> {
+    fn collect(
+        frame: &CallFrame,
+        block_number: BlockNumber,
+        block_hash: H256,
+        transaction_hash: H256,
+        transaction_index: u64,
+        calls: &mut Vec<EthereumCall>,
+    ) {
+        // Contract to contract value transfers have no input, and call
+        // handlers only trigger on explicit method calls
+        if frame.is_call() && frame.error.is_none() && frame.input.0.len() >= 4 {
+            if let Some(to) = frame.to {
+                calls.push(EthereumCall {
+                    from: frame.from,
+                    to,
+                    value: frame.value.unwrap_or_default(),
+                    gas_used: frame.gas_used,
+                    input: frame.input.clone(),
+                    output: frame.output.clone().unwrap_or_default(),
+                    block_number,
+                    block_hash,
+                    transaction_hash: Some(transaction_hash),
+                    transaction_index,
+                });
+            }
+        }
+        for call in &frame.calls {
+            collect(
+                call,
+                block_number,
+                block_hash,
+                transaction_hash,
+                transaction_index,
+                calls,
+            );
+        }
+    }
+
+    let traces: Vec<TransactionTrace> =
+        serde_json::from_value(traces).context("invalid callTracer result")?;
+    if traces.len() != transactions.len() {
+        return Err(anyhow!(
+            "debug_traceBlockByHash returned {} traces for the {} transactions of block {}",
+            traces.len(),
+            transactions.len(),
+            block_hash
+        ));
+    }
+
+    let mut calls = Vec::new();
+    for (index, (trace, transaction_hash)) in traces.iter().zip(transactions).enumerate() {
+        collect(
+            &trace.result,
+            block_number,
+            block_hash,
+            *transaction_hash,
+            index as u64,
+            &mut calls,
+        );
+    }
+    Ok(calls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_from_call_tracer_result() {
+        // One transaction that calls a contract which makes a successful
+        // static call and a value transfer, then creates a contract which
+        // makes a call, and finally makes a call that fails
+        let traces = json!([{
+            "result": {
+                "type": "CALL",
+                "from": "0x0000000000000000000000000000000000000001",
+                "to": "0x0000000000000000000000000000000000000002",
+                "value": "0x5",
+                "gas": "0x1000",
+                "gasUsed": "0x100",
+                "input": "0x12345678",
+                "output": "0x01",
+                "calls": [
+                    {
+                        "type": "STATICCALL",
+                        "from": "0x0000000000000000000000000000000000000002",
+                        "to": "0x0000000000000000000000000000000000000003",
+                        "gas": "0x800",
+                        "gasUsed": "0x10",
+                        "input": "0xaabbccdd00"
+                    },
+                    {
+                        "type": "CALL",
+                        "from": "0x0000000000000000000000000000000000000002",
+                        "to": "0x0000000000000000000000000000000000000004",
+                        "value": "0x1",
+                        "gas": "0x800",
+                        "gasUsed": "0x0",
+                        "input": "0x"
+                    },
+                    {
+                        "type": "CREATE",
+                        "from": "0x0000000000000000000000000000000000000002",
+                        "to": "0x0000000000000000000000000000000000000005",
+                        "gas": "0x800",
+                        "gasUsed": "0x20",
+                        "input": "0x60806040",
+                        "calls": [{
+                            "type": "CALL",
+                            "from": "0x0000000000000000000000000000000000000005",
+                            "to": "0x0000000000000000000000000000000000000003",
+                            "gas": "0x400",
+                            "gasUsed": "0x10",
+                            "input": "0x11111111"
+                        }]
+                    },
+                    {
+                        "type": "CALL",
+                        "from": "0x0000000000000000000000000000000000000002",
+                        "to": "0x0000000000000000000000000000000000000003",
+                        "gas": "0x400",
+                        "gasUsed": "0x400",
+                        "input": "0x22222222",
+                        "error": "execution reverted"
+                    }
+                ]
+            }
+        }]);
+
+        let block_hash = H256::from_low_u64_be(7);
+        let transaction = H256::from_low_u64_be(8);
+        let calls = calls_from_traces(traces.clone(), 10, block_hash, &[transaction]).unwrap();
+
+        let inputs: Vec<_> = calls.iter().map(|call| call.input.0.clone()).collect();
+        assert_eq!(
+            vec![
+                vec![0x12, 0x34, 0x56, 0x78],
+                vec![0xaa, 0xbb, 0xcc, 0xdd, 0x00],
+                vec![0x11, 0x11, 0x11, 0x11],
+            ],
+            inputs
+        );
+        assert_eq!(U256::from(5), calls[0].value);
+        assert_eq!(Bytes(vec![1]), calls[0].output);
+        assert_eq!(U256::zero(), calls[1].value);
+        assert!(calls.iter().all(|call| call.block_number == 10
+            && call.block_hash == block_hash
+            && call.transaction_hash == Some(transaction)
+            && call.transaction_index == 0));
+
+        assert!(calls_from_traces(traces, 10, block_hash, &[]).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Cross-check the JSON-RPC providers of a chain against each other. A
+//! provider that follows a different fork than the others, or that stopped
+//! following the chain, otherwise goes unnoticed until subgraphs have
+//! indexed its blocks
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use graph::components::adapter::ChainId;
+use graph::futures03::compat::Future01CompatExt as _;
+use graph::futures03::future::join_all;
+use graph::prelude::{
+    info, warn, web3::types::H256, BlockNumber, GaugeVec, Logger, MetricsRegistry,
+};
+use graph::tokio;
+
+use crate::adapter::EthereumAdapter as _;
+use crate::network::EthereumNetworkAdapters;
+use crate::{EthereumAdapter, ENV_VARS};
+
+/// The metrics are shared by the monitors of all chains
+struct DivergenceMetrics {
+    diverged: GaugeVec,
+    head_lag: GaugeVec,
+    quarantined: GaugeVec,
+}
+
+impl DivergenceMetrics {
+    fn new(registry: &MetricsRegistry) -> Self {
+        let labels = ["network", "provider"];
+        let diverged = registry
+            .global_gauge_vec(
+                "eth_rpc_provider_diverged",
+                "Whether the block hashes of the provider disagreed with the majority of \
+                 the providers of its chain in the last check; 0 = no, 1 = yes",
+                &labels,
+            )
+            .unwrap();
+        let head_lag = registry
+            .global_gauge_vec(
+                "eth_rpc_provider_head_lag",
+                "How many blocks the head of the provider was behind the highest head of \
+                 the providers of its chain in the last check",
+                &labels,
+            )
+            .unwrap();
+        let quarantined = registry
+            .global_gauge_vec(
+                "eth_rpc_provider_quarantined",
+                "Whether the provider is not used because it diverged; 0 = no, 1 = yes",
+                &labels,
+            )
+            .unwrap();
+        Self {
+            diverged,
+            head_lag,
+            quarantined,
+        }
+    }
+}
+
+/// The outcome of comparing the hashes that providers reported for the
+/// same block
+#[derive(Debug, PartialEq)]
+pub(crate) struct Divergence {
+    /// The hash that more than half of the providers reported
+    pub majority: Option<H256>,
+    /// The providers that reported a different hash than the majority, or
+    /// all providers if there is no majority and they do not all agree
+    pub diverged: Vec<String>,
+}
+
+impl Divergence {
+    pub fn from_hashes(hashes: &[(String, H256)]) -> Self {
+        let mut counts: HashMap<H256, usize> = HashMap::new();
+        for (_, hash) in hashes {
+            *counts.entry(*hash).or_default() += 1;
+        }
+        let majority = counts
+            .iter()
+            .find(|(_, count)| **count * 2 > hashes.len())
+            .map(|(hash, _)| *hash);
+
+        let diverged = match majority {
+            Some(majority) => hashes
+                .iter()
+                .filter(|(_, hash)| *hash != majority)
+                .map(|(provider, _)| provider.clone())
+                .collect(),
+            None if counts.len() > 1 => hashes
+                .iter()
+                .map(|(provider, _)| provider.clone())
+                .collect(),
+            None => vec![],
+        };
+        Divergence { majority, diverged }
+    }
+}
+
+/// Periodically compares the chain heads of the providers of a chain and
+/// the hashes they report for a recent block, and optionally quarantines
+/// providers whose hashes disagree with the majority
+pub struct DivergenceMonitor {
+    logger: Logger,
+    chain_id: ChainId,
+    adapters: EthereumNetworkAdapters,
+    metrics: DivergenceMetrics,
+}
+
+impl DivergenceMonitor {
+    pub fn new(
+        logger: Logger,
+        chain_id: ChainId,
+        adapters: EthereumNetworkAdapters,
+        registry: &MetricsRegistry,
+    ) -> Self {
+        Self {
+            logger,
+            chain_id,
+            adapters,
+            metrics: DivergenceMetrics::new(registry),
+        }
+    }
+
+    pub async fn run(self) {
+        let interval = ENV_VARS.divergence_check_interval;
+        if interval.is_zero() {
+            return;
+        }
+
+        loop {
+            tokio::time::sleep(interval).await;
+            self.check().await;
+        }
+    }
+
+    async fn check(&self) {
+        let adapters = self.adapters.all_including_quarantined();
+        if adapters.len() < 2 {
+            return;
+        }
+
+        let heads = join_all(adapters.iter().map(|adapter| async move {
+            let head = adapter
+                .latest_block_header(&self.logger)
+                .compat()
+                .await
+                .ok()
+                .and_then(|block| block.number)
+                .map(|number| number.as_u64() as BlockNumber);
+            (adapter, head)
+        }))
+        .await;
+        let heads: Vec<(&Arc<EthereumAdapter>, BlockNumber)> = heads
+            .into_iter()
+            .filter_map(|(adapter, head)| head.map(|head| (adapter, head)))
+            .collect();
+        let highest = match heads.iter().map(|(_, head)| *head).max() {
+            Some(highest) => highest,
+            None => return,
+        };
+
+        let mut lowest = highest;
+        for (adapter, head) in &heads {
+            let lag = highest - head;
+            self.metrics
+                .head_lag
+                .with_label_values(&[self.chain_id.as_str(), adapter.provider()])
+                .set(lag as f64);
+            if lag > ENV_VARS.divergence_max_lag {
+                warn!(self.logger, "Provider is lagging behind the other providers of the chain";
+                    "provider" => adapter.provider(),
+                    "head" => head,
+                    "highest_head" => highest,
+                    "lag" => lag);
+            } else {
+                lowest = lowest.min(*head);
+            }
+        }
+
+        let number = lowest - ENV_VARS.divergence_check_depth;
+        if number < 0 {
+            return;
+        }
+        let hashes = join_all(heads.iter().map(|(adapter, _)| async move {
+            let hash = adapter
+                .block_hash_by_block_number(&self.logger, number)
+                .compat()
+                .await
+                .ok()
+                .flatten();
+            (adapter.provider().to_string(), hash)
+        }))
+        .await;
+        let hashes: Vec<_> = hashes
+            .into_iter()
+            .filter_map(|(provider, hash)| hash.map(|hash| (provider, hash)))
+            .collect();
+        if hashes.len() < 2 {
+            return;
+        }
+
+        let divergence = Divergence::from_hashes(&hashes);
+        for (provider, hash) in &hashes {
+            let diverged = divergence.diverged.contains(provider);
+            self.metrics
+                .diverged
+                .with_label_values(&[self.chain_id.as_str(), provider])
+                .set(if diverged { 1.0 } else { 0.0 });
+
+            if diverged {
+                warn!(self.logger, "Provider disagrees with the other providers of the chain";
+                    "provider" => provider,
+                    "block_number" => number,
+                    "hash" => format!("{:x}", hash),
+                    "majority_hash" => divergence
+                        .majority
+                        .map(|hash| format!("{:x}", hash))
+                        .unwrap_or_else(|| "none".to_string()));
+            }
+
+            // Without a majority, we can not tell which provider is right
+            if ENV_VARS.divergence_quarantine && divergence.majority.is_some() {
+                self.quarantine(provider, diverged);
+            }
+        }
+    }
+
+    fn quarantine(&self, provider: &str, quarantine: bool) {
+        let was_quarantined = self
+            .metrics
+            .quarantined
+            .with_label_values(&[self.chain_id.as_str(), provider])
+            .get()
+            > 0.0;
+        let quarantined = self.adapters.set_quarantined(provider, quarantine);
+        self.metrics
+            .quarantined
+            .with_label_values(&[self.chain_id.as_str(), provider])
+            .set(if quarantined { 1.0 } else { 0.0 });
+
+        if quarantined && !was_quarantined {
+            warn!(self.logger, "Quarantined provider"; "provider" => provider);
+        } else if !quarantined && was_quarantined {
+            info!(self.logger, "Released provider from quarantine"; "provider" => provider);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divergence_from_hashes() {
+        let a = H256::from_low_u64_be(1);
+        let b = H256::from_low_u64_be(2);
+        let hashes = |hashes: &[(&str, H256)]| {
+            hashes
+                .iter()
+                .map(|(provider, hash)| (provider.to_string(), *hash))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            Divergence {
+                majority: Some(a),
+                diverged: vec![]
+            },
+            Divergence::from_hashes(&hashes(&[("p1", a), ("p2", a)]))
+        );
+        assert_eq!(
+            Divergence {
+                majority: Some(a),
+                diverged: vec!["p2".to_string()]
+            },
+            Divergence::from_hashes(&hashes(&[("p1", a), ("p2", b), ("p3", a)]))
+        );
+        // Two providers that disagree have no majority
+        assert_eq!(
+            Divergence {
+                majority: None,
+                diverged: vec!["p1".to_string(), "p2".to_string()]
+            },
+            Divergence::from_hashes(&hashes(&[("p1", a), ("p2", b)]))
+        );
+    }
+}
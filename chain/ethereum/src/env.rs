@@ -30,6 +30,13 @@ pub struct EnvVars {
     /// default value is 500 blocks, which is reasonable according to Ethereum
     /// node operators.
     pub max_event_only_range: BlockNumber,
+    /// `eth_getLogs` requests that take less than this let ranges grow
+    /// again after a provider needed smaller ranges.
+    ///
+    /// Set by the environment variable
+    /// `GRAPH_ETHEREUM_GET_LOGS_FAST_RESPONSE` (expressed in seconds). The
+    /// default value is 5 seconds.
+    pub get_logs_fast_response: Duration,
     /// Set by the environment variable `ETHEREUM_BLOCK_BATCH_SIZE`. The
     /// default value is 10 blocks.
     pub block_batch_size: usize,
@@ -66,6 +73,12 @@ pub struct EnvVars {
     /// by default on macOS (to avoid DNS issues) and disabled by default on all
     /// other systems.
     pub fetch_receipts_in_batches: bool,
+    /// Store blocks that the block ingestor fetches without receipts, and
+    /// only fetch the receipts of a block when a block stream needs them.
+    /// See `EthereumBlock::slim`
+    ///
+    /// Set by the flag `GRAPH_ETHEREUM_RECEIPT_LIGHT_BLOCKS`. Off by default.
+    pub receipt_light_blocks: bool,
     /// `graph_node::config` disallows setting this in a store with multiple
     /// shards. See 8b6ad0c64e244023ac20ced7897fe666 for the reason.
     ///
@@ -88,6 +101,60 @@ pub struct EnvVars {
     /// This is a comma separated list of chain ids for which the gas field will not be set
     /// when calling `eth_call`.
     pub eth_call_no_gas: Vec<String>,
+    /// The largest number of calls that one `ethereum.callMany` sends to
+    /// the Ethereum node at the same time.
+    ///
+    /// Set by the environment variable `GRAPH_ETHEREUM_CALL_MANY_CONCURRENCY`.
+    /// The default value is 10.
+    pub call_many_concurrency: usize,
+    /// The largest number of calls that are batched into one call of the
+    /// Multicall3 contract of a chain that has one configured.
+    ///
+    /// Set by the environment variable `GRAPH_ETHEREUM_MULTICALL_BATCH_SIZE`.
+    /// The default value is 100.
+    pub multicall_batch_size: usize,
+    /// How long the block ingestor waits for a new head from a `newHeads`
+    /// subscription before it polls and subscribes again. Without a
+    /// provider that supports subscriptions, the ingestor polls.
+    ///
+    /// Set by the environment variable `GRAPH_ETHEREUM_NEW_HEADS_TIMEOUT`
+    /// (expressed in seconds). The default value is 60s.
+    pub new_heads_timeout: Duration,
+    /// How often the providers of a chain are checked against each other
+    /// for diverging chain heads and block hashes. A zero interval turns
+    /// the checks off.
+    ///
+    /// Set by the environment variable
+    /// `GRAPH_ETHEREUM_DIVERGENCE_CHECK_INTERVAL` (expressed in seconds).
+    /// The default value is 60s.
+    pub divergence_check_interval: Duration,
+    /// How many blocks below the lowest head of the providers of a chain
+    /// their block hashes are compared.
+    ///
+    /// Set by the environment variable
+    /// `GRAPH_ETHEREUM_DIVERGENCE_CHECK_DEPTH`. The default value is 10
+    /// blocks.
+    pub divergence_check_depth: BlockNumber,
+    /// A provider whose head is more than this many blocks behind the
+    /// highest head of the providers of its chain is reported as lagging.
+    ///
+    /// Set by the environment variable `GRAPH_ETHEREUM_DIVERGENCE_MAX_LAG`.
+    /// The default value is 100 blocks.
+    pub divergence_max_lag: BlockNumber,
+    /// Set by the flag `GRAPH_ETHEREUM_DIVERGENCE_QUARANTINE`. Whether
+    /// providers whose block hashes disagree with the majority of the
+    /// providers of their chain stop being used until they agree again.
+    /// Off by default.
+    pub divergence_quarantine: bool,
+    /// Deployments with `ingestion = "hybrid"` get their blocks over
+    /// Firehose while they are more than this many blocks behind the chain
+    /// head and poll JSON-RPC otherwise. Once polling, a deployment only
+    /// goes back to Firehose when it falls behind by twice this distance.
+    ///
+    /// Set by the environment variable
+    /// `GRAPH_ETHEREUM_HYBRID_SWITCH_DISTANCE`. The default value is 1000
+    /// blocks.
+    pub hybrid_switch_distance: BlockNumber,
 }
 
 // This does not print any values avoid accidentally leaking any sensitive env vars
@@ -115,6 +182,7 @@ impl From<Inner> for EnvVars {
                 .collect(),
             trace_stream_step_size: x.trace_stream_step_size,
             max_event_only_range: x.max_event_only_range,
+            get_logs_fast_response: Duration::from_secs(x.get_logs_fast_response_in_secs),
             block_batch_size: x.block_batch_size,
             max_block_range_size: x.max_block_range_size,
             json_rpc_timeout: Duration::from_secs(x.json_rpc_timeout_in_secs),
@@ -128,6 +196,7 @@ impl From<Inner> for EnvVars {
                 .fetch_receipts_in_batches
                 .map(|b| b.0)
                 .unwrap_or(cfg!(target_os = "macos")),
+            receipt_light_blocks: x.receipt_light_blocks.0,
             cleanup_blocks: x.cleanup_blocks.0,
             target_triggers_per_block_range: x.target_triggers_per_block_range,
             genesis_block_number: x.genesis_block_number,
@@ -137,6 +206,14 @@ impl From<Inner> for EnvVars {
                 .filter(|s| !s.is_empty())
                 .map(str::to_string)
                 .collect(),
+            call_many_concurrency: x.call_many_concurrency.max(1),
+            multicall_batch_size: x.multicall_batch_size.max(1),
+            new_heads_timeout: Duration::from_secs(x.new_heads_timeout_in_secs),
+            divergence_check_interval: Duration::from_secs(x.divergence_check_interval_in_secs),
+            divergence_check_depth: x.divergence_check_depth.max(0),
+            divergence_max_lag: x.divergence_max_lag,
+            divergence_quarantine: x.divergence_quarantine.0,
+            hybrid_switch_distance: x.hybrid_switch_distance.max(1),
         }
     }
 }
@@ -158,6 +235,8 @@ struct Inner {
     trace_stream_step_size: BlockNumber,
     #[envconfig(from = "GRAPH_ETHEREUM_MAX_EVENT_ONLY_RANGE", default = "500")]
     max_event_only_range: BlockNumber,
+    #[envconfig(from = "GRAPH_ETHEREUM_GET_LOGS_FAST_RESPONSE", default = "5")]
+    get_logs_fast_response_in_secs: u64,
     #[envconfig(from = "ETHEREUM_BLOCK_BATCH_SIZE", default = "10")]
     block_batch_size: usize,
     #[envconfig(from = "GRAPH_ETHEREUM_MAX_BLOCK_RANGE_SIZE", default = "2000")]
@@ -175,6 +254,8 @@ struct Inner {
     block_ingestor_max_concurrent_json_rpc_calls: usize,
     #[envconfig(from = "GRAPH_ETHEREUM_FETCH_TXN_RECEIPTS_IN_BATCHES")]
     fetch_receipts_in_batches: Option<EnvVarBoolean>,
+    #[envconfig(from = "GRAPH_ETHEREUM_RECEIPT_LIGHT_BLOCKS", default = "false")]
+    receipt_light_blocks: EnvVarBoolean,
     #[envconfig(from = "GRAPH_ETHEREUM_CLEANUP_BLOCKS", default = "false")]
     cleanup_blocks: EnvVarBoolean,
     #[envconfig(
@@ -186,4 +267,20 @@ struct Inner {
     genesis_block_number: u64,
     #[envconfig(from = "GRAPH_ETH_CALL_NO_GAS", default = "421613,421614")]
     eth_call_no_gas: String,
+    #[envconfig(from = "GRAPH_ETHEREUM_CALL_MANY_CONCURRENCY", default = "10")]
+    call_many_concurrency: usize,
+    #[envconfig(from = "GRAPH_ETHEREUM_MULTICALL_BATCH_SIZE", default = "100")]
+    multicall_batch_size: usize,
+    #[envconfig(from = "GRAPH_ETHEREUM_NEW_HEADS_TIMEOUT", default = "60")]
+    new_heads_timeout_in_secs: u64,
+    #[envconfig(from = "GRAPH_ETHEREUM_DIVERGENCE_CHECK_INTERVAL", default = "60")]
+    divergence_check_interval_in_secs: u64,
+    #[envconfig(from = "GRAPH_ETHEREUM_DIVERGENCE_CHECK_DEPTH", default = "10")]
+    divergence_check_depth: BlockNumber,
+    #[envconfig(from = "GRAPH_ETHEREUM_DIVERGENCE_MAX_LAG", default = "100")]
+    divergence_max_lag: BlockNumber,
+    #[envconfig(from = "GRAPH_ETHEREUM_DIVERGENCE_QUARANTINE", default = "false")]
+    divergence_quarantine: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_ETHEREUM_HYBRID_SWITCH_DISTANCE", default = "1000")]
+    hybrid_switch_distance: BlockNumber,
 }
@@ -7,10 +7,11 @@ use graph::data::store::ethereum::call;
 use graph::data::store::scalar;
 use graph::data::subgraph::UnifiedMappingApiVersion;
 use graph::data::subgraph::API_VERSION_0_0_7;
+use graph::endpoint::RequestClass;
 use graph::futures01::stream;
 use graph::futures01::Future;
 use graph::futures01::Stream;
-use graph::futures03::future::try_join_all;
+use graph::futures03::future::{join_all, try_join_all};
 use graph::futures03::{
     self, compat::Future01CompatExt, FutureExt, StreamExt, TryFutureExt, TryStreamExt,
 };
@@ -18,6 +19,7 @@ use graph::prelude::ethabi::ParamType;
 use graph::prelude::ethabi::Token;
 use graph::prelude::tokio::try_join;
 use graph::prelude::web3::types::U256;
+use graph::prelude::web3::Transport as _;
 use graph::slog::o;
 use graph::tokio::sync::RwLock;
 use graph::tokio::time::timeout;
@@ -31,7 +33,7 @@ use graph::{
             self,
             types::{
                 Address, BlockId, BlockNumber as Web3BlockNumber, Bytes, CallRequest, Filter,
-                FilterBuilder, Log, Transaction, TransactionReceipt, H256,
+                FilterBuilder, Log, Transaction, TransactionReceipt, H256, U64,
             },
         },
         BlockNumber, ChainStore, CheapClone, DynTryFuture, Error, EthereumCallCache, Logger,
@@ -50,11 +52,15 @@ use std::convert::TryFrom;
 use std::iter::FromIterator;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::adapter::EthereumRpcError;
 use crate::adapter::ProviderStatus;
+use crate::blobs::BlockBlobs;
 use crate::chain::BlockFinality;
+use crate::debug_trace;
+use crate::ingestor::HeadBlock;
+use crate::proofs::AccountProof;
 use crate::trigger::LogRef;
 use crate::Chain;
 use crate::NodeCapabilities;
@@ -78,8 +84,20 @@ pub struct EthereumAdapter {
     supports_eip_1898: bool,
     call_only: bool,
     supports_block_receipts: Arc<RwLock<Option<bool>>>,
+    /// Whether to get the calls for call handlers through
+    /// `debug_traceBlockByHash` instead of `trace_filter`
+    debug_traces: bool,
+    /// The address of the Multicall3 contract through which calls for the
+    /// same block are batched, if the chain has one
+    multicall: Option<Address>,
+    /// The size of block ranges for `eth_getLogs` that the provider copes
+    /// with, learned from its responses to all deployments
+    log_range: Arc<LogRange>,
 }
 
+/// The function of Multicall3 that we batch calls with
+const MULTICALL_AGGREGATE3: &str = "aggregate3((address,bool,bytes)[])";
+
 impl CheapClone for EthereumAdapter {
     fn cheap_clone(&self) -> Self {
         Self {
@@ -90,8 +108,53 @@ impl CheapClone for EthereumAdapter {
             supports_eip_1898: self.supports_eip_1898,
             call_only: self.call_only,
             supports_block_receipts: self.supports_block_receipts.cheap_clone(),
+            debug_traces: self.debug_traces,
+            multicall: self.multicall,
+            log_range: self.log_range.cheap_clone(),
+        }
+    }
+}
+
+/// Adapts the number of blocks that we ask for in one `eth_getLogs` to the
+/// provider: ranges grow while the provider answers quickly, and shrink
+/// when it says that a range has too many logs or takes too long for it.
+/// Sizes are in steps, the number of blocks past the first block of a range
+#[derive(Debug, Default)]
+struct LogRange {
+    /// `None` until the provider had trouble with a range, since there is
+    /// no reason to limit ranges before that
+    step: std::sync::Mutex<Option<BlockNumber>>,
+}
+
+impl LogRange {
+    /// The step for a range that should be at most `max_step`
+    fn step(&self, max_step: BlockNumber) -> BlockNumber {
+        match *self.step.lock().unwrap() {
+            Some(step) => step.min(max_step),
+            None => max_step,
+        }
+    }
+
+    /// A request with `step` returned all logs after `elapsed`
+    fn succeeded(&self, step: BlockNumber, elapsed: Duration) {
+        let mut learned = self.step.lock().unwrap();
+        if let Some(learned_step) = *learned {
+            // Only grow after requests for full ranges, the last range of
+            // a scan is usually shorter than that
+            if step >= learned_step && elapsed < ENV_VARS.get_logs_fast_response {
+                *learned = Some(learned_step.saturating_mul(2).max(1));
+            }
         }
     }
+
+    /// The provider would not, or could not in time, return the logs of a
+    /// range with `step`. Returns the smaller step to try instead
+    fn too_heavy(&self, step: BlockNumber) -> BlockNumber {
+        let new_step = step / 10;
+        let mut learned = self.step.lock().unwrap();
+        *learned = Some(learned.map_or(new_step, |learned_step| learned_step.min(new_step)));
+        new_step
+    }
 }
 
 impl EthereumAdapter {
@@ -106,6 +169,8 @@ impl EthereumAdapter {
         provider_metrics: Arc<ProviderEthRpcMetrics>,
         supports_eip_1898: bool,
         call_only: bool,
+        debug_traces: bool,
+        multicall: Option<Address>,
     ) -> Self {
         let web3 = Arc::new(Web3::new(transport));
 
@@ -126,6 +191,9 @@ impl EthereumAdapter {
             supports_eip_1898: supports_eip_1898 && !is_ganache,
             call_only,
             supports_block_receipts: Arc::new(RwLock::new(None)),
+            debug_traces,
+            multicall,
+            log_range: Arc::new(LogRange::default()),
         }
     }
 
@@ -222,6 +290,360 @@ impl EthereumAdapter {
             .await
     }
 
+    /// Get the calls in `block` from `debug_traceBlockByHash` with the
+    /// `callTracer`. Unlike `trace_filter`, that can not filter by the
+    /// address that is called, and always returns all calls in the block
+    async fn debug_traces(
+        self,
+        logger: Logger,
+        subgraph_metrics: Arc<SubgraphEthRpcMetrics>,
+        block: BlockId,
+    ) -> Result<Vec<EthereumCall>, Error> {
+        assert!(!self.call_only);
+
+        let eth = self.clone();
+        let retry_log_message = format!("debug_traceBlockByHash RPC call for block {:?}", block);
+        retry(retry_log_message, &logger)
+            .limit(ENV_VARS.request_retries)
+            .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+            .run(move || {
+                let eth = eth.cheap_clone();
+                let logger = logger.clone();
+                let subgraph_metrics = subgraph_metrics.clone();
+                let provider_metrics = eth.metrics.clone();
+                let provider = eth.provider.clone();
+
+                async move {
+                    let start = Instant::now();
+                    let result = eth.trace_block(block).await;
+
+                    let elapsed = start.elapsed().as_secs_f64();
+                    provider_metrics.observe_request(elapsed, "debug_traceBlockByHash", &provider);
+                    subgraph_metrics.observe_request(elapsed, "debug_traceBlockByHash", &provider);
+                    if let Err(e) = &result {
+                        provider_metrics.add_error("debug_traceBlockByHash", &provider);
+                        subgraph_metrics.add_error("debug_traceBlockByHash", &provider);
+                        debug!(logger, "Error tracing block";
+                            "block" => format!("{:?}", block),
+                            "error" => format!("{:#}", e));
+                    }
+                    result
+                }
+            })
+            .map_err(move |e| {
+                e.into_inner().unwrap_or_else(move || {
+                    anyhow::anyhow!(
+                        "Ethereum node took too long to respond to debug_traceBlockByHash \
+                         (block {:?})",
+                        block
+                    )
+                })
+            })
+            .await
+    }
+
+    /// Get the blob-carrying transactions of the block with `block_hash`.
+    /// Returns `None` if the Ethereum node does not know the block
+    /// Whether the transport supports subscriptions, which only WebSocket
+    /// and IPC do
+    pub(crate) fn supports_subscriptions(&self) -> bool {
+        !matches!(self.web3.transport(), Transport::RPC { .. })
+    }
+
+    pub(crate) async fn subscribe_new_heads(
+        &self,
+    ) -> Result<
+        futures03::stream::BoxStream<'static, Result<web3::types::BlockHeader, web3::Error>>,
+        Error,
+    > {
+        let heads = match self.web3.transport() {
+            Transport::WS(ws) => Web3::new(ws.clone())
+                .eth_subscribe()
+                .subscribe_new_heads()
+                .await
+                .map(|heads| heads.boxed()),
+            Transport::IPC(ipc) => Web3::new(ipc.clone())
+                .eth_subscribe()
+                .subscribe_new_heads()
+                .await
+                .map(|heads| heads.boxed()),
+            Transport::RPC { .. } => {
+                return Err(anyhow!(
+                    "provider {} does not support subscriptions",
+                    self.provider
+                ))
+            }
+        };
+        heads.map_err(|e| anyhow!("could not subscribe to newHeads: {}", e))
+    }
+
+    /// Like `load_full_block`, but instead of fetching the receipts of all
+    /// transactions, make receipts from the logs of the block which we get
+    /// with a single `eth_getLogs`. See `EthereumBlock::slim`
+    pub(crate) async fn load_slim_block(
+        &self,
+        logger: &Logger,
+        block: LightEthereumBlock,
+    ) -> Result<EthereumBlock, IngestorError> {
+        let block_hash = block.hash.expect("block is missing block hash");
+        if block.transactions.is_empty() {
+            return Ok(EthereumBlock {
+                block: Arc::new(block),
+                transaction_receipts: Vec::new(),
+                slim: true,
+            });
+        }
+
+        let web3 = self.web3.cheap_clone();
+        let logs = retry(
+            format!("eth_getLogs RPC call for block {:?}", block_hash),
+            logger,
+        )
+        .limit(ENV_VARS.request_retries)
+        .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+        .run(move || {
+            let web3 = web3.cheap_clone();
+            async move {
+                let logs = web3
+                    .transport()
+                    .execute(
+                        "eth_getLogs",
+                        vec![json::json!({ "blockHash": block_hash })],
+                    )
+                    .await
+                    .map_err(|e| anyhow!("could not get logs of block {:?}: {}", block_hash, e))?;
+                let logs: Vec<Log> = json::from_value(logs).map_err(|e| {
+                    IngestorError::Unknown(anyhow!(
+                        "invalid logs for block {:?}: {}",
+                        block_hash,
+                        e
+                    ))
+                })?;
+                Ok(logs)
+            }
+        })
+        .await
+        .map_err(move |e| {
+            e.into_inner().unwrap_or_else(move || {
+                anyhow!(
+                    "Ethereum node took too long to return logs of block {:?}",
+                    block_hash
+                )
+                .into()
+            })
+        })?;
+
+        let transaction_receipts = slim_receipts(&block, logs);
+        Ok(EthereumBlock {
+            block: Arc::new(block),
+            transaction_receipts,
+            slim: true,
+        })
+    }
+
+    /// The header of the block with the tag of `head`, like `latest_block_header`
+    /// for `HeadBlock::Latest`. We can not use `web3::types::BlockNumber`
+    /// for tags other than `latest` since it does not know about them
+    pub(crate) async fn tagged_block_header(
+        &self,
+        logger: &Logger,
+        head: HeadBlock,
+    ) -> Result<web3::types::Block<H256>, IngestorError> {
+        let web3 = self.web3.cheap_clone();
+        retry(
+            format!("eth_getBlockByNumber({}) no txs RPC call", head.tag()),
+            logger,
+        )
+        .no_limit()
+        .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+        .run(move || {
+            let web3 = web3.cheap_clone();
+            async move {
+                let block = web3
+                    .transport()
+                    .execute(
+                        "eth_getBlockByNumber",
+                        vec![json::json!(head.tag()), json::json!(false)],
+                    )
+                    .await
+                    .map_err(|e| {
+                        anyhow!("could not get {} block from Ethereum: {}", head.tag(), e)
+                    })?;
+                let block: Option<web3::types::Block<H256>> = json::from_value(block)
+                    .map_err(|e| anyhow!("invalid {} block: {}", head.tag(), e))?;
+                block.ok_or_else(|| {
+                    IngestorError::Unknown(anyhow!(
+                        "no {} block returned from Ethereum",
+                        head.tag()
+                    ))
+                })
+            }
+        })
+        .await
+        .map_err(move |e| {
+            e.into_inner().unwrap_or_else(move || {
+                anyhow!("Ethereum node took too long to return {} block", head.tag()).into()
+            })
+        })
+    }
+
+    pub(crate) async fn block_blobs(
+        &self,
+        logger: &Logger,
+        block_hash: H256,
+    ) -> Result<Option<BlockBlobs>, Error> {
+        let web3 = self.web3.cheap_clone();
+        retry(
+            format!("eth_getBlockByHash for blobs of block {:?}", block_hash),
+            logger,
+        )
+        .limit(ENV_VARS.request_retries)
+        .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+        .run(move || {
+            let web3 = web3.cheap_clone();
+            async move {
+                let block = web3
+                    .transport()
+                    .execute(
+                        "eth_getBlockByHash",
+                        vec![json::json!(block_hash), json::json!(true)],
+                    )
+                    .await?;
+                match block {
+                    json::Value::Null => Ok(None),
+                    block => BlockBlobs::from_json(block).map(Some),
+                }
+            }
+        })
+        .await
+        .map_err(|e| {
+            e.into_inner().unwrap_or_else(|| {
+                anyhow!(
+                    "Ethereum node took too long to return block {:?}",
+                    block_hash
+                )
+            })
+        })
+    }
+
+    /// The state root of the block with `block_hash`, or `None` if the
+    /// Ethereum node does not have that block
+    pub(crate) async fn state_root(
+        &self,
+        logger: &Logger,
+        block_hash: H256,
+    ) -> Result<Option<H256>, Error> {
+        let web3 = self.web3.cheap_clone();
+        retry(
+            format!(
+                "eth_getBlockByHash for state root of block {:?}",
+                block_hash
+            ),
+            logger,
+        )
+        .limit(ENV_VARS.request_retries)
+        .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+        .run(move || {
+            let web3 = web3.cheap_clone();
+            async move {
+                web3.eth()
+                    .block(BlockId::Hash(block_hash))
+                    .await
+                    .map(|block| block.map(|block| block.state_root))
+                    .map_err(Error::from)
+            }
+        })
+        .await
+        .map_err(|e| {
+            e.into_inner().unwrap_or_else(|| {
+                anyhow!(
+                    "Ethereum node took too long to return block {:?}",
+                    block_hash
+                )
+            })
+        })
+    }
+
+    /// The proof from `eth_getProof` for the account at `address` and its
+    /// storage `slots` in the block `block_ptr`. The proof is not verified
+    pub(crate) async fn account_proof(
+        &self,
+        logger: &Logger,
+        address: H160,
+        slots: Vec<H256>,
+        block_ptr: &BlockPtr,
+    ) -> Result<AccountProof, Error> {
+        let web3 = self.web3.cheap_clone();
+        let logger = Logger::new(logger, o!("provider" => self.provider.clone()));
+        let block_id = self.block_ptr_to_id(block_ptr);
+        retry(
+            format!("eth_getProof RPC call for block {}", block_ptr),
+            &logger,
+        )
+        .limit(ENV_VARS.request_retries)
+        .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+        .run(move || {
+            let web3 = web3.cheap_clone();
+            let slots = slots.clone();
+            async move {
+                let proof = web3
+                    .transport()
+                    .execute(
+                        "eth_getProof",
+                        vec![
+                            json::json!(address),
+                            json::json!(slots),
+                            json::json!(block_id),
+                        ],
+                    )
+                    .await?;
+                let proof = AccountProof::from_json(proof)?;
+                let keys = proof.storage_proof.iter().map(|slot| slot.key);
+                if proof.address != address || !keys.eq(slots.iter().cloned()) {
+                    bail!("eth_getProof returned the proof for a different account or slots");
+                }
+                Ok(proof)
+            }
+        })
+        .await
+        .map_err(|e| {
+            e.into_inner().unwrap_or_else(|| {
+                anyhow!(
+                    "Ethereum node took too long to return the proof for {:?}",
+                    address
+                )
+            })
+        })
+    }
+
+    async fn trace_block(&self, block_id: BlockId) -> Result<Vec<EthereumCall>, Error> {
+        // The traces do not say which block and transactions they are for,
+        // so we get the block first, and then trace it by hash to be sure
+        // that the traces are for that block
+        let block = self
+            .web3
+            .eth()
+            .block(block_id)
+            .await?
+            .ok_or_else(|| anyhow!("Ethereum node could not find block {:?}", block_id))?;
+        let hash = block
+            .hash
+            .ok_or_else(|| anyhow!("block {:?} has no hash", block_id))?;
+        let number = block
+            .number
+            .ok_or_else(|| anyhow!("block {} has no number", hash))?
+            .as_u64() as BlockNumber;
+        let traces = self
+            .web3
+            .transport()
+            .execute(
+                "debug_traceBlockByHash",
+                vec![json::json!(hash), debug_trace::call_tracer_options()],
+            )
+            .await?;
+        debug_trace::calls_from_traces(traces, number, hash, &block.transactions)
+    }
+
     // This is a lazy check for block receipt support. It is only called once and then the result is
     // cached. The result is not used for anything critical, so it is fine to be lazy.
     async fn check_block_receipt_support_and_update_cache(
@@ -314,7 +736,9 @@ impl EthereumAdapter {
                         .to_block(to.into())
                         .address(filter.contracts.clone())
                         .topics(
-                            Some(filter.event_signatures.clone()),
+                            // Filters for all events of a contract have no signatures
+                            Some(filter.event_signatures.clone())
+                                .filter(|signatures| !signatures.is_empty()),
                             filter.topic1.clone(),
                             filter.topic2.clone(),
                             filter.topic3.clone(),
@@ -399,10 +823,12 @@ impl EthereumAdapter {
     ) -> DynTryFuture<'static, Vec<Log>, Error> {
         // Codes returned by Ethereum node providers if an eth_getLogs request is too heavy.
         const TOO_MANY_LOGS_FINGERPRINTS: &[&str] = &[
-            "ServerError(-32005)",       // Infura
-            "503 Service Unavailable",   // Alchemy
-            "ServerError(-32000)",       // Alchemy
-            "Try with this block range", // zKSync era
+            "ServerError(-32005)",        // Infura
+            "503 Service Unavailable",    // Alchemy
+            "ServerError(-32000)",        // Alchemy
+            "Try with this block range",  // zKSync era
+            "query returned more than",   // Geth
+            "Log response size exceeded", // Alchemy
         ];
 
         if from > to {
@@ -416,15 +842,17 @@ impl EthereumAdapter {
         let eth = self.cheap_clone();
         let filter = Arc::new(filter);
 
-        let step = match filter.contracts.is_empty() {
+        let max_step = match filter.contracts.is_empty() {
             // `to - from + 1`  blocks will be scanned.
             false => to - from,
             true => (to - from).min(ENV_VARS.max_event_only_range - 1),
         };
+        let step = self.log_range.step(max_step);
 
         // Typically this will loop only once and fetch the entire range in one request. But if the
-        // node returns an error that signifies the request is to heavy to process, the range will
-        // be broken down to smaller steps.
+        // node returns an error that signifies the request is to heavy to process, or takes too
+        // long, the range will be broken down to smaller steps. The provider's `LogRange`
+        // remembers that for later requests, and grows ranges again while they are quick.
         futures03::stream::try_unfold((from, step), move |(start, step)| {
             let logger = logger.cheap_clone();
             let filter = filter.cheap_clone();
@@ -441,16 +869,24 @@ impl EthereumAdapter {
                     logger,
                     "Requesting logs for blocks [{}, {}], {}", start, end, filter
                 );
-                let res = eth
-                    .logs_with_sigs(
-                        logger.cheap_clone(),
-                        subgraph_metrics.cheap_clone(),
-                        start,
-                        end,
-                        filter.cheap_clone(),
-                        TOO_MANY_LOGS_FINGERPRINTS,
-                    )
-                    .await;
+                let started = Instant::now();
+                let logs = eth.logs_with_sigs(
+                    logger.cheap_clone(),
+                    subgraph_metrics.cheap_clone(),
+                    start,
+                    end,
+                    filter.cheap_clone(),
+                    TOO_MANY_LOGS_FINGERPRINTS,
+                );
+                // A range of more than one block that the provider can not return in time is
+                // split up instead of retrying it over and over
+                let res = if step > 0 {
+                    timeout(ENV_VARS.json_rpc_timeout, logs)
+                        .await
+                        .unwrap_or(Err(TimeoutError::Elapsed))
+                } else {
+                    logs.await
+                };
 
                 match res {
                     Err(e) => {
@@ -458,14 +894,15 @@ impl EthereumAdapter {
 
                         // If the step is already 0, the request is too heavy even for a single
                         // block. We hope this never happens, but if it does, make sure to error.
-                        if TOO_MANY_LOGS_FINGERPRINTS
-                            .iter()
-                            .any(|f| string_err.contains(f))
+                        if (e.is_elapsed()
+                            || TOO_MANY_LOGS_FINGERPRINTS
+                                .iter()
+                                .any(|f| string_err.contains(f)))
                             && step > 0
                         {
                             // The range size for a request is `step + 1`. So it's ok if the step
                             // goes down to 0, in that case we'll request one block at a time.
-                            let new_step = step / 10;
+                            let new_step = eth.log_range.too_heavy(step);
                             debug!(logger, "Reducing block range size to scan for events";
                                                "new_size" => new_step + 1);
                             Ok(Some((vec![], (start, new_step))))
@@ -474,7 +911,10 @@ impl EthereumAdapter {
                             Err(anyhow!("{}", string_err))
                         }
                     }
-                    Ok(logs) => Ok(Some((logs, (end + 1, step)))),
+                    Ok(logs) => {
+                        eth.log_range.succeeded(step, started.elapsed());
+                        Ok(Some((logs, (end + 1, eth.log_range.step(max_step)))))
+                    }
                 }
             }
         })
@@ -749,6 +1189,142 @@ impl EthereumAdapter {
 
         Ok(req.response(result, call::Source::Rpc))
     }
+
+    /// Make the calls for `reqs`, which must all be for the same block,
+    /// through the Multicall3 contract at `multicall` in batches of
+    /// `GRAPH_ETHEREUM_MULTICALL_BATCH_SIZE` calls. Returns the responses
+    /// for the calls that succeeded, and the requests that still need to be
+    /// made one by one. Those are the calls that failed, since inside of a
+    /// batch a call might only run out of gas because of the other calls,
+    /// and all calls of a batch for which the multicall failed, for example
+    /// because the contract was not deployed yet at that block.
+    ///
+    /// Inside of a batch, calls see the Multicall3 contract as
+    /// `msg.sender` and might get less gas than on their own, so their
+    /// results are not put into the call cache, which is shared with
+    /// deployments on nodes that do not batch calls
+    async fn multicall_calls(
+        &self,
+        logger: &Logger,
+        multicall: Address,
+        calls: &[&ContractCall],
+        reqs: Vec<call::Request>,
+    ) -> (Vec<call::Response>, Vec<call::Request>) {
+        let calls_of = |batch: &[call::Request]| {
+            batch
+                .iter()
+                .map(|req| calls[req.index as usize])
+                .collect::<Vec<_>>()
+        };
+
+        let batches = reqs
+            .chunks(ENV_VARS.multicall_batch_size)
+            .map(|batch| async move {
+                let results = self
+                    .multicall(logger, multicall, batch, &calls_of(batch))
+                    .await;
+                (batch, results)
+            });
+
+        let mut resps = Vec::new();
+        let mut missing = Vec::new();
+        for (batch, results) in join_all(batches).await {
+            let results = match results {
+                Some(results) => results,
+                None => {
+                    missing.extend(batch.iter().cloned());
+                    continue;
+                }
+            };
+            for (req, result) in batch.iter().zip(results) {
+                match result {
+                    Some(result) => {
+                        resps.push(req.cheap_clone().response(result, call::Source::Rpc));
+                    }
+                    None => missing.push(req.cheap_clone()),
+                }
+            }
+        }
+
+        debug!(logger, "Batched calls through multicall";
+            "calls" => reqs.len(),
+            "batched" => resps.len(),
+            "remaining" => missing.len());
+
+        (resps, missing)
+    }
+
+    /// Call `aggregate3` of the Multicall3 contract at `multicall` with
+    /// `batch`, allowing each call to fail. Returns `None` if the multicall
+    /// failed or returned something unexpected, and otherwise the result of
+    /// each call, which is `None` if that call reverted
+    async fn multicall(
+        &self,
+        logger: &Logger,
+        multicall: Address,
+        batch: &[call::Request],
+        calls: &[&ContractCall],
+    ) -> Option<Vec<Option<call::Retval>>> {
+        let block_ptr = calls[0].block_ptr.clone();
+        // The multicall forwards the gas that is left to each call, so that
+        // a call could use gas that the calls before it did not need. Give
+        // the multicall only the smallest gas limit of the calls so that no
+        // call gets more gas than on its own; calls that run out of gas
+        // because of that are made one by one
+        let gas = calls.iter().filter_map(|call| call.gas).min();
+
+        let tokens = batch
+            .iter()
+            .map(|req| {
+                Token::Tuple(vec![
+                    Token::Address(req.address),
+                    Token::Bool(true),
+                    Token::Bytes(req.encoded_call.to_vec()),
+                ])
+            })
+            .collect();
+        let mut encoded_call =
+            tiny_keccak::keccak256(MULTICALL_AGGREGATE3.as_bytes())[..4].to_vec();
+        encoded_call.extend(ethabi::encode(&[Token::Array(tokens)]));
+        let req = call::Request::new(multicall, encoded_call, 0);
+
+        let output = match self.call(logger.clone(), req, block_ptr, gas).await {
+            Ok(call::Retval::Value(output)) => output,
+            Ok(call::Retval::Null) => {
+                debug!(logger, "Multicall reverted, making the calls one by one";
+                    "multicall" => format!("{:?}", multicall));
+                return None;
+            }
+            Err(e) => {
+                debug!(logger, "Multicall failed, making the calls one by one";
+                    "multicall" => format!("{:?}", multicall),
+                    "error" => e.to_string());
+                return None;
+            }
+        };
+
+        let return_type = ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])));
+        let results = ethabi::decode(&[return_type], &output)
+            .ok()?
+            .pop()?
+            .into_array()?
+            .into_iter()
+            .map(|result| match result.into_tuple()?.as_slice() {
+                [Token::Bool(success), Token::Bytes(data)] => {
+                    Some(success.then(|| call::Retval::Value(scalar::Bytes::from(data.clone()))))
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        // A contract at `multicall` that is not Multicall3 might return
+        // something that happens to decode
+        (results.len() == batch.len()).then_some(results)
+    }
+
     /// Request blocks by hash through JSON-RPC.
     fn load_blocks_rpc(
         &self,
@@ -763,7 +1339,7 @@ impl EthereumAdapter {
                 .limit(ENV_VARS.request_retries)
                 .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
                 .run(move || {
-                    Box::pin(web3.eth().block_with_txs(BlockId::Hash(hash)))
+                    Box::pin(block_with_txs(web3.cheap_clone(), BlockId::Hash(hash)))
                         .compat()
                         .from_err::<Error>()
                         .and_then(move |block| {
@@ -904,6 +1480,27 @@ impl EthereumAdapter {
             return Box::new(stream::empty());
         }
 
+        if self.debug_traces {
+            let logger = logger.clone();
+            return Box::new(
+                stream::iter_ok::<_, Error>(from..=to)
+                    .map(move |number| {
+                        eth.clone()
+                            .debug_traces(
+                                logger.cheap_clone(),
+                                subgraph_metrics.clone(),
+                                BlockId::Number(number.into()),
+                            )
+                            .boxed()
+                            .compat()
+                    })
+                    .buffered(ENV_VARS.block_batch_size)
+                    .map(stream::iter_ok)
+                    .flatten()
+                    .filter(move |call| call_filter.matches(call)),
+            );
+        }
+
         // if wildcard_signatures is on, we can't filter by topic so we need to get all the traces.
         if addresses.len() > 100 || !wildcard_signatures.is_empty() {
             // If the address list is large, request all traces, this avoids generating huge
@@ -1001,6 +1598,13 @@ impl EthereumAdapter {
         block_number: BlockNumber,
         block_hash: H256,
     ) -> Result<Vec<EthereumCall>, Error> {
+        if self.debug_traces {
+            return self
+                .clone()
+                .debug_traces(logger.clone(), subgraph_metrics, BlockId::Hash(block_hash))
+                .await;
+        }
+
         let eth = self.clone();
         let addresses = Vec::new();
         let traces = eth
@@ -1230,9 +1834,7 @@ impl EthereumAdapterTrait for EthereumAdapter {
                 .run(move || {
                     let web3 = web3.cheap_clone();
                     async move {
-                        let block_opt = web3
-                            .eth()
-                            .block_with_txs(Web3BlockNumber::Latest.into())
+                        let block_opt = block_with_txs(web3, Web3BlockNumber::Latest.into())
                             .await
                             .map_err(|e| {
                                 anyhow!("could not get latest block from Ethereum: {}", e)
@@ -1285,10 +1887,13 @@ impl EthereumAdapterTrait for EthereumAdapter {
                 .limit(ENV_VARS.request_retries)
                 .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
                 .run(move || {
-                    Box::pin(web3.eth().block_with_txs(BlockId::Hash(block_hash)))
-                        .compat()
-                        .from_err()
-                        .compat()
+                    Box::pin(block_with_txs(
+                        web3.cheap_clone(),
+                        BlockId::Hash(block_hash),
+                    ))
+                    .compat()
+                    .from_err()
+                    .compat()
                 })
                 .map_err(move |e| {
                     e.into_inner().unwrap_or_else(move || {
@@ -1318,8 +1923,7 @@ impl EthereumAdapterTrait for EthereumAdapter {
                 .run(move || {
                     let web3 = web3.cheap_clone();
                     async move {
-                        web3.eth()
-                            .block_with_txs(BlockId::Number(block_number.into()))
+                        block_with_txs(web3, BlockId::Number(block_number.into()))
                             .await
                             .map_err(Error::from)
                     }
@@ -1354,6 +1958,7 @@ impl EthereumAdapterTrait for EthereumAdapter {
             return Box::pin(std::future::ready(Ok(EthereumBlock {
                 block: Arc::new(block),
                 transaction_receipts: Vec::new(),
+                slim: false,
             })));
         }
         let hashes: Vec<_> = block.transactions.iter().map(|txn| txn.hash).collect();
@@ -1377,6 +1982,7 @@ impl EthereumAdapterTrait for EthereumAdapter {
                 EthereumBlock {
                     block: Arc::new(block),
                     transaction_receipts,
+                    slim: false,
                 }
             });
 
@@ -1616,6 +2222,17 @@ impl EthereumAdapterTrait for EthereumAdapter {
             .map_err(|e| error!(logger, "call cache get error"; "error" => e.to_string()))
             .unwrap_or_else(|_| (Vec::new(), reqs));
 
+        let missing = match self.multicall {
+            Some(multicall) if missing.len() > 1 => {
+                let (batched, missing) = self
+                    .multicall_calls(logger, multicall, calls, missing)
+                    .await;
+                resps.extend(batched);
+                missing
+            }
+            _ => missing,
+        };
+
         let futs = missing.into_iter().map(|req| {
             let cache = cache.clone();
             async move {
@@ -1907,7 +2524,7 @@ pub(crate) async fn get_calls(
             } else {
                 client
                     .rpc()?
-                    .cheapest_with(capabilities)
+                    .cheapest_with(capabilities, RequestClass::Traces)
                     .await?
                     .calls_in_block(
                         &logger,
@@ -1926,6 +2543,117 @@ pub(crate) async fn get_calls(
     }
 }
 
+/// Like `web3.eth().block_with_txs`, but keeps the fields that blocks have
+/// since the merge and the fields that L2s add to transactions, which the
+/// types from `web3` drop
+async fn block_with_txs(
+    web3: Arc<Web3<Transport>>,
+    block: BlockId,
+) -> Result<Option<LightEthereumBlock>, web3::Error> {
+    let (method, id) = match block {
+        BlockId::Hash(hash) => ("eth_getBlockByHash", json::json!(hash)),
+        BlockId::Number(number) => ("eth_getBlockByNumber", json::json!(number)),
+    };
+    let block = web3
+        .transport()
+        .execute(method, vec![id, json::json!(true)])
+        .await?;
+    LightEthereumBlock::from_rpc(block).map_err(|e| web3::Error::Decoder(e.to_string()))
+}
+
+/// Like `web3.eth().transaction_receipt`, but keeps the fields that L2s add
+/// to receipts. The request is made right away so that this also works for
+/// batches
+fn transaction_receipt<T: web3::Transport>(
+    web3: &Web3<T>,
+    transaction_hash: H256,
+) -> impl std::future::Future<Output = Result<Option<EthereumTransactionReceipt>, web3::Error>> {
+    let receipt = web3.transport().execute(
+        "eth_getTransactionReceipt",
+        vec![json::json!(transaction_hash)],
+    );
+    async move { json::from_value(receipt.await?).map_err(|e| web3::Error::Decoder(e.to_string())) }
+}
+
+/// Receipts for the transactions of `block` that emitted any of `logs`,
+/// which are all logs of the block. Only successful transactions emit logs,
+/// so the status of these receipts is right; the gas, the bloom filter and
+/// the contract address are left empty
+fn slim_receipts(
+    block: &LightEthereumBlock,
+    logs: Vec<Log>,
+) -> Vec<Arc<EthereumTransactionReceipt>> {
+    let mut logs_by_transaction: HashMap<H256, Vec<Log>> = HashMap::new();
+    for log in logs {
+        if let Some(transaction_hash) = log.transaction_hash {
+            logs_by_transaction
+                .entry(transaction_hash)
+                .or_default()
+                .push(log);
+        }
+    }
+
+    block
+        .transactions
+        .iter()
+        .filter_map(|transaction| {
+            let logs = logs_by_transaction.remove(&transaction.hash)?;
+            let receipt = TransactionReceipt {
+                transaction_hash: transaction.hash,
+                transaction_index: transaction.transaction_index.unwrap_or_default(),
+                block_hash: block.hash,
+                block_number: block.number,
+                cumulative_gas_used: U256::zero(),
+                gas_used: None,
+                contract_address: None,
+                logs,
+                status: Some(U64::from(1)),
+                root: None,
+                logs_bloom: Default::default(),
+                from: transaction.from.unwrap_or_default(),
+                to: transaction.to,
+                transaction_type: transaction.transaction_type,
+                effective_gas_price: None,
+            };
+            Some(Arc::new(receipt.into()))
+        })
+        .collect()
+}
+
+/// Fetch the receipts of a slim block if `requires_receipts`, and store the
+/// full block so that other deployments that need receipts find them
+pub(crate) async fn get_receipts(
+    client: &Arc<ChainClient<Chain>>,
+    logger: &Logger,
+    chain_store: Arc<dyn ChainStore>,
+    capabilities: &NodeCapabilities,
+    requires_receipts: bool,
+    block: BlockFinality,
+) -> Result<BlockFinality, Error> {
+    match block {
+        BlockFinality::NonFinal(EthereumBlockWithCalls {
+            ethereum_block,
+            calls,
+        }) if requires_receipts && ethereum_block.slim => {
+            debug!(logger, "Fetching receipts of slim block";
+                   "block_hash" => format!("{:?}", ethereum_block.block.hash));
+            let ethereum_block = client
+                .rpc()?
+                .cheapest_with(capabilities, RequestClass::Blocks)
+                .await?
+                .load_full_block(logger, ethereum_block.block.as_ref().clone())
+                .await?;
+            let block = BlockFinality::NonFinal(EthereumBlockWithCalls {
+                ethereum_block,
+                calls,
+            });
+            chain_store.upsert_block(Arc::new(block.clone())).await?;
+            Ok(block)
+        }
+        block => Ok(block),
+    }
+}
+
 pub(crate) fn parse_log_triggers(
     log_filter: &EthereumLogFilter,
     block: &EthereumBlock,
@@ -2202,7 +2930,7 @@ async fn fetch_transaction_receipts_in_batch_with_retry(
     hashes: Vec<H256>,
     block_hash: H256,
     logger: Logger,
-) -> Result<Vec<Arc<TransactionReceipt>>, IngestorError> {
+) -> Result<Vec<Arc<EthereumTransactionReceipt>>, IngestorError> {
     let retry_log_message = format!(
         "batch eth_getTransactionReceipt RPC call for block {:?}",
         block_hash
@@ -2227,14 +2955,13 @@ async fn fetch_transaction_receipts_in_batch(
     hashes: Vec<H256>,
     block_hash: H256,
     logger: Logger,
-) -> Result<Vec<Arc<TransactionReceipt>>, IngestorError> {
+) -> Result<Vec<Arc<EthereumTransactionReceipt>>, IngestorError> {
     let batching_web3 = Web3::new(Batch::new(web3.transport().clone()));
-    let eth = batching_web3.eth();
     let receipt_futures = hashes
         .into_iter()
         .map(move |hash| {
             let logger = logger.cheap_clone();
-            eth.transaction_receipt(hash)
+            transaction_receipt(&batching_web3, hash)
                 .map_err(IngestorError::from)
                 .and_then(move |some_receipt| async move {
                     resolve_transaction_receipt(some_receipt, hash, block_hash, logger)
@@ -2285,7 +3012,7 @@ async fn fetch_receipts_with_retry(
     block_hash: H256,
     logger: Logger,
     supports_block_receipts: bool,
-) -> Result<Vec<Arc<TransactionReceipt>>, IngestorError> {
+) -> Result<Vec<Arc<EthereumTransactionReceipt>>, IngestorError> {
     if supports_block_receipts {
         return fetch_block_receipts_with_retry(web3, hashes, block_hash, logger).await;
     }
@@ -2298,7 +3025,7 @@ async fn fetch_individual_receipts_with_retry(
     hashes: Vec<H256>,
     block_hash: H256,
     logger: Logger,
-) -> Result<Vec<Arc<TransactionReceipt>>, IngestorError> {
+) -> Result<Vec<Arc<EthereumTransactionReceipt>>, IngestorError> {
     if ENV_VARS.fetch_receipts_in_batches {
         return fetch_transaction_receipts_in_batch_with_retry(web3, hashes, block_hash, logger)
             .await;
@@ -2317,9 +3044,9 @@ async fn fetch_individual_receipts_with_retry(
         })
         .buffered(ENV_VARS.block_ingestor_max_concurrent_json_rpc_calls);
 
-    graph::tokio_stream::StreamExt::collect::<Result<Vec<Arc<TransactionReceipt>>, IngestorError>>(
-        receipt_stream,
-    )
+    graph::tokio_stream::StreamExt::collect::<
+        Result<Vec<Arc<EthereumTransactionReceipt>>, IngestorError>,
+    >(receipt_stream)
     .await
 }
 
@@ -2329,7 +3056,7 @@ async fn fetch_block_receipts_with_retry(
     hashes: Vec<H256>,
     block_hash: H256,
     logger: Logger,
-) -> Result<Vec<Arc<TransactionReceipt>>, IngestorError> {
+) -> Result<Vec<Arc<EthereumTransactionReceipt>>, IngestorError> {
     let logger = logger.cheap_clone();
     let retry_log_message = format!("eth_getBlockReceipts RPC call for block {:?}", block_hash);
 
@@ -2337,7 +3064,21 @@ async fn fetch_block_receipts_with_retry(
     let receipts_option = retry(retry_log_message, &logger)
         .limit(ENV_VARS.request_retries)
         .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
-        .run(move || web3.eth().block_receipts(BlockId::Hash(block_hash)).boxed())
+        .run(move || {
+            let web3 = web3.cheap_clone();
+            async move {
+                let receipts = web3
+                    .transport()
+                    .execute(
+                        "eth_getBlockReceipts",
+                        vec![json::json!(BlockId::Hash(block_hash))],
+                    )
+                    .await?;
+                json::from_value::<Option<Vec<EthereumTransactionReceipt>>>(receipts)
+                    .map_err(|e| web3::Error::Decoder(e.to_string()))
+            }
+            .boxed()
+        })
         .await
         .map_err(|_timeout| -> IngestorError { anyhow!(block_hash).into() })?;
 
@@ -2372,7 +3113,7 @@ async fn fetch_transaction_receipt_with_retry(
     transaction_hash: H256,
     block_hash: H256,
     logger: Logger,
-) -> Result<Arc<TransactionReceipt>, IngestorError> {
+) -> Result<Arc<EthereumTransactionReceipt>, IngestorError> {
     let logger = logger.cheap_clone();
     let retry_log_message = format!(
         "eth_getTransactionReceipt RPC call for transaction {:?}",
@@ -2381,7 +3122,7 @@ async fn fetch_transaction_receipt_with_retry(
     retry(retry_log_message, &logger)
         .limit(ENV_VARS.request_retries)
         .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
-        .run(move || web3.eth().transaction_receipt(transaction_hash).boxed())
+        .run(move || transaction_receipt(&web3, transaction_hash).boxed())
         .await
         .map_err(|_timeout| anyhow!(block_hash).into())
         .and_then(move |some_receipt| {
@@ -2391,11 +3132,11 @@ async fn fetch_transaction_receipt_with_retry(
 }
 
 fn resolve_transaction_receipt(
-    transaction_receipt: Option<TransactionReceipt>,
+    transaction_receipt: Option<EthereumTransactionReceipt>,
     transaction_hash: H256,
     block_hash: H256,
     logger: Logger,
-) -> Result<TransactionReceipt, IngestorError> {
+) -> Result<EthereumTransactionReceipt, IngestorError> {
     match transaction_receipt {
         // A receipt might be missing because the block was uncled, and the transaction never
         // made it back into the main chain.
@@ -2475,7 +3216,7 @@ async fn get_logs_and_transactions(
             if let Some(signature) = log.topics.first() {
                 log_filter.requires_transaction_receipt(signature, Some(&log.address), &log.topics)
             } else {
-                false
+                log_filter.all_events_require_transaction_receipt(&log.address)
             }
         })
         .filter_map(|log| {
@@ -2523,10 +3264,10 @@ async fn get_transaction_receipts_for_transaction_hashes(
     transaction_hashes_by_block: &HashMap<H256, HashSet<H256>>,
     subgraph_metrics: Arc<SubgraphEthRpcMetrics>,
     logger: Logger,
-) -> Result<HashMap<H256, Arc<TransactionReceipt>>, anyhow::Error> {
+) -> Result<HashMap<H256, Arc<EthereumTransactionReceipt>>, anyhow::Error> {
     use std::collections::hash_map::Entry::Vacant;
 
-    let mut receipts_by_hash: HashMap<H256, Arc<TransactionReceipt>> = HashMap::new();
+    let mut receipts_by_hash: HashMap<H256, Arc<EthereumTransactionReceipt>> = HashMap::new();
 
     // Return early if input set is empty
     if transaction_hashes_by_block.is_empty() {
@@ -2603,14 +3344,14 @@ mod tests {
     use crate::trigger::{EthereumBlockTriggerType, EthereumTrigger};
 
     use super::{
-        check_block_receipt_support, parse_block_triggers, EthereumBlock, EthereumBlockFilter,
-        EthereumBlockWithCalls,
+        check_block_receipt_support, parse_block_triggers, slim_receipts, EthereumBlock,
+        EthereumBlockFilter, EthereumBlockWithCalls, LightEthereumBlock, LogRange,
     };
     use graph::blockchain::BlockPtr;
     use graph::prelude::ethabi::ethereum_types::U64;
     use graph::prelude::tokio::{self};
     use graph::prelude::web3::transports::test::TestTransport;
-    use graph::prelude::web3::types::{Address, Block, Bytes, H256};
+    use graph::prelude::web3::types::{Address, Block, Bytes, Log, Transaction, H256};
     use graph::prelude::web3::Web3;
     use graph::prelude::EthereumCall;
     use jsonrpc_core::serde_json::{self, Value};
@@ -2622,11 +3363,14 @@ mod tests {
     fn parse_block_triggers_every_block() {
         let block = EthereumBlockWithCalls {
             ethereum_block: EthereumBlock {
-                block: Arc::new(Block {
-                    hash: Some(hash(2)),
-                    number: Some(U64::from(2)),
-                    ..Default::default()
-                }),
+                block: Arc::new(
+                    Block {
+                        hash: Some(hash(2)),
+                        number: Some(U64::from(2)),
+                        ..Default::default()
+                    }
+                    .into(),
+                ),
                 ..Default::default()
             },
             calls: Some(vec![EthereumCall {
@@ -2788,11 +3532,14 @@ mod tests {
     fn parse_block_triggers_specific_call_not_found() {
         let block = EthereumBlockWithCalls {
             ethereum_block: EthereumBlock {
-                block: Arc::new(Block {
-                    hash: Some(hash(2)),
-                    number: Some(U64::from(2)),
-                    ..Default::default()
-                }),
+                block: Arc::new(
+                    Block {
+                        hash: Some(hash(2)),
+                        number: Some(U64::from(2)),
+                        ..Default::default()
+                    }
+                    .into(),
+                ),
                 ..Default::default()
             },
             calls: Some(vec![EthereumCall {
@@ -2820,11 +3567,14 @@ mod tests {
     fn parse_block_triggers_specific_call_found() {
         let block = EthereumBlockWithCalls {
             ethereum_block: EthereumBlock {
-                block: Arc::new(Block {
-                    hash: Some(hash(2)),
-                    number: Some(U64::from(2)),
-                    ..Default::default()
-                }),
+                block: Arc::new(
+                    Block {
+                        hash: Some(hash(2)),
+                        number: Some(U64::from(2)),
+                        ..Default::default()
+                    }
+                    .into(),
+                ),
                 ..Default::default()
             },
             calls: Some(vec![EthereumCall {
@@ -2851,6 +3601,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn log_range_adapts_to_provider() {
+        use std::time::Duration;
+
+        let fast = Duration::from_millis(10);
+        let slow = Duration::from_secs(60);
+        let range = LogRange::default();
+
+        // Ranges are not limited until the provider has trouble
+        assert_eq!(1000, range.step(1000));
+        range.succeeded(1000, fast);
+        assert_eq!(1000, range.step(1000));
+
+        assert_eq!(100, range.too_heavy(1000));
+        assert_eq!(100, range.step(1000));
+        assert_eq!(50, range.step(50));
+
+        // Slow requests and short ranges at the end of a scan do not grow
+        // the range, quick requests for full ranges do
+        range.succeeded(100, slow);
+        range.succeeded(30, fast);
+        assert_eq!(100, range.step(1000));
+        range.succeeded(100, fast);
+        assert_eq!(200, range.step(1000));
+
+        // A failure of a larger range in a scan that started before the
+        // range was learned does not grow it
+        assert_eq!(500, range.too_heavy(5000));
+        assert_eq!(200, range.step(1000));
+        assert_eq!(0, range.too_heavy(5));
+        range.succeeded(0, fast);
+        assert_eq!(1, range.step(1000));
+    }
+
+    #[test]
+    fn slim_receipts_from_logs() {
+        let transaction = |id: u8| Transaction {
+            hash: hash(id),
+            from: Some(address(id as u64)),
+            ..Default::default()
+        };
+        let log = |transaction: Option<u8>| Log {
+            address: Address::zero(),
+            topics: vec![],
+            data: Bytes::default(),
+            block_hash: Some(hash(9)),
+            block_number: Some(U64::from(9)),
+            transaction_hash: transaction.map(hash),
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+            log_type: None,
+            removed: Some(false),
+        };
+        let block: LightEthereumBlock = Block {
+            hash: Some(hash(9)),
+            number: Some(U64::from(9)),
+            transactions: vec![transaction(1), transaction(2), transaction(3)],
+            ..Default::default()
+        }
+        .into();
+
+        let receipts = slim_receipts(
+            &block,
+            vec![log(Some(3)), log(Some(1)), log(None), log(Some(3))],
+        );
+        let summary: Vec<_> = receipts
+            .iter()
+            .map(|receipt| {
+                (
+                    receipt.transaction_hash,
+                    receipt.logs.len(),
+                    receipt.from,
+                    receipt.status,
+                    receipt.block_hash,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (hash(1), 1, address(1), Some(U64::from(1)), Some(hash(9))),
+                (hash(3), 2, address(3), Some(U64::from(1)), Some(hash(9))),
+            ],
+            summary
+        );
+    }
+
     fn address(id: u64) -> Address {
         Address::from_low_u64_be(id)
     }
@@ -4,6 +4,9 @@ use graph::blockchain::client::ChainClient;
 use graph::blockchain::BlockchainKind;
 use graph::components::adapter::ChainId;
 use graph::futures03::compat::Future01CompatExt as _;
+use graph::futures03::stream::BoxStream;
+use graph::futures03::{FutureExt as _, StreamExt as _};
+use graph::prelude::web3::{self, types::BlockHeader};
 use graph::slog::o;
 use graph::util::backoff::ExponentialBackoff;
 use graph::{
@@ -14,7 +17,46 @@ use graph::{
         Error, EthereumBlockWithCalls, LogCode, Logger,
     },
 };
-use std::{sync::Arc, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Which block the ingestor treats as the head of the chain. Subgraphs
+/// never index past the chain head, so with `Safe` or `Finalized` they
+/// trail the chain but will, barring an attack on consensus, never see a
+/// reorg
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeadBlock {
+    #[default]
+    Latest,
+    Safe,
+    Finalized,
+}
+
+impl HeadBlock {
+    /// The block tag for `eth_getBlockByNumber`
+    pub fn tag(&self) -> &'static str {
+        match self {
+            HeadBlock::Latest => "latest",
+            HeadBlock::Safe => "safe",
+            HeadBlock::Finalized => "finalized",
+        }
+    }
+}
+
+type NewHeads = BoxStream<'static, Result<BlockHeader, web3::Error>>;
+
+/// The `newHeads` subscription of the ingestor
+#[derive(Default)]
+struct Subscription {
+    heads: Option<NewHeads>,
+    /// The earliest time to subscribe again, so that we do not keep asking
+    /// providers whose subscriptions fail right away
+    retry_at: Option<Instant>,
+}
 
 pub struct PollingBlockIngestor {
     logger: Logger,
@@ -22,6 +64,7 @@ pub struct PollingBlockIngestor {
     chain_client: Arc<ChainClient<crate::chain::Chain>>,
     chain_store: Arc<dyn ChainStore>,
     polling_interval: Duration,
+    head: HeadBlock,
     network_name: ChainId,
 }
 
@@ -32,6 +75,7 @@ impl PollingBlockIngestor {
         chain_client: Arc<ChainClient<crate::chain::Chain>>,
         chain_store: Arc<dyn ChainStore>,
         polling_interval: Duration,
+        head: HeadBlock,
         network_name: ChainId,
     ) -> Result<PollingBlockIngestor, Error> {
         Ok(PollingBlockIngestor {
@@ -40,6 +84,7 @@ impl PollingBlockIngestor {
             chain_client,
             chain_store,
             polling_interval,
+            head,
             network_name,
         })
     }
@@ -65,10 +110,87 @@ impl PollingBlockIngestor {
         }
     }
 
+    /// Subscribe to new heads with the first adapter that supports it
+    async fn subscribe(&self) -> Option<NewHeads> {
+        let adapters = self.chain_client.rpc().ok()?.subscribable().await;
+        for adapter in adapters {
+            match adapter.subscribe_new_heads().await {
+                Ok(heads) => {
+                    info!(self.logger, "Subscribed to new heads";
+                        "provider" => adapter.provider());
+                    return Some(heads);
+                }
+                Err(e) => warn!(self.logger, "Failed to subscribe to new heads";
+                    "provider" => adapter.provider(),
+                    "error" => e.to_string()),
+            }
+        }
+        None
+    }
+
+    /// Wait until it is time to poll again: until the subscription tells us
+    /// about a new head, or for the polling interval without one. Returns
+    /// the newest head that the subscription sent
+    async fn wait_for_head(&self, subscription: &mut Subscription) -> Option<BlockPtr> {
+        if subscription.heads.is_none()
+            && subscription
+                .retry_at
+                .map_or(true, |retry_at| retry_at <= Instant::now())
+        {
+            subscription.heads = self.subscribe().await;
+            subscription.retry_at = Some(Instant::now() + ENV_VARS.new_heads_timeout);
+        }
+
+        let heads = match subscription.heads.as_mut() {
+            Some(heads) => heads,
+            None => {
+                tokio::time::sleep(self.polling_interval).await;
+                return None;
+            }
+        };
+
+        let header = match tokio::time::timeout(ENV_VARS.new_heads_timeout, heads.next()).await {
+            Ok(Some(Ok(mut header))) => {
+                // Skip heads that arrived while we were ingesting; ingesting
+                // the newest one also ingests its ancestors
+                while let Some(Some(Ok(newer))) = heads.next().now_or_never() {
+                    header = newer;
+                }
+                header
+            }
+            Ok(Some(Err(e))) => {
+                warn!(self.logger, "New heads subscription failed, polling instead";
+                    "error" => e.to_string());
+                subscription.heads = None;
+                return None;
+            }
+            Ok(None) => {
+                warn!(self.logger, "New heads subscription ended, polling instead");
+                subscription.heads = None;
+                return None;
+            }
+            Err(_) => {
+                warn!(
+                    self.logger,
+                    "No new head for {}s, polling instead",
+                    ENV_VARS.new_heads_timeout.as_secs()
+                );
+                subscription.heads = None;
+                return None;
+            }
+        };
+
+        match (header.hash, header.number) {
+            (Some(hash), Some(number)) => Some(BlockPtr::from((hash, number.as_u64()))),
+            _ => None,
+        }
+    }
+
     async fn do_poll(
         &self,
         logger: &Logger,
         eth_adapter: Arc<EthereumAdapter>,
+        new_head: Option<BlockPtr>,
     ) -> Result<(), IngestorError> {
         trace!(&logger, "BlockIngestor::do_poll");
 
@@ -77,8 +199,12 @@ impl PollingBlockIngestor {
 
         // To check if there is a new block or not, fetch only the block header since that's cheaper
         // than the full block. This is worthwhile because most of the time there won't be a new
-        // block, as we expect the poll interval to be much shorter than the block time.
-        let latest_block = self.latest_block(logger, &eth_adapter).await?;
+        // block, as we expect the poll interval to be much shorter than the block time. A head
+        // from the subscription saves even that, unless we follow a safe or finalized head
+        let latest_block = match new_head {
+            Some(new_head) if self.head == HeadBlock::Latest => new_head,
+            _ => self.latest_block(logger, &eth_adapter).await?,
+        };
 
         if let Some(head_block) = head_block_ptr_opt.as_ref() {
             // If latest block matches head block in store, nothing needs to be done
@@ -178,7 +304,11 @@ impl PollingBlockIngestor {
             .compat()
             .await?
             .ok_or(IngestorError::BlockUnavailable(block_hash))?;
-        let ethereum_block = eth_adapter.load_full_block(&logger, block).await?;
+        let ethereum_block = if ENV_VARS.receipt_light_blocks {
+            eth_adapter.load_slim_block(logger, block).await?
+        } else {
+            eth_adapter.load_full_block(logger, block).await?
+        };
 
         // We need something that implements `Block` to store the block; the
         // store does not care whether the block is final or not
@@ -208,11 +338,17 @@ impl PollingBlockIngestor {
         logger: &Logger,
         eth_adapter: &Arc<EthereumAdapter>,
     ) -> Result<BlockPtr, IngestorError> {
-        eth_adapter
-            .latest_block_header(&logger)
-            .compat()
-            .await
-            .map(|block| block.into())
+        match self.head {
+            HeadBlock::Latest => eth_adapter
+                .latest_block_header(&logger)
+                .compat()
+                .await
+                .map(|block| block.into()),
+            head => eth_adapter
+                .tagged_block_header(&logger, head)
+                .await
+                .map(|block| block.into()),
+        }
     }
 
     async fn eth_adapter(&self) -> anyhow::Result<Arc<EthereumAdapter>> {
@@ -229,6 +365,8 @@ impl BlockIngestor for PollingBlockIngestor {
     async fn run(self: Box<Self>) {
         let mut backoff =
             ExponentialBackoff::new(Duration::from_millis(250), Duration::from_secs(30));
+        let mut subscription = Subscription::default();
+        let mut new_head = None;
 
         loop {
             let eth_adapter = match self.eth_adapter().await {
@@ -250,7 +388,7 @@ impl BlockIngestor for PollingBlockIngestor {
                 .logger
                 .new(o!("provider" => eth_adapter.provider().to_string()));
 
-            match self.do_poll(&logger, eth_adapter).await {
+            match self.do_poll(&logger, eth_adapter, new_head.take()).await {
                 // Some polls will fail due to transient issues
                 Err(err) => {
                     error!(logger, "Trying again after block polling failed: {}", err);
@@ -262,7 +400,7 @@ impl BlockIngestor for PollingBlockIngestor {
                 self.cleanup_cached_blocks()
             }
 
-            tokio::time::sleep(self.polling_interval).await;
+            new_head = self.wait_for_head(&mut subscription).await;
         }
     }
 
@@ -1,16 +1,23 @@
 mod adapter;
+pub mod blobs;
 mod buffered_call_cache;
 mod capabilities;
 pub mod codec;
 mod data_source;
+mod debug_trace;
+mod divergence;
 mod env;
 mod ethereum_adapter;
 mod ingestor;
+mod proofs;
 pub mod runtime;
 mod transport;
 
-pub use self::capabilities::NodeCapabilities;
+pub use self::blobs::{BlobClient, BlobSource};
+pub use self::capabilities::{ChainCapabilities, NodeCapabilities};
+pub use self::divergence::DivergenceMonitor;
 pub use self::ethereum_adapter::EthereumAdapter;
+pub use self::ingestor::HeadBlock;
 pub use self::runtime::RuntimeAdapter;
 pub use self::transport::Transport;
 pub use env::ENV_VARS;
@@ -1,9 +1,9 @@
 use anyhow::{anyhow, bail};
 use graph::blockchain::ChainIdentifier;
 use graph::components::adapter::{ChainId, NetIdentifiable, ProviderManager, ProviderName};
-use graph::endpoint::EndpointMetrics;
+use graph::endpoint::{EndpointMetrics, RequestClass};
 use graph::firehose::{AvailableCapacity, SubgraphLimit};
-use graph::prelude::rand::seq::IteratorRandom;
+use graph::prelude::rand::seq::SliceRandom;
 use graph::prelude::rand::{self, Rng};
 use std::sync::Arc;
 
@@ -26,6 +26,8 @@ pub struct EthereumNetworkAdapter {
     /// that limit. That's a somewhat imprecise but convenient way to
     /// determine the number of connections
     limit: SubgraphLimit,
+    /// The chain id that the provider is configured to report
+    expected_chain_id: Option<u64>,
 }
 
 #[async_trait]
@@ -36,6 +38,12 @@ impl NetIdentifiable for EthereumNetworkAdapter {
     fn provider_name(&self) -> ProviderName {
         self.adapter.provider().into()
     }
+    fn expected_chain_id(&self) -> Option<u64> {
+        self.expected_chain_id
+    }
+    async fn chain_id(&self) -> Result<u64, anyhow::Error> {
+        self.adapter.chain_id().await
+    }
 }
 
 impl EthereumNetworkAdapter {
@@ -50,6 +58,15 @@ impl EthereumNetworkAdapter {
             capabilities,
             adapter,
             limit,
+            expected_chain_id: None,
+        }
+    }
+
+    /// Fail validation of the provider if it does not report `chain_id`
+    pub fn with_expected_chain_id(self, chain_id: Option<u64>) -> Self {
+        Self {
+            expected_chain_id: chain_id,
+            ..self
         }
     }
 
@@ -65,6 +82,14 @@ impl EthereumNetworkAdapter {
     pub fn current_error_count(&self) -> u64 {
         self.endpoint_metrics.get_count(&self.provider().into())
     }
+
+    /// How well the provider serves requests of `class` at the moment;
+    /// lower is better
+    pub fn score(&self, class: RequestClass, archive: bool) -> f64 {
+        self.endpoint_metrics
+            .score(&self.provider().into(), class, archive)
+    }
+
     pub fn provider(&self) -> &str {
         self.adapter.provider()
     }
@@ -74,7 +99,7 @@ impl EthereumNetworkAdapter {
 pub struct EthereumNetworkAdapters {
     chain_id: ChainId,
     manager: ProviderManager<EthereumNetworkAdapter>,
-    call_only_adapters: Vec<EthereumNetworkAdapter>,
+    call_only_manager: ProviderManager<EthereumNetworkAdapter>,
     // Percentage of request that should be used to retest errored adapters.
     retest_percent: f64,
 }
@@ -84,7 +109,7 @@ impl EthereumNetworkAdapters {
         Self {
             chain_id: "".into(),
             manager: ProviderManager::default(),
-            call_only_adapters: vec![],
+            call_only_manager: ProviderManager::default(),
             retest_percent: DEFAULT_ADAPTER_ERROR_RETEST_PERCENT,
         }
     }
@@ -112,25 +137,35 @@ impl EthereumNetworkAdapters {
             Arc::new(NoopIdentValidator),
         );
         provider.mark_all_valid().await;
+        let call_only = ProviderManager::new(
+            Logger::root(Discard, o!()),
+            vec![(chain_id.clone(), call_only)].into_iter(),
+            Arc::new(NoopIdentValidator),
+        );
 
         Self::new(chain_id, provider, call_only, None)
     }
 
+    /// Call-only adapters are never validated, and `call_only_manager`
+    /// must only contain call-only adapters
     pub fn new(
         chain_id: ChainId,
         manager: ProviderManager<EthereumNetworkAdapter>,
-        call_only_adapters: Vec<EthereumNetworkAdapter>,
+        call_only_manager: ProviderManager<EthereumNetworkAdapter>,
         retest_percent: Option<f64>,
     ) -> Self {
         #[cfg(debug_assertions)]
-        call_only_adapters.iter().for_each(|a| {
-            a.is_call_only();
-        });
+        call_only_manager
+            .get_all_unverified(&chain_id)
+            .iter()
+            .for_each(|a| {
+                a.is_call_only();
+            });
 
         Self {
             chain_id,
             manager,
-            call_only_adapters,
+            call_only_manager,
             retest_percent: retest_percent.unwrap_or(DEFAULT_ADAPTER_ERROR_RETEST_PERCENT),
         }
     }
@@ -152,29 +187,20 @@ impl EthereumNetworkAdapters {
 
     /// returns all the available adapters that meet the required capabilities
     /// if no adapters are available at the time or none that meet the capabilities then
-    /// an empty iterator is returned.
+    /// an empty list is returned.
     pub async fn all_cheapest_with(
         &self,
         required_capabilities: &NodeCapabilities,
-    ) -> impl Iterator<Item = &EthereumNetworkAdapter> + '_ {
+    ) -> Vec<EthereumNetworkAdapter> {
         let all = self
             .manager
             .get_all(&self.chain_id)
             .await
             .unwrap_or_default();
 
-        Self::available_with_capabilities(all, required_capabilities)
-    }
-
-    // get all the adapters, don't trigger the ProviderManager's validations because we want
-    // this function to remain sync. If no adapters are available an empty iterator is returned.
-    pub(crate) fn all_unverified_cheapest_with(
-        &self,
-        required_capabilities: &NodeCapabilities,
-    ) -> impl Iterator<Item = &EthereumNetworkAdapter> + '_ {
-        let all = self.manager.get_all_unverified(&self.chain_id);
-
-        Self::available_with_capabilities(all, required_capabilities)
+        Self::available_with_capabilities(all.iter().collect(), required_capabilities)
+            .cloned()
+            .collect()
     }
 
     // handle adapter selection from a list, implements the availability checking with an abstracted
@@ -182,45 +208,56 @@ impl EthereumNetworkAdapters {
     fn cheapest_from(
         input: Vec<&EthereumNetworkAdapter>,
         required_capabilities: &NodeCapabilities,
+        class: RequestClass,
         retest_percent: f64,
     ) -> Result<Arc<EthereumAdapter>, Error> {
         let retest_rng: f64 = (&mut rand::thread_rng()).gen();
 
-        let cheapest = input
+        let mut scored: Vec<_> = input
             .into_iter()
-            .choose_multiple(&mut rand::thread_rng(), 3);
-        let cheapest = cheapest.iter();
+            .map(|adapter| (adapter.score(class, required_capabilities.archive), adapter))
+            .collect();
+        // Shuffle so that ties, for example between adapters that have not
+        // served any requests yet, are broken randomly
+        scored.shuffle(&mut rand::thread_rng());
+        let scored = scored.into_iter();
 
         // If request falls below the retest threshold, use this request to try and
         // reset the failed adapter. If a request succeeds the adapter will be more
         // likely to be selected afterwards.
         if retest_rng < retest_percent {
-            cheapest.max_by_key(|adapter| adapter.current_error_count())
+            scored.max_by(|(a, _), (b, _)| a.total_cmp(b))
         } else {
             // The assumption here is that most RPC endpoints will not have limits
             // which makes the check for low/high available capacity less relevant.
             // So we essentially assume if it had available capacity when calling
             // `all_cheapest_with` then it prolly maintains that state and so we
-            // just select whichever adapter is working better according to
-            // the number of errors.
-            cheapest.min_by_key(|adapter| adapter.current_error_count())
+            // just select whichever adapter currently serves this class of
+            // requests best, according to its latency, errors and rate limits
+            scored.min_by(|(a, _), (b, _)| a.total_cmp(b))
         }
-        .map(|adapter| adapter.adapter.clone())
+        .map(|(_, adapter)| adapter.adapter.clone())
         .ok_or(anyhow!(
             "A matching Ethereum network with {:?} was not found.",
             required_capabilities
         ))
     }
 
+    // get all the adapters, don't trigger the ProviderManager's validations because we want
+    // this function to remain sync.
     pub(crate) fn unverified_cheapest_with(
         &self,
         required_capabilities: &NodeCapabilities,
+        class: RequestClass,
     ) -> Result<Arc<EthereumAdapter>, Error> {
-        let cheapest = self.all_unverified_cheapest_with(required_capabilities);
+        let all = self.manager.get_all_unverified(&self.chain_id);
+        let cheapest =
+            Self::available_with_capabilities(all.iter().collect(), required_capabilities);
 
         Self::cheapest_from(
-            cheapest.choose_multiple(&mut rand::thread_rng(), 3),
+            cheapest.collect(),
             required_capabilities,
+            class,
             self.retest_percent,
         )
     }
@@ -229,24 +266,65 @@ impl EthereumNetworkAdapters {
     pub async fn cheapest_with(
         &self,
         required_capabilities: &NodeCapabilities,
+        class: RequestClass,
     ) -> Result<Arc<EthereumAdapter>, Error> {
-        let cheapest = self
-            .all_cheapest_with(required_capabilities)
+        let all = self
+            .manager
+            .get_all(&self.chain_id)
             .await
-            .choose_multiple(&mut rand::thread_rng(), 3);
+            .unwrap_or_default();
+        let cheapest =
+            Self::available_with_capabilities(all.iter().collect(), required_capabilities)
+                .collect();
 
-        Self::cheapest_from(cheapest, required_capabilities, self.retest_percent)
+        Self::cheapest_from(cheapest, required_capabilities, class, self.retest_percent)
     }
 
     pub async fn cheapest(&self) -> Option<Arc<EthereumAdapter>> {
         // EthereumAdapters are sorted by their NodeCapabilities when the EthereumNetworks
-        // struct is instantiated so they do not need to be sorted here
+        // struct is instantiated so they do not need to be sorted here. Of the adapters
+        // with the cheapest capabilities, use the one that serves blocks best, which is
+        // the first one as long as we know nothing about any of them
+        let all = self
+            .manager
+            .get_all(&self.chain_id)
+            .await
+            .unwrap_or_default();
+        let capabilities = all.iter().next()?.capabilities;
+        all.iter()
+            .filter(|adapter| adapter.capabilities == capabilities)
+            .map(|adapter| (adapter.score(RequestClass::Blocks, false), adapter))
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, adapter)| adapter.adapter.clone())
+    }
+
+    /// The adapters that can subscribe to new heads
+    pub(crate) async fn subscribable(&self) -> Vec<Arc<EthereumAdapter>> {
         self.manager
             .get_all(&self.chain_id)
             .await
             .unwrap_or_default()
-            .first()
-            .map(|ethereum_network_adapter| ethereum_network_adapter.adapter.clone())
+            .iter()
+            .filter(|adapter| adapter.adapter.supports_subscriptions())
+            .map(|adapter| adapter.adapter.clone())
+            .collect()
+    }
+
+    /// All adapters of the chain without verification, including the
+    /// ones of quarantined providers
+    pub(crate) fn all_including_quarantined(&self) -> Vec<Arc<EthereumAdapter>> {
+        self.manager
+            .get_all_including_quarantined(&self.chain_id)
+            .iter()
+            .map(|adapter| adapter.adapter.clone())
+            .collect()
+    }
+
+    /// Stop using `provider` for the chain, or start using it again.
+    /// Returns whether the provider is quarantined now
+    pub(crate) fn set_quarantined(&self, provider: &str, quarantined: bool) -> bool {
+        self.manager
+            .set_quarantined(&self.chain_id, &provider.into(), quarantined)
     }
 
     /// call_or_cheapest will bypass ProviderManagers' validation in order to remain non async.
@@ -261,22 +339,25 @@ impl EthereumNetworkAdapters {
         match self.call_only_adapter() {
             Ok(Some(adapter)) => Ok(adapter),
             _ => {
-                self.unverified_cheapest_with(capabilities.unwrap_or(&NodeCapabilities {
-                    // Archive is required for call_only
-                    archive: true,
-                    traces: false,
-                }))
+                self.unverified_cheapest_with(
+                    capabilities.unwrap_or(&NodeCapabilities {
+                        // Archive is required for call_only
+                        archive: true,
+                        traces: false,
+                    }),
+                    RequestClass::Calls,
+                )
             }
         }
     }
 
     pub fn call_only_adapter(&self) -> anyhow::Result<Option<Arc<EthereumAdapter>>> {
-        if self.call_only_adapters.is_empty() {
+        let call_only_adapters = self.call_only_manager.get_all_unverified(&self.chain_id);
+        if call_only_adapters.is_empty() {
             return Ok(None);
         }
 
-        let adapters = self
-            .call_only_adapters
+        let adapters = call_only_adapters
             .iter()
             .min_by_key(|x| Arc::strong_count(&x.adapter))
             .ok_or(anyhow!("no available call only endpoints"))?;
@@ -303,7 +384,7 @@ mod tests {
     use graph::data::value::Word;
     use graph::http::HeaderMap;
     use graph::{
-        endpoint::EndpointMetrics,
+        endpoint::{ConnectionType, EndpointMetrics, RequestClass, RequestLabels, RequestOutcome},
         firehose::SubgraphLimit,
         prelude::MetricsRegistry,
         slog::{o, Discard, Logger},
@@ -311,6 +392,7 @@ mod tests {
         url::Url,
     };
     use std::sync::Arc;
+    use std::time::Duration;
     use uuid::Uuid;
 
     use crate::{EthereumAdapter, EthereumAdapterTrait, ProviderEthRpcMetrics, Transport};
@@ -393,6 +475,8 @@ mod tests {
                 provider_metrics.clone(),
                 true,
                 true,
+                false,
+                None,
             )
             .await,
         );
@@ -405,6 +489,8 @@ mod tests {
                 provider_metrics.clone(),
                 true,
                 false,
+                false,
+                None,
             )
             .await,
         );
@@ -437,19 +523,25 @@ mod tests {
         {
             // Not Found
             assert!(adapters
-                .cheapest_with(&NodeCapabilities {
-                    archive: false,
-                    traces: true,
-                })
+                .cheapest_with(
+                    &NodeCapabilities {
+                        archive: false,
+                        traces: true,
+                    },
+                    RequestClass::Other
+                )
                 .await
                 .is_err());
 
             // Check cheapest is not call only
             let adapter = adapters
-                .cheapest_with(&NodeCapabilities {
-                    archive: true,
-                    traces: false,
-                })
+                .cheapest_with(
+                    &NodeCapabilities {
+                        archive: true,
+                        traces: false,
+                    },
+                    RequestClass::Other,
+                )
                 .await
                 .unwrap();
             assert_eq!(adapter.is_call_only(), false);
@@ -467,7 +559,7 @@ mod tests {
 
         // Check empty falls back to call only
         {
-            adapters.call_only_adapters = vec![];
+            adapters.call_only_manager = ProviderManager::default();
             let adapter = adapters
                 .call_or_cheapest(Some(&NodeCapabilities {
                     archive: true,
@@ -499,6 +591,8 @@ mod tests {
                 provider_metrics.clone(),
                 true,
                 true,
+                false,
+                None,
             )
             .await,
         );
@@ -511,6 +605,8 @@ mod tests {
                 provider_metrics.clone(),
                 true,
                 false,
+                false,
+                None,
             )
             .await,
         );
@@ -570,6 +666,8 @@ mod tests {
                 provider_metrics.clone(),
                 true,
                 true,
+                false,
+                None,
             )
             .await,
         );
@@ -582,6 +680,8 @@ mod tests {
                 provider_metrics.clone(),
                 true,
                 false,
+                false,
+                None,
             )
             .await,
         );
@@ -637,6 +737,8 @@ mod tests {
                 provider_metrics.clone(),
                 true,
                 false,
+                false,
+                None,
             )
             .await,
         );
@@ -724,6 +826,7 @@ mod tests {
                 },
                 adapter: adapter.clone(),
                 limit: limit.clone(),
+                expected_chain_id: None,
             });
             always_retest_adapters.push(EthereumNetworkAdapter {
                 endpoint_metrics: metrics.clone(),
@@ -733,6 +836,7 @@ mod tests {
                 },
                 adapter,
                 limit,
+                expected_chain_id: None,
             });
         });
         let manager = ProviderManager::<EthereumNetworkAdapter>::new(
@@ -753,18 +857,25 @@ mod tests {
         let no_retest_adapters = EthereumNetworkAdapters::new(
             chain_id.clone(),
             manager.cheap_clone(),
-            vec![],
+            ProviderManager::default(),
             Some(0f64),
         );
-        let always_retest_adapters =
-            EthereumNetworkAdapters::new(chain_id, manager.cheap_clone(), vec![], Some(1f64));
+        let always_retest_adapters = EthereumNetworkAdapters::new(
+            chain_id,
+            manager.cheap_clone(),
+            ProviderManager::default(),
+            Some(1f64),
+        );
 
         assert_eq!(
             no_retest_adapters
-                .cheapest_with(&NodeCapabilities {
-                    archive: true,
-                    traces: false,
-                })
+                .cheapest_with(
+                    &NodeCapabilities {
+                        archive: true,
+                        traces: false,
+                    },
+                    RequestClass::Other
+                )
                 .await
                 .unwrap()
                 .provider(),
@@ -772,10 +883,13 @@ mod tests {
         );
         assert_eq!(
             always_retest_adapters
-                .cheapest_with(&NodeCapabilities {
-                    archive: true,
-                    traces: false,
-                })
+                .cheapest_with(
+                    &NodeCapabilities {
+                        archive: true,
+                        traces: false,
+                    },
+                    RequestClass::Other
+                )
                 .await
                 .unwrap()
                 .provider(),
@@ -817,6 +931,7 @@ mod tests {
             adapter: fake_adapter(&logger, &error_provider, &provider_metrics, &metrics, false)
                 .await,
             limit: SubgraphLimit::Unlimited,
+            expected_chain_id: None,
         });
 
         let mut always_retest_adapters = vec![];
@@ -835,6 +950,7 @@ mod tests {
             )
             .await,
             limit: SubgraphLimit::Unlimited,
+            expected_chain_id: None,
         });
         let manager = ProviderManager::<EthereumNetworkAdapter>::new(
             logger.clone(),
@@ -849,15 +965,18 @@ mod tests {
         let always_retest_adapters = EthereumNetworkAdapters::new(
             chain_id.clone(),
             manager.cheap_clone(),
-            vec![],
+            ProviderManager::default(),
             Some(1f64),
         );
         assert_eq!(
             always_retest_adapters
-                .cheapest_with(&NodeCapabilities {
-                    archive: true,
-                    traces: false,
-                })
+                .cheapest_with(
+                    &NodeCapabilities {
+                        archive: true,
+                        traces: false,
+                    },
+                    RequestClass::Other
+                )
                 .await
                 .unwrap()
                 .provider(),
@@ -874,14 +993,21 @@ mod tests {
         );
         manager.mark_all_valid().await;
 
-        let no_retest_adapters =
-            EthereumNetworkAdapters::new(chain_id.clone(), manager, vec![], Some(0f64));
+        let no_retest_adapters = EthereumNetworkAdapters::new(
+            chain_id.clone(),
+            manager,
+            ProviderManager::default(),
+            Some(0f64),
+        );
         assert_eq!(
             no_retest_adapters
-                .cheapest_with(&NodeCapabilities {
-                    archive: true,
-                    traces: false,
-                })
+                .cheapest_with(
+                    &NodeCapabilities {
+                        archive: true,
+                        traces: false,
+                    },
+                    RequestClass::Other
+                )
                 .await
                 .unwrap()
                 .provider(),
@@ -904,6 +1030,7 @@ mod tests {
             )
             .await,
             limit: SubgraphLimit::Disabled,
+            expected_chain_id: None,
         });
         let manager = ProviderManager::new(
             logger,
@@ -916,16 +1043,100 @@ mod tests {
         );
         manager.mark_all_valid().await;
 
-        let no_available_adapter = EthereumNetworkAdapters::new(chain_id, manager, vec![], None);
+        let no_available_adapter =
+            EthereumNetworkAdapters::new(chain_id, manager, ProviderManager::default(), None);
         let res = no_available_adapter
-            .cheapest_with(&NodeCapabilities {
-                archive: true,
-                traces: false,
-            })
+            .cheapest_with(
+                &NodeCapabilities {
+                    archive: true,
+                    traces: false,
+                },
+                RequestClass::Other,
+            )
             .await;
         assert!(res.is_err(), "{:?}", res);
     }
 
+    #[tokio::test]
+    async fn eth_adapter_selection_by_request_class() {
+        let logger = Logger::root(Discard, o!());
+        let limited_provider = Uuid::new_v4().to_string();
+        let slow_provider = Uuid::new_v4().to_string();
+
+        let mock_registry = Arc::new(MetricsRegistry::mock());
+        let metrics = Arc::new(EndpointMetrics::new(
+            logger,
+            &[limited_provider.clone(), slow_provider.clone()],
+            mock_registry.clone(),
+        ));
+        let logger = graph::log::logger(true);
+        let provider_metrics = Arc::new(ProviderEthRpcMetrics::new(mock_registry.clone()));
+        let chain_id: Word = "chain_id".into();
+
+        // The limited provider is faster, but rate limits calls
+        let record = |provider: &str, method: &str, ms: u64, outcome: RequestOutcome| {
+            let labels = RequestLabels {
+                provider: provider.into(),
+                req_type: method.into(),
+                conn_type: ConnectionType::Rpc,
+            };
+            metrics.record(&labels, Some(Duration::from_millis(ms)), outcome);
+        };
+        record(
+            &limited_provider,
+            "eth_getLogs",
+            10,
+            RequestOutcome::Success,
+        );
+        record(
+            &limited_provider,
+            "eth_call",
+            10,
+            RequestOutcome::RateLimited,
+        );
+        record(&slow_provider, "eth_getLogs", 50, RequestOutcome::Success);
+        record(&slow_provider, "eth_call", 50, RequestOutcome::Success);
+
+        let mut adapters = vec![];
+        for provider in [&limited_provider, &slow_provider] {
+            adapters.push(EthereumNetworkAdapter {
+                endpoint_metrics: metrics.clone(),
+                capabilities: NodeCapabilities {
+                    archive: true,
+                    traces: false,
+                },
+                adapter: fake_adapter(&logger, provider, &provider_metrics, &metrics, false).await,
+                limit: SubgraphLimit::Unlimited,
+                expected_chain_id: None,
+            });
+        }
+        let manager = ProviderManager::<EthereumNetworkAdapter>::new(
+            logger,
+            vec![(chain_id.clone(), adapters)].into_iter(),
+            Arc::new(NoopIdentValidator),
+        );
+        manager.mark_all_valid().await;
+        let adapters =
+            EthereumNetworkAdapters::new(chain_id, manager, ProviderManager::default(), Some(0f64));
+
+        let capabilities = NodeCapabilities {
+            archive: true,
+            traces: false,
+        };
+        assert_eq!(
+            adapters
+                .cheapest_with(&capabilities, RequestClass::Logs)
+                .await
+                .unwrap()
+                .provider(),
+            limited_provider
+        );
+        assert_eq!(
+            adapters.call_or_cheapest(None).unwrap().provider(),
+            slow_provider
+        );
+    }
+
     async fn fake_adapter(
         logger: &Logger,
         provider: &str,
@@ -948,6 +1159,8 @@ mod tests {
                 provider_metrics.clone(),
                 true,
                 call_only,
+                false,
+                None,
             )
             .await,
         )
@@ -0,0 +1,380 @@
+//! Account and storage proofs from `eth_getProof` (EIP-1186) and their
+//! verification against the state root of a block. A verified proof shows
+//! that the values in it are the ones in the state of the block, no matter
+//! which Ethereum node the proof came from
+use graph::prelude::{
+    anyhow::{anyhow, bail, Context},
+    serde_json,
+    web3::types::{Bytes, H160, H256, U256},
+    Error,
+};
+use rlp::Rlp;
+use serde::Deserialize;
+use tiny_keccak::keccak256;
+
+/// A storage slot of an account, its value and the proof for it
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageProof {
+    pub key: H256,
+    pub value: U256,
+    pub proof: Vec<Bytes>,
+}
+
+/// An account and some of its storage slots, with the proofs for them
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountProof {
+    pub address: H160,
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+    pub storage_hash: H256,
+    pub account_proof: Vec<Bytes>,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+impl AccountProof {
+    /// Parse the result of `eth_getProof`
+    pub fn from_json(proof: serde_json::Value) -> Result<Self, Error> {
+        #[derive(Deserialize)]
+        struct RawStorageProof {
+            key: U256,
+            value: U256,
+            proof: Vec<Bytes>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct RawProof {
+            address: H160,
+            balance: U256,
+            nonce: U256,
+            code_hash: H256,
+            storage_hash: H256,
+            account_proof: Vec<Bytes>,
+            storage_proof: Vec<RawStorageProof>,
+        }
+
+        let proof: RawProof = serde_json::from_value(proof).context("invalid proof")?;
+        let storage_proof = proof
+            .storage_proof
+            .into_iter()
+            .map(|slot| {
+                let mut key = H256::zero();
+                slot.key.to_big_endian(&mut key.0);
+                StorageProof {
+                    key,
+                    value: slot.value,
+                    proof: slot.proof,
+                }
+            })
+            .collect();
+        Ok(AccountProof {
+            address: proof.address,
+            balance: proof.balance,
+            nonce: proof.nonce,
+            code_hash: proof.code_hash,
+            storage_hash: proof.storage_hash,
+            account_proof: proof.account_proof,
+            storage_proof,
+        })
+    }
+
+    /// Check that the account is the one in the state with `state_root`,
+    /// and that every storage slot has its value in the storage of the
+    /// account
+    pub fn verify(&self, state_root: H256) -> Result<(), Error> {
+        let account = verify_proof(state_root, self.address.as_bytes(), &self.account_proof)
+            .with_context(|| format!("invalid proof for account {:?}", self.address))?;
+        let (nonce, balance, storage_hash, code_hash) = match account {
+            Some(account) => {
+                let account = Rlp::new(account);
+                (
+                    uint(account.at(0)?.data()?)?,
+                    uint(account.at(1)?.data()?)?,
+                    hash(account.at(2)?.data()?)?,
+                    hash(account.at(3)?.data()?)?,
+                )
+            }
+            None => (
+                U256::zero(),
+                U256::zero(),
+                empty_trie_root(),
+                H256(keccak256(&[])),
+            ),
+        };
+        if (nonce, balance, storage_hash, code_hash)
+            != (self.nonce, self.balance, self.storage_hash, self.code_hash)
+        {
+            bail!(
+                "the proof for account {:?} does not match the account in the state",
+                self.address
+            );
+        }
+
+        for slot in &self.storage_proof {
+            let value = verify_proof(self.storage_hash, slot.key.as_bytes(), &slot.proof)
+                .with_context(|| {
+                    format!(
+                        "invalid proof for storage slot {:?} of account {:?}",
+                        slot.key, self.address
+                    )
+                })?;
+            let value = match value {
+                Some(value) => uint(Rlp::new(value).data()?)?,
+                None => U256::zero(),
+            };
+            if value != slot.value {
+                bail!(
+                    "the proof for storage slot {:?} of account {:?} does not match its value",
+                    slot.key,
+                    self.address
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The root of a trie without any entries, like the storage trie of an
+/// account without storage
+fn empty_trie_root() -> H256 {
+    // The hash of the empty string in RLP
+    H256(keccak256(&[0x80]))
+}
+
+fn uint(bytes: &[u8]) -> Result<U256, Error> {
+    if bytes.len() > 32 {
+        bail!("integer in proof is longer than 32 bytes");
+    }
+    Ok(U256::from_big_endian(bytes))
+}
+
+fn hash(bytes: &[u8]) -> Result<H256, Error> {
+    if bytes.len() != 32 {
+        bail!("hash in proof is {} bytes long instead of 32", bytes.len());
+    }
+    Ok(H256::from_slice(bytes))
+}
+
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// The nibbles of the hex-prefix encoded path of a leaf or extension node,
+/// and whether the node is a leaf
+fn decode_path(encoded: &[u8]) -> Result<(bool, Vec<u8>), Error> {
+    let first = *encoded
+        .first()
+        .ok_or_else(|| anyhow!("trie node with an empty path"))?;
+    let flag = first >> 4;
+    if flag > 3 {
+        bail!("trie node with invalid path prefix {}", flag);
+    }
+    let mut path = Vec::with_capacity(encoded.len() * 2);
+    if flag & 1 == 1 {
+        path.push(first & 0x0f);
+    }
+    path.extend(nibbles(&encoded[1..]));
+    Ok((flag & 2 == 2, path))
+}
+
+/// A reference from a trie node to another node, which is either its hash
+/// or, for nodes shorter than 32 bytes, the node itself
+enum Node<'a> {
+    Hash(H256),
+    Inline(Rlp<'a>),
+}
+
+fn child(item: Rlp<'_>) -> Result<Option<Node<'_>>, Error> {
+    if item.is_list() {
+        return Ok(Some(Node::Inline(item)));
+    }
+    match item.data()? {
+        [] => Ok(None),
+        data if data.len() == 32 => Ok(Some(Node::Hash(H256::from_slice(data)))),
+        _ => bail!("invalid reference to a trie node"),
+    }
+}
+
+/// Follow the nodes in `proof` from the root of the trie with hash `root`
+/// to the value for `key` in that trie. Keys in the state and storage tries
+/// are hashed. Returns `None` if the proof shows that the trie does not
+/// have a value for `key`
+fn verify_proof<'a>(root: H256, key: &[u8], proof: &'a [Bytes]) -> Result<Option<&'a [u8]>, Error> {
+    if root == empty_trie_root() {
+        return Ok(None);
+    }
+
+    let path = nibbles(&keccak256(key));
+    let mut proof = proof.iter();
+    let mut next = Node::Hash(root);
+    let mut pos = 0;
+    loop {
+        let node = match next {
+            Node::Hash(hash) => {
+                let node = proof
+                    .next()
+                    .ok_or_else(|| anyhow!("the proof ends before it reaches the value"))?;
+                if H256(keccak256(&node.0)) != hash {
+                    bail!("the proof has no node with hash {:?}", hash);
+                }
+                Rlp::new(&node.0)
+            }
+            Node::Inline(node) => node,
+        };
+
+        match node.item_count()? {
+            17 => {
+                // All keys of a trie have the same length, and values are
+                // therefore only ever in leaves
+                let nibble = *path
+                    .get(pos)
+                    .ok_or_else(|| anyhow!("the proof has a branch node at the end of the key"))?;
+                match child(node.at(nibble as usize)?)? {
+                    Some(child) => next = child,
+                    None => return Ok(None),
+                }
+                pos += 1;
+            }
+            2 => {
+                let (leaf, node_path) = decode_path(node.at(0)?.data()?)?;
+                let rest = &path[pos..];
+                if leaf {
+                    if rest != node_path.as_slice() {
+                        return Ok(None);
+                    }
+                    return Ok(Some(node.at(1)?.data()?));
+                }
+                if !rest.starts_with(&node_path) {
+                    return Ok(None);
+                }
+                pos += node_path.len();
+                next = child(node.at(1)?)?
+                    .ok_or_else(|| anyhow!("the proof has an extension node without child"))?;
+            }
+            n => bail!("the proof has a trie node with {} items", n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rlp::RlpStream;
+
+    use super::*;
+
+    fn trimmed(value: U256) -> Vec<u8> {
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        let start = bytes.iter().position(|b| *b != 0).unwrap_or(32);
+        bytes[start..].to_vec()
+    }
+
+    /// A leaf with the hex-prefix encoding of an odd number of nibbles
+    fn leaf(path: &[u8], value: Vec<u8>) -> Vec<u8> {
+        assert_eq!(1, path.len() % 2);
+        let mut encoded = vec![0x30 | path[0]];
+        encoded.extend(path[1..].chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&encoded);
+        stream.append(&value);
+        stream.out().to_vec()
+    }
+
+    /// A trie with just the value for `key`, stored in a leaf below a
+    /// branch node. Returns the root of the trie and the proof for `key`
+    fn trie(key: &[u8], value: Vec<u8>) -> (H256, Vec<Bytes>) {
+        let path = nibbles(&keccak256(key));
+        let leaf = leaf(&path[1..], value);
+
+        let mut branch = RlpStream::new_list(17);
+        for nibble in 0..16 {
+            if nibble == path[0] {
+                branch.append(&keccak256(&leaf).to_vec());
+            } else {
+                branch.append_empty_data();
+            }
+        }
+        branch.append_empty_data();
+        let branch = branch.out().to_vec();
+
+        (H256(keccak256(&branch)), vec![Bytes(branch), Bytes(leaf)])
+    }
+
+    #[test]
+    fn verify_account_and_storage_proofs() {
+        let address = H160::from_low_u64_be(0x1234);
+        let slot = H256::from_low_u64_be(3);
+        let value = U256::from(0xdead_beef_u64);
+
+        let stored = rlp::encode(&trimmed(value)).to_vec();
+        let (storage_hash, storage_proof) = trie(slot.as_bytes(), stored);
+
+        let balance = U256::from(1_000_000);
+        let code_hash = H256(keccak256(&[0x60, 0x80]));
+        let mut account = RlpStream::new_list(4);
+        account.append(&trimmed(U256::from(7)));
+        account.append(&trimmed(balance));
+        account.append(&storage_hash.as_bytes().to_vec());
+        account.append(&code_hash.as_bytes().to_vec());
+        let (state_root, account_proof) = trie(address.as_bytes(), account.out().to_vec());
+
+        let other_slot = H256::from_low_u64_be(4);
+        let proof = AccountProof {
+            address,
+            balance,
+            nonce: U256::from(7),
+            code_hash,
+            storage_hash,
+            account_proof,
+            storage_proof: vec![
+                StorageProof {
+                    key: slot,
+                    value,
+                    proof: storage_proof.clone(),
+                },
+                // The proof for `slot` also shows that `other_slot` is empty
+                StorageProof {
+                    key: other_slot,
+                    value: U256::zero(),
+                    proof: storage_proof,
+                },
+            ],
+        };
+        proof.verify(state_root).unwrap();
+
+        assert!(proof.verify(H256::from_low_u64_be(1)).is_err());
+
+        let mut wrong_balance = proof.clone();
+        wrong_balance.balance = U256::from(1);
+        assert!(wrong_balance.verify(state_root).is_err());
+
+        let mut wrong_value = proof.clone();
+        wrong_value.storage_proof[0].value = U256::from(1);
+        assert!(wrong_value.verify(state_root).is_err());
+
+        let mut missing_value = proof;
+        missing_value.storage_proof[1].value = U256::from(1);
+        assert!(missing_value.verify(state_root).is_err());
+    }
+
+    #[test]
+    fn proof_from_json() {
+        let proof = serde_json::json!({
+            "address": "0x0000000000000000000000000000000000001234",
+            "accountProof": ["0x80"],
+            "balance": "0x0",
+            "codeHash": "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            "nonce": "0x0",
+            "storageHash": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "storageProof": [{ "key": "0x3", "value": "0x0", "proof": [] }]
+        });
+
+        let proof = AccountProof::from_json(proof).unwrap();
+        assert_eq!(H256::from_low_u64_be(3), proof.storage_proof[0].key);
+        assert_eq!(empty_trie_root(), proof.storage_hash);
+        assert_eq!(H256(keccak256(&[])), proof.code_hash);
+        assert_eq!(vec![Bytes(vec![0x80])], proof.account_proof);
+    }
+}
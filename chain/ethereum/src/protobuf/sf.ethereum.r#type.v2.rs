@@ -162,6 +162,18 @@ pub struct BlockHeader {
     /// Base fee per gas according to EIP-1559 (e.g. London Fork) rules, only set if London is present/active on the chain.
     #[prost(message, optional, tag = "18")]
     pub base_fee_per_gas: ::core::option::Option<BigInt>,
+    /// BlobGasUsed is the total amount of blob gas consumed by the transactions of the block, only set
+    /// if Cancun (EIP-4844) is present/active on the chain.
+    #[prost(uint64, optional, tag = "22")]
+    pub blob_gas_used: ::core::option::Option<u64>,
+    /// ExcessBlobGas is a running total of blob gas consumed in excess of the target, prior to the
+    /// block, only set if Cancun (EIP-4844) is present/active on the chain.
+    #[prost(uint64, optional, tag = "23")]
+    pub excess_blob_gas: ::core::option::Option<u64>,
+    /// ParentBeaconRoot is the root of the parent beacon block according to EIP-4788, only set if
+    /// Cancun is present/active on the chain.
+    #[prost(bytes = "vec", tag = "24")]
+    pub parent_beacon_root: ::prost::alloc::vec::Vec<u8>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1,11 +1,16 @@
-use super::runtime_adapter::UnresolvedContractCall;
+use super::runtime_adapter::{
+    DecodedEvent, EventDecodeRequest, ProofRequest, UnresolvedContractCall,
+};
+use crate::blobs::BlobTransaction;
+use crate::proofs::{AccountProof, StorageProof};
 use crate::trigger::{
     EthereumBlockData, EthereumCallData, EthereumEventData, EthereumTransactionData,
 };
+use graph::components::ethereum::{EthereumTransactionReceipt, Withdrawal};
 use graph::{
     prelude::{
         ethabi,
-        web3::types::{Log, TransactionReceipt, H256},
+        web3::types::{AccessListItem, Bytes, Log, TransactionReceipt, H256},
         BigInt,
     },
     runtime::{
@@ -150,6 +155,369 @@ impl FromAscObj<AscUnresolvedContractCall_0_0_4> for UnresolvedContractCall {
     }
 }
 
+/// The calls for `ethereum.callMany`
+pub struct AscUnresolvedContractCallArray(Array<AscPtr<AscUnresolvedContractCall_0_0_4>>);
+
+impl AscType for AscUnresolvedContractCallArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl FromAscObj<AscUnresolvedContractCallArray> for Vec<UnresolvedContractCall> {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_calls: AscUnresolvedContractCallArray,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Vec::from_asc_obj(asc_calls.0, heap, gas, depth)
+    }
+}
+
+impl AscIndexId for AscUnresolvedContractCallArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArraySmartContractCall;
+}
+
+/// The results of `ethereum.callMany`; calls that reverted are `null`
+pub struct AscContractCallResultArray(Array<AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>>);
+
+impl AscType for AscContractCallResultArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl ToAscObj<AscContractCallResultArray> for Vec<Option<Vec<ethabi::Token>>> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscContractCallResultArray, HostExportError> {
+        let results = self
+            .iter()
+            .map(|result| match result {
+                Some(tokens) => asc_new(heap, tokens.as_slice(), gas),
+                None => Ok(AscPtr::null()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscContractCallResultArray(Array::new(&results, heap, gas)?))
+    }
+}
+
+impl AscIndexId for AscContractCallResultArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayArrayEthereumValue;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscBlobTransaction {
+    pub hash: AscPtr<AscH256>,
+    pub index: AscPtr<AscBigInt>,
+    pub from: AscPtr<AscH160>,
+    pub to: AscPtr<AscH160>,
+    pub max_fee_per_blob_gas: AscPtr<AscBigInt>,
+    pub blob_versioned_hashes: AscPtr<AscTopicArray>,
+}
+
+impl AscIndexId for AscBlobTransaction {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BlobTransaction;
+}
+
+impl ToAscObj<AscBlobTransaction> for BlobTransaction {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBlobTransaction, HostExportError> {
+        Ok(AscBlobTransaction {
+            hash: asc_new(heap, &self.hash, gas)?,
+            index: asc_new(heap, &BigInt::from_unsigned_u128(self.index), gas)?,
+            from: asc_new(heap, &self.from, gas)?,
+            to: self
+                .to
+                .map(|to| asc_new(heap, &to, gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            max_fee_per_blob_gas: asc_new(
+                heap,
+                &BigInt::from_unsigned_u256(&self.max_fee_per_blob_gas),
+                gas,
+            )?,
+            blob_versioned_hashes: asc_new(heap, &self.blob_versioned_hashes, gas)?,
+        })
+    }
+}
+
+pub struct AscBlobTransactionArray(Array<AscPtr<AscBlobTransaction>>);
+
+impl AscType for AscBlobTransactionArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl ToAscObj<AscBlobTransactionArray> for Vec<BlobTransaction> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBlobTransactionArray, HostExportError> {
+        let transactions = self
+            .iter()
+            .map(|tx| asc_new(heap, tx, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscBlobTransactionArray(Array::new(
+            &transactions,
+            heap,
+            gas,
+        )?))
+    }
+}
+
+impl AscIndexId for AscBlobTransactionArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayBlobTransaction;
+}
+
+impl FromAscObj<AscTopicArray> for Vec<H256> {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_hashes: AscTopicArray,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Vec::from_asc_obj(asc_hashes.0, heap, gas, depth)
+    }
+}
+
+/// The account and storage slots for `ethereum.getProof`
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscProofRequest {
+    pub address: AscPtr<AscAddress>,
+    pub slots: AscPtr<AscTopicArray>,
+}
+
+impl AscIndexId for AscProofRequest {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ProofRequest;
+}
+
+impl FromAscObj<AscProofRequest> for ProofRequest {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_request: AscProofRequest,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(ProofRequest {
+            address: asc_get(heap, asc_request.address, gas, depth)?,
+            slots: asc_get(heap, asc_request.slots, gas, depth)?,
+        })
+    }
+}
+
+/// The log and the names of the candidate ABIs for `ethereum.decodeEvent`
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscEventDecodeRequest {
+    pub abis: AscPtr<Array<AscPtr<AscString>>>,
+    pub topics: AscPtr<AscTopicArray>,
+    pub data: AscPtr<Uint8Array>,
+}
+
+impl AscIndexId for AscEventDecodeRequest {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EventDecodeRequest;
+}
+
+impl FromAscObj<AscEventDecodeRequest> for EventDecodeRequest {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_request: AscEventDecodeRequest,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(EventDecodeRequest {
+            abis: asc_get(heap, asc_request.abis, gas, depth)?,
+            topics: asc_get(heap, asc_request.topics, gas, depth)?,
+            data: asc_get(heap, asc_request.data, gas, depth)?,
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscDecodedEvent {
+    pub abi: AscPtr<AscString>,
+    pub signature: AscPtr<AscString>,
+    pub params: AscPtr<AscLogParamArray>,
+}
+
+impl AscIndexId for AscDecodedEvent {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::DecodedEvent;
+}
+
+impl ToAscObj<AscDecodedEvent> for DecodedEvent {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscDecodedEvent, HostExportError> {
+        Ok(AscDecodedEvent {
+            abi: asc_new(heap, self.abi.as_str(), gas)?,
+            signature: asc_new(heap, self.signature.as_str(), gas)?,
+            params: asc_new(heap, &self.params, gas)?,
+        })
+    }
+}
+
+/// The nodes of a Merkle proof, which are an `Array<Bytes>` in mappings
+/// just like topics
+pub struct AscProofNodeArray(Array<AscPtr<Uint8Array>>);
+
+impl AscType for AscProofNodeArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl ToAscObj<AscProofNodeArray> for Vec<Bytes> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscProofNodeArray, HostExportError> {
+        let nodes = self
+            .iter()
+            .map(|node| asc_new(heap, node, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscProofNodeArray(Array::new(&nodes, heap, gas)?))
+    }
+}
+
+impl AscIndexId for AscProofNodeArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayH256;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscStorageProof {
+    pub key: AscPtr<AscH256>,
+    pub value: AscPtr<AscBigInt>,
+    pub proof: AscPtr<AscProofNodeArray>,
+}
+
+impl AscIndexId for AscStorageProof {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::StorageProof;
+}
+
+impl ToAscObj<AscStorageProof> for StorageProof {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscStorageProof, HostExportError> {
+        Ok(AscStorageProof {
+            key: asc_new(heap, &self.key, gas)?,
+            value: asc_new(heap, &BigInt::from_unsigned_u256(&self.value), gas)?,
+            proof: asc_new(heap, &self.proof, gas)?,
+        })
+    }
+}
+
+pub struct AscStorageProofArray(Array<AscPtr<AscStorageProof>>);
+
+impl AscType for AscStorageProofArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl ToAscObj<AscStorageProofArray> for Vec<StorageProof> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscStorageProofArray, HostExportError> {
+        let slots = self
+            .iter()
+            .map(|slot| asc_new(heap, slot, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscStorageProofArray(Array::new(&slots, heap, gas)?))
+    }
+}
+
+impl AscIndexId for AscStorageProofArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayStorageProof;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscAccountProof {
+    pub address: AscPtr<AscH160>,
+    pub balance: AscPtr<AscBigInt>,
+    pub nonce: AscPtr<AscBigInt>,
+    pub code_hash: AscPtr<AscH256>,
+    pub storage_hash: AscPtr<AscH256>,
+    pub account_proof: AscPtr<AscProofNodeArray>,
+    pub storage_proof: AscPtr<AscStorageProofArray>,
+}
+
+impl AscIndexId for AscAccountProof {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::AccountProof;
+}
+
+impl ToAscObj<AscAccountProof> for AccountProof {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscAccountProof, HostExportError> {
+        Ok(AscAccountProof {
+            address: asc_new(heap, &self.address, gas)?,
+            balance: asc_new(heap, &BigInt::from_unsigned_u256(&self.balance), gas)?,
+            nonce: asc_new(heap, &BigInt::from_unsigned_u256(&self.nonce), gas)?,
+            code_hash: asc_new(heap, &self.code_hash, gas)?,
+            storage_hash: asc_new(heap, &self.storage_hash, gas)?,
+            account_proof: asc_new(heap, &self.account_proof, gas)?,
+            storage_proof: asc_new(heap, &self.storage_proof, gas)?,
+        })
+    }
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub struct AscUnresolvedContractCall {
@@ -223,6 +591,98 @@ impl AscIndexId for AscEthereumBlock_0_0_6 {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumBlock;
 }
 
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscWithdrawal {
+    pub index: AscPtr<AscBigInt>,
+    pub validator_index: AscPtr<AscBigInt>,
+    pub address: AscPtr<AscH160>,
+    pub amount: AscPtr<AscBigInt>,
+}
+
+impl AscIndexId for AscWithdrawal {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::Withdrawal;
+}
+
+impl ToAscObj<AscWithdrawal> for Withdrawal {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscWithdrawal, HostExportError> {
+        Ok(AscWithdrawal {
+            index: asc_new(heap, &BigInt::from(self.index), gas)?,
+            validator_index: asc_new(heap, &BigInt::from(self.validator_index), gas)?,
+            address: asc_new(heap, &self.address, gas)?,
+            amount: asc_new(heap, &BigInt::from(self.amount), gas)?,
+        })
+    }
+}
+
+pub struct AscWithdrawalArray(Array<AscPtr<AscWithdrawal>>);
+
+impl AscType for AscWithdrawalArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl ToAscObj<AscWithdrawalArray> for Vec<Withdrawal> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscWithdrawalArray, HostExportError> {
+        let withdrawals = self
+            .iter()
+            .map(|withdrawal| asc_new(heap, withdrawal, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscWithdrawalArray(Array::new(&withdrawals, heap, gas)?))
+    }
+}
+
+impl AscIndexId for AscWithdrawalArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayWithdrawal;
+}
+
+/// Introduced in API Version 0.0.11, this is the same as [`AscEthereumBlock_0_0_6`] with the
+/// fields that blocks have since the merge. They are null for blocks from before the fork that
+/// introduced them
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscEthereumBlock_0_0_11 {
+    pub hash: AscPtr<AscH256>,
+    pub parent_hash: AscPtr<AscH256>,
+    pub uncles_hash: AscPtr<AscH256>,
+    pub author: AscPtr<AscH160>,
+    pub state_root: AscPtr<AscH256>,
+    pub transactions_root: AscPtr<AscH256>,
+    pub receipts_root: AscPtr<AscH256>,
+    pub number: AscPtr<AscBigInt>,
+    pub gas_used: AscPtr<AscBigInt>,
+    pub gas_limit: AscPtr<AscBigInt>,
+    pub timestamp: AscPtr<AscBigInt>,
+    pub difficulty: AscPtr<AscBigInt>,
+    pub total_difficulty: AscPtr<AscBigInt>,
+    pub size: AscPtr<AscBigInt>,
+    pub base_fee_per_block: AscPtr<AscBigInt>,
+    pub withdrawals: AscPtr<AscWithdrawalArray>,
+    pub parent_beacon_block_root: AscPtr<AscH256>,
+    pub blob_gas_used: AscPtr<AscBigInt>,
+    pub excess_blob_gas: AscPtr<AscBigInt>,
+}
+
+impl AscIndexId for AscEthereumBlock_0_0_11 {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumBlock;
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub(crate) struct AscEthereumTransaction_0_0_1 {
@@ -274,6 +734,90 @@ impl AscIndexId for AscEthereumTransaction_0_0_6 {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumTransaction;
 }
 
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscAccessListItem {
+    pub address: AscPtr<AscH160>,
+    pub storage_keys: AscPtr<AscTopicArray>,
+}
+
+impl AscIndexId for AscAccessListItem {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::AccessListItem;
+}
+
+impl ToAscObj<AscAccessListItem> for AccessListItem {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscAccessListItem, HostExportError> {
+        Ok(AscAccessListItem {
+            address: asc_new(heap, &self.address, gas)?,
+            storage_keys: asc_new(heap, &self.storage_keys, gas)?,
+        })
+    }
+}
+
+pub struct AscAccessListItemArray(Array<AscPtr<AscAccessListItem>>);
+
+impl AscType for AscAccessListItemArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl ToAscObj<AscAccessListItemArray> for Vec<AccessListItem> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscAccessListItemArray, HostExportError> {
+        let items = self
+            .iter()
+            .map(|item| asc_new(heap, item, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscAccessListItemArray(Array::new(&items, heap, gas)?))
+    }
+}
+
+impl AscIndexId for AscAccessListItemArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayAccessListItem;
+}
+
+/// Introduced in API Version 0.0.11, this is the same as [`AscEthereumTransaction_0_0_6`] with
+/// the type and the access list of the transaction and the fields of transaction types that only
+/// exist on L2s. The access list is null for legacy transactions
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscEthereumTransaction_0_0_11 {
+    pub hash: AscPtr<AscH256>,
+    pub index: AscPtr<AscBigInt>,
+    pub from: AscPtr<AscH160>,
+    pub to: AscPtr<AscH160>,
+    pub value: AscPtr<AscBigInt>,
+    pub gas_limit: AscPtr<AscBigInt>,
+    pub gas_price: AscPtr<AscBigInt>,
+    pub input: AscPtr<Uint8Array>,
+    pub nonce: AscPtr<AscBigInt>,
+    pub transaction_type: AscPtr<AscBigInt>,
+    pub source_hash: AscPtr<AscH256>,
+    pub mint: AscPtr<AscBigInt>,
+    pub is_system_tx: AscPtr<AscWrapped<bool>>,
+    pub request_id: AscPtr<AscH256>,
+    pub access_list: AscPtr<AscAccessListItemArray>,
+}
+
+impl AscIndexId for AscEthereumTransaction_0_0_11 {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumTransaction;
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub(crate) struct AscEthereumEvent<T, B>
@@ -342,6 +886,40 @@ impl AscIndexId for AscEthereumTransactionReceipt {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::TransactionReceipt;
 }
 
+/// Introduced in API Version 0.0.11, this is the same as [`AscEthereumTransactionReceipt`] with
+/// the type of the transaction, the gas price it actually paid, and the fees that L2s charge for
+/// posting the transaction to L1. The L1 fees are null on other chains
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscEthereumTransactionReceipt_0_0_11 {
+    pub transaction_hash: AscPtr<AscH256>,
+    pub transaction_index: AscPtr<AscBigInt>,
+    pub block_hash: AscPtr<AscH256>,
+    pub block_number: AscPtr<AscBigInt>,
+    pub cumulative_gas_used: AscPtr<AscBigInt>,
+    pub gas_used: AscPtr<AscBigInt>,
+    pub contract_address: AscPtr<AscAddress>,
+    pub logs: AscPtr<AscLogArray>,
+    pub status: AscPtr<AscBigInt>,
+    pub root: AscPtr<AscH256>,
+    pub logs_bloom: AscPtr<AscH2048>,
+    pub transaction_type: AscPtr<AscBigInt>,
+    pub effective_gas_price: AscPtr<AscBigInt>,
+    pub l1_fee: AscPtr<AscBigInt>,
+    pub l1_gas_price: AscPtr<AscBigInt>,
+    pub l1_gas_used: AscPtr<AscBigInt>,
+    pub l1_fee_scalar: AscPtr<AscString>,
+    pub l1_base_fee_scalar: AscPtr<AscBigInt>,
+    pub l1_blob_base_fee: AscPtr<AscBigInt>,
+    pub l1_blob_base_fee_scalar: AscPtr<AscBigInt>,
+    pub gas_used_for_l1: AscPtr<AscBigInt>,
+    pub l1_block_number: AscPtr<AscBigInt>,
+}
+
+impl AscIndexId for AscEthereumTransactionReceipt_0_0_11 {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::TransactionReceipt;
+}
+
 /// Introduced in API Version 0.0.7, this is the same as [`AscEthereumEvent`] with an added
 /// `receipt` field.
 #[repr(C)]
@@ -365,6 +943,34 @@ impl AscIndexId for AscEthereumEvent_0_0_7<AscEthereumTransaction_0_0_6, AscEthe
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumEvent;
 }
 
+/// Introduced in API Version 0.0.11, this is the same as [`AscEthereumEvent_0_0_7`] with the
+/// receipt of API Version 0.0.11 and the topics and data of the log, which is all that fallback
+/// event handlers get since their `params` are empty
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscEthereumEvent_0_0_11<T, B>
+where
+    T: AscType,
+    B: AscType,
+{
+    pub address: AscPtr<AscAddress>,
+    pub log_index: AscPtr<AscBigInt>,
+    pub transaction_log_index: AscPtr<AscBigInt>,
+    pub log_type: AscPtr<AscString>,
+    pub block: AscPtr<B>,
+    pub transaction: AscPtr<T>,
+    pub params: AscPtr<AscLogParamArray>,
+    pub receipt: AscPtr<AscEthereumTransactionReceipt_0_0_11>,
+    pub topics: AscPtr<AscTopicArray>,
+    pub data: AscPtr<Uint8Array>,
+}
+
+impl AscIndexId
+    for AscEthereumEvent_0_0_11<AscEthereumTransaction_0_0_11, AscEthereumBlock_0_0_11>
+{
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumEvent;
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub(crate) struct AscLogParam {
@@ -481,6 +1087,66 @@ impl ToAscObj<AscEthereumBlock_0_0_6> for EthereumBlockData {
     }
 }
 
+impl ToAscObj<AscEthereumBlock_0_0_11> for EthereumBlockData {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumBlock_0_0_11, HostExportError> {
+        let AscEthereumBlock_0_0_6 {
+            hash,
+            parent_hash,
+            uncles_hash,
+            author,
+            state_root,
+            transactions_root,
+            receipts_root,
+            number,
+            gas_used,
+            gas_limit,
+            timestamp,
+            difficulty,
+            total_difficulty,
+            size,
+            base_fee_per_block,
+        } = self.to_asc_obj(heap, gas)?;
+        Ok(AscEthereumBlock_0_0_11 {
+            hash,
+            parent_hash,
+            uncles_hash,
+            author,
+            state_root,
+            transactions_root,
+            receipts_root,
+            number,
+            gas_used,
+            gas_limit,
+            timestamp,
+            difficulty,
+            total_difficulty,
+            size,
+            base_fee_per_block,
+            withdrawals: self
+                .withdrawals
+                .as_ref()
+                .map(|withdrawals| asc_new(heap, withdrawals, gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            parent_beacon_block_root: self
+                .parent_beacon_block_root
+                .map(|root| asc_new(heap, &root, gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            blob_gas_used: self
+                .blob_gas_used
+                .map(|gas_used| asc_new(heap, &BigInt::from(gas_used), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            excess_blob_gas: self
+                .excess_blob_gas
+                .map(|excess| asc_new(heap, &BigInt::from(excess), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+        })
+    }
+}
+
 impl ToAscObj<AscEthereumTransaction_0_0_1> for EthereumTransactionData {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -547,6 +1213,74 @@ impl ToAscObj<AscEthereumTransaction_0_0_6> for EthereumTransactionData {
     }
 }
 
+impl ToAscObj<AscEthereumTransaction_0_0_11> for EthereumTransactionData {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumTransaction_0_0_11, HostExportError> {
+        let AscEthereumTransaction_0_0_6 {
+            hash,
+            index,
+            from,
+            to,
+            value,
+            gas_limit,
+            gas_price,
+            input,
+            nonce,
+        } = self.to_asc_obj(heap, gas)?;
+        Ok(AscEthereumTransaction_0_0_11 {
+            hash,
+            index,
+            from,
+            to,
+            value,
+            gas_limit,
+            gas_price,
+            input,
+            nonce,
+            transaction_type: self
+                .transaction_type
+                .map(|transaction_type| asc_new(heap, &BigInt::from(transaction_type), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            source_hash: self
+                .l2
+                .source_hash
+                .map(|source_hash| asc_new(heap, &source_hash, gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            mint: self
+                .l2
+                .mint
+                .map(|mint| asc_new(heap, &BigInt::from_unsigned_u256(&mint), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            is_system_tx: self
+                .l2
+                .is_system_tx
+                .map(|is_system_tx| {
+                    asc_new(
+                        heap,
+                        &AscWrapped {
+                            inner: is_system_tx,
+                        },
+                        gas,
+                    )
+                })
+                .unwrap_or(Ok(AscPtr::null()))?,
+            request_id: self
+                .l2
+                .request_id
+                .map(|request_id| asc_new(heap, &request_id, gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            access_list: self
+                .access_list
+                .as_ref()
+                .map(|access_list| asc_new(heap, access_list, gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+        })
+    }
+}
+
 impl<T, B> ToAscObj<AscEthereumEvent<T, B>> for EthereumEventData
 where
     T: AscType + AscIndexId,
@@ -580,7 +1314,7 @@ where
 }
 
 impl<T, B> ToAscObj<AscEthereumEvent_0_0_7<T, B>>
-    for (EthereumEventData, Option<&TransactionReceipt>)
+    for (EthereumEventData, Option<&EthereumTransactionReceipt>)
 where
     T: AscType + AscIndexId,
     B: AscType + AscIndexId,
@@ -603,7 +1337,8 @@ where
             params,
         } = event_data.to_asc_obj(heap, gas)?;
         let receipt = if let Some(receipt_data) = optional_receipt {
-            asc_new(heap, receipt_data, gas)?
+            let receipt_data: &TransactionReceipt = receipt_data;
+            asc_new(heap, &receipt_data, gas)?
         } else {
             AscPtr::null()
         };
@@ -620,6 +1355,49 @@ where
     }
 }
 
+impl<T, B> ToAscObj<AscEthereumEvent_0_0_11<T, B>>
+    for (EthereumEventData, Option<&EthereumTransactionReceipt>)
+where
+    T: AscType + AscIndexId,
+    B: AscType + AscIndexId,
+    EthereumTransactionData: ToAscObj<T>,
+    EthereumBlockData: ToAscObj<B>,
+{
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumEvent_0_0_11<T, B>, HostExportError> {
+        let (event_data, optional_receipt) = self;
+        let AscEthereumEvent {
+            address,
+            log_index,
+            transaction_log_index,
+            log_type,
+            block,
+            transaction,
+            params,
+        } = event_data.to_asc_obj(heap, gas)?;
+        let receipt = if let Some(receipt_data) = optional_receipt {
+            asc_new(heap, *receipt_data, gas)?
+        } else {
+            AscPtr::null()
+        };
+        Ok(AscEthereumEvent_0_0_11 {
+            address,
+            log_index,
+            transaction_log_index,
+            log_type,
+            block,
+            transaction,
+            params,
+            receipt,
+            topics: asc_new(heap, &event_data.topics, gas)?,
+            data: asc_new(heap, event_data.data.as_slice(), gas)?,
+        })
+    }
+}
+
 impl ToAscObj<AscEthereumLog> for Log {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -711,6 +1489,88 @@ impl ToAscObj<AscEthereumTransactionReceipt> for &TransactionReceipt {
     }
 }
 
+impl ToAscObj<AscEthereumTransactionReceipt_0_0_11> for EthereumTransactionReceipt {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumTransactionReceipt_0_0_11, HostExportError> {
+        let receipt: &TransactionReceipt = &self.inner;
+        let AscEthereumTransactionReceipt {
+            transaction_hash,
+            transaction_index,
+            block_hash,
+            block_number,
+            cumulative_gas_used,
+            gas_used,
+            contract_address,
+            logs,
+            status,
+            root,
+            logs_bloom,
+        } = receipt.to_asc_obj(heap, gas)?;
+        let l2 = &self.l2;
+        Ok(AscEthereumTransactionReceipt_0_0_11 {
+            transaction_hash,
+            transaction_index,
+            block_hash,
+            block_number,
+            cumulative_gas_used,
+            gas_used,
+            contract_address,
+            logs,
+            status,
+            root,
+            logs_bloom,
+            transaction_type: receipt
+                .transaction_type
+                .map(|transaction_type| asc_new(heap, &BigInt::from(transaction_type), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            effective_gas_price: receipt
+                .effective_gas_price
+                .map(|price| asc_new(heap, &BigInt::from_unsigned_u256(&price), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            l1_fee: l2
+                .l1_fee
+                .map(|fee| asc_new(heap, &BigInt::from_unsigned_u256(&fee), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            l1_gas_price: l2
+                .l1_gas_price
+                .map(|price| asc_new(heap, &BigInt::from_unsigned_u256(&price), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            l1_gas_used: l2
+                .l1_gas_used
+                .map(|gas_used| asc_new(heap, &BigInt::from_unsigned_u256(&gas_used), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            l1_fee_scalar: l2
+                .l1_fee_scalar
+                .as_ref()
+                .map(|scalar| asc_new(heap, scalar.as_str(), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            l1_base_fee_scalar: l2
+                .l1_base_fee_scalar
+                .map(|scalar| asc_new(heap, &BigInt::from_unsigned_u256(&scalar), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            l1_blob_base_fee: l2
+                .l1_blob_base_fee
+                .map(|fee| asc_new(heap, &BigInt::from_unsigned_u256(&fee), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            l1_blob_base_fee_scalar: l2
+                .l1_blob_base_fee_scalar
+                .map(|scalar| asc_new(heap, &BigInt::from_unsigned_u256(&scalar), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            gas_used_for_l1: l2
+                .gas_used_for_l1
+                .map(|gas_used| asc_new(heap, &BigInt::from_unsigned_u256(&gas_used), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+            l1_block_number: l2
+                .l1_block_number
+                .map(|number| asc_new(heap, &BigInt::from(number), gas))
+                .unwrap_or(Ok(AscPtr::null()))?,
+        })
+    }
+}
+
 impl ToAscObj<AscEthereumCall> for EthereumCallData {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -771,6 +1631,28 @@ impl ToAscObj<AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_6, AscEthereumBlo
     }
 }
 
+impl ToAscObj<AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_11, AscEthereumBlock_0_0_11>>
+    for EthereumCallData
+{
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<
+        AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_11, AscEthereumBlock_0_0_11>,
+        HostExportError,
+    > {
+        Ok(AscEthereumCall_0_0_3 {
+            to: asc_new(heap, &self.to, gas)?,
+            from: asc_new(heap, &self.from, gas)?,
+            block: asc_new(heap, &self.block, gas)?,
+            transaction: asc_new(heap, &self.transaction, gas)?,
+            inputs: asc_new(heap, &self.inputs, gas)?,
+            outputs: asc_new(heap, &self.outputs, gas)?,
+        })
+    }
+}
+
 impl ToAscObj<AscLogParam> for ethabi::LogParam {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -1,10 +1,14 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
 use std::{sync::Arc, time::Instant};
 
 use crate::adapter::EthereumRpcError;
+use crate::blobs::{BlobClient, BlockBlobs};
 use crate::data_source::MappingABI;
+use crate::proofs::AccountProof;
 use crate::{
-    capabilities::NodeCapabilities, network::EthereumNetworkAdapters, Chain, ContractCall,
-    ContractCallError, DataSource, EthereumAdapter, EthereumAdapterTrait, ENV_VARS,
+    capabilities::NodeCapabilities, network::EthereumNetworkAdapters, Chain, ChainCapabilities,
+    ContractCall, ContractCallError, DataSource, EthereumAdapter, EthereumAdapterTrait, ENV_VARS,
 };
 use anyhow::{anyhow, Context, Error};
 use blockchain::HostFn;
@@ -12,10 +16,11 @@ use graph::blockchain::ChainIdentifier;
 use graph::components::subgraph::HostMetrics;
 use graph::data::store::ethereum::call;
 use graph::data::store::scalar::BigInt;
-use graph::data::subgraph::API_VERSION_0_0_9;
+use graph::data::subgraph::{API_VERSION_0_0_11, API_VERSION_0_0_9};
+use graph::endpoint::RequestClass;
 use graph::futures03::compat::Future01CompatExt;
-use graph::prelude::web3::types::H160;
-use graph::runtime::gas::Gas;
+use graph::prelude::web3::types::{H160, H256};
+use graph::runtime::gas::{complexity, Gas, DEFAULT_GAS_OP};
 use graph::runtime::{AscIndexId, IndexForAscTypeId};
 use graph::slog::debug;
 use graph::{
@@ -29,10 +34,16 @@ use graph::{
     semver::Version,
     slog::Logger,
 };
-use graph_runtime_wasm::asc_abi::class::{AscBigInt, AscEnumArray, AscWrapped, EthereumValueKind};
+use graph_runtime_wasm::asc_abi::class::{
+    AscBigInt, AscEnumArray, AscWrapped, EthereumValueKind, Uint8Array,
+};
 use itertools::Itertools;
 
-use super::abi::{AscUnresolvedContractCall, AscUnresolvedContractCall_0_0_4};
+use super::abi::{
+    AscAccountProof, AscBlobTransactionArray, AscContractCallResultArray, AscDecodedEvent,
+    AscEventDecodeRequest, AscProofRequest, AscUnresolvedContractCall,
+    AscUnresolvedContractCallArray, AscUnresolvedContractCall_0_0_4,
+};
 
 /// Gas limit for `eth_call`. The value of 50_000_000 is a protocol-wide parameter so this
 /// should be changed only for debugging purposes and never on an indexer in the network. This
@@ -60,17 +71,55 @@ pub const ETH_GET_BALANCE: Gas = Gas::new(5_000_000_000);
 // TODO: Determine the appropriate gas cost for `ETH_HAS_CODE`, initially aligned with `ETHEREUM_CALL`.
 pub const ETH_HAS_CODE: Gas = Gas::new(5_000_000_000);
 
+// Getting the blob transactions of a block means fetching the block with all
+// its transactions, which costs about as much as an `eth_call`
+pub const ETH_GET_BLOB_TRANSACTIONS: Gas = Gas::new(5_000_000_000);
+
+// A blob is 128KiB that have to be fetched from the blob source and copied
+// into the mapping, which is more work than a typical `eth_call`
+pub const ETH_GET_BLOB: Gas = Gas::new(10_000_000_000);
+
+// Getting a proof costs about as much as an `eth_call`, and every storage
+// slot adds a proof that has to be verified and copied into the mapping
+pub const ETH_GET_PROOF: Gas = Gas::new(5_000_000_000);
+pub const ETH_GET_PROOF_SLOT: Gas = Gas::new(500_000_000);
+
 pub struct RuntimeAdapter {
     pub eth_adapters: Arc<EthereumNetworkAdapters>,
     pub call_cache: Arc<dyn EthereumCallCache>,
     pub chain_identifier: Arc<ChainIdentifier>,
+    /// Where `ethereum.getBlob` gets the contents of blobs from
+    pub blobs: Option<Arc<BlobClient>>,
+    pub capabilities: ChainCapabilities,
+}
+
+/// The blob transactions of the block that handlers ran in most recently,
+/// so that the handlers for one block fetch them only once
+#[derive(Default)]
+struct BlobBlockCache(Mutex<Option<Arc<BlockBlobs>>>);
+
+/// The verified proofs of the block that handlers ran in most recently, so
+/// that the handlers for one block fetch the proof for each account and
+/// storage slot only once
+#[derive(Default)]
+struct ProofBlockCache(Mutex<Option<BlockProofs>>);
+
+struct BlockProofs {
+    hash: H256,
+    state_root: H256,
+    accounts: HashMap<H160, AccountProof>,
 }
 
-pub fn eth_call_gas(chain_identifier: &ChainIdentifier) -> Option<u32> {
-    // Check if the current network version is in the eth_call_no_gas list
-    let should_skip_gas = ENV_VARS
-        .eth_call_no_gas
-        .contains(&chain_identifier.net_version);
+pub fn eth_call_gas(
+    chain_identifier: &ChainIdentifier,
+    capabilities: &ChainCapabilities,
+) -> Option<u32> {
+    // Check if the chain registry says so or if the current network version
+    // is in the eth_call_no_gas list
+    let should_skip_gas = capabilities.no_eth_call_gas
+        || ENV_VARS
+            .eth_call_no_gas
+            .contains(&chain_identifier.net_version);
 
     if should_skip_gas {
         None
@@ -85,7 +134,7 @@ impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
         let call_cache = self.call_cache.cheap_clone();
         let eth_adapters = self.eth_adapters.cheap_clone();
         let archive = ds.mapping.requires_archive()?;
-        let eth_call_gas = eth_call_gas(&self.chain_identifier);
+        let eth_call_gas = eth_call_gas(&self.chain_identifier, &self.capabilities);
 
         let ethereum_call = HostFn {
             name: "ethereum.call",
@@ -107,14 +156,39 @@ impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
             }),
         };
 
+        let abis = ds.mapping.abis.clone();
+        let call_cache = self.call_cache.cheap_clone();
         let eth_adapters = self.eth_adapters.cheap_clone();
-        let ethereum_get_balance = HostFn {
-            name: "ethereum.getBalance",
+        let ethereum_call_many = HostFn {
+            name: "ethereum.callMany",
             func: Arc::new(move |ctx, wasm_ptr| {
-                let eth_adapter = eth_adapters.unverified_cheapest_with(&NodeCapabilities {
+                let eth_adapter = eth_adapters.call_or_cheapest(Some(&NodeCapabilities {
                     archive,
                     traces: false,
-                })?;
+                }))?;
+                ethereum_call_many(
+                    &eth_adapter,
+                    call_cache.cheap_clone(),
+                    ctx,
+                    wasm_ptr,
+                    &abis,
+                    eth_call_gas,
+                )
+                .map(|ptr| ptr.wasm_ptr())
+            }),
+        };
+
+        let eth_adapters = self.eth_adapters.cheap_clone();
+        let ethereum_get_balance = HostFn {
+            name: "ethereum.getBalance",
+            func: Arc::new(move |ctx, wasm_ptr| {
+                let eth_adapter = eth_adapters.unverified_cheapest_with(
+                    &NodeCapabilities {
+                        archive,
+                        traces: false,
+                    },
+                    RequestClass::Calls,
+                )?;
                 eth_get_balance(&eth_adapter, ctx, wasm_ptr).map(|ptr| ptr.wasm_ptr())
             }),
         };
@@ -123,15 +197,87 @@ impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
         let ethereum_get_code = HostFn {
             name: "ethereum.hasCode",
             func: Arc::new(move |ctx, wasm_ptr| {
-                let eth_adapter = eth_adapters.unverified_cheapest_with(&NodeCapabilities {
-                    archive,
-                    traces: false,
-                })?;
+                let eth_adapter = eth_adapters.unverified_cheapest_with(
+                    &NodeCapabilities {
+                        archive,
+                        traces: false,
+                    },
+                    RequestClass::Calls,
+                )?;
                 eth_has_code(&eth_adapter, ctx, wasm_ptr).map(|ptr| ptr.wasm_ptr())
             }),
         };
 
-        Ok(vec![ethereum_call, ethereum_get_balance, ethereum_get_code])
+        let blob_cache = Arc::new(BlobBlockCache::default());
+
+        let eth_adapters = self.eth_adapters.cheap_clone();
+        let cache = blob_cache.cheap_clone();
+        let ethereum_get_blob_transactions = HostFn {
+            name: "ethereum.getBlobTransactions",
+            func: Arc::new(move |ctx, wasm_ptr| {
+                let eth_adapter = eth_adapters.unverified_cheapest_with(
+                    &NodeCapabilities {
+                        archive: false,
+                        traces: false,
+                    },
+                    RequestClass::Blocks,
+                )?;
+                eth_get_blob_transactions(&eth_adapter, &cache, ctx, wasm_ptr)
+                    .map(|ptr| ptr.wasm_ptr())
+            }),
+        };
+
+        let eth_adapters = self.eth_adapters.cheap_clone();
+        let blobs = self.blobs.clone();
+        let ethereum_get_blob = HostFn {
+            name: "ethereum.getBlob",
+            func: Arc::new(move |ctx, wasm_ptr| {
+                let eth_adapter = eth_adapters.unverified_cheapest_with(
+                    &NodeCapabilities {
+                        archive: false,
+                        traces: false,
+                    },
+                    RequestClass::Blocks,
+                )?;
+                eth_get_blob(&eth_adapter, blobs.as_deref(), &blob_cache, ctx, wasm_ptr)
+                    .map(|ptr| ptr.wasm_ptr())
+            }),
+        };
+
+        let eth_adapters = self.eth_adapters.cheap_clone();
+        let proof_cache = Arc::new(ProofBlockCache::default());
+        let ethereum_get_proof = HostFn {
+            name: "ethereum.getProof",
+            func: Arc::new(move |ctx, wasm_ptr| {
+                let eth_adapter = eth_adapters.unverified_cheapest_with(
+                    &NodeCapabilities {
+                        archive,
+                        traces: false,
+                    },
+                    RequestClass::Calls,
+                )?;
+                eth_get_proof(&eth_adapter, &proof_cache, ctx, wasm_ptr).map(|ptr| ptr.wasm_ptr())
+            }),
+        };
+
+        let abis = ds.mapping.abis.clone();
+        let ethereum_decode_event = HostFn {
+            name: "ethereum.decodeEvent",
+            func: Arc::new(move |ctx, wasm_ptr| {
+                eth_decode_event(&abis, ctx, wasm_ptr).map(|ptr| ptr.wasm_ptr())
+            }),
+        };
+
+        Ok(vec![
+            ethereum_call,
+            ethereum_call_many,
+            ethereum_get_balance,
+            ethereum_get_code,
+            ethereum_get_blob_transactions,
+            ethereum_get_blob,
+            ethereum_get_proof,
+            ethereum_decode_event,
+        ])
     }
 }
 
@@ -172,6 +318,41 @@ fn ethereum_call(
     }
 }
 
+/// function ethereum.callMany(calls: Array<SmartContractCall>): Array<Array<Token> | null>
+fn ethereum_call_many(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    ctx: HostFnCtx,
+    wasm_ptr: u32,
+    abis: &[Arc<MappingABI>],
+    eth_call_gas: Option<u32>,
+) -> Result<AscPtr<AscContractCallResultArray>, HostExportError> {
+    if ctx.heap.api_version() < API_VERSION_0_0_9 {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.callMany is not supported before API version 0.0.9"
+        )));
+    }
+
+    let calls: Vec<UnresolvedContractCall> =
+        asc_get::<_, AscUnresolvedContractCallArray, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+    // Each call costs as much as if it had been made with `ethereum.call`
+    let gas = Gas::new(ETHEREUM_CALL.value().saturating_mul(calls.len() as u64));
+    ctx.gas
+        .consume_host_fn_with_metrics(gas, "ethereum_call_many")?;
+
+    let results = eth_call_many(
+        eth_adapter,
+        call_cache,
+        &ctx.logger,
+        &ctx.block_ptr,
+        calls,
+        abis,
+        eth_call_gas,
+        ctx.metrics.cheap_clone(),
+    )?;
+    Ok(asc_new(ctx.heap, &results, &ctx.gas)?)
+}
+
 fn eth_get_balance(
     eth_adapter: &EthereumAdapter,
     ctx: HostFnCtx<'_>,
@@ -246,6 +427,284 @@ fn eth_has_code(
     }
 }
 
+/// The blob transactions of the block that the handler runs in
+fn block_blobs(
+    eth_adapter: &EthereumAdapter,
+    cache: &BlobBlockCache,
+    ctx: &HostFnCtx<'_>,
+) -> Result<Arc<BlockBlobs>, HostExportError> {
+    let block_hash = ctx.block_ptr.hash_as_h256();
+    if let Some(blobs) = cache.0.lock().unwrap().as_ref() {
+        if blobs.hash == block_hash {
+            return Ok(blobs.cheap_clone());
+        }
+    }
+
+    let blobs = graph::block_on(eth_adapter.block_blobs(&ctx.logger, block_hash))
+        .map_err(HostExportError::Unknown)?
+        .ok_or_else(|| {
+            HostExportError::PossibleReorg(anyhow!(
+                "block {} was not found on the Ethereum node",
+                ctx.block_ptr
+            ))
+        })?;
+    let blobs = Arc::new(blobs);
+    *cache.0.lock().unwrap() = Some(blobs.cheap_clone());
+    Ok(blobs)
+}
+
+/// function ethereum.getBlobTransactions(blockHash: Bytes): Array<BlobTransaction>
+fn eth_get_blob_transactions(
+    eth_adapter: &EthereumAdapter,
+    cache: &BlobBlockCache,
+    ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+) -> Result<AscPtr<AscBlobTransactionArray>, HostExportError> {
+    ctx.gas
+        .consume_host_fn_with_metrics(ETH_GET_BLOB_TRANSACTIONS, "eth_get_blob_transactions")?;
+
+    if ctx.heap.api_version() < API_VERSION_0_0_9 {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.getBlobTransactions is not supported before API version 0.0.9"
+        )));
+    }
+
+    // Only the current block is allowed since the blob transactions of other
+    // blocks might differ between the chains that different indexers follow
+    let block_hash: H256 = asc_get::<_, Uint8Array, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+    if block_hash != ctx.block_ptr.hash_as_h256() {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.getBlobTransactions can only be called for the current block {}, not {:?}",
+            ctx.block_ptr,
+            block_hash
+        )));
+    }
+
+    let blobs = block_blobs(eth_adapter, cache, &ctx)?;
+    Ok(asc_new(ctx.heap, &blobs.transactions, &ctx.gas)?)
+}
+
+/// function ethereum.getBlob(versionedHash: Bytes): Bytes | null
+fn eth_get_blob(
+    eth_adapter: &EthereumAdapter,
+    blob_client: Option<&BlobClient>,
+    cache: &BlobBlockCache,
+    ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+) -> Result<AscPtr<Uint8Array>, HostExportError> {
+    ctx.gas
+        .consume_host_fn_with_metrics(ETH_GET_BLOB, "eth_get_blob")?;
+
+    if ctx.heap.api_version() < API_VERSION_0_0_9 {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.getBlob is not supported before API version 0.0.9"
+        )));
+    }
+
+    let versioned_hash: H256 = asc_get::<_, Uint8Array, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+
+    // Whether the blob belongs to the current block is deterministic, but
+    // whether we can get its contents is not and must not fail the subgraph
+    let blobs = block_blobs(eth_adapter, cache, &ctx)?;
+    if !blobs.contains(&versioned_hash) {
+        return Ok(AscPtr::null());
+    }
+
+    let blob_client = blob_client.ok_or_else(|| {
+        HostExportError::Unknown(anyhow!(
+            "ethereum.getBlob needs a blob source for the chain in the configuration"
+        ))
+    })?;
+    let data = graph::block_on(blob_client.blob(&blobs, &versioned_hash))
+        .map_err(HostExportError::Unknown)?;
+    Ok(asc_new(ctx.heap, data.as_slice(), &ctx.gas)?)
+}
+
+/// function ethereum.getProof(request: ProofRequest): AccountProof
+///
+/// The proof is for the current block and has been verified against its
+/// state root, which makes the values in it as trustworthy as the block
+fn eth_get_proof(
+    eth_adapter: &EthereumAdapter,
+    cache: &ProofBlockCache,
+    ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+) -> Result<AscPtr<AscAccountProof>, HostExportError> {
+    ctx.gas
+        .consume_host_fn_with_metrics(ETH_GET_PROOF, "eth_get_proof")?;
+
+    if ctx.heap.api_version() < API_VERSION_0_0_11 {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.getProof is not supported before API version 0.0.11"
+        )));
+    }
+
+    let request: ProofRequest =
+        asc_get::<_, AscProofRequest, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+    ctx.gas
+        .consume_host_fn_with_metrics(ETH_GET_PROOF_SLOT * request.slots.len(), "eth_get_proof")?;
+
+    let block_hash = ctx.block_ptr.hash_as_h256();
+    let cached = cache
+        .0
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|proofs| proofs.hash == block_hash)
+        .map(|proofs| {
+            (
+                proofs.state_root,
+                proofs.accounts.get(&request.address).cloned(),
+            )
+        });
+    let (state_root, account) = match cached {
+        Some(cached) => cached,
+        None => {
+            let state_root = graph::block_on(eth_adapter.state_root(&ctx.logger, block_hash))
+                .map_err(HostExportError::PossibleReorg)?
+                .ok_or_else(|| {
+                    HostExportError::PossibleReorg(anyhow!(
+                        "block {} was not found on the Ethereum node",
+                        ctx.block_ptr
+                    ))
+                })?;
+            (state_root, None)
+        }
+    };
+
+    let missing = request
+        .slots
+        .iter()
+        .filter(|slot| match &account {
+            Some(account) => !account.storage_proof.iter().any(|p| &p.key == *slot),
+            None => true,
+        })
+        .cloned()
+        .unique()
+        .collect_vec();
+    let account = match account {
+        Some(account) if missing.is_empty() => account,
+        account => {
+            let mut proof = graph::block_on(eth_adapter.account_proof(
+                &ctx.logger,
+                request.address,
+                missing,
+                &ctx.block_ptr,
+            ))
+            .map_err(HostExportError::PossibleReorg)?;
+            // A proof that does not match the state root means that the
+            // Ethereum node is broken or that it is on a different chain
+            proof.verify(state_root).map_err(|e| {
+                HostExportError::Unknown(e.context(format!(
+                    "the Ethereum node returned an invalid proof for block {}",
+                    ctx.block_ptr
+                )))
+            })?;
+            if let Some(account) = account {
+                proof.storage_proof.extend(account.storage_proof);
+            }
+            proof
+        }
+    };
+
+    let mut cached = cache.0.lock().unwrap();
+    let mut proofs = match cached.take() {
+        Some(proofs) if proofs.hash == block_hash => proofs,
+        _ => BlockProofs {
+            hash: block_hash,
+            state_root,
+            accounts: HashMap::new(),
+        },
+    };
+    proofs.accounts.insert(request.address, account.clone());
+    *cached = Some(proofs);
+    drop(cached);
+
+    // Return the slots in the order in which the mapping asked for them
+    let storage_proof = request
+        .slots
+        .iter()
+        .map(|slot| {
+            account
+                .storage_proof
+                .iter()
+                .find(|p| &p.key == slot)
+                .cloned()
+                .expect("we have the proofs for all slots")
+        })
+        .collect();
+    let proof = AccountProof {
+        storage_proof,
+        ..account
+    };
+    Ok(asc_new(ctx.heap, &proof, &ctx.gas)?)
+}
+
+/// function ethereum.decodeEvent(request: EventDecodeRequest): DecodedEvent | null
+///
+/// Decode a log against the events of the ABIs of the data source with the
+/// names in the request, for logs that fallback event handlers receive
+fn eth_decode_event(
+    abis: &[Arc<MappingABI>],
+    ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+) -> Result<AscPtr<AscDecodedEvent>, HostExportError> {
+    if ctx.heap.api_version() < API_VERSION_0_0_11 {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.decodeEvent is not supported before API version 0.0.11"
+        )));
+    }
+
+    let request: EventDecodeRequest =
+        asc_get::<_, AscEventDecodeRequest, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+    // Every candidate ABI costs about as much as an `ethereum.decode` of the data
+    let gas = DEFAULT_GAS_OP.with_args(complexity::Size, &request.data) * request.abis.len();
+    ctx.gas
+        .consume_host_fn_with_metrics(gas, "eth_decode_event")?;
+
+    match decode_event(abis, &request).map_err(HostExportError::Deterministic)? {
+        Some(event) => Ok(asc_new(ctx.heap, &event, &ctx.gas)?),
+        None => Ok(AscPtr::null()),
+    }
+}
+
+/// Decode the log in `request` with the first event of the candidate ABIs
+/// that can decode it. Events with a matching signature are tried before
+/// anonymous events of the same ABI. Returns `Ok(None)` if no event can
+/// decode the log
+pub(crate) fn decode_event(
+    abis: &[Arc<MappingABI>],
+    request: &EventDecodeRequest,
+) -> Result<Option<DecodedEvent>, Error> {
+    for name in &request.abis {
+        let abi = abis
+            .iter()
+            .find(|abi| &abi.name == name)
+            .ok_or_else(|| anyhow!("No ABI entry with name `{}` found", name))?;
+
+        let events = abi.contract.events().collect_vec();
+        let candidates = events
+            .iter()
+            .filter(|event| !event.anonymous && request.topics.first() == Some(&event.signature()))
+            .chain(events.iter().filter(|event| event.anonymous));
+        for event in candidates {
+            let raw_log = ethabi::RawLog {
+                topics: request.topics.clone(),
+                data: request.data.clone(),
+            };
+            if let Ok(log) = event.parse_log(raw_log) {
+                let types = event.inputs.iter().map(|input| input.kind.to_string());
+                return Ok(Some(DecodedEvent {
+                    abi: abi.name.clone(),
+                    signature: format!("{}({})", event.name, types.format(",")),
+                    params: log.params,
+                }));
+            }
+        }
+    }
+    Ok(None)
+}
+
 /// Returns `Ok(None)` if the call was reverted.
 fn eth_call(
     eth_adapter: &EthereumAdapter,
@@ -272,6 +731,130 @@ fn eth_call(
 
     let start_time = Instant::now();
 
+    let call = resolve_call(&unresolved_call, abis, block_ptr, eth_call_gas)?;
+
+    // Run Ethereum call in tokio runtime
+    let logger1 = logger.clone();
+    let call_cache = call_cache.clone();
+    let (result, source) =
+        match graph::block_on(eth_adapter.contract_call(&logger1, &call, call_cache)) {
+            Ok((result, source)) => (Ok(result), source),
+            Err(e) => (Err(e), call::Source::Rpc),
+        };
+    let result = result.map_err(|e| {
+        contract_call_error(
+            &format!(
+                "function \"{}\" of contract \"{}\"",
+                unresolved_call.function_name, unresolved_call.contract_name
+            ),
+            e,
+        )
+    });
+
+    let elapsed = start_time.elapsed();
+
+    if source.observe() {
+        metrics.observe_eth_call_execution_time(
+            elapsed.as_secs_f64(),
+            &unresolved_call.contract_name,
+            &unresolved_call.function_name,
+        );
+    }
+
+    debug!(logger, "Contract call finished";
+              "address" => format!("0x{:x}", &unresolved_call.contract_address),
+              "contract" => &unresolved_call.contract_name,
+              "signature" => &unresolved_call.function_signature,
+              "args" => format!("[{}]", tokens_as_string(&unresolved_call.function_args)),
+              "time_ms" => format!("{}ms", elapsed.as_millis()),
+              "result" => result_as_string(&result),
+              "block_hash" => block_ptr.hash_hex(),
+              "block_number" => block_ptr.block_number(),
+              "source" => source.to_string());
+
+    result
+}
+
+/// Make all `unresolved_calls` and return their results in the same
+/// order. Identical calls are only made once, and at most
+/// `GRAPH_ETHEREUM_CALL_MANY_CONCURRENCY` calls are sent to the Ethereum
+/// node at the same time. Reverted calls have a result of `None`
+fn eth_call_many(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    logger: &Logger,
+    block_ptr: &BlockPtr,
+    unresolved_calls: Vec<UnresolvedContractCall>,
+    abis: &[Arc<MappingABI>],
+    eth_call_gas: Option<u32>,
+    metrics: Arc<HostMetrics>,
+) -> Result<Vec<Option<Vec<Token>>>, HostExportError> {
+    let start_time = Instant::now();
+
+    let calls = unresolved_calls
+        .iter()
+        .map(|call| resolve_call(call, abis, block_ptr, eth_call_gas))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // `positions[i]` is the index in `unique` of the call that produces
+    // the result for `calls[i]`. Calls whose input can not be encoded are
+    // left alone so that `contract_calls` reports the error
+    let mut unique: Vec<&ContractCall> = Vec::new();
+    let mut seen: HashMap<(Address, Vec<u8>), usize> = HashMap::new();
+    let mut positions = Vec::with_capacity(calls.len());
+    for call in &calls {
+        let pos = match call.function.encode_input(&call.args) {
+            Ok(input) => *seen.entry((call.address, input)).or_insert_with(|| {
+                unique.push(call);
+                unique.len() - 1
+            }),
+            Err(_) => {
+                unique.push(call);
+                unique.len() - 1
+            }
+        };
+        positions.push(pos);
+    }
+
+    let mut results = Vec::with_capacity(unique.len());
+    for chunk in unique.chunks(ENV_VARS.call_many_concurrency) {
+        let chunk_start = Instant::now();
+        let chunk_results =
+            graph::block_on(eth_adapter.contract_calls(logger, chunk, call_cache.cheap_clone()))
+                .map_err(|e| contract_call_error("a function with `ethereum.callMany`", e))?;
+        let elapsed = chunk_start.elapsed();
+        for (call, (_, source)) in chunk.iter().zip(&chunk_results) {
+            if source.observe() {
+                metrics.observe_eth_call_execution_time(
+                    elapsed.as_secs_f64(),
+                    &call.contract_name,
+                    &call.function.name,
+                );
+            }
+        }
+        results.extend(chunk_results.into_iter().map(|(result, _)| result));
+    }
+
+    debug!(logger, "Contract calls finished";
+              "calls" => calls.len(),
+              "unique_calls" => unique.len(),
+              "time_ms" => format!("{}ms", start_time.elapsed().as_millis()),
+              "block_hash" => block_ptr.hash_hex(),
+              "block_number" => block_ptr.block_number());
+
+    Ok(positions
+        .into_iter()
+        .map(|pos| results[pos].clone())
+        .collect())
+}
+
+/// Find the function for `unresolved_call` in the `abis` of the data source
+fn resolve_call(
+    unresolved_call: &UnresolvedContractCall,
+    abis: &[Arc<MappingABI>],
+    block_ptr: &BlockPtr,
+    eth_call_gas: Option<u32>,
+) -> Result<ContractCall, HostExportError> {
     // Obtain the path to the contract ABI
     let abi = abis
         .iter()
@@ -293,73 +876,62 @@ fn eth_call(
         )
         .map_err(HostExportError::Deterministic)?;
 
-    let call = ContractCall {
+    Ok(ContractCall {
         contract_name: unresolved_call.contract_name.clone(),
         address: unresolved_call.contract_address,
         block_ptr: block_ptr.cheap_clone(),
         function: function.clone(),
         args: unresolved_call.function_args.clone(),
         gas: eth_call_gas,
-    };
+    })
+}
 
-    // Run Ethereum call in tokio runtime
-    let logger1 = logger.clone();
-    let call_cache = call_cache.clone();
-    let (result, source) =
-        match graph::block_on(eth_adapter.contract_call(&logger1, &call, call_cache)) {
-            Ok((result, source)) => (Ok(result), source),
-            Err(e) => (Err(e), call::Source::Rpc),
-        };
-    let result = match result {
-            Ok(res) => Ok(res),
-
-            // Any error reported by the Ethereum node could be due to the block no longer being on
-            // the main chain. This is very unespecific but we don't want to risk failing a
-            // subgraph due to a transient error such as a reorg.
-            Err(ContractCallError::Web3Error(e)) => Err(HostExportError::PossibleReorg(anyhow::anyhow!(
-                "Ethereum node returned an error when calling function \"{}\" of contract \"{}\": {}",
-                unresolved_call.function_name,
-                unresolved_call.contract_name,
-                e
-            ))),
-
-            // Also retry on timeouts.
-            Err(ContractCallError::Timeout) => Err(HostExportError::PossibleReorg(anyhow::anyhow!(
-                "Ethereum node did not respond when calling function \"{}\" of contract \"{}\"",
-                unresolved_call.function_name,
-                unresolved_call.contract_name,
-            ))),
-
-            Err(e) => Err(HostExportError::Unknown(anyhow::anyhow!(
-                "Failed to call function \"{}\" of contract \"{}\": {}",
-                unresolved_call.function_name,
-                unresolved_call.contract_name,
-                e
-            ))),
-        };
+/// Turn the error from calling `what` into the error for the mapping
+fn contract_call_error(what: &str, e: ContractCallError) -> HostExportError {
+    match e {
+        // Any error reported by the Ethereum node could be due to the block no longer being on
+        // the main chain. This is very unespecific but we don't want to risk failing a
+        // subgraph due to a transient error such as a reorg.
+        ContractCallError::Web3Error(e) => HostExportError::PossibleReorg(anyhow::anyhow!(
+            "Ethereum node returned an error when calling {}: {}",
+            what,
+            e
+        )),
 
-    let elapsed = start_time.elapsed();
+        // Also retry on timeouts.
+        ContractCallError::Timeout => HostExportError::PossibleReorg(anyhow::anyhow!(
+            "Ethereum node did not respond when calling {}",
+            what
+        )),
 
-    if source.observe() {
-        metrics.observe_eth_call_execution_time(
-            elapsed.as_secs_f64(),
-            &unresolved_call.contract_name,
-            &unresolved_call.function_name,
-        );
+        e => HostExportError::Unknown(anyhow::anyhow!("Failed to call {}: {}", what, e)),
     }
+}
 
-    debug!(logger, "Contract call finished";
-              "address" => format!("0x{:x}", &unresolved_call.contract_address),
-              "contract" => &unresolved_call.contract_name,
-              "signature" => &unresolved_call.function_signature,
-              "args" => format!("[{}]", tokens_as_string(&unresolved_call.function_args)),
-              "time_ms" => format!("{}ms", elapsed.as_millis()),
-              "result" => result_as_string(&result),
-              "block_hash" => block_ptr.hash_hex(),
-              "block_number" => block_ptr.block_number(),
-              "source" => source.to_string());
+/// An account and the storage slots of it for `ethereum.getProof`
+#[derive(Clone, Debug)]
+pub struct ProofRequest {
+    pub address: Address,
+    pub slots: Vec<H256>,
+}
 
-    result
+/// A log and the names of the ABIs to decode it with for
+/// `ethereum.decodeEvent`
+#[derive(Clone, Debug)]
+pub struct EventDecodeRequest {
+    pub abis: Vec<String>,
+    pub topics: Vec<H256>,
+    pub data: Vec<u8>,
+}
+
+/// A log decoded by `ethereum.decodeEvent`
+#[derive(Clone, Debug)]
+pub struct DecodedEvent {
+    /// The name of the ABI with the event
+    pub abi: String,
+    /// The signature of the event, like `Transfer(address,address,uint256)`
+    pub signature: String,
+    pub params: Vec<ethabi::LogParam>,
 }
 
 #[derive(Clone, Debug)]
@@ -2,8 +2,10 @@ use std::sync::Arc;
 
 use graph::{
     blockchain::{block_stream::BlockWithTriggers, BlockPtr},
+    components::ethereum::EthereumTransactionReceipt,
     prelude::{
-        web3::types::{Address, Bytes, Log, H160, H256, U64},
+        serde_json::{self, json},
+        web3::types::{Address, Bytes, Log, H160, H256, U256, U64},
         EthereumCall, LightEthereumBlock,
     },
     slog::{self, o, Logger},
@@ -11,7 +13,10 @@ use graph::{
 
 use crate::{
     chain::BlockFinality,
-    trigger::{EthereumBlockTriggerType, EthereumTrigger, LogRef},
+    trigger::{
+        EthereumBlockData, EthereumBlockTriggerType, EthereumTransactionData, EthereumTrigger,
+        LogRef,
+    },
 };
 
 #[test]
@@ -208,3 +213,261 @@ fn test_trigger_dedup() {
         vec![log1, log2, call1, log3, call2, call3, block2, block1]
     );
 }
+
+#[test]
+fn block_with_post_merge_fields() {
+    let mut block = json!({
+        "hash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+        "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+        "miner": "0x0000000000000000000000000000000000000001",
+        "stateRoot": "0x0000000000000000000000000000000000000000000000000000000000000003",
+        "transactionsRoot": "0x0000000000000000000000000000000000000000000000000000000000000004",
+        "receiptsRoot": "0x0000000000000000000000000000000000000000000000000000000000000005",
+        "number": "0x2",
+        "gasUsed": "0x0",
+        "gasLimit": "0x1c9c380",
+        "baseFeePerGas": "0x7",
+        "extraData": "0x",
+        "timestamp": "0x65f6a2c0",
+        "difficulty": "0x0",
+        "totalDifficulty": "0xc70d815d562d3cfa955",
+        "uncles": [],
+        "transactions": [],
+        "size": "0x220",
+        "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000006",
+        "nonce": "0x0000000000000000",
+        "withdrawalsRoot": "0x0000000000000000000000000000000000000000000000000000000000000007",
+        "withdrawals": [{
+            "index": "0x2a",
+            "validatorIndex": "0x3e8",
+            "address": "0x0000000000000000000000000000000000000009",
+            "amount": "0xb7c4"
+        }],
+        "parentBeaconBlockRoot": "0x0000000000000000000000000000000000000000000000000000000000000008",
+        "blobGasUsed": "0x20000",
+        "excessBlobGas": "0x0"
+    });
+
+    let light: LightEthereumBlock = serde_json::from_value(block.clone()).unwrap();
+    assert_eq!(Some(U64::from(2)), light.number);
+    let data = EthereumBlockData::from(&light);
+    let withdrawals = data.withdrawals.unwrap();
+    assert_eq!(1, withdrawals.len());
+    assert_eq!(U64::from(42), withdrawals[0].index);
+    assert_eq!(U64::from(1000), withdrawals[0].validator_index);
+    assert_eq!(H160::from_low_u64_be(9), withdrawals[0].address);
+    assert_eq!(U64::from(47044), withdrawals[0].amount);
+    assert_eq!(
+        Some(H256::from_low_u64_be(8)),
+        data.parent_beacon_block_root
+    );
+    assert_eq!(Some(U64::from(0x20000)), data.blob_gas_used);
+    assert_eq!(Some(U64::zero()), data.excess_blob_gas);
+
+    // The fields survive a round trip through the chain store
+    let stored = serde_json::to_value(&light).unwrap();
+    assert_eq!(light, serde_json::from_value(stored).unwrap());
+
+    // Blocks from before Shanghai and Cancun, and blocks that were stored
+    // before we kept these fields, have none of them
+    for field in [
+        "withdrawalsRoot",
+        "withdrawals",
+        "parentBeaconBlockRoot",
+        "blobGasUsed",
+        "excessBlobGas",
+    ] {
+        block.as_object_mut().unwrap().remove(field);
+    }
+    let light: LightEthereumBlock = serde_json::from_value(block).unwrap();
+    let data = EthereumBlockData::from(&light);
+    assert_eq!(None, data.withdrawals);
+    assert_eq!(None, data.parent_beacon_block_root);
+    assert_eq!(None, data.blob_gas_used);
+    assert_eq!(None, data.excess_blob_gas);
+}
+
+#[test]
+fn l2_deposit_transactions_and_l1_fees() {
+    let deposit = json!({
+        "hash": "0x0000000000000000000000000000000000000000000000000000000000000011",
+        "nonce": "0x5",
+        "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+        "blockNumber": "0x2",
+        "transactionIndex": "0x0",
+        "from": "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001",
+        "to": "0x4200000000000000000000000000000000000015",
+        "value": "0x0",
+        "gasPrice": "0x0",
+        "gas": "0xf4240",
+        "input": "0x",
+        "type": "0x7e",
+        "v": "0x0",
+        "r": "0x0",
+        "s": "0x0",
+        "sourceHash": "0x0000000000000000000000000000000000000000000000000000000000000012",
+        "mint": "0x64",
+        "isSystemTx": false
+    });
+    let mut transfer = deposit.clone();
+    for field in ["sourceHash", "mint", "isSystemTx"] {
+        transfer.as_object_mut().unwrap().remove(field);
+    }
+    transfer["hash"] = json!("0x0000000000000000000000000000000000000000000000000000000000000013");
+    transfer["transactionIndex"] = json!("0x1");
+    transfer["type"] = json!("0x2");
+    let block = json!({
+        "hash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+        "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+        "miner": "0x0000000000000000000000000000000000000001",
+        "stateRoot": "0x0000000000000000000000000000000000000000000000000000000000000003",
+        "transactionsRoot": "0x0000000000000000000000000000000000000000000000000000000000000004",
+        "receiptsRoot": "0x0000000000000000000000000000000000000000000000000000000000000005",
+        "number": "0x2",
+        "gasUsed": "0x0",
+        "gasLimit": "0x1c9c380",
+        "extraData": "0x",
+        "timestamp": "0x65f6a2c0",
+        "difficulty": "0x0",
+        "uncles": [],
+        "transactions": [deposit, transfer],
+        "size": "0x220",
+        "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000006",
+        "nonce": "0x0000000000000000"
+    });
+
+    let light = LightEthereumBlock::from_rpc(block).unwrap().unwrap();
+    assert_eq!(1, light.l2_transactions.len());
+    let data = EthereumTransactionData::new(&light.transactions[0], &light);
+    assert_eq!(Some(U64::from(0x7e)), data.transaction_type);
+    assert_eq!(Some(H256::from_low_u64_be(0x12)), data.l2.source_hash);
+    assert_eq!(Some(U256::from(100)), data.l2.mint);
+    assert_eq!(Some(false), data.l2.is_system_tx);
+    let data = EthereumTransactionData::new(&light.transactions[1], &light);
+    assert_eq!(Some(U64::from(2)), data.transaction_type);
+    assert!(data.l2.is_empty());
+
+    // The fields survive a round trip through the chain store
+    let stored = serde_json::to_value(&light).unwrap();
+    assert_eq!(light, serde_json::from_value(stored).unwrap());
+    assert_eq!(None, LightEthereumBlock::from_rpc(json!(null)).unwrap());
+
+    let receipt: EthereumTransactionReceipt = serde_json::from_value(json!({
+        "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000013",
+        "transactionIndex": "0x1",
+        "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+        "blockNumber": "0x2",
+        "from": "0x0000000000000000000000000000000000000001",
+        "to": "0x0000000000000000000000000000000000000002",
+        "cumulativeGasUsed": "0xa410",
+        "gasUsed": "0x5208",
+        "contractAddress": null,
+        "logs": [],
+        "logsBloom": format!("0x{}", "0".repeat(512)),
+        "status": "0x1",
+        "type": "0x2",
+        "effectiveGasPrice": "0x3b9aca00",
+        "l1Fee": "0x2386f26fc10000",
+        "l1GasPrice": "0x4a817c800",
+        "l1GasUsed": "0x640",
+        "l1FeeScalar": "0.684"
+    }))
+    .unwrap();
+    assert_eq!(Some(U64::from(1)), receipt.status);
+    assert_eq!(
+        Some(U256::from(10_000_000_000_000_000u64)),
+        receipt.l2.l1_fee
+    );
+    assert_eq!(Some(U256::from(1600)), receipt.l2.l1_gas_used);
+    assert_eq!(Some("0.684".to_string()), receipt.l2.l1_fee_scalar);
+    assert_eq!(None, receipt.l2.gas_used_for_l1);
+    let stored = serde_json::to_value(&receipt).unwrap();
+    assert_eq!(receipt, serde_json::from_value(stored).unwrap());
+}
+
+#[test]
+fn decode_event_with_candidate_abis() {
+    use graph::prelude::ethabi::{self, Contract, Token};
+
+    use crate::data_source::MappingABI;
+    use crate::runtime::runtime_adapter::{decode_event, EventDecodeRequest};
+
+    let abi = |name: &str, abi: serde_json::Value| {
+        Arc::new(MappingABI {
+            name: name.to_string(),
+            contract: Contract::load(abi.to_string().as_bytes()).unwrap(),
+        })
+    };
+    let abis = vec![
+        abi(
+            "Other",
+            json!([{
+                "type": "event",
+                "name": "Approval",
+                "anonymous": false,
+                "inputs": [{ "name": "owner", "type": "address", "indexed": true }]
+            }]),
+        ),
+        abi(
+            "ERC20",
+            json!([
+                {
+                    "type": "event",
+                    "name": "Transfer",
+                    "anonymous": false,
+                    "inputs": [
+                        { "name": "from", "type": "address", "indexed": true },
+                        { "name": "to", "type": "address", "indexed": true },
+                        { "name": "value", "type": "uint256", "indexed": false }
+                    ]
+                },
+                {
+                    "type": "event",
+                    "name": "Skim",
+                    "anonymous": true,
+                    "inputs": [{ "name": "amount", "type": "uint256", "indexed": false }]
+                }
+            ]),
+        ),
+    ];
+
+    let request = |abis: &[&str], topics: Vec<H256>, data: Vec<u8>| EventDecodeRequest {
+        abis: abis.iter().map(|abi| abi.to_string()).collect(),
+        topics,
+        data,
+    };
+    let transfer = abis[1].contract.event("Transfer").unwrap().signature();
+    let from = H256::from(H160::from_low_u64_be(1));
+    let to = H256::from(H160::from_low_u64_be(2));
+    let value = ethabi::encode(&[Token::Uint(U256::from(7))]);
+
+    // The first ABI has no matching event, the second one has
+    let event = decode_event(
+        &abis,
+        &request(&["Other", "ERC20"], vec![transfer, from, to], value.clone()),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!("ERC20", event.abi);
+    assert_eq!("Transfer(address,address,uint256)", event.signature);
+    assert_eq!(3, event.params.len());
+    assert_eq!(Token::Uint(U256::from(7)), event.params[2].value);
+
+    // A log without topics can only be an anonymous event
+    let event = decode_event(&abis, &request(&["ERC20"], vec![], value.clone()))
+        .unwrap()
+        .unwrap();
+    assert_eq!("Skim(uint256)", event.signature);
+
+    // No candidate can decode the log
+    assert!(
+        decode_event(&abis, &request(&["Other"], vec![transfer], value.clone()))
+            .unwrap()
+            .is_none()
+    );
+
+    // ABIs that the data source does not have are an error
+    assert!(decode_event(&abis, &request(&["Missing"], vec![], value)).is_err());
+}
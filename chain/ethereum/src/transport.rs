@@ -1,5 +1,5 @@
 use graph::components::adapter::ProviderName;
-use graph::endpoint::{EndpointMetrics, RequestLabels};
+use graph::endpoint::{EndpointMetrics, RequestLabels, RequestOutcome};
 use jsonrpc_core::types::Call;
 use jsonrpc_core::Value;
 
@@ -9,6 +9,7 @@ use web3::RequestId;
 use graph::prelude::*;
 use graph::url::Url;
 use std::future::Future;
+use std::time::Instant;
 
 /// Abstraction over the different web3 transports.
 #[derive(Clone, Debug)]
@@ -99,11 +100,9 @@ impl web3::Transport for Transport {
                     conn_type: graph::endpoint::ConnectionType::Rpc,
                 };
                 let out = async move {
+                    let start = Instant::now();
                     let out = client.send(id, request).await;
-                    match out {
-                        Ok(_) => metrics.success(&labels),
-                        Err(_) => metrics.failure(&labels),
-                    }
+                    metrics.record(&labels, Some(start.elapsed()), outcome(&out));
 
                     out
                 };
@@ -116,6 +115,32 @@ impl web3::Transport for Transport {
     }
 }
 
+/// Tell rate limiting and missing state apart from other errors since
+/// they say something about the provider rather than the request
+fn outcome(out: &Result<Value, web3::error::Error>) -> RequestOutcome {
+    // The code that Infura, Alchemy and others use for rate limiting
+    const LIMIT_EXCEEDED: i64 = -32005;
+
+    let error = match out {
+        Ok(_) => return RequestOutcome::Success,
+        Err(web3::error::Error::Rpc(e)) if e.code.code() == LIMIT_EXCEEDED => {
+            return RequestOutcome::RateLimited
+        }
+        Err(e) => e.to_string().to_lowercase(),
+    };
+    if error.contains("429") || error.contains("rate limit") || error.contains("too many requests")
+    {
+        RequestOutcome::RateLimited
+    } else if error.contains("missing trie node")
+        || error.contains("historical state")
+        || error.contains("state is not available")
+    {
+        RequestOutcome::MissingState
+    } else {
+        RequestOutcome::Failure
+    }
+}
+
 impl web3::BatchTransport for Transport {
     type Batch = Box<
         dyn Future<Output = Result<Vec<Result<Value, web3::error::Error>>, web3::error::Error>>
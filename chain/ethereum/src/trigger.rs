@@ -1,5 +1,7 @@
 use graph::blockchain::MappingTriggerTrait;
 use graph::blockchain::TriggerData;
+use graph::components::ethereum::{EthereumTransactionReceipt, L2TransactionFields, Withdrawal};
+use graph::data::subgraph::API_VERSION_0_0_11;
 use graph::data::subgraph::API_VERSION_0_0_2;
 use graph::data::subgraph::API_VERSION_0_0_6;
 use graph::data::subgraph::API_VERSION_0_0_7;
@@ -11,13 +13,12 @@ use graph::prelude::ethabi::ethereum_types::U64;
 use graph::prelude::ethabi::Address;
 use graph::prelude::ethabi::Bytes;
 use graph::prelude::ethabi::LogParam;
-use graph::prelude::web3::types::Block;
+use graph::prelude::web3::types::AccessList;
 use graph::prelude::web3::types::Log;
 use graph::prelude::web3::types::Transaction;
-use graph::prelude::web3::types::TransactionReceipt;
 use graph::prelude::BlockNumber;
 use graph::prelude::BlockPtr;
-use graph::prelude::{CheapClone, EthereumCall};
+use graph::prelude::{CheapClone, EthereumCall, LightEthereumBlock};
 use graph::runtime::asc_new;
 use graph::runtime::gas::GasCounter;
 use graph::runtime::AscHeap;
@@ -30,25 +31,25 @@ use std::{cmp::Ordering, sync::Arc};
 
 use crate::data_source::DeclaredCall;
 use crate::runtime::abi::AscEthereumBlock;
+use crate::runtime::abi::AscEthereumBlock_0_0_11;
 use crate::runtime::abi::AscEthereumBlock_0_0_6;
 use crate::runtime::abi::AscEthereumCall;
 use crate::runtime::abi::AscEthereumCall_0_0_3;
 use crate::runtime::abi::AscEthereumEvent;
+use crate::runtime::abi::AscEthereumEvent_0_0_11;
 use crate::runtime::abi::AscEthereumEvent_0_0_7;
 use crate::runtime::abi::AscEthereumTransaction_0_0_1;
+use crate::runtime::abi::AscEthereumTransaction_0_0_11;
 use crate::runtime::abi::AscEthereumTransaction_0_0_2;
 use crate::runtime::abi::AscEthereumTransaction_0_0_6;
 
-// ETHDEP: This should be defined in only one place.
-type LightEthereumBlock = Block<Transaction>;
-
 pub enum MappingTrigger {
     Log {
         block: Arc<LightEthereumBlock>,
         transaction: Arc<Transaction>,
         log: Arc<Log>,
         params: Vec<LogParam>,
-        receipt: Option<Arc<TransactionReceipt>>,
+        receipt: Option<Arc<EthereumTransactionReceipt>>,
         calls: Vec<DeclaredCall>,
     },
     Call {
@@ -147,14 +148,26 @@ impl ToAscPtr for MappingTrigger {
                 let api_version = heap.api_version();
                 let ethereum_event_data = EthereumEventData {
                     block: EthereumBlockData::from(block.as_ref()),
-                    transaction: EthereumTransactionData::from(transaction.deref()),
+                    transaction: EthereumTransactionData::new(transaction.deref(), &block),
                     address: log.address,
                     log_index: log.log_index.unwrap_or(U256::zero()),
                     transaction_log_index: log.log_index.unwrap_or(U256::zero()),
                     log_type: log.log_type.clone(),
                     params,
+                    topics: log.topics.clone(),
+                    data: log.data.0.clone(),
                 };
-                if api_version >= API_VERSION_0_0_7 {
+                if api_version >= API_VERSION_0_0_11 {
+                    asc_new::<
+                        AscEthereumEvent_0_0_11<
+                            AscEthereumTransaction_0_0_11,
+                            AscEthereumBlock_0_0_11,
+                        >,
+                        _,
+                        _,
+                    >(heap, &(ethereum_event_data, receipt.as_deref()), gas)?
+                    .erase()
+                } else if api_version >= API_VERSION_0_0_7 {
                     asc_new::<
                         AscEthereumEvent_0_0_7<
                             AscEthereumTransaction_0_0_6,
@@ -198,11 +211,21 @@ impl ToAscPtr for MappingTrigger {
                     to: call.to,
                     from: call.from,
                     block: EthereumBlockData::from(block.as_ref()),
-                    transaction: EthereumTransactionData::from(transaction.deref()),
+                    transaction: EthereumTransactionData::new(transaction.deref(), &block),
                     inputs,
                     outputs,
                 };
-                if heap.api_version() >= Version::new(0, 0, 6) {
+                if heap.api_version() >= API_VERSION_0_0_11 {
+                    asc_new::<
+                        AscEthereumCall_0_0_3<
+                            AscEthereumTransaction_0_0_11,
+                            AscEthereumBlock_0_0_11,
+                        >,
+                        _,
+                        _,
+                    >(heap, &call, gas)?
+                    .erase()
+                } else if heap.api_version() >= Version::new(0, 0, 6) {
                     asc_new::<
                         AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_6, AscEthereumBlock_0_0_6>,
                         _,
@@ -222,7 +245,9 @@ impl ToAscPtr for MappingTrigger {
             }
             MappingTrigger::Block { block } => {
                 let block = EthereumBlockData::from(block.as_ref());
-                if heap.api_version() >= Version::new(0, 0, 6) {
+                if heap.api_version() >= API_VERSION_0_0_11 {
+                    asc_new::<AscEthereumBlock_0_0_11, _, _>(heap, &block, gas)?.erase()
+                } else if heap.api_version() >= Version::new(0, 0, 6) {
                     asc_new::<AscEthereumBlock_0_0_6, _, _>(heap, &block, gas)?.erase()
                 } else {
                     asc_new::<AscEthereumBlock, _, _>(heap, &block, gas)?.erase()
@@ -234,8 +259,8 @@ impl ToAscPtr for MappingTrigger {
 
 #[derive(Clone, Debug)]
 pub enum LogRef {
-    FullLog(Arc<Log>, Option<Arc<TransactionReceipt>>),
-    LogPosition(usize, Arc<TransactionReceipt>),
+    FullLog(Arc<Log>, Option<Arc<EthereumTransactionReceipt>>),
+    LogPosition(usize, Arc<EthereumTransactionReceipt>),
 }
 
 impl LogRef {
@@ -246,7 +271,7 @@ impl LogRef {
         }
     }
 
-    pub fn receipt(&self) -> Option<&Arc<TransactionReceipt>> {
+    pub fn receipt(&self) -> Option<&Arc<EthereumTransactionReceipt>> {
         match self {
             LogRef::FullLog(_, receipt) => receipt.as_ref(),
             LogRef::LogPosition(_, receipt) => Some(receipt),
@@ -437,10 +462,14 @@ pub struct EthereumBlockData {
     pub total_difficulty: U256,
     pub size: Option<U256>,
     pub base_fee_per_gas: Option<U256>,
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    pub parent_beacon_block_root: Option<H256>,
+    pub blob_gas_used: Option<U64>,
+    pub excess_blob_gas: Option<U64>,
 }
 
-impl<'a, T> From<&'a Block<T>> for EthereumBlockData {
-    fn from(block: &'a Block<T>) -> EthereumBlockData {
+impl<'a> From<&'a LightEthereumBlock> for EthereumBlockData {
+    fn from(block: &'a LightEthereumBlock) -> EthereumBlockData {
         EthereumBlockData {
             hash: block.hash.unwrap(),
             parent_hash: block.parent_hash,
@@ -457,6 +486,10 @@ impl<'a, T> From<&'a Block<T>> for EthereumBlockData {
             total_difficulty: block.total_difficulty.unwrap_or_default(),
             size: block.size,
             base_fee_per_gas: block.base_fee_per_gas,
+            withdrawals: block.post_merge.withdrawals.clone(),
+            parent_beacon_block_root: block.post_merge.parent_beacon_block_root,
+            blob_gas_used: block.post_merge.blob_gas_used,
+            excess_blob_gas: block.post_merge.excess_blob_gas,
         }
     }
 }
@@ -473,6 +506,26 @@ pub struct EthereumTransactionData {
     pub gas_price: U256,
     pub input: Bytes,
     pub nonce: U256,
+    pub transaction_type: Option<U64>,
+    /// The addresses and storage keys that the transaction declared it
+    /// would access, for transactions of type 1 and later
+    pub access_list: Option<AccessList>,
+    pub l2: L2TransactionFields,
+}
+
+impl EthereumTransactionData {
+    /// The data of `tx` with the fields that L2s add to it, which only
+    /// `block` has
+    pub fn new(tx: &Transaction, block: &LightEthereumBlock) -> Self {
+        EthereumTransactionData {
+            l2: block
+                .l2_transactions
+                .get(&tx.hash)
+                .cloned()
+                .unwrap_or_default(),
+            ..EthereumTransactionData::from(tx)
+        }
+    }
 }
 
 impl From<&'_ Transaction> for EthereumTransactionData {
@@ -490,6 +543,9 @@ impl From<&'_ Transaction> for EthereumTransactionData {
             gas_price: tx.gas_price.unwrap_or(U256::zero()), // EIP-1559 made this optional.
             input: tx.input.0.clone(),
             nonce: tx.nonce,
+            transaction_type: tx.transaction_type,
+            access_list: tx.access_list.clone(),
+            l2: L2TransactionFields::default(),
         }
     }
 }
@@ -504,6 +560,8 @@ pub struct EthereumEventData {
     pub block: EthereumBlockData,
     pub transaction: EthereumTransactionData,
     pub params: Vec<LogParam>,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
 }
 
 /// An Ethereum call executed within a transaction within a block to a contract address.
@@ -16,6 +16,7 @@ const BASIC_RECEIPT_FILTER_TYPE_URL: &str =
 pub struct TriggerFilter {
     pub(crate) block_filter: NearBlockFilter,
     pub(crate) receipt_filter: NearReceiptFilter,
+    pub(crate) function_call_filter: NearFunctionCallFilter,
 }
 
 impl TriggerFilter {
@@ -48,10 +49,12 @@ impl bc::TriggerFilter<Chain> for TriggerFilter {
         let TriggerFilter {
             block_filter,
             receipt_filter,
+            function_call_filter,
         } = self;
 
         block_filter.extend(NearBlockFilter::from_data_sources(data_sources.clone()));
-        receipt_filter.extend(NearReceiptFilter::from_data_sources(data_sources));
+        receipt_filter.extend(NearReceiptFilter::from_data_sources(data_sources.clone()));
+        function_call_filter.extend(NearFunctionCallFilter::from_data_sources(data_sources));
     }
 
     fn node_capabilities(&self) -> bc::EmptyNodeCapabilities<Chain> {
@@ -68,6 +71,8 @@ impl bc::TriggerFilter<Chain> for TriggerFilter {
         let TriggerFilter {
             block_filter: block,
             receipt_filter: receipt,
+            // Function calls are filtered by the accounts of the receipt filter
+            function_call_filter: _,
         } = self;
 
         if block.trigger_every_block {
@@ -100,6 +105,7 @@ impl bc::TriggerFilter<Chain> for TriggerFilter {
 pub(crate) type Account = String;
 
 /// NearReceiptFilter requires the account to be set, it will match every receipt where `source.account` is the recipient.
+/// It covers data sources with receipt handlers as well as data sources with function call handlers.
 /// see docs: https://thegraph.com/docs/en/supported-networks/near/
 #[derive(Clone, Debug, Default)]
 pub(crate) struct NearReceiptFilter {
@@ -148,7 +154,8 @@ impl NearReceiptFilter {
             .into_iter()
             .filter(|data_source| {
                 (data_source.source.account.is_some() || data_source.source.accounts.is_some())
-                    && !data_source.mapping.receipt_handlers.is_empty()
+                    && (!data_source.mapping.receipt_handlers.is_empty()
+                        || !data_source.mapping.function_call_handlers.is_empty())
             })
             .map(|ds| Source {
                 account: ds.source.account.clone(),
@@ -225,6 +232,33 @@ impl NearReceiptFilter {
     }
 }
 
+/// NearFunctionCallFilter matches the function calls of the methods that data sources have
+/// function call handlers for. The receipts of the calls still have to match the receipt filter.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NearFunctionCallFilter {
+    pub methods: HashSet<String>,
+}
+
+impl NearFunctionCallFilter {
+    pub fn matches(&self, method: &str) -> bool {
+        self.methods.contains(method)
+    }
+
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            methods: iter
+                .into_iter()
+                .flat_map(|data_source| data_source.mapping.function_call_handlers.iter())
+                .map(|handler| handler.method.clone())
+                .collect(),
+        }
+    }
+
+    pub fn extend(&mut self, other: NearFunctionCallFilter) {
+        self.methods.extend(other.methods);
+    }
+}
+
 /// NearBlockFilter will match every block regardless of source being set.
 /// see docs: https://thegraph.com/docs/en/supported-networks/near/
 #[derive(Clone, Debug, Default)]
@@ -250,7 +284,7 @@ impl NearBlockFilter {
 mod test {
     use std::collections::HashSet;
 
-    use super::NearBlockFilter;
+    use super::{NearBlockFilter, NearFunctionCallFilter};
     use crate::adapter::{NearReceiptFilter, TriggerFilter, BASIC_RECEIPT_FILTER_TYPE_URL};
     use graph::{
         blockchain::TriggerFilter as _,
@@ -270,6 +304,7 @@ mod test {
                 accounts: HashSet::new(),
                 partial_accounts: HashSet::new(),
             },
+            function_call_filter: NearFunctionCallFilter::default(),
         };
         assert_eq!(filter.to_module_params(), "0,0\n\n");
         assert_eq!(filter.to_firehose_filter(), vec![]);
@@ -285,6 +320,7 @@ mod test {
                 accounts: HashSet::from_iter(vec!["acc1".into(), "acc2".into(), "acc3".into()]),
                 partial_accounts: HashSet::new(),
             },
+            function_call_filter: NearFunctionCallFilter::default(),
         };
 
         let filter = filter.to_firehose_filter();
@@ -301,6 +337,7 @@ mod test {
                 accounts: HashSet::from_iter(vec!["acc1".into(), "acc2".into(), "acc3".into()]),
                 partial_accounts: HashSet::new(),
             },
+            function_call_filter: NearFunctionCallFilter::default(),
         };
 
         let filter = filter.to_firehose_filter();
@@ -332,6 +369,7 @@ mod test {
                     (Some("acc3".into()), Some("acc4".into())),
                 ]),
             },
+            function_call_filter: NearFunctionCallFilter::default(),
         };
 
         let filter = filter.to_firehose_filter();
@@ -466,6 +504,7 @@ mod test {
             let tf = TriggerFilter {
                 block_filter: NearBlockFilter::default(),
                 receipt_filter: case.input,
+                function_call_filter: NearFunctionCallFilter::default(),
             };
             let param = tf.to_module_params();
             let filter = NearFilter::try_from(param.as_str()).expect(&format!(
@@ -3,8 +3,8 @@ use graph::blockchain::client::ChainClient;
 use graph::blockchain::firehose_block_ingestor::FirehoseBlockIngestor;
 use graph::blockchain::substreams_block_stream::SubstreamsBlockStream;
 use graph::blockchain::{
-    BasicBlockchainBuilder, BlockIngestor, BlockchainBuilder, BlockchainKind, NoopDecoderHook,
-    NoopRuntimeAdapter,
+    BasicBlockchainBuilder, BlockIngestor, BlockchainBuilder, BlockchainKind, IngestionMode,
+    NoopDecoderHook, NoopRuntimeAdapter,
 };
 use graph::cheap_clone::CheapClone;
 use graph::components::adapter::ChainId;
@@ -34,7 +34,7 @@ use graph::{
 use prost::Message;
 use std::sync::Arc;
 
-use crate::adapter::TriggerFilter;
+use crate::adapter::{NearFunctionCallFilter, TriggerFilter};
 use crate::codec::substreams_triggers::BlockAndReceipts;
 use crate::data_source::{DataSourceTemplate, UnresolvedDataSourceTemplate};
 use crate::trigger::{self, NearTrigger};
@@ -232,6 +232,7 @@ impl Blockchain for Chain {
         start_blocks: Vec<BlockNumber>,
         filter: Arc<Self::TriggerFilter>,
         unified_api_version: UnifiedMappingApiVersion,
+        _ingestion: IngestionMode,
     ) -> Result<Box<dyn BlockStream<Self>>, Error> {
         if self.prefer_substreams {
             return self
@@ -334,6 +335,7 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
         let TriggerFilter {
             block_filter,
             receipt_filter,
+            function_call_filter,
         } = filter;
 
         // Filter non-successful or non-action receipts.
@@ -374,7 +376,7 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
         });
 
         let mut trigger_data: Vec<_> = receipts
-            .map(|r| NearTrigger::Receipt(Arc::new(r)))
+            .flat_map(|r| receipt_triggers(function_call_filter, r))
             .collect();
 
         if block_filter.trigger_every_block {
@@ -461,12 +463,15 @@ impl BlockStreamMapper<Chain> for FirehoseMapper {
         let trigger_data = outcome
             .into_iter()
             .zip(receipt.into_iter())
-            .map(|(outcome, receipt)| {
-                NearTrigger::Receipt(Arc::new(trigger::ReceiptWithOutcome {
-                    outcome,
-                    receipt,
-                    block: arc_block.clone(),
-                }))
+            .flat_map(|(outcome, receipt)| {
+                receipt_triggers(
+                    &self.filter.function_call_filter,
+                    trigger::ReceiptWithOutcome {
+                        outcome,
+                        receipt,
+                        block: arc_block.clone(),
+                    },
+                )
             })
             .collect();
 
@@ -480,6 +485,31 @@ impl BlockStreamMapper<Chain> for FirehoseMapper {
     }
 }
 
+/// The triggers for a receipt: the receipt itself, followed by those of its
+/// function calls that match the function call filter.
+fn receipt_triggers(
+    function_call_filter: &NearFunctionCallFilter,
+    receipt: trigger::ReceiptWithOutcome,
+) -> Vec<NearTrigger> {
+    let receipt = Arc::new(receipt);
+
+    let mut triggers = vec![NearTrigger::Receipt(receipt.cheap_clone())];
+    triggers.extend(
+        receipt
+            .receipt
+            .function_calls()
+            .filter(|(_, function_call)| function_call_filter.matches(&function_call.method_name))
+            .map(|(action_index, function_call)| {
+                NearTrigger::FunctionCall(Arc::new(trigger::FunctionCallWithOutcome {
+                    function_call: function_call.clone(),
+                    action_index,
+                    receipt: receipt.cheap_clone(),
+                }))
+            }),
+    );
+    triggers
+}
+
 #[async_trait]
 impl FirehoseMapperTrait<Chain> for FirehoseMapper {
     fn trigger_filter(&self) -> &TriggerFilter {
@@ -579,13 +609,15 @@ mod test {
     };
 
     use crate::{
-        adapter::{NearReceiptFilter, TriggerFilter},
+        adapter::{NearFunctionCallFilter, NearReceiptFilter, TriggerFilter},
         codec::{
             self, execution_outcome, receipt, Block, BlockHeader, DataReceiver, ExecutionOutcome,
             ExecutionOutcomeWithId, IndexerExecutionOutcomeWithReceipt, IndexerShard,
             ReceiptAction, SuccessValueExecutionStatus,
         },
-        data_source::{DataSource, Mapping, PartialAccounts, ReceiptHandler, NEAR_KIND},
+        data_source::{
+            DataSource, FunctionCallHandler, Mapping, PartialAccounts, ReceiptHandler, NEAR_KIND,
+        },
         trigger::{NearTrigger, ReceiptWithOutcome},
         Chain,
     };
@@ -968,6 +1000,80 @@ mod test {
         assert_eq!(height.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_trigger_filter_function_calls() {
+        let account1: String = "account1".into();
+
+        let adapter = TriggersAdapter {};
+
+        let logger = Logger::root(slog::Discard, o!());
+        let mut block1 = new_success_block(11, &account1);
+        let receipt = block1.shards[0].receipt_execution_outcomes[0]
+            .receipt
+            .as_mut()
+            .unwrap();
+        if let Some(receipt::Receipt::Action(action)) = receipt.receipt.as_mut() {
+            action.actions = vec![
+                function_call_action("ft_transfer", r#"{"receiver_id":"bob.near"}"#),
+                function_call_action("ft_transfer", r#"{"receiver_id":"alice.near"}"#),
+                function_call_action("storage_deposit", "{}"),
+            ];
+        }
+
+        let filter = TriggerFilter {
+            receipt_filter: NearReceiptFilter {
+                accounts: HashSet::from_iter(vec![account1.clone()]),
+                partial_accounts: HashSet::new(),
+            },
+            function_call_filter: NearFunctionCallFilter {
+                methods: HashSet::from_iter(vec!["ft_transfer".to_string()]),
+            },
+            ..Default::default()
+        };
+
+        let block_with_triggers: BlockWithTriggers<Chain> = adapter
+            .triggers_in_block(&logger, block1, &filter)
+            .await
+            .expect("failed to execute triggers_in_block");
+        // The receipt and its two `ft_transfer` calls
+        assert_eq!(block_with_triggers.trigger_count(), 3);
+
+        let mut ds = new_data_source(Some(account1), None);
+        ds.mapping.receipt_handlers = vec![];
+        ds.mapping.function_call_handlers = vec![FunctionCallHandler {
+            handler: "handleTransfer".to_string(),
+            method: "ft_transfer".to_string(),
+            args_prefixes: vec![r#"{"receiver_id":"bob."#.to_string()],
+        }];
+
+        let block = Arc::new(block_with_triggers.block.clone());
+        let handlers: Vec<_> = block_with_triggers
+            .trigger_data
+            .iter()
+            .map(|trigger| {
+                ds.match_and_decode(trigger, &block, &logger)
+                    .expect("unable to process block")
+                    .map(|trigger| trigger.handler_name().to_string())
+            })
+            .collect();
+        assert_eq!(
+            handlers,
+            vec![None, Some("handleTransfer".to_string()), None]
+        );
+    }
+
+    fn function_call_action(method_name: &str, args: &str) -> codec::Action {
+        codec::Action {
+            action: Some(codec::action::Action::FunctionCall(
+                codec::FunctionCallAction {
+                    method_name: method_name.to_string(),
+                    args: args.as_bytes().to_vec(),
+                    ..Default::default()
+                },
+            )),
+        }
+    }
+
     fn heights_from_triggers(block: &BlockWithTriggers<Chain>) -> Vec<u64> {
         block
             .trigger_data
@@ -1039,6 +1145,7 @@ mod test {
                 receipt_handlers: vec![ReceiptHandler {
                     handler: "asdsa".to_string(),
                 }],
+                function_call_handlers: vec![],
                 runtime: Arc::new(vec![]),
                 link: Link::default(),
             },
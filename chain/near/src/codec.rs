@@ -121,6 +121,25 @@ impl execution_outcome::Status {
     }
 }
 
+impl Receipt {
+    /// The function call actions of an action receipt, with their index in
+    /// the actions of the receipt
+    pub fn function_calls(&self) -> impl Iterator<Item = (usize, &FunctionCallAction)> {
+        let actions = match &self.receipt {
+            Some(receipt::Receipt::Action(action)) => action.actions.as_slice(),
+            _ => &[],
+        };
+
+        actions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, action)| match &action.action {
+                Some(action::Action::FunctionCall(function_call)) => Some((index, function_call)),
+                _ => None,
+            })
+    }
+}
+
 fn block_time_from_header(header: &BlockHeader) -> BlockTime {
     // The timstamp is in ns since the epoch
     let ts = i64::try_from(header.timestamp_nanosec).unwrap();
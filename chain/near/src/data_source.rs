@@ -14,11 +14,13 @@ use std::collections::HashSet;
 use std::sync::Arc;
 
 use crate::chain::Chain;
+use crate::codec;
 use crate::trigger::{NearTrigger, ReceiptWithOutcome};
 
 pub const NEAR_KIND: &str = "near";
 const BLOCK_HANDLER_KIND: &str = "block";
 const RECEIPT_HANDLER_KIND: &str = "receipt";
+const FUNCTION_CALL_HANDLER_KIND: &str = "function_call";
 
 /// Runtime representation of a data source.
 #[derive(Clone, Debug)]
@@ -90,6 +92,10 @@ impl blockchain::DataSource<Chain> for DataSource {
             kinds.insert(RECEIPT_HANDLER_KIND);
         }
 
+        if !self.mapping.function_call_handlers.is_empty() {
+            kinds.insert(FUNCTION_CALL_HANDLER_KIND);
+        }
+
         kinds
     }
 
@@ -158,6 +164,19 @@ impl blockchain::DataSource<Chain> for DataSource {
                     None => return Ok(None),
                 }
             }
+
+            // A function call trigger matches if the receiver matches `source.account` and a
+            // function call handler for the method and arguments is present.
+            NearTrigger::FunctionCall(function_call) => {
+                if !account_matches(self, &function_call.receipt) {
+                    return Ok(None);
+                }
+
+                match self.handler_for_function_call(&function_call.function_call) {
+                    Some(handler) => &handler.handler,
+                    None => return Ok(None),
+                }
+            }
         };
 
         Ok(Some(TriggerWithHandler::<Chain>::new(
@@ -210,6 +229,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             && name == &other.name
             && source == &other.source
             && mapping.block_handlers == other.mapping.block_handlers
+            && mapping.function_call_handlers == other.mapping.function_call_handlers
             && context == &other.context
     }
 
@@ -246,7 +266,8 @@ impl blockchain::DataSource<Chain> for DataSource {
             Some(addrs) => addrs.is_empty(),
         };
 
-        let has_receipt_handlers = !self.mapping.receipt_handlers.is_empty();
+        let has_receipt_handlers = !self.mapping.receipt_handlers.is_empty()
+            || !self.mapping.function_call_handlers.is_empty();
 
         // Validate not both address and partial addresses are empty.
         if (no_source_address && no_partial_addresses) && has_receipt_handlers {
@@ -272,6 +293,29 @@ impl blockchain::DataSource<Chain> for DataSource {
             errors.push(anyhow!("data source has duplicated receipt handlers"));
         }
 
+        // Validate that function call handlers have a method and that no two of them have the
+        // same method and argument prefixes
+        let mut function_calls = HashSet::new();
+        for handler in &self.mapping.function_call_handlers {
+            if handler.method.is_empty() {
+                errors.push(anyhow!("function call handlers must have a method"));
+            }
+            if handler.args_prefixes.iter().any(|x| x.is_empty()) {
+                errors.push(anyhow!(
+                    "function call handler argument prefixes can't have empty values"
+                ));
+            }
+
+            let mut args_prefixes = handler.args_prefixes.clone();
+            args_prefixes.sort();
+            if !function_calls.insert((&handler.method, args_prefixes)) {
+                errors.push(anyhow!(
+                    "data source has duplicated function call handlers for method {}",
+                    handler.method
+                ));
+            }
+        }
+
         errors
     }
 
@@ -314,6 +358,18 @@ impl DataSource {
     fn handler_for_receipt(&self) -> Option<&ReceiptHandler> {
         self.mapping.receipt_handlers.first()
     }
+
+    /// The first function call handler for the method of the call whose
+    /// argument prefixes match the arguments of the call
+    fn handler_for_function_call(
+        &self,
+        function_call: &codec::FunctionCallAction,
+    ) -> Option<&FunctionCallHandler> {
+        self.mapping
+            .function_call_handlers
+            .iter()
+            .find(|handler| handler.matches(function_call))
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
@@ -426,6 +482,8 @@ pub struct UnresolvedMapping {
     pub block_handlers: Vec<MappingBlockHandler>,
     #[serde(default)]
     pub receipt_handlers: Vec<ReceiptHandler>,
+    #[serde(default)]
+    pub function_call_handlers: Vec<FunctionCallHandler>,
     pub file: Link,
 }
 
@@ -441,6 +499,7 @@ impl UnresolvedMapping {
             entities,
             block_handlers,
             receipt_handlers,
+            function_call_handlers,
             file: link,
         } = self;
 
@@ -457,6 +516,7 @@ impl UnresolvedMapping {
             entities,
             block_handlers,
             receipt_handlers,
+            function_call_handlers,
             runtime: Arc::new(module_bytes),
             link,
         })
@@ -470,6 +530,7 @@ pub struct Mapping {
     pub entities: Vec<String>,
     pub block_handlers: Vec<MappingBlockHandler>,
     pub receipt_handlers: Vec<ReceiptHandler>,
+    pub function_call_handlers: Vec<FunctionCallHandler>,
     pub runtime: Arc<Vec<u8>>,
     pub link: Link,
 }
@@ -484,6 +545,28 @@ pub struct ReceiptHandler {
     pub(crate) handler: String,
 }
 
+/// A handler for the function calls of a method. When `args_prefixes` is not
+/// empty, only calls whose arguments start with one of the prefixes match
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCallHandler {
+    pub(crate) handler: String,
+    pub(crate) method: String,
+    #[serde(default)]
+    pub(crate) args_prefixes: Vec<String>,
+}
+
+impl FunctionCallHandler {
+    pub(crate) fn matches(&self, function_call: &codec::FunctionCallAction) -> bool {
+        function_call.method_name == self.method
+            && (self.args_prefixes.is_empty()
+                || self
+                    .args_prefixes
+                    .iter()
+                    .any(|prefix| function_call.args.starts_with(prefix.as_bytes())))
+    }
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize, Default)]
 pub(crate) struct PartialAccounts {
     #[serde(default)]
@@ -1,5 +1,5 @@
 use crate::codec;
-use crate::trigger::ReceiptWithOutcome;
+use crate::trigger::{FunctionCallWithOutcome, ReceiptWithOutcome};
 use graph::anyhow::anyhow;
 use graph::runtime::gas::GasCounter;
 use graph::runtime::{asc_new, AscHeap, AscPtr, DeterministicHostError, HostExportError, ToAscObj};
@@ -117,6 +117,19 @@ impl ToAscObj<AscReceiptWithOutcome> for ReceiptWithOutcome {
     }
 }
 
+impl ToAscObj<AscFunctionCallWithOutcome> for FunctionCallWithOutcome {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscFunctionCallWithOutcome, HostExportError> {
+        Ok(AscFunctionCallWithOutcome {
+            function_call: asc_new(heap, &self.function_call, gas)?,
+            receipt: asc_new(heap, self.receipt.as_ref(), gas)?,
+        })
+    }
+}
+
 impl ToAscObj<AscActionReceipt> for codec::Receipt {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -618,3 +618,14 @@ pub(crate) struct AscReceiptWithOutcome {
 impl AscIndexId for AscReceiptWithOutcome {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::NearReceiptWithOutcome;
 }
+
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscFunctionCallWithOutcome {
+    pub function_call: AscPtr<AscFunctionCallAction>,
+    pub receipt: AscPtr<AscReceiptWithOutcome>,
+}
+
+impl AscIndexId for AscFunctionCallWithOutcome {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::NearFunctionCallWithOutcome;
+}
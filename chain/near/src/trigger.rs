@@ -24,6 +24,11 @@ impl std::fmt::Debug for NearTrigger {
                 outcome: &'a codec::ExecutionOutcomeWithId,
                 receipt: &'a codec::Receipt,
             },
+
+            FunctionCall {
+                method_name: &'a str,
+                receipt: &'a codec::Receipt,
+            },
         }
 
         let trigger_without_block = match self {
@@ -32,6 +37,10 @@ impl std::fmt::Debug for NearTrigger {
                 outcome: &receipt.outcome,
                 receipt: &receipt.receipt,
             },
+            NearTrigger::FunctionCall(function_call) => MappingTriggerWithoutBlock::FunctionCall {
+                method_name: &function_call.function_call.method_name,
+                receipt: &function_call.receipt.receipt,
+            },
         };
 
         write!(f, "{:?}", trigger_without_block)
@@ -47,6 +56,9 @@ impl ToAscPtr for NearTrigger {
         Ok(match self {
             NearTrigger::Block(block) => asc_new(heap, block.as_ref(), gas)?.erase(),
             NearTrigger::Receipt(receipt) => asc_new(heap, receipt.as_ref(), gas)?.erase(),
+            NearTrigger::FunctionCall(function_call) => {
+                asc_new(heap, function_call.as_ref(), gas)?.erase()
+            }
         })
     }
 }
@@ -55,6 +67,7 @@ impl ToAscPtr for NearTrigger {
 pub enum NearTrigger {
     Block(Arc<codec::Block>),
     Receipt(Arc<ReceiptWithOutcome>),
+    FunctionCall(Arc<FunctionCallWithOutcome>),
 }
 
 impl PartialEq for NearTrigger {
@@ -62,8 +75,12 @@ impl PartialEq for NearTrigger {
         match (self, other) {
             (Self::Block(a_ptr), Self::Block(b_ptr)) => a_ptr == b_ptr,
             (Self::Receipt(a), Self::Receipt(b)) => a.receipt.receipt_id == b.receipt.receipt_id,
+            (Self::FunctionCall(a), Self::FunctionCall(b)) => {
+                a.receipt.receipt.receipt_id == b.receipt.receipt.receipt_id
+                    && a.action_index == b.action_index
+            }
 
-            (Self::Block(_), Self::Receipt(_)) | (Self::Receipt(_), Self::Block(_)) => false,
+            _ => false,
         }
     }
 }
@@ -75,6 +92,7 @@ impl NearTrigger {
         match self {
             NearTrigger::Block(block) => block.number(),
             NearTrigger::Receipt(receipt) => receipt.block.number(),
+            NearTrigger::FunctionCall(function_call) => function_call.receipt.block.number(),
         }
     }
 
@@ -82,6 +100,9 @@ impl NearTrigger {
         match self {
             NearTrigger::Block(block) => block.ptr().hash_as_h256(),
             NearTrigger::Receipt(receipt) => receipt.block.ptr().hash_as_h256(),
+            NearTrigger::FunctionCall(function_call) => {
+                function_call.receipt.block.ptr().hash_as_h256()
+            }
         }
     }
 
@@ -98,6 +119,23 @@ impl NearTrigger {
                     self.block_hash()
                 )
             }
+            NearTrigger::FunctionCall(function_call) => {
+                format!(
+                    "function call {} of receipt id {}, block #{} ({})",
+                    function_call.function_call.method_name,
+                    hex::encode(
+                        &function_call
+                            .receipt
+                            .receipt
+                            .receipt_id
+                            .as_ref()
+                            .unwrap()
+                            .bytes
+                    ),
+                    self.block_number(),
+                    self.block_hash()
+                )
+            }
         }
     }
 }
@@ -114,7 +152,11 @@ impl Ord for NearTrigger {
 
             // Execution outcomes have no intrinsic ordering information, so we keep the order in
             // which they are included in the `receipt_execution_outcomes` field of `IndexerShard`.
-            (Self::Receipt(..), Self::Receipt(..)) => Ordering::Equal,
+            // Function calls are kept in the order of the actions of their receipt.
+            (
+                Self::Receipt(..) | Self::FunctionCall(..),
+                Self::Receipt(..) | Self::FunctionCall(..),
+            ) => Ordering::Equal,
         }
     }
 }
@@ -148,6 +190,15 @@ pub struct ReceiptWithOutcome {
     pub block: Arc<codec::Block>,
 }
 
+/// A function call action of a receipt, together with the receipt so that
+/// handlers have access to the execution outcome and its logs
+pub struct FunctionCallWithOutcome {
+    pub function_call: codec::FunctionCallAction,
+    /// The index of the action in the actions of the receipt
+    pub action_index: usize,
+    pub receipt: Arc<ReceiptWithOutcome>,
+}
+
 #[cfg(test)]
 mod tests {
     use std::convert::TryFrom;
@@ -185,6 +236,28 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn function_call_trigger_to_asc_ptr() {
+        let mut heap = BytesHeap::new(API_VERSION_0_0_5);
+        let trigger = NearTrigger::FunctionCall(Arc::new(FunctionCallWithOutcome {
+            function_call: codec::FunctionCallAction {
+                method_name: "ft_transfer".to_string(),
+                args: br#"{"receiver_id":"receiver.near"}"#.to_vec(),
+                gas: 1000,
+                deposit: big_int(1),
+            },
+            action_index: 0,
+            receipt: Arc::new(ReceiptWithOutcome {
+                block: Arc::new(block()),
+                outcome: execution_outcome_with_id().unwrap(),
+                receipt: receipt().unwrap(),
+            }),
+        }));
+
+        let result = trigger.to_asc_ptr(&mut heap, &GasCounter::new(GasMetrics::mock()));
+        assert!(result.is_ok());
+    }
+
     fn block() -> codec::Block {
         codec::Block {
             author: "test".to_string(),
@@ -0,0 +1,211 @@
+use crate::{data_source::DataSource, Chain};
+use graph::blockchain as bc;
+use graph::prelude::*;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Default)]
+pub struct TriggerFilter {
+    pub(crate) block_filter: SolanaBlockFilter,
+    pub(crate) instruction_filter: SolanaInstructionFilter,
+    pub(crate) account_filter: SolanaAccountFilter,
+}
+
+impl bc::TriggerFilter<Chain> for TriggerFilter {
+    fn extend<'a>(&mut self, data_sources: impl Iterator<Item = &'a DataSource> + Clone) {
+        let TriggerFilter {
+            block_filter,
+            instruction_filter,
+            account_filter,
+        } = self;
+
+        block_filter.extend(SolanaBlockFilter::from_data_sources(data_sources.clone()));
+        instruction_filter.extend(SolanaInstructionFilter::from_data_sources(
+            data_sources.clone(),
+        ));
+        account_filter.extend(SolanaAccountFilter::from_data_sources(data_sources));
+    }
+
+    fn node_capabilities(&self) -> bc::EmptyNodeCapabilities<Chain> {
+        bc::EmptyNodeCapabilities::default()
+    }
+
+    fn extend_with_template(
+        &mut self,
+        _data_source: impl Iterator<Item = <Chain as bc::Blockchain>::DataSourceTemplate>,
+    ) {
+    }
+
+    fn to_firehose_filter(self) -> Vec<prost_types::Any> {
+        vec![]
+    }
+}
+
+/// SolanaInstructionFilter matches the instructions of the programs of the
+/// data sources that have instruction handlers
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SolanaInstructionFilter {
+    program_ids: HashSet<Vec<u8>>,
+}
+
+impl SolanaInstructionFilter {
+    pub fn matches(&self, program_id: &[u8]) -> bool {
+        self.program_ids.contains(program_id)
+    }
+
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        let program_ids = iter
+            .into_iter()
+            .filter(|data_source| !data_source.mapping.instruction_handlers.is_empty())
+            .filter_map(|data_source| data_source.source.program_id.as_ref())
+            .map(|program_id| program_id.as_ref().to_vec())
+            .collect();
+
+        Self { program_ids }
+    }
+
+    pub fn extend(&mut self, other: SolanaInstructionFilter) {
+        self.program_ids.extend(other.program_ids);
+    }
+}
+
+/// SolanaAccountFilter matches the accounts that data sources have account
+/// handlers for
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SolanaAccountFilter {
+    accounts: HashSet<Vec<u8>>,
+}
+
+impl SolanaAccountFilter {
+    pub fn matches(&self, account: &[u8]) -> bool {
+        self.accounts.contains(account)
+    }
+
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        let accounts = iter
+            .into_iter()
+            .flat_map(|data_source| data_source.mapping.account_handlers.iter())
+            .map(|handler| handler.account.as_ref().to_vec())
+            .collect();
+
+        Self { accounts }
+    }
+
+    pub fn extend(&mut self, other: SolanaAccountFilter) {
+        self.accounts.extend(other.accounts);
+    }
+}
+
+/// SolanaBlockFilter will match every block if any data source has a block
+/// handler
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SolanaBlockFilter {
+    pub trigger_every_block: bool,
+}
+
+impl SolanaBlockFilter {
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            trigger_every_block: iter
+                .into_iter()
+                .any(|data_source| !data_source.mapping.block_handlers.is_empty()),
+        }
+    }
+
+    pub fn extend(&mut self, other: SolanaBlockFilter) {
+        self.trigger_every_block = self.trigger_every_block || other.trigger_every_block;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use graph::{prelude::Link, semver::Version};
+
+    use crate::data_source::{AccountHandler, DataSource, InstructionHandler, Mapping, Source};
+    use crate::pubkey::Pubkey;
+
+    use super::{SolanaAccountFilter, SolanaInstructionFilter};
+
+    const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+    const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+
+    #[test]
+    fn instruction_filter_matches_program_ids() {
+        let dss = vec![
+            new_datasource(None, vec![]),
+            new_datasource(Some(TOKEN_PROGRAM), vec![]),
+        ];
+
+        let filter = SolanaInstructionFilter::from_data_sources(dss.iter());
+        assert!(filter.matches(pubkey(TOKEN_PROGRAM).as_ref()));
+        assert!(!filter.matches(pubkey(SYSTEM_PROGRAM).as_ref()));
+    }
+
+    #[test]
+    fn instruction_filter_extend_matches() {
+        let mut filter = SolanaInstructionFilter::from_data_sources(
+            vec![new_datasource(Some(TOKEN_PROGRAM), vec![])].iter(),
+        );
+        filter.extend(SolanaInstructionFilter::from_data_sources(
+            vec![new_datasource(Some(SYSTEM_PROGRAM), vec![])].iter(),
+        ));
+
+        assert!(filter.matches(pubkey(TOKEN_PROGRAM).as_ref()));
+        assert!(filter.matches(pubkey(SYSTEM_PROGRAM).as_ref()));
+    }
+
+    #[test]
+    fn account_filter_matches_accounts_with_handlers() {
+        let dss = vec![
+            new_datasource(Some(TOKEN_PROGRAM), vec![]),
+            new_datasource(None, vec![SYSTEM_PROGRAM]),
+        ];
+
+        let filter = SolanaAccountFilter::from_data_sources(dss.iter());
+        assert!(filter.matches(pubkey(SYSTEM_PROGRAM).as_ref()));
+        assert!(!filter.matches(pubkey(TOKEN_PROGRAM).as_ref()));
+    }
+
+    fn pubkey(key: &str) -> Pubkey {
+        key.parse().unwrap()
+    }
+
+    fn new_datasource(program_id: Option<&str>, accounts: Vec<&str>) -> DataSource {
+        let instruction_handlers = match program_id {
+            Some(_) => vec![InstructionHandler {
+                handler: "handleInstruction".into(),
+            }],
+            None => vec![],
+        };
+
+        DataSource {
+            kind: "solana".into(),
+            network: None,
+            name: "".into(),
+            source: Source {
+                program_id: program_id.map(pubkey),
+                start_block: 10,
+                end_block: None,
+            },
+            mapping: Mapping {
+                api_version: Version::new(1, 2, 3),
+                language: "".into(),
+                entities: vec![],
+                block_handlers: vec![],
+                instruction_handlers,
+                account_handlers: accounts
+                    .into_iter()
+                    .map(|account| AccountHandler {
+                        handler: "handleAccount".into(),
+                        account: pubkey(account),
+                    })
+                    .collect(),
+                runtime: Arc::new(vec![]),
+                link: Link { link: "".into() },
+            },
+            context: Arc::new(None),
+            creation_block: None,
+        }
+    }
+}
@@ -0,0 +1,534 @@
+use graph::anyhow::{self, anyhow};
+use graph::blockchain::client::ChainClient;
+use graph::blockchain::firehose_block_ingestor::FirehoseBlockIngestor;
+use graph::blockchain::{
+    BasicBlockchainBuilder, Block, BlockIngestor, BlockchainBuilder, BlockchainKind,
+    EmptyNodeCapabilities, IngestionMode, NoopDecoderHook, NoopRuntimeAdapter,
+};
+use graph::cheap_clone::CheapClone;
+use graph::components::adapter::ChainId;
+use graph::components::store::DeploymentCursorTracker;
+use graph::data::subgraph::UnifiedMappingApiVersion;
+use graph::env::EnvVars;
+use graph::firehose::FirehoseEndpoint;
+use graph::prelude::MetricsRegistry;
+use graph::substreams::Clock;
+use graph::{
+    blockchain::{
+        block_stream::{
+            BlockStreamEvent, BlockWithTriggers, FirehoseError,
+            FirehoseMapper as FirehoseMapperTrait, TriggersAdapter as TriggersAdapterTrait,
+        },
+        firehose_block_stream::FirehoseBlockStream,
+        BlockHash, BlockPtr, Blockchain, IngestorError, RuntimeAdapter as RuntimeAdapterTrait,
+    },
+    components::store::DeploymentLocator,
+    firehose::{self as firehose, ForkStep},
+    prelude::{async_trait, o, BlockNumber, ChainStore, Error, Logger, LoggerFactory},
+};
+use prost::Message;
+use std::sync::Arc;
+
+use crate::adapter::TriggerFilter;
+use crate::data_source::{DataSourceTemplate, UnresolvedDataSourceTemplate};
+use crate::trigger::{AccountChange, InstructionWithInfo, SolanaTrigger};
+use crate::{
+    codec,
+    data_source::{DataSource, UnresolvedDataSource},
+};
+use graph::blockchain::block_stream::{
+    BlockStream, BlockStreamError, BlockStreamMapper, FirehoseCursor,
+};
+
+/// Solana roots, i.e., finalizes, a block once about 32 more blocks were
+/// voted on
+const FINALITY_DEPTH: BlockNumber = 32;
+/// How many slots in a row we expect to be skipped at most when looking
+/// for the final block
+const MAX_SKIPPED_SLOTS: BlockNumber = 100;
+
+pub struct Chain {
+    logger_factory: LoggerFactory,
+    name: ChainId,
+    client: Arc<ChainClient<Self>>,
+    chain_store: Arc<dyn ChainStore>,
+    metrics_registry: Arc<MetricsRegistry>,
+}
+
+impl std::fmt::Debug for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "chain: solana")
+    }
+}
+
+#[async_trait]
+impl BlockchainBuilder<Chain> for BasicBlockchainBuilder {
+    async fn build(self, _config: &Arc<EnvVars>) -> Chain {
+        Chain {
+            logger_factory: self.logger_factory,
+            name: self.name,
+            client: Arc::new(ChainClient::<Chain>::new_firehose(self.firehose_endpoints)),
+            chain_store: self.chain_store,
+            metrics_registry: self.metrics_registry,
+        }
+    }
+}
+
+#[async_trait]
+impl Blockchain for Chain {
+    const KIND: BlockchainKind = BlockchainKind::Solana;
+
+    type Client = ();
+    type Block = codec::Block;
+
+    type DataSource = DataSource;
+
+    type UnresolvedDataSource = UnresolvedDataSource;
+
+    type DataSourceTemplate = DataSourceTemplate;
+
+    type UnresolvedDataSourceTemplate = UnresolvedDataSourceTemplate;
+
+    type TriggerData = crate::trigger::SolanaTrigger;
+
+    type MappingTrigger = crate::trigger::SolanaTrigger;
+
+    type TriggerFilter = crate::adapter::TriggerFilter;
+
+    type NodeCapabilities = EmptyNodeCapabilities<Self>;
+
+    type DecoderHook = NoopDecoderHook;
+
+    fn triggers_adapter(
+        &self,
+        _loc: &DeploymentLocator,
+        _capabilities: &Self::NodeCapabilities,
+        _unified_api_version: UnifiedMappingApiVersion,
+    ) -> Result<Arc<dyn TriggersAdapterTrait<Self>>, Error> {
+        let adapter = TriggersAdapter {
+            chain_store: self.chain_store.cheap_clone(),
+        };
+        Ok(Arc::new(adapter))
+    }
+
+    fn is_refetch_block_required(&self) -> bool {
+        false
+    }
+
+    async fn refetch_firehose_block(
+        &self,
+        _logger: &Logger,
+        _cursor: FirehoseCursor,
+    ) -> Result<codec::Block, Error> {
+        Err(anyhow!(
+            "solana blocks never need to be refetched since `is_refetch_block_required` is false"
+        ))
+    }
+
+    async fn new_block_stream(
+        &self,
+        deployment: DeploymentLocator,
+        store: impl DeploymentCursorTracker,
+        start_blocks: Vec<BlockNumber>,
+        filter: Arc<Self::TriggerFilter>,
+        unified_api_version: UnifiedMappingApiVersion,
+        _ingestion: IngestionMode,
+    ) -> Result<Box<dyn BlockStream<Self>>, Error> {
+        let adapter = self
+            .triggers_adapter(
+                &deployment,
+                &EmptyNodeCapabilities::default(),
+                unified_api_version,
+            )
+            .unwrap_or_else(|_| panic!("no adapter for network {}", self.name));
+
+        let logger = self
+            .logger_factory
+            .subgraph_logger(&deployment)
+            .new(o!("component" => "FirehoseBlockStream"));
+
+        let firehose_mapper = Arc::new(FirehoseMapper { adapter, filter });
+
+        Ok(Box::new(FirehoseBlockStream::new(
+            deployment.hash,
+            self.chain_client(),
+            store.block_ptr(),
+            store.firehose_cursor(),
+            firehose_mapper,
+            start_blocks,
+            logger,
+            self.metrics_registry.clone(),
+        )))
+    }
+
+    fn chain_store(&self) -> Arc<dyn ChainStore> {
+        self.chain_store.clone()
+    }
+
+    async fn block_pointer_from_number(
+        &self,
+        logger: &Logger,
+        number: BlockNumber,
+    ) -> Result<BlockPtr, IngestorError> {
+        self.client
+            .firehose_endpoint()
+            .await?
+            .block_ptr_for_number::<codec::Block>(logger, number)
+            .await
+            .map_err(Into::into)
+    }
+
+    fn runtime(&self) -> anyhow::Result<(Arc<dyn RuntimeAdapterTrait<Self>>, Self::DecoderHook)> {
+        Ok((Arc::new(NoopRuntimeAdapter::default()), NoopDecoderHook))
+    }
+
+    fn chain_client(&self) -> Arc<ChainClient<Self>> {
+        self.client.clone()
+    }
+
+    async fn block_ingestor(&self) -> anyhow::Result<Box<dyn BlockIngestor>> {
+        let ingestor = FirehoseBlockIngestor::<crate::Block, Self>::new(
+            self.chain_store.cheap_clone(),
+            self.chain_client(),
+            self.logger_factory
+                .component_logger("SolanaFirehoseBlockIngestor", None),
+            self.name.clone(),
+        );
+        Ok(Box::new(ingestor))
+    }
+}
+
+pub struct TriggersAdapter {
+    chain_store: Arc<dyn ChainStore>,
+}
+
+#[async_trait]
+impl TriggersAdapterTrait<Chain> for TriggersAdapter {
+    async fn scan_triggers(
+        &self,
+        _from: BlockNumber,
+        _to: BlockNumber,
+        _filter: &TriggerFilter,
+    ) -> Result<(Vec<BlockWithTriggers<Chain>>, BlockNumber), Error> {
+        panic!("Should never be called since not used by FirehoseBlockStream")
+    }
+
+    async fn triggers_in_block(
+        &self,
+        logger: &Logger,
+        block: codec::Block,
+        filter: &TriggerFilter,
+    ) -> Result<BlockWithTriggers<Chain>, Error> {
+        // TODO: Find the best place to introduce an `Arc` and avoid this clone.
+        let shared_block = Arc::new(block.clone());
+
+        // Failed transactions have no effect apart from paying their fee
+        let mut trigger_data: Vec<_> = block
+            .transactions
+            .iter()
+            .filter(|tx| !tx.failed())
+            .flat_map(|tx| transaction_triggers(tx, &shared_block, filter))
+            .collect();
+
+        if filter.block_filter.trigger_every_block {
+            trigger_data.push(SolanaTrigger::Block(shared_block.cheap_clone()));
+        }
+
+        Ok(BlockWithTriggers::new(block, trigger_data, logger))
+    }
+
+    async fn is_on_main_chain(&self, _ptr: BlockPtr) -> Result<bool, Error> {
+        panic!("Should never be called since not used by FirehoseBlockStream")
+    }
+
+    async fn ancestor_block(
+        &self,
+        _ptr: BlockPtr,
+        _offset: BlockNumber,
+        _root: Option<BlockHash>,
+    ) -> Result<Option<codec::Block>, Error> {
+        panic!("Should never be called since FirehoseBlockStream cannot resolve it")
+    }
+
+    /// Panics if `block` is genesis.
+    /// But that's ok since this is only called when reverting `block`.
+    /// The parent of `block` as the block ingestor recorded it in the
+    /// chain store. Since slots can be skipped, the slot of the parent has
+    /// to come from the chain store, too. Fails if either block is not in
+    /// the chain store
+    async fn parent_ptr(&self, block: &BlockPtr) -> Result<Option<BlockPtr>, Error> {
+        if block.number == 0 {
+            return Ok(None);
+        }
+
+        let missing = |ptr: &dyn std::fmt::Display| {
+            anyhow!(
+                "can not find the parent of block {} since {} is not in the chain store",
+                block,
+                ptr
+            )
+        };
+        let parent_hash = self
+            .chain_store
+            .block_number(&block.hash)
+            .await?
+            .and_then(|(_, _, _, parent_hash)| parent_hash)
+            .ok_or_else(|| missing(block))?;
+        let (_, parent_number, _, _) = self
+            .chain_store
+            .block_number(&parent_hash)
+            .await?
+            .ok_or_else(|| missing(&parent_hash))?;
+        Ok(Some(BlockPtr {
+            hash: parent_hash,
+            number: parent_number,
+        }))
+    }
+}
+
+/// The triggers of a successful transaction: the instructions, including
+/// inner instructions, in the order in which they ran, followed by the
+/// accounts that the transaction could have changed
+fn transaction_triggers(
+    tx: &codec::ConfirmedTransaction,
+    block: &Arc<codec::Block>,
+    filter: &TriggerFilter,
+) -> Vec<SolanaTrigger> {
+    let keys = tx.account_keys();
+    let key = |index: u32| keys.get(index as usize).copied().unwrap_or_default();
+
+    let instructions = tx
+        .transaction
+        .as_ref()
+        .and_then(|tx| tx.message.as_ref())
+        .map(|message| message.instructions.as_slice())
+        .unwrap_or_default();
+    let inner_instructions = tx
+        .meta
+        .as_ref()
+        .map(|meta| meta.inner_instructions.as_slice())
+        .unwrap_or_default();
+    let balances = |index: usize| {
+        let balance = |balances: &[u64]| balances.get(index).copied().unwrap_or_default();
+        tx.meta
+            .as_ref()
+            .map(|meta| (balance(&meta.pre_balances), balance(&meta.post_balances)))
+            .unwrap_or_default()
+    };
+
+    // (index, inner index, program id index, accounts, data)
+    let mut matched = Vec::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        let index = index as u32;
+        if filter
+            .instruction_filter
+            .matches(key(instruction.program_id_index))
+        {
+            matched.push((
+                index,
+                None,
+                instruction.program_id_index,
+                &instruction.accounts,
+                &instruction.data,
+            ));
+        }
+
+        let nested = inner_instructions
+            .iter()
+            .filter(|inner| inner.index == index)
+            .flat_map(|inner| inner.instructions.iter());
+        for (inner_index, inner) in nested.enumerate() {
+            if filter
+                .instruction_filter
+                .matches(key(inner.program_id_index))
+            {
+                matched.push((
+                    index,
+                    Some(inner_index as u32),
+                    inner.program_id_index,
+                    &inner.accounts,
+                    &inner.data,
+                ));
+            }
+        }
+    }
+
+    let changed: Vec<_> = keys
+        .iter()
+        .enumerate()
+        .filter(|(index, key)| filter.account_filter.matches(key) && tx.is_writable(*index))
+        .collect();
+
+    if matched.is_empty() && changed.is_empty() {
+        return vec![];
+    }
+
+    let transaction = Arc::new(tx.clone());
+    let instructions =
+        matched
+            .into_iter()
+            .map(|(index, inner_index, program_id_index, accounts, data)| {
+                SolanaTrigger::Instruction(Arc::new(InstructionWithInfo {
+                    program_id: key(program_id_index).to_vec(),
+                    accounts: accounts
+                        .iter()
+                        .map(|index| key(*index as u32).to_vec())
+                        .collect(),
+                    data: data.clone(),
+                    index,
+                    inner_index,
+                    transaction: transaction.cheap_clone(),
+                    block: block.cheap_clone(),
+                }))
+            });
+    let accounts = changed.into_iter().map(|(index, account)| {
+        let (pre_balance, post_balance) = balances(index);
+        SolanaTrigger::Account(Arc::new(AccountChange {
+            account: account.to_vec(),
+            pre_balance,
+            post_balance,
+            transaction: transaction.cheap_clone(),
+            block: block.cheap_clone(),
+        }))
+    });
+
+    instructions.chain(accounts).collect()
+}
+
+pub struct FirehoseMapper {
+    adapter: Arc<dyn TriggersAdapterTrait<Chain>>,
+    filter: Arc<TriggerFilter>,
+}
+
+#[async_trait]
+impl BlockStreamMapper<Chain> for FirehoseMapper {
+    fn decode_block(
+        &self,
+        output: Option<&[u8]>,
+    ) -> Result<Option<codec::Block>, BlockStreamError> {
+        let block = match output {
+            Some(block) => codec::Block::decode(block)?,
+            None => {
+                return Err(anyhow::anyhow!(
+                    "Solana mapper is expected to always have a block"
+                ))?
+            }
+        };
+
+        Ok(Some(block))
+    }
+
+    async fn block_with_triggers(
+        &self,
+        logger: &Logger,
+        block: codec::Block,
+    ) -> Result<BlockWithTriggers<Chain>, BlockStreamError> {
+        self.adapter
+            .triggers_in_block(logger, block, self.filter.as_ref())
+            .await
+            .map_err(BlockStreamError::from)
+    }
+
+    async fn handle_substreams_block(
+        &self,
+        _logger: &Logger,
+        _clock: Clock,
+        _cursor: FirehoseCursor,
+        _block: Vec<u8>,
+    ) -> Result<BlockStreamEvent<Chain>, BlockStreamError> {
+        Err(BlockStreamError::Unknown(anyhow!(
+            "solana subgraphs can not be indexed from substreams"
+        )))
+    }
+}
+
+#[async_trait]
+impl FirehoseMapperTrait<Chain> for FirehoseMapper {
+    fn trigger_filter(&self) -> &TriggerFilter {
+        self.filter.as_ref()
+    }
+
+    async fn to_block_stream_event(
+        &self,
+        logger: &Logger,
+        response: &firehose::Response,
+    ) -> Result<BlockStreamEvent<Chain>, FirehoseError> {
+        let step = ForkStep::try_from(response.step).unwrap_or_else(|_| {
+            panic!(
+                "unknown step i32 value {}, maybe you forgot update & re-regenerate the protobuf definitions?",
+                response.step
+            )
+        });
+
+        let any_block = response
+            .block
+            .as_ref()
+            .expect("block payload information should always be present");
+
+        // unwrap: Input cannot be None so output will be error or block.
+        let block = self
+            .decode_block(Some(&any_block.value.as_ref()))
+            .map_err(Error::from)?
+            .unwrap();
+
+        use ForkStep::*;
+        match step {
+            StepNew => Ok(BlockStreamEvent::ProcessBlock(
+                self.block_with_triggers(&logger, block)
+                    .await
+                    .map_err(Error::from)?,
+                FirehoseCursor::from(response.cursor.clone()),
+            )),
+
+            StepUndo => {
+                let parent_ptr = block
+                    .parent_ptr()
+                    .expect("Genesis block should never be reverted");
+
+                Ok(BlockStreamEvent::Revert(
+                    parent_ptr,
+                    FirehoseCursor::from(response.cursor.clone()),
+                ))
+            }
+
+            StepFinal => {
+                panic!("irreversible step is not handled and should not be requested in the Firehose request")
+            }
+
+            StepUnset => {
+                panic!("unknown step should not happen in the Firehose response")
+            }
+        }
+    }
+
+    async fn block_ptr_for_number(
+        &self,
+        logger: &Logger,
+        endpoint: &Arc<FirehoseEndpoint>,
+        number: BlockNumber,
+    ) -> Result<BlockPtr, Error> {
+        endpoint
+            .block_ptr_for_number::<codec::Block>(logger, number)
+            .await
+    }
+
+    /// Slots can be skipped, so the final block is the closest block at
+    /// or before `FINALITY_DEPTH` slots behind `block`
+    async fn final_block_ptr_for(
+        &self,
+        logger: &Logger,
+        endpoint: &Arc<FirehoseEndpoint>,
+        block: &codec::Block,
+    ) -> Result<BlockPtr, Error> {
+        let mut number = (block.number() - FINALITY_DEPTH).max(0);
+        let lowest = (number - MAX_SKIPPED_SLOTS).max(0);
+
+        loop {
+            match self.block_ptr_for_number(logger, endpoint, number).await {
+                Ok(ptr) => return Ok(ptr),
+                Err(e) if number <= lowest => return Err(e),
+                Err(_) => number -= 1,
+            }
+        }
+    }
+}
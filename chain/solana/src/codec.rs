@@ -0,0 +1,134 @@
+#[rustfmt::skip]
+#[path = "protobuf/sf.solana.r#type.v1.rs"]
+mod pbcodec;
+
+use graph::{
+    blockchain::Block as BlockchainBlock,
+    blockchain::{BlockPtr, BlockTime},
+    prelude::BlockNumber,
+};
+
+pub use pbcodec::*;
+
+/// Solana encodes block hashes as base58 strings
+fn decode_hash(hash: &str) -> Vec<u8> {
+    bs58::decode(hash)
+        .into_vec()
+        .expect("Firehose sends base58 encoded block hashes")
+}
+
+impl Block {
+    pub fn hash(&self) -> Vec<u8> {
+        decode_hash(&self.blockhash)
+    }
+
+    pub fn previous_hash(&self) -> Vec<u8> {
+        decode_hash(&self.previous_blockhash)
+    }
+}
+
+impl BlockchainBlock for Block {
+    /// Blocks are numbered by slot. Slots in which no block was produced
+    /// are skipped, so the parent of a block is not always the previous
+    /// slot
+    fn number(&self) -> i32 {
+        BlockNumber::try_from(self.slot).unwrap()
+    }
+
+    fn ptr(&self) -> BlockPtr {
+        BlockPtr {
+            hash: self.hash().into(),
+            number: self.number(),
+        }
+    }
+
+    fn parent_ptr(&self) -> Option<BlockPtr> {
+        if self.slot == 0 {
+            return None;
+        }
+
+        Some(BlockPtr {
+            hash: self.previous_hash().into(),
+            number: BlockNumber::try_from(self.parent_slot).unwrap(),
+        })
+    }
+
+    fn timestamp(&self) -> BlockTime {
+        let timestamp = self
+            .block_time
+            .as_ref()
+            .map(|time| time.timestamp)
+            .unwrap_or_default();
+        BlockTime::since_epoch(timestamp, 0)
+    }
+}
+
+impl ConfirmedTransaction {
+    /// The first signature of a transaction identifies it
+    pub fn signature(&self) -> &[u8] {
+        self.transaction
+            .as_ref()
+            .and_then(|tx| tx.signatures.first())
+            .map(|signature| signature.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Failed transactions only pay their fee; all their other changes
+    /// are discarded
+    pub fn failed(&self) -> bool {
+        self.meta.as_ref().map_or(false, |meta| meta.err.is_some())
+    }
+
+    /// The accounts of the transaction in the order in which instructions
+    /// refer to them: the accounts of the message followed by the writable
+    /// and the read-only accounts loaded from address lookup tables
+    pub fn account_keys(&self) -> Vec<&[u8]> {
+        let static_keys = self
+            .transaction
+            .as_ref()
+            .and_then(|tx| tx.message.as_ref())
+            .map(|message| message.account_keys.as_slice())
+            .unwrap_or_default();
+        let (writable, readonly) = match &self.meta {
+            Some(meta) => (
+                meta.loaded_writable_addresses.as_slice(),
+                meta.loaded_readonly_addresses.as_slice(),
+            ),
+            None => (Default::default(), Default::default()),
+        };
+
+        static_keys
+            .iter()
+            .chain(writable)
+            .chain(readonly)
+            .map(|key| key.as_slice())
+            .collect()
+    }
+
+    /// Whether the transaction may change the account at `index` in
+    /// `account_keys`. The accounts of the message are ordered as signed
+    /// writable, signed read-only, unsigned writable and unsigned read-only
+    pub fn is_writable(&self, index: usize) -> bool {
+        let message = match self.transaction.as_ref().and_then(|tx| tx.message.as_ref()) {
+            Some(message) => message,
+            None => return false,
+        };
+        let header = message.header.clone().unwrap_or_default();
+        let static_len = message.account_keys.len();
+
+        if index < static_len {
+            let signed = header.num_required_signatures as usize;
+            if index < signed {
+                index < signed.saturating_sub(header.num_readonly_signed_accounts as usize)
+            } else {
+                index < static_len.saturating_sub(header.num_readonly_unsigned_accounts as usize)
+            }
+        } else {
+            let loaded_writable = self
+                .meta
+                .as_ref()
+                .map_or(0, |meta| meta.loaded_writable_addresses.len());
+            index - static_len < loaded_writable
+        }
+    }
+}
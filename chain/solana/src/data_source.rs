@@ -0,0 +1,458 @@
+use graph::anyhow::Context;
+use graph::blockchain::{Block, TriggerWithHandler};
+use graph::components::store::StoredDynamicDataSource;
+use graph::components::subgraph::InstanceDSTemplateInfo;
+use graph::data::subgraph::DataSourceContext;
+use graph::prelude::SubgraphManifestValidationError;
+use graph::{
+    anyhow::{anyhow, Error},
+    blockchain::{self, Blockchain},
+    prelude::{async_trait, BlockNumber, CheapClone, Deserialize, Link, LinkResolver, Logger},
+    semver,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::chain::Chain;
+use crate::pubkey::Pubkey;
+use crate::trigger::SolanaTrigger;
+
+pub const SOLANA_KIND: &str = "solana";
+const BLOCK_HANDLER_KIND: &str = "block";
+const INSTRUCTION_HANDLER_KIND: &str = "instruction";
+const ACCOUNT_HANDLER_KIND: &str = "account";
+
+/// Runtime representation of a data source.
+#[derive(Clone, Debug)]
+pub struct DataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: Mapping,
+    pub context: Arc<Option<DataSourceContext>>,
+    pub creation_block: Option<BlockNumber>,
+}
+
+impl blockchain::DataSource<Chain> for DataSource {
+    fn from_template_info(
+        _info: InstanceDSTemplateInfo,
+        _template: &graph::data_source::DataSourceTemplate<Chain>,
+    ) -> Result<Self, Error> {
+        Err(anyhow!("Solana subgraphs do not support templates"))
+    }
+
+    fn address(&self) -> Option<&[u8]> {
+        self.source.program_id.as_ref().map(|id| id.as_ref())
+    }
+
+    fn start_block(&self) -> BlockNumber {
+        self.source.start_block
+    }
+
+    fn handler_kinds(&self) -> HashSet<&str> {
+        let mut kinds = HashSet::new();
+
+        if self.handler_for_block().is_some() {
+            kinds.insert(BLOCK_HANDLER_KIND);
+        }
+
+        if self.handler_for_instruction().is_some() {
+            kinds.insert(INSTRUCTION_HANDLER_KIND);
+        }
+
+        if !self.mapping.account_handlers.is_empty() {
+            kinds.insert(ACCOUNT_HANDLER_KIND);
+        }
+
+        kinds
+    }
+
+    fn end_block(&self) -> Option<BlockNumber> {
+        self.source.end_block
+    }
+
+    fn match_and_decode(
+        &self,
+        trigger: &<Chain as Blockchain>::TriggerData,
+        block: &Arc<<Chain as Blockchain>::Block>,
+        _logger: &Logger,
+    ) -> Result<Option<TriggerWithHandler<Chain>>, Error> {
+        if self.source.start_block > block.number() {
+            return Ok(None);
+        }
+
+        let handler = match trigger {
+            // A block trigger matches if a block handler is present.
+            SolanaTrigger::Block(_) => match self.handler_for_block() {
+                Some(handler) => &handler.handler,
+                None => return Ok(None),
+            },
+            // An instruction trigger matches if it is for the program of the
+            // data source and an instruction handler is present.
+            SolanaTrigger::Instruction(instruction) => {
+                if self.address() != Some(instruction.program_id.as_slice()) {
+                    return Ok(None);
+                }
+                match self.handler_for_instruction() {
+                    Some(handler) => &handler.handler,
+                    None => return Ok(None),
+                }
+            }
+            // An account trigger matches if there is a handler for the account.
+            SolanaTrigger::Account(change) => match self.handler_for_account(&change.account) {
+                Some(handler) => &handler.handler,
+                None => return Ok(None),
+            },
+        };
+
+        Ok(Some(TriggerWithHandler::<Chain>::new(
+            trigger.cheap_clone(),
+            handler.clone(),
+            block.ptr(),
+            block.timestamp(),
+        )))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    fn context(&self) -> Arc<Option<DataSourceContext>> {
+        self.context.cheap_clone()
+    }
+
+    fn creation_block(&self) -> Option<BlockNumber> {
+        self.creation_block
+    }
+
+    fn is_duplicate_of(&self, other: &Self) -> bool {
+        let DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+
+            // The creation block is ignored for detection duplicate data sources.
+            creation_block: _,
+        } = self;
+
+        kind == &other.kind
+            && network == &other.network
+            && name == &other.name
+            && source == &other.source
+            && mapping.block_handlers == other.mapping.block_handlers
+            && mapping.instruction_handlers == other.mapping.instruction_handlers
+            && mapping.account_handlers == other.mapping.account_handlers
+            && context == &other.context
+    }
+
+    fn as_stored_dynamic_data_source(&self) -> StoredDynamicDataSource {
+        unreachable!("Solana subgraphs do not support templates")
+    }
+
+    fn from_stored_dynamic_data_source(
+        _template: &DataSourceTemplate,
+        _stored: StoredDynamicDataSource,
+    ) -> Result<Self, Error> {
+        Err(anyhow!("Solana subgraphs do not support templates"))
+    }
+
+    fn validate(&self, _: &semver::Version) -> Vec<Error> {
+        let mut errors = Vec::new();
+
+        if self.kind != SOLANA_KIND {
+            errors.push(anyhow!(
+                "data source has invalid `kind`, expected {} but found {}",
+                SOLANA_KIND,
+                self.kind
+            ))
+        }
+
+        let Mapping {
+            block_handlers,
+            instruction_handlers,
+            account_handlers,
+            ..
+        } = &self.mapping;
+
+        if block_handlers.is_empty()
+            && instruction_handlers.is_empty()
+            && account_handlers.is_empty()
+        {
+            errors.push(anyhow!("data source does not define any handler"));
+        }
+
+        // Validate that there is a `source` program id if there are instruction handlers
+        if self.address().is_none() && !instruction_handlers.is_empty() {
+            errors.push(SubgraphManifestValidationError::SourceAddressRequired.into());
+        };
+
+        // Validate that there are no more than one of both block handlers and instruction handlers
+        if block_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated block handlers"));
+        }
+        if instruction_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated instruction handlers"));
+        }
+
+        let mut accounts = HashSet::new();
+        for handler in account_handlers {
+            if !accounts.insert(&handler.account) {
+                errors.push(anyhow!(
+                    "data source has duplicated account handlers for account {}",
+                    handler.account
+                ));
+            }
+        }
+
+        errors
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+}
+
+impl DataSource {
+    fn from_manifest(
+        kind: String,
+        network: Option<String>,
+        name: String,
+        source: Source,
+        mapping: Mapping,
+        context: Option<DataSourceContext>,
+    ) -> Result<Self, Error> {
+        // Data sources in the manifest are created "before genesis" so they have no creation block.
+        let creation_block = None;
+
+        Ok(DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context: Arc::new(context),
+            creation_block,
+        })
+    }
+
+    fn handler_for_block(&self) -> Option<&MappingBlockHandler> {
+        self.mapping.block_handlers.first()
+    }
+
+    fn handler_for_instruction(&self) -> Option<&InstructionHandler> {
+        self.mapping.instruction_handlers.first()
+    }
+
+    fn handler_for_account(&self, account: &[u8]) -> Option<&AccountHandler> {
+        self.mapping
+            .account_handlers
+            .iter()
+            .find(|handler| handler.account.as_ref() == account)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct UnresolvedDataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: UnresolvedMapping,
+    pub context: Option<DataSourceContext>,
+}
+
+#[async_trait]
+impl blockchain::UnresolvedDataSource<Chain> for UnresolvedDataSource {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSource, Error> {
+        let UnresolvedDataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+        } = self;
+
+        let mapping = mapping.resolve(resolver, logger).await.with_context(|| {
+            format!(
+                "failed to resolve data source {} with program id {:?} and start_block {}",
+                name, source.program_id, source.start_block
+            )
+        })?;
+
+        DataSource::from_manifest(kind, network, name, source, mapping, context)
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+pub struct BaseDataSourceTemplate<M> {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub mapping: M,
+}
+
+pub type UnresolvedDataSourceTemplate = BaseDataSourceTemplate<UnresolvedMapping>;
+pub type DataSourceTemplate = BaseDataSourceTemplate<Mapping>;
+
+#[async_trait]
+impl blockchain::UnresolvedDataSourceTemplate<Chain> for UnresolvedDataSourceTemplate {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSourceTemplate, Error> {
+        let UnresolvedDataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        } = self;
+
+        let mapping = mapping
+            .resolve(resolver, logger)
+            .await
+            .with_context(|| format!("failed to resolve data source template {}", name))?;
+
+        Ok(DataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        })
+    }
+}
+
+impl blockchain::DataSourceTemplate<Chain> for DataSourceTemplate {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+
+    fn manifest_idx(&self) -> u32 {
+        unreachable!("solana does not support dynamic data sources")
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedMapping {
+    pub api_version: String,
+    pub language: String,
+    pub entities: Vec<String>,
+    #[serde(default)]
+    pub block_handlers: Vec<MappingBlockHandler>,
+    #[serde(default)]
+    pub instruction_handlers: Vec<InstructionHandler>,
+    #[serde(default)]
+    pub account_handlers: Vec<AccountHandler>,
+    pub file: Link,
+}
+
+impl UnresolvedMapping {
+    pub async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+    ) -> Result<Mapping, Error> {
+        let UnresolvedMapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            instruction_handlers,
+            account_handlers,
+            file: link,
+        } = self;
+
+        let api_version = semver::Version::parse(&api_version)?;
+
+        let module_bytes = resolver
+            .cat(logger, &link)
+            .await
+            .with_context(|| format!("failed to resolve mapping {}", link.link))?;
+
+        Ok(Mapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            instruction_handlers,
+            account_handlers,
+            runtime: Arc::new(module_bytes),
+            link,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Mapping {
+    pub api_version: semver::Version,
+    pub language: String,
+    pub entities: Vec<String>,
+    pub block_handlers: Vec<MappingBlockHandler>,
+    pub instruction_handlers: Vec<InstructionHandler>,
+    pub account_handlers: Vec<AccountHandler>,
+    pub runtime: Arc<Vec<u8>>,
+    pub link: Link,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingBlockHandler {
+    pub handler: String,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct InstructionHandler {
+    pub handler: String,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct AccountHandler {
+    pub handler: String,
+    pub account: Pubkey,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Source {
+    // A data source that does not have a program id can not have instruction handlers.
+    #[serde(default)]
+    pub(crate) program_id: Option<Pubkey>,
+    #[serde(default)]
+    pub(crate) start_block: BlockNumber,
+    pub(crate) end_block: Option<BlockNumber>,
+}
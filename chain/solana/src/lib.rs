@@ -0,0 +1,10 @@
+mod adapter;
+mod chain;
+mod codec;
+mod data_source;
+mod pubkey;
+mod runtime;
+mod trigger;
+
+pub use crate::chain::Chain;
+pub use codec::Block;
@@ -0,0 +1,99 @@
+use std::{
+    fmt::{Debug, Display, Formatter},
+    str::FromStr,
+};
+
+use graph::anyhow;
+use serde::{de::Visitor, Deserialize};
+
+/// The address of a Solana account or program, written as a base58 string
+/// in manifests
+#[derive(Clone, Hash, PartialEq, Eq)]
+pub struct Pubkey([u8; 32]);
+
+struct PubkeyVisitor;
+
+impl Debug for Pubkey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Display for Pubkey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", bs58::encode(self.0).into_string())
+    }
+}
+
+impl From<[u8; 32]> for Pubkey {
+    fn from(value: [u8; 32]) -> Self {
+        Self(value)
+    }
+}
+
+impl AsRef<[u8]> for Pubkey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl FromStr for Pubkey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s).into_vec()?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            anyhow::anyhow!("expected 32 bytes but got {}", bytes.len())
+        })?;
+        Ok(Pubkey(bytes))
+    }
+}
+
+impl<'de> Deserialize<'de> for Pubkey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(PubkeyVisitor)
+    }
+}
+
+impl<'de> Visitor<'de> for PubkeyVisitor {
+    type Value = Pubkey;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Pubkey::from_str(v).map_err(|_| {
+            serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(v),
+                &"base58 encoded public key",
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_pubkey() {
+        let key: Pubkey = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            key.to_string()
+        );
+
+        assert!("not base58 0OIl".parse::<Pubkey>().is_err());
+        // Valid base58, but too short
+        assert!("abc".parse::<Pubkey>().is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::codec;
+use crate::trigger::{AccountChange, InstructionWithInfo};
+use graph::runtime::gas::GasCounter;
+use graph::runtime::{asc_new, AscHeap, AscPtr, HostExportError, ToAscObj};
+use graph_runtime_wasm::asc_abi::class::{Array, Uint8Array};
+
+pub(crate) use super::generated::*;
+
+impl ToAscObj<AscBytesArray> for Vec<Vec<u8>> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBytesArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x.as_slice(), gas))
+            .collect::<Result<Vec<AscPtr<Uint8Array>>, _>>()?;
+        Ok(AscBytesArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscBlock> for codec::Block {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBlock, HostExportError> {
+        Ok(AscBlock {
+            slot: self.slot,
+            parent_slot: self.parent_slot,
+            height: self
+                .block_height
+                .as_ref()
+                .map(|height| height.block_height)
+                .unwrap_or_default(),
+            timestamp: self
+                .block_time
+                .as_ref()
+                .map(|time| time.timestamp)
+                .unwrap_or_default(),
+            hash: asc_new(heap, self.hash().as_slice(), gas)?,
+            previous_hash: asc_new(heap, self.previous_hash().as_slice(), gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscTransaction> for codec::ConfirmedTransaction {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscTransaction, HostExportError> {
+        let meta = self.meta.clone().unwrap_or_default();
+        let account_keys: Vec<Vec<u8>> = self
+            .account_keys()
+            .into_iter()
+            .map(|key| key.to_vec())
+            .collect();
+
+        Ok(AscTransaction {
+            fee: meta.fee,
+            signature: asc_new(heap, self.signature(), gas)?,
+            account_keys: asc_new(heap, &account_keys, gas)?,
+            log_messages: asc_new(heap, &meta.log_messages, gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscInstruction> for InstructionWithInfo {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscInstruction, HostExportError> {
+        Ok(AscInstruction {
+            index: self.index,
+            inner_index: self.inner_index.unwrap_or_default(),
+            is_inner: self.inner_index.is_some(),
+            program_id: asc_new(heap, self.program_id.as_slice(), gas)?,
+            accounts: asc_new(heap, &self.accounts, gas)?,
+            data: asc_new(heap, self.data.as_slice(), gas)?,
+            transaction: asc_new(heap, self.transaction.as_ref(), gas)?,
+            block: asc_new(heap, self.block.as_ref(), gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscAccountChange> for AccountChange {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscAccountChange, HostExportError> {
+        Ok(AscAccountChange {
+            pre_balance: self.pre_balance,
+            post_balance: self.post_balance,
+            account: asc_new(heap, self.account.as_slice(), gas)?,
+            transaction: asc_new(heap, self.transaction.as_ref(), gas)?,
+            block: asc_new(heap, self.block.as_ref(), gas)?,
+        })
+    }
+}
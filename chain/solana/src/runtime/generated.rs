@@ -0,0 +1,82 @@
+use graph::runtime::{AscIndexId, AscPtr, AscType, DeterministicHostError, IndexForAscTypeId};
+use graph::semver::Version;
+use graph_runtime_derive::AscType;
+use graph_runtime_wasm::asc_abi::class::{Array, AscString, Uint8Array};
+
+pub struct AscBytesArray(pub(crate) Array<AscPtr<Uint8Array>>);
+
+impl AscType for AscBytesArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscBytesArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaArrayBytes;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscBlock {
+    pub slot: u64,
+    pub parent_slot: u64,
+    pub height: u64,
+    pub timestamp: i64,
+    pub hash: AscPtr<Uint8Array>,
+    pub previous_hash: AscPtr<Uint8Array>,
+}
+
+impl AscIndexId for AscBlock {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaBlock;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscTransaction {
+    pub fee: u64,
+    pub signature: AscPtr<Uint8Array>,
+    pub account_keys: AscPtr<AscBytesArray>,
+    pub log_messages: AscPtr<Array<AscPtr<AscString>>>,
+}
+
+impl AscIndexId for AscTransaction {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaTransaction;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscInstruction {
+    pub index: u32,
+    pub inner_index: u32,
+    pub is_inner: bool,
+    pub program_id: AscPtr<Uint8Array>,
+    pub accounts: AscPtr<AscBytesArray>,
+    pub data: AscPtr<Uint8Array>,
+    pub transaction: AscPtr<AscTransaction>,
+    pub block: AscPtr<AscBlock>,
+}
+
+impl AscIndexId for AscInstruction {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaInstruction;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscAccountChange {
+    pub pre_balance: u64,
+    pub post_balance: u64,
+    pub account: AscPtr<Uint8Array>,
+    pub transaction: AscPtr<AscTransaction>,
+    pub block: AscPtr<AscBlock>,
+}
+
+impl AscIndexId for AscAccountChange {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaAccountChange;
+}
@@ -0,0 +1,193 @@
+use graph::blockchain::Block;
+use graph::blockchain::MappingTriggerTrait;
+use graph::blockchain::TriggerData;
+use graph::derive::CheapClone;
+use graph::prelude::BlockNumber;
+use graph::runtime::asc_new;
+use graph::runtime::gas::GasCounter;
+use graph::runtime::AscHeap;
+use graph::runtime::AscPtr;
+use graph::runtime::HostExportError;
+use graph_runtime_wasm::module::ToAscPtr;
+use std::{cmp::Ordering, sync::Arc};
+
+use crate::codec;
+
+// Logging the block is too verbose, so this strips the block from the trigger for Debug.
+impl std::fmt::Debug for SolanaTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[allow(unused)]
+        #[derive(Debug)]
+        pub enum MappingTriggerWithoutBlock {
+            Block,
+            Instruction { signature: String, index: u32 },
+            Account { signature: String, account: String },
+        }
+
+        let trigger_without_block = match self {
+            SolanaTrigger::Block(_) => MappingTriggerWithoutBlock::Block,
+            SolanaTrigger::Instruction(instruction) => MappingTriggerWithoutBlock::Instruction {
+                signature: bs58::encode(instruction.transaction.signature()).into_string(),
+                index: instruction.index,
+            },
+            SolanaTrigger::Account(change) => MappingTriggerWithoutBlock::Account {
+                signature: bs58::encode(change.transaction.signature()).into_string(),
+                account: bs58::encode(&change.account).into_string(),
+            },
+        };
+
+        write!(f, "{:?}", trigger_without_block)
+    }
+}
+
+impl ToAscPtr for SolanaTrigger {
+    fn to_asc_ptr<H: AscHeap>(
+        self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<()>, HostExportError> {
+        Ok(match self {
+            SolanaTrigger::Block(block) => asc_new(heap, block.as_ref(), gas)?.erase(),
+            SolanaTrigger::Instruction(instruction) => {
+                asc_new(heap, instruction.as_ref(), gas)?.erase()
+            }
+            SolanaTrigger::Account(change) => asc_new(heap, change.as_ref(), gas)?.erase(),
+        })
+    }
+}
+
+#[derive(Clone, CheapClone)]
+pub enum SolanaTrigger {
+    Block(Arc<codec::Block>),
+    Instruction(Arc<InstructionWithInfo>),
+    Account(Arc<AccountChange>),
+}
+
+impl PartialEq for SolanaTrigger {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Block(a), Self::Block(b)) => a == b,
+            (Self::Instruction(a), Self::Instruction(b)) => {
+                a.transaction.signature() == b.transaction.signature()
+                    && a.index == b.index
+                    && a.inner_index == b.inner_index
+            }
+            (Self::Account(a), Self::Account(b)) => {
+                a.transaction.signature() == b.transaction.signature() && a.account == b.account
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for SolanaTrigger {}
+
+impl SolanaTrigger {
+    fn block(&self) -> &codec::Block {
+        match self {
+            SolanaTrigger::Block(block) => block,
+            SolanaTrigger::Instruction(instruction) => &instruction.block,
+            SolanaTrigger::Account(change) => &change.block,
+        }
+    }
+
+    pub fn block_number(&self) -> BlockNumber {
+        self.block().number()
+    }
+
+    fn error_context(&self) -> std::string::String {
+        let block = self.block();
+        match self {
+            SolanaTrigger::Block(..) => {
+                format!("Block #{} ({})", block.number(), block.blockhash)
+            }
+            SolanaTrigger::Instruction(instruction) => {
+                format!(
+                    "Instruction #{} of program {}, tx {}, block #{} ({})",
+                    instruction.index,
+                    bs58::encode(&instruction.program_id).into_string(),
+                    bs58::encode(instruction.transaction.signature()).into_string(),
+                    block.number(),
+                    block.blockhash
+                )
+            }
+            SolanaTrigger::Account(change) => {
+                format!(
+                    "Account {}, tx {}, block #{} ({})",
+                    bs58::encode(&change.account).into_string(),
+                    bs58::encode(change.transaction.signature()).into_string(),
+                    block.number(),
+                    block.blockhash
+                )
+            }
+        }
+    }
+}
+
+impl Ord for SolanaTrigger {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            // Keep the order when comparing two block triggers
+            (Self::Block(..), Self::Block(..)) => Ordering::Equal,
+
+            // Block triggers always come last
+            (Self::Block(..), _) => Ordering::Greater,
+            (_, Self::Block(..)) => Ordering::Less,
+
+            // Instruction and account triggers are created in the order of
+            // the transactions and instructions in the block, and we keep
+            // that order
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+impl PartialOrd for SolanaTrigger {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl TriggerData for SolanaTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+
+    fn address_match(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+impl MappingTriggerTrait for SolanaTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+}
+
+/// An instruction of a successful transaction, either one of the
+/// instructions of the transaction itself or one that a program invoked
+/// while executing them
+pub struct InstructionWithInfo {
+    pub program_id: Vec<u8>,
+    pub accounts: Vec<Vec<u8>>,
+    pub data: Vec<u8>,
+    /// The position of the instruction in the transaction, or of the
+    /// instruction that invoked it for inner instructions
+    pub index: u32,
+    /// The position among the instructions that the instruction at `index`
+    /// invoked; `None` for instructions of the transaction itself
+    pub inner_index: Option<u32>,
+    pub transaction: Arc<codec::ConfirmedTransaction>,
+    pub block: Arc<codec::Block>,
+}
+
+/// An account that a successful transaction could write to. Firehose
+/// blocks do not contain account data, so this only has the lamports of
+/// the account before and after the transaction
+pub struct AccountChange {
+    pub account: Vec<u8>,
+    pub pre_balance: u64,
+    pub post_balance: u64,
+    pub transaction: Arc<codec::ConfirmedTransaction>,
+    pub block: Arc<codec::Block>,
+}
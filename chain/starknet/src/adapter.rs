@@ -1,27 +1,156 @@
-use graph::blockchain::{EmptyNodeCapabilities, TriggerFilter as TriggerFilterTrait};
+use graph::blockchain::{self as bc, EmptyNodeCapabilities, TriggerFilter as TriggerFilterTrait};
+use std::collections::HashSet;
 
 use crate::{
+    codec,
     data_source::{DataSource, DataSourceTemplate},
+    felt::Felt,
     Chain,
 };
 
-#[derive(Default, Clone)]
-pub struct TriggerFilter;
+#[derive(Clone, Debug, Default)]
+pub struct TriggerFilter {
+    pub(crate) block_filter: StarknetBlockFilter,
+    pub(crate) event_filter: StarknetEventFilter,
+}
 
 impl TriggerFilterTrait<Chain> for TriggerFilter {
-    #[allow(unused)]
-    fn extend_with_template(&mut self, data_source: impl Iterator<Item = DataSourceTemplate>) {
-        todo!()
-    }
+    fn extend_with_template(&mut self, _data_source: impl Iterator<Item = DataSourceTemplate>) {}
 
-    #[allow(unused)]
-    fn extend<'a>(&mut self, data_sources: impl Iterator<Item = &'a DataSource> + Clone) {}
+    fn extend<'a>(&mut self, data_sources: impl Iterator<Item = &'a DataSource> + Clone) {
+        let TriggerFilter {
+            block_filter,
+            event_filter,
+        } = self;
+
+        block_filter.extend(StarknetBlockFilter::from_data_sources(data_sources.clone()));
+        event_filter.extend(StarknetEventFilter::from_data_sources(data_sources));
+    }
 
     fn node_capabilities(&self) -> EmptyNodeCapabilities<Chain> {
-        todo!()
+        bc::EmptyNodeCapabilities::default()
     }
 
     fn to_firehose_filter(self) -> Vec<prost_types::Any> {
-        todo!()
+        vec![]
+    }
+}
+
+/// Matches events by the address of the contract that emitted them and
+/// their first key, the selector of the event
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StarknetEventFilter {
+    events: HashSet<(Felt, Felt)>,
+}
+
+impl StarknetEventFilter {
+    pub fn matches(&self, event: &codec::Event) -> bool {
+        let key = match event.keys.first().and_then(|key| Felt::from_padded(key)) {
+            Some(key) => key,
+            None => return false,
+        };
+        match Felt::from_padded(&event.from_addr) {
+            Some(address) => self.events.contains(&(address, key)),
+            None => false,
+        }
+    }
+
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        let events = iter
+            .into_iter()
+            .filter_map(|ds| {
+                ds.source
+                    .address
+                    .as_ref()
+                    .map(|address| (address, &ds.mapping.event_handlers))
+            })
+            .flat_map(|(address, handlers)| {
+                handlers
+                    .iter()
+                    .map(move |handler| (address.clone(), handler.event_selector.clone()))
+            })
+            .collect();
+
+        Self { events }
+    }
+
+    pub fn extend(&mut self, other: StarknetEventFilter) {
+        self.events.extend(other.events);
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StarknetBlockFilter {
+    pub trigger_every_block: bool,
+}
+
+impl StarknetBlockFilter {
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            trigger_every_block: iter
+                .into_iter()
+                .any(|ds| ds.mapping.block_handler.is_some()),
+        }
+    }
+
+    pub fn extend(&mut self, other: StarknetBlockFilter) {
+        self.trigger_every_block = self.trigger_every_block || other.trigger_every_block;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        codec,
+        data_source::{DataSource, Mapping, MappingEventHandler, Source, STARKNET_KIND},
+    };
+
+    use super::StarknetEventFilter;
+
+    #[test]
+    fn event_filter_matches_address_and_selector() {
+        let ds = new_data_source([1u8; 32], [2u8; 32]);
+        let filter = StarknetEventFilter::from_data_sources(vec![&ds]);
+
+        assert!(filter.matches(&event(vec![1u8; 32], vec![2u8; 32])));
+        assert!(!filter.matches(&event(vec![1u8; 32], vec![3u8; 32])));
+        assert!(!filter.matches(&event(vec![3u8; 32], vec![2u8; 32])));
+        assert!(!filter.matches(&event(vec![1u8; 32], vec![])));
+
+        // Felts in blocks may have their leading zeros stripped
+        let ds = new_data_source([0u8; 32], [0u8; 32]);
+        let filter = StarknetEventFilter::from_data_sources(vec![&ds]);
+        assert!(filter.matches(&event(vec![], vec![0u8; 2])));
+    }
+
+    fn event(from_addr: Vec<u8>, key: Vec<u8>) -> codec::Event {
+        codec::Event {
+            from_addr,
+            keys: if key.is_empty() { vec![] } else { vec![key] },
+            data: vec![],
+        }
+    }
+
+    fn new_data_source(address: [u8; 32], selector: [u8; 32]) -> DataSource {
+        DataSource {
+            kind: STARKNET_KIND.to_string(),
+            network: "starknet-mainnet".into(),
+            name: "ds".to_string(),
+            source: Source {
+                start_block: 0,
+                end_block: None,
+                address: Some(address.into()),
+            },
+            mapping: Mapping {
+                block_handler: None,
+                event_handlers: vec![MappingEventHandler {
+                    handler: "handleEvent".into(),
+                    event_selector: selector.into(),
+                }],
+                runtime: Arc::new(vec![]),
+            },
+        }
     }
 }
@@ -1,5 +1,5 @@
 use graph::{
-    anyhow::Result,
+    anyhow::{anyhow, Result},
     blockchain::{
         block_stream::{
             BlockStream, BlockStreamBuilder, BlockStreamEvent, BlockWithTriggers, FirehoseCursor,
@@ -9,9 +9,10 @@ use graph::{
         client::ChainClient,
         firehose_block_ingestor::FirehoseBlockIngestor,
         firehose_block_stream::FirehoseBlockStream,
+        polling_block_stream::PollingBlockStream,
         BasicBlockchainBuilder, Block, BlockIngestor, BlockPtr, Blockchain, BlockchainBuilder,
-        BlockchainKind, EmptyNodeCapabilities, IngestorError, NoopDecoderHook, NoopRuntimeAdapter,
-        RuntimeAdapter as RuntimeAdapterTrait,
+        BlockchainKind, ChainHeadUpdateListener, EmptyNodeCapabilities, IngestionMode,
+        IngestorError, NoopDecoderHook, NoopRuntimeAdapter, RuntimeAdapter as RuntimeAdapterTrait,
     },
     cheap_clone::CheapClone,
     components::{
@@ -24,13 +25,13 @@ use graph::{
     futures03::future::TryFutureExt,
     prelude::{
         async_trait, BlockHash, BlockNumber, ChainStore, Error, Logger, LoggerFactory,
-        MetricsRegistry,
+        MetricsRegistry, NodeId,
     },
     schema::InputSchema,
     slog::o,
 };
 use prost::Message;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
     adapter::TriggerFilter,
@@ -38,9 +39,19 @@ use crate::{
     data_source::{
         DataSource, DataSourceTemplate, UnresolvedDataSource, UnresolvedDataSourceTemplate,
     },
+    ingestor::StarknetRpcBlockIngestor,
+    rpc::StarknetRpcClients,
     trigger::{StarknetBlockTrigger, StarknetEventTrigger, StarknetTrigger},
 };
 
+/// The sequencer rarely reverts blocks that it accepted, so block streams
+/// over JSON-RPC only treat the most recent blocks as reversible
+const RPC_REORG_THRESHOLD: BlockNumber = 10;
+/// Block streams over JSON-RPC fetch one block per request, so they scan
+/// small ranges
+const RPC_MAX_BLOCK_RANGE_SIZE: BlockNumber = 100;
+const RPC_TARGET_TRIGGERS_PER_BLOCK_RANGE: u64 = 100;
+
 pub struct Chain {
     logger_factory: LoggerFactory,
     name: ChainId,
@@ -48,6 +59,14 @@ pub struct Chain {
     chain_store: Arc<dyn ChainStore>,
     metrics_registry: Arc<MetricsRegistry>,
     block_stream_builder: Arc<dyn BlockStreamBuilder<Self>>,
+    /// Set for chains that are indexed over JSON-RPC
+    rpc: Option<RpcSettings>,
+}
+
+struct RpcSettings {
+    node_id: NodeId,
+    chain_head_update_listener: Arc<dyn ChainHeadUpdateListener>,
+    polling_interval: Duration,
 }
 
 pub struct StarknetStreamBuilder;
@@ -57,7 +76,12 @@ pub struct FirehoseMapper {
     filter: Arc<TriggerFilter>,
 }
 
-pub struct TriggersAdapter;
+pub struct TriggersAdapter {
+    logger: Logger,
+    /// The providers to fetch blocks from when the chain is indexed over
+    /// JSON-RPC
+    rpc: Option<StarknetRpcClients>,
+}
 
 #[async_trait]
 impl BlockchainBuilder<Chain> for BasicBlockchainBuilder {
@@ -69,10 +93,30 @@ impl BlockchainBuilder<Chain> for BasicBlockchainBuilder {
             client: Arc::new(ChainClient::new_firehose(self.firehose_endpoints)),
             metrics_registry: self.metrics_registry,
             block_stream_builder: Arc::new(StarknetStreamBuilder {}),
+            rpc: None,
         }
     }
 }
 
+impl Chain {
+    /// Index the chain over the JSON-RPC API of `rpc` instead of Firehose
+    pub fn with_rpc(
+        mut self,
+        rpc: StarknetRpcClients,
+        node_id: NodeId,
+        chain_head_update_listener: Arc<dyn ChainHeadUpdateListener>,
+        polling_interval: Duration,
+    ) -> Self {
+        self.client = Arc::new(ChainClient::new_rpc(rpc));
+        self.rpc = Some(RpcSettings {
+            node_id,
+            chain_head_update_listener,
+            polling_interval,
+        });
+        self
+    }
+}
+
 impl std::fmt::Debug for Chain {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "chain: starknet")
@@ -83,7 +127,7 @@ impl std::fmt::Debug for Chain {
 impl Blockchain for Chain {
     const KIND: BlockchainKind = BlockchainKind::Starknet;
 
-    type Client = ();
+    type Client = StarknetRpcClients;
     type Block = codec::Block;
     type DataSource = DataSource;
     type UnresolvedDataSource = UnresolvedDataSource;
@@ -103,11 +147,14 @@ impl Blockchain for Chain {
 
     fn triggers_adapter(
         &self,
-        _log: &DeploymentLocator,
+        loc: &DeploymentLocator,
         _capabilities: &Self::NodeCapabilities,
         _unified_api_version: UnifiedMappingApiVersion,
     ) -> Result<Arc<dyn TriggersAdapterTrait<Self>>, Error> {
-        Ok(Arc::new(TriggersAdapter))
+        Ok(Arc::new(TriggersAdapter {
+            logger: self.logger_factory.subgraph_logger(loc),
+            rpc: self.client.rpc().ok().cloned(),
+        }))
     }
 
     async fn new_block_stream(
@@ -117,7 +164,22 @@ impl Blockchain for Chain {
         start_blocks: Vec<BlockNumber>,
         filter: Arc<Self::TriggerFilter>,
         unified_api_version: UnifiedMappingApiVersion,
+        _ingestion: IngestionMode,
     ) -> Result<Box<dyn BlockStream<Self>>, Error> {
+        if !self.client.is_firehose() {
+            return self
+                .block_stream_builder
+                .build_polling(
+                    self,
+                    deployment,
+                    start_blocks,
+                    store.block_ptr(),
+                    filter,
+                    unified_api_version,
+                )
+                .await;
+        }
+
         self.block_stream_builder
             .build_firehose(
                 self,
@@ -152,6 +214,10 @@ impl Blockchain for Chain {
         logger: &Logger,
         number: BlockNumber,
     ) -> Result<BlockPtr, IngestorError> {
+        if let Ok(rpc) = self.client.rpc() {
+            return Ok(rpc.block_by_number(number).await?.ptr());
+        }
+
         let firehose_endpoint = self.client.firehose_endpoint().await?;
 
         firehose_endpoint
@@ -171,6 +237,17 @@ impl Blockchain for Chain {
     }
 
     async fn block_ingestor(&self) -> Result<Box<dyn BlockIngestor>> {
+        if let (Ok(rpc), Some(settings)) = (self.client.rpc(), &self.rpc) {
+            return Ok(Box::new(StarknetRpcBlockIngestor::new(
+                self.chain_store.cheap_clone(),
+                rpc.clone(),
+                self.logger_factory
+                    .component_logger("StarknetRpcBlockIngestor", None),
+                self.name.clone(),
+                settings.polling_interval,
+            )));
+        }
+
         let ingestor = FirehoseBlockIngestor::<crate::Block, Self>::new(
             self.chain_store.cheap_clone(),
             self.chain_client(),
@@ -235,14 +312,46 @@ impl BlockStreamBuilder<Chain> for StarknetStreamBuilder {
 
     async fn build_polling(
         &self,
-        _chain: &Chain,
-        _deployment: DeploymentLocator,
-        _start_blocks: Vec<BlockNumber>,
-        _subgraph_current_block: Option<BlockPtr>,
-        _filter: Arc<TriggerFilter>,
-        _unified_api_version: UnifiedMappingApiVersion,
+        chain: &Chain,
+        deployment: DeploymentLocator,
+        start_blocks: Vec<BlockNumber>,
+        subgraph_current_block: Option<BlockPtr>,
+        filter: Arc<TriggerFilter>,
+        unified_api_version: UnifiedMappingApiVersion,
     ) -> Result<Box<dyn BlockStream<Chain>>> {
-        panic!("StarkNet does not support polling block stream")
+        let settings = chain
+            .rpc
+            .as_ref()
+            .ok_or_else(|| anyhow!("chain {} has no Starknet RPC providers", chain.name))?;
+        let adapter = chain.triggers_adapter(
+            &deployment,
+            &EmptyNodeCapabilities::default(),
+            unified_api_version.clone(),
+        )?;
+
+        let logger = chain
+            .logger_factory
+            .subgraph_logger(&deployment)
+            .new(o!("component" => "BlockStream"));
+        let chain_head_update_stream = settings
+            .chain_head_update_listener
+            .subscribe(chain.name.to_string(), logger.clone());
+
+        Ok(Box::new(PollingBlockStream::new(
+            chain.chain_store(),
+            chain_head_update_stream,
+            adapter,
+            settings.node_id.clone(),
+            deployment.hash,
+            filter,
+            start_blocks,
+            RPC_REORG_THRESHOLD,
+            logger,
+            RPC_MAX_BLOCK_RANGE_SIZE,
+            RPC_TARGET_TRIGGERS_PER_BLOCK_RANGE,
+            unified_api_version,
+            subgraph_current_block,
+        )))
     }
 }
 
@@ -354,19 +463,39 @@ impl FirehoseMapperTrait<Chain> for FirehoseMapper {
     }
 }
 
+impl TriggersAdapter {
+    fn rpc(&self) -> Result<&StarknetRpcClients, Error> {
+        self.rpc
+            .as_ref()
+            .ok_or_else(|| anyhow!("blocks can only be fetched from Starknet RPC providers"))
+    }
+}
+
 #[async_trait]
 impl TriggersAdapterTrait<Chain> for TriggersAdapter {
     // Return the block that is `offset` blocks before the block pointed to
-    // by `ptr` from the local cache. An offset of 0 means the block itself,
-    // an offset of 1 means the block's parent etc. If the block is not in
-    // the local cache, return `None`
+    // by `ptr`, or the child of `root` if that comes first. An offset of 0
+    // means the block itself, an offset of 1 means the block's parent etc.
     async fn ancestor_block(
         &self,
-        _ptr: BlockPtr,
-        _offset: BlockNumber,
-        _root: Option<BlockHash>,
+        ptr: BlockPtr,
+        offset: BlockNumber,
+        root: Option<BlockHash>,
     ) -> Result<Option<codec::Block>, Error> {
-        panic!("Should never be called since FirehoseBlockStream cannot resolve it")
+        let rpc = self.rpc()?;
+
+        let mut block = rpc.block_by_hash(&ptr.hash).await?;
+        for _ in 0..offset {
+            let parent = match block.parent_ptr() {
+                Some(parent) => parent,
+                None => return Ok(None),
+            };
+            if root.as_ref() == Some(&parent.hash) {
+                break;
+            }
+            block = rpc.block_by_hash(&parent.hash).await?;
+        }
+        Ok(Some(block))
     }
 
     // Returns a sequence of blocks in increasing order of block number.
@@ -377,14 +506,23 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
     // `step_size` is the suggested number blocks to be scanned.
     async fn scan_triggers(
         &self,
-        _from: BlockNumber,
-        _to: BlockNumber,
-        _filter: &crate::adapter::TriggerFilter,
+        from: BlockNumber,
+        to: BlockNumber,
+        filter: &crate::adapter::TriggerFilter,
     ) -> Result<(Vec<BlockWithTriggers<Chain>>, BlockNumber), Error> {
-        panic!("Should never be called since not used by FirehoseBlockStream")
+        let rpc = self.rpc()?;
+
+        let mut blocks = Vec::new();
+        for number in from..=to {
+            let block = rpc.block_by_number(number).await?;
+            let block = self.triggers_in_block(&self.logger, block, filter).await?;
+            if block.trigger_count() > 0 || number == to {
+                blocks.push(block);
+            }
+        }
+        Ok((blocks, to))
     }
 
-    #[allow(unused)]
     async fn triggers_in_block(
         &self,
         logger: &Logger,
@@ -396,37 +534,49 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
         let mut triggers: Vec<_> = shared_block
             .transactions
             .iter()
-            .flat_map(|transaction| -> Vec<StarknetTrigger> {
+            .enumerate()
+            .flat_map(|(transaction_index, transaction)| -> Vec<StarknetTrigger> {
                 let transaction = Arc::new(transaction.clone());
                 transaction
                     .events
                     .iter()
-                    .map(|event| {
+                    .enumerate()
+                    .filter(|(_, event)| filter.event_filter.matches(event))
+                    .map(|(event_index, event)| {
                         StarknetTrigger::Event(StarknetEventTrigger {
                             event: Arc::new(event.clone()),
                             block: shared_block.clone(),
                             transaction: transaction.clone(),
+                            transaction_index: transaction_index as u32,
+                            event_index: event_index as u32,
                         })
                     })
                     .collect()
             })
             .collect();
 
-        triggers.push(StarknetTrigger::Block(StarknetBlockTrigger {
-            block: shared_block,
-        }));
+        if filter.block_filter.trigger_every_block {
+            triggers.push(StarknetTrigger::Block(StarknetBlockTrigger {
+                block: shared_block,
+            }));
+        }
 
         Ok(BlockWithTriggers::new(block, triggers, logger))
     }
 
     /// Return `true` if the block with the given hash and number is on the
     /// main chain, i.e., the chain going back from the current chain head.
-    async fn is_on_main_chain(&self, _ptr: BlockPtr) -> Result<bool, Error> {
-        panic!("Should never be called since not used by FirehoseBlockStream")
+    async fn is_on_main_chain(&self, ptr: BlockPtr) -> Result<bool, Error> {
+        let block = self.rpc()?.block_by_number(ptr.number).await?;
+        Ok(block.ptr() == ptr)
     }
 
     /// Get pointer to parent of `block`. This is called when reverting `block`.
     async fn parent_ptr(&self, block: &BlockPtr) -> Result<Option<BlockPtr>, Error> {
+        if let Ok(rpc) = self.rpc() {
+            return Ok(rpc.block_by_hash(&block.hash).await?.parent_ptr());
+        }
+
         // Panics if `block` is genesis.
         // But that's ok since this is only called when reverting `block`.
         Ok(Some(BlockPtr {
@@ -253,11 +253,11 @@ impl DataSource {
     /// if event.fromAddr matches the source address. Note this only supports the default
     /// Starknet behavior of one key per event.
     fn handler_for_event(&self, event: &StarknetEventTrigger) -> Option<MappingEventHandler> {
-        let event_key: Felt = Self::pad_to_32_bytes(event.event.keys.first()?)?.into();
+        let event_key = Felt::from_padded(event.event.keys.first()?)?;
 
         // Always padding first here seems fine as we expect most sources to define an address
         // filter anyways. Alternatively we can use lazy init here, which seems unnecessary.
-        let event_from_addr: Felt = Self::pad_to_32_bytes(&event.event.from_addr)?.into();
+        let event_from_addr = Felt::from_padded(&event.event.from_addr)?;
 
         return self
             .mapping
@@ -276,18 +276,6 @@ impl DataSource {
             })
             .cloned();
     }
-
-    /// We need to pad incoming event selectors and addresses to 32 bytes as our data source uses
-    /// padded 32 bytes.
-    fn pad_to_32_bytes(slice: &[u8]) -> Option<[u8; 32]> {
-        if slice.len() > 32 {
-            None
-        } else {
-            let mut buffer = [0u8; 32];
-            buffer[(32 - slice.len())..].copy_from_slice(slice);
-            Some(buffer)
-        }
-    }
 }
 
 #[async_trait]
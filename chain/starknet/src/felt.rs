@@ -8,7 +8,7 @@ use serde::{de::Visitor, Deserialize};
 
 /// Represents the primitive `FieldElement` type used in Starknet. Each `FieldElement` is 252-bit
 /// in size.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Felt([u8; 32]);
 
 struct FeltVisitor;
@@ -25,6 +25,21 @@ impl From<[u8; 32]> for Felt {
     }
 }
 
+impl Felt {
+    /// Felts in blocks are big-endian and may have leading zeros stripped,
+    /// so they are padded to 32 bytes before comparing them with felts from
+    /// the manifest
+    pub fn from_padded(slice: &[u8]) -> Option<Self> {
+        if slice.len() > 32 {
+            None
+        } else {
+            let mut buffer = [0u8; 32];
+            buffer[(32 - slice.len())..].copy_from_slice(slice);
+            Some(Felt(buffer))
+        }
+    }
+}
+
 impl AsRef<[u8]> for Felt {
     fn as_ref(&self) -> &[u8] {
         &self.0
@@ -0,0 +1,79 @@
+use graph::{
+    blockchain::{BlockIngestor, BlockchainKind},
+    cheap_clone::CheapClone,
+    components::adapter::ChainId,
+    prelude::{async_trait, error, info, tokio, ChainStore, Error, Logger},
+};
+use std::{sync::Arc, time::Duration};
+
+use crate::rpc::StarknetRpcClients;
+
+/// Keeps the chain head of a chain that is indexed over JSON-RPC up to
+/// date. Only the latest block is stored; block streams fetch the blocks
+/// they process themselves
+pub struct StarknetRpcBlockIngestor {
+    chain_store: Arc<dyn ChainStore>,
+    rpc: StarknetRpcClients,
+    logger: Logger,
+    chain_name: ChainId,
+    polling_interval: Duration,
+}
+
+impl StarknetRpcBlockIngestor {
+    pub fn new(
+        chain_store: Arc<dyn ChainStore>,
+        rpc: StarknetRpcClients,
+        logger: Logger,
+        chain_name: ChainId,
+        polling_interval: Duration,
+    ) -> Self {
+        Self {
+            chain_store,
+            rpc,
+            logger,
+            chain_name,
+            polling_interval,
+        }
+    }
+
+    async fn do_poll(&self) -> Result<(), Error> {
+        let head = self.chain_store.cheap_clone().chain_head_ptr().await?;
+        let latest = self.rpc.block_number().await?;
+
+        match &head {
+            // An ingestor never goes back, not even when a provider does
+            Some(head) if latest <= head.number => return Ok(()),
+            Some(_) => (),
+            None => info!(self.logger, "Starting to ingest blocks"; "block_number" => latest),
+        }
+
+        let block = self.rpc.block_by_number(latest).await?;
+        self.chain_store
+            .cheap_clone()
+            .set_chain_head(Arc::new(block), String::new())
+            .await
+    }
+}
+
+#[async_trait]
+impl BlockIngestor for StarknetRpcBlockIngestor {
+    async fn run(self: Box<Self>) {
+        loop {
+            if let Err(e) = self.do_poll().await {
+                error!(
+                    self.logger,
+                    "Trying again after block polling failed: {:#}", e
+                );
+            }
+            tokio::time::sleep(self.polling_interval).await;
+        }
+    }
+
+    fn network_name(&self) -> ChainId {
+        self.chain_name.clone()
+    }
+
+    fn kind(&self) -> BlockchainKind {
+        BlockchainKind::Starknet
+    }
+}
@@ -3,8 +3,11 @@ mod chain;
 pub mod codec;
 mod data_source;
 mod felt;
+mod ingestor;
+mod rpc;
 mod runtime;
 mod trigger;
 
 pub use crate::chain::{Chain, StarknetStreamBuilder};
+pub use crate::rpc::{StarknetRpcClient, StarknetRpcClients};
 pub use codec::Block;
@@ -0,0 +1,263 @@
+//! Fetch blocks from the JSON-RPC API of Starknet nodes, for chains that
+//! are indexed without Firehose
+use graph::{
+    anyhow::{anyhow, bail, Context},
+    blockchain::BlockHash,
+    http::HeaderMap,
+    prelude::{
+        reqwest,
+        serde_json::{self, json, Value},
+        BlockNumber, Deserialize, Error,
+    },
+    url::Url,
+};
+use std::sync::Arc;
+
+use crate::{codec, felt::Felt};
+
+/// A Starknet JSON-RPC provider. Blocks are fetched with
+/// `starknet_getBlockWithReceipts`, which nodes support from version 0.7
+/// of the API
+#[derive(Debug)]
+pub struct StarknetRpcClient {
+    provider: String,
+    url: Url,
+    http: reqwest::Client,
+}
+
+impl StarknetRpcClient {
+    pub fn new(provider: impl Into<String>, url: Url, headers: HeaderMap) -> Result<Self, Error> {
+        let http = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()?;
+
+        Ok(Self {
+            provider: provider.into(),
+            url,
+            http,
+        })
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut response: Value = self
+            .http
+            .post(self.url.clone())
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(error) = response.get("error") {
+            bail!("{} failed: {}", method, error);
+        }
+        match response.get_mut("result") {
+            Some(result) => Ok(result.take()),
+            None => bail!("{} returned neither a result nor an error", method),
+        }
+    }
+
+    /// The number of the latest accepted block
+    pub async fn block_number(&self) -> Result<BlockNumber, Error> {
+        let number: u64 =
+            serde_json::from_value(self.call("starknet_blockNumber", json!([])).await?)?;
+        Ok(BlockNumber::try_from(number)?)
+    }
+
+    pub async fn block_by_number(&self, number: BlockNumber) -> Result<codec::Block, Error> {
+        self.block(json!({ "block_number": number })).await
+    }
+
+    pub async fn block_by_hash(&self, hash: &BlockHash) -> Result<codec::Block, Error> {
+        self.block(json!({ "block_hash": format!("0x{}", hash.hash_hex()) }))
+            .await
+    }
+
+    async fn block(&self, block_id: Value) -> Result<codec::Block, Error> {
+        let block = self
+            .call("starknet_getBlockWithReceipts", json!([block_id]))
+            .await?;
+        let block: RpcBlock = serde_json::from_value(block)
+            .with_context(|| format!("invalid block from provider {}", self.provider))?;
+        block.try_into()
+    }
+}
+
+/// The RPC providers of a chain. Requests go to the first provider and
+/// only move on to the next one when a request fails
+#[derive(Clone, Debug)]
+pub struct StarknetRpcClients {
+    clients: Vec<Arc<StarknetRpcClient>>,
+}
+
+impl StarknetRpcClients {
+    pub fn new(clients: Vec<Arc<StarknetRpcClient>>) -> Self {
+        Self { clients }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    async fn first_ok<'a, T, F, Fut>(&'a self, f: F) -> Result<T, Error>
+    where
+        F: Fn(&'a StarknetRpcClient) -> Fut,
+        Fut: std::future::Future<Output = Result<T, Error>>,
+    {
+        let mut last_error = anyhow!("no Starknet RPC providers");
+        for client in &self.clients {
+            match f(client).await {
+                Ok(value) => return Ok(value),
+                Err(e) => last_error = e.context(format!("provider {} failed", client.provider())),
+            }
+        }
+        Err(last_error)
+    }
+
+    pub async fn block_number(&self) -> Result<BlockNumber, Error> {
+        self.first_ok(|client| client.block_number()).await
+    }
+
+    pub async fn block_by_number(&self, number: BlockNumber) -> Result<codec::Block, Error> {
+        self.first_ok(move |client| client.block_by_number(number))
+            .await
+    }
+
+    pub async fn block_by_hash(&self, hash: &BlockHash) -> Result<codec::Block, Error> {
+        self.first_ok(move |client| client.block_by_hash(hash))
+            .await
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcBlock {
+    block_hash: Felt,
+    parent_hash: Felt,
+    block_number: u64,
+    timestamp: u64,
+    transactions: Vec<RpcTransactionWithReceipt>,
+}
+
+#[derive(Deserialize)]
+struct RpcTransactionWithReceipt {
+    receipt: RpcReceipt,
+}
+
+#[derive(Deserialize)]
+struct RpcReceipt {
+    r#type: String,
+    transaction_hash: Felt,
+    #[serde(default)]
+    events: Vec<RpcEvent>,
+}
+
+#[derive(Deserialize)]
+struct RpcEvent {
+    from_address: Felt,
+    keys: Vec<Felt>,
+    data: Vec<Felt>,
+}
+
+impl TryFrom<RpcBlock> for codec::Block {
+    type Error = Error;
+
+    fn try_from(block: RpcBlock) -> Result<Self, Error> {
+        let transactions = block
+            .transactions
+            .into_iter()
+            .map(|tx| {
+                let receipt = tx.receipt;
+                let r#type = match receipt.r#type.as_str() {
+                    "DEPLOY" => codec::TransactionType::Deploy,
+                    "INVOKE" => codec::TransactionType::InvokeFunction,
+                    "DECLARE" => codec::TransactionType::Declare,
+                    "L1_HANDLER" => codec::TransactionType::L1Handler,
+                    "DEPLOY_ACCOUNT" => codec::TransactionType::DeployAccount,
+                    other => bail!("unknown transaction type {}", other),
+                };
+                let felts = |felts: Vec<Felt>| -> Vec<Vec<u8>> {
+                    felts.iter().map(|felt| felt.as_ref().to_vec()).collect()
+                };
+
+                Ok(codec::Transaction {
+                    r#type: r#type as i32,
+                    hash: receipt.transaction_hash.as_ref().to_vec(),
+                    events: receipt
+                        .events
+                        .into_iter()
+                        .map(|event| codec::Event {
+                            from_addr: event.from_address.as_ref().to_vec(),
+                            keys: felts(event.keys),
+                            data: felts(event.data),
+                        })
+                        .collect(),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(codec::Block {
+            height: block.block_number,
+            hash: block.block_hash.as_ref().to_vec(),
+            prev_hash: block.parent_hash.as_ref().to_vec(),
+            timestamp: block.timestamp,
+            transactions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::prelude::serde_json;
+
+    use super::RpcBlock;
+    use crate::codec;
+
+    #[test]
+    fn block_from_rpc() {
+        let block: RpcBlock = serde_json::from_str(
+            r#"{
+                "status": "ACCEPTED_ON_L2",
+                "block_hash": "0x2",
+                "parent_hash": "0x1",
+                "block_number": 2,
+                "timestamp": 1700000000,
+                "transactions": [{
+                    "transaction": { "type": "INVOKE", "version": "0x1" },
+                    "receipt": {
+                        "type": "INVOKE",
+                        "transaction_hash": "0xabc",
+                        "events": [{ "from_address": "0x10", "keys": ["0x20"], "data": ["0x1", "0x0"] }]
+                    }
+                }]
+            }"#,
+        )
+        .unwrap();
+        let block = codec::Block::try_from(block).unwrap();
+
+        let felt = |byte: u8| {
+            let mut felt = vec![0u8; 32];
+            felt[31] = byte;
+            felt
+        };
+        assert_eq!(2, block.height);
+        assert_eq!(felt(2), block.hash);
+        assert_eq!(felt(1), block.prev_hash);
+
+        let tx = &block.transactions[0];
+        assert_eq!(codec::TransactionType::InvokeFunction as i32, tx.r#type);
+        assert_eq!(felt(0x10), tx.events[0].from_addr);
+        assert_eq!(vec![felt(0x20)], tx.events[0].keys);
+        assert_eq!(vec![felt(1), felt(0)], tx.events[0].data);
+    }
+}
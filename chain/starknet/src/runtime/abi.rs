@@ -6,6 +6,7 @@ use graph_runtime_wasm::asc_abi::class::{Array, AscEnum, EnumPayload};
 
 use crate::{
     codec,
+    felt::Felt,
     trigger::{StarknetBlockTrigger, StarknetEventTrigger},
 };
 
@@ -96,11 +97,24 @@ impl ToAscObj<AscEvent> for StarknetEventTrigger {
         gas: &GasCounter,
     ) -> Result<AscEvent, HostExportError> {
         Ok(AscEvent {
-            from_addr: asc_new(heap, self.event.from_addr.as_slice(), gas)?,
-            keys: asc_new(heap, &self.event.keys, gas)?,
-            data: asc_new(heap, &self.event.data, gas)?,
+            from_addr: asc_new(heap, felt(&self.event.from_addr).as_slice(), gas)?,
+            keys: asc_new(heap, &felts(&self.event.keys), gas)?,
+            data: asc_new(heap, &felts(&self.event.data), gas)?,
             block: asc_new(heap, self.block.as_ref(), gas)?,
             transaction: asc_new(heap, self.transaction.as_ref(), gas)?,
         })
     }
 }
+
+/// Mappings always get felts as 32 big-endian bytes, no matter whether the
+/// block left out their leading zeros. A `Uint256` takes up two felts, the
+/// low 128 bits followed by the high 128 bits
+fn felt(bytes: &[u8]) -> Vec<u8> {
+    Felt::from_padded(bytes)
+        .map(|felt| felt.as_ref().to_vec())
+        .unwrap_or_else(|| bytes.to_vec())
+}
+
+fn felts(felts: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    felts.iter().map(|bytes| felt(bytes)).collect()
+}
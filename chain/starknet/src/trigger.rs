@@ -23,6 +23,10 @@ pub struct StarknetEventTrigger {
     pub(crate) event: Arc<codec::Event>,
     pub(crate) block: Arc<codec::Block>,
     pub(crate) transaction: Arc<codec::Transaction>,
+    /// The position of the transaction in the block and of the event in
+    /// the transaction
+    pub(crate) transaction_index: u32,
+    pub(crate) event_index: u32,
 }
 
 impl PartialEq for StarknetTrigger {
@@ -30,11 +34,9 @@ impl PartialEq for StarknetTrigger {
         match (self, other) {
             (Self::Block(l), Self::Block(r)) => l.block == r.block,
             (Self::Event(l), Self::Event(r)) => {
-                // Without event index we can't really tell if they're the same
-                // TODO: implement add event index to trigger data
                 l.block.hash == r.block.hash
-                    && l.transaction.hash == r.transaction.hash
-                    && l.event == r.event
+                    && l.transaction_index == r.transaction_index
+                    && l.event_index == r.event_index
             }
             _ => false,
         }
@@ -58,9 +60,10 @@ impl Ord for StarknetTrigger {
             (Self::Block(..), _) => Ordering::Greater,
             (_, Self::Block(..)) => Ordering::Less,
 
-            // Keep the order when comparing two event triggers
-            // TODO: compare block hash, tx index, and event index
-            (Self::Event(..), Self::Event(..)) => Ordering::Equal,
+            // Events in the order in which they were emitted
+            (Self::Event(l), Self::Event(r)) => {
+                (l.transaction_index, l.event_index).cmp(&(r.transaction_index, r.event_index))
+            }
         }
     }
 }
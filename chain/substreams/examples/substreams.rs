@@ -73,6 +73,7 @@ async fn main() -> Result<(), Error> {
             Arc::new(Mapper {
                 schema: None,
                 skip_empty_blocks: false,
+                entity_mappings: None,
             }),
             package.modules.clone().unwrap_or_default(),
             module_name.to_string(),
@@ -146,6 +146,7 @@ impl BlockIngestor for SubstreamsBlockIngestor {
         let mapper = Arc::new(Mapper {
             schema: None,
             skip_empty_blocks: false,
+            entity_mappings: None,
         });
         let mut latest_cursor = FirehoseCursor::from(self.fetch_head_cursor().await);
         let mut backoff =
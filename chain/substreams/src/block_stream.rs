@@ -73,6 +73,7 @@ impl BlockStreamBuilderTrait<Chain> for BlockStreamBuilder {
                 Arc::new(Mapper {
                     schema: Some(schema),
                     skip_empty_blocks: true,
+                    entity_mappings: filter.entity_mappings.clone(),
                 }),
                 filter.modules.clone().unwrap_or_default(),
                 filter.module_name.clone(),
@@ -3,8 +3,8 @@ use crate::{data_source::*, EntityChanges, TriggerData, TriggerFilter, TriggersA
 use anyhow::Error;
 use graph::blockchain::client::ChainClient;
 use graph::blockchain::{
-    BasicBlockchainBuilder, BlockIngestor, BlockTime, EmptyNodeCapabilities, NoopDecoderHook,
-    NoopRuntimeAdapter,
+    BasicBlockchainBuilder, BlockIngestor, BlockTime, EmptyNodeCapabilities, IngestionMode,
+    NoopDecoderHook, NoopRuntimeAdapter,
 };
 use graph::components::adapter::ChainId;
 use graph::components::store::DeploymentCursorTracker;
@@ -142,6 +142,7 @@ impl Blockchain for Chain {
         _start_blocks: Vec<BlockNumber>,
         filter: Arc<Self::TriggerFilter>,
         _unified_api_version: UnifiedMappingApiVersion,
+        _ingestion: IngestionMode,
     ) -> Result<Box<dyn BlockStream<Self>>, Error> {
         self.block_stream_builder
             .build_substreams(
@@ -1,18 +1,22 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
 
 use anyhow::{anyhow, Context, Error};
 use graph::{
     blockchain,
     cheap_clone::CheapClone,
     components::{link_resolver::LinkResolver, subgraph::InstanceDSTemplateInfo},
-    prelude::{async_trait, BlockNumber, Link},
+    prelude::{async_trait, serde_yaml, BlockNumber, Link},
+    schema::InputSchema,
     slog::Logger,
 };
 
 use prost::Message;
 use serde::Deserialize;
 
-use crate::{chain::Chain, Block, TriggerData};
+use crate::{chain::Chain, Block, EntityMappings, TriggerData, UnresolvedEntityMapping};
 
 pub const SUBSTREAMS_KIND: &str = "substreams";
 
@@ -129,9 +133,20 @@ impl blockchain::DataSource<Chain> for DataSource {
             ))
         }
 
+        if self.mapping.entities.is_some() && self.mapping.handler.is_some() {
+            errs.push(anyhow!("mapping can not have both a handler and entities"));
+        }
+
         errs
     }
 
+    fn validate_schema(&self, schema: &InputSchema) -> Vec<Error> {
+        match &self.mapping.entities {
+            Some(entities) => entities.validate(schema),
+            None => vec![],
+        }
+    }
+
     fn from_stored_dynamic_data_source(
         _template: &<Chain as blockchain::Blockchain>::DataSourceTemplate,
         _stored: graph::components::store::StoredDynamicDataSource,
@@ -152,6 +167,9 @@ pub struct Mapping {
     pub api_version: semver::Version,
     pub kind: String,
     pub handler: Option<MappingHandler>,
+    /// How the output of the module maps to entities when it is not in the
+    /// `EntityChanges` format
+    pub entities: Option<Arc<EntityMappings>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -178,6 +196,8 @@ pub struct UnresolvedMapping {
     pub kind: String,
     pub handler: Option<String>,
     pub file: Option<Link>,
+    #[serde(default)]
+    pub entities: Vec<UnresolvedEntityMapping>,
 }
 
 #[async_trait]
@@ -192,17 +212,36 @@ impl blockchain::UnresolvedDataSource<Chain> for UnresolvedDataSource {
 
         let mut package = graph::substreams::Package::decode(content.as_ref())?;
 
-        let module = match package.modules.as_mut() {
+        let params = self
+            .source
+            .package
+            .params
+            .map(|params| params.by_module(&self.source.package.module_name))
+            .unwrap_or_default();
+        for (module_name, params) in params {
+            let module = package
+                .modules
+                .as_mut()
+                .and_then(|modules| {
+                    modules
+                        .modules
+                        .iter_mut()
+                        .find(|module| module.name == module_name)
+                })
+                .ok_or_else(|| {
+                    anyhow!(
+                        "can not set params for Substreams module {} because it does not exist",
+                        module_name
+                    )
+                })?;
+            graph::substreams::patch_module_params(params, module);
+        }
+
+        let module = match package.modules.as_ref() {
             Some(modules) => modules
                 .modules
-                .iter_mut()
-                .find(|module| module.name == self.source.package.module_name)
-                .map(|module| {
-                    if let Some(params) = self.source.package.params {
-                        graph::substreams::patch_module_params(params, module);
-                    }
-                    module
-                }),
+                .iter()
+                .find(|module| module.name == self.source.package.module_name),
             None => None,
         };
 
@@ -246,6 +285,15 @@ impl blockchain::UnresolvedDataSource<Chain> for UnresolvedDataSource {
             _ => None,
         };
 
+        let entities = match self.mapping.entities {
+            entities if entities.is_empty() => None,
+            entities => Some(Arc::new(EntityMappings::resolve(
+                &package,
+                &self.source.package.module_name,
+                entities,
+            )?)),
+        };
+
         Ok(DataSource {
             kind: SUBSTREAMS_KIND.into(),
             network: self.network,
@@ -258,6 +306,7 @@ impl blockchain::UnresolvedDataSource<Chain> for UnresolvedDataSource {
                 api_version: semver::Version::parse(&self.mapping.api_version)?,
                 kind: self.mapping.kind,
                 handler,
+                entities,
             },
             context: Arc::new(None),
             initial_block,
@@ -280,7 +329,78 @@ pub struct UnresolvedSource {
 pub struct UnresolvedPackage {
     pub module_name: String,
     pub file: Link,
-    pub params: Option<String>,
+    pub params: Option<UnresolvedParams>,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[serde(untagged)]
+/// The params of the package in the manifest, either a string for the
+/// output module or a map from module name to the params of that module
+pub enum UnresolvedParams {
+    Output(String),
+    Modules(BTreeMap<String, String>),
+}
+
+impl UnresolvedParams {
+    /// The params for each module, where `output_module` is the module that
+    /// params given as a string are for
+    pub fn by_module(self, output_module: &str) -> BTreeMap<String, String> {
+        match self {
+            UnresolvedParams::Output(params) => {
+                BTreeMap::from([(output_module.to_string(), params)])
+            }
+            UnresolvedParams::Modules(params) => params,
+        }
+    }
+}
+
+impl From<&str> for UnresolvedParams {
+    fn from(params: &str) -> Self {
+        UnresolvedParams::Output(params.to_string())
+    }
+}
+
+/// Replace the params of the modules of all substreams data sources in the
+/// raw `manifest` with `overrides`, keeping the params from the manifest for
+/// other modules. This is how the params that were set for a deployment
+/// with `graphman substreams-params` take effect
+pub fn override_params(
+    manifest: &mut serde_yaml::Mapping,
+    overrides: &BTreeMap<String, String>,
+) -> Result<(), Error> {
+    use serde_yaml::Value;
+
+    let data_sources = match manifest.get_mut("dataSources") {
+        Some(Value::Sequence(data_sources)) => data_sources,
+        _ => return Ok(()),
+    };
+    for ds in data_sources {
+        if ds.get("kind").and_then(Value::as_str) != Some(SUBSTREAMS_KIND) {
+            continue;
+        }
+        let package = match ds
+            .get_mut("source")
+            .and_then(|source| source.get_mut("package"))
+            .and_then(Value::as_mapping_mut)
+        {
+            Some(package) => package,
+            None => continue,
+        };
+        let module_name = package
+            .get("moduleName")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let mut params = match package.remove("params") {
+            Some(params) => serde_yaml::from_value::<UnresolvedParams>(params)
+                .context("invalid substreams params in the manifest")?
+                .by_module(&module_name),
+            None => BTreeMap::new(),
+        };
+        params.extend(overrides.clone());
+        package.insert("params".into(), serde_yaml::to_value(params)?);
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -324,7 +444,7 @@ impl blockchain::UnresolvedDataSourceTemplate<Chain> for NoopDataSourceTemplate
 
 #[cfg(test)]
 mod test {
-    use std::{str::FromStr, sync::Arc};
+    use std::{collections::BTreeMap, str::FromStr, sync::Arc};
 
     use anyhow::Error;
     use graph::{
@@ -343,7 +463,10 @@ mod test {
     };
     use prost::Message;
 
-    use crate::{DataSource, Mapping, UnresolvedDataSource, UnresolvedMapping, SUBSTREAMS_KIND};
+    use crate::{
+        override_params, DataSource, Mapping, UnresolvedDataSource, UnresolvedEntityMapping,
+        UnresolvedMapping, UnresolvedParams, SUBSTREAMS_KIND,
+    };
 
     #[test]
     fn parse_data_source() {
@@ -367,6 +490,7 @@ mod test {
                 kind: "substreams/graph-entities".into(),
                 handler: None,
                 file: None,
+                entities: vec![],
             },
         };
         assert_eq!(ds, expected);
@@ -395,6 +519,7 @@ mod test {
                 kind: "substreams/graph-entities".into(),
                 handler: None,
                 file: None,
+                entities: vec![],
             },
         };
         assert_eq!(ds, expected);
@@ -423,6 +548,7 @@ mod test {
                 kind: "substreams/graph-entities".into(),
                 handler: None,
                 file: None,
+                entities: vec![],
             },
         };
         assert_eq!(ds, expected);
@@ -446,6 +572,7 @@ mod test {
                 api_version: semver::Version::from_str("0.0.7").unwrap(),
                 kind: "substreams/graph-entities".into(),
                 handler: None,
+                entities: None,
             },
             context: Arc::new(None),
             initial_block: Some(123),
@@ -483,6 +610,7 @@ mod test {
                 api_version: semver::Version::from_str("0.0.7").unwrap(),
                 kind: "substreams/graph-entities".into(),
                 handler: None,
+                entities: None,
             },
             context: Arc::new(None),
             initial_block: Some(123),
@@ -490,6 +618,63 @@ mod test {
         assert_eq!(ds, expected);
     }
 
+    #[tokio::test]
+    async fn data_source_conversion_module_params() {
+        let mut package = gen_package();
+        let mut modules = package.modules.unwrap();
+        modules.modules.get_mut(1).map(|module| {
+            module.inputs = vec![graph::substreams::module::Input {
+                input: Some(Input::Params(Params {
+                    value: "0xabc".into(),
+                })),
+            }]
+        });
+        package.modules = Some(modules);
+
+        let ds: UnresolvedDataSource =
+            serde_yaml::from_str(TEMPLATE_DATA_SOURCE_WITH_MODULE_PARAMS).unwrap();
+        let link_resolver: Arc<dyn LinkResolver> = Arc::new(NoopLinkResolver {});
+        let logger = Logger::root(Discard, o!());
+        let ds: DataSource = ds.resolve(&link_resolver, &logger, 0).await.unwrap();
+        assert_eq!(ds.source.package, package);
+
+        let mut ds: UnresolvedDataSource =
+            serde_yaml::from_str(TEMPLATE_DATA_SOURCE_WITH_MODULE_PARAMS).unwrap();
+        ds.source.package.params = Some(UnresolvedParams::Modules(BTreeMap::from([(
+            "no_such_module".to_string(),
+            "0xabc".to_string(),
+        )])));
+        let err = ds.resolve(&link_resolver, &logger, 0).await.unwrap_err();
+        assert_eq!(
+            "can not set params for Substreams module no_such_module because it does not exist",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn override_manifest_params() {
+        let data_source: serde_yaml::Value =
+            serde_yaml::from_str(TEMPLATE_DATA_SOURCE_WITH_PARAMS).unwrap();
+        let mut manifest = serde_yaml::Mapping::new();
+        manifest.insert(
+            "dataSources".into(),
+            serde_yaml::Value::Sequence(vec![data_source]),
+        );
+
+        let overrides = BTreeMap::from([("store_mod".to_string(), "0xabc".to_string())]);
+        override_params(&mut manifest, &overrides).unwrap();
+
+        let ds: UnresolvedDataSource =
+            serde_yaml::from_value(manifest["dataSources"][0].clone()).unwrap();
+        assert_eq!(
+            Some(UnresolvedParams::Modules(BTreeMap::from([
+                ("output".to_string(), "x\ny\n123\n".to_string()),
+                ("store_mod".to_string(), "0xabc".to_string()),
+            ]))),
+            ds.source.package.params
+        );
+    }
+
     #[test]
     fn data_source_validation() {
         let mut ds = gen_data_source();
@@ -542,11 +727,33 @@ mod test {
                 file: Some(Link {
                     link: "./src/mappings.ts".to_string(),
                 }),
+                entities: vec![],
             },
         };
         assert_eq!(ds, expected);
     }
 
+    #[test]
+    fn parse_data_source_with_entities() {
+        let ds: UnresolvedDataSource =
+            serde_yaml::from_str(TEMPLATE_DATA_SOURCE_WITH_ENTITIES).unwrap();
+
+        assert_eq!(
+            ds.mapping.entities,
+            vec![UnresolvedEntityMapping {
+                entity: "Pool".into(),
+                items: Some("pools".into()),
+                id: "address".into(),
+                fields: [
+                    ("token0".to_string(), "token0.address".to_string()),
+                    ("feeTier".to_string(), "fee".to_string()),
+                ]
+                .into_iter()
+                .collect(),
+            }]
+        );
+    }
+
     fn gen_package() -> Package {
         Package {
             proto_files: vec![],
@@ -614,6 +821,7 @@ mod test {
                 api_version: semver::Version::from_str("0.0.7").unwrap(),
                 kind: "substreams/graph-entities".into(),
                 handler: None,
+                entities: None,
             },
             context: Arc::new(None),
             initial_block: None,
@@ -673,6 +881,27 @@ mod test {
           handler: bananas
     "#;
 
+    const TEMPLATE_DATA_SOURCE_WITH_ENTITIES: &str = r#"
+        kind: substreams
+        name: Uniswap
+        network: mainnet
+        source:
+          package:
+            moduleName: map_pools
+            file:
+              /: /ipfs/QmbHnhUFZa6qqqRyubUYhXntox1TCBxqryaBM1iNGqVJzT
+        mapping:
+          kind: substreams/graph-entities
+          apiVersion: 0.0.7
+          entities:
+            - entity: Pool
+              items: pools
+              id: address
+              fields:
+                token0: token0.address
+                feeTier: fee
+    "#;
+
     const TEMPLATE_DATA_SOURCE_WITH_PARAMS: &str = r#"
         kind: substreams
         name: Uniswap
@@ -692,6 +921,22 @@ mod test {
           apiVersion: 0.0.7
     "#;
 
+    const TEMPLATE_DATA_SOURCE_WITH_MODULE_PARAMS: &str = r#"
+        kind: substreams
+        name: Uniswap
+        network: mainnet
+        source:
+          package:
+            moduleName: output
+            file:
+              /: /ipfs/QmbHnhUFZa6qqqRyubUYhXntox1TCBxqryaBM1iNGqVJzT
+            params:
+              store_mod: "0xabc"
+        mapping:
+          kind: substreams/graph-entities
+          apiVersion: 0.0.7
+    "#;
+
     #[derive(Debug)]
     struct NoopLinkResolver {}
 
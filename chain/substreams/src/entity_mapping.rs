@@ -0,0 +1,858 @@
+//! Map the output of substreams modules that do not produce `EntityChanges`
+//! into entities. The manifest declares which messages in the output of the
+//! module become entities and which fields of those messages the fields of
+//! the entities come from. The types of the messages are taken from the
+//! protobuf descriptors in the package, so that any module can be consumed
+//! without adding a `graph_out` module to it
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Error};
+use graph::data::store::scalar::{BigDecimal, BigInt, Bytes, Timestamp};
+use graph::data::store::{IdType, ValueType};
+use graph::data::value::Word;
+use graph::data_source::CausalityRegion;
+use graph::prelude::Value;
+use graph::schema::InputSchema;
+use graph::substreams::{module::Kind, Package};
+use prost::encoding::{decode_key, decode_varint, WireType};
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::DescriptorProto;
+use serde::Deserialize;
+
+use crate::ParsedChanges;
+
+/// How the entities of one type are taken from the output of the module,
+/// as declared in the `entities` of the mapping in the manifest. Paths are
+/// field names separated by dots, like `pool.token0.address`
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedEntityMapping {
+    pub entity: String,
+    /// The path to a repeated message field of the output; every message
+    /// in it becomes an entity. Without it, the output itself becomes one
+    /// entity
+    pub items: Option<String>,
+    /// The path from an item to the field that holds the id of the entity
+    pub id: String,
+    /// For each field of the entity, the path from an item to the field
+    /// that holds its value
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+/// A field of a protobuf message
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ProtoField {
+    name: String,
+    number: u32,
+    kind: Type,
+    repeated: bool,
+    /// The full name of the type of message and enum fields
+    type_name: String,
+    /// The names of the values of enum fields
+    enum_values: BTreeMap<i32, String>,
+}
+
+impl ProtoField {
+    fn is_message(&self) -> bool {
+        self.kind == Type::Message || self.kind == Type::Group
+    }
+
+    /// The type of the values that `decode_scalar` produces for this field
+    fn value_type(&self) -> ValueType {
+        match self.kind {
+            Type::Double | Type::Float => ValueType::BigDecimal,
+            Type::Int32 | Type::Sint32 | Type::Sfixed32 => ValueType::Int,
+            Type::Uint32 | Type::Fixed32 | Type::Int64 | Type::Sint64 | Type::Sfixed64 => {
+                ValueType::Int8
+            }
+            Type::Uint64 | Type::Fixed64 => ValueType::BigInt,
+            Type::Bool => ValueType::Boolean,
+            Type::String | Type::Enum | Type::Message | Type::Group => ValueType::String,
+            Type::Bytes => ValueType::Bytes,
+        }
+    }
+}
+
+/// The message and enum types of a package by their full name, like
+/// `.uniswap.v1.Pool`
+struct Descriptors<'a> {
+    messages: HashMap<String, &'a DescriptorProto>,
+    enums: HashMap<String, BTreeMap<i32, String>>,
+}
+
+impl<'a> Descriptors<'a> {
+    fn new(package: &'a Package) -> Self {
+        let mut descriptors = Descriptors {
+            messages: HashMap::new(),
+            enums: HashMap::new(),
+        };
+        for file in &package.proto_files {
+            let prefix = match file.package() {
+                "" => String::new(),
+                package => format!(".{}", package),
+            };
+            for message in &file.message_type {
+                descriptors.add_message(&prefix, message);
+            }
+            for enum_type in &file.enum_type {
+                descriptors.add_enum(&prefix, enum_type);
+            }
+        }
+        descriptors
+    }
+
+    fn add_message(&mut self, prefix: &str, message: &'a DescriptorProto) {
+        let name = format!("{}.{}", prefix, message.name());
+        for nested in &message.nested_type {
+            self.add_message(&name, nested);
+        }
+        for enum_type in &message.enum_type {
+            self.add_enum(&name, enum_type);
+        }
+        self.messages.insert(name, message);
+    }
+
+    fn add_enum(&mut self, prefix: &str, enum_type: &prost_types::EnumDescriptorProto) {
+        let values = enum_type
+            .value
+            .iter()
+            .map(|value| (value.number(), value.name().to_string()))
+            .collect();
+        self.enums
+            .insert(format!("{}.{}", prefix, enum_type.name()), values);
+    }
+
+    /// Resolve `path` starting from the message type `message`. All fields
+    /// but the last one have to be singular message fields
+    fn path(&self, message: &str, path: &str) -> Result<Vec<ProtoField>, Error> {
+        let mut fields: Vec<ProtoField> = vec![];
+        let mut current = message.to_string();
+        for name in path.split('.') {
+            if let Some(field) = fields.last() {
+                if !field.is_message() || field.repeated {
+                    bail!(
+                        "`{}` in `{}` is not a field that holds a single message",
+                        field.name,
+                        path
+                    );
+                }
+                current = field.type_name.clone();
+            }
+            let descriptor = self
+                .messages
+                .get(&current)
+                .ok_or_else(|| anyhow!("message type {} is not in the package", &current[1..]))?;
+            let field = descriptor
+                .field
+                .iter()
+                .find(|field| field.name() == name || field.json_name() == name)
+                .ok_or_else(|| anyhow!("message {} has no field `{}`", &current[1..], name))?;
+            let enum_values = match field.r#type() {
+                Type::Enum => self
+                    .enums
+                    .get(field.type_name())
+                    .cloned()
+                    .unwrap_or_default(),
+                _ => BTreeMap::new(),
+            };
+            fields.push(ProtoField {
+                name: name.to_string(),
+                number: field.number() as u32,
+                kind: field.r#type(),
+                repeated: field.label() == Label::Repeated,
+                type_name: field.type_name().to_string(),
+                enum_values,
+            });
+        }
+        Ok(fields)
+    }
+
+    /// Like `path`, but the last field has to hold scalar values
+    fn scalar_path(&self, message: &str, path: &str) -> Result<Vec<ProtoField>, Error> {
+        let fields = self.path(message, path)?;
+        if fields.last().map_or(true, |field| field.is_message()) {
+            bail!("`{}` does not refer to a field with scalar values", path);
+        }
+        Ok(fields)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityMapping {
+    pub entity: String,
+    items: Vec<ProtoField>,
+    id: Vec<ProtoField>,
+    fields: Vec<(String, Vec<ProtoField>)>,
+}
+
+/// The entity mappings of a data source, resolved against the output type
+/// of its module
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityMappings {
+    pub mappings: Vec<EntityMapping>,
+}
+
+impl EntityMappings {
+    pub fn resolve(
+        package: &Package,
+        module_name: &str,
+        unresolved: Vec<UnresolvedEntityMapping>,
+    ) -> Result<Self, Error> {
+        let output_type = package
+            .modules
+            .as_ref()
+            .and_then(|modules| modules.modules.iter().find(|m| m.name == module_name))
+            .and_then(|module| match &module.kind {
+                Some(Kind::KindMap(map)) => Some(map.output_type.as_str()),
+                _ => None,
+            })
+            .ok_or_else(|| anyhow!("Substreams module {} must be of 'map' kind", module_name))?;
+        let message = format!(".{}", output_type.trim_start_matches("proto:"));
+        let descriptors = Descriptors::new(package);
+        if !descriptors.messages.contains_key(&message) {
+            bail!(
+                "the output type {} of module {} is not in the package",
+                &message[1..],
+                module_name
+            );
+        }
+
+        let mut mappings = vec![];
+        for mapping in unresolved {
+            let UnresolvedEntityMapping {
+                entity,
+                items,
+                id,
+                fields,
+            } = mapping;
+            let resolve = || -> Result<EntityMapping, Error> {
+                let (items, item) = match items {
+                    Some(items) => {
+                        let items = descriptors.path(&message, &items)?;
+                        let last = items.last().unwrap();
+                        if !last.is_message() || !last.repeated {
+                            bail!("`{}` is not a repeated message field", last.name);
+                        }
+                        let item = last.type_name.clone();
+                        (items, item)
+                    }
+                    None => (vec![], message.clone()),
+                };
+                let id = descriptors.scalar_path(&item, &id)?;
+                if id.last().unwrap().repeated {
+                    bail!("the id can not come from a repeated field");
+                }
+                let fields = fields
+                    .into_iter()
+                    .map(|(field, path)| {
+                        let path = descriptors.scalar_path(&item, &path)?;
+                        Ok((field, path))
+                    })
+                    .collect::<Result<_, Error>>()?;
+                Ok(EntityMapping {
+                    entity: entity.clone(),
+                    items,
+                    id,
+                    fields,
+                })
+            };
+            let mapping =
+                resolve().map_err(|e| anyhow!("invalid mapping for entity {}: {:#}", entity, e))?;
+            mappings.push(mapping);
+        }
+        Ok(EntityMappings { mappings })
+    }
+
+    /// Check that the entities and fields that the mappings refer to exist
+    /// in `schema`, and that the values of the protobuf fields can be used
+    /// for them
+    pub fn validate(&self, schema: &InputSchema) -> Vec<Error> {
+        let mut errors = vec![];
+        for mapping in &self.mappings {
+            let entity_type = match schema.entity_type(&mapping.entity) {
+                Ok(entity_type) if entity_type.object_type().is_ok() => entity_type,
+                _ => {
+                    errors.push(anyhow!(
+                        "entity mapping refers to {}, which is not an entity type of the schema",
+                        mapping.entity
+                    ));
+                    continue;
+                }
+            };
+
+            let id = mapping.id.last().unwrap();
+            if matches!(id.value_type(), ValueType::BigDecimal | ValueType::Boolean) {
+                errors.push(anyhow!(
+                    "the id of {} can not come from field `{}`",
+                    mapping.entity,
+                    id.name
+                ));
+            }
+
+            for (name, path) in &mapping.fields {
+                let field = match entity_type.field(name) {
+                    Some(field) if name != "id" && !field.is_derived() => field,
+                    _ => {
+                        errors.push(anyhow!(
+                            "entity type {} has no field `{}` that can be mapped",
+                            mapping.entity,
+                            name
+                        ));
+                        continue;
+                    }
+                };
+                let proto = path.last().unwrap();
+                if proto.repeated != field.is_list() {
+                    errors.push(anyhow!(
+                        "field `{}` of {} is {}a list, but `{}` is {}repeated",
+                        name,
+                        mapping.entity,
+                        if field.is_list() { "" } else { "not " },
+                        proto.name,
+                        if proto.repeated { "" } else { "not " },
+                    ));
+                }
+                if !can_coerce(proto.value_type(), field.value_type) {
+                    errors.push(anyhow!(
+                        "field `{}` of {} has type {}, which can not hold the values of `{}`",
+                        name,
+                        mapping.entity,
+                        field.value_type.to_str(),
+                        proto.name
+                    ));
+                }
+            }
+        }
+        errors
+    }
+
+    /// Turn the output of the module for one block into entity changes
+    pub fn parse_changes(
+        &self,
+        output: &[u8],
+        schema: &InputSchema,
+    ) -> Result<Vec<ParsedChanges>, Error> {
+        let mut changes = vec![];
+        for mapping in &self.mappings {
+            let entity_type = schema.entity_type(&mapping.entity)?;
+            let items = match mapping.items.is_empty() {
+                true => vec![output.to_vec()],
+                false => values(output, &mapping.items)?
+                    .into_iter()
+                    .map(|item| match item {
+                        RawValue::Bytes(item) => Ok(item),
+                        _ => Err(anyhow!("unexpected encoding of `{}`", mapping.entity)),
+                    })
+                    .collect::<Result<_, Error>>()?,
+            };
+
+            for item in items {
+                let id = match scalar(&item, &mapping.id)? {
+                    Value::String(id) => id,
+                    Value::Bytes(id) => id.to_string(),
+                    other => other.to_string(),
+                };
+                // See `parse_changes` in `mapper.rs` for why ids of type
+                // `Bytes` need the `0x` prefix
+                let id = match entity_type.id_type()? {
+                    IdType::Bytes if !id.starts_with("0x") => format!("0x{}", id),
+                    _ => id,
+                };
+                let key = entity_type.parse_key_in(Word::from(id), CausalityRegion::ONCHAIN)?;
+
+                let mut data: HashMap<Word, Value> = HashMap::new();
+                data.insert(Word::from("id"), key.id_value());
+                for (name, path) in &mapping.fields {
+                    let value = scalar(&item, path)?;
+                    let value = match entity_type.field(name) {
+                        Some(field) => coerce(value, field.value_type)?,
+                        None => value,
+                    };
+                    data.insert(Word::from(name.as_str()), value);
+                }
+                let entity = schema.make_entity(data)?;
+                changes.push(ParsedChanges::Upsert { key, entity });
+            }
+        }
+        Ok(changes)
+    }
+}
+
+/// A field value as it is encoded on the wire
+#[derive(Clone, Debug, PartialEq)]
+enum RawValue {
+    Varint(u64),
+    Fixed64(u64),
+    Fixed32(u32),
+    Bytes(Vec<u8>),
+}
+
+/// The fields of the encoded message `buf` with their number, in the order
+/// in which they appear
+fn decode_message(mut buf: &[u8]) -> Result<Vec<(u32, RawValue)>, Error> {
+    fn take<'b>(buf: &mut &'b [u8], len: usize) -> Result<&'b [u8], Error> {
+        if buf.len() < len {
+            bail!("message is truncated");
+        }
+        let (value, rest) = buf.split_at(len);
+        *buf = rest;
+        Ok(value)
+    }
+
+    let mut fields = vec![];
+    while !buf.is_empty() {
+        let (number, wire_type) = decode_key(&mut buf)?;
+        let value = match wire_type {
+            WireType::Varint => RawValue::Varint(decode_varint(&mut buf)?),
+            WireType::SixtyFourBit => {
+                RawValue::Fixed64(u64::from_le_bytes(take(&mut buf, 8)?.try_into()?))
+            }
+            WireType::ThirtyTwoBit => {
+                RawValue::Fixed32(u32::from_le_bytes(take(&mut buf, 4)?.try_into()?))
+            }
+            WireType::LengthDelimited => {
+                let len = decode_varint(&mut buf)? as usize;
+                RawValue::Bytes(take(&mut buf, len)?.to_vec())
+            }
+            WireType::StartGroup | WireType::EndGroup => {
+                bail!("groups are not supported")
+            }
+        };
+        fields.push((number, value));
+    }
+    Ok(fields)
+}
+
+/// The values of the field at the end of `path` in the encoded message
+/// `message`. Occurrences of a singular message field are merged, like
+/// protobuf does, by decoding their concatenation
+fn values(message: &[u8], path: &[ProtoField]) -> Result<Vec<RawValue>, Error> {
+    let (field, rest) = path.split_first().unwrap();
+    let values = decode_message(message)?
+        .into_iter()
+        .filter(|(number, _)| *number == field.number)
+        .map(|(_, value)| value);
+    if rest.is_empty() {
+        return Ok(values.collect());
+    }
+
+    let mut nested = vec![];
+    for value in values {
+        match value {
+            RawValue::Bytes(bytes) => nested.extend(bytes),
+            _ => bail!("unexpected encoding of `{}`", field.name),
+        }
+    }
+    self::values(&nested, rest)
+}
+
+/// The value of the scalar field at the end of `path` in `message`; a list
+/// for repeated fields. Missing singular fields have their default value
+fn scalar(message: &[u8], path: &[ProtoField]) -> Result<Value, Error> {
+    let field = path.last().unwrap();
+    let packed = !matches!(field.kind, Type::String | Type::Bytes);
+    let mut raw = vec![];
+    for value in values(message, path)? {
+        match value {
+            RawValue::Bytes(bytes) if packed => {
+                let mut buf = bytes.as_slice();
+                while !buf.is_empty() {
+                    let value = match field.kind {
+                        Type::Double | Type::Fixed64 | Type::Sfixed64 => {
+                            let (value, rest) = buf.split_at(8.min(buf.len()));
+                            buf = rest;
+                            RawValue::Fixed64(u64::from_le_bytes(value.try_into()?))
+                        }
+                        Type::Float | Type::Fixed32 | Type::Sfixed32 => {
+                            let (value, rest) = buf.split_at(4.min(buf.len()));
+                            buf = rest;
+                            RawValue::Fixed32(u32::from_le_bytes(value.try_into()?))
+                        }
+                        _ => RawValue::Varint(decode_varint(&mut buf)?),
+                    };
+                    raw.push(value);
+                }
+            }
+            value => raw.push(value),
+        }
+    }
+
+    let mut values = raw
+        .into_iter()
+        .map(|value| decode_scalar(field, value))
+        .collect::<Result<Vec<_>, _>>()?;
+    if field.repeated {
+        return Ok(Value::List(values));
+    }
+    match values.pop() {
+        Some(value) => Ok(value),
+        None => {
+            let default = match field.kind {
+                Type::Double | Type::Fixed64 | Type::Sfixed64 => RawValue::Fixed64(0),
+                Type::Float | Type::Fixed32 | Type::Sfixed32 => RawValue::Fixed32(0),
+                Type::String | Type::Bytes => RawValue::Bytes(vec![]),
+                _ => RawValue::Varint(0),
+            };
+            decode_scalar(field, default)
+        }
+    }
+}
+
+fn decode_scalar(field: &ProtoField, value: RawValue) -> Result<Value, Error> {
+    fn zigzag(n: u64) -> i64 {
+        ((n >> 1) as i64) ^ -((n & 1) as i64)
+    }
+
+    let value = match (field.kind, value) {
+        (Type::Double, RawValue::Fixed64(n)) => float(f64::from_bits(n))?,
+        (Type::Float, RawValue::Fixed32(n)) => float(f32::from_bits(n) as f64)?,
+        (Type::Int32, RawValue::Varint(n)) => Value::Int(n as i32),
+        (Type::Sint32, RawValue::Varint(n)) => Value::Int(zigzag(n) as i32),
+        (Type::Sfixed32, RawValue::Fixed32(n)) => Value::Int(n as i32),
+        (Type::Uint32, RawValue::Varint(n)) => Value::Int8(n as u32 as i64),
+        (Type::Fixed32, RawValue::Fixed32(n)) => Value::Int8(n as i64),
+        (Type::Int64, RawValue::Varint(n)) => Value::Int8(n as i64),
+        (Type::Sint64, RawValue::Varint(n)) => Value::Int8(zigzag(n)),
+        (Type::Sfixed64, RawValue::Fixed64(n)) => Value::Int8(n as i64),
+        (Type::Uint64, RawValue::Varint(n)) | (Type::Fixed64, RawValue::Fixed64(n)) => {
+            Value::BigInt(BigInt::from(n))
+        }
+        (Type::Bool, RawValue::Varint(n)) => Value::Bool(n != 0),
+        (Type::Enum, RawValue::Varint(n)) => {
+            let n = n as i32;
+            match field.enum_values.get(&n) {
+                Some(name) => Value::String(name.clone()),
+                None => Value::String(n.to_string()),
+            }
+        }
+        (Type::String, RawValue::Bytes(bytes)) => {
+            let string = String::from_utf8(bytes)
+                .map_err(|_| anyhow!("field `{}` is not valid UTF-8", field.name))?;
+            // Strip null characters since they are not accepted by Postgres.
+            Value::String(string.replace('\u{0000}', ""))
+        }
+        (Type::Bytes, RawValue::Bytes(bytes)) => Value::Bytes(Bytes::from(bytes)),
+        (_, value) => bail!(
+            "unexpected encoding {:?} for field `{}` of type {:?}",
+            value,
+            field.name,
+            field.kind
+        ),
+    };
+    Ok(value)
+}
+
+fn float(n: f64) -> Result<Value, Error> {
+    if !n.is_finite() {
+        bail!("can not store the floating point value {}", n);
+    }
+    Ok(Value::BigDecimal(BigDecimal::from(n)))
+}
+
+/// Whether `coerce` turns values of type `from` into values of type `to`
+fn can_coerce(from: ValueType, to: ValueType) -> bool {
+    use ValueType::*;
+
+    from == to
+        || matches!(
+            (from, to),
+            (String, BigInt | BigDecimal | Bytes | Int | Int8)
+                | (Int, Int8 | BigInt | BigDecimal)
+                | (Int8, Int | BigInt | BigDecimal | Timestamp)
+                | (BigInt, BigDecimal)
+                | (Bytes, String)
+        )
+}
+
+/// Convert `value` into a value of type `to`, for example by parsing
+/// numbers that substreams send as strings
+fn coerce(value: Value, to: ValueType) -> Result<Value, Error> {
+    let value = match (value, to) {
+        (Value::List(values), _) => Value::List(
+            values
+                .into_iter()
+                .map(|value| coerce(value, to))
+                .collect::<Result<_, _>>()?,
+        ),
+        (Value::String(s), ValueType::BigInt) => Value::BigInt(BigInt::from_str(&s)?),
+        (Value::String(s), ValueType::BigDecimal) => Value::BigDecimal(BigDecimal::from_str(&s)?),
+        (Value::String(s), ValueType::Bytes) => Value::Bytes(Bytes::from_str(&s)?),
+        (Value::String(s), ValueType::Int) => Value::Int(s.parse()?),
+        (Value::String(s), ValueType::Int8) => Value::Int8(s.parse()?),
+        (Value::Int(n), ValueType::Int8) => Value::Int8(n as i64),
+        (Value::Int(n), ValueType::BigInt) => Value::BigInt(BigInt::from(n)),
+        (Value::Int(n), ValueType::BigDecimal) => Value::BigDecimal(BigDecimal::from(n)),
+        (Value::Int8(n), ValueType::Int) => Value::Int(i32::try_from(n)?),
+        (Value::Int8(n), ValueType::BigInt) => Value::BigInt(BigInt::from(n)),
+        (Value::Int8(n), ValueType::BigDecimal) => Value::BigDecimal(BigDecimal::from(n)),
+        (Value::Int8(n), ValueType::Timestamp) => {
+            Value::Timestamp(Timestamp::from_microseconds_since_epoch(n)?)
+        }
+        (Value::BigInt(n), ValueType::BigDecimal) => Value::BigDecimal(BigDecimal::new(n, 0)),
+        (Value::Bytes(bytes), ValueType::String) => Value::String(bytes.to_string()),
+        (value, _) => value,
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use prost::Message;
+    use prost_types::{
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FileDescriptorProto,
+    };
+
+    use graph::data::subgraph::{DeploymentHash, LATEST_VERSION};
+    use graph::substreams::{module::KindMap, Module, Modules};
+
+    use super::*;
+
+    fn field(
+        name: &str,
+        number: i32,
+        kind: Type,
+        label: Label,
+        type_name: &str,
+    ) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(label as i32),
+            r#type: Some(kind as i32),
+            type_name: (!type_name.is_empty()).then(|| type_name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// A package with a module `pools` whose output is
+    /// `message Pools { repeated Pool pools = 1; }` with
+    /// `message Pool { string address = 1; string tvl = 2; uint64 created = 3; Token token = 4; Kind kind = 5; repeated int32 fees = 6; }`
+    /// and `message Token { bytes address = 1; }`
+    fn package() -> Package {
+        use Label::*;
+
+        let file = FileDescriptorProto {
+            name: Some("pools.proto".to_string()),
+            package: Some("test.v1".to_string()),
+            message_type: vec![
+                DescriptorProto {
+                    name: Some("Pools".to_string()),
+                    field: vec![field("pools", 1, Type::Message, Repeated, ".test.v1.Pool")],
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: Some("Pool".to_string()),
+                    field: vec![
+                        field("address", 1, Type::String, Optional, ""),
+                        field("tvl", 2, Type::String, Optional, ""),
+                        field("created", 3, Type::Uint64, Optional, ""),
+                        field("token", 4, Type::Message, Optional, ".test.v1.Pool.Token"),
+                        field("kind", 5, Type::Enum, Optional, ".test.v1.Kind"),
+                        field("fees", 6, Type::Int32, Repeated, ""),
+                    ],
+                    nested_type: vec![DescriptorProto {
+                        name: Some("Token".to_string()),
+                        field: vec![field("address", 1, Type::Bytes, Optional, "")],
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+            ],
+            enum_type: vec![EnumDescriptorProto {
+                name: Some("Kind".to_string()),
+                value: vec![
+                    EnumValueDescriptorProto {
+                        name: Some("STABLE".to_string()),
+                        number: Some(0),
+                        ..Default::default()
+                    },
+                    EnumValueDescriptorProto {
+                        name: Some("VOLATILE".to_string()),
+                        number: Some(1),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        Package {
+            proto_files: vec![file],
+            modules: Some(Modules {
+                modules: vec![Module {
+                    name: "pools".to_string(),
+                    kind: Some(Kind::KindMap(KindMap {
+                        output_type: "proto:test.v1.Pools".to_string(),
+                    })),
+                    ..Default::default()
+                }],
+                binaries: vec![],
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn mapping(fields: &[(&str, &str)]) -> UnresolvedEntityMapping {
+        UnresolvedEntityMapping {
+            entity: "Pool".to_string(),
+            items: Some("pools".to_string()),
+            id: "address".to_string(),
+            fields: fields
+                .iter()
+                .map(|(field, path)| (field.to_string(), path.to_string()))
+                .collect(),
+        }
+    }
+
+    const SCHEMA: &str = "
+        type Pool @entity {
+            id: ID!
+            tvl: BigDecimal!
+            token: Bytes!
+            kind: String!
+            fees: [Int!]!
+            count: Int
+        }";
+
+    #[test]
+    fn resolve_entity_mappings() {
+        let package = package();
+        let schema =
+            InputSchema::parse(LATEST_VERSION, SCHEMA, DeploymentHash::new("test").unwrap())
+                .unwrap();
+
+        let mappings = EntityMappings::resolve(
+            &package,
+            "pools",
+            vec![mapping(&[("tvl", "tvl"), ("token", "token.address")])],
+        )
+        .unwrap();
+        assert!(mappings.validate(&schema).is_empty());
+
+        // Paths that do not exist or do not end in a scalar
+        for fields in [&[("tvl", "missing")][..], &[("token", "token")][..]] {
+            assert!(EntityMappings::resolve(&package, "pools", vec![mapping(fields)]).is_err());
+        }
+        assert!(EntityMappings::resolve(&package, "missing", vec![mapping(&[])]).is_err());
+
+        // Fields that are not in the schema or do not fit
+        let mappings = EntityMappings::resolve(
+            &package,
+            "pools",
+            vec![mapping(&[
+                ("missing", "tvl"),
+                ("count", "created"),
+                ("fees", "tvl"),
+            ])],
+        )
+        .unwrap();
+        assert_eq!(3, mappings.validate(&schema).len());
+    }
+
+    #[test]
+    fn parse_entity_changes() {
+        #[derive(Clone, PartialEq, Message)]
+        struct Token {
+            #[prost(bytes, tag = "1")]
+            address: Vec<u8>,
+        }
+        #[derive(Clone, PartialEq, Message)]
+        struct Pool {
+            #[prost(string, tag = "1")]
+            address: String,
+            #[prost(string, tag = "2")]
+            tvl: String,
+            #[prost(uint64, tag = "3")]
+            created: u64,
+            #[prost(message, optional, tag = "4")]
+            token: Option<Token>,
+            #[prost(int32, tag = "5")]
+            kind: i32,
+            #[prost(int32, repeated, tag = "6")]
+            fees: Vec<i32>,
+        }
+        #[derive(Clone, PartialEq, Message)]
+        struct Pools {
+            #[prost(message, repeated, tag = "1")]
+            pools: Vec<Pool>,
+        }
+
+        let schema =
+            InputSchema::parse(LATEST_VERSION, SCHEMA, DeploymentHash::new("test").unwrap())
+                .unwrap();
+        let mappings = EntityMappings::resolve(
+            &package(),
+            "pools",
+            vec![mapping(&[
+                ("tvl", "tvl"),
+                ("token", "token.address"),
+                ("kind", "kind"),
+                ("fees", "fees"),
+            ])],
+        )
+        .unwrap();
+
+        let output = Pools {
+            pools: vec![
+                Pool {
+                    address: "a".to_string(),
+                    tvl: "1.5".to_string(),
+                    created: 0,
+                    token: Some(Token {
+                        address: vec![1, 2],
+                    }),
+                    kind: 1,
+                    fees: vec![1, -2],
+                },
+                Pool {
+                    address: "b".to_string(),
+                    tvl: "2".to_string(),
+                    created: 0,
+                    token: None,
+                    kind: 0,
+                    fees: vec![],
+                },
+            ],
+        }
+        .encode_to_vec();
+
+        let changes = mappings.parse_changes(&output, &schema).unwrap();
+        assert_eq!(2, changes.len());
+        let ParsedChanges::Upsert { key, entity } = &changes[0] else {
+            panic!("expected an upsert")
+        };
+        assert_eq!("a", key.entity_id.to_string());
+        assert_eq!(
+            &Value::BigDecimal(BigDecimal::from_str("1.5").unwrap()),
+            entity.get("tvl").unwrap()
+        );
+        assert_eq!(
+            &Value::Bytes(Bytes::from(vec![1u8, 2])),
+            entity.get("token").unwrap()
+        );
+        assert_eq!(
+            &Value::String("VOLATILE".to_string()),
+            entity.get("kind").unwrap()
+        );
+        assert_eq!(
+            &Value::List(vec![Value::Int(1), Value::Int(-2)]),
+            entity.get("fees").unwrap()
+        );
+
+        // Missing fields have their default value
+        let ParsedChanges::Upsert { entity, .. } = &changes[1] else {
+            panic!("expected an upsert")
+        };
+        assert_eq!(
+            &Value::Bytes(Bytes::from(vec![])),
+            entity.get("token").unwrap()
+        );
+        assert_eq!(
+            &Value::String("STABLE".to_string()),
+            entity.get("kind").unwrap()
+        );
+    }
+}
@@ -2,6 +2,7 @@ mod block_stream;
 mod chain;
 mod codec;
 mod data_source;
+mod entity_mapping;
 mod trigger;
 
 pub mod block_ingestor;
@@ -12,6 +13,7 @@ pub use block_stream::BlockStreamBuilder;
 pub use chain::*;
 pub use codec::EntityChanges;
 pub use data_source::*;
+pub use entity_mapping::{EntityMapping, EntityMappings, UnresolvedEntityMapping};
 pub use trigger::*;
 
 pub use codec::Field;
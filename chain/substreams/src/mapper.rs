@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use crate::codec::{entity_change, EntityChanges};
 use anyhow::{anyhow, Error};
@@ -19,7 +20,7 @@ use graph::slog::error;
 use graph::substreams::Clock;
 use prost::Message;
 
-use crate::{Block, Chain, ParsedChanges, TriggerData};
+use crate::{Block, Chain, EntityMappings, ParsedChanges, TriggerData};
 
 // WasmBlockMapper will not perform any transformation to the block and cannot make assumptions
 // about the block format. This mode just works a passthrough from the block stream to the subgraph
@@ -100,11 +101,26 @@ pub struct Mapper {
     // Block ingestors need the block to be returned so they can populate the cache
     // block streams, however, can shave some time by just skipping.
     pub skip_empty_blocks: bool,
+    // If set, the output is not in the graph-out format and gets mapped to entities
+    // as declared in the manifest. This requires a schema.
+    pub entity_mappings: Option<Arc<EntityMappings>>,
 }
 
 #[async_trait]
 impl BlockStreamMapper<Chain> for Mapper {
     fn decode_block(&self, output: Option<&[u8]>) -> Result<Option<Block>, BlockStreamError> {
+        if let (Some(mappings), Some(schema)) = (&self.entity_mappings, &self.schema) {
+            let parsed_changes = mappings
+                .parse_changes(output.unwrap_or_default(), schema)
+                .map_err(|e| SubstreamsError::EntityMappingError(format!("{:#}", e)))?;
+            return Ok(Some(Block {
+                hash: BlockHash::zero(),
+                number: BlockNumber::MIN,
+                changes: EntityChanges::default(),
+                parsed_changes,
+            }));
+        }
+
         let changes: EntityChanges = match output {
             Some(msg) => Message::decode(msg).map_err(SubstreamsError::DecodingError)?,
             None => EntityChanges {
@@ -136,7 +152,7 @@ impl BlockStreamMapper<Chain> for Mapper {
         block: Block,
     ) -> Result<BlockWithTriggers<Chain>, BlockStreamError> {
         let mut triggers = vec![];
-        if block.changes.entity_changes.len() >= 1 {
+        if block.changes.entity_changes.len() >= 1 || !block.parsed_changes.is_empty() {
             triggers.push(TriggerData {});
         }
 
@@ -18,7 +18,7 @@ use graph_runtime_wasm::module::ToAscPtr;
 use lazy_static::__Deref;
 use std::sync::Arc;
 
-use crate::{Block, Chain, NoopDataSourceTemplate, ParsedChanges};
+use crate::{Block, Chain, EntityMappings, NoopDataSourceTemplate, ParsedChanges};
 
 #[derive(Eq, PartialEq, PartialOrd, Ord, Debug)]
 pub struct TriggerData {}
@@ -60,6 +60,8 @@ pub struct TriggerFilter {
     // the handler to call for subgraph mappings, if this is set then the binary block content
     // should be passed to the mappings.
     pub(crate) mapping_handler: Option<String>,
+    // how to turn the output of the module into entities if it is not in the graph-out format
+    pub(crate) entity_mappings: Option<Arc<EntityMappings>>,
 }
 
 #[cfg(debug_assertions)]
@@ -99,6 +101,7 @@ impl blockchain::TriggerFilter<Chain> for TriggerFilter {
             start_block,
             data_sources_len,
             mapping_handler,
+            entity_mappings,
         } = self;
 
         if *data_sources_len >= 1 {
@@ -111,6 +114,7 @@ impl blockchain::TriggerFilter<Chain> for TriggerFilter {
             *module_name = ds.source.module_name.clone();
             *start_block = ds.initial_block;
             *mapping_handler = ds.mapping.handler.as_ref().map(|h| h.handler.clone());
+            *entity_mappings = ds.mapping.entities.clone();
         }
     }
 
@@ -6,20 +6,27 @@ use graph::{
     data_source::offchain::Base64,
     derive::CheapClone,
     prelude::CheapClone,
+    tokio,
 };
 use std::{sync::Arc, time::Duration};
 use tower::{buffer::Buffer, ServiceBuilder, ServiceExt};
 
+use super::OffchainCache;
+
 pub type ArweaveService = Buffer<Base64, BoxFuture<'static, Result<Option<Bytes>, Error>>>;
 
 pub fn arweave_service(
     client: Arc<ArweaveClient>,
     rate_limit: u16,
     max_file_size: FileSizeLimit,
+    timeout: Duration,
+    cache: Option<Arc<OffchainCache>>,
 ) -> ArweaveService {
     let arweave = ArweaveServiceInner {
         client,
         max_file_size,
+        timeout,
+        cache,
     };
 
     let svc = ServiceBuilder::new()
@@ -36,15 +43,40 @@ pub fn arweave_service(
 struct ArweaveServiceInner {
     client: Arc<ArweaveClient>,
     max_file_size: FileSizeLimit,
+    timeout: Duration,
+    cache: Option<Arc<OffchainCache>>,
 }
 
 impl ArweaveServiceInner {
     async fn call_inner(self, req: Base64) -> Result<Option<Bytes>, Error> {
-        self.client
-            .get_with_limit(&req, &self.max_file_size)
-            .await
-            .map(Bytes::from)
-            .map(Some)
-            .map_err(Error::from)
+        if let Some(cache) = &self.cache {
+            match (cache.get("arweave", &req).await, &self.max_file_size) {
+                (Some(file_bytes), FileSizeLimit::MaxBytes(max))
+                    if file_bytes.len() as u64 > *max => {}
+                (Some(file_bytes), _) => return Ok(Some(file_bytes)),
+                (None, _) => {}
+            }
+        }
+
+        let res = tokio::time::timeout(
+            self.timeout,
+            self.client.get_with_limit(&req, &self.max_file_size),
+        )
+        .await;
+
+        // Like for IPFS, files that the gateways do not have yet or that
+        // take too long are not available, and will be polled again
+        match res {
+            Ok(Ok(file_bytes)) => {
+                let file_bytes = Bytes::from(file_bytes);
+                if let Some(cache) = &self.cache {
+                    cache.insert("arweave", &req, &file_bytes).await;
+                }
+                Ok(Some(file_bytes))
+            }
+            Ok(Err(err)) if err.is_not_found() => Ok(None),
+            Ok(Err(err)) => Err(err.into()),
+            Err(_) => Ok(None),
+        }
     }
 }
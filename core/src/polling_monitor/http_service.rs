@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Error};
+use bytes::Bytes;
+use graph::data_source::offchain::HttpSource;
+use graph::futures03::future::BoxFuture;
+use graph::futures03::TryStreamExt;
+use graph::prelude::hex;
+use graph::prelude::reqwest::{self, StatusCode};
+use graph::{derive::CheapClone, prelude::CheapClone};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tower::{buffer::Buffer, BoxError, ServiceBuilder, ServiceExt};
+
+use super::{OffchainCache, PermanentError};
+
+pub type HttpService = Buffer<HttpSource, BoxFuture<'static, Result<Option<Bytes>, BoxError>>>;
+
+/// A service that fetches the files of `file/http` data sources from the
+/// `allowed_hosts`, and only returns files whose contents have the hash
+/// that the data source expects. Redirects are not followed since they
+/// could lead to hosts that are not allowed
+pub fn http_service(
+    allowed_hosts: Vec<String>,
+    max_file_size: usize,
+    timeout: Duration,
+    rate_limit: u16,
+    cache: Option<Arc<OffchainCache>>,
+) -> HttpService {
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("building an HTTP client without redirects works");
+    let http = HttpServiceInner {
+        client,
+        allowed_hosts: Arc::new(allowed_hosts),
+        max_file_size,
+        timeout,
+        cache,
+    };
+
+    let svc = ServiceBuilder::new()
+        .rate_limit(rate_limit.into(), Duration::from_secs(1))
+        .service_fn(move |req| http.cheap_clone().call_inner(req))
+        .boxed();
+
+    // The `Buffer` makes it so the rate limit is shared among clones.
+    // Make it unbounded to avoid any risk of starvation.
+    Buffer::new(svc, u32::MAX as usize)
+}
+
+#[derive(Clone, CheapClone)]
+struct HttpServiceInner {
+    client: reqwest::Client,
+    allowed_hosts: Arc<Vec<String>>,
+    max_file_size: usize,
+    timeout: Duration,
+    cache: Option<Arc<OffchainCache>>,
+}
+
+impl HttpServiceInner {
+    async fn call_inner(self, req: HttpSource) -> Result<Option<Bytes>, BoxError> {
+        let url = req.url.as_str().parse::<reqwest::Url>()?;
+        let host = url.host_str().unwrap_or_default().to_lowercase();
+        if !self
+            .allowed_hosts
+            .iter()
+            .any(|allowed| allowed == "*" || *allowed == host)
+        {
+            return Err(anyhow!(
+                "the host `{}` is not allowed for `file/http` data sources. \
+                 Hint: add it to GRAPH_FILE_HTTP_ALLOWED_HOSTS",
+                host
+            )
+            .into());
+        }
+
+        // The contents are addressed by their hash, so they can be cached
+        // even though the server could change the file
+        let key = hex::encode(req.sha256);
+        if let Some(cache) = &self.cache {
+            match cache.get("http", &key).await {
+                Some(bytes) if bytes.len() <= self.max_file_size => return Ok(Some(bytes)),
+                _ => {}
+            }
+        }
+
+        let res = graph::tokio::time::timeout(self.timeout, self.fetch(url)).await;
+
+        let bytes = match res {
+            Ok(Ok(Some(bytes))) => bytes,
+            // Like for IPFS, files that are missing or take too long are not
+            // available, and will be polled again
+            Ok(Ok(None)) | Err(_) => return Ok(None),
+            Ok(Err(err)) => return Err(err.into()),
+        };
+
+        // The data source names the contents it expects, and fetching the
+        // file again would not change that the server has different ones
+        let sha256: [u8; 32] = Sha256::digest(&bytes).into();
+        if sha256 != req.sha256 {
+            return Err(PermanentError(format!(
+                "the contents of `{}` have the hash {} instead of the expected {}",
+                req.url,
+                hex::encode(sha256),
+                hex::encode(req.sha256)
+            ))
+            .into());
+        }
+
+        if let Some(cache) = &self.cache {
+            cache.insert("http", &key, &bytes).await;
+        }
+        Ok(Some(bytes))
+    }
+
+    async fn fetch(&self, url: reqwest::Url) -> Result<Option<Bytes>, Error> {
+        let rsp = self.client.get(url.clone()).send().await?;
+        if rsp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if rsp.status().is_redirection() {
+            return Err(anyhow!(
+                "`{}` redirects elsewhere, but redirects are not followed for `file/http` data sources",
+                url
+            ));
+        }
+        let rsp = rsp.error_for_status()?;
+
+        let too_large = || {
+            anyhow!(
+                "the file at `{}` is larger than the limit of {} bytes",
+                url,
+                self.max_file_size
+            )
+        };
+        if rsp.content_length().unwrap_or(0) > self.max_file_size as u64 {
+            return Err(too_large());
+        }
+
+        let mut body = Vec::new();
+        let mut stream = rsp.bytes_stream();
+        while let Some(chunk) = stream.try_next().await? {
+            if body.len() + chunk.len() > self.max_file_size {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(Some(Bytes::from(body)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use graph::tokio;
+    use tower::ServiceExt;
+    use wiremock::matchers as m;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn http_get_checks_the_hash() {
+        let server = MockServer::start().await;
+        Mock::given(m::method("GET"))
+            .and(m::path("/1.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"{}"))
+            .mount(&server)
+            .await;
+
+        let source = |path: &str, contents: &[u8]| -> HttpSource {
+            let hash = hex::encode(Sha256::digest(contents));
+            format!("{}{}#sha256={}", server.uri(), path, hash)
+                .parse()
+                .unwrap()
+        };
+        let svc = http_service(
+            vec!["*".to_string()],
+            1000,
+            Duration::from_secs(10),
+            10,
+            None,
+        );
+
+        let contents = svc.clone().oneshot(source("/1.json", b"{}")).await.unwrap();
+        assert_eq!(Some(Bytes::from_static(b"{}")), contents);
+
+        // A file that changed is never returned, and that can't be fixed
+        // by fetching it again
+        let err = svc
+            .clone()
+            .oneshot(source("/1.json", b"{\"changed\":true}"))
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<PermanentError>().is_some());
+
+        // Redirects are not followed
+        Mock::given(m::method("GET"))
+            .and(m::path("/moved.json"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/1.json"))
+            .mount(&server)
+            .await;
+        let err = svc
+            .clone()
+            .oneshot(source("/moved.json", b"{}"))
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<PermanentError>().is_none());
+
+        // A missing file is not available yet
+        let contents = svc.oneshot(source("/2.json", b"{}")).await.unwrap();
+        assert_eq!(None, contents);
+
+        // Only allowed hosts are fetched from
+        let svc = http_service(
+            vec!["example.com".to_string()],
+            1000,
+            Duration::from_secs(10),
+            10,
+            None,
+        );
+        svc.oneshot(source("/1.json", b"{}")).await.unwrap_err();
+    }
+}
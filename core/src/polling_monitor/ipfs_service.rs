@@ -7,10 +7,13 @@ use bytes::Bytes;
 use graph::futures03::future::BoxFuture;
 use graph::ipfs::ContentPath;
 use graph::ipfs::IpfsClient;
+use graph::ipfs::PinningService;
 use graph::ipfs::RetryPolicy;
 use graph::{derive::CheapClone, prelude::CheapClone};
 use tower::{buffer::Buffer, ServiceBuilder, ServiceExt};
 
+use super::OffchainCache;
+
 pub type IpfsService = Buffer<ContentPath, BoxFuture<'static, Result<Option<Bytes>, Error>>>;
 
 pub fn ipfs_service(
@@ -18,11 +21,15 @@ pub fn ipfs_service(
     max_file_size: usize,
     timeout: Duration,
     rate_limit: u16,
+    cache: Option<Arc<OffchainCache>>,
+    pinning: Option<Arc<PinningService>>,
 ) -> IpfsService {
     let ipfs = IpfsServiceInner {
         client,
         timeout,
         max_file_size,
+        cache,
+        pinning,
     };
 
     let svc = ServiceBuilder::new()
@@ -40,6 +47,8 @@ struct IpfsServiceInner {
     client: Arc<dyn IpfsClient>,
     timeout: Duration,
     max_file_size: usize,
+    cache: Option<Arc<OffchainCache>>,
+    pinning: Option<Arc<PinningService>>,
 }
 
 impl IpfsServiceInner {
@@ -49,6 +58,17 @@ impl IpfsServiceInner {
             return Err(anyhow!("CID multihash {} is not allowed", multihash));
         }
 
+        let key = path.to_string();
+        if let Some(cache) = &self.cache {
+            match cache.get("ipfs", &key).await {
+                Some(file_bytes) if file_bytes.len() <= self.max_file_size => {
+                    self.pin(&path);
+                    return Ok(Some(file_bytes));
+                }
+                _ => {}
+            }
+        }
+
         let res = self
             .client
             .cat(
@@ -60,7 +80,13 @@ impl IpfsServiceInner {
             .await;
 
         match res {
-            Ok(file_bytes) => Ok(Some(file_bytes)),
+            Ok(file_bytes) => {
+                if let Some(cache) = &self.cache {
+                    cache.insert("ipfs", &key, &file_bytes).await;
+                }
+                self.pin(&path);
+                Ok(Some(file_bytes))
+            }
             Err(err) if err.is_timeout() => {
                 // Timeouts in IPFS mean that the content is not available, so we return `None`.
                 Ok(None)
@@ -68,6 +94,12 @@ impl IpfsServiceInner {
             Err(err) => Err(err.into()),
         }
     }
+
+    fn pin(&self, path: &ContentPath) {
+        if let Some(pinning) = &self.pinning {
+            pinning.pin(path);
+        }
+    }
 }
 
 // Multihashes that are collision resistant. This is not complete but covers the commonly used ones.
@@ -127,7 +159,14 @@ mod test {
             IpfsRpcClient::new_unchecked(ServerAddress::local_rpc_api(), &graph::log::discard())
                 .unwrap();
 
-        let svc = ipfs_service(Arc::new(client), 100000, Duration::from_secs(30), 10);
+        let svc = ipfs_service(
+            Arc::new(client),
+            100000,
+            Duration::from_secs(30),
+            10,
+            None,
+            None,
+        );
 
         let path = ContentPath::new(format!("{dir_cid}/file.txt")).unwrap();
         let content = svc.oneshot(path).await.unwrap().unwrap();
@@ -155,7 +194,14 @@ mod test {
 
         let server = MockServer::start().await;
         let ipfs_client = IpfsRpcClient::new_unchecked(server.uri(), &discard()).unwrap();
-        let ipfs_service = ipfs_service(Arc::new(ipfs_client), 10, Duration::from_secs(1), 1);
+        let ipfs_service = ipfs_service(
+            Arc::new(ipfs_client),
+            10,
+            Duration::from_secs(1),
+            1,
+            None,
+            None,
+        );
         let path = ContentPath::new(CID).unwrap();
 
         Mock::given(m::method("POST"))
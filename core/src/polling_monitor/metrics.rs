@@ -10,6 +10,7 @@ pub struct PollingMonitorMetrics {
     pub requests: Counter,
     pub errors: Counter,
     pub not_found: Counter,
+    pub given_up: Counter,
     pub queue_depth: Gauge,
 }
 
@@ -36,6 +37,13 @@ impl PollingMonitorMetrics {
                 subgraph_hash.as_str(),
             )
             .unwrap();
+        let given_up = registry
+            .new_deployment_counter(
+                "polling_monitor_given_up",
+                "counts the objects that the monitor stopped polling after too many failed attempts",
+                subgraph_hash.as_str(),
+            )
+            .unwrap();
         let queue_depth = registry
             .new_deployment_gauge(
                 "polling_monitor_queue_depth",
@@ -47,6 +55,7 @@ impl PollingMonitorMetrics {
             requests,
             errors,
             not_found,
+            given_up,
             queue_depth,
         }
     }
@@ -57,6 +66,7 @@ impl PollingMonitorMetrics {
             requests: Counter::new("x", " ").unwrap(),
             errors: Counter::new("y", " ").unwrap(),
             not_found: Counter::new("z", " ").unwrap(),
+            given_up: Counter::new("v", " ").unwrap(),
             queue_depth: Gauge::new("w", " ").unwrap(),
         }
     }
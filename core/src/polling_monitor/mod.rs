@@ -1,6 +1,8 @@
 mod arweave_service;
+mod http_service;
 mod ipfs_service;
 mod metrics;
+mod offchain_cache;
 
 use std::collections::HashMap;
 use std::fmt::Display;
@@ -10,6 +12,7 @@ use std::task::Poll;
 use std::time::Duration;
 
 use graph::cheap_clone::CheapClone;
+use graph::components::subgraph::FetchPolicy;
 use graph::futures03::future::BoxFuture;
 use graph::futures03::stream::StreamExt;
 use graph::futures03::{stream, Future, FutureExt, TryFutureExt};
@@ -21,33 +24,49 @@ use graph::util::monitored::MonitoredVecDeque as VecDeque;
 use tokio::sync::{mpsc, watch};
 use tower::retry::backoff::{Backoff, ExponentialBackoff, ExponentialBackoffMaker, MakeBackoff};
 use tower::util::rng::HasherRng;
-use tower::{Service, ServiceExt};
+use tower::{BoxError, Service, ServiceExt};
 
 pub use self::metrics::PollingMonitorMetrics;
+pub use self::offchain_cache::OffchainCache;
 pub use arweave_service::{arweave_service, ArweaveService};
+pub use http_service::{http_service, HttpService};
 pub use ipfs_service::{ipfs_service, IpfsService};
 
-const MIN_BACKOFF: Duration = Duration::from_secs(5);
+/// An error that polling the object again can not fix, like a file whose
+/// contents do not have the expected hash. The monitor gives up on the
+/// object right away, regardless of how many attempts the policy allows
+#[derive(Debug)]
+pub struct PermanentError(pub String);
 
-const MAX_BACKOFF: Duration = Duration::from_secs(600);
+impl Display for PermanentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for PermanentError {}
 
 struct Backoffs<ID> {
     backoff_maker: ExponentialBackoffMaker,
     backoffs: HashMap<ID, ExponentialBackoff>,
+    max_attempts: Option<u32>,
+    failed_attempts: HashMap<ID, u32>,
 }
 
-impl<ID: Eq + Hash> Backoffs<ID> {
-    fn new() -> Self {
-        // Unwrap: Config is constant and valid.
+impl<ID: Eq + Hash + Clone> Backoffs<ID> {
+    fn new(policy: &FetchPolicy) -> Self {
+        // Unwrap: The policy has been validated.
         Self {
             backoff_maker: ExponentialBackoffMaker::new(
-                MIN_BACKOFF,
-                MAX_BACKOFF,
+                policy.min_backoff(),
+                policy.max_backoff(),
                 1.0,
                 HasherRng::new(),
             )
             .unwrap(),
             backoffs: HashMap::new(),
+            max_attempts: policy.max_attempts,
+            failed_attempts: HashMap::new(),
         }
     }
 
@@ -58,8 +77,24 @@ impl<ID: Eq + Hash> Backoffs<ID> {
             .next_backoff()
     }
 
+    /// Count a failed attempt for `id`, and return `true` if that was the
+    /// last attempt that the policy allows
+    fn fail(&mut self, id: &ID) -> bool {
+        let Some(max_attempts) = self.max_attempts else {
+            return false;
+        };
+        let attempts = self.failed_attempts.entry(id.clone()).or_default();
+        *attempts += 1;
+        if *attempts >= max_attempts {
+            self.remove(id);
+            return true;
+        }
+        false
+    }
+
     fn remove(&mut self, id: &ID) {
         self.backoffs.remove(id);
+        self.failed_attempts.remove(id);
     }
 }
 
@@ -94,20 +129,24 @@ impl<T> Queue<T> {
 
 /// Spawn a monitor that actively polls a service. Whenever the service has capacity, the monitor
 /// pulls object ids from the queue and polls the service. If the object is not present or in case
-/// of error, the object id is pushed to the back of the queue to be polled again.
+/// of error, the object id is pushed to the back of the queue to be polled again. Once polling an
+/// object failed as often as the `policy` allows, or failed with a [`PermanentError`], the monitor
+/// gives up on it and sends its id together with the reason to `failure_sender`.
 ///
 /// The service returns the request ID along with errors or responses. The response is an
 /// `Option`, to represent the object not being found.
 pub fn spawn_monitor<ID, S, E, Res: Send + 'static>(
     service: S,
     response_sender: mpsc::UnboundedSender<(ID, Res)>,
+    failure_sender: mpsc::UnboundedSender<(ID, String)>,
+    policy: &FetchPolicy,
     logger: Logger,
     metrics: Arc<PollingMonitorMetrics>,
 ) -> PollingMonitor<ID>
 where
     S: Service<ID, Response = Option<Res>, Error = E> + Send + 'static,
     ID: Display + Clone + Default + Eq + Send + Sync + Hash + 'static,
-    E: Display + Send + 'static,
+    E: Into<BoxError> + Send + 'static,
     S::Future: Send,
 {
     let service = ReturnRequest { service };
@@ -147,8 +186,8 @@ where
 
     {
         let queue = queue.cheap_clone();
+        let mut backoffs = Backoffs::new(policy);
         graph::spawn(async move {
-            let mut backoffs = Backoffs::new();
             let mut responses = service.call_all(queue_to_stream).unordered().boxed();
             while let Some(response) = responses.next().await {
                 // Note: Be careful not to `await` within this loop, as that could block requests in
@@ -169,15 +208,30 @@ where
                         debug!(logger, "not found on polling"; "object_id" => id.to_string());
 
                         metrics.not_found.inc();
+                        if backoffs.fail(&id) {
+                            metrics.given_up.inc();
+                            let _ = failure_sender.send((id, "the file was not found".to_string()));
+                            continue;
+                        }
                         queue.push_back(id);
                     }
 
                     // Error polling, log it and push the id to the back of the queue.
                     Err((id, e)) => {
+                        let e: BoxError = e.into();
                         debug!(logger, "error polling";
                                     "error" => format!("{:#}", e),
                                     "object_id" => id.to_string());
                         metrics.errors.inc();
+                        let permanent = e.downcast_ref::<PermanentError>().is_some();
+                        if permanent {
+                            backoffs.remove(&id);
+                        }
+                        if permanent || backoffs.fail(&id) {
+                            metrics.given_up.inc();
+                            let _ = failure_sender.send((id, format!("{:#}", e)));
+                            continue;
+                        }
 
                         // Requests that return errors could mean there is a permanent issue with
                         // fetching the given item, or could signal the endpoint is overloaded.
@@ -259,9 +313,12 @@ mod tests {
     ) {
         let (svc, handle) = mock::pair();
         let (tx, rx) = mpsc::unbounded_channel();
+        let (failure_tx, _) = mpsc::unbounded_channel();
         let monitor = spawn_monitor(
             svc,
             tx,
+            failure_tx,
+            &FetchPolicy::default(),
             log::discard(),
             Arc::new(PollingMonitorMetrics::mock()),
         );
@@ -274,8 +331,10 @@ mod tests {
         let shared_svc = tower::buffer::Buffer::new(tower::limit::ConcurrencyLimit::new(svc, 1), 1);
         let make_monitor = |svc| {
             let (tx, rx) = mpsc::unbounded_channel();
+            let (failure_tx, _) = mpsc::unbounded_channel();
             let metrics = Arc::new(PollingMonitorMetrics::mock());
-            let monitor = spawn_monitor(svc, tx, log::discard(), metrics);
+            let policy = FetchPolicy::default();
+            let monitor = spawn_monitor(svc, tx, failure_tx, &policy, log::discard(), metrics);
             (monitor, rx)
         };
 
@@ -341,6 +400,68 @@ mod tests {
         assert_eq!(rx.recv().await, Some(("req-1", "res-1")));
     }
 
+    #[tokio::test]
+    async fn polling_monitor_gives_up() {
+        let (svc, mut handle) = mock::pair();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (failure_tx, mut failure_rx) = mpsc::unbounded_channel();
+        let policy = FetchPolicy {
+            max_attempts: Some(2),
+            min_backoff_secs: Some(1),
+            max_backoff_secs: Some(1),
+            ..Default::default()
+        };
+        let monitor = spawn_monitor(
+            svc,
+            tx,
+            failure_tx,
+            &policy,
+            log::discard(),
+            Arc::new(PollingMonitorMetrics::mock()),
+        );
+
+        // Not found and errors both count as failed attempts
+        monitor.monitor("req-0");
+        send_response(&mut handle, None).await;
+        let req = handle.next_request().await.unwrap().1;
+        req.send_error(anyhow!("e"));
+        assert_eq!(failure_rx.recv().await, Some(("req-0", "e".to_string())));
+
+        // A success resets the count
+        monitor.monitor("req-1");
+        send_response(&mut handle, None).await;
+        send_response(&mut handle, Some("res-1")).await;
+        assert_eq!(rx.recv().await, Some(("req-1", "res-1")));
+        monitor.monitor("req-1");
+        send_response(&mut handle, None).await;
+        send_response(&mut handle, Some("res-1")).await;
+        assert_eq!(rx.recv().await, Some(("req-1", "res-1")));
+    }
+
+    #[tokio::test]
+    async fn polling_monitor_gives_up_on_permanent_errors() {
+        let (svc, mut handle) = mock::pair();
+        let (tx, _rx) = mpsc::unbounded_channel::<(&str, &str)>();
+        let (failure_tx, mut failure_rx) = mpsc::unbounded_channel();
+        let monitor = spawn_monitor(
+            svc,
+            tx,
+            failure_tx,
+            &FetchPolicy::default(),
+            log::discard(),
+            Arc::new(PollingMonitorMetrics::mock()),
+        );
+
+        // The default policy polls forever, but not after permanent errors
+        monitor.monitor("req-0");
+        let req = handle.next_request().await.unwrap().1;
+        req.send_error(PermanentError("wrong hash".to_string()));
+        assert_eq!(
+            failure_rx.recv().await,
+            Some(("req-0", "wrong hash".to_string()))
+        );
+    }
+
     #[tokio::test]
     async fn polling_monitor_cancelation() {
         // Cancelation on receiver drop, no pending request.
@@ -0,0 +1,237 @@
+//! A cache of the files that offchain data sources fetched, on disk. The
+//! files are immutable since they are addressed by their contents, and
+//! fetching them again from gateways after a restart, a rewind or a copy of
+//! a deployment is slow and puts load on the gateways
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use anyhow::Error;
+use bytes::Bytes;
+use graph::prelude::hex;
+use graph::slog::{warn, Logger};
+use graph::tokio;
+use sha2::{Digest, Sha256};
+
+/// The files in the cache, keyed by file name
+#[derive(Default)]
+struct Entries {
+    /// Increases with every use of a file
+    tick: u64,
+    /// The total size of the files
+    size: u64,
+    /// File names mapped to when they were last used and their size
+    files: HashMap<String, (u64, u64)>,
+    /// When files were last used mapped to their name, least recently
+    /// used first
+    uses: BTreeMap<u64, String>,
+}
+
+impl Entries {
+    fn touch(&mut self, name: &str) -> bool {
+        let Some((used, _)) = self.files.get_mut(name) else {
+            return false;
+        };
+        self.uses.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.uses.insert(self.tick, name.to_string());
+        true
+    }
+
+    fn insert(&mut self, name: String, size: u64) {
+        if self.touch(&name) {
+            return;
+        }
+        self.tick += 1;
+        self.size += size;
+        self.files.insert(name.clone(), (self.tick, size));
+        self.uses.insert(self.tick, name);
+    }
+
+    fn remove(&mut self, name: &str) {
+        if let Some((used, size)) = self.files.remove(name) {
+            self.uses.remove(&used);
+            self.size -= size;
+        }
+    }
+
+    /// Remove the least recently used files until the files take up at
+    /// most `max_size` bytes, and return their names
+    fn evict(&mut self, max_size: u64) -> Vec<String> {
+        let mut evicted = vec![];
+        while self.size > max_size {
+            let Some((_, name)) = self.uses.pop_first() else {
+                break;
+            };
+            if let Some((_, size)) = self.files.remove(&name) {
+                self.size -= size;
+            }
+            evicted.push(name);
+        }
+        evicted
+    }
+}
+
+/// Stores the contents of offchain files in a directory, and removes the
+/// least recently used files once they take up more than `max_size` bytes.
+/// Problems with the cache are logged but never fail a request; in the
+/// worst case, the file is fetched again
+pub struct OffchainCache {
+    dir: PathBuf,
+    max_size: u64,
+    entries: Mutex<Entries>,
+    logger: Logger,
+}
+
+impl OffchainCache {
+    /// Create a cache in `dir` that knows about the files that are already
+    /// in it, for example from before a restart
+    pub fn new(logger: Logger, dir: PathBuf, max_size: usize) -> Result<Self, Error> {
+        fs::create_dir_all(&dir)?;
+
+        let mut files = vec![];
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Leftovers from writes that were interrupted
+            if name.contains(".tmp-") {
+                let _ = fs::remove_file(entry.path());
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                files.push((metadata.modified()?, name, metadata.len()));
+            }
+        }
+        // Without better information, files that were written last are
+        // considered the most recently used ones
+        files.sort();
+
+        let mut entries = Entries::default();
+        for (_, name, size) in files {
+            entries.insert(name, size);
+        }
+
+        let cache = OffchainCache {
+            dir,
+            max_size: max_size as u64,
+            entries: Mutex::new(entries),
+            logger,
+        };
+        cache.evict();
+        Ok(cache)
+    }
+
+    fn file_name(kind: &str, key: &str) -> String {
+        format!("{kind}-{}", hex::encode(Sha256::digest(key)))
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// The contents of the file of `kind` with `key`, if they are cached
+    pub async fn get(&self, kind: &str, key: &str) -> Option<Bytes> {
+        let name = Self::file_name(kind, key);
+        if !self.entries.lock().unwrap().touch(&name) {
+            return None;
+        }
+
+        match tokio::fs::read(self.path(&name)).await {
+            Ok(data) => Some(Bytes::from(data)),
+            Err(e) => {
+                warn!(self.logger, "Failed to read offchain file from cache";
+                    "kind" => kind,
+                    "key" => key,
+                    "error" => e.to_string());
+                self.entries.lock().unwrap().remove(&name);
+                None
+            }
+        }
+    }
+
+    /// Store the contents of the file of `kind` with `key`
+    pub async fn insert(&self, kind: &str, key: &str, data: &Bytes) {
+        let name = Self::file_name(kind, key);
+        if data.len() as u64 > self.max_size {
+            return;
+        }
+
+        if let Err(e) = write(&self.path(&name), data).await {
+            warn!(self.logger, "Failed to store offchain file in cache";
+                "kind" => kind,
+                "key" => key,
+                "error" => e.to_string());
+            return;
+        }
+
+        self.entries.lock().unwrap().insert(name, data.len() as u64);
+        self.evict();
+    }
+
+    fn evict(&self) {
+        let evicted = self.entries.lock().unwrap().evict(self.max_size);
+        for name in evicted {
+            let _ = fs::remove_file(self.path(&name));
+        }
+    }
+}
+
+async fn write(path: &Path, data: &Bytes) -> Result<(), Error> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+
+    // The same file might be fetched for several deployments at the same
+    // time; writing to a temporary file and renaming it makes sure that
+    // nobody ever reads a partially written file
+    let tmp = path.with_extension(format!(
+        "tmp-{}-{}",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    tokio::fs::write(&tmp, data).await?;
+    if let Err(e) = tokio::fs::rename(&tmp, path).await {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use graph::log::discard;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn least_recently_used_files_are_evicted() {
+        let dir = std::env::temp_dir().join(format!("offchain-cache-{}", uuid::Uuid::new_v4()));
+        let cache = OffchainCache::new(discard(), dir.clone(), 10).unwrap();
+        let data = |s: &'static str| Bytes::from_static(s.as_bytes());
+
+        cache.insert("ipfs", "a", &data("aaaa")).await;
+        cache.insert("ipfs", "b", &data("bbbb")).await;
+        assert_eq!(Some(data("aaaa")), cache.get("ipfs", "a").await);
+        // Files of different kinds do not clash
+        assert_eq!(None, cache.get("arweave", "a").await);
+
+        // `b` is the least recently used file
+        cache.insert("ipfs", "c", &data("cccc")).await;
+        assert_eq!(None, cache.get("ipfs", "b").await);
+        assert_eq!(Some(data("aaaa")), cache.get("ipfs", "a").await);
+        assert_eq!(Some(data("cccc")), cache.get("ipfs", "c").await);
+
+        // Files that are larger than the cache are not stored
+        cache.insert("ipfs", "d", &data("ddddddddddd")).await;
+        assert_eq!(None, cache.get("ipfs", "d").await);
+
+        // The files are still there after a restart
+        let cache = OffchainCache::new(discard(), dir.clone(), 10).unwrap();
+        assert_eq!(Some(data("aaaa")), cache.get("ipfs", "a").await);
+        assert_eq!(Some(data("cccc")), cache.get("ipfs", "c").await);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,6 +4,8 @@ use anyhow::ensure;
 use graph::futures01::sync::mpsc::Sender;
 use graph::{
     blockchain::{Blockchain, TriggerData as _},
+    components::subgraph::HandlerLimits,
+    data::store::scalar::BigDecimalPrecision,
     data_source::{
         causality_region::CausalityRegionSeq, offchain, CausalityRegion, DataSource,
         DataSourceTemplate, TriggerData,
@@ -11,7 +13,7 @@ use graph::{
     prelude::*,
 };
 use hosts::{OffchainHosts, OnchainHosts};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub(crate) struct SubgraphInstance<C: Blockchain, T: RuntimeHostBuilder<C>> {
     subgraph_id: DeploymentHash,
@@ -21,6 +23,14 @@ pub(crate) struct SubgraphInstance<C: Blockchain, T: RuntimeHostBuilder<C>> {
     /// The data sources declared in the subgraph manifest. This does not include dynamic data sources.
     pub(super) static_data_sources: Arc<Vec<DataSource<C>>>,
     host_metrics: Arc<HostMetrics>,
+    big_decimal_precision: BigDecimalPrecision,
+    /// Whether the manifest lets the store prune the history of the
+    /// deployment
+    prunes_history: bool,
+    /// The limits for handlers, with the defaults from the manifest and the environment applied
+    handler_limits: HandlerLimits,
+    /// The constants that mappings can read with `dataSource.env`
+    mapping_env: Arc<BTreeMap<String, String>>,
 
     /// The hosts represent the data sources in the subgraph. There is one host per data source.
     /// Data sources with no mappings (e.g. direct substreams) have no host.
@@ -31,6 +41,10 @@ pub(crate) struct SubgraphInstance<C: Blockchain, T: RuntimeHostBuilder<C>> {
 
     offchain_hosts: OffchainHosts<C, T>,
 
+    /// Hosts for subgraph data sources. These can only be declared in the manifest, so there
+    /// are few of them and they are never reverted.
+    subgraph_hosts: Vec<Arc<T::Host>>,
+
     /// Maps the hash of a module to a channel to the thread in which the module is instantiated.
     module_cache: HashMap<[u8; 32], Sender<T::Req>>,
 
@@ -68,9 +82,15 @@ where
         host_builder: T,
         host_metrics: Arc<HostMetrics>,
         causality_region_seq: CausalityRegionSeq,
+        handler_limits: HandlerLimits,
+        mapping_env: BTreeMap<String, String>,
     ) -> Self {
         let subgraph_id = manifest.id.clone();
         let network = manifest.network_name();
+        let big_decimal_precision = manifest.big_decimal_precision();
+        let prunes_history =
+            manifest.history_blocks() != BLOCK_NUMBER_MAX || manifest.schema.has_retention();
+        let handler_limits = handler_limits.with_defaults(manifest.handler_fuel);
         let templates = Arc::new(manifest.templates);
 
         SubgraphInstance {
@@ -80,9 +100,14 @@ where
             static_data_sources: Arc::new(manifest.data_sources),
             onchain_hosts: OnchainHosts::new(),
             offchain_hosts: OffchainHosts::new(),
+            subgraph_hosts: Vec::new(),
             module_cache: HashMap::new(),
             templates,
             host_metrics,
+            big_decimal_precision,
+            prunes_history,
+            handler_limits,
+            mapping_env: Arc::new(mapping_env),
             causality_region_seq,
         }
     }
@@ -108,6 +133,7 @@ where
                     logger,
                     self.subgraph_id.clone(),
                     self.host_metrics.cheap_clone(),
+                    &self.handler_limits,
                 )?;
                 self.module_cache.insert(module_hash, sender.clone());
                 sender
@@ -121,6 +147,9 @@ where
             self.templates.cheap_clone(),
             mapping_request_sender,
             self.host_metrics.cheap_clone(),
+            self.big_decimal_precision,
+            self.prunes_history,
+            self.mapping_env.cheap_clone(),
         )?;
         Ok(Some(Arc::new(host)))
     }
@@ -139,6 +168,7 @@ where
         }
 
         let is_onchain = data_source.is_onchain();
+        let is_subgraph = data_source.is_subgraph();
         let Some(host) = self.new_host(logger.clone(), data_source)? else {
             return Ok(None);
         };
@@ -160,6 +190,13 @@ where
                 self.onchain_hosts.push(host.cheap_clone());
                 Ok(Some(host))
             }
+        } else if is_subgraph {
+            if self.subgraph_hosts.contains(&host) {
+                Ok(None)
+            } else {
+                self.subgraph_hosts.push(host.cheap_clone());
+                Ok(Some(host))
+            }
         } else {
             if self.offchain_hosts.contains(&host) {
                 Ok(None)
@@ -226,6 +263,10 @@ where
             TriggerData::Offchain(trigger) => self
                 .offchain_hosts
                 .matches_by_address(trigger.source.address().as_ref().map(|a| a.as_slice())),
+            // `match_and_decode` checks that the trigger comes from the source of the data source
+            TriggerData::Subgraph(_) => {
+                Box::new(self.subgraph_hosts.iter().map(|host| host.as_ref()))
+            }
         }
     }
 
@@ -234,7 +275,7 @@ where
     }
 
     pub fn hosts_len(&self) -> usize {
-        self.onchain_hosts.len() + self.offchain_hosts.len()
+        self.onchain_hosts.len() + self.offchain_hosts.len() + self.subgraph_hosts.len()
     }
 
     pub fn first_host(&self) -> Option<&Arc<T::Host>> {
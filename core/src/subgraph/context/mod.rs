@@ -1,34 +1,40 @@
 mod instance;
 
 use crate::polling_monitor::{
-    spawn_monitor, ArweaveService, IpfsService, PollingMonitor, PollingMonitorMetrics,
+    spawn_monitor, ArweaveService, HttpService, IpfsService, PollingMonitor, PollingMonitorMetrics,
 };
 use anyhow::{self, Error};
 use bytes::Bytes;
 use graph::{
     blockchain::{BlockTime, Blockchain},
     components::{
-        store::{DeploymentId, SubgraphFork},
-        subgraph::{HostMetrics, MappingError, RuntimeHost as _, SharedProofOfIndexing},
+        store::{DeploymentId, DeploymentLocator, SubgraphFork, SubgraphStore},
+        subgraph::{
+            FetchPolicy, HandlerLimits, HostMetrics, MappingError, OffchainFailure,
+            RuntimeHost as _, SharedProofOfIndexing,
+        },
     },
     data::subgraph::SubgraphManifest,
     data_source::{
         causality_region::CausalityRegionSeq,
-        offchain::{self, Base64},
+        offchain::{self, Base64, HttpSource, OffchainDataSourceKind},
         CausalityRegion, DataSource, DataSourceTemplate,
     },
     derive::CheapClone,
     ipfs::ContentPath,
     prelude::{
-        BlockNumber, BlockPtr, BlockState, CancelGuard, CheapClone, DeploymentHash,
-        MetricsRegistry, RuntimeHostBuilder, SubgraphCountMetric, SubgraphInstanceMetrics,
-        TriggerProcessor,
+        BlockNumber, BlockPtr, BlockState, CancelGuard, CheapClone, MetricsRegistry,
+        RuntimeHostBuilder, SubgraphCountMetric, SubgraphInstanceMetrics, TriggerProcessor,
     },
-    slog::Logger,
-    tokio::sync::mpsc,
+    slog::{debug, warn, Logger},
+    tokio::sync::{mpsc, Semaphore},
 };
 use std::sync::{Arc, RwLock};
-use std::{collections::HashMap, time::Instant};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::Instant,
+};
+use tower::limit::ConcurrencyLimit;
 
 use self::instance::SubgraphInstance;
 
@@ -84,6 +90,8 @@ impl<C: Blockchain, T: RuntimeHostBuilder<C>> IndexingContext<C, T> {
         host_builder: T,
         host_metrics: Arc<HostMetrics>,
         causality_region_seq: CausalityRegionSeq,
+        handler_limits: HandlerLimits,
+        mapping_env: BTreeMap<String, String>,
         instances: SubgraphKeepAlive,
         offchain_monitor: OffchainMonitor,
         trigger_processor: Box<dyn TriggerProcessor<C, T>>,
@@ -94,6 +102,8 @@ impl<C: Blockchain, T: RuntimeHostBuilder<C>> IndexingContext<C, T> {
             host_builder,
             host_metrics.clone(),
             causality_region_seq,
+            handler_limits,
+            mapping_env,
         );
 
         Self {
@@ -230,78 +240,217 @@ impl<C: Blockchain, T: RuntimeHostBuilder<C>> IndexingContext<C, T> {
 pub struct OffchainMonitor {
     ipfs_monitor: PollingMonitor<ContentPath>,
     ipfs_monitor_rx: mpsc::UnboundedReceiver<(ContentPath, Bytes)>,
+    ipfs_failure_rx: mpsc::UnboundedReceiver<(ContentPath, String)>,
     arweave_monitor: PollingMonitor<Base64>,
     arweave_monitor_rx: mpsc::UnboundedReceiver<(Base64, Bytes)>,
+    arweave_failure_rx: mpsc::UnboundedReceiver<(Base64, String)>,
+    http_monitor: PollingMonitor<HttpSource>,
+    http_monitor_rx: mpsc::UnboundedReceiver<(HttpSource, Bytes)>,
+    http_failure_rx: mpsc::UnboundedReceiver<(HttpSource, String)>,
+    policy: FetchPolicy,
+    /// The kind and source of the files that the deployment gave up on
+    failed: HashSet<(String, String)>,
+    store: Arc<dyn SubgraphStore>,
+    deployment: DeploymentLocator,
+    logger: Logger,
 }
 
 impl OffchainMonitor {
     pub fn new(
         logger: Logger,
         registry: Arc<MetricsRegistry>,
-        subgraph_hash: &DeploymentHash,
+        deployment: &DeploymentLocator,
         ipfs_service: IpfsService,
         arweave_service: ArweaveService,
-    ) -> Self {
-        let metrics = Arc::new(PollingMonitorMetrics::new(registry, subgraph_hash));
-        // The channel is unbounded, as it is expected that `fn ready_offchain_events` is called
+        http_service: HttpService,
+        store: Arc<dyn SubgraphStore>,
+    ) -> Result<Self, Error> {
+        let policy = store.fetch_policy(deployment)?.unwrap_or_default();
+        let failed = store
+            .offchain_failures(deployment)?
+            .into_iter()
+            .map(|failure| (failure.kind, failure.source))
+            .collect();
+
+        let metrics = Arc::new(PollingMonitorMetrics::new(registry, &deployment.hash));
+        // The channels are unbounded, as it is expected that `fn ready_offchain_events` is called
         // frequently, or at least with the same frequency that requests are sent.
         let (ipfs_monitor_tx, ipfs_monitor_rx) = mpsc::unbounded_channel();
+        let (ipfs_failure_tx, ipfs_failure_rx) = mpsc::unbounded_channel();
         let (arweave_monitor_tx, arweave_monitor_rx) = mpsc::unbounded_channel();
+        let (arweave_failure_tx, arweave_failure_rx) = mpsc::unbounded_channel();
+        let (http_monitor_tx, http_monitor_rx) = mpsc::unbounded_channel();
+        let (http_failure_tx, http_failure_rx) = mpsc::unbounded_channel();
+
+        // Files of all kinds count towards the files the deployment may
+        // fetch at the same time
+        let in_flight = Arc::new(Semaphore::new(
+            policy.max_in_flight.unwrap_or(Semaphore::MAX_PERMITS),
+        ));
 
         let ipfs_monitor = spawn_monitor(
-            ipfs_service,
+            ConcurrencyLimit::with_semaphore(ipfs_service, in_flight.cheap_clone()),
             ipfs_monitor_tx,
+            ipfs_failure_tx,
+            &policy,
             logger.cheap_clone(),
             metrics.cheap_clone(),
         );
 
-        let arweave_monitor = spawn_monitor(arweave_service, arweave_monitor_tx, logger, metrics);
-        Self {
+        let arweave_monitor = spawn_monitor(
+            ConcurrencyLimit::with_semaphore(arweave_service, in_flight.cheap_clone()),
+            arweave_monitor_tx,
+            arweave_failure_tx,
+            &policy,
+            logger.cheap_clone(),
+            metrics.cheap_clone(),
+        );
+        let http_monitor = spawn_monitor(
+            ConcurrencyLimit::with_semaphore(http_service, in_flight),
+            http_monitor_tx,
+            http_failure_tx,
+            &policy,
+            logger.cheap_clone(),
+            metrics,
+        );
+        Ok(Self {
             ipfs_monitor,
             ipfs_monitor_rx,
+            ipfs_failure_rx,
             arweave_monitor,
             arweave_monitor_rx,
-        }
+            arweave_failure_rx,
+            http_monitor,
+            http_monitor_rx,
+            http_failure_rx,
+            policy,
+            failed,
+            store,
+            deployment: deployment.clone(),
+            logger,
+        })
     }
 
     fn add_source(&mut self, source: offchain::Source) -> Result<(), Error> {
+        let failure_key = failure_key(&source);
+        if self.failed.contains(&failure_key) {
+            debug!(self.logger, "Not fetching offchain file that was given up on before, \
+                use `graphman offchain-failures` to retry it";
+                "source" => &failure_key.1);
+            return Ok(());
+        }
+
         match source {
             offchain::Source::Ipfs(cid_file) => self.ipfs_monitor.monitor(cid_file),
             offchain::Source::Arweave(base64) => self.arweave_monitor.monitor(base64),
+            offchain::Source::Http(source) => self.http_monitor.monitor(source),
         };
         Ok(())
     }
 
     pub fn ready_offchain_events(&mut self) -> Result<Vec<offchain::TriggerData>, Error> {
-        use graph::tokio::sync::mpsc::error::TryRecvError;
+        let mut responses = vec![];
+        responses.extend(
+            drain(&mut self.ipfs_monitor_rx, "ipfs")?
+                .into_iter()
+                .map(|(cid_file, data)| (offchain::Source::Ipfs(cid_file), data)),
+        );
+        responses.extend(
+            drain(&mut self.arweave_monitor_rx, "arweave")?
+                .into_iter()
+                .map(|(base64, data)| (offchain::Source::Arweave(base64), data)),
+        );
+        responses.extend(
+            drain(&mut self.http_monitor_rx, "http")?
+                .into_iter()
+                .map(|(source, data)| (offchain::Source::Http(source), data)),
+        );
+
+        let mut failures = vec![];
+        failures.extend(
+            drain(&mut self.ipfs_failure_rx, "ipfs")?
+                .into_iter()
+                .map(|(cid_file, error)| (offchain::Source::Ipfs(cid_file), error)),
+        );
+        failures.extend(
+            drain(&mut self.arweave_failure_rx, "arweave")?
+                .into_iter()
+                .map(|(base64, error)| (offchain::Source::Arweave(base64), error)),
+        );
+        failures.extend(
+            drain(&mut self.http_failure_rx, "http")?
+                .into_iter()
+                .map(|(source, error)| (offchain::Source::Http(source), error)),
+        );
 
         let mut triggers = vec![];
-        loop {
-            match self.ipfs_monitor_rx.try_recv() {
-                Ok((cid_file, data)) => triggers.push(offchain::TriggerData {
-                    source: offchain::Source::Ipfs(cid_file),
+        for (source, data) in responses {
+            match self.policy.check(&data) {
+                Ok(()) => triggers.push(offchain::TriggerData {
+                    source,
                     data: Arc::new(data),
                 }),
-                Err(TryRecvError::Disconnected) => {
-                    anyhow::bail!("ipfs monitor unexpectedly terminated")
-                }
-                Err(TryRecvError::Empty) => break,
+                Err(error) => failures.push((source, error)),
             }
         }
 
-        loop {
-            match self.arweave_monitor_rx.try_recv() {
-                Ok((base64, data)) => triggers.push(offchain::TriggerData {
-                    source: offchain::Source::Arweave(base64),
-                    data: Arc::new(data),
-                }),
-                Err(TryRecvError::Disconnected) => {
-                    anyhow::bail!("arweave monitor unexpectedly terminated")
-                }
-                Err(TryRecvError::Empty) => break,
-            }
+        for (source, error) in failures {
+            self.give_up(&source, error);
         }
 
         Ok(triggers)
     }
+
+    /// Stop fetching the file of `source` for good, also across restarts,
+    /// until it is retried with graphman
+    fn give_up(&mut self, source: &offchain::Source, error: String) {
+        let (kind, key) = failure_key(source);
+        warn!(self.logger, "Giving up on offchain file";
+            "kind" => &kind,
+            "source" => &key,
+            "error" => &error);
+
+        let failure = OffchainFailure {
+            kind,
+            source: key,
+            error,
+        };
+        // The failure is still remembered until the deployment restarts
+        if let Err(e) = self
+            .store
+            .record_offchain_failure(&self.deployment, &failure)
+        {
+            warn!(self.logger, "Failed to record offchain file that was given up on";
+                "source" => &failure.source,
+                "error" => e.to_string());
+        }
+        self.failed.insert((failure.kind, failure.source));
+    }
+}
+
+/// The kind and source under which failures of `source` are recorded
+fn failure_key(source: &offchain::Source) -> (String, String) {
+    let (kind, key) = match source {
+        offchain::Source::Ipfs(path) => (OffchainDataSourceKind::Ipfs, path.to_string()),
+        offchain::Source::Arweave(base64) => (OffchainDataSourceKind::Arweave, base64.to_string()),
+        offchain::Source::Http(source) => (OffchainDataSourceKind::Http, source.to_string()),
+    };
+    (kind.to_string(), key)
+}
+
+/// Everything that is in `rx` right now
+fn drain<T>(rx: &mut mpsc::UnboundedReceiver<T>, name: &str) -> Result<Vec<T>, Error> {
+    use graph::tokio::sync::mpsc::error::TryRecvError;
+
+    let mut items = vec![];
+    loop {
+        match rx.try_recv() {
+            Ok(item) => items.push(item),
+            Err(TryRecvError::Disconnected) => {
+                anyhow::bail!("{} monitor unexpectedly terminated", name)
+            }
+            Err(TryRecvError::Empty) => break,
+        }
+    }
+    Ok(items)
 }
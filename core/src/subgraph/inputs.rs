@@ -1,14 +1,14 @@
 use graph::{
-    blockchain::{Blockchain, TriggersAdapter},
+    blockchain::{Blockchain, IngestionMode, TriggersAdapter},
     components::{
-        store::{DeploymentLocator, SubgraphFork, WritableStore},
-        subgraph::ProofOfIndexingVersion,
+        store::{DeploymentLocator, SourceableStore, SubgraphFork, WritableStore},
+        subgraph::{ProofOfIndexingVersion, RetryPolicy},
     },
     data::subgraph::{SubgraphFeature, UnifiedMappingApiVersion},
     data_source::DataSourceTemplate,
-    prelude::BlockNumber,
+    prelude::{BlockNumber, DeploymentHash},
 };
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 pub struct IndexingInputs<C: Blockchain> {
@@ -30,6 +30,15 @@ pub struct IndexingInputs<C: Blockchain> {
     /// Whether to instrument trigger processing and log additional,
     /// possibly expensive and noisy, information
     pub instrument: bool,
+
+    /// How to retry after non-deterministic errors
+    pub retry_policy: RetryPolicy,
+
+    /// How the deployment gets its blocks
+    pub ingestion: IngestionMode,
+
+    /// The deployments that subgraph data sources use as their source
+    pub source_stores: Arc<BTreeMap<DeploymentHash, Arc<dyn SourceableStore>>>,
 }
 
 impl<C: Blockchain> IndexingInputs<C> {
@@ -50,6 +59,9 @@ impl<C: Blockchain> IndexingInputs<C> {
             poi_version,
             network,
             instrument,
+            retry_policy,
+            ingestion,
+            source_stores,
         } = self;
         IndexingInputs {
             deployment: deployment.clone(),
@@ -67,6 +79,9 @@ impl<C: Blockchain> IndexingInputs<C> {
             poi_version: *poi_version,
             network: network.clone(),
             instrument: *instrument,
+            retry_policy: retry_policy.clone(),
+            ingestion: *ingestion,
+            source_stores: source_stores.clone(),
         }
     }
 }
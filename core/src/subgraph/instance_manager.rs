@@ -1,15 +1,18 @@
-use crate::polling_monitor::{ArweaveService, IpfsService};
+use crate::polling_monitor::{ArweaveService, HttpService, IpfsService};
 use crate::subgraph::context::{IndexingContext, SubgraphKeepAlive};
 use crate::subgraph::inputs::IndexingInputs;
 use crate::subgraph::loader::load_dynamic_data_sources;
+use crate::subgraph::warm_up::WarmUp;
 use crate::subgraph::Decoder;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::subgraph::runner::SubgraphRunner;
 use graph::blockchain::block_stream::BlockStreamMetrics;
-use graph::blockchain::{Blockchain, BlockchainKind, DataSource, NodeCapabilities};
+use graph::blockchain::{
+    Blockchain, BlockchainKind, DataSource, NodeCapabilities, TriggerFilter as _,
+};
 use graph::components::metrics::gas::GasMetrics;
-use graph::components::subgraph::ProofOfIndexingVersion;
+use graph::components::subgraph::{ProofOfIndexingVersion, RetryPolicy, STREAM_STATUSES, WARM_UPS};
 use graph::data::subgraph::{UnresolvedSubgraphManifest, SPEC_VERSION_0_0_6};
 use graph::data::value::Word;
 use graph::data_source::causality_region::CausalityRegionSeq;
@@ -33,6 +36,7 @@ pub struct SubgraphInstanceManager<S: SubgraphStore> {
     link_resolver: Arc<dyn LinkResolver>,
     ipfs_service: IpfsService,
     arweave_service: ArweaveService,
+    http_service: HttpService,
     static_filters: bool,
     env_vars: Arc<EnvVars>,
 }
@@ -135,6 +139,34 @@ impl<S: SubgraphStore> SubgraphInstanceManagerTrait for SubgraphInstanceManager<
 
                     self.start_subgraph_inner(logger, loc, runner).await
                 }
+                BlockchainKind::Solana => {
+                    let runner = instance_manager
+                        .build_subgraph_runner::<graph_chain_solana::Chain>(
+                            logger.clone(),
+                            self.env_vars.cheap_clone(),
+                            loc.clone(),
+                            manifest,
+                            stop_block,
+                            Box::new(SubgraphTriggerProcessor {}),
+                        )
+                        .await?;
+
+                    self.start_subgraph_inner(logger, loc, runner).await
+                }
+                BlockchainKind::Bitcoin => {
+                    let runner = instance_manager
+                        .build_subgraph_runner::<graph_chain_bitcoin::Chain>(
+                            logger.clone(),
+                            self.env_vars.cheap_clone(),
+                            loc.clone(),
+                            manifest,
+                            stop_block,
+                            Box::new(SubgraphTriggerProcessor {}),
+                        )
+                        .await?;
+
+                    self.start_subgraph_inner(logger, loc, runner).await
+                }
             }
         };
 
@@ -167,6 +199,8 @@ impl<S: SubgraphStore> SubgraphInstanceManagerTrait for SubgraphInstanceManager<
         }
 
         self.instances.remove(&loc.id);
+        WARM_UPS.stop(loc.id);
+        STREAM_STATUSES.remove(&loc.hash);
 
         info!(logger, "Stopped subgraph");
     }
@@ -183,6 +217,7 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
         link_resolver: Arc<dyn LinkResolver>,
         ipfs_service: IpfsService,
         arweave_service: ArweaveService,
+        http_service: HttpService,
         static_filters: bool,
     ) -> Self {
         let logger = logger_factory.component_logger("SubgraphInstanceManager", None);
@@ -199,6 +234,7 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             static_filters,
             env_vars,
             arweave_service,
+            http_service,
         }
     }
 
@@ -219,6 +255,15 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
         let registry = self.metrics_registry.cheap_clone();
 
         let raw_yaml = serde_yaml::to_string(&manifest).unwrap();
+
+        // Params that were set for the deployment override the ones from the
+        // manifest; `raw_yaml` stays the manifest as it was deployed
+        let mut manifest = manifest;
+        if C::KIND == BlockchainKind::Substreams {
+            if let Some(params) = self.subgraph_store.substreams_params(&deployment)? {
+                graph_chain_substreams::override_params(&mut manifest, &params)?;
+            }
+        }
         let manifest = UnresolvedSubgraphManifest::parse(deployment.hash.cheap_clone(), manifest)?;
 
         // Allow for infinite retries for subgraph definition files.
@@ -318,7 +363,7 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
 
         let start_blocks: Vec<BlockNumber> = data_sources
             .iter()
-            .filter_map(|d| d.as_onchain().map(|d: &C::DataSource| d.start_block()))
+            .filter_map(|d| d.start_block())
             .collect();
 
         let end_blocks: BTreeSet<BlockNumber> = manifest
@@ -331,6 +376,24 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             })
             .collect();
 
+        // Subgraph data sources get their triggers from the deployments
+        // they use as their source; they can only be declared in the
+        // manifest
+        let mut source_stores = BTreeMap::new();
+        for ds in manifest.data_sources.iter().filter_map(|d| d.as_subgraph()) {
+            let source = &ds.source.address;
+            if !source_stores.contains_key(source) {
+                source_stores.insert(source.clone(), self.subgraph_store.sourceable(source)?);
+            }
+        }
+        if !source_stores.is_empty() && !C::TriggerFilter::default().extend_with_every_block() {
+            return Err(anyhow!(
+                "subgraph data sources are not supported for {} subgraphs",
+                C::KIND
+            ));
+        }
+        let source_stores = Arc::new(source_stores);
+
         let templates = Arc::new(manifest.templates.clone());
 
         // Obtain the debug fork from the subgraph store
@@ -381,10 +444,12 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
         let offchain_monitor = OffchainMonitor::new(
             logger.cheap_clone(),
             registry.cheap_clone(),
-            &manifest.id,
+            &deployment,
             self.ipfs_service.clone(),
             self.arweave_service.clone(),
-        );
+            self.http_service.clone(),
+            self.subgraph_store.cheap_clone(),
+        )?;
 
         // Initialize deployment_head with current deployment head. Any sort of trouble in
         // getting the deployment head ptr leads to initializing with 0
@@ -392,10 +457,30 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
         block_stream_metrics.deployment_head.set(deployment_head);
 
         let (runtime_adapter, decoder_hook) = chain.runtime()?;
+
+        // Subgraph data sources get their triggers from other deployments,
+        // there is nothing to warm up for them
+        if source_stores.is_empty() {
+            let (_, warm_up_hook) = chain.runtime()?;
+            WarmUp {
+                logger: logger.cheap_clone(),
+                deployment: deployment.id,
+                chain: chain.cheap_clone(),
+                triggers_adapter: triggers_adapter.cheap_clone(),
+                decoder_hook: warm_up_hook,
+                data_sources: onchain_data_sources,
+                store: store.cheap_clone(),
+            }
+            .spawn();
+        }
+
         let host_builder = graph_runtime_wasm::RuntimeHostBuilder::new(
             runtime_adapter,
             self.link_resolver.cheap_clone(),
             subgraph_store.ens_lookup(),
+            subgraph_store.http_cache(),
+            subgraph_store.deployment_logs(),
+            source_stores.cheap_clone(),
         );
 
         let features = manifest.features.clone();
@@ -410,6 +495,19 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             CausalityRegionSeq::from_current(store.causality_region_curr_val().await?);
 
         let instrument = self.subgraph_store.instrument(&deployment)?;
+        let retry_policy = self
+            .subgraph_store
+            .retry_policy(&deployment)?
+            .unwrap_or_else(|| RetryPolicy::from_env(&env_vars));
+        let handler_limits = self
+            .subgraph_store
+            .handler_limits(&deployment)?
+            .unwrap_or_default();
+        let mapping_env = self
+            .subgraph_store
+            .mapping_env(&deployment)?
+            .unwrap_or_default();
+        let ingestion = self.subgraph_store.ingestion(&deployment)?;
 
         let decoder = Box::new(Decoder::new(decoder_hook));
 
@@ -429,6 +527,9 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             poi_version,
             network: network.to_string(),
             instrument,
+            retry_policy,
+            ingestion,
+            source_stores,
         };
 
         // Initialize the indexing context, including both static and dynamic data sources.
@@ -440,6 +541,8 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
                 host_builder,
                 host_metrics.clone(),
                 causality_region_seq,
+                handler_limits,
+                mapping_env,
                 self.instances.cheap_clone(),
                 offchain_monitor,
                 tp,
@@ -462,7 +565,6 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             ctx,
             logger.cheap_clone(),
             metrics,
-            env_vars,
         ))
     }
 
@@ -9,6 +9,7 @@ mod runner;
 mod state;
 mod stream;
 mod trigger_processor;
+mod warm_up;
 
 pub use self::instance_manager::SubgraphInstanceManager;
 pub use self::provider::SubgraphAssignmentProvider;
@@ -435,6 +435,42 @@ where
                 )
                 .await?
             }
+            BlockchainKind::Solana => {
+                create_subgraph_version::<graph_chain_solana::Chain, _>(
+                    &logger,
+                    self.store.clone(),
+                    self.chains.cheap_clone(),
+                    name.clone(),
+                    hash.cheap_clone(),
+                    start_block_override,
+                    graft_block_override,
+                    raw,
+                    node_id,
+                    debug_fork,
+                    self.version_switching_mode,
+                    &self.resolver,
+                    history_blocks,
+                )
+                .await?
+            }
+            BlockchainKind::Bitcoin => {
+                create_subgraph_version::<graph_chain_bitcoin::Chain, _>(
+                    &logger,
+                    self.store.clone(),
+                    self.chains.cheap_clone(),
+                    name.clone(),
+                    hash.cheap_clone(),
+                    start_block_override,
+                    graft_block_override,
+                    raw,
+                    node_id,
+                    debug_fork,
+                    self.version_switching_mode,
+                    &self.resolver,
+                    history_blocks,
+                )
+                .await?
+            }
         };
 
         debug!(
@@ -644,7 +680,9 @@ async fn create_subgraph_version<C: Blockchain, S: SubgraphStore>(
         .await
         .map_err(SubgraphRegistrarError::ManifestValidationError)?;
 
-    let network_name: Word = manifest.network_name().into();
+    // Deployments are always stored with the network name that the chain
+    // is configured with, even if the manifest uses an alias for it
+    let network_name: Word = chains.resolve(manifest.network_name().into());
 
     let chain = chains
         .get::<C>(network_name.clone())
@@ -714,6 +752,12 @@ async fn create_subgraph_version<C: Blockchain, S: SubgraphStore>(
     // creating a new subgraph deployment if one doesn't exist.
     let mut deployment = DeploymentCreate::new(raw_string, &manifest, start_block)
         .graft(base_block)
+        .graft_in_place(
+            manifest
+                .graft
+                .as_ref()
+                .map_or(false, |graft| graft.in_place),
+        )
         .debug(debug_fork)
         .entities_with_causality_region(needs_causality_region);
 
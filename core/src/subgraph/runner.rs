@@ -9,7 +9,8 @@ use graph::blockchain::block_stream::{
 };
 use graph::blockchain::{Block, BlockTime, Blockchain, DataSource as _, TriggerFilter as _};
 use graph::components::store::{EmptyStore, GetScope, ReadStore, StoredDynamicDataSource};
-use graph::components::subgraph::InstanceDSTemplate;
+use graph::components::subgraph::{InstanceDSTemplate, OnExhaustion};
+use graph::components::tracing::{self, KeyValue, TraceFutureExt as _};
 use graph::components::{
     store::ModificationsAndCache,
     subgraph::{MappingError, PoICausalityRegion, ProofOfIndexing, SharedProofOfIndexing},
@@ -20,25 +21,25 @@ use graph::data::subgraph::{
     SubgraphFeature,
 };
 use graph::data_source::{
-    offchain, CausalityRegion, DataSource, DataSourceCreationError, TriggerData,
+    offchain, subgraph, CausalityRegion, DataSource, DataSourceCreationError, TriggerData,
 };
-use graph::env::EnvVars;
 use graph::futures03::stream::StreamExt;
 use graph::futures03::TryStreamExt;
 use graph::prelude::*;
 use graph::schema::EntityKey;
-use graph::util::{backoff::ExponentialBackoff, lfu_cache::LfuCache};
+use graph::util::lfu_cache::LfuCache;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-const MINUTE: Duration = Duration::from_secs(60);
-
 const SKIP_PTR_UPDATES_THRESHOLD: Duration = Duration::from_secs(60 * 5);
 const HANDLE_REVERT_SECTION_NAME: &str = "handle_revert";
 const PROCESS_BLOCK_SECTION_NAME: &str = "process_block";
 const PROCESS_WASM_BLOCK_SECTION_NAME: &str = "process_wasm_block";
 const PROCESS_TRIGGERS_SECTION_NAME: &str = "process_triggers";
 const HANDLE_CREATED_DS_SECTION_NAME: &str = "handle_new_data_sources";
+/// How often to check whether the source of a subgraph data source has
+/// caught up with the block that is being processed
+const SOURCE_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 pub struct SubgraphRunner<C, T>
 where
@@ -62,19 +63,15 @@ where
         ctx: IndexingContext<C, T>,
         logger: Logger,
         metrics: RunnerMetrics,
-        env_vars: Arc<EnvVars>,
     ) -> Self {
+        let backoff = inputs.retry_policy.backoff();
         Self {
             inputs: Arc::new(inputs),
             ctx,
             state: IndexingState {
                 should_try_unfail_non_deterministic: true,
                 skip_ptr_updates_timer: Instant::now(),
-                backoff: ExponentialBackoff::with_jitter(
-                    (MINUTE * 2).min(env_vars.subgraph_error_retry_ceil),
-                    env_vars.subgraph_error_retry_ceil,
-                    env_vars.subgraph_error_retry_jitter,
-                ),
+                backoff,
                 entity_lfu_cache: LfuCache::new(),
                 cached_head_ptr: None,
             },
@@ -117,6 +114,19 @@ where
     }
 
     fn build_filter(&self) -> C::TriggerFilter {
+        let mut filter = self.build_onchain_filter();
+
+        // Subgraph data sources get their triggers from their source and
+        // need to see every block; `build_subgraph_runner` checks that the
+        // chain supports that
+        if !self.inputs.source_stores.is_empty() {
+            filter.extend_with_every_block();
+        }
+
+        filter
+    }
+
+    fn build_onchain_filter(&self) -> C::TriggerFilter {
         let current_ptr = self.inputs.store.block_ptr();
         let static_filters = self.is_static_filters_enabled();
 
@@ -226,18 +236,36 @@ where
 
             // Process events from the stream as long as no restart is needed
             loop {
+                // The span of the event starts before waiting for it so
+                // that traces show how long the block stream took
+                let event_cx = tracing::root_span(
+                    "block",
+                    tracing::deployment_attributes(&self.inputs.deployment.hash),
+                );
                 let event = {
                     let _section = self.metrics.stream.stopwatch.start_section("scan_blocks");
+                    let _stream_cx = tracing::span(&event_cx, "block_stream.next", vec![]);
 
                     block_stream.next().await
                 };
+                if let Some(Ok(event)) = &event {
+                    tracing::set_attributes(
+                        &event_cx,
+                        tracing::block_attributes(&self.inputs.deployment.hash, &event.block_ptr()),
+                    );
+                }
 
                 // TODO: move cancel handle to the Context
                 // This will require some code refactor in how the BlockStream is created
                 let block_start = Instant::now();
                 match self
                     .handle_stream_event(event, &block_stream_cancel_handle)
+                    .with_context(event_cx.clone())
                     .await
+                    .map_err(|e| {
+                        tracing::record_error(&event_cx, &e);
+                        e
+                    })
                     .map(|res| {
                         self.metrics
                             .subgraph
@@ -272,6 +300,104 @@ where
         }
     }
 
+    /// The triggers for subgraph data sources in `block`, i.e., the entities that their sources
+    /// created or updated in that block. This waits until all sources have processed `block`.
+    ///
+    /// Returns `None` if a source processed a different block with the same number; either this
+    /// subgraph or the source is on a fork that will be reverted. Sources that are already past
+    /// `block` are checked against the block cache.
+    async fn subgraph_triggers(
+        &self,
+        logger: &Logger,
+        block: &BlockPtr,
+        cancel_handle: &CancelHandle,
+    ) -> Result<Option<Vec<subgraph::TriggerData>>, BlockProcessingError> {
+        let mut triggers = Vec::new();
+
+        for (source, store) in self.inputs.source_stores.iter() {
+            let start_block = self
+                .ctx
+                .static_data_sources()
+                .iter()
+                .filter_map(|ds| ds.as_subgraph())
+                .filter(|ds| &ds.source.address == source)
+                .map(|ds| ds.source.start_block)
+                .min();
+            if start_block.map_or(true, |start_block| block.number < start_block) {
+                continue;
+            }
+
+            loop {
+                if cancel_handle.is_canceled() {
+                    return Err(BlockProcessingError::Canceled);
+                }
+
+                match store.block_ptr().await? {
+                    Some(ptr) if ptr.number >= block.number => {
+                        // The source is on the same chain as this subgraph; check that it
+                        // processed `block` itself and not another block at that height
+                        let source_block = if ptr.number == block.number {
+                            Some(ptr)
+                        } else {
+                            let offset = ptr.number - block.number;
+                            self.inputs
+                                .chain
+                                .chain_store()
+                                .ancestor_block(ptr, offset, None)
+                                .await?
+                                .map(|(_, ptr)| ptr)
+                        };
+                        match source_block {
+                            Some(source_block) if &source_block == block => break,
+                            Some(source_block) => {
+                                info!(logger, "Source deployment processed a different block, restarting";
+                                    "source" => source.as_str(),
+                                    "source_block_hash" => source_block.hash_hex(),
+                                );
+                                return Ok(None);
+                            }
+                            None => {
+                                return Err(BlockProcessingError::Unknown(anyhow!(
+                                    "can not check which block source deployment {} processed \
+                                     at block number {} since it is not in the block cache, \
+                                     possibly because of a reorg",
+                                    source,
+                                    block.number
+                                )))
+                            }
+                        }
+                    }
+                    ptr => {
+                        debug!(logger, "Waiting for source deployment to process block";
+                            "source" => source.as_str(),
+                            "source_block" => ptr.map(|ptr| ptr.number),
+                        );
+                        tokio::time::sleep(SOURCE_POLL_INTERVAL).await;
+                    }
+                }
+            }
+
+            let mut source_triggers: Vec<_> = store
+                .get_changes(block.number)?
+                .into_iter()
+                .filter_map(|op| match op {
+                    EntityOperation::Set { key, data } => Some(subgraph::TriggerData {
+                        source: source.clone(),
+                        key,
+                        entity: data,
+                    }),
+                    // Handlers are only called for entities that were created or updated
+                    EntityOperation::Remove { .. } => None,
+                })
+                .collect();
+            // Make the order of triggers deterministic
+            source_triggers.sort_by(|a, b| a.key.cmp(&b.key));
+            triggers.extend(source_triggers);
+        }
+
+        Ok(Some(triggers))
+    }
+
     /// Processes a block and returns the updated context and a boolean flag indicating
     /// whether new dynamic data sources have been added to the subgraph.
     async fn process_block(
@@ -289,8 +415,15 @@ where
                 "block_hash" => format!("{}", block_ptr.hash)
         ));
 
+        let Some(subgraph_triggers) = self
+            .subgraph_triggers(&logger, &block_ptr, block_stream_cancel_handle)
+            .await?
+        else {
+            return Ok(Action::Restart);
+        };
+
         debug!(logger, "Start processing block";
-               "triggers" => triggers.len());
+               "triggers" => triggers.len() + subgraph_triggers.len());
 
         let proof_of_indexing = if self.inputs.store.supports_proof_of_indexing().await? {
             Some(Arc::new(AtomicRefCell::new(ProofOfIndexing::new(
@@ -315,6 +448,14 @@ where
             .stopwatch
             .start_section(PROCESS_TRIGGERS_SECTION_NAME);
 
+        let triggers_cx = tracing::child_span(
+            "process_triggers",
+            vec![KeyValue::new(
+                "triggers",
+                (triggers.len() + subgraph_triggers.len()) as i64,
+            )],
+        );
+
         // Match and decode all triggers in the block
         let hosts_filter = |trigger: &TriggerData<C>| self.ctx.instance.hosts_for_trigger(trigger);
         let match_res = self
@@ -323,10 +464,14 @@ where
             .match_and_decode_many(
                 &logger,
                 &block,
-                triggers.into_iter().map(TriggerData::Onchain),
+                triggers
+                    .into_iter()
+                    .map(TriggerData::Onchain)
+                    .chain(subgraph_triggers.into_iter().map(TriggerData::Subgraph)),
                 hosts_filter,
                 &self.metrics.subgraph,
             )
+            .with_context(tracing::span(&triggers_cx, "match_and_decode", vec![]))
             .await;
 
         // Process events one after the other, passing in entity operations
@@ -349,6 +494,7 @@ where
                             &self.metrics.subgraph,
                             self.inputs.instrument,
                         )
+                        .with_context(triggers_cx.clone())
                         .await
                         .map_err(|e| e.add_trigger_context(&runnable.trigger));
                     match process_res {
@@ -365,6 +511,11 @@ where
             }
         };
 
+        if let Err(MappingError::PossibleReorg(e) | MappingError::Unknown(e)) = &res {
+            tracing::record_error(&triggers_cx, e);
+        }
+        drop(triggers_cx);
+
         match res {
             // Triggers processed with no errors or with only deterministic errors.
             Ok(state) => block_state = state,
@@ -639,6 +790,10 @@ where
         let is_caught_up = self.is_caught_up(&block_ptr).await?;
 
         persisted_data_sources.extend(persisted_off_chain_data_sources);
+        let transact_cx = tracing::child_span(
+            "transact_block",
+            vec![KeyValue::new("entity_operations", mods.len() as i64)],
+        );
         self.inputs
             .store
             .transact_block_operations(
@@ -653,8 +808,14 @@ where
                 is_non_fatal_errors_active,
                 is_caught_up,
             )
+            .with_context(transact_cx.clone())
             .await
+            .map_err(|e| {
+                tracing::record_error(&transact_cx, &e);
+                e
+            })
             .context("Failed to transact block operations")?;
+        drop(transact_cx);
 
         // For subgraphs with `nonFatalErrors` feature disabled, we consider
         // any error as fatal.
@@ -910,6 +1071,24 @@ where
                         // Retry logic below:
 
                         let message = format!("{:#}", e).replace('\n', "\t");
+                        let policy = &self.inputs.retry_policy;
+                        if policy.is_exhausted(self.state.backoff.attempt) {
+                            error!(self.logger, "Subgraph failed with non-deterministic error and will not be retried: {}", message;
+                                "attempts" => self.state.backoff.attempt,
+                                "on_exhaustion" => policy.on_exhaustion.as_str());
+
+                            return match policy.on_exhaustion {
+                                OnExhaustion::Fail => Err(err),
+                                OnExhaustion::Pause => {
+                                    self.inputs
+                                        .store
+                                        .pause_subgraph()
+                                        .context("Failed to pause subgraph")?;
+                                    Ok(Action::Stop)
+                                }
+                            };
+                        }
+
                         error!(self.logger, "Subgraph failed with non-deterministic error: {}", message;
                             "attempt" => self.state.backoff.attempt,
                             "retry_delay_s" => self.state.backoff.delay().as_secs());
@@ -1579,7 +1758,7 @@ async fn update_proof_of_indexing(
             ),
             (digest_name, Value::from(digest)),
         ];
-        if entity_cache.schema.has_aggregations() {
+        if entity_cache.schema.needs_block_time() {
             let block_time = Value::Int8(block_time.as_secs_since_epoch() as i64);
             data.push((entity_cache.schema.poi_block_time(), block_time));
         }
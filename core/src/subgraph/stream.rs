@@ -2,7 +2,7 @@ use crate::subgraph::inputs::IndexingInputs;
 use anyhow::bail;
 use graph::blockchain::block_stream::{BlockStream, BufferedBlockStream};
 use graph::blockchain::Blockchain;
-use graph::prelude::{CheapClone, Error, SubgraphInstanceMetrics};
+use graph::prelude::{CheapClone, Error, SubgraphInstanceMetrics, ENV_VARS};
 use std::sync::Arc;
 
 pub async fn new_block_stream<C: Blockchain>(
@@ -20,13 +20,19 @@ pub async fn new_block_stream<C: Blockchain>(
             inputs.start_blocks.clone(),
             Arc::new(filter.clone()),
             inputs.unified_api_version.clone(),
+            inputs.ingestion,
         )
         .await
     {
-        Ok(block_stream) => Ok(BufferedBlockStream::spawn_from_stream(
-            block_stream.buffer_size_hint(),
-            block_stream,
-        )),
+        Ok(block_stream) => {
+            let buffer_size = ENV_VARS
+                .block_stream_buffer_size
+                .unwrap_or_else(|| block_stream.buffer_size_hint());
+            Ok(BufferedBlockStream::spawn_from_stream(
+                buffer_size.max(1),
+                block_stream,
+            ))
+        }
         Err(e) => {
             if is_firehose {
                 metrics.firehose_connection_errors.inc();
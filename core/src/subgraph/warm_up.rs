@@ -0,0 +1,143 @@
+//! Warm up the block and call caches for a deployment that starts far
+//! behind the chain head. The requests that the deployment will make while
+//! syncing are predictable from its data sources, and making them ahead of
+//! the runner with some parallelism means that the runner finds most of
+//! what it needs in the caches instead of waiting for the provider
+use std::sync::Arc;
+
+use graph::blockchain::block_stream::TriggersAdapter;
+use graph::blockchain::{Block, Blockchain, DataSource, DecoderHook, TriggerFilter as _};
+use graph::components::store::{DeploymentId, WritableStore};
+use graph::components::subgraph::WARM_UPS;
+use graph::futures03::{stream, StreamExt};
+use graph::prelude::*;
+
+/// The number of blocks that one request for triggers covers
+const WARM_UP_RANGE: BlockNumber = 1000;
+
+pub(crate) struct WarmUp<C: Blockchain> {
+    pub logger: Logger,
+    pub deployment: DeploymentId,
+    pub chain: Arc<C>,
+    pub triggers_adapter: Arc<dyn TriggersAdapter<C>>,
+    pub decoder_hook: C::DecoderHook,
+    pub data_sources: Vec<C::DataSource>,
+    pub store: Arc<dyn WritableStore>,
+}
+
+impl<C: Blockchain> WarmUp<C> {
+    /// Start warming up the caches in the background if the deployment is
+    /// more than `GRAPH_CACHE_WARM_UP_BLOCKS` behind the chain head
+    pub fn spawn(self) {
+        if ENV_VARS.cache_warm_up_blocks <= 0 || self.data_sources.is_empty() {
+            return;
+        }
+
+        graph::spawn(async move {
+            let logger = self.logger.clone();
+            if let Err(e) = self.run().await {
+                warn!(logger, "Warming up caches failed"; "error" => format!("{:#}", e));
+            }
+        });
+    }
+
+    async fn run(self) -> Result<(), Error> {
+        let head = match self.chain.chain_store().chain_head_ptr().await? {
+            Some(head) => head.number,
+            None => return Ok(()),
+        };
+        let start = match self.store.block_ptr() {
+            Some(ptr) => ptr.number + 1,
+            None => self
+                .data_sources
+                .iter()
+                .map(|ds| ds.start_block())
+                .min()
+                .unwrap_or(0),
+        };
+        let blocks = ENV_VARS.cache_warm_up_blocks;
+        if head - start <= blocks {
+            return Ok(());
+        }
+        // Only warm up blocks that are final so that nothing we cache is
+        // reverted before the runner gets to it
+        let end = (start + blocks - 1).min(head - ENV_VARS.reorg_threshold);
+        if end < start {
+            return Ok(());
+        }
+
+        info!(self.logger, "Warming up caches";
+            "start_block" => start,
+            "end_block" => end,
+            "parallelism" => ENV_VARS.cache_warm_up_parallelism);
+        let warm_up = WARM_UPS.start(self.deployment, start, end);
+
+        let filter = C::TriggerFilter::from_data_sources(self.data_sources.iter());
+        let ranges = (start..=end)
+            .step_by(WARM_UP_RANGE as usize)
+            .map(|from| (from, (from + WARM_UP_RANGE - 1).min(end)));
+        let mut ranges = stream::iter(ranges)
+            .map(|(from, to)| self.warm_up_range(&filter, from, to))
+            .buffered(ENV_VARS.cache_warm_up_parallelism.max(1));
+
+        let mut res = Ok(());
+        while let Some(range) = ranges.next().await {
+            match range {
+                Ok(to) => {
+                    if !WARM_UPS.advance(self.deployment, warm_up, to) {
+                        debug!(self.logger, "Stopped warming up caches"; "block" => to);
+                        break;
+                    }
+                }
+                Err(e) => {
+                    res = Err(e);
+                    break;
+                }
+            }
+        }
+        WARM_UPS.finish(self.deployment, warm_up);
+
+        if res.is_ok() {
+            info!(self.logger, "Warmed up caches"; "end_block" => end);
+        }
+        res
+    }
+
+    /// Scan `from..=to` for triggers, which puts the blocks with triggers
+    /// into the block cache, and let the decoder hook warm up what the
+    /// triggers need. Returns `to`
+    async fn warm_up_range(
+        &self,
+        filter: &C::TriggerFilter,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<BlockNumber, Error> {
+        // The runner caught up with the warm-up
+        if self.store.block_ptr().map_or(false, |ptr| ptr.number >= to) {
+            return Ok(to);
+        }
+
+        let (blocks, _) = self
+            .triggers_adapter
+            .scan_triggers(from, to, filter)
+            .await?;
+        for block in blocks {
+            let block_ptr = block.block.ptr();
+            let triggers = block.trigger_data;
+            let block = Arc::new(block.block);
+
+            let mut mapping_triggers = Vec::new();
+            for trigger in &triggers {
+                for ds in &self.data_sources {
+                    if let Some(trigger) = ds.match_and_decode(trigger, &block, &self.logger)? {
+                        mapping_triggers.push(trigger.trigger);
+                    }
+                }
+            }
+            self.decoder_hook
+                .warm_up(&self.logger, &block_ptr, mapping_triggers)
+                .await?;
+        }
+        Ok(to)
+    }
+}
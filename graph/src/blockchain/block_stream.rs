@@ -495,6 +495,10 @@ pub enum SubstreamsError {
 
     #[error("unexpected store delta output")]
     UnexpectedStoreDeltaOutput,
+
+    /// The module output could not be mapped to entities as declared in the manifest
+    #[error("mapping module output to entities failed: {0}")]
+    EntityMappingError(String),
 }
 
 impl SubstreamsError {
@@ -502,7 +506,7 @@ impl SubstreamsError {
         use SubstreamsError::*;
 
         match self {
-            EntityValidationError(_) => true,
+            EntityValidationError(_) | EntityMappingError(_) => true,
             MissingClockError
             | InvalidUndoError
             | DecodingError(_)
@@ -541,6 +545,26 @@ pub enum BlockStreamEvent<C: Blockchain> {
     ProcessWasmBlock(BlockPtr, BlockTime, Box<[u8]>, String, FirehoseCursor),
 }
 
+impl<C: Blockchain> BlockStreamEvent<C> {
+    /// The block the subgraph is at once it has handled this event
+    pub fn block_ptr(&self) -> BlockPtr {
+        match self {
+            Self::Revert(ptr, _) => ptr.clone(),
+            Self::ProcessBlock(block, _) => block.ptr(),
+            Self::ProcessWasmBlock(ptr, ..) => ptr.clone(),
+        }
+    }
+
+    /// The cursor at which a block stream continues after this event
+    pub fn cursor(&self) -> &FirehoseCursor {
+        match self {
+            Self::Revert(_, cursor) => cursor,
+            Self::ProcessBlock(_, cursor) => cursor,
+            Self::ProcessWasmBlock(.., cursor) => cursor,
+        }
+    }
+}
+
 impl<C: Blockchain> Clone for BlockStreamEvent<C>
 where
     C::TriggerData: Clone,
@@ -5,14 +5,18 @@ use super::client::ChainClient;
 use super::Blockchain;
 use crate::blockchain::block_stream::FirehoseCursor;
 use crate::blockchain::TriggerFilter;
+use crate::components::subgraph::STREAM_STATUSES;
+use crate::components::tracing::{self, KeyValue};
+use crate::endpoint::ConnectionType;
 use crate::prelude::*;
 use crate::util::backoff::ExponentialBackoff;
 use crate::{firehose, firehose::FirehoseEndpoint};
 use async_stream::try_stream;
 use futures03::{Stream, StreamExt};
+use prost::Message as _;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tonic::Status;
 
 struct FirehoseBlockStreamMetrics {
@@ -21,6 +25,11 @@ struct FirehoseBlockStreamMetrics {
     connect_duration: GaugeVec,
     time_between_responses: HistogramVec,
     responses: CounterVec,
+    received_bytes: CounterVec,
+    cursor_resets: CounterVec,
+    connected: GaugeVec,
+    undos: CounterVec,
+    cursor_updated_at: GaugeVec,
 }
 
 impl FirehoseBlockStreamMetrics {
@@ -59,6 +68,46 @@ impl FirehoseBlockStreamMetrics {
                     vec!["deployment", "provider", "kind"].as_slice(),
                 )
                 .unwrap(),
+
+            received_bytes: registry
+                .global_counter_vec(
+                    "deployment_firehose_blockstream_received_bytes",
+                    "Counts the bytes of the responses received from a Firehose block stream before compression",
+                    vec!["deployment", "provider"].as_slice(),
+                )
+                .unwrap(),
+
+            cursor_resets: registry
+                .global_counter_vec(
+                    "deployment_firehose_blockstream_cursor_resets",
+                    "Counts the number of times a provider rejected the cursor of a Firehose block stream",
+                    vec!["deployment", "provider"].as_slice(),
+                )
+                .unwrap(),
+
+            connected: registry
+                .global_gauge_vec(
+                    "deployment_firehose_blockstream_connected",
+                    "Whether the Firehose block stream is connected; 0 = no, 1 = yes",
+                    vec!["deployment"].as_slice(),
+                )
+                .unwrap(),
+
+            undos: registry
+                .global_counter_vec(
+                    "deployment_firehose_blockstream_undos",
+                    "Counts the number of undo signals received from a Firehose block stream",
+                    vec!["deployment", "provider"].as_slice(),
+                )
+                .unwrap(),
+
+            cursor_updated_at: registry
+                .global_gauge_vec(
+                    "deployment_firehose_blockstream_cursor_updated_at",
+                    "The time, in seconds since the epoch, at which the cursor of a Firehose block stream last advanced",
+                    vec!["deployment"].as_slice(),
+                )
+                .unwrap(),
         }
     }
 
@@ -69,6 +118,10 @@ impl FirehoseBlockStreamMetrics {
         self.connect_duration
             .with_label_values(&[&self.deployment, &provider])
             .set(time.elapsed().as_secs_f64());
+        self.connected
+            .with_label_values(&[&self.deployment])
+            .set(1.0);
+        STREAM_STATUSES.connect(&self.deployment, "firehose", provider, true);
 
         // Reset last connection timestamp
         *time = Instant::now();
@@ -81,11 +134,22 @@ impl FirehoseBlockStreamMetrics {
         self.connect_duration
             .with_label_values(&[&self.deployment, &provider])
             .set(time.elapsed().as_secs_f64());
+        self.connected
+            .with_label_values(&[&self.deployment])
+            .set(0.0);
+        STREAM_STATUSES.connect(&self.deployment, "firehose", provider, false);
 
         // Reset last connection timestamp
         *time = Instant::now();
     }
 
+    fn observe_disconnect(&self) {
+        self.connected
+            .with_label_values(&[&self.deployment])
+            .set(0.0);
+        STREAM_STATUSES.disconnect(&self.deployment);
+    }
+
     fn observe_response(&self, kind: &str, time: &mut Instant, provider: &str) {
         self.time_between_responses
             .with_label_values(&[&self.deployment, &provider])
@@ -97,6 +161,46 @@ impl FirehoseBlockStreamMetrics {
         // Reset last response timestamp
         *time = Instant::now();
     }
+
+    fn observe_received_bytes(&self, bytes: usize, provider: &str) {
+        self.received_bytes
+            .with_label_values(&[&self.deployment, &provider])
+            .inc_by(bytes as f64);
+        STREAM_STATUSES.receive(&self.deployment, bytes);
+    }
+
+    /// Record that the stream advanced its cursor to `block`; `undo` says
+    /// whether the provider sent an undo signal for it
+    fn observe_block(&self, block: BlockNumber, undo: bool, provider: &str) {
+        if undo {
+            self.undos
+                .with_label_values(&[&self.deployment, &provider])
+                .inc();
+        }
+        self.cursor_updated_at
+            .with_label_values(&[&self.deployment])
+            .set(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64(),
+            );
+        STREAM_STATUSES.advance(&self.deployment, block, undo);
+    }
+
+    fn observe_cursor_reset(&self, provider: &str) {
+        self.cursor_resets
+            .with_label_values(&[&self.deployment, &provider])
+            .inc();
+    }
+}
+
+impl Drop for FirehoseBlockStreamMetrics {
+    /// The stream is dropped, for example because the deployment was
+    /// stopped
+    fn drop(&mut self) {
+        self.observe_disconnect();
+    }
 }
 
 pub struct FirehoseBlockStream<C: Blockchain> {
@@ -211,6 +315,8 @@ fn stream_blocks<C: Blockchain, F: FirehoseMapper<C>>(
     // This attribute is needed because `try_stream!` seems to break detection of `skip_backoff` assignments
     #[allow(unused_assignments)]
     let mut skip_backoff = false;
+    #[allow(unused_assignments)]
+    let mut reset_cursor = false;
 
     try_stream! {
         loop {
@@ -255,8 +361,15 @@ fn stream_blocks<C: Blockchain, F: FirehoseMapper<C>>(
                     let mut last_response_time = Instant::now();
                     let mut expected_stream_end = false;
 
-                    for await response in stream {
-                        match process_firehose_response(
+                    let mut stream = stream;
+                    while let Some(response) = next_response(&mut stream).await {
+                        if let Ok(response) = &response {
+                            metrics.observe_received_bytes(response.encoded_len(), &endpoint.provider);
+                        }
+                        let mut response_attributes = tracing::deployment_attributes(&metrics.deployment);
+                        response_attributes.push(KeyValue::new("provider", endpoint.provider.to_string()));
+                        let response_cx = tracing::root_span("firehose.response", response_attributes);
+                        let result = process_firehose_response(
                             &endpoint,
                             response,
                             &mut check_subgraph_continuity,
@@ -264,16 +377,32 @@ fn stream_blocks<C: Blockchain, F: FirehoseMapper<C>>(
                             subgraph_current_block.as_ref(),
                             mapper.as_ref(),
                             &logger,
-                        ).await {
+                        ).await;
+                        match &result {
+                            Ok(BlockResponse::Proceed(event, _)) => tracing::set_attributes(
+                                &response_cx,
+                                tracing::block_attributes(&metrics.deployment, &event.block_ptr()),
+                            ),
+                            Ok(BlockResponse::Rewind(_)) => {}
+                            Err(e) => tracing::record_error(&response_cx, e),
+                        }
+                        drop(response_cx);
+
+                        match result {
                             Ok(BlockResponse::Proceed(event, cursor)) => {
                                 // Reset backoff because we got a good value from the stream
                                 backoff.reset();
 
                                 metrics.observe_response("proceed", &mut last_response_time, &endpoint.provider);
 
+                                let block_ptr = event.block_ptr();
+                                let undo = matches!(event, BlockStreamEvent::Revert(..));
+                                metrics.observe_block(block_ptr.number, undo, &endpoint.provider);
+
                                 yield event;
 
                                 latest_cursor = FirehoseCursor::from(cursor);
+                                subgraph_current_block = Some(block_ptr);
                             },
                             Ok(BlockResponse::Rewind(revert_to)) => {
                                 // Reset backoff because we got a good value from the stream
@@ -309,12 +438,20 @@ fn stream_blocks<C: Blockchain, F: FirehoseMapper<C>>(
                                 metrics.observe_response("error", &mut last_response_time, &endpoint.provider);
 
                                 error!(logger, "{:#}", err);
+                                // Make the next connection prefer another endpoint of the chain
+                                endpoint.report_stream_failure(ConnectionType::Firehose);
+                                if is_cursor_rejected(&err, &latest_cursor) {
+                                    metrics.observe_cursor_reset(&endpoint.provider);
+                                    reset_cursor = true;
+                                }
                                 expected_stream_end = true;
                                 break;
                             }
                         }
                     }
 
+                    metrics.observe_disconnect();
+
                     if !expected_stream_end {
                         error!(logger, "Stream blocks complete unexpectedly, expecting stream to always stream blocks");
                     }
@@ -327,9 +464,35 @@ fn stream_blocks<C: Blockchain, F: FirehoseMapper<C>>(
                     metrics.observe_failed_connection(&mut connect_start, &endpoint.provider);
 
                     error!(logger, "Unable to connect to endpoint: {:#}", e);
+                    if is_cursor_rejected(&e, &latest_cursor) {
+                        metrics.observe_cursor_reset(&endpoint.provider);
+                        reset_cursor = true;
+                    }
                 }
             }
 
+            // The cursor might have been issued by a different provider, or the provider lost the
+            // history it refers to. Without a way to resume from it, we start over from the block
+            // the subgraph is at, which requires checking that the block is still on the chain.
+            if reset_cursor {
+                warn!(logger, "Provider rejected the cursor, resuming from the subgraph block pointer";
+                    "cursor" => latest_cursor.to_string(),
+                    "subgraph_current_block" => subgraph_current_block.as_ref().map(|ptr| ptr.to_string()));
+
+                reset_cursor = false;
+                latest_cursor = FirehoseCursor::None;
+                start_block_num = subgraph_current_block
+                    .as_ref()
+                    .map(|ptr| ptr.block_number() + 1 as BlockNumber)
+                    .unwrap_or(manifest_start_block_num);
+                check_subgraph_continuity = must_check_subgraph_continuity(
+                    &logger,
+                    &subgraph_current_block,
+                    &latest_cursor,
+                    manifest_start_block_num,
+                );
+            }
+
             // If we reach this point, we must wait a bit before retrying, unless `skip_backoff` is true
             if !skip_backoff {
                 backoff.sleep_async().await;
@@ -338,6 +501,39 @@ fn stream_blocks<C: Blockchain, F: FirehoseMapper<C>>(
     }
 }
 
+/// Wait for the next response of `stream`, treating a stream that has not
+/// sent anything for `GRAPH_NODE_FIREHOSE_STALL_TIMEOUT` as failed so that
+/// we reconnect instead of waiting forever on a stalled provider
+pub(crate) async fn next_response<S, T>(stream: &mut S) -> Option<Result<T, Status>>
+where
+    S: Stream<Item = Result<T, Status>> + Unpin,
+{
+    let timeout = ENV_VARS.firehose_stall_timeout;
+    if timeout.is_zero() {
+        return stream.next().await;
+    }
+
+    match tokio::time::timeout(timeout, stream.next()).await {
+        Ok(response) => response,
+        Err(_) => Some(Err(Status::deadline_exceeded(format!(
+            "no response from the stream in {}s",
+            timeout.as_secs()
+        )))),
+    }
+}
+
+/// Whether the provider refused to resume the stream from `cursor`
+fn is_cursor_rejected(err: &Error, cursor: &FirehoseCursor) -> bool {
+    !cursor.is_none()
+        && err
+            .chain()
+            .filter_map(|e| e.downcast_ref::<Status>())
+            .any(|status| {
+                status.code() == tonic::Code::InvalidArgument
+                    && status.message().to_lowercase().contains("cursor")
+            })
+}
+
 enum BlockResponse<C: Blockchain> {
     Proceed(BlockStreamEvent<C>, String),
     Rewind(BlockPtr),
@@ -443,10 +639,13 @@ fn must_check_subgraph_continuity(
 #[cfg(test)]
 mod tests {
     use crate::blockchain::{
-        block_stream::FirehoseCursor, firehose_block_stream::must_check_subgraph_continuity,
+        block_stream::FirehoseCursor,
+        firehose_block_stream::{is_cursor_rejected, must_check_subgraph_continuity},
         BlockPtr,
     };
+    use anyhow::{anyhow, Context};
     use slog::{o, Logger};
+    use tonic::{Code, Status};
 
     #[test]
     fn check_continuity() {
@@ -507,4 +706,23 @@ mod tests {
             false,
         );
     }
+
+    #[test]
+    fn cursor_rejected() {
+        let cursor = FirehoseCursor::from("abc".to_string());
+        let rejected = || {
+            Err::<(), _>(Status::new(Code::InvalidArgument, "invalid start cursor"))
+                .context("An error occurred while streaming blocks")
+                .unwrap_err()
+        };
+
+        assert!(is_cursor_rejected(&rejected(), &cursor));
+        // Without a cursor, there is nothing to reset
+        assert!(!is_cursor_rejected(&rejected(), &FirehoseCursor::None));
+        assert!(!is_cursor_rejected(
+            &Status::new(Code::Unavailable, "cursor").into(),
+            &cursor
+        ));
+        assert!(!is_cursor_rejected(&anyhow!("invalid cursor"), &cursor));
+    }
 }
@@ -0,0 +1,192 @@
+//! A block stream for deployments with `IngestionMode::Hybrid`. It
+//! backfills over Firehose while the deployment is far behind the chain
+//! head, and switches to polling JSON-RPC once it gets close to the chain
+//! head. A deployment that falls far behind again goes back to Firehose
+use super::block_stream::{
+    BlockStream, BlockStreamError, BlockStreamEvent, FirehoseCursor, FIREHOSE_BUFFER_STREAM_SIZE,
+};
+use super::Blockchain;
+use crate::prelude::*;
+use async_stream::try_stream;
+use futures03::{Stream, StreamExt};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// How often the stream checks how far it is behind the chain head
+const HEAD_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Builds a block stream that continues after the given block and cursor
+pub type BuildBlockStream<C> =
+    Box<dyn Fn(Option<BlockPtr>, FirehoseCursor) -> Box<dyn BlockStream<C>> + Send + Sync>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Source {
+    Backfill,
+    Head,
+}
+
+impl Source {
+    /// The source to use at block `current` when the chain head is at
+    /// `head`. Once the stream is near the chain head, it only goes back to
+    /// backfilling when it falls behind by twice `distance` so that it does
+    /// not switch back and forth around `distance`
+    fn choose(
+        self,
+        current: BlockNumber,
+        head: Option<BlockNumber>,
+        distance: BlockNumber,
+    ) -> Self {
+        let head = match head {
+            Some(head) => head,
+            None => return self,
+        };
+        let lag = head - current;
+        match self {
+            Source::Backfill if lag <= distance => Source::Head,
+            Source::Head if lag > 2 * distance => Source::Backfill,
+            source => source,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Source::Backfill => "firehose",
+            Source::Head => "rpc",
+        }
+    }
+}
+
+pub struct HybridBlockStream<C: Blockchain> {
+    stream: Pin<Box<dyn Stream<Item = Result<BlockStreamEvent<C>, BlockStreamError>> + Send>>,
+}
+
+impl<C: Blockchain> HybridBlockStream<C> {
+    /// Create a stream that uses `backfill` while the deployment is more
+    /// than `distance` blocks behind the chain head and `head` otherwise
+    pub fn new(
+        chain_store: Arc<dyn ChainStore>,
+        subgraph_current_block: Option<BlockPtr>,
+        cursor: FirehoseCursor,
+        start_blocks: Vec<BlockNumber>,
+        distance: BlockNumber,
+        backfill: BuildBlockStream<C>,
+        head: BuildBlockStream<C>,
+        logger: Logger,
+    ) -> Self {
+        let start_block = start_blocks.into_iter().min().unwrap_or(0);
+
+        HybridBlockStream {
+            stream: Box::pin(stream_blocks(
+                chain_store,
+                subgraph_current_block,
+                cursor,
+                start_block,
+                distance,
+                backfill,
+                head,
+                logger,
+            )),
+        }
+    }
+}
+
+fn stream_blocks<C: Blockchain>(
+    chain_store: Arc<dyn ChainStore>,
+    mut current: Option<BlockPtr>,
+    mut cursor: FirehoseCursor,
+    start_block: BlockNumber,
+    distance: BlockNumber,
+    backfill: BuildBlockStream<C>,
+    head: BuildBlockStream<C>,
+    logger: Logger,
+) -> impl Stream<Item = Result<BlockStreamEvent<C>, BlockStreamError>> {
+    let number = move |current: &Option<BlockPtr>| {
+        current
+            .as_ref()
+            .map(|ptr| ptr.number)
+            .unwrap_or(start_block)
+    };
+
+    try_stream! {
+        let mut source = Source::Backfill;
+        'streams: loop {
+            let head_number = chain_head_number(&chain_store).await?;
+            source = source.choose(number(&current), head_number, distance);
+
+            info!(&logger, "Streaming blocks";
+                "source" => source.as_str(),
+                "subgraph_current_block" => number(&current),
+                "chain_head" => head_number);
+            let mut stream = match source {
+                Source::Backfill => backfill(current.clone(), cursor.clone()),
+                // Polling does not use cursors
+                Source::Head => head(current.clone(), FirehoseCursor::None),
+            };
+
+            let mut checked = Instant::now();
+            loop {
+                let event = match stream.next().await {
+                    Some(event) => event?,
+                    None => break 'streams,
+                };
+                current = Some(event.block_ptr());
+                cursor = event.cursor().clone();
+                yield event;
+
+                if checked.elapsed() >= HEAD_CHECK_INTERVAL {
+                    checked = Instant::now();
+                    let head_number = chain_head_number(&chain_store).await?;
+                    // The next stream starts after the last event we
+                    // yielded, so dropping this one loses nothing
+                    if source.choose(number(&current), head_number, distance) != source {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn chain_head_number(
+    chain_store: &Arc<dyn ChainStore>,
+) -> Result<Option<BlockNumber>, Error> {
+    let head = chain_store.cheap_clone().chain_head_ptr().await?;
+    Ok(head.map(|ptr| ptr.number))
+}
+
+impl<C: Blockchain> Stream for HybridBlockStream<C> {
+    type Item = Result<BlockStreamEvent<C>, BlockStreamError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+impl<C: Blockchain> BlockStream<C> for HybridBlockStream<C> {
+    fn buffer_size_hint(&self) -> usize {
+        FIREHOSE_BUFFER_STREAM_SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Source;
+
+    #[test]
+    fn choose_source() {
+        use Source::*;
+
+        // Far behind the head, keep backfilling
+        assert_eq!(Backfill, Backfill.choose(100, Some(10_000), 1000));
+        // Close to the head, switch to polling
+        assert_eq!(Head, Backfill.choose(9_000, Some(10_000), 1000));
+        // Without a chain head, keep going as before
+        assert_eq!(Backfill, Backfill.choose(9_000, None, 1000));
+        assert_eq!(Head, Head.choose(100, None, 1000));
+        // Falling a little behind again does not switch back
+        assert_eq!(Head, Head.choose(8_500, Some(10_000), 1000));
+        // Falling far behind does
+        assert_eq!(Backfill, Head.choose(7_999, Some(10_000), 1000));
+    }
+}
@@ -12,12 +12,13 @@ use anyhow::Error;
 use async_trait::async_trait;
 use serde::Deserialize;
 use std::{collections::HashSet, convert::TryFrom, sync::Arc};
+use web3::types::H256;
 
 use super::{
     block_stream::{self, BlockStream, FirehoseCursor},
     client::ChainClient,
-    BlockIngestor, BlockTime, EmptyNodeCapabilities, HostFn, IngestorError, MappingTriggerTrait,
-    NoopDecoderHook, TriggerWithHandler,
+    BlockIngestor, BlockTime, EmptyNodeCapabilities, HostFn, IngestionMode, IngestorError,
+    MappingTriggerTrait, NoopDecoderHook, TriggerWithHandler,
 };
 
 use super::{
@@ -36,11 +37,13 @@ pub struct MockBlock {
 
 impl Block for MockBlock {
     fn ptr(&self) -> BlockPtr {
-        todo!()
+        (H256::from_low_u64_be(self.number), self.number).into()
     }
 
     fn parent_ptr(&self) -> Option<BlockPtr> {
-        todo!()
+        self.number
+            .checked_sub(1)
+            .map(|number| MockBlock { number }.ptr())
     }
 
     fn timestamp(&self) -> BlockTime {
@@ -349,6 +352,7 @@ impl Blockchain for MockBlockchain {
         _start_blocks: Vec<BlockNumber>,
         _filter: Arc<Self::TriggerFilter>,
         _unified_api_version: UnifiedMappingApiVersion,
+        _ingestion: IngestionMode,
     ) -> Result<Box<dyn BlockStream<Self>>, Error> {
         todo!()
     }
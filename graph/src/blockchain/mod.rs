@@ -8,6 +8,7 @@ pub mod client;
 mod empty_node_capabilities;
 pub mod firehose_block_ingestor;
 pub mod firehose_block_stream;
+pub mod hybrid_block_stream;
 pub mod mock;
 mod noop_runtime_adapter;
 pub mod polling_block_stream;
@@ -25,9 +26,10 @@ use crate::{
         trigger_processor::RunnableTriggers,
     },
     data::subgraph::{UnifiedMappingApiVersion, MIN_SPEC_VERSION},
-    data_source::{self, DataSourceTemplateInfo},
+    data_source::{self, subgraph::SUBGRAPH_DS_KIND, DataSourceTemplateInfo},
     prelude::DataSourceContext,
     runtime::{gas::GasCounter, AscHeap, HostExportError},
+    schema::InputSchema,
 };
 use crate::{
     components::store::{BlockNumber, ChainStore},
@@ -191,6 +193,7 @@ pub trait Blockchain: Debug + Sized + Send + Sync + Unpin + 'static {
         start_blocks: Vec<BlockNumber>,
         filter: Arc<Self::TriggerFilter>,
         unified_api_version: UnifiedMappingApiVersion,
+        ingestion: IngestionMode,
     ) -> Result<Box<dyn BlockStream<Self>>, Error>;
 
     fn chain_store(&self) -> Arc<dyn ChainStore>;
@@ -260,6 +263,13 @@ pub trait TriggerFilter<C: Blockchain>: Default + Clone + Send + Sync {
 
     fn extend<'a>(&mut self, data_sources: impl Iterator<Item = &'a C::DataSource> + Clone);
 
+    /// Make the filter match every block. Subgraph data sources need this
+    /// since their triggers come from another deployment and not from the
+    /// chain. Return `false` if the chain does not support that
+    fn extend_with_every_block(&mut self) -> bool {
+        false
+    }
+
     fn node_capabilities(&self) -> C::NodeCapabilities;
 
     fn to_firehose_filter(self) -> Vec<prost_types::Any>;
@@ -319,6 +329,12 @@ pub trait DataSource<C: Blockchain>: 'static + Sized + Send + Sync + Clone {
     /// Used as part of manifest validation. If there are no errors, return an empty vector.
     fn validate(&self, spec_version: &semver::Version) -> Vec<Error>;
 
+    /// Used as part of manifest validation for data sources that refer to
+    /// the entity types of the subgraph schema
+    fn validate_schema(&self, _schema: &InputSchema) -> Vec<Error> {
+        vec![]
+    }
+
     fn has_expired(&self, block: BlockNumber) -> bool {
         self.end_block()
             .map_or(false, |end_block| block > end_block)
@@ -400,6 +416,18 @@ pub trait DecoderHook<C: Blockchain> {
         triggers: Vec<RunnableTriggers<'a, C>>,
         metrics: &Arc<SubgraphInstanceMetrics>,
     ) -> Result<Vec<RunnableTriggers<'a, C>>, MappingError>;
+
+    /// Fill caches with what processing the `triggers` of a block will
+    /// need, ahead of processing it. Called when a deployment that starts
+    /// far behind the chain head warms up its caches
+    async fn warm_up(
+        &self,
+        _logger: &Logger,
+        _block_ptr: &BlockPtr,
+        _triggers: Vec<C::MappingTrigger>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 /// A decoder hook that does nothing and just returns the triggers that were
@@ -462,6 +490,12 @@ pub enum BlockchainKind {
     Substreams,
 
     Starknet,
+
+    /// Solana chains
+    Solana,
+
+    /// Bitcoin and other chains that use the UTXO model of Bitcoin
+    Bitcoin,
 }
 
 impl fmt::Display for BlockchainKind {
@@ -473,6 +507,8 @@ impl fmt::Display for BlockchainKind {
             BlockchainKind::Cosmos => "cosmos",
             BlockchainKind::Substreams => "substreams",
             BlockchainKind::Starknet => "starknet",
+            BlockchainKind::Solana => "solana",
+            BlockchainKind::Bitcoin => "bitcoin",
         };
         write!(f, "{}", value)
     }
@@ -489,6 +525,8 @@ impl FromStr for BlockchainKind {
             "cosmos" => Ok(BlockchainKind::Cosmos),
             "substreams" => Ok(BlockchainKind::Substreams),
             "starknet" => Ok(BlockchainKind::Starknet),
+            "solana" => Ok(BlockchainKind::Solana),
+            "bitcoin" => Ok(BlockchainKind::Bitcoin),
             _ => Err(anyhow!("unknown blockchain kind {}", s)),
         }
     }
@@ -498,25 +536,70 @@ impl BlockchainKind {
     pub fn from_manifest(manifest: &serde_yaml::Mapping) -> Result<Self, Error> {
         use serde_yaml::Value;
 
-        // The `kind` field of the first data source in the manifest.
+        // The `kind` field of the first data source in the manifest that is
+        // not a subgraph data source, since those do not say which chain
+        // the subgraph indexes.
         //
         // Split by `/` to, for example, read 'ethereum' in 'ethereum/contracts'.
         manifest
             .get(&Value::String("dataSources".to_owned()))
             .and_then(|ds| ds.as_sequence())
-            .and_then(|ds| ds.first())
-            .and_then(|ds| ds.as_mapping())
-            .and_then(|ds| ds.get(&Value::String("kind".to_owned())))
-            .and_then(|kind| kind.as_str())
+            .and_then(|ds| {
+                ds.iter()
+                    .filter_map(|ds| ds.as_mapping())
+                    .filter_map(|ds| ds.get(&Value::String("kind".to_owned())))
+                    .filter_map(|kind| kind.as_str())
+                    .find(|kind| *kind != SUBGRAPH_DS_KIND)
+            })
             .and_then(|kind| kind.split('/').next())
             .context("invalid manifest")
             .and_then(BlockchainKind::from_str)
     }
 }
 
+/// How a deployment gets its blocks
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IngestionMode {
+    /// Use the block stream that the chain is configured for
+    #[default]
+    Default,
+    /// Backfill over Firehose while the deployment is far behind the chain
+    /// head and switch to polling JSON-RPC near the chain head. Chains that
+    /// can't do that use their default block stream
+    Hybrid,
+}
+
+impl fmt::Display for IngestionMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = match self {
+            IngestionMode::Default => "default",
+            IngestionMode::Hybrid => "hybrid",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl FromStr for IngestionMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(IngestionMode::Default),
+            "hybrid" => Ok(IngestionMode::Hybrid),
+            _ => Err(anyhow!("unknown ingestion mode {}", s)),
+        }
+    }
+}
+
 /// A collection of blockchains, keyed by `BlockchainKind` and network.
 #[derive(Default, Debug, Clone)]
-pub struct BlockchainMap(HashMap<(BlockchainKind, ChainId), Arc<dyn Any + Send + Sync>>);
+pub struct BlockchainMap {
+    chains: HashMap<(BlockchainKind, ChainId), Arc<dyn Any + Send + Sync>>,
+    /// Other names that manifests may use for networks, mapped to the
+    /// network that they refer to
+    aliases: HashMap<ChainId, ChainId>,
+}
 
 impl BlockchainMap {
     pub fn new() -> Self {
@@ -526,18 +609,29 @@ impl BlockchainMap {
     pub fn iter(
         &self,
     ) -> impl Iterator<Item = (&(BlockchainKind, ChainId), &Arc<dyn Any + Sync + Send>)> {
-        self.0.iter()
+        self.chains.iter()
     }
 
     pub fn insert<C: Blockchain>(&mut self, network: ChainId, chain: Arc<C>) {
-        self.0.insert((C::KIND, network), chain);
+        self.chains.insert((C::KIND, network), chain);
+    }
+
+    /// Make `get` find the chains of `network` under the name `alias`, too
+    pub fn insert_alias(&mut self, alias: ChainId, network: ChainId) {
+        self.aliases.insert(alias, network);
+    }
+
+    /// The network that `network` is an alias for, or `network` itself if
+    /// it is not an alias
+    pub fn resolve(&self, network: ChainId) -> ChainId {
+        self.aliases.get(&network).cloned().unwrap_or(network)
     }
 
     pub fn get_all_by_kind<C: Blockchain>(
         &self,
         kind: BlockchainKind,
     ) -> Result<Vec<Arc<C>>, Error> {
-        self.0
+        self.chains
             .iter()
             .flat_map(|((k, _), chain)| {
                 if k.eq(&kind) {
@@ -552,7 +646,8 @@ impl BlockchainMap {
     }
 
     pub fn get<C: Blockchain>(&self, network: ChainId) -> Result<Arc<C>, Error> {
-        self.0
+        let network = self.resolve(network);
+        self.chains
             .get(&(C::KIND, network.clone()))
             .with_context(|| format!("no network {} found on chain {}", network, C::KIND))?
             .cheap_clone()
@@ -6,6 +6,7 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
+use tokio::task::JoinHandle;
 
 use super::block_stream::{
     BlockStream, BlockStreamError, BlockStreamEvent, BlockWithTriggers, ChainHeadUpdateStream,
@@ -14,6 +15,7 @@ use super::block_stream::{
 use super::{Block, BlockPtr, Blockchain};
 
 use crate::components::store::BlockNumber;
+use crate::components::tracing::{self, KeyValue, TraceFutureExt as _};
 use crate::data::subgraph::UnifiedMappingApiVersion;
 use crate::prelude::*;
 
@@ -124,6 +126,19 @@ pub struct PollingBlockStream<C: Blockchain> {
     consecutive_err_count: u32,
     chain_head_update_stream: ChainHeadUpdateStream,
     ctx: PollingBlockStreamContext<C>,
+    /// The reconciliation that continues after the blocks we are currently
+    /// yielding. It runs in its own task so that scanning the next block
+    /// range overlaps with the subgraph processing the current one; we
+    /// never prefetch more than one range ahead
+    prefetch: Option<JoinHandle<Result<NextBlocks<C>, Error>>>,
+}
+
+impl<C: Blockchain> Drop for PollingBlockStream<C> {
+    fn drop(&mut self) {
+        if let Some(prefetch) = self.prefetch.take() {
+            prefetch.abort();
+        }
+    }
 }
 
 // This is the same as `ReconciliationStep` but without retries.
@@ -178,6 +193,7 @@ where
                 target_triggers_per_block_range,
                 unified_api_version,
             },
+            prefetch: None,
         }
     }
 }
@@ -379,7 +395,21 @@ where
             );
 
             // Update with actually scanned range, to account for any skipped null blocks.
-            let (blocks, to) = self.adapter.scan_triggers(from, to, &self.filter).await?;
+            let mut scan_attributes = tracing::deployment_attributes(&self.subgraph_id);
+            scan_attributes.push(KeyValue::new("from", from as i64));
+            scan_attributes.push(KeyValue::new("to", to as i64));
+            let scan_cx = tracing::root_span("block_stream.scan_triggers", scan_attributes);
+            let (blocks, to) = self
+                .adapter
+                .scan_triggers(from, to, &self.filter)
+                .with_context(scan_cx.clone())
+                .await
+                .map_err(|e| {
+                    tracing::record_error(&scan_cx, &e);
+                    e
+                })?;
+            tracing::set_attributes(&scan_cx, vec![KeyValue::new("blocks", blocks.len() as i64)]);
+            drop(scan_cx);
             let range_size = to - from + 1;
 
             // If the target block (`to`) is within the reorg threshold, indicating no non-null finalized blocks are
@@ -541,6 +571,19 @@ impl<C: Blockchain> Stream for PollingBlockStream<C> {
                                     );
                                 }
 
+                                // Start looking for the blocks after the ones we are
+                                // about to yield
+                                if ENV_VARS.block_stream_prefetch {
+                                    if let Some(last) = next_blocks.back() {
+                                        let mut ctx = self.ctx.clone();
+                                        ctx.current_block = Some(last.block.ptr());
+                                        self.prefetch =
+                                            Some(crate::spawn(
+                                                async move { ctx.next_blocks().await },
+                                            ));
+                                    }
+                                }
+
                                 // Switch to yielding state until next_blocks is depleted
                                 self.state =
                                     BlockStreamState::YieldingBlocks(Box::new(next_blocks));
@@ -604,7 +647,13 @@ impl<C: Blockchain> Stream for PollingBlockStream<C> {
 
                         // Done yielding blocks
                         None => {
-                            self.state = BlockStreamState::BeginReconciliation;
+                            self.state = match self.prefetch.take() {
+                                Some(prefetch) => {
+                                    let fut = async move { prefetch.await? };
+                                    BlockStreamState::Reconciliation(fut.boxed())
+                                }
+                                None => BlockStreamState::BeginReconciliation,
+                            };
                         }
                     }
                 }
@@ -645,3 +694,337 @@ impl<C: Blockchain> Stream for PollingBlockStream<C> {
         result.map_err(BlockStreamError::from)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use futures03::{stream, StreamExt};
+    use tokio::sync::oneshot;
+    use web3::types::H256;
+
+    use super::PollingBlockStream;
+    use crate::blockchain::block_stream::{BlockStreamEvent, BlockWithTriggers, TriggersAdapter};
+    use crate::blockchain::mock::{MockBlock, MockBlockchain, MockTriggerFilter};
+    use crate::blockchain::{Block, BlockHash, BlockPtr, ChainIdentifier};
+    use crate::components::store::{BlockNumber, ChainStore, StoreError};
+    use crate::components::transaction_receipt::LightTransactionReceipt;
+    use crate::data::subgraph::{DeploymentHash, UnifiedMappingApiVersion};
+    use crate::prelude::{anyhow, async_trait, o, Error, Logger, NodeId};
+
+    const HEAD: u64 = 100;
+
+    /// A chain store that only knows the chain head
+    struct TestChainStore;
+
+    #[async_trait]
+    impl ChainStore for TestChainStore {
+        fn genesis_block_ptr(&self) -> Result<BlockPtr, Error> {
+            unimplemented!()
+        }
+
+        async fn upsert_block(&self, _block: Arc<dyn Block>) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn upsert_light_blocks(&self, _blocks: &[&dyn Block]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        async fn attempt_chain_head_update(
+            self: Arc<Self>,
+            _ancestor_count: BlockNumber,
+        ) -> Result<Option<H256>, Error> {
+            unimplemented!()
+        }
+
+        async fn chain_head_ptr(self: Arc<Self>) -> Result<Option<BlockPtr>, Error> {
+            Ok(Some(MockBlock { number: HEAD }.ptr()))
+        }
+
+        fn chain_head_cursor(&self) -> Result<Option<String>, Error> {
+            unimplemented!()
+        }
+
+        async fn set_chain_head(
+            self: Arc<Self>,
+            _block: Arc<dyn Block>,
+            _cursor: String,
+        ) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        async fn blocks(
+            self: Arc<Self>,
+            _hashes: Vec<BlockHash>,
+        ) -> Result<Vec<serde_json::Value>, Error> {
+            unimplemented!()
+        }
+
+        async fn ancestor_block(
+            self: Arc<Self>,
+            _block_ptr: BlockPtr,
+            _offset: BlockNumber,
+            _root: Option<BlockHash>,
+        ) -> Result<Option<(serde_json::Value, BlockPtr)>, Error> {
+            unimplemented!()
+        }
+
+        fn cleanup_cached_blocks(
+            &self,
+            _ancestor_count: BlockNumber,
+        ) -> Result<Option<(BlockNumber, usize)>, Error> {
+            unimplemented!()
+        }
+
+        fn block_hashes_by_block_number(
+            &self,
+            _number: BlockNumber,
+        ) -> Result<Vec<BlockHash>, Error> {
+            unimplemented!()
+        }
+
+        fn confirm_block_hash(
+            &self,
+            _number: BlockNumber,
+            _hash: &BlockHash,
+        ) -> Result<usize, Error> {
+            unimplemented!()
+        }
+
+        async fn block_number(
+            &self,
+            _hash: &BlockHash,
+        ) -> Result<Option<(String, BlockNumber, Option<u64>, Option<BlockHash>)>, StoreError>
+        {
+            unimplemented!()
+        }
+
+        async fn block_numbers(
+            &self,
+            _hashes: Vec<BlockHash>,
+        ) -> Result<HashMap<BlockHash, BlockNumber>, StoreError> {
+            unimplemented!()
+        }
+
+        async fn transaction_receipts_in_block(
+            &self,
+            _block_ptr: &H256,
+        ) -> Result<Vec<LightTransactionReceipt>, StoreError> {
+            unimplemented!()
+        }
+
+        async fn clear_call_cache(
+            &self,
+            _from: BlockNumber,
+            _to: BlockNumber,
+        ) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn chain_identifier(&self) -> Result<ChainIdentifier, Error> {
+            unimplemented!()
+        }
+
+        fn set_chain_identifier(&self, _ident: &ChainIdentifier) -> Result<(), Error> {
+            unimplemented!()
+        }
+    }
+
+    /// What a call to `scan_triggers` does
+    enum Scan {
+        /// Return the block at the start of the range
+        Blocks,
+        Fail,
+        /// Signal `started`, then never finish; `dropped` is dropped
+        /// together with the scan
+        Hang {
+            started: oneshot::Sender<()>,
+            dropped: oneshot::Sender<()>,
+        },
+    }
+
+    #[derive(Default)]
+    struct TestAdapter {
+        /// The behavior of the next calls to `scan_triggers`. Once it is
+        /// empty, scans return blocks
+        scans: Mutex<VecDeque<Scan>>,
+        /// The start of the ranges that were scanned
+        scanned: Mutex<Vec<BlockNumber>>,
+        /// Blocks that the next call to `is_on_main_chain` reports as
+        /// uncled
+        uncled: Mutex<HashSet<BlockNumber>>,
+    }
+
+    impl TestAdapter {
+        fn new(scans: Vec<Scan>) -> Self {
+            Self {
+                scans: Mutex::new(scans.into()),
+                ..Default::default()
+            }
+        }
+
+        fn scanned(&self) -> Vec<BlockNumber> {
+            self.scanned.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl TriggersAdapter<MockBlockchain> for TestAdapter {
+        async fn ancestor_block(
+            &self,
+            _ptr: BlockPtr,
+            _offset: BlockNumber,
+            _root: Option<BlockHash>,
+        ) -> Result<Option<MockBlock>, Error> {
+            unimplemented!()
+        }
+
+        async fn scan_triggers(
+            &self,
+            from: BlockNumber,
+            to: BlockNumber,
+            _filter: &MockTriggerFilter,
+        ) -> Result<(Vec<BlockWithTriggers<MockBlockchain>>, BlockNumber), Error> {
+            self.scanned.lock().unwrap().push(from);
+            let scan = self.scans.lock().unwrap().pop_front();
+            match scan.unwrap_or(Scan::Blocks) {
+                Scan::Blocks => {
+                    let block = BlockWithTriggers::new(
+                        MockBlock {
+                            number: from as u64,
+                        },
+                        vec![],
+                        &Logger::root(slog::Discard, o!()),
+                    );
+                    Ok((vec![block], to))
+                }
+                Scan::Fail => Err(anyhow!("scan failed")),
+                Scan::Hang { started, dropped } => {
+                    started.send(()).unwrap();
+                    let _dropped = dropped;
+                    futures03::future::pending().await
+                }
+            }
+        }
+
+        async fn triggers_in_block(
+            &self,
+            _logger: &Logger,
+            _block: MockBlock,
+            _filter: &MockTriggerFilter,
+        ) -> Result<BlockWithTriggers<MockBlockchain>, Error> {
+            unimplemented!()
+        }
+
+        async fn is_on_main_chain(&self, ptr: BlockPtr) -> Result<bool, Error> {
+            Ok(!self.uncled.lock().unwrap().remove(&ptr.number))
+        }
+
+        async fn parent_ptr(&self, block: &BlockPtr) -> Result<Option<BlockPtr>, Error> {
+            Ok(MockBlock {
+                number: block.number as u64,
+            }
+            .parent_ptr())
+        }
+    }
+
+    /// A block stream that starts after block 1 and that is too far
+    /// behind the chain head to be affected by the reorg threshold. It
+    /// scans one block at a time
+    fn block_stream(adapter: Arc<TestAdapter>) -> PollingBlockStream<MockBlockchain> {
+        PollingBlockStream::new(
+            Arc::new(TestChainStore),
+            Box::new(stream::pending::<()>()),
+            adapter,
+            NodeId::new("test").unwrap(),
+            DeploymentHash::new("pollingBlockStream").unwrap(),
+            Arc::new(MockTriggerFilter),
+            vec![],
+            0,
+            Logger::root(slog::Discard, o!()),
+            1,
+            1,
+            UnifiedMappingApiVersion::try_from_versions(std::iter::empty()).unwrap(),
+            Some(MockBlock { number: 1 }.ptr()),
+        )
+    }
+
+    async fn next_block(stream: &mut PollingBlockStream<MockBlockchain>) -> u64 {
+        match stream.next().await {
+            Some(Ok(BlockStreamEvent::ProcessBlock(block, _))) => block.block.number,
+            _ => panic!("expected a block"),
+        }
+    }
+
+    #[tokio::test]
+    async fn prefetch_finds_next_blocks() {
+        let adapter = Arc::new(TestAdapter::default());
+        let mut stream = block_stream(adapter.clone());
+
+        assert_eq!(2, next_block(&mut stream).await);
+        assert_eq!(3, next_block(&mut stream).await);
+        assert_eq!(4, next_block(&mut stream).await);
+        // Blocks 3 and 4 came from prefetches, and no range was scanned
+        // twice
+        assert_eq!(&[2, 3, 4], &adapter.scanned()[..3]);
+    }
+
+    #[tokio::test]
+    async fn prefetch_reverts_after_reorg() {
+        let adapter = Arc::new(TestAdapter::default());
+        let mut stream = block_stream(adapter.clone());
+
+        // Block 2 is uncled while it is being processed. The prefetch for
+        // the blocks after it notices, and the stream reverts to block 1
+        // and continues from there instead of using blocks that were
+        // found for block 2
+        adapter.uncled.lock().unwrap().insert(2);
+        assert_eq!(2, next_block(&mut stream).await);
+        match stream.next().await {
+            Some(Ok(BlockStreamEvent::Revert(ptr, _))) => {
+                assert_eq!(MockBlock { number: 1 }.ptr(), ptr)
+            }
+            _ => panic!("expected a revert"),
+        }
+        assert_eq!(2, next_block(&mut stream).await);
+        assert_eq!(&[2, 2], &adapter.scanned()[..2]);
+    }
+
+    #[tokio::test]
+    async fn prefetch_error() {
+        let adapter = Arc::new(TestAdapter::new(vec![Scan::Blocks, Scan::Fail]));
+        let mut stream = block_stream(adapter.clone());
+
+        assert_eq!(2, next_block(&mut stream).await);
+        // The error from the prefetch is reported by the stream
+        match stream.next().await {
+            Some(Err(e)) => assert!(e.to_string().contains("scan failed")),
+            _ => panic!("expected an error"),
+        }
+        assert_eq!(vec![2, 3], adapter.scanned());
+    }
+
+    #[tokio::test]
+    async fn drop_stream_during_prefetch() {
+        let (started, started_rcv) = oneshot::channel();
+        let (dropped, dropped_rcv) = oneshot::channel();
+        let adapter = Arc::new(TestAdapter::new(vec![
+            Scan::Blocks,
+            Scan::Hang { started, dropped },
+        ]));
+        let mut stream = block_stream(adapter.clone());
+
+        assert_eq!(2, next_block(&mut stream).await);
+        started_rcv.await.unwrap();
+
+        // Dropping the stream aborts the prefetch
+        drop(stream);
+        let res = tokio::time::timeout(Duration::from_secs(5), dropped_rcv)
+            .await
+            .expect("the prefetch was aborted");
+        assert!(res.is_err());
+    }
+}
@@ -2,8 +2,12 @@ use super::block_stream::{
     BlockStreamError, BlockStreamMapper, FirehoseCursor, SUBSTREAMS_BUFFER_STREAM_SIZE,
 };
 use super::client::ChainClient;
+use super::firehose_block_stream::next_response;
 use crate::blockchain::block_stream::{BlockStream, BlockStreamEvent};
 use crate::blockchain::Blockchain;
+use crate::components::subgraph::STREAM_STATUSES;
+use crate::components::tracing::{self, KeyValue};
+use crate::endpoint::ConnectionType;
 use crate::firehose::ConnectionHeaders;
 use crate::prelude::*;
 use crate::substreams::Modules;
@@ -12,9 +16,10 @@ use crate::util::backoff::ExponentialBackoff;
 use async_stream::try_stream;
 use futures03::{Stream, StreamExt};
 use humantime::format_duration;
+use prost::Message as _;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tonic::{Code, Status};
 
 struct SubstreamsBlockStreamMetrics {
@@ -23,6 +28,10 @@ struct SubstreamsBlockStreamMetrics {
     connect_duration: GaugeVec,
     time_between_responses: HistogramVec,
     responses: CounterVec,
+    received_bytes: CounterVec,
+    connected: GaugeVec,
+    undos: CounterVec,
+    cursor_updated_at: GaugeVec,
 }
 
 impl SubstreamsBlockStreamMetrics {
@@ -60,6 +69,38 @@ impl SubstreamsBlockStreamMetrics {
                     vec!["deployment", "provider", "kind"].as_slice(),
                 )
                 .unwrap(),
+
+            received_bytes: registry
+                .global_counter_vec(
+                    "deployment_substreams_blockstream_received_bytes",
+                    "Counts the bytes of the responses received from a Substreams block stream before compression",
+                    vec!["deployment", "provider"].as_slice(),
+                )
+                .unwrap(),
+
+            connected: registry
+                .global_gauge_vec(
+                    "deployment_substreams_blockstream_connected",
+                    "Whether the Substreams block stream is connected; 0 = no, 1 = yes",
+                    vec!["deployment"].as_slice(),
+                )
+                .unwrap(),
+
+            undos: registry
+                .global_counter_vec(
+                    "deployment_substreams_blockstream_undos",
+                    "Counts the number of undo signals received from a Substreams block stream",
+                    vec!["deployment", "provider"].as_slice(),
+                )
+                .unwrap(),
+
+            cursor_updated_at: registry
+                .global_gauge_vec(
+                    "deployment_substreams_blockstream_cursor_updated_at",
+                    "The time, in seconds since the epoch, at which the cursor of a Substreams block stream last advanced",
+                    vec!["deployment"].as_slice(),
+                )
+                .unwrap(),
         }
     }
 
@@ -70,6 +111,10 @@ impl SubstreamsBlockStreamMetrics {
         self.connect_duration
             .with_label_values(&[&self.deployment, &provider])
             .set(time.elapsed().as_secs_f64());
+        self.connected
+            .with_label_values(&[&self.deployment])
+            .set(1.0);
+        STREAM_STATUSES.connect(&self.deployment, "substreams", provider, true);
 
         // Reset last connection timestamp
         *time = Instant::now();
@@ -82,11 +127,22 @@ impl SubstreamsBlockStreamMetrics {
         self.connect_duration
             .with_label_values(&[&self.deployment, &provider])
             .set(time.elapsed().as_secs_f64());
+        self.connected
+            .with_label_values(&[&self.deployment])
+            .set(0.0);
+        STREAM_STATUSES.connect(&self.deployment, "substreams", provider, false);
 
         // Reset last connection timestamp
         *time = Instant::now();
     }
 
+    fn observe_disconnect(&self) {
+        self.connected
+            .with_label_values(&[&self.deployment])
+            .set(0.0);
+        STREAM_STATUSES.disconnect(&self.deployment);
+    }
+
     fn observe_response(&self, kind: &str, time: &mut Instant, provider: &str) {
         self.time_between_responses
             .with_label_values(&[&self.deployment, &provider])
@@ -98,6 +154,40 @@ impl SubstreamsBlockStreamMetrics {
         // Reset last response timestamp
         *time = Instant::now();
     }
+
+    fn observe_received_bytes(&self, bytes: usize, provider: &str) {
+        self.received_bytes
+            .with_label_values(&[&self.deployment, &provider])
+            .inc_by(bytes as f64);
+        STREAM_STATUSES.receive(&self.deployment, bytes);
+    }
+
+    /// Record that the stream advanced its cursor to `block`; `undo` says
+    /// whether the provider sent an undo signal for it
+    fn observe_block(&self, block: BlockNumber, undo: bool, provider: &str) {
+        if undo {
+            self.undos
+                .with_label_values(&[&self.deployment, &provider])
+                .inc();
+        }
+        self.cursor_updated_at
+            .with_label_values(&[&self.deployment])
+            .set(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64(),
+            );
+        STREAM_STATUSES.advance(&self.deployment, block, undo);
+    }
+}
+
+impl Drop for SubstreamsBlockStreamMetrics {
+    /// The stream is dropped, for example because the deployment was
+    /// stopped
+    fn drop(&mut self) {
+        self.observe_disconnect();
+    }
 }
 
 pub struct SubstreamsBlockStream<C: Blockchain> {
@@ -194,10 +284,12 @@ fn stream_blocks<C: Blockchain, F: BlockStreamMapper<C>>(
                 )))?;
             }
 
+        loop {
+            // Pick the endpoint again on every reconnect so that we fail over
+            // to another endpoint when this one keeps failing
             let endpoint = client.firehose_endpoint().await?;
             let mut logger = logger.new(o!("deployment" => deployment.clone(), "provider" => endpoint.provider.to_string()));
 
-        loop {
             // We just reconnected, assume that we want to back off on errors
             skip_backoff = false;
 
@@ -225,13 +317,31 @@ fn stream_blocks<C: Blockchain, F: BlockStreamMapper<C>>(
                     let mut last_response_time = Instant::now();
                     let mut expected_stream_end = false;
 
-                    for await response in stream{
-                        match process_substreams_response(
+                    let mut stream = stream;
+                    while let Some(response) = next_response(&mut stream).await {
+                        if let Ok(response) = &response {
+                            metrics.observe_received_bytes(response.encoded_len(), &endpoint.provider);
+                        }
+                        let mut response_attributes = tracing::deployment_attributes(&metrics.deployment);
+                        response_attributes.push(KeyValue::new("provider", endpoint.provider.to_string()));
+                        let response_cx = tracing::root_span("substreams.response", response_attributes);
+                        let result = process_substreams_response(
                             response,
                             mapper.as_ref(),
                             &mut logger,
                             &mut log_data,
-                        ).await {
+                        ).await;
+                        match &result {
+                            Ok(Some(BlockResponse::Proceed(event, _))) => tracing::set_attributes(
+                                &response_cx,
+                                tracing::block_attributes(&metrics.deployment, &event.block_ptr()),
+                            ),
+                            Ok(None) => {}
+                            Err(e) => tracing::record_error(&response_cx, e),
+                        }
+                        drop(response_cx);
+
+                        match result {
                             Ok(block_response) => {
                                 match block_response {
                                     None => {}
@@ -240,6 +350,8 @@ fn stream_blocks<C: Blockchain, F: BlockStreamMapper<C>>(
                                         backoff.reset();
 
                                         metrics.observe_response("proceed", &mut last_response_time, &endpoint.provider);
+                                        let undo = matches!(event, BlockStreamEvent::Revert(..));
+                                        metrics.observe_block(event.block_ptr().number, undo, &endpoint.provider);
 
                                         yield event;
 
@@ -265,12 +377,16 @@ fn stream_blocks<C: Blockchain, F: BlockStreamMapper<C>>(
                                 metrics.observe_response("error", &mut last_response_time, &endpoint.provider);
 
                                 error!(logger, "{:#}", err);
+                                // Make the next connection prefer another endpoint of the chain
+                                endpoint.report_stream_failure(ConnectionType::Substreams);
                                 expected_stream_end = true;
                                 break;
                             }
                         }
                     }
 
+                    metrics.observe_disconnect();
+
                     if !expected_stream_end {
                         error!(logger, "Stream blocks complete unexpectedly, expecting stream to always stream blocks");
                     }
@@ -2,20 +2,24 @@ use core::time;
 use std::{
     collections::HashMap,
     ops::{Add, Deref},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
 
 use itertools::Itertools;
-use slog::{o, warn, Discard, Logger};
+use slog::{info, o, warn, Discard, Logger};
 use thiserror::Error;
 
 use crate::{
     blockchain::{BlockHash, ChainIdentifier},
     cheap_clone::CheapClone,
     data::value::Word,
+    env::ENV_VARS,
     prelude::error,
     tokio::sync::RwLock,
 };
@@ -35,6 +39,13 @@ pub enum ProviderManagerError {
         expected: ChainIdentifier,
         actual: ChainIdentifier,
     },
+    #[error("provider {provider} on chain {chain_id} failed verification, expected chain id {expected}, got {actual}")]
+    ChainIdMismatch {
+        chain_id: ChainId,
+        provider: ProviderName,
+        expected: u64,
+        actual: u64,
+    },
     #[error("no providers available for chain {0}")]
     NoProvidersAvailable(ChainId),
     #[error("all providers for chain_id {0} have failed")]
@@ -51,6 +62,17 @@ pub trait NetIdentifiable: Sync + Send {
     }
     async fn net_identifiers(&self) -> Result<ChainIdentifier, anyhow::Error>;
     fn provider_name(&self) -> ProviderName;
+
+    /// The chain id that the configuration expects the provider to report. Validation checks it
+    /// in addition to the chain identifier when it is set
+    fn expected_chain_id(&self) -> Option<u64> {
+        None
+    }
+
+    /// The chain id that the provider reports; only called when `expected_chain_id` is set
+    async fn chain_id(&self) -> Result<u64, anyhow::Error> {
+        Err(anyhow::anyhow!("provider does not report a chain id"))
+    }
 }
 
 #[async_trait]
@@ -61,6 +83,12 @@ impl<T: NetIdentifiable> NetIdentifiable for Arc<T> {
     fn provider_name(&self) -> ProviderName {
         self.as_ref().provider_name()
     }
+    fn expected_chain_id(&self) -> Option<u64> {
+        self.as_ref().expected_chain_id()
+    }
+    async fn chain_id(&self) -> Result<u64, anyhow::Error> {
+        self.as_ref().chain_id().await
+    }
 }
 
 pub type ProviderName = Word;
@@ -204,10 +232,103 @@ impl IdentValidator for NoopIdentValidator {
     }
 }
 
+/// Validates providers against the genesis block hash that the configuration sets for a chain
+/// instead of the one in the store. This is meant for chains that went through a regenesis:
+/// providers of the new chain pass validation and replace the identifier in the store, while
+/// providers that still serve the old chain fail. Chains without an override are passed on to
+/// `validator`
+pub struct GenesisOverrideValidator {
+    logger: Logger,
+    validator: Arc<dyn IdentValidator>,
+    genesis_hashes: HashMap<ChainId, BlockHash>,
+}
+
+impl GenesisOverrideValidator {
+    pub fn new(
+        logger: Logger,
+        validator: Arc<dyn IdentValidator>,
+        genesis_hashes: HashMap<ChainId, BlockHash>,
+    ) -> Self {
+        Self {
+            logger,
+            validator,
+            genesis_hashes,
+        }
+    }
+}
+
+impl IdentValidator for GenesisOverrideValidator {
+    fn check_ident(
+        &self,
+        chain_id: &ChainId,
+        ident: &ChainIdentifier,
+    ) -> Result<(), IdentValidatorError> {
+        let genesis_hash = match self.genesis_hashes.get(chain_id) {
+            Some(genesis_hash) => genesis_hash,
+            None => return self.validator.check_ident(chain_id, ident),
+        };
+
+        if genesis_hash != &ident.genesis_block_hash {
+            return Err(IdentValidatorError::ChangedHash {
+                chain_id: chain_id.clone(),
+                store_hash: genesis_hash.clone(),
+                chain_hash: ident.genesis_block_hash.clone(),
+            });
+        }
+
+        match self.validator.check_ident(chain_id, ident) {
+            Err(
+                e @ IdentValidatorError::ChangedHash { .. }
+                | e @ IdentValidatorError::ChangedNetVersion { .. },
+            ) => {
+                warn!(
+                    self.logger,
+                    "chain identifier in the store differs from the genesis block hash in the configuration, updating it: {}",
+                    e
+                );
+                self.validator.update_ident(chain_id, ident)?;
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    fn update_ident(
+        &self,
+        chain_id: &ChainId,
+        ident: &ChainIdentifier,
+    ) -> Result<(), anyhow::Error> {
+        self.validator.update_ident(chain_id, ident)
+    }
+}
+
+/// The outcome of the latest validation of a provider, as reported by
+/// `ProviderManager::validation_status`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProviderValidation {
+    pub provider: ProviderName,
+    /// One of `not checked`, `temporary failure`, `valid` or `failed`
+    pub status: &'static str,
+    /// The error of the latest validation if it did not succeed
+    pub error: Option<String>,
+    pub quarantined: bool,
+}
+
 /// ProviderCorrectness will maintain a list of providers which have had their
 /// ChainIdentifiers checked. The first identifier is considered correct, if a later
 /// provider for the same chain offers a different ChainIdentifier, this will be considered a
 /// failed validation and it will be disabled.
+///
+/// The providers of a chain can be replaced with `update` while the manager is in use, for
+/// example when the configuration changes. Callers get a snapshot of the providers in the form of
+/// [`Providers`] which a later update does not affect.
+///
+/// Providers that passed or failed validation are validated again with `revalidate` so that a
+/// provider that starts serving a different chain is taken out of use while the node is running.
+///
+/// Providers can also be quarantined, for example because they are on a different fork than the
+/// other providers of their chain. Quarantined providers are left out of snapshots until they are
+/// released again.
 #[derive(Clone, Debug)]
 pub struct ProviderManager<T: NetIdentifiable + Clone + 'static> {
     inner: Arc<Inner<T>>,
@@ -226,8 +347,7 @@ impl<T: NetIdentifiable + Clone> Default for ProviderManager<T> {
         Self {
             inner: Arc::new(Inner {
                 logger: Logger::root(Discard, o!()),
-                adapters: HashMap::default(),
-                status: vec![],
+                adapters: std::sync::RwLock::new(HashMap::default()),
                 validator: Arc::new(NoopIdentValidator {}),
             }),
         }
@@ -240,43 +360,15 @@ impl<T: NetIdentifiable + Clone + 'static> ProviderManager<T> {
         adapters: impl Iterator<Item = (ChainId, Vec<T>)>,
         validator: Arc<dyn IdentValidator>,
     ) -> Self {
-        let mut status: Vec<(Ident, RwLock<GenesisCheckStatus>)> = Vec::new();
-
         let adapters = HashMap::from_iter(adapters.map(|(chain_id, adapters)| {
-            let adapters = adapters
-                .into_iter()
-                .map(|adapter| {
-                    let name = adapter.provider_name();
-
-                    // Get status index or add new status.
-                    let index = match status
-                        .iter()
-                        .find_position(|(ident, _)| ident.provider.eq(&name))
-                    {
-                        Some((index, _)) => index,
-                        None => {
-                            status.push((
-                                Ident {
-                                    provider: name,
-                                    chain_id: chain_id.clone(),
-                                },
-                                RwLock::new(GenesisCheckStatus::NotChecked),
-                            ));
-                            status.len() - 1
-                        }
-                    };
-                    (index, adapter)
-                })
-                .collect_vec();
-
-            (chain_id, adapters)
+            let adapters = Inner::with_status(&chain_id, adapters, vec![]);
+            (chain_id, Arc::new(adapters))
         }));
 
         Self {
             inner: Arc::new(Inner {
                 logger,
-                adapters,
-                status,
+                adapters: std::sync::RwLock::new(adapters),
                 validator,
             }),
         }
@@ -284,27 +376,99 @@ impl<T: NetIdentifiable + Clone + 'static> ProviderManager<T> {
 
     pub fn len(&self, chain_id: &ChainId) -> usize {
         self.inner
-            .adapters
-            .get(chain_id)
+            .chain_adapters(chain_id)
             .map(|a| a.len())
             .unwrap_or_default()
     }
 
+    /// Replace the providers of `chain_id` with `adapters`. Providers whose name is in
+    /// `unchanged` keep the result of their genesis check, all others have to be validated
+    /// again before `get_all` returns them. Snapshots that callers got earlier keep the
+    /// previous providers.
+    pub fn update(&self, chain_id: &ChainId, adapters: Vec<T>, unchanged: &[ProviderName]) {
+        let mut all = self.inner.adapters.write().unwrap();
+        let previous = all
+            .get(chain_id)
+            .map(|previous| {
+                previous
+                    .iter()
+                    .filter(|(status, _)| unchanged.contains(&status.ident.provider))
+                    .map(|(status, _)| status.cheap_clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let adapters = Inner::with_status(chain_id, adapters, previous);
+        all.insert(chain_id.clone(), Arc::new(adapters));
+    }
+
     pub async fn mark_all_valid(&self) {
-        for (_, status) in self.inner.status.iter() {
-            let mut s = status.write().await;
-            *s = GenesisCheckStatus::Valid;
+        let all: Vec<_> = self
+            .inner
+            .adapters
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect();
+        for adapters in all {
+            for (status, _) in adapters.iter() {
+                let mut s = status.check.write().await;
+                *s = GenesisCheckStatus::Valid;
+            }
         }
     }
 
-    async fn verify(&self, adapters: &Vec<(usize, T)>) -> Result<(), ProviderManagerError> {
+    /// Validate all providers that were checked before again, without making callers wait for
+    /// the checks. Providers that fail are not used anymore, and providers that failed earlier
+    /// but pass now are used again. A check that fails temporarily keeps the previous result
+    pub async fn revalidate(&self) {
+        let all: Vec<_> = self
+            .inner
+            .adapters
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect();
         let mut tasks = vec![];
+        for adapters in all {
+            for (status, adapter) in adapters.iter() {
+                let inner = self.inner.cheap_clone();
+                tasks.push(inner.revalidate_provider(status.cheap_clone(), adapter.clone()));
+            }
+        }
+        crate::futures03::future::join_all(tasks).await;
+    }
 
-        for (index, adapter) in adapters.into_iter() {
+    /// The outcome of the latest validation of each provider of `chain_id`
+    pub async fn validation_status(&self, chain_id: &ChainId) -> Vec<ProviderValidation> {
+        let adapters = match self.inner.chain_adapters(chain_id) {
+            Some(adapters) => adapters,
+            None => return vec![],
+        };
+        let mut out = vec![];
+        for (status, _) in adapters.iter() {
+            let check = status.check.read().await;
+            out.push(ProviderValidation {
+                provider: status.ident.provider.clone(),
+                status: check.as_str(),
+                error: status.error.lock().unwrap().clone(),
+                quarantined: status.is_quarantined(),
+            });
+        }
+        out
+    }
+
+    async fn verify(
+        &self,
+        adapters: &[(Arc<ProviderStatus>, T)],
+    ) -> Result<(), ProviderManagerError> {
+        let mut tasks = vec![];
+
+        for (status, adapter) in adapters.iter() {
             let inner = self.inner.cheap_clone();
             let adapter = adapter.clone();
-            let index = *index;
-            tasks.push(inner.verify_provider(index, adapter));
+            tasks.push(inner.verify_provider(status.cheap_clone(), adapter));
         }
 
         crate::futures03::future::join_all(tasks)
@@ -315,36 +479,82 @@ impl<T: NetIdentifiable + Clone + 'static> ProviderManager<T> {
         Ok(())
     }
 
+    /// Quarantine `provider` of `chain_id`, or release it from quarantine. The last provider of a
+    /// chain that is not quarantined can not be quarantined. Returns whether the provider is
+    /// quarantined now
+    pub fn set_quarantined(
+        &self,
+        chain_id: &ChainId,
+        provider: &ProviderName,
+        quarantined: bool,
+    ) -> bool {
+        let adapters = match self.inner.chain_adapters(chain_id) {
+            Some(adapters) => adapters,
+            None => return false,
+        };
+        let status = match adapters
+            .iter()
+            .find(|(status, _)| &status.ident.provider == provider)
+        {
+            Some((status, _)) => status,
+            None => return false,
+        };
+
+        if quarantined {
+            let others = adapters
+                .iter()
+                .filter(|(other, _)| {
+                    other.ident.provider != status.ident.provider && !other.is_quarantined()
+                })
+                .count();
+            if others == 0 {
+                return status.is_quarantined();
+            }
+        }
+        status.quarantined.store(quarantined, Ordering::SeqCst);
+        quarantined
+    }
+
+    /// All providers of `chain_id`, including quarantined ones and without verification
+    pub fn get_all_including_quarantined(&self, chain_id: &ChainId) -> Providers<T> {
+        self.inner
+            .chain_adapters(chain_id)
+            .map(|adapters| {
+                let selected = (0..adapters.len()).collect();
+                Providers { adapters, selected }
+            })
+            .unwrap_or_default()
+    }
+
     /// get_all_unverified it's an escape hatch for places where checking the adapter status is
     /// undesirable or just can't be done because async can't be used. This function just returns
     /// the stored adapters and doesn't try to perform any verification. It will also return
     /// adapters that failed verification. For the most part this should be fine since ideally
     /// get_all would have been used before. Nevertheless, it is possible that a misconfigured
     /// adapter is returned from this list even after validation.
-    pub fn get_all_unverified(&self, chain_id: &ChainId) -> Vec<&T> {
+    pub fn get_all_unverified(&self, chain_id: &ChainId) -> Providers<T> {
         self.inner
-            .adapters
-            .get(chain_id)
-            .map(|v| v.iter().map(|v| &v.1).collect())
+            .chain_adapters(chain_id)
+            .map(Providers::all)
             .unwrap_or_default()
     }
 
     /// get_all will trigger the verification of the endpoints for the provided chain_id, hence the
     /// async. If this is undesirable, check `get_all_unverified` as an alternatives that does not
     /// cause the validation but also doesn't not guaratee any adapters have been validated.
-    pub async fn get_all(&self, chain_id: &ChainId) -> Result<Vec<&T>, ProviderManagerError> {
+    pub async fn get_all(&self, chain_id: &ChainId) -> Result<Providers<T>, ProviderManagerError> {
         tokio::time::timeout(std::time::Duration::from_secs(5), async move {
-            let adapters = match self.inner.adapters.get(chain_id) {
+            let adapters = match self.inner.chain_adapters(chain_id) {
                 Some(adapters) if !adapters.is_empty() => adapters,
-                _ => return Ok(vec![]),
+                _ => return Ok(Providers::default()),
             };
 
             // Optimistic check
-            if self.inner.is_all_verified(&adapters).await {
-                return Ok(adapters.iter().map(|v| &v.1).collect());
+            if Inner::<T>::is_all_verified(&adapters).await {
+                return Ok(Providers::all(adapters));
             }
 
-            match self.verify(adapters).await {
+            match self.verify(&adapters).await {
                 Ok(_) => {}
                 Err(error) => error!(
                     self.inner.logger,
@@ -353,24 +563,89 @@ impl<T: NetIdentifiable + Clone + 'static> ProviderManager<T> {
                 ),
             }
 
-            self.inner.get_verified_for_chain(&chain_id).await
+            Inner::<T>::get_verified_for_chain(chain_id, adapters).await
         })
         .await
         .map_err(|_| crate::anyhow::anyhow!("timed out, validation took too long"))?
     }
 }
 
+/// The providers of a chain as they were when they were requested from a
+/// `ProviderManager`; later changes to the providers of the chain do not
+/// affect them
+pub struct Providers<T> {
+    adapters: Arc<Vec<(Arc<ProviderStatus>, T)>>,
+    // The positions of the providers in `adapters` that were selected
+    selected: Vec<usize>,
+}
+
+impl<T> Providers<T> {
+    /// All providers in `adapters` that are not quarantined
+    fn all(adapters: Arc<Vec<(Arc<ProviderStatus>, T)>>) -> Self {
+        let selected = adapters
+            .iter()
+            .enumerate()
+            .filter(|(_, (status, _))| !status.is_quarantined())
+            .map(|(index, _)| index)
+            .collect();
+        Self { adapters, selected }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.selected.iter().map(move |i| &self.adapters[*i].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.selected.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+}
+
+impl<T> Default for Providers<T> {
+    fn default() -> Self {
+        Self {
+            adapters: Arc::new(vec![]),
+            selected: vec![],
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Providers<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Status per (ChainId, ProviderName) pair. The RwLock here helps prevent multiple concurrent
+// checks for the same provider, when one provider is being checked, all other uses will wait,
+// this is correct because no provider should be used until they have been validated.
+struct ProviderStatus {
+    ident: Ident,
+    check: RwLock<GenesisCheckStatus>,
+    // The error of the latest validation, if it did not succeed
+    error: std::sync::Mutex<Option<String>>,
+    quarantined: AtomicBool,
+}
+
+impl ProviderStatus {
+    fn is_quarantined(&self) -> bool {
+        self.quarantined.load(Ordering::SeqCst)
+    }
+
+    fn set_error(&self, res: &Result<(), ProviderManagerError>) {
+        *self.error.lock().unwrap() = res.as_ref().err().map(|e| e.to_string());
+    }
+}
+
 struct Inner<T: NetIdentifiable> {
     logger: Logger,
-    // Most operations start by getting the value so we keep track of the index to minimize the
-    // locked surface.
-    adapters: HashMap<ChainId, Vec<(usize, T)>>,
-    // Status per (ChainId, ProviderName) pair. The RwLock here helps prevent multiple concurrent
-    // checks for the same provider, when one provider is being checked, all other uses will wait,
-    // this is correct because no provider should be used until they have been validated.
-    // There shouldn't be many values here so Vec is fine even if less ergonomic, because we track
-    // the index alongside the adapter it should be O(1) after initialization.
-    status: Vec<(Ident, RwLock<GenesisCheckStatus>)>,
+    // The providers of each chain, together with their status. The status is shared by all
+    // adapters of the same provider. Updates replace the list of a chain as a whole so that
+    // callers that hold on to a list see a consistent set of providers.
+    adapters: std::sync::RwLock<HashMap<ChainId, Arc<Vec<(Arc<ProviderStatus>, T)>>>>,
     // Validator used to compare the existing identifier to the one returned by an adapter.
     validator: Arc<dyn IdentValidator + 'static>,
 }
@@ -382,9 +657,50 @@ impl<T: NetIdentifiable + 'static> std::fmt::Debug for Inner<T> {
 }
 
 impl<T: NetIdentifiable + 'static> Inner<T> {
-    async fn is_all_verified(&self, adapters: &Vec<(usize, T)>) -> bool {
-        for (index, _) in adapters.iter() {
-            let status = self.status.get(*index).unwrap().1.read().await;
+    /// Pair `adapters` with their status, using the status from `previous`
+    /// for providers that have one and a new one for all others
+    fn with_status(
+        chain_id: &ChainId,
+        adapters: Vec<T>,
+        mut previous: Vec<Arc<ProviderStatus>>,
+    ) -> Vec<(Arc<ProviderStatus>, T)> {
+        adapters
+            .into_iter()
+            .map(|adapter| {
+                let name = adapter.provider_name();
+
+                // Get the existing status or add a new one.
+                let status = match previous
+                    .iter()
+                    .find(|status| status.ident.provider.eq(&name))
+                {
+                    Some(status) => status.cheap_clone(),
+                    None => {
+                        let status = Arc::new(ProviderStatus {
+                            ident: Ident {
+                                provider: name,
+                                chain_id: chain_id.clone(),
+                            },
+                            check: RwLock::new(GenesisCheckStatus::NotChecked),
+                            error: std::sync::Mutex::new(None),
+                            quarantined: AtomicBool::new(false),
+                        });
+                        previous.push(status.cheap_clone());
+                        status
+                    }
+                };
+                (status, adapter)
+            })
+            .collect_vec()
+    }
+
+    fn chain_adapters(&self, chain_id: &ChainId) -> Option<Arc<Vec<(Arc<ProviderStatus>, T)>>> {
+        self.adapters.read().unwrap().get(chain_id).cloned()
+    }
+
+    async fn is_all_verified(adapters: &[(Arc<ProviderStatus>, T)]) -> bool {
+        for (status, _) in adapters.iter() {
+            let status = status.check.read().await;
             if *status != GenesisCheckStatus::Valid {
                 return false;
             }
@@ -398,18 +714,20 @@ impl<T: NetIdentifiable + 'static> Inner<T> {
     /// so that that caller can handle the different situations, as one is permanent and the other
     /// is retryable.
     async fn get_verified_for_chain(
-        &self,
         chain_id: &ChainId,
-    ) -> Result<Vec<&T>, ProviderManagerError> {
-        let mut out = vec![];
-        let adapters = match self.adapters.get(chain_id) {
-            Some(adapters) if !adapters.is_empty() => adapters,
-            _ => return Ok(vec![]),
-        };
+        adapters: Arc<Vec<(Arc<ProviderStatus>, T)>>,
+    ) -> Result<Providers<T>, ProviderManagerError> {
+        let mut selected = vec![];
+        if adapters.is_empty() {
+            return Ok(Providers::default());
+        }
 
         let mut failed = 0;
-        for (index, adapter) in adapters.iter() {
-            let status = self.status.get(*index).unwrap().1.read().await;
+        for (index, (status, _)) in adapters.iter().enumerate() {
+            if status.is_quarantined() {
+                continue;
+            }
+            let status = status.check.read().await;
             match status.deref() {
                 GenesisCheckStatus::Valid => {}
                 GenesisCheckStatus::Failed => {
@@ -420,10 +738,10 @@ impl<T: NetIdentifiable + 'static> Inner<T> {
                     continue
                 }
             }
-            out.push(adapter);
+            selected.push(index);
         }
 
-        if out.is_empty() {
+        if selected.is_empty() {
             if failed == adapters.len() {
                 return Err(ProviderManagerError::AllProvidersFailed(chain_id.clone()));
             }
@@ -431,14 +749,7 @@ impl<T: NetIdentifiable + 'static> Inner<T> {
             return Err(ProviderManagerError::NoProvidersAvailable(chain_id.clone()));
         }
 
-        Ok(out)
-    }
-
-    async fn get_ident_status(&self, index: usize) -> (Ident, GenesisCheckStatus) {
-        match self.status.get(index) {
-            Some(status) => (status.0.clone(), status.1.read().await.clone()),
-            None => (Ident::default(), GenesisCheckStatus::Failed),
-        }
+        Ok(Providers { adapters, selected })
     }
 
     fn ttl_has_elapsed(checked_at: &DateTime<Utc>) -> bool {
@@ -460,20 +771,79 @@ impl<T: NetIdentifiable + 'static> Inner<T> {
 
     async fn verify_provider(
         self: Arc<Inner<T>>,
-        index: usize,
+        provider: Arc<ProviderStatus>,
         adapter: T,
     ) -> Result<(), ProviderManagerError> {
-        let (ident, status) = self.get_ident_status(index).await;
-        if !Self::should_verify(&status) {
+        if !Self::should_verify(&provider.check.read().await) {
             return Ok(());
         }
 
-        let mut status = self.status.get(index).unwrap().1.write().await;
+        let mut status = provider.check.write().await;
         // double check nothing has changed.
         if !Self::should_verify(&status) {
             return Ok(());
         }
 
+        let (check, res) = self.check_provider(&provider.ident, &adapter).await;
+        *status = check;
+        provider.set_error(&res);
+        res
+    }
+
+    async fn revalidate_provider(self: Arc<Inner<T>>, provider: Arc<ProviderStatus>, adapter: T) {
+        let previous = provider.check.read().await.clone();
+        if previous != GenesisCheckStatus::Valid && previous != GenesisCheckStatus::Failed {
+            // The first check happens when the provider is used
+            return;
+        }
+
+        // Do not hold the lock during the check so that callers can keep using the provider
+        let timeout = ENV_VARS.genesis_validation_timeout;
+        let (check, res) =
+            match tokio::time::timeout(timeout, self.check_provider(&provider.ident, &adapter))
+                .await
+            {
+                Ok(checked) => checked,
+                Err(_) => return,
+            };
+        if let GenesisCheckStatus::TemporaryFailure { .. } | GenesisCheckStatus::NotChecked = check
+        {
+            return;
+        }
+
+        let mut status = provider.check.write().await;
+        if *status != previous {
+            // The provider was checked in the meantime
+            return;
+        }
+        match (&previous, &check, &res) {
+            (GenesisCheckStatus::Valid, GenesisCheckStatus::Failed, Err(e)) => error!(
+                self.logger,
+                "provider failed revalidation and will not be used anymore: {}", e
+            ),
+            (GenesisCheckStatus::Failed, GenesisCheckStatus::Valid, _) => info!(
+                self.logger,
+                "provider {} on chain {} passed revalidation and will be used again",
+                provider.ident.provider,
+                provider.ident.chain_id
+            ),
+            _ => {}
+        }
+        *status = check;
+        provider.set_error(&res);
+    }
+
+    /// Check the chain identifier and the chain id of `adapter`, returning the status the
+    /// provider should have and the error if the check did not succeed
+    async fn check_provider(
+        &self,
+        ident: &Ident,
+        adapter: &T,
+    ) -> (GenesisCheckStatus, Result<(), ProviderManagerError>) {
+        let temporary_failure = || GenesisCheckStatus::TemporaryFailure {
+            checked_at: Utc::now(),
+        };
+
         let chain_ident = match adapter.net_identifiers().await {
             Ok(ident) => ident,
             Err(err) => {
@@ -482,24 +852,40 @@ impl<T: NetIdentifiable + 'static> Inner<T> {
                     "failed to get net identifiers: {}",
                     err.to_string()
                 );
-                *status = GenesisCheckStatus::TemporaryFailure {
-                    checked_at: Utc::now(),
-                };
-
-                return Err(err.into());
+                return (temporary_failure(), Err(err.into()));
             }
         };
 
-        match self.validator.check_ident(&ident.chain_id, &chain_ident) {
-            Ok(_) => {
-                *status = GenesisCheckStatus::Valid;
+        if let Some(expected) = adapter.expected_chain_id() {
+            match adapter.chain_id().await {
+                Ok(actual) if actual == expected => {}
+                Ok(actual) => {
+                    return (
+                        GenesisCheckStatus::Failed,
+                        Err(ProviderManagerError::ChainIdMismatch {
+                            chain_id: ident.chain_id.clone(),
+                            provider: ident.provider.clone(),
+                            expected,
+                            actual,
+                        }),
+                    );
+                }
+                Err(err) => {
+                    error!(&self.logger, "failed to get chain id: {}", err.to_string());
+                    return (temporary_failure(), Err(err.into()));
+                }
             }
+        }
+
+        match self.validator.check_ident(&ident.chain_id, &chain_ident) {
+            Ok(_) => (GenesisCheckStatus::Valid, Ok(())),
             Err(err) => match err {
                 IdentValidatorError::UnsetIdent => {
-                    self.validator
-                        .update_ident(&ident.chain_id, &chain_ident)
-                        .map_err(ProviderManagerError::from)?;
-                    *status = GenesisCheckStatus::Valid;
+                    match self.validator.update_ident(&ident.chain_id, &chain_ident) {
+                        Ok(()) => (GenesisCheckStatus::Valid, Ok(())),
+                        // Check again the next time the provider is used
+                        Err(e) => (GenesisCheckStatus::NotChecked, Err(e.into())),
+                    }
                 }
                 IdentValidatorError::ChangedNetVersion {
                     chain_id,
@@ -511,16 +897,16 @@ impl<T: NetIdentifiable + 'static> Inner<T> {
                             chain_id,
                             chain_net_version,
                             );
-                    *status = GenesisCheckStatus::Valid;
+                    (GenesisCheckStatus::Valid, Ok(()))
                 }
                 IdentValidatorError::ChangedNetVersion {
                     store_net_version,
                     chain_net_version,
                     ..
-                } => {
-                    *status = GenesisCheckStatus::Failed;
-                    return Err(ProviderManagerError::ProviderFailedValidation {
-                        provider: ident.provider,
+                } => (
+                    GenesisCheckStatus::Failed,
+                    Err(ProviderManagerError::ProviderFailedValidation {
+                        provider: ident.provider.clone(),
                         expected: ChainIdentifier {
                             net_version: store_net_version,
                             genesis_block_hash: chain_ident.genesis_block_hash.clone(),
@@ -530,16 +916,16 @@ impl<T: NetIdentifiable + 'static> Inner<T> {
                             genesis_block_hash: chain_ident.genesis_block_hash,
                         },
                         chain_id: ident.chain_id.clone(),
-                    });
-                }
+                    }),
+                ),
                 IdentValidatorError::ChangedHash {
                     store_hash,
                     chain_hash,
                     ..
-                } => {
-                    *status = GenesisCheckStatus::Failed;
-                    return Err(ProviderManagerError::ProviderFailedValidation {
-                        provider: ident.provider,
+                } => (
+                    GenesisCheckStatus::Failed,
+                    Err(ProviderManagerError::ProviderFailedValidation {
+                        provider: ident.provider.clone(),
                         expected: ChainIdentifier {
                             net_version: chain_ident.net_version.clone(),
                             genesis_block_hash: store_hash,
@@ -549,22 +935,17 @@ impl<T: NetIdentifiable + 'static> Inner<T> {
                             genesis_block_hash: chain_hash,
                         },
                         chain_id: ident.chain_id.clone(),
-                    });
-                }
+                    }),
+                ),
                 e @ IdentValidatorError::UnavailableStore(_)
-                | e @ IdentValidatorError::UnknownError(_) => {
-                    *status = GenesisCheckStatus::TemporaryFailure {
-                        checked_at: Utc::now(),
-                    };
-
-                    return Err(ProviderManagerError::Unknown(crate::anyhow::anyhow!(
+                | e @ IdentValidatorError::UnknownError(_) => (
+                    temporary_failure(),
+                    Err(ProviderManagerError::Unknown(crate::anyhow::anyhow!(
                         e.to_string()
-                    )));
-                }
+                    ))),
+                ),
             },
         }
-
-        Ok(())
     }
 }
 
@@ -576,6 +957,17 @@ enum GenesisCheckStatus {
     Failed,
 }
 
+impl GenesisCheckStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            GenesisCheckStatus::NotChecked => "not checked",
+            GenesisCheckStatus::TemporaryFailure { .. } => "temporary failure",
+            GenesisCheckStatus::Valid => "valid",
+            GenesisCheckStatus::Failed => "failed",
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::{
@@ -601,8 +993,8 @@ mod test {
     use crate::{blockchain::ChainIdentifier, components::adapter::ProviderManagerError};
 
     use super::{
-        IdentValidator, IdentValidatorError, NetIdentifiable, ProviderManager, ProviderName,
-        VALIDATION_ATTEMPT_TTL,
+        GenesisOverrideValidator, IdentValidator, IdentValidatorError, NetIdentifiable,
+        ProviderManager, ProviderName, ProviderValidation, Providers, VALIDATION_ATTEMPT_TTL,
     };
 
     const TEST_CHAIN_ID: &str = "valid";
@@ -788,29 +1180,33 @@ mod test {
 
             let manager = ProviderManager::new(logger, adapters.clone().into_iter(), validator);
 
-            for (_, adapters) in adapters.iter() {
+            for (adapters_chain, adapters) in adapters.iter() {
+                let statuses = manager.inner.chain_adapters(adapters_chain).unwrap();
                 for adapter in adapters.iter() {
                     let provider = adapter.provider.clone();
-                    let slot = manager
-                        .inner
-                        .status
+                    let slot = statuses
                         .iter()
-                        .find(|(ident, _)| ident.provider.eq(&provider))
+                        .find(|(status, _)| status.ident.provider.eq(&provider))
                         .expect(&format!(
                             "case: {} - there should be a status for provider \"{}\"",
                             name, provider
                         ));
-                    let mut s = slot.1.write().await;
+                    let mut s = slot.0.check.write().await;
                     *s = adapter.status.clone();
                 }
             }
 
-            let result = manager.get_all(&chain_id).await;
+            let result = manager
+                .get_all(&chain_id)
+                .await
+                .map(|result| result.iter().cloned().collect::<Vec<_>>());
             match (expected, result) {
                 (Ok(expected), Ok(result)) => assert_eq!(
-                    expected, result,
+                    expected.into_iter().cloned().collect::<Vec<_>>(),
+                    result,
                     "case {} failed. Result: {:?}",
-                    name, result
+                    name,
+                    result
                 ),
                 (Err(expected), Err(result)) => assert_eq!(
                     expected.to_string(),
@@ -890,9 +1286,318 @@ mod test {
             validator.clone(),
         );
 
-        let mut result = manager.get_all(&chain_id).await.unwrap();
+        let result = manager.get_all(&chain_id).await.unwrap();
         assert_eq!(result.len(), 1);
-        assert_eq!(&adapter, result.pop().unwrap());
+        assert_eq!(Some(&adapter), result.iter().next());
         assert_eq!(validator.called.load(Ordering::SeqCst), true);
     }
+
+    #[tokio::test]
+    async fn test_provider_manager_update() {
+        async fn statuses(
+            manager: &ProviderManager<MockAdapter>,
+            chain_id: &ChainId,
+        ) -> Vec<(String, GenesisCheckStatus)> {
+            let mut out = vec![];
+            for (status, adapter) in manager.inner.chain_adapters(chain_id).unwrap().iter() {
+                out.push((
+                    adapter.provider.to_string(),
+                    status.check.read().await.clone(),
+                ));
+            }
+            out
+        }
+
+        let adapter = |name: &str| MockAdapter {
+            provider: name.into(),
+            status: GenesisCheckStatus::NotChecked,
+        };
+        let chain_id: ChainId = TEST_CHAIN_ID.into();
+        let manager = ProviderManager::new(
+            Logger::root(Discard, o!()),
+            vec![(chain_id.clone(), vec![adapter("a"), adapter("b")])].into_iter(),
+            Arc::new(NoopIdentValidator),
+        );
+        manager.mark_all_valid().await;
+        let before = manager.get_all(&chain_id).await.unwrap();
+
+        // Remove `b` and add `c`; `a` keeps the result of its check
+        manager.update(&chain_id, vec![adapter("a"), adapter("c")], &["a".into()]);
+        assert_eq!(
+            vec!["a".to_string(), "b".to_string()],
+            before
+                .iter()
+                .map(|adapter| adapter.provider.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                ("a".to_string(), GenesisCheckStatus::Valid),
+                ("c".to_string(), GenesisCheckStatus::NotChecked)
+            ],
+            statuses(&manager, &chain_id).await
+        );
+
+        // A provider that changed has to be checked again
+        manager.update(&chain_id, vec![adapter("a")], &[]);
+        assert_eq!(
+            vec![("a".to_string(), GenesisCheckStatus::NotChecked)],
+            statuses(&manager, &chain_id).await
+        );
+        let after = manager.get_all(&chain_id).await.unwrap();
+        assert_eq!(Some(&adapter("a")), after.iter().next());
+        assert_eq!(1, manager.len(&chain_id));
+    }
+
+    #[tokio::test]
+    async fn test_provider_manager_quarantine() {
+        fn names(providers: &Providers<MockAdapter>) -> Vec<String> {
+            providers
+                .iter()
+                .map(|adapter| adapter.provider.to_string())
+                .collect()
+        }
+
+        let adapter = |name: &str| MockAdapter {
+            provider: name.into(),
+            status: GenesisCheckStatus::NotChecked,
+        };
+        let chain_id: ChainId = TEST_CHAIN_ID.into();
+        let manager = ProviderManager::new(
+            Logger::root(Discard, o!()),
+            vec![(chain_id.clone(), vec![adapter("a"), adapter("b")])].into_iter(),
+            Arc::new(NoopIdentValidator),
+        );
+        manager.mark_all_valid().await;
+
+        assert!(manager.set_quarantined(&chain_id, &"a".into(), true));
+        assert_eq!(vec!["b"], names(&manager.get_all(&chain_id).await.unwrap()));
+        assert_eq!(vec!["b"], names(&manager.get_all_unverified(&chain_id)));
+        assert_eq!(
+            vec!["a", "b"],
+            names(&manager.get_all_including_quarantined(&chain_id))
+        );
+
+        // The last provider that is not quarantined stays available
+        assert!(!manager.set_quarantined(&chain_id, &"b".into(), true));
+        assert_eq!(vec!["b"], names(&manager.get_all(&chain_id).await.unwrap()));
+
+        // Quarantine survives configuration updates that keep the provider
+        manager.update(
+            &chain_id,
+            vec![adapter("a"), adapter("b")],
+            &["a".into(), "b".into()],
+        );
+        assert_eq!(vec!["b"], names(&manager.get_all(&chain_id).await.unwrap()));
+
+        assert!(!manager.set_quarantined(&chain_id, &"a".into(), false));
+        assert_eq!(
+            vec!["a", "b"],
+            names(&manager.get_all(&chain_id).await.unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_provider_manager_revalidate() {
+        struct SwitchValidator {
+            changed: AtomicBool,
+        }
+
+        impl IdentValidator for SwitchValidator {
+            fn check_ident(
+                &self,
+                chain_id: &ChainId,
+                ident: &ChainIdentifier,
+            ) -> Result<(), IdentValidatorError> {
+                if !self.changed.load(Ordering::SeqCst) {
+                    return Ok(());
+                }
+                Err(IdentValidatorError::ChangedHash {
+                    chain_id: chain_id.clone(),
+                    store_hash: BlockHash::from(H256::repeat_byte(2)),
+                    chain_hash: ident.genesis_block_hash.clone(),
+                })
+            }
+
+            fn update_ident(
+                &self,
+                _chain_id: &ChainId,
+                _ident: &ChainIdentifier,
+            ) -> Result<(), anyhow::Error> {
+                unreachable!("unexpected call to update_ident");
+            }
+        }
+
+        let adapter = MockAdapter {
+            provider: "a".into(),
+            status: GenesisCheckStatus::NotChecked,
+        };
+        let chain_id: ChainId = TEST_CHAIN_ID.into();
+        let validator = Arc::new(SwitchValidator {
+            changed: AtomicBool::new(false),
+        });
+        let manager = ProviderManager::new(
+            Logger::root(Discard, o!()),
+            vec![(chain_id.clone(), vec![adapter])].into_iter(),
+            validator.clone(),
+        );
+        assert_eq!(1, manager.get_all(&chain_id).await.unwrap().len());
+
+        // The provider starts serving a different chain
+        validator.changed.store(true, Ordering::SeqCst);
+        manager.revalidate().await;
+        let status = manager.validation_status(&chain_id).await;
+        assert_eq!("failed", status[0].status);
+        assert!(status[0].error.is_some());
+        assert!(matches!(
+            manager.get_all(&chain_id).await,
+            Err(ProviderManagerError::AllProvidersFailed(_))
+        ));
+
+        // And is fixed again
+        validator.changed.store(false, Ordering::SeqCst);
+        manager.revalidate().await;
+        assert_eq!(
+            vec![ProviderValidation {
+                provider: "a".into(),
+                status: "valid",
+                error: None,
+                quarantined: false,
+            }],
+            manager.validation_status(&chain_id).await
+        );
+        assert_eq!(1, manager.get_all(&chain_id).await.unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn test_provider_manager_chain_id() {
+        #[derive(Clone, Debug)]
+        struct ChainIdAdapter {
+            provider: Word,
+            expected: Option<u64>,
+        }
+
+        #[async_trait]
+        impl NetIdentifiable for ChainIdAdapter {
+            async fn net_identifiers(&self) -> Result<ChainIdentifier, anyhow::Error> {
+                Ok(NEW_CHAIN_IDENT.clone())
+            }
+            fn provider_name(&self) -> ProviderName {
+                self.provider.clone()
+            }
+            fn expected_chain_id(&self) -> Option<u64> {
+                self.expected
+            }
+            async fn chain_id(&self) -> Result<u64, anyhow::Error> {
+                Ok(1)
+            }
+        }
+
+        let adapter = |name: &str, expected| ChainIdAdapter {
+            provider: name.into(),
+            expected,
+        };
+        let chain_id: ChainId = TEST_CHAIN_ID.into();
+        let manager = ProviderManager::new(
+            Logger::root(Discard, o!()),
+            vec![(
+                chain_id.clone(),
+                vec![
+                    adapter("unset", None),
+                    adapter("right", Some(1)),
+                    adapter("wrong", Some(10)),
+                ],
+            )]
+            .into_iter(),
+            Arc::new(NoopIdentValidator),
+        );
+
+        let providers = manager.get_all(&chain_id).await.unwrap();
+        assert_eq!(
+            vec!["unset", "right"],
+            providers
+                .iter()
+                .map(|adapter| adapter.provider.to_string())
+                .collect::<Vec<_>>()
+        );
+        let status = manager.validation_status(&chain_id).await;
+        assert_eq!("failed", status[2].status);
+        assert_eq!(
+            Some(
+                ProviderManagerError::ChainIdMismatch {
+                    chain_id: chain_id.clone(),
+                    provider: "wrong".into(),
+                    expected: 10,
+                    actual: 1,
+                }
+                .to_string()
+            ),
+            status[2].error
+        );
+    }
+
+    #[test]
+    fn test_genesis_override_validator() {
+        struct StoreValidator {
+            ident: std::sync::Mutex<ChainIdentifier>,
+        }
+
+        impl IdentValidator for StoreValidator {
+            fn check_ident(
+                &self,
+                chain_id: &ChainId,
+                ident: &ChainIdentifier,
+            ) -> Result<(), IdentValidatorError> {
+                let store_ident = self.ident.lock().unwrap();
+                if store_ident.genesis_block_hash != ident.genesis_block_hash {
+                    return Err(IdentValidatorError::ChangedHash {
+                        chain_id: chain_id.clone(),
+                        store_hash: store_ident.genesis_block_hash.clone(),
+                        chain_hash: ident.genesis_block_hash.clone(),
+                    });
+                }
+                Ok(())
+            }
+
+            fn update_ident(
+                &self,
+                _chain_id: &ChainId,
+                ident: &ChainIdentifier,
+            ) -> Result<(), anyhow::Error> {
+                *self.ident.lock().unwrap() = ident.clone();
+                Ok(())
+            }
+        }
+
+        let ident = |byte| ChainIdentifier {
+            net_version: "1".to_string(),
+            genesis_block_hash: BlockHash::from(H256::repeat_byte(byte)),
+        };
+        let chain_id: ChainId = TEST_CHAIN_ID.into();
+        let other: ChainId = "other".into();
+        let store = Arc::new(StoreValidator {
+            ident: std::sync::Mutex::new(ident(1)),
+        });
+        let validator = GenesisOverrideValidator::new(
+            Logger::root(Discard, o!()),
+            store.clone(),
+            [(chain_id.clone(), ident(2).genesis_block_hash)]
+                .into_iter()
+                .collect(),
+        );
+
+        // Providers of the old chain fail, providers of the new chain pass and update the store
+        assert!(matches!(
+            validator.check_ident(&chain_id, &ident(1)),
+            Err(IdentValidatorError::ChangedHash { .. })
+        ));
+        assert_eq!(Ok(()), validator.check_ident(&chain_id, &ident(2)));
+        assert_eq!(ident(2), *store.ident.lock().unwrap());
+
+        // Chains without an override are checked against the store
+        assert!(matches!(
+            validator.check_ident(&other, &ident(3)),
+            Err(IdentValidatorError::ChangedHash { .. })
+        ));
+    }
 }
@@ -2,5 +2,6 @@ mod types;
 
 pub use self::types::{
     evaluate_transaction_status, EthereumBlock, EthereumBlockWithCalls, EthereumCall,
-    LightEthereumBlock, LightEthereumBlockExt,
+    EthereumTransactionReceipt, L2ReceiptFields, L2TransactionFields, LightEthereumBlock,
+    LightEthereumBlockExt, PostMergeFields, Withdrawal,
 };
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
 use web3::types::{
     Action, Address, Block, Bytes, Log, Res, Trace, Transaction, TransactionReceipt, H256, U256,
     U64,
@@ -10,7 +15,194 @@ use crate::{
     prelude::BlockNumber,
 };
 
-pub type LightEthereumBlock = Block<Transaction>;
+/// A block with its transactions. It derefs to the `web3` block, which
+/// lacks the fields that forks since the merge added
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct LightEthereumBlock {
+    #[serde(flatten)]
+    pub inner: Block<Transaction>,
+    #[serde(flatten)]
+    pub post_merge: PostMergeFields,
+    /// The fields that L2s add to transactions, by transaction hash, for
+    /// the transactions that have any of them
+    #[serde(
+        default,
+        rename = "l2Transactions",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub l2_transactions: BTreeMap<H256, L2TransactionFields>,
+}
+
+impl LightEthereumBlock {
+    /// Decode the result of `eth_getBlockByHash` or `eth_getBlockByNumber`
+    /// with full transactions. Unlike deserializing a `LightEthereumBlock`
+    /// directly, this keeps the fields that L2s add to transactions
+    pub fn from_rpc(block: serde_json::Value) -> Result<Option<Self>, serde_json::Error> {
+        #[derive(Deserialize)]
+        struct RawTransaction {
+            hash: H256,
+            #[serde(flatten)]
+            l2: L2TransactionFields,
+        }
+
+        #[derive(Deserialize)]
+        struct RawBlock {
+            #[serde(default)]
+            transactions: Vec<RawTransaction>,
+        }
+
+        if block.is_null() {
+            return Ok(None);
+        }
+        let l2_transactions = RawBlock::deserialize(&block)?
+            .transactions
+            .into_iter()
+            .filter(|tx| !tx.l2.is_empty())
+            .map(|tx| (tx.hash, tx.l2))
+            .collect();
+        let mut block: LightEthereumBlock = serde_json::from_value(block)?;
+        block.l2_transactions = l2_transactions;
+        Ok(Some(block))
+    }
+}
+
+impl Deref for LightEthereumBlock {
+    type Target = Block<Transaction>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl DerefMut for LightEthereumBlock {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl From<Block<Transaction>> for LightEthereumBlock {
+    fn from(inner: Block<Transaction>) -> Self {
+        LightEthereumBlock {
+            inner,
+            post_merge: PostMergeFields::default(),
+            l2_transactions: BTreeMap::new(),
+        }
+    }
+}
+
+impl<'a> From<&'a LightEthereumBlock> for BlockPtr {
+    fn from(b: &'a LightEthereumBlock) -> BlockPtr {
+        BlockPtr::from(&b.inner)
+    }
+}
+
+/// The fields that Shanghai (withdrawals) and Cancun (blobs and the beacon
+/// block root) added to blocks. They are `None` for older blocks and for
+/// blocks that we stored before we kept these fields
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PostMergeFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<H256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<U64>,
+}
+
+/// A withdrawal from the beacon chain to the execution layer (EIP-4895)
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    pub index: U64,
+    pub validator_index: U64,
+    pub address: Address,
+    /// In Gwei
+    pub amount: U64,
+}
+
+/// The fields of the transaction types that only exist on L2s, like the
+/// deposits of Optimism and the retryables and internal transactions of
+/// Arbitrum
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct L2TransactionFields {
+    /// The hash that identifies the L1 origin of an Optimism deposit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<H256>,
+    /// The ETH that an Optimism deposit mints on L2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<U256>,
+    /// Whether an Optimism deposit is a system transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_system_tx: Option<bool>,
+    /// The id of the L1 message of an Arbitrum deposit or retryable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<H256>,
+}
+
+impl L2TransactionFields {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// A transaction receipt. It derefs to the `web3` receipt, which lacks the
+/// fields that L2s add to receipts
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct EthereumTransactionReceipt {
+    #[serde(flatten)]
+    pub inner: TransactionReceipt,
+    #[serde(flatten)]
+    pub l2: L2ReceiptFields,
+}
+
+impl Deref for EthereumTransactionReceipt {
+    type Target = TransactionReceipt;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl From<TransactionReceipt> for EthereumTransactionReceipt {
+    fn from(inner: TransactionReceipt) -> Self {
+        EthereumTransactionReceipt {
+            inner,
+            l2: L2ReceiptFields::default(),
+        }
+    }
+}
+
+/// The fees that L2s charge for posting a transaction to L1. Optimism
+/// reports the L1 gas and its price, Arbitrum the L2 gas that pays for it
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct L2ReceiptFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_gas_price: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_gas_used: Option<U256>,
+    /// A decimal like `0.684` before the Ecotone upgrade of Optimism
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee_scalar: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_base_fee_scalar: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_blob_base_fee: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_blob_base_fee_scalar: Option<U256>,
+    /// Arbitrum only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used_for_l1: Option<U256>,
+    /// Arbitrum only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_block_number: Option<U64>,
+}
 
 pub trait LightEthereumBlockExt {
     fn number(&self) -> BlockNumber;
@@ -107,7 +299,12 @@ pub fn evaluate_transaction_status(receipt_status: Option<U64>) -> bool {
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
 pub struct EthereumBlock {
     pub block: Arc<LightEthereumBlock>,
-    pub transaction_receipts: Vec<Arc<TransactionReceipt>>,
+    pub transaction_receipts: Vec<Arc<EthereumTransactionReceipt>>,
+    /// Whether the block was stored without fetching its receipts. A slim
+    /// block only has receipts for the transactions that emitted logs, and
+    /// these receipts only have the logs and the status
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub slim: bool,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
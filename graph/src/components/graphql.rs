@@ -1,7 +1,10 @@
 use crate::data::query::QueryResults;
-use crate::data::query::{Query, QueryTarget};
+use crate::data::query::{
+    EntityHistory, EntityHistoryQuery, IncrementalResults, Query, QueryExecutionError, QueryTarget,
+    SqlQueryResult,
+};
 use crate::data::subscription::{Subscription, SubscriptionError, SubscriptionResult};
-use crate::prelude::DeploymentHash;
+use crate::prelude::{BlockNumber, DeploymentHash};
 
 use async_trait::async_trait;
 use futures01::Future;
@@ -33,6 +36,42 @@ pub trait GraphQlRunner: Send + Sync + 'static {
         max_skip: Option<u32>,
     ) -> QueryResults;
 
+    /// Runs a GraphQL query whose top-level fields may use `@defer` and
+    /// `@stream` and returns the parts of its result as they are computed
+    async fn run_incremental_query(
+        self: Arc<Self>,
+        query: Query,
+        target: QueryTarget,
+    ) -> IncrementalResults;
+
+    /// Runs a federated GraphQL query. Each top-level field of the query
+    /// must be the name of one of the `namespaces`, and its selection set
+    /// is run as a query against the corresponding target. All targets
+    /// that index the same network are queried at the same block
+    async fn run_federated_query(
+        self: Arc<Self>,
+        query: Query,
+        namespaces: Vec<(String, QueryTarget)>,
+    ) -> QueryResults;
+
+    /// Runs a read-only SQL query against the entity tables of `target`
+    /// at `block`, or at the latest block the deployment has processed if
+    /// `block` is `None`
+    async fn run_sql_query(
+        self: Arc<Self>,
+        sql: String,
+        block: Option<BlockNumber>,
+        target: QueryTarget,
+    ) -> Result<SqlQueryResult, QueryExecutionError>;
+
+    /// Returns the versions of the entities of `target` that were written
+    /// or removed in the block range of `query`
+    async fn run_history_query(
+        self: Arc<Self>,
+        query: EntityHistoryQuery,
+        target: QueryTarget,
+    ) -> Result<EntityHistory, QueryExecutionError>;
+
     /// Runs a GraphQL subscription and returns a stream of results.
     async fn run_subscription(
         self: Arc<Self>,
@@ -2,9 +2,9 @@ use std::pin::Pin;
 
 use async_trait::async_trait;
 use futures03::prelude::Stream;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde_json::Value;
-use slog::{debug, Logger};
+use slog::{debug, warn, Logger};
 use thiserror::Error;
 
 use crate::data_source::offchain::Base64;
@@ -23,9 +23,11 @@ pub struct JsonStreamValue {
 pub type JsonValueStream =
     Pin<Box<dyn Stream<Item = Result<JsonStreamValue, Error>> + Send + 'static>>;
 
+/// Fetches files from Arweave gateways. Each request goes to the gateways
+/// in order until one of them has the file
 #[derive(Debug)]
 pub struct ArweaveClient {
-    base_url: url::Url,
+    base_urls: Vec<url::Url>,
     client: Client,
     logger: Logger,
 }
@@ -41,7 +43,7 @@ impl Default for ArweaveClient {
         use slog::o;
 
         Self {
-            base_url: "https://arweave.net".parse().unwrap(),
+            base_urls: vec!["https://arweave.net".parse().unwrap()],
             client: Client::default(),
             logger: Logger::root(slog::Discard, o!()),
         }
@@ -49,27 +51,26 @@ impl Default for ArweaveClient {
 }
 
 impl ArweaveClient {
-    pub fn new(logger: Logger, base_url: url::Url) -> Self {
+    pub fn new(logger: Logger, base_urls: Vec<url::Url>) -> Self {
+        assert!(
+            !base_urls.is_empty(),
+            "at least one Arweave gateway is required"
+        );
+
         Self {
-            base_url,
+            base_urls,
             logger,
             client: Client::default(),
         }
     }
-}
-
-#[async_trait]
-impl ArweaveResolver for ArweaveClient {
-    async fn get(&self, file: &Base64) -> Result<Vec<u8>, ArweaveClientError> {
-        self.get_with_limit(file, &FileSizeLimit::Unlimited).await
-    }
 
-    async fn get_with_limit(
+    async fn get_from(
         &self,
+        base_url: &url::Url,
         file: &Base64,
         limit: &FileSizeLimit,
     ) -> Result<Vec<u8>, ArweaveClientError> {
-        let url = self.base_url.join(file.as_str())?;
+        let url = base_url.join(file.as_str())?;
         let rsp = self
             .client
             .get(url)
@@ -77,6 +78,14 @@ impl ArweaveResolver for ArweaveClient {
             .await
             .map_err(ArweaveClientError::from)?;
 
+        // Only the contents of successful responses are passed to
+        // handlers; anything else would not be deterministic
+        match rsp.status() {
+            status if status.is_success() => {}
+            StatusCode::NOT_FOUND => return Err(ArweaveClientError::NotFound),
+            status => return Err(ArweaveClientError::Status(status)),
+        }
+
         match (&limit, rsp.content_length()) {
             (_, None) => return Err(ArweaveClientError::UnableToCheckFileSize),
             (FileSizeLimit::MaxBytes(max), Some(cl)) if cl > *max => {
@@ -94,6 +103,44 @@ impl ArweaveResolver for ArweaveClient {
     }
 }
 
+#[async_trait]
+impl ArweaveResolver for ArweaveClient {
+    async fn get(&self, file: &Base64) -> Result<Vec<u8>, ArweaveClientError> {
+        self.get_with_limit(file, &FileSizeLimit::Unlimited).await
+    }
+
+    async fn get_with_limit(
+        &self,
+        file: &Base64,
+        limit: &FileSizeLimit,
+    ) -> Result<Vec<u8>, ArweaveClientError> {
+        let mut last_err = None;
+
+        for base_url in &self.base_urls {
+            match self.get_from(base_url, file, limit).await {
+                Ok(bytes) => return Ok(bytes),
+                // Every gateway would say the same about an invalid or
+                // too large file
+                Err(
+                    err @ (ArweaveClientError::InvalidUrl(_)
+                    | ArweaveClientError::FileTooLarge { .. }),
+                ) => return Err(err),
+                Err(err) => {
+                    if self.base_urls.len() > 1 && !err.is_not_found() {
+                        warn!(self.logger, "Failed to get arweave file from gateway";
+                            "gateway" => base_url.as_str(),
+                            "file" => file.as_str(),
+                            "error" => err.to_string());
+                    }
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        Err(last_err.unwrap())
+    }
+}
+
 #[async_trait]
 pub trait ArweaveResolver: Send + Sync + 'static + Debug {
     async fn get(&self, file: &Base64) -> Result<Vec<u8>, ArweaveClientError>;
@@ -112,10 +159,22 @@ pub enum ArweaveClientError {
     UnableToCheckFileSize,
     #[error("Arweave file is too large. The limit is {max} and file content was {got} bytes")]
     FileTooLarge { got: u64, max: u64 },
+    #[error("Arweave file not found")]
+    NotFound,
+    #[error("Arweave gateway responded with status {0}")]
+    Status(StatusCode),
     #[error("Unknown error")]
     Unknown(#[from] reqwest::Error),
 }
 
+impl ArweaveClientError {
+    /// Whether the gateways do not have the file, possibly because it was
+    /// not mined yet
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ArweaveClientError::NotFound)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use serde_derive::Deserialize;
@@ -146,4 +205,52 @@ mod test {
             }
         );
     }
+
+    #[tokio::test]
+    async fn get_fails_over_to_the_next_gateway() {
+        use wiremock::{matchers as m, Mock, MockServer, ResponseTemplate};
+
+        const ID: &str = "8APeQ5lW0-csTcBaGdPBDLAL2ci2AT9pTn2tppGPU_8";
+
+        let server_1 = MockServer::start().await;
+        let server_2 = MockServer::start().await;
+        let server_3 = MockServer::start().await;
+        for (server, status) in [(&server_1, 502), (&server_2, 404)] {
+            Mock::given(m::method("GET"))
+                .and(m::path(format!("/{ID}")))
+                .respond_with(ResponseTemplate::new(status).set_body_bytes(b"error page"))
+                .expect(1)
+                .mount(server)
+                .await;
+        }
+        Mock::given(m::method("GET"))
+            .and(m::path(format!("/{ID}")))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"file"))
+            .expect(1)
+            .mount(&server_3)
+            .await;
+
+        let gateways = |servers: &[&MockServer]| {
+            servers
+                .iter()
+                .map(|server| server.uri().parse().unwrap())
+                .collect::<Vec<_>>()
+        };
+        let logger = crate::log::discard();
+
+        let client =
+            ArweaveClient::new(logger.clone(), gateways(&[&server_1, &server_2, &server_3]));
+        assert_eq!(
+            b"file".to_vec(),
+            client.get(&Base64::from(ID)).await.unwrap()
+        );
+
+        // The error page of a gateway is never returned as the file
+        let client = ArweaveClient::new(logger, gateways(&[&server_2]));
+        assert!(client
+            .get(&Base64::from(ID))
+            .await
+            .unwrap_err()
+            .is_not_found());
+    }
 }
@@ -0,0 +1,175 @@
+//! Splitting CARv1 files into their IPLD blocks while they are downloaded.
+//! See https://ipld.io/specs/transport/car/carv1/ for the format
+use anyhow::{anyhow, Error};
+use bytes::{Buf, BytesMut};
+use cid::Cid;
+
+/// The largest header or block that we accept. IPFS itself does not
+/// exchange blocks larger than a few MiB, and the limit keeps the memory
+/// that reading a malicious file needs bounded
+const MAX_SECTION_LEN: u64 = 8 * 1024 * 1024;
+
+/// Varints in CAR files are unsigned LEB128 and at most 9 bytes long
+const MAX_VARINT_LEN: usize = 9;
+
+/// The header of a CARv2 file, which starts with a fixed pragma that
+/// looks like a CARv1 header with `version: 2`
+const CARV2_PRAGMA: [u8; 11] = [
+    0x0a, 0xa1, 0x67, 0x76, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, 0x02,
+];
+
+/// Decode the varint at the start of `buf` into its value and its length
+/// in bytes. Returns `None` if `buf` does not contain the whole varint yet
+fn read_varint(buf: &[u8]) -> Result<Option<(u64, usize)>, Error> {
+    let mut value: u64 = 0;
+    for (i, byte) in buf.iter().take(MAX_VARINT_LEN).enumerate() {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(Some((value, i + 1)));
+        }
+    }
+    if buf.len() >= MAX_VARINT_LEN {
+        return Err(anyhow!("invalid CAR file: varint is too long"));
+    }
+    Ok(None)
+}
+
+/// Reads the blocks of a CARv1 file from the chunks in which it arrives,
+/// only ever keeping the current block in memory
+#[derive(Default)]
+pub(crate) struct CarReader {
+    buf: BytesMut,
+    header_read: bool,
+}
+
+impl CarReader {
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Split the next section off the buffer, or return `None` if it has
+    /// not been received completely yet
+    fn next_section(&mut self) -> Result<Option<BytesMut>, Error> {
+        if !self.header_read && self.buf.starts_with(&CARV2_PRAGMA) {
+            return Err(anyhow!("CARv2 files are not supported, only CARv1"));
+        }
+        let (len, varint_len) = match read_varint(&self.buf)? {
+            Some(varint) => varint,
+            None => return Ok(None),
+        };
+        if len > MAX_SECTION_LEN {
+            return Err(anyhow!(
+                "invalid CAR file: section of {} bytes is larger than the maximum of {} bytes",
+                len,
+                MAX_SECTION_LEN
+            ));
+        }
+        let len = len as usize;
+        if self.buf.len() < varint_len + len {
+            return Ok(None);
+        }
+        self.buf.advance(varint_len);
+        Ok(Some(self.buf.split_to(len)))
+    }
+
+    /// Return the CID and data of the next block, or `None` if more of the
+    /// file needs to be pushed first
+    pub fn next_block(&mut self) -> Result<Option<(Cid, Vec<u8>)>, Error> {
+        if !self.header_read {
+            // The header contains the roots of the file, which we don't
+            // need since we hand out all blocks
+            match self.next_section()? {
+                Some(_) => self.header_read = true,
+                None => return Ok(None),
+            }
+        }
+        let section = match self.next_section()? {
+            Some(section) => section,
+            None => return Ok(None),
+        };
+        let mut data = &section[..];
+        let cid = Cid::read_bytes(&mut data)
+            .map_err(|e| anyhow!("invalid CAR file: invalid CID: {}", e))?;
+        Ok(Some((cid, data.to_vec())))
+    }
+
+    /// Check that the file did not end in the middle of a section
+    pub fn finish(&self) -> Result<(), Error> {
+        if !self.header_read {
+            return Err(anyhow!("invalid CAR file: the header is missing"));
+        }
+        if !self.buf.is_empty() {
+            return Err(anyhow!(
+                "invalid CAR file: {} bytes of an incomplete block at the end",
+                self.buf.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::Multihash;
+
+    use super::*;
+
+    const RAW: u64 = 0x55;
+    const SHA2_256: u64 = 0x12;
+
+    fn section(bytes: &[u8]) -> Vec<u8> {
+        let mut section = vec![bytes.len() as u8];
+        section.extend_from_slice(bytes);
+        section
+    }
+
+    fn block(digest: u8, data: &[u8]) -> (Cid, Vec<u8>) {
+        let hash = Multihash::<64>::wrap(SHA2_256, &[digest; 32]).unwrap();
+        (Cid::new_v1(RAW, hash), data.to_vec())
+    }
+
+    fn car(blocks: &[(Cid, Vec<u8>)]) -> Vec<u8> {
+        // A header with `{ roots: [], version: 1 }`
+        let mut file = section(&[
+            0xa2, 0x65, 0x72, 0x6f, 0x6f, 0x74, 0x73, 0x80, 0x67, 0x76, 0x65, 0x72, 0x73, 0x69,
+            0x6f, 0x6e, 0x01,
+        ]);
+        for (cid, data) in blocks {
+            let mut bytes = cid.to_bytes();
+            bytes.extend_from_slice(data);
+            file.extend(section(&bytes));
+        }
+        file
+    }
+
+    #[test]
+    fn reads_blocks_split_across_chunks() {
+        let blocks = vec![block(1, b"hello"), block(2, b""), block(3, b"world")];
+        let file = car(&blocks);
+
+        // Push the file one byte at a time
+        let mut reader = CarReader::default();
+        let mut read = Vec::new();
+        for byte in &file {
+            reader.push(&[*byte]);
+            while let Some(block) = reader.next_block().unwrap() {
+                read.push(block);
+            }
+        }
+        reader.finish().unwrap();
+        assert_eq!(blocks, read);
+    }
+
+    #[test]
+    fn rejects_truncated_and_carv2_files() {
+        let file = car(&[block(1, b"hello")]);
+        let mut reader = CarReader::default();
+        reader.push(&file[..file.len() - 1]);
+        assert!(reader.next_block().unwrap().is_none());
+        assert!(reader.finish().is_err());
+
+        let mut reader = CarReader::default();
+        reader.push(&CARV2_PRAGMA);
+        assert!(reader.next_block().is_err());
+    }
+}
@@ -3,15 +3,18 @@ use std::sync::Mutex;
 use std::time::Duration;
 
 use anyhow::anyhow;
+use async_stream::try_stream;
 use async_trait::async_trait;
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use derivative::Derivative;
 use futures03::compat::Stream01CompatExt;
+use futures03::stream::BoxStream;
 use futures03::stream::StreamExt;
 use futures03::stream::TryStreamExt;
 use lru_time_cache::LruCache;
 use serde_json::Value;
 
+use super::car::CarReader;
 use crate::derive::CheapClone;
 use crate::env::EnvVars;
 use crate::futures01::stream::poll_fn;
@@ -21,6 +24,8 @@ use crate::futures01::Async;
 use crate::futures01::Poll;
 use crate::ipfs::ContentPath;
 use crate::ipfs::IpfsClient;
+use crate::ipfs::IpfsResult;
+use crate::ipfs::PinningService;
 use crate::ipfs::RetryPolicy;
 use crate::prelude::{LinkResolver as LinkResolverTrait, *};
 
@@ -40,6 +45,10 @@ pub struct IpfsResolver {
 
     /// When set to `true`, it means infinite retries, ignoring the timeout setting.
     retry: bool,
+
+    /// Pins the files that were fetched
+    #[derivative(Debug = "ignore")]
+    pinning: Option<Arc<PinningService>>,
 }
 
 impl IpfsResolver {
@@ -56,6 +65,19 @@ impl IpfsResolver {
             max_map_file_size: env.max_ipfs_map_file_size,
             max_cache_file_size: env.max_ipfs_cache_file_size,
             retry: false,
+            pinning: None,
+        }
+    }
+
+    /// Pins every file that the resolver fetches with `pinning`
+    pub fn with_pinning(mut self, pinning: Option<Arc<PinningService>>) -> Self {
+        self.pinning = pinning;
+        self
+    }
+
+    fn pin(&self, path: &ContentPath) {
+        if let Some(pinning) = &self.pinning {
+            pinning.pin(path);
         }
     }
 }
@@ -99,6 +121,7 @@ impl LinkResolverTrait for IpfsResolver {
             .cat(&path, max_file_size, timeout, retry_policy)
             .await?
             .to_vec();
+        self.pin(&path);
 
         if data.len() <= max_cache_file_size {
             let mut cache = self.cache.lock().unwrap();
@@ -136,6 +159,7 @@ impl LinkResolverTrait for IpfsResolver {
             .get_block(&path, timeout, retry_policy)
             .await?
             .to_vec();
+        self.pin(&path);
 
         Ok(data)
     }
@@ -161,6 +185,7 @@ impl LinkResolverTrait for IpfsResolver {
             .fuse()
             .boxed()
             .compat();
+        self.pin(&path);
 
         let mut buf = BytesMut::with_capacity(1024);
 
@@ -175,16 +200,6 @@ impl LinkResolverTrait for IpfsResolver {
         let stream: JsonValueStream = Box::pin(
             poll_fn(move || -> Poll<Option<JsonStreamValue>, Error> {
                 loop {
-                    cumulative_file_size += buf.len();
-
-                    if cumulative_file_size > max_map_file_size {
-                        return Err(anyhow!(
-                            "IPFS file {} is too large. It can be at most {} bytes",
-                            path,
-                            max_map_file_size,
-                        ));
-                    }
-
                     if let Some(offset) = buf.iter().position(|b| *b == b'\n') {
                         let line_bytes = buf.split_to(offset + 1);
                         count += 1;
@@ -220,8 +235,18 @@ impl LinkResolverTrait for IpfsResolver {
                         // that means the input was not terminated with a newline. We
                         // add that so that the last line gets picked up in the next
                         // run through the loop.
-                        match try_ready!(stream.poll().map_err(|e| anyhow::anyhow!("{}", e))) {
-                            Some(b) => buf.extend_from_slice(&b),
+                        // Keep the `IpfsError` so that callers can tell network
+                        // errors apart from problems with the file
+                        match try_ready!(stream.poll().map_err(Error::from)) {
+                            Some(b) => {
+                                cumulative_file_size += b.len();
+                                check_map_file_size(
+                                    &path,
+                                    cumulative_file_size,
+                                    max_map_file_size,
+                                )?;
+                                buf.extend_from_slice(&b)
+                            }
                             None if !buf.is_empty() => buf.extend_from_slice(&[b'\n']),
                             None => return Ok(Async::Ready(None)),
                         }
@@ -233,6 +258,70 @@ impl LinkResolverTrait for IpfsResolver {
 
         Ok(stream)
     }
+
+    async fn car_stream(&self, logger: &Logger, link: &Link) -> Result<JsonValueStream, Error> {
+        let path = ContentPath::new(&link.link)?;
+
+        trace!(logger, "IPFS CAR stream"; "hash" => path.to_string());
+
+        let (timeout, retry_policy) = if self.retry {
+            (None, RetryPolicy::NonDeterministic)
+        } else {
+            (Some(self.timeout), RetryPolicy::Networking)
+        };
+
+        let chunks = self
+            .client
+            .clone()
+            .cat_stream(&path, timeout, retry_policy)
+            .await?;
+        self.pin(&path);
+
+        Ok(Box::pin(car_blocks(chunks, path, self.max_map_file_size)))
+    }
+}
+
+fn check_map_file_size(path: &ContentPath, size: usize, max_size: usize) -> Result<(), Error> {
+    if size > max_size {
+        return Err(anyhow!(
+            "IPFS file {} is too large. It can be at most {} bytes",
+            path,
+            max_size,
+        ));
+    }
+    Ok(())
+}
+
+/// Split the `chunks` of the CAR file at `path` into its blocks
+fn car_blocks(
+    mut chunks: BoxStream<'static, IpfsResult<Bytes>>,
+    path: ContentPath,
+    max_map_file_size: usize,
+) -> impl futures03::Stream<Item = Result<JsonStreamValue, Error>> {
+    try_stream! {
+        let mut reader = CarReader::default();
+        let mut count = 0;
+        let mut cumulative_file_size = 0;
+
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            cumulative_file_size += chunk.len();
+            check_map_file_size(&path, cumulative_file_size, max_map_file_size)?;
+
+            reader.push(&chunk);
+            while let Some((cid, data)) = reader.next_block()? {
+                count += 1;
+                yield JsonStreamValue {
+                    value: serde_json::json!({
+                        "cid": cid.to_string(),
+                        "data": format!("0x{}", hex::encode(data)),
+                    }),
+                    line: count,
+                };
+            }
+        }
+        reader.finish()?;
+    }
 }
 
 #[cfg(test)]
@@ -7,6 +7,7 @@ use crate::prelude::Error;
 use std::fmt::Debug;
 
 mod arweave;
+mod car;
 mod ipfs;
 
 pub use arweave::*;
@@ -33,4 +34,14 @@ pub trait LinkResolver: Send + Sync + 'static + Debug {
     /// as they are used to split the file contents and each line is deserialized
     /// separately.
     async fn json_stream(&self, logger: &Logger, link: &Link) -> Result<JsonValueStream, Error>;
+
+    /// Read the contents of `link` as a CARv1 file and turn each of its
+    /// blocks into a JSON object with the `cid` of the block as a string and
+    /// its `data` as a `0x` prefixed hex string. The `line` of each value is
+    /// the position of the block in the file, starting at 1
+    async fn car_stream(&self, _logger: &Logger, _link: &Link) -> Result<JsonValueStream, Error> {
+        Err(anyhow::anyhow!(
+            "reading CAR files is not supported by this link resolver"
+        ))
+    }
 }
@@ -57,6 +57,9 @@ pub mod trigger_processor;
 /// Components dealing with collecting metrics
 pub mod metrics;
 
+/// Components dealing with tracing what the node does with OpenTelemetry
+pub mod tracing;
+
 /// Components dealing with versioning
 pub mod versions;
 
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use crate::components::store::StoreError;
+use crate::data::subgraph::DeploymentHash;
+
+/// The name of the HTTP header that clients use to send their API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// An API key for the query port. Only the hash of the secret that clients
+/// send is ever stored
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiKey {
+    /// The name under which the key is managed and reported in metrics
+    pub name: String,
+    /// The deployments the key can query; `None` means all deployments
+    pub deployments: Option<Vec<DeploymentHash>>,
+    /// The maximum number of requests per minute; `None` means unlimited
+    pub rate_limit: Option<u32>,
+}
+
+impl ApiKey {
+    pub fn allows(&self, deployment: &DeploymentHash) -> bool {
+        match &self.deployments {
+            None => true,
+            Some(deployments) => deployments.contains(deployment),
+        }
+    }
+}
+
+/// All API keys that have not been revoked
+#[derive(Clone, Debug, Default)]
+pub struct ApiKeys {
+    /// The keys, indexed by the hex-encoded blake3 hash of their secret
+    pub keys: HashMap<String, ApiKey>,
+    /// The current deployment of each subgraph whose current deployment
+    /// appears in the allowlist of some key, so that queries by subgraph
+    /// name can be checked against allowlists
+    pub names: HashMap<String, DeploymentHash>,
+}
+
+/// Hash `secret` the way the hashes in `ApiKeys::keys` are computed
+pub fn hash_secret(secret: &str) -> String {
+    blake3::hash(secret.as_bytes()).to_hex().to_string()
+}
+
+pub trait ApiKeyStore: Send + Sync + 'static {
+    /// Load all API keys that have not been revoked
+    fn active_keys(&self) -> Result<ApiKeys, StoreError>;
+}
@@ -8,3 +8,6 @@ pub mod subscription;
 pub mod index_node;
 
 pub mod server;
+
+/// API keys for the query port.
+pub mod api_key;
@@ -3,8 +3,8 @@ use std::net::SocketAddr;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
-use hyper::body::Incoming;
-use hyper::Request;
+use hyper::body::{Body, Incoming};
+use hyper::{Request, Response};
 
 use crate::cheap_clone::CheapClone;
 use crate::hyper::server::conn::http1;
@@ -17,7 +17,7 @@ use crate::{anyhow, tokio};
 
 use crate::prelude::Logger;
 
-use super::query::ServerResult;
+use super::query::ServerError;
 
 /// A handle to the server that can be used to shut it down. The `accepting`
 /// field is only used in tests to check if the server is running
@@ -26,14 +26,20 @@ pub struct ServerHandle {
     pub accepting: Arc<AtomicBool>,
 }
 
-pub async fn start<F, S>(
+/// Serve requests on `port` with `handler`. Handlers usually respond with
+/// a `ServerResponse`, but can use any body type, e.g., to stream their
+/// response
+pub async fn start<F, S, B>(
     logger: Logger,
     port: u16,
     handler: F,
 ) -> Result<ServerHandle, anyhow::Error>
 where
     F: Fn(Request<Incoming>) -> S + Send + Clone + 'static,
-    S: Future<Output = ServerResult> + Send + 'static,
+    S: Future<Output = Result<Response<B>, ServerError>> + Send + 'static,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     let listener = TcpListener::bind(addr).await?;
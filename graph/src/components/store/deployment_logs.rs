@@ -0,0 +1,85 @@
+//! The messages that mappings log with `log.*` and that are kept in the
+//! store for each deployment
+use chrono::{DateTime, Utc};
+use slog::Level;
+
+use super::BlockNumber;
+
+/// The name of `level` in the status API and graphman; these are the
+/// levels that mappings can log with
+pub fn log_level_name(level: Level) -> &'static str {
+    match level {
+        Level::Critical => "critical",
+        Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Info => "info",
+        Level::Debug | Level::Trace => "debug",
+    }
+}
+
+pub fn parse_log_level(name: &str) -> Option<Level> {
+    match name {
+        "critical" => Some(Level::Critical),
+        "error" => Some(Level::Error),
+        "warning" => Some(Level::Warning),
+        "info" => Some(Level::Info),
+        "debug" => Some(Level::Debug),
+        _ => None,
+    }
+}
+
+/// A message that a mapping logged
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeploymentLog {
+    pub level: Level,
+    pub message: String,
+    /// The handler that logged the message; `None` for messages that were
+    /// logged outside of a handler, for example while starting the module
+    pub handler: Option<String>,
+    pub block_number: BlockNumber,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Which logs to return. Logs have to match all conditions that are set
+#[derive(Clone, Debug)]
+pub struct DeploymentLogFilter {
+    /// Only return logs that are at least as severe as this
+    pub level: Option<Level>,
+    pub handler: Option<String>,
+    /// Only return logs for blocks in this range; both ends are inclusive
+    pub from_block: Option<BlockNumber>,
+    pub to_block: Option<BlockNumber>,
+    /// The most logs to return
+    pub first: usize,
+}
+
+impl Default for DeploymentLogFilter {
+    fn default() -> Self {
+        Self {
+            level: None,
+            handler: None,
+            from_block: None,
+            to_block: None,
+            first: 100,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_level_names() {
+        for level in [
+            Level::Critical,
+            Level::Error,
+            Level::Warning,
+            Level::Info,
+            Level::Debug,
+        ] {
+            assert_eq!(Some(level), parse_log_level(log_level_name(level)));
+        }
+        assert_eq!(None, parse_log_level("warn"));
+    }
+}
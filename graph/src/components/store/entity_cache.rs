@@ -1,19 +1,19 @@
 use anyhow::anyhow;
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::{self, Debug};
 use std::sync::Arc;
 
 use crate::cheap_clone::CheapClone;
 use crate::components::store::write::EntityModification;
 use crate::components::store::{self as s, Entity, EntityOperation};
-use crate::data::store::{EntityValidationError, Id, IdType, IntoEntityIterator};
+use crate::data::store::{EntityValidationError, Id, IdType, IntoEntityIterator, Value};
 use crate::prelude::ENV_VARS;
 use crate::schema::{EntityKey, InputSchema};
 use crate::util::intern::Error as InternError;
 use crate::util::lfu_cache::{EvictStats, LfuCache};
 
-use super::{BlockNumber, DerivedEntityQuery, LoadRelatedRequest, StoreError};
+use super::{BlockNumber, DerivedEntityFilter, DerivedEntityQuery, LoadRelatedRequest, StoreError};
 
 pub type EntityLfuCache = LfuCache<EntityKey, Option<Arc<Entity>>>;
 
@@ -218,17 +218,68 @@ impl EntityCache {
         Ok(entity)
     }
 
+    /// Get the entities for `keys`, in the order of `keys`. All entities
+    /// that are not in the cache yet are loaded from the store with one
+    /// query
+    pub fn get_many(&mut self, keys: &[EntityKey]) -> Result<Vec<Option<Arc<Entity>>>, StoreError> {
+        let missing: BTreeSet<EntityKey> = keys
+            .iter()
+            .filter(|key| !self.current.contains_key(key))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            let mut entities = self.store.get_many(missing.clone())?;
+            for key in missing {
+                let entity = entities.remove(&key).map(Arc::new);
+                self.current.insert(key, entity);
+            }
+        }
+
+        keys.iter()
+            .map(|key| self.get(key, GetScope::Store))
+            .collect()
+    }
+
+    /// The value that the entities related to `eref` store in the field
+    /// they are derived from. That is the id of the parent or, for fields
+    /// derived with a `key`, the value of the key in the parent. Return
+    /// `None` if the parent does not exist or does not have a key, since
+    /// it can then not have related entities
+    fn related_value(&mut self, eref: &LoadRelatedRequest) -> Result<Option<Id>, anyhow::Error> {
+        let key = eref
+            .entity_type
+            .field(&eref.entity_field)
+            .and_then(|field| field.derived_key());
+        let Some(key) = key else {
+            return Ok(Some(eref.entity_id.clone()));
+        };
+        let parent = eref
+            .entity_type
+            .key_in(eref.entity_id.clone(), eref.causality_region);
+        let value = self
+            .get(&parent, GetScope::Store)?
+            .and_then(|parent| parent.get(key).cloned());
+        match value {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => Id::try_from(value).map(Some),
+        }
+    }
+
     pub fn load_related(
         &mut self,
         eref: &LoadRelatedRequest,
     ) -> Result<Vec<Entity>, anyhow::Error> {
         let (entity_type, field) = self.schema.get_field_related(eref)?;
+        let Some(value) = self.related_value(eref)? else {
+            return Ok(vec![]);
+        };
 
         let query = DerivedEntityQuery {
             entity_type,
             entity_field: field.name.clone().into(),
-            value: eref.entity_id.clone(),
+            value,
             causality_region: eref.causality_region,
+            filter: DerivedEntityFilter::default(),
         };
 
         let mut entity_map = self.store.get_derived(&query)?;
@@ -340,6 +391,69 @@ impl EntityCache {
         Ok(entity_map.into_values().collect())
     }
 
+    /// Like `load_related`, but only return the entities that match
+    /// `filter`, in the order that it prescribes. Only the first
+    /// `filter.first` entities are loaded from the store, plus one for
+    /// each entity of the derived type that was changed in this block
+    /// since the store does not know about those changes
+    pub fn load_related_filtered(
+        &mut self,
+        eref: &LoadRelatedRequest,
+        filter: &DerivedEntityFilter,
+    ) -> Result<Vec<Entity>, anyhow::Error> {
+        let (entity_type, field) = self.schema.get_field_related(eref)?;
+        let Some(value) = self.related_value(eref)? else {
+            return Ok(vec![]);
+        };
+
+        let changed: Vec<EntityKey> = self
+            .updates
+            .keys()
+            .chain(self.handler_updates.keys())
+            .filter(|key| {
+                key.entity_type == entity_type && key.causality_region == eref.causality_region
+            })
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let mut store_filter = filter.clone();
+        store_filter.first = filter.first.map(|first| first + changed.len());
+        let query = DerivedEntityQuery {
+            entity_type,
+            entity_field: field.name.clone().into(),
+            value,
+            causality_region: eref.causality_region,
+            filter: store_filter,
+        };
+
+        let mut entities = Vec::new();
+        for (key, entity) in self.store.get_derived(&query)? {
+            if changed.binary_search(&key).is_ok() {
+                continue;
+            }
+            // Only insert to the cache if it's not already there
+            if !self.current.contains_key(&key) {
+                self.current
+                    .insert(key.clone(), Some(Arc::new(entity.clone())));
+            }
+            entities.push(entity);
+        }
+
+        // Entities that were changed in this block need to be checked
+        // against the query with all their changes applied
+        for (key, entity) in changed.iter().zip(self.get_many(&changed)?) {
+            if let Some(entity) = entity {
+                if query.matches(key, &entity) && filter.matches(&entity) {
+                    entities.push(entity.as_ref().clone());
+                }
+            }
+        }
+
+        Ok(filter.apply(entities))
+    }
+
     pub fn remove(&mut self, key: EntityKey) {
         self.entity_op(key, EntityOp::Remove);
     }
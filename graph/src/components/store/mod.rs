@@ -1,8 +1,10 @@
+mod deployment_logs;
 mod entity_cache;
 mod err;
 mod traits;
 pub mod write;
 
+pub use deployment_logs::{log_level_name, parse_log_level, DeploymentLog, DeploymentLogFilter};
 pub use entity_cache::{EntityCache, EntityLfuCache, GetScope, ModificationsAndCache};
 use futures03::future::{FutureExt, TryFutureExt};
 use slog::{trace, Logger};
@@ -29,7 +31,7 @@ use crate::blockchain::{Block, BlockHash, BlockPtr};
 use crate::cheap_clone::CheapClone;
 use crate::components::store::write::EntityModification;
 use crate::constraint_violation;
-use crate::data::store::scalar::Bytes;
+use crate::data::store::scalar::{Bytes, GeoPoint};
 use crate::data::store::{Id, IdList, Value};
 use crate::data::value::Word;
 use crate::data_source::CausalityRegion;
@@ -84,6 +86,8 @@ pub struct DerivedEntityQuery {
     /// doing the lookup. So if the entity exists but was created on a different causality region,
     /// the lookup will return empty.
     pub causality_region: CausalityRegion,
+    /// Further restrictions on the entities that are returned
+    pub filter: DerivedEntityFilter,
 }
 
 impl DerivedEntityQuery {
@@ -98,12 +102,107 @@ impl DerivedEntityQuery {
     }
 }
 
+/// Restricts the entities that a `DerivedEntityQuery` returns to those
+/// whose attributes have certain values and, if `first` is set, to the
+/// first `first` of them in the order given by `order_by` and `descending`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DerivedEntityFilter {
+    /// Only return entities where each of these attributes has the
+    /// corresponding value; a `Value::Null` matches entities that do not
+    /// have the attribute
+    pub values: Vec<(Word, Value)>,
+    /// The attribute to order by. Entities are always ordered by their id
+    /// after that
+    pub order_by: Option<Word>,
+    pub descending: bool,
+    /// Return at most this many entities
+    pub first: Option<usize>,
+}
+
+impl DerivedEntityFilter {
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.first.is_none()
+    }
+
+    pub fn matches(&self, entity: &Entity) -> bool {
+        self.values
+            .iter()
+            .all(|(attr, value)| entity.get(attr).unwrap_or(&Value::Null) == value)
+    }
+
+    /// The filter on the attribute values as an `EntityFilter`, or `None`
+    /// if there is nothing to filter on
+    pub fn entity_filter(&self) -> Option<EntityFilter> {
+        if self.values.is_empty() {
+            return None;
+        }
+        let filters = self
+            .values
+            .iter()
+            .map(|(attr, value)| EntityFilter::Equal(attr.to_string(), value.clone()))
+            .collect();
+        Some(EntityFilter::And(filters))
+    }
+
+    /// Compare two entities in the order in which the store returns them.
+    /// Like Postgres, nulls sort after all other values in ascending order
+    /// and strings are compared bytewise
+    pub fn compare(&self, a: &Entity, b: &Entity) -> std::cmp::Ordering {
+        use std::cmp::Ordering::*;
+        use Value as V;
+
+        fn compare_values(a: &Value, b: &Value) -> std::cmp::Ordering {
+            match (a, b) {
+                (V::Null, V::Null) => Equal,
+                (V::Null, _) => Greater,
+                (_, V::Null) => Less,
+                (V::String(a), V::String(b)) => a.cmp(b),
+                (V::Int(a), V::Int(b)) => a.cmp(b),
+                (V::Int8(a), V::Int8(b)) => a.cmp(b),
+                (V::Timestamp(a), V::Timestamp(b)) => a.cmp(b),
+                (V::BigDecimal(a), V::BigDecimal(b)) => a.cmp(b),
+                (V::BigInt(a), V::BigInt(b)) => a.cmp(b),
+                (V::Bool(a), V::Bool(b)) => a.cmp(b),
+                (V::Bytes(a), V::Bytes(b)) => a.cmp(b),
+                _ => Equal,
+            }
+        }
+
+        let order = match &self.order_by {
+            Some(attr) => compare_values(
+                a.get(attr).unwrap_or(&Value::Null),
+                b.get(attr).unwrap_or(&Value::Null),
+            ),
+            None => Equal,
+        }
+        .then_with(|| a.id().cmp(&b.id()));
+        if self.descending {
+            order.reverse()
+        } else {
+            order
+        }
+    }
+
+    /// Sort `entities` and keep only the first `first` of them. The
+    /// entities must already match the filter
+    pub fn apply(&self, mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort_by(|a, b| self.compare(a, b));
+        if let Some(first) = self.first {
+            entities.truncate(first);
+        }
+        entities
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Child {
     pub attr: Attribute,
     pub entity_type: EntityType,
     pub filter: Box<EntityFilter>,
     pub derived: bool,
+    /// For derived children, the attribute of the parent whose value the
+    /// children store in `attr`; `None` means they store the parent's id
+    pub key: Option<Attribute>,
 }
 
 /// Supported types of store filters.
@@ -134,6 +233,12 @@ pub enum EntityFilter {
     ChangeBlockGte(BlockNumber),
     Child(Child),
     Fulltext(Attribute, Value),
+    /// Points that are at most the given number of meters away from the
+    /// given point
+    WithinDistance(Attribute, GeoPoint, f64),
+    /// Points inside the box with the given south-west and north-east
+    /// corners
+    WithinBox(Attribute, GeoPoint, GeoPoint),
 }
 
 // A somewhat concise string representation of a filter
@@ -173,6 +278,8 @@ impl fmt::Display for EntityFilter {
             NotEndsWith(a, v) => write!(f, "{a} !~ *{v}$"),
             NotEndsWithNoCase(a, v) => write!(f, "{a} !~ *{v}$i"),
             ChangeBlockGte(b) => write!(f, "block >= {b}"),
+            WithinDistance(a, p, m) => write!(f, "{a} within {m}m of ({p})"),
+            WithinBox(a, sw, ne) => write!(f, "{a} within box ({sw}) ({ne})"),
             Child(child /* a, et, cf, _ */) => write!(
                 f,
                 "join on {} with {}({})",
@@ -350,6 +457,10 @@ impl ChildMultiplicity {
 pub enum EntityLink {
     /// The parent id is stored in this child attribute
     Direct(WindowAttribute, ChildMultiplicity),
+    /// This child attribute stores the value of an attribute of the parent
+    /// other than its id. The ith entry in the `IdList` is that value for
+    /// the parent with id `EntityWindow.ids[i]`
+    Key(WindowAttribute, IdList, ChildMultiplicity),
     /// Join with the parents table to get at the parent id
     Parent(EntityType, ParentLink),
 }
@@ -1034,6 +1145,10 @@ impl ReadStore for EmptyStore {
         Ok(None)
     }
 
+    fn get_at_block(&self, _: &EntityKey, _: BlockNumber) -> Result<Option<Entity>, StoreError> {
+        Ok(None)
+    }
+
     fn get_many(&self, _: BTreeSet<EntityKey>) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
         Ok(BTreeMap::new())
     }
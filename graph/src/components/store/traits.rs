@@ -6,13 +6,15 @@ use web3::types::{Address, H256};
 
 use super::*;
 use crate::blockchain::block_stream::FirehoseCursor;
-use crate::blockchain::{BlockTime, ChainIdentifier};
+use crate::blockchain::{BlockTime, ChainIdentifier, IngestionMode};
 use crate::components::metrics::stopwatch::StopwatchMetrics;
 use crate::components::server::index_node::VersionInfo;
-use crate::components::subgraph::SubgraphVersionSwitchingMode;
+use crate::components::subgraph::{
+    FetchPolicy, HandlerLimits, OffchainFailure, RetryPolicy, SubgraphVersionSwitchingMode,
+};
 use crate::components::transaction_receipt;
 use crate::components::versions::ApiVersion;
-use crate::data::query::Trace;
+use crate::data::query::{EntityHistory, EntityHistoryQuery, SqlQueryResult, Trace};
 use crate::data::store::ethereum::call;
 use crate::data::store::QueryObject;
 use crate::data::subgraph::{status, DeploymentFeatures};
@@ -46,6 +48,41 @@ pub trait EnsLookup: Send + Sync + 'static {
     fn is_table_empty(&self) -> Result<bool, StoreError>;
 }
 
+/// Stores the responses to `http.get` requests from mappings so that
+/// handlers see the same response every time they run for a deployment
+pub trait HttpCache: Send + Sync + 'static {
+    /// The body of the response that was stored for `url` when an offchain
+    /// data source with `source`, usually its content hash, fetched it
+    fn get(
+        &self,
+        deployment: &DeploymentHash,
+        source: &str,
+        url: &str,
+    ) -> Result<Option<Vec<u8>>, StoreError>;
+    /// Store `body` as the response for `url` and `source` unless a
+    /// response is already stored, and return the stored response
+    fn insert(
+        &self,
+        deployment: &DeploymentHash,
+        source: &str,
+        url: &str,
+        body: Vec<u8>,
+    ) -> Result<Vec<u8>, StoreError>;
+}
+
+/// Stores the messages that mappings log with `log.*` so that subgraph
+/// developers can look at them. Only the most recent messages of each
+/// deployment are kept
+pub trait DeploymentLogs: Send + Sync + 'static {
+    fn append(&self, deployment: &DeploymentHash, log: DeploymentLog) -> Result<(), StoreError>;
+    /// The logs of `deployment` that match `filter`, newest first
+    fn find(
+        &self,
+        deployment: &DeploymentHash,
+        filter: &DeploymentLogFilter,
+    ) -> Result<Vec<DeploymentLog>, StoreError>;
+}
+
 /// An entry point for all operations that require access to the node's storage
 /// layer. It provides access to a [`BlockStore`] and a [`SubgraphStore`].
 pub trait Store: Clone + StatusStore + Send + Sync + 'static {
@@ -65,6 +102,10 @@ pub trait Store: Clone + StatusStore + Send + Sync + 'static {
 pub trait SubgraphStore: Send + Sync + 'static {
     fn ens_lookup(&self) -> Arc<dyn EnsLookup>;
 
+    fn http_cache(&self) -> Arc<dyn HttpCache>;
+
+    fn deployment_logs(&self) -> Arc<dyn DeploymentLogs>;
+
     /// Check if the store is accepting queries for the specified subgraph.
     /// May return true even if the specified subgraph is not currently assigned to an indexing
     /// node, as the store will still accept queries.
@@ -215,12 +256,78 @@ pub trait SubgraphStore: Send + Sync + 'static {
     /// When this flag is set, indexing of the deployment should log
     /// additional diagnostic information
     fn instrument(&self, deployment: &DeploymentLocator) -> Result<bool, StoreError>;
+
+    /// Return the retry policy for non-deterministic errors that was
+    /// configured for the deployment, or `None` if the defaults should be
+    /// used
+    fn retry_policy(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<RetryPolicy>, StoreError>;
+
+    /// Return the limits for mapping handlers that were configured for the
+    /// deployment, or `None` if the defaults should be used
+    fn handler_limits(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<HandlerLimits>, StoreError>;
+
+    /// Return the named constants that mappings of the deployment can read
+    /// with `dataSource.env`, or `None` if none were configured
+    fn mapping_env(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<BTreeMap<String, String>>, StoreError>;
+
+    /// Return the params for substreams modules, by module name, that
+    /// override the params from the manifest of the deployment, or `None`
+    /// if there are no overrides
+    fn substreams_params(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<BTreeMap<String, String>>, StoreError>;
+
+    /// Return how the deployment gets its blocks
+    fn ingestion(&self, deployment: &DeploymentLocator) -> Result<IngestionMode, StoreError>;
+
+    /// Return how the file data sources of the deployment fetch their
+    /// files, or `None` if the defaults should be used
+    fn fetch_policy(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<FetchPolicy>, StoreError>;
+
+    /// Return the offchain files that the deployment gave up on fetching
+    fn offchain_failures(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Vec<OffchainFailure>, StoreError>;
+
+    /// Record that the deployment gave up on fetching an offchain file so
+    /// that it does not try again when it is restarted
+    fn record_offchain_failure(
+        &self,
+        deployment: &DeploymentLocator,
+        failure: &OffchainFailure,
+    ) -> Result<(), StoreError>;
+
+    /// Return a read-only view of the active deployment with hash `id` so
+    /// that subgraph data sources can use it as their source
+    fn sourceable(&self, id: &DeploymentHash) -> Result<Arc<dyn SourceableStore>, StoreError>;
 }
 
 pub trait ReadStore: Send + Sync + 'static {
     /// Looks up an entity using the given store key at the latest block.
     fn get(&self, key: &EntityKey) -> Result<Option<Entity>, StoreError>;
 
+    /// Looks up an entity as it was once `block` had been processed. Fails
+    /// if the history of the deployment for `block` has been pruned
+    fn get_at_block(
+        &self,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<Entity>, StoreError>;
+
     /// Look up multiple entities as of the latest block.
     fn get_many(
         &self,
@@ -242,6 +349,14 @@ impl<T: ?Sized + ReadStore> ReadStore for Arc<T> {
         (**self).get(key)
     }
 
+    fn get_at_block(
+        &self,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<Entity>, StoreError> {
+        (**self).get_at_block(key, block)
+    }
+
     fn get_many(
         &self,
         keys: BTreeSet<EntityKey>,
@@ -261,6 +376,25 @@ impl<T: ?Sized + ReadStore> ReadStore for Arc<T> {
     }
 }
 
+/// Read-only access to a deployment that subgraph data sources use as
+/// their source
+#[async_trait]
+pub trait SourceableStore: Send + Sync + 'static {
+    /// The changes the deployment made to its entities in `block`
+    fn get_changes(&self, block: BlockNumber) -> Result<Vec<EntityOperation>, StoreError>;
+
+    /// Look up an entity as of `block`
+    fn get(&self, key: &EntityKey, block: BlockNumber) -> Result<Option<Entity>, StoreError>;
+
+    fn input_schema(&self) -> InputSchema;
+
+    /// The network that the deployment indexes
+    fn network(&self) -> &str;
+
+    /// The block the deployment has processed up to
+    async fn block_ptr(&self) -> Result<Option<BlockPtr>, StoreError>;
+}
+
 pub trait DeploymentCursorTracker: Sync + Send + 'static {
     fn input_schema(&self) -> InputSchema;
 
@@ -358,6 +492,9 @@ pub trait WritableStore: ReadStore + DeploymentCursorTracker {
 
     fn unassign_subgraph(&self) -> Result<(), StoreError>;
 
+    /// Pause the subgraph so that no node indexes it until it is resumed
+    fn pause_subgraph(&self) -> Result<(), StoreError>;
+
     /// Load the dynamic data sources for the given deployment
     async fn load_dynamic_data_sources(
         &self,
@@ -593,6 +730,22 @@ pub trait QueryStore: Send + Sync {
         query: EntityQuery,
     ) -> Result<(Vec<QueryObject>, Trace), QueryExecutionError>;
 
+    /// Run the read-only SQL query `sql` against the entity tables of
+    /// this deployment as they were at `block`. The query is validated
+    /// and rewritten before it is run so that it can only see entity data
+    fn execute_sql(
+        &self,
+        sql: &str,
+        block: BlockNumber,
+    ) -> Result<SqlQueryResult, QueryExecutionError>;
+
+    /// Return the versions of entities that were written or removed in
+    /// the block range given by `query`
+    fn entity_history(
+        &self,
+        query: &EntityHistoryQuery,
+    ) -> Result<EntityHistory, QueryExecutionError>;
+
     async fn is_deployment_synced(&self) -> Result<bool, Error>;
 
     async fn block_ptr(&self) -> Result<Option<BlockPtr>, StoreError>;
@@ -0,0 +1,221 @@
+//! How file data sources of a deployment fetch their files
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// The backoff between failed attempts to fetch a file starts at this and
+/// doubles with every further failure
+pub const DEFAULT_MIN_BACKOFF: Duration = Duration::from_secs(5);
+
+/// The backoff between failed attempts never gets longer than this
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// The content types that `FetchPolicy::content_types` can refer to; see
+/// `sniff_content_type`
+pub const CONTENT_TYPES: &[&str] = &[
+    "application/json",
+    "application/pdf",
+    "application/octet-stream",
+    "image/gif",
+    "image/jpeg",
+    "image/png",
+    "image/svg+xml",
+    "image/webp",
+    "text/plain",
+];
+
+/// How the file data sources of a deployment fetch their files. Fields
+/// that are not set keep the default behavior, which is to poll for files
+/// forever, at whatever concurrency the node allows, and to accept any file
+/// up to `GRAPH_MAX_IPFS_FILE_BYTES`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FetchPolicy {
+    /// How often fetching a file may fail, because it could not be found
+    /// or because of an error, before the deployment gives up on it
+    pub max_attempts: Option<u32>,
+    /// The backoff after the first error, in seconds
+    pub min_backoff_secs: Option<u64>,
+    /// The longest backoff between errors, in seconds
+    pub max_backoff_secs: Option<u64>,
+    /// The largest file that is accepted, in bytes. This can only lower
+    /// `GRAPH_MAX_IPFS_FILE_BYTES`, not raise it
+    pub max_file_bytes: Option<usize>,
+    /// How many files the deployment may fetch at the same time
+    pub max_in_flight: Option<usize>,
+    /// The content types that files must have, like `application/json` or
+    /// `image/*`
+    pub content_types: Option<Vec<String>>,
+}
+
+impl FetchPolicy {
+    pub fn min_backoff(&self) -> Duration {
+        self.min_backoff_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_MIN_BACKOFF)
+    }
+
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_MAX_BACKOFF)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == Some(0) {
+            return Err("the maximum number of attempts must be at least 1".to_string());
+        }
+        if self.min_backoff().is_zero() {
+            return Err("the minimum backoff must be at least 1 second".to_string());
+        }
+        if self.min_backoff() > self.max_backoff() {
+            return Err(format!(
+                "the minimum backoff of {}s is longer than the maximum backoff of {}s",
+                self.min_backoff().as_secs(),
+                self.max_backoff().as_secs()
+            ));
+        }
+        if self.max_file_bytes == Some(0) {
+            return Err("the maximum file size must be at least 1 byte".to_string());
+        }
+        if self.max_in_flight == Some(0) {
+            return Err("the number of files in flight must be at least 1".to_string());
+        }
+        for content_type in self.content_types.iter().flatten() {
+            if !CONTENT_TYPES
+                .iter()
+                .any(|known| content_type_matches(content_type, known))
+            {
+                return Err(format!(
+                    "unknown content type `{}`, expected one of {}",
+                    content_type,
+                    CONTENT_TYPES.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the contents of a file are acceptable. Returns why they
+    /// are not otherwise
+    pub fn check(&self, data: &[u8]) -> Result<(), String> {
+        if let Some(max) = self.max_file_bytes {
+            if data.len() > max {
+                return Err(format!(
+                    "the file has {} bytes but may have at most {} bytes",
+                    data.len(),
+                    max
+                ));
+            }
+        }
+        if let Some(content_types) = &self.content_types {
+            let sniffed = sniff_content_type(data);
+            if !content_types
+                .iter()
+                .any(|content_type| content_type_matches(content_type, sniffed))
+            {
+                return Err(format!(
+                    "the file has content type `{}` but must have one of {}",
+                    sniffed,
+                    content_types.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `content_type` is `pattern`, or matches a pattern like `image/*`
+fn content_type_matches(pattern: &str, content_type: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(prefix) => content_type
+            .split_once('/')
+            .is_some_and(|(kind, _)| kind == prefix),
+        None => pattern == content_type,
+    }
+}
+
+/// Determine the content type of a file from its contents. The servers
+/// that files come from do not necessarily report a content type, and
+/// looking at the contents means that every node arrives at the same
+/// answer for the same file
+pub fn sniff_content_type(data: &[u8]) -> &'static str {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+    ];
+
+    if let Some((_, content_type)) = MAGIC.iter().find(|(magic, _)| data.starts_with(magic)) {
+        return content_type;
+    }
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+    if serde_json::from_slice::<serde::de::IgnoredAny>(data).is_ok() {
+        return "application/json";
+    }
+    match std::str::from_utf8(data) {
+        Ok(text) => {
+            let text = text.trim_start();
+            if text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg")) {
+                "image/svg+xml"
+            } else {
+                "text/plain"
+            }
+        }
+        Err(_) => "application/octet-stream",
+    }
+}
+
+/// An offchain file that a deployment gave up on fetching
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffchainFailure {
+    /// The kind of the data source, like `file/ipfs`
+    pub kind: String,
+    /// The source of the data source, like a CID
+    pub source: String,
+    /// Why the deployment gave up on the file
+    pub error: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sniff_content_type, FetchPolicy};
+
+    #[test]
+    fn check_files() {
+        let policy: FetchPolicy = toml::from_str(
+            r#"
+            max_attempts = 3
+            max_file_bytes = 20
+            content_types = ["application/json", "image/*"]
+            "#,
+        )
+        .unwrap();
+        assert!(policy.validate().is_ok());
+
+        assert!(policy.check(br#"{"name":"token"}"#).is_ok());
+        assert!(policy.check(b"\x89PNG\r\n\x1a\n").is_ok());
+        assert!(policy.check(b"hello").is_err());
+        assert!(policy.check(br#"{"name":"a much longer token"}"#).is_err());
+        // Without restrictions, every file is acceptable
+        assert!(FetchPolicy::default().check(b"\x00\x01").is_ok());
+
+        assert_eq!("application/octet-stream", sniff_content_type(b"\x00\xff"));
+        assert_eq!("image/svg+xml", sniff_content_type(b"  <svg></svg>"));
+        assert_eq!("text/plain", sniff_content_type(b"hello"));
+
+        assert!(toml::from_str::<FetchPolicy>("retries = 3").is_err());
+        for invalid in [
+            "max_attempts = 0",
+            "min_backoff_secs = 60\nmax_backoff_secs = 10",
+            "content_types = [\"video/*\"]",
+        ] {
+            let policy: FetchPolicy = toml::from_str(invalid).unwrap();
+            assert!(policy.validate().is_err(), "{invalid}");
+        }
+    }
+}
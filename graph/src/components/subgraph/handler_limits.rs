@@ -0,0 +1,95 @@
+//! Limits on the resources that mapping handlers of a deployment may use
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::env::ENV_VARS;
+
+/// Limits for every invocation of a mapping handler of a deployment. Fields
+/// that are not set take their default from the `GRAPH_MAPPING_HANDLER_*`
+/// and `GRAPH_RUNTIME_*` environment variables; `None` after applying the
+/// defaults means that the resource is not limited
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HandlerLimits {
+    /// How long a handler may run, in seconds. Since this depends on the
+    /// hardware, hitting it is a non-deterministic error
+    pub timeout_secs: Option<u64>,
+    /// The fuel, roughly the number of WASM instructions, a handler may
+    /// use. A `handlerFuel` in the manifest takes precedence; only running
+    /// out of that is a deterministic error
+    pub fuel: Option<u64>,
+    /// How large the WASM memory of a handler may grow, in bytes. Hitting
+    /// it is a non-deterministic error
+    pub max_memory_bytes: Option<u64>,
+    /// Whether `fuel` is the `handlerFuel` from the manifest. Every indexer
+    /// uses the same budget then, and running out of it is deterministic
+    #[serde(skip)]
+    pub manifest_fuel: bool,
+}
+
+impl HandlerLimits {
+    /// Fill in the limits that are not set from `manifest_fuel`, the fuel
+    /// that the manifest of the deployment sets, and the environment
+    pub fn with_defaults(self, manifest_fuel: Option<u64>) -> Self {
+        let mappings = &ENV_VARS.mappings;
+        Self {
+            timeout_secs: self
+                .timeout_secs
+                .or(mappings.timeout.map(|timeout| timeout.as_secs())),
+            fuel: manifest_fuel.or(self.fuel).or(mappings.handler_fuel),
+            max_memory_bytes: self
+                .max_memory_bytes
+                .or(mappings.max_memory_size.map(|size| size as u64)),
+            manifest_fuel: manifest_fuel.is_some(),
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs.map(Duration::from_secs)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_secs == Some(0) {
+            return Err("the handler timeout must be at least 1 second".to_string());
+        }
+        if self.fuel == Some(0) {
+            return Err("the handler fuel must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HandlerLimits;
+
+    #[test]
+    fn manifest_fuel_takes_precedence() {
+        let limits: HandlerLimits = toml::from_str(
+            r#"
+            timeout_secs = 30
+            fuel = 1000
+            "#,
+        )
+        .unwrap();
+        assert!(limits.validate().is_ok());
+
+        let resolved = limits.clone().with_defaults(None);
+        assert_eq!(Some(30), resolved.timeout_secs);
+        assert_eq!(Some(1000), resolved.fuel);
+        assert!(!resolved.manifest_fuel);
+
+        let resolved = limits.with_defaults(Some(5000));
+        assert_eq!(Some(5000), resolved.fuel);
+        assert!(resolved.manifest_fuel);
+
+        assert!(toml::from_str::<HandlerLimits>("memory = 10").is_err());
+        assert!(HandlerLimits {
+            fuel: Some(0),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
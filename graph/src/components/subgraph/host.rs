@@ -1,4 +1,5 @@
 use std::cmp::PartialEq;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -9,12 +10,16 @@ use futures01::sync::mpsc;
 use crate::blockchain::BlockTime;
 use crate::components::metrics::gas::GasMetrics;
 use crate::components::store::SubgraphFork;
+use crate::data::store::scalar::BigDecimalPrecision;
 use crate::data_source::{
     DataSource, DataSourceTemplate, MappingTrigger, TriggerData, TriggerWithHandler,
 };
 use crate::prelude::*;
 use crate::runtime::HostExportError;
-use crate::{blockchain::Blockchain, components::subgraph::SharedProofOfIndexing};
+use crate::{
+    blockchain::Blockchain,
+    components::subgraph::{HandlerLimits, SharedProofOfIndexing},
+};
 
 #[derive(Debug)]
 pub enum MappingError {
@@ -225,14 +230,19 @@ pub trait RuntimeHostBuilder<C: Blockchain>: Clone + Send + Sync + 'static {
         top_level_templates: Arc<Vec<DataSourceTemplate<C>>>,
         mapping_request_sender: mpsc::Sender<Self::Req>,
         metrics: Arc<HostMetrics>,
+        big_decimal_precision: BigDecimalPrecision,
+        prunes_history: bool,
+        mapping_env: Arc<BTreeMap<String, String>>,
     ) -> Result<Self::Host, Error>;
 
     /// Spawn a mapping and return a channel for mapping requests. The sender should be able to be
-    /// cached and shared among mappings that use the same wasm file.
+    /// cached and shared among mappings that use the same wasm file. Each handler invocation is
+    /// subject to `limits`.
     fn spawn_mapping(
         raw_module: &[u8],
         logger: Logger,
         subgraph_id: DeploymentHash,
         metrics: Arc<HostMetrics>,
+        limits: &HandlerLimits,
     ) -> Result<mpsc::Sender<Self::Req>, anyhow::Error>;
 }
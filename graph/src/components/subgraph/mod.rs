@@ -1,13 +1,20 @@
+mod fetch_policy;
+mod handler_limits;
 mod host;
 mod instance;
 mod instance_manager;
 mod proof_of_indexing;
 mod provider;
 mod registrar;
+mod retry_policy;
 mod settings;
+mod stream_status;
+mod warm_up;
 
 pub use crate::prelude::Entity;
 
+pub use self::fetch_policy::{sniff_content_type, FetchPolicy, OffchainFailure};
+pub use self::handler_limits::HandlerLimits;
 pub use self::host::{HostMetrics, MappingError, RuntimeHost, RuntimeHostBuilder};
 pub use self::instance::{BlockState, InstanceDSTemplate, InstanceDSTemplateInfo};
 pub use self::instance_manager::SubgraphInstanceManager;
@@ -17,4 +24,7 @@ pub use self::proof_of_indexing::{
 };
 pub use self::provider::SubgraphAssignmentProvider;
 pub use self::registrar::{SubgraphRegistrar, SubgraphVersionSwitchingMode};
+pub use self::retry_policy::{OnExhaustion, RetryPolicy};
 pub use self::settings::{Setting, Settings};
+pub use self::stream_status::{StreamStatus, StreamStatuses, STREAM_STATUSES};
+pub use self::warm_up::{WarmUps, WARM_UPS};
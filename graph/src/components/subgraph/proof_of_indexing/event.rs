@@ -56,6 +56,15 @@ pub enum ProofOfIndexingEvent<'a> {
     ///
     /// for the first and second cases respectively.
     DeterministicError { redacted_events: u64 },
+    /// For when a mapping reads one of the constants that the operator set
+    /// for the deployment with `dataSource.env`. Since the results of the
+    /// handler depend on these, indexers with different constants must not
+    /// end up with the same PoI. `value` is `None` if there is no constant
+    /// with that key
+    ReadEnv {
+        key: &'a str,
+        value: Option<&'a str>,
+    },
 }
 
 impl stable_hash_legacy::StableHash for ProofOfIndexingEvent<'_> {
@@ -84,6 +93,10 @@ impl stable_hash_legacy::StableHash for ProofOfIndexingEvent<'_> {
             DeterministicError { redacted_events } => {
                 redacted_events.stable_hash(sequence_number.next_child(), state)
             }
+            ReadEnv { key, value } => {
+                key.stable_hash(sequence_number.next_child(), state);
+                value.stable_hash(sequence_number.next_child(), state);
+            }
         }
     }
 }
@@ -113,6 +126,11 @@ impl stable_hash::StableHash for ProofOfIndexingEvent<'_> {
                 redacted_events.stable_hash(field_address.child(0), state);
                 3
             }
+            Self::ReadEnv { key, value } => {
+                key.stable_hash(field_address.child(0), state);
+                value.stable_hash(field_address.child(1), state);
+                4
+            }
         };
 
         state.write(field_address, &[variant]);
@@ -150,6 +168,10 @@ impl fmt::Debug for ProofOfIndexingEvent<'_> {
             Self::DeterministicError { redacted_events } => {
                 builder.field("redacted_events", redacted_events);
             }
+            Self::ReadEnv { key, value } => {
+                builder.field("key", key);
+                builder.field("value", value);
+            }
         }
         builder.finish()
     }
@@ -0,0 +1,143 @@
+//! How the subgraph runner retries after non-deterministic errors
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::env::{EnvVars, ENV_VARS};
+use crate::util::backoff::ExponentialBackoff;
+
+/// What to do with a deployment once all retries for a non-deterministic
+/// error have been used up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnExhaustion {
+    /// Stop indexing and leave the deployment marked as failed
+    #[default]
+    Fail,
+    /// Pause the deployment so that it can be resumed with `graphman`
+    Pause,
+}
+
+impl OnExhaustion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OnExhaustion::Fail => "fail",
+            OnExhaustion::Pause => "pause",
+        }
+    }
+}
+
+impl std::str::FromStr for OnExhaustion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fail" => Ok(OnExhaustion::Fail),
+            "pause" => Ok(OnExhaustion::Pause),
+            _ => Err(format!(
+                "invalid value `{s}`, expected one of `fail` or `pause`"
+            )),
+        }
+    }
+}
+
+/// The retry policy for non-deterministic errors of a deployment. Retries
+/// are delayed with an exponential backoff that starts at
+/// `initial_delay_secs` and doubles with every attempt until it reaches
+/// `max_delay_secs`. Fields that are not set take their default from the
+/// `GRAPH_SUBGRAPH_ERROR_RETRY_*` environment variables
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryPolicy {
+    /// How many times to retry before giving up; `None` means that we
+    /// retry forever
+    pub max_retries: Option<u32>,
+    pub initial_delay_secs: u64,
+    pub max_delay_secs: u64,
+    /// Randomize delays by up to this fraction; between 0.0 and 1.0
+    pub jitter: f64,
+    pub on_exhaustion: OnExhaustion,
+}
+
+impl RetryPolicy {
+    /// The policy we use when nothing was configured for a deployment
+    pub fn from_env(env_vars: &EnvVars) -> Self {
+        let max_delay = env_vars.subgraph_error_retry_ceil.as_secs();
+        Self {
+            max_retries: None,
+            initial_delay_secs: max_delay.min(120),
+            max_delay_secs: max_delay,
+            jitter: env_vars.subgraph_error_retry_jitter,
+            on_exhaustion: OnExhaustion::Fail,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.initial_delay_secs > self.max_delay_secs {
+            return Err(format!(
+                "the initial retry delay of {}s is longer than the maximum delay of {}s",
+                self.initial_delay_secs, self.max_delay_secs
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(format!(
+                "the retry jitter must be between 0 and 1 but is {}",
+                self.jitter
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoff::with_jitter(
+            Duration::from_secs(self.initial_delay_secs),
+            Duration::from_secs(self.max_delay_secs),
+            self.jitter,
+        )
+    }
+
+    /// Return `true` if we should not retry again after `attempts` retries
+    pub fn is_exhausted(&self, attempts: u64) -> bool {
+        self.max_retries
+            .map(|max| attempts >= max as u64)
+            .unwrap_or(false)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from_env(&ENV_VARS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OnExhaustion, RetryPolicy};
+
+    #[test]
+    fn parse_partial_policy() {
+        let policy: RetryPolicy = toml::from_str(
+            r#"
+            max_retries = 3
+            on_exhaustion = "pause"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(Some(3), policy.max_retries);
+        assert_eq!(OnExhaustion::Pause, policy.on_exhaustion);
+        assert_eq!(RetryPolicy::default().max_delay_secs, policy.max_delay_secs);
+
+        assert!(!policy.is_exhausted(2));
+        assert!(policy.is_exhausted(3));
+        assert!(!RetryPolicy::default().is_exhausted(u64::MAX));
+
+        let policy = RetryPolicy {
+            initial_delay_secs: 10,
+            max_delay_secs: 5,
+            ..policy
+        };
+        assert!(policy.validate().is_err());
+
+        assert!(toml::from_str::<RetryPolicy>("retries = 3").is_err());
+    }
+}
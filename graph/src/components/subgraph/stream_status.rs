@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+
+use crate::components::store::BlockNumber;
+use crate::data::subgraph::DeploymentHash;
+
+lazy_static! {
+    /// The state of the Firehose and Substreams block streams of the
+    /// deployments that this node indexes
+    pub static ref STREAM_STATUSES: StreamStatuses = StreamStatuses::default();
+}
+
+/// What graphman reports about the block stream of a deployment
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamStatus {
+    /// `firehose` or `substreams`
+    pub kind: &'static str,
+    /// The provider that the stream is connected to, or that it last tried
+    /// to connect to
+    pub provider: String,
+    pub connected: bool,
+    /// How often the stream connected, or tried to, after its first
+    /// attempt
+    pub reconnects: u64,
+    /// How many undo signals the stream received
+    pub undos: u64,
+    /// The block of the latest event the stream produced
+    pub latest_block: Option<BlockNumber>,
+    /// How long ago the stream last advanced its cursor
+    pub cursor_age: Option<Duration>,
+    /// The rates since the stream connected; zero when not connected
+    pub blocks_per_second: f64,
+    pub bytes_per_second: f64,
+}
+
+struct StreamState {
+    kind: &'static str,
+    provider: String,
+    attempts: u64,
+    undos: u64,
+    latest_block: Option<BlockNumber>,
+    cursor_updated: Option<Instant>,
+    /// When the stream connected, and the blocks and bytes it received
+    /// since then. `None` when the stream is not connected
+    connection: Option<(Instant, u64, u64)>,
+}
+
+/// Keeps track of the block streams of deployments so that graphman can
+/// report on them. Only streams that connected at least once are tracked
+#[derive(Default)]
+pub struct StreamStatuses {
+    streams: RwLock<HashMap<DeploymentHash, StreamState>>,
+}
+
+impl StreamStatuses {
+    /// Record an attempt to connect to `provider`; `connected` says whether
+    /// it succeeded
+    pub fn connect(
+        &self,
+        deployment: &DeploymentHash,
+        kind: &'static str,
+        provider: &str,
+        connected: bool,
+    ) {
+        let mut streams = self.streams.write().unwrap();
+        let state = streams
+            .entry(deployment.clone())
+            .or_insert_with(|| StreamState {
+                kind,
+                provider: provider.to_string(),
+                attempts: 0,
+                undos: 0,
+                latest_block: None,
+                cursor_updated: None,
+                connection: None,
+            });
+        state.kind = kind;
+        state.provider = provider.to_string();
+        state.attempts += 1;
+        state.connection = connected.then(|| (Instant::now(), 0, 0));
+    }
+
+    pub fn disconnect(&self, deployment: &DeploymentHash) {
+        if let Some(state) = self.streams.write().unwrap().get_mut(deployment) {
+            state.connection = None;
+        }
+    }
+
+    pub fn receive(&self, deployment: &DeploymentHash, bytes: usize) {
+        if let Some(state) = self.streams.write().unwrap().get_mut(deployment) {
+            if let Some((_, _, received)) = state.connection.as_mut() {
+                *received += bytes as u64;
+            }
+        }
+    }
+
+    /// Record that the stream produced an event for `block` and advanced
+    /// its cursor; `undo` says whether the event reverts to `block`
+    pub fn advance(&self, deployment: &DeploymentHash, block: BlockNumber, undo: bool) {
+        if let Some(state) = self.streams.write().unwrap().get_mut(deployment) {
+            state.latest_block = Some(block);
+            state.cursor_updated = Some(Instant::now());
+            if undo {
+                state.undos += 1;
+            }
+            if let Some((_, blocks, _)) = state.connection.as_mut() {
+                *blocks += 1;
+            }
+        }
+    }
+
+    /// Stop tracking the stream of `deployment`, for example because the
+    /// deployment was stopped
+    pub fn remove(&self, deployment: &DeploymentHash) {
+        self.streams.write().unwrap().remove(deployment);
+    }
+
+    pub fn status(&self, deployment: &DeploymentHash) -> Option<StreamStatus> {
+        let streams = self.streams.read().unwrap();
+        let state = streams.get(deployment)?;
+
+        let (blocks_per_second, bytes_per_second) = match state.connection {
+            Some((since, blocks, bytes)) => {
+                let secs = since.elapsed().as_secs_f64().max(1.0);
+                (blocks as f64 / secs, bytes as f64 / secs)
+            }
+            None => (0.0, 0.0),
+        };
+        Some(StreamStatus {
+            kind: state.kind,
+            provider: state.provider.clone(),
+            connected: state.connection.is_some(),
+            reconnects: state.attempts.saturating_sub(1),
+            undos: state.undos,
+            latest_block: state.latest_block,
+            cursor_age: state.cursor_updated.map(|updated| updated.elapsed()),
+            blocks_per_second,
+            bytes_per_second,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_status() {
+        let statuses = StreamStatuses::default();
+        let hash = DeploymentHash::new("QmStream").unwrap();
+
+        // Nothing is tracked before the stream tries to connect
+        statuses.advance(&hash, 10, false);
+        assert_eq!(None, statuses.status(&hash));
+
+        statuses.connect(&hash, "firehose", "fh1", false);
+        statuses.connect(&hash, "firehose", "fh2", true);
+        statuses.receive(&hash, 100);
+        statuses.advance(&hash, 11, false);
+        statuses.advance(&hash, 10, true);
+
+        let status = statuses.status(&hash).unwrap();
+        assert_eq!("fh2", status.provider);
+        assert!(status.connected);
+        assert_eq!(1, status.reconnects);
+        assert_eq!(1, status.undos);
+        assert_eq!(Some(10), status.latest_block);
+        assert!(status.cursor_age.is_some());
+        assert!(status.blocks_per_second > 0.0);
+        assert!(status.bytes_per_second > 0.0);
+
+        statuses.disconnect(&hash);
+        let status = statuses.status(&hash).unwrap();
+        assert!(!status.connected);
+        assert_eq!(0.0, status.blocks_per_second);
+
+        statuses.remove(&hash);
+        assert_eq!(None, statuses.status(&hash));
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+use crate::components::store::{BlockNumber, DeploymentId};
+use crate::data::subgraph::status::WarmUpStatus;
+
+lazy_static! {
+    /// The warm-ups that are running in this node
+    pub static ref WARM_UPS: WarmUps = WarmUps::default();
+}
+
+/// Keeps track of the warm-ups of the block and call caches for
+/// deployments that start far behind the chain head, so that the status
+/// API can report on them. A warm-up stops when its deployment is removed
+/// from here
+#[derive(Default)]
+pub struct WarmUps {
+    /// The warm-up of each deployment together with a number that tells
+    /// it apart from earlier warm-ups of the same deployment
+    warm_ups: RwLock<HashMap<DeploymentId, (u64, WarmUpStatus)>>,
+    next: AtomicU64,
+}
+
+impl WarmUps {
+    /// Start a warm-up for `id`, replacing any earlier one. The returned
+    /// number identifies the warm-up for `advance`
+    pub fn start(&self, id: DeploymentId, start_block: BlockNumber, end_block: BlockNumber) -> u64 {
+        let warm_up = self.next.fetch_add(1, Ordering::SeqCst);
+        let status = WarmUpStatus {
+            start_block,
+            end_block,
+            latest_block: None,
+        };
+        self.warm_ups.write().unwrap().insert(id, (warm_up, status));
+        warm_up
+    }
+
+    /// Record that `warm_up` warmed up all blocks up to `block`. Returns
+    /// `false` if the warm-up was stopped or replaced
+    pub fn advance(&self, id: DeploymentId, warm_up: u64, block: BlockNumber) -> bool {
+        match self.warm_ups.write().unwrap().get_mut(&id) {
+            Some((current, status)) if *current == warm_up => {
+                status.latest_block = Some(block);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove `warm_up` once it is done
+    pub fn finish(&self, id: DeploymentId, warm_up: u64) {
+        let mut warm_ups = self.warm_ups.write().unwrap();
+        if warm_ups.get(&id).map(|(current, _)| *current) == Some(warm_up) {
+            warm_ups.remove(&id);
+        }
+    }
+
+    /// Stop the warm-up for `id`, for example because the deployment was
+    /// stopped
+    pub fn stop(&self, id: DeploymentId) {
+        self.warm_ups.write().unwrap().remove(&id);
+    }
+
+    pub fn status(&self, id: DeploymentId) -> Option<WarmUpStatus> {
+        self.warm_ups
+            .read()
+            .unwrap()
+            .get(&id)
+            .map(|(_, status)| status.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warm_up_progress() {
+        let warm_ups = WarmUps::default();
+        let id = DeploymentId::new(1);
+
+        assert!(!warm_ups.advance(id, 0, 10));
+        let first = warm_ups.start(id, 10, 100);
+        assert!(warm_ups.advance(id, first, 50));
+        assert_eq!(
+            Some(WarmUpStatus {
+                start_block: 10,
+                end_block: 100,
+                latest_block: Some(50),
+            }),
+            warm_ups.status(id)
+        );
+
+        // Restarting the deployment replaces the warm-up
+        let second = warm_ups.start(id, 50, 100);
+        assert!(!warm_ups.advance(id, first, 60));
+        warm_ups.finish(id, first);
+        assert!(warm_ups.advance(id, second, 60));
+
+        warm_ups.stop(id);
+        assert_eq!(None, warm_ups.status(id));
+        assert!(!warm_ups.advance(id, second, 100));
+    }
+}
@@ -0,0 +1,111 @@
+//! End-to-end tracing with OpenTelemetry. The major pipelines of the node,
+//! like block streams, trigger processing, handlers, store transactions and
+//! GraphQL queries, create spans that are exported via OTLP when
+//! `GRAPH_OTLP_ENDPOINT` is set. Without it, spans go to the no-op tracer
+//! and cost next to nothing.
+//!
+//! Spans of the same block or query are nested through the OpenTelemetry
+//! [`TraceContext`]: async code passes it on with [`TraceFutureExt::with_context`]
+//! and code that runs on other threads attaches it there. Work that is not
+//! done on behalf of a single block, like writing batches of blocks to the
+//! store, gets spans of its own that carry the same `deployment` and
+//! `block.number` attributes so that both can be correlated.
+
+use std::fmt;
+
+use anyhow::Error;
+use opentelemetry::global;
+use opentelemetry::trace::{Status, TraceContextExt as _, Tracer as _};
+use opentelemetry_otlp::WithExportConfig as _;
+use opentelemetry_sdk::trace::{self as sdktrace, Sampler};
+use opentelemetry_sdk::Resource;
+use slog::{info, Logger};
+
+use crate::blockchain::BlockPtr;
+use crate::data::subgraph::DeploymentHash;
+use crate::env::ENV_VARS;
+
+pub use opentelemetry::trace::FutureExt as TraceFutureExt;
+pub use opentelemetry::{Context as TraceContext, KeyValue};
+
+/// The name of the tracer that creates all spans
+const TRACER: &str = "graph-node";
+
+/// Start exporting spans to `GRAPH_OTLP_ENDPOINT` if it is set. Must be
+/// called from within a Tokio runtime
+pub fn init(logger: &Logger) -> Result<(), Error> {
+    let Some(endpoint) = &ENV_VARS.otlp_endpoint else {
+        return Ok(());
+    };
+
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+        ENV_VARS.otlp_sample_ratio,
+    )));
+    let resource = Resource::new(vec![KeyValue::new(
+        "service.name",
+        ENV_VARS.otlp_service_name.clone(),
+    )]);
+    // This also installs the tracer provider globally
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            sdktrace::config()
+                .with_sampler(sampler)
+                .with_resource(resource),
+        )
+        .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+
+    info!(logger, "Exporting OpenTelemetry spans"; "endpoint" => endpoint);
+    Ok(())
+}
+
+/// Start a span called `name` that is a child of the span in `parent`, if
+/// there is one, and return a context with the new span. The span ends
+/// when the returned context and all its clones are dropped
+pub fn span(parent: &TraceContext, name: &'static str, attributes: Vec<KeyValue>) -> TraceContext {
+    let tracer = global::tracer(TRACER);
+    let span = tracer
+        .span_builder(name)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, parent);
+    parent.with_span(span)
+}
+
+/// Start a span that is a child of the span of the current context
+pub fn child_span(name: &'static str, attributes: Vec<KeyValue>) -> TraceContext {
+    span(&TraceContext::current(), name, attributes)
+}
+
+/// Start a span that is the root of a new trace
+pub fn root_span(name: &'static str, attributes: Vec<KeyValue>) -> TraceContext {
+    span(&TraceContext::new(), name, attributes)
+}
+
+/// The attributes that correlate spans by deployment
+pub fn deployment_attributes(deployment: &DeploymentHash) -> Vec<KeyValue> {
+    vec![KeyValue::new("deployment", deployment.to_string())]
+}
+
+/// The attributes that correlate spans by deployment and block
+pub fn block_attributes(deployment: &DeploymentHash, block: &BlockPtr) -> Vec<KeyValue> {
+    vec![
+        KeyValue::new("deployment", deployment.to_string()),
+        KeyValue::new("block.number", block.number as i64),
+        KeyValue::new("block.hash", block.hash_hex()),
+    ]
+}
+
+/// Add `attributes` to the span in `cx`
+pub fn set_attributes(cx: &TraceContext, attributes: Vec<KeyValue>) {
+    cx.span().set_attributes(attributes);
+}
+
+/// Mark the span in `cx` as failed because of `error`
+pub fn record_error(cx: &TraceContext, error: &impl fmt::Display) {
+    cx.span().set_status(Status::error(format!("{:#}", error)));
+}
@@ -323,6 +323,12 @@ pub trait DirectiveFinder {
             .and_then(|directive| directive.argument("field"))
             .and_then(|value| value.as_str())
     }
+
+    fn derived_key(&self) -> Option<&str> {
+        self.find_directive("derivedFrom")
+            .and_then(|directive| directive.argument("key"))
+            .and_then(|value| value.as_str())
+    }
 }
 
 impl DirectiveFinder for ObjectType {
@@ -78,6 +78,7 @@ pub enum QueryExecutionError {
     IdMissing,
     IdNotString,
     ConstraintViolation(String),
+    InvalidSqlQuery(String),
 }
 
 impl QueryExecutionError {
@@ -116,7 +117,8 @@ impl QueryExecutionError {
             | CyclicalFragment(_)
             | UndefinedFragment(_)
             | FulltextQueryInvalidSyntax(_)
-            | FulltextQueryRequiresFilter => true,
+            | FulltextQueryRequiresFilter
+            | InvalidSqlQuery(_) => true,
             ListValueError(_, _)
             | ResolveEntitiesError(_)
             | RangeArgumentsError(_, _, _)
@@ -289,6 +291,7 @@ impl fmt::Display for QueryExecutionError {
             IdMissing => write!(f, "entity is missing an `id` attribute"),
             IdNotString => write!(f, "entity `id` attribute is not a string"),
             ConstraintViolation(msg) => write!(f, "internal constraint violated: {}", msg),
+            InvalidSqlQuery(msg) => write!(f, "invalid SQL query: {}", msg),
         }
     }
 }
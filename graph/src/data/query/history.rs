@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{r, BlockNumber};
+
+fn default_first() -> u32 {
+    100
+}
+
+/// A request for the versions of the entities of one type that were
+/// written or removed in the blocks `from_block` to `to_block`, both
+/// inclusive. If `id` is set, only versions of that entity are returned
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EntityHistoryQuery {
+    /// The name of the entity type as used in the GraphQL schema
+    pub entity: String,
+    pub id: Option<String>,
+    pub from_block: BlockNumber,
+    pub to_block: BlockNumber,
+    #[serde(default = "default_first")]
+    pub first: u32,
+    #[serde(default)]
+    pub skip: u32,
+}
+
+impl EntityHistoryQuery {
+    pub fn validate(&self, max_first: u32) -> Result<(), String> {
+        if self.from_block > self.to_block {
+            return Err(format!(
+                "fromBlock {} must not be larger than toBlock {}",
+                self.from_block, self.to_block
+            ));
+        }
+        if self.first > max_first {
+            return Err(format!(
+                "first {} is larger than the maximum of {}",
+                self.first, max_first
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// One version of an entity. The version was written at `block_from` and
+/// replaced or removed at `block_to`. If `block_to` is `None`, the version
+/// was still current at the end of the requested block range
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityVersion {
+    pub block_from: BlockNumber,
+    pub block_to: Option<BlockNumber>,
+    pub data: r::Value,
+}
+
+/// The versions matching an `EntityHistoryQuery`, ordered by the block at
+/// which they were written
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct EntityHistory {
+    pub versions: Vec<EntityVersion>,
+}
@@ -0,0 +1,113 @@
+use futures03::stream::BoxStream;
+use serde_json::{json, Value};
+
+use super::QueryResults;
+
+/// The payloads for a query that uses `@defer` or `@stream`. The first
+/// payload is always an `IncrementalPart::Initial`
+pub type IncrementalResults = BoxStream<'static, IncrementalPayload>;
+
+pub enum IncrementalPart {
+    /// The result for all fields that are neither deferred nor streamed,
+    /// including the initial items of streamed fields
+    Initial(QueryResults),
+    /// The result for the fields of a deferred fragment
+    Deferred {
+        label: Option<String>,
+        results: QueryResults,
+    },
+    /// The next batch of items of a streamed field. The items are the list
+    /// under `key` in `results`, and the first of them is the item at
+    /// index `start` of the field
+    Items {
+        label: Option<String>,
+        key: String,
+        start: usize,
+        results: QueryResults,
+    },
+}
+
+/// One part of the response to a query that is delivered incrementally
+pub struct IncrementalPayload {
+    pub part: IncrementalPart,
+    /// Whether more payloads follow this one
+    pub has_next: bool,
+}
+
+impl IncrementalPayload {
+    pub fn new(part: IncrementalPart, has_next: bool) -> Self {
+        IncrementalPayload { part, has_next }
+    }
+
+    /// The JSON for this payload in the format of the GraphQL incremental
+    /// delivery proposal
+    pub fn to_json(&self) -> Value {
+        fn to_value(results: &QueryResults) -> Value {
+            serde_json::to_value(results)
+                .unwrap_or_else(|e| json!({ "errors": [{ "message": e.to_string() }] }))
+        }
+
+        fn incremental(
+            mut entry: serde_json::Map<String, Value>,
+            label: &Option<String>,
+            errors: Option<Value>,
+            has_next: bool,
+        ) -> Value {
+            if let Some(label) = label {
+                entry.insert("label".to_string(), json!(label));
+            }
+            if let Some(errors) = errors {
+                entry.insert("errors".to_string(), errors);
+            }
+            json!({ "incremental": [entry], "hasNext": has_next })
+        }
+
+        match &self.part {
+            IncrementalPart::Initial(results) => {
+                let mut value = to_value(results);
+                if let Some(obj) = value.as_object_mut() {
+                    obj.insert("hasNext".to_string(), json!(self.has_next));
+                }
+                value
+            }
+            IncrementalPart::Deferred { label, results } => {
+                let mut value = to_value(results);
+                let data = value
+                    .get_mut("data")
+                    .map(Value::take)
+                    .unwrap_or_else(|| json!({}));
+                let errors = value.get_mut("errors").map(Value::take);
+
+                let mut entry = serde_json::Map::new();
+                entry.insert("data".to_string(), data);
+                entry.insert("path".to_string(), json!([]));
+                incremental(entry, label, errors, self.has_next)
+            }
+            IncrementalPart::Items {
+                label,
+                key,
+                start,
+                results,
+            } => {
+                let mut value = to_value(results);
+                let items = value
+                    .get_mut("data")
+                    .and_then(|data| data.get_mut(key))
+                    .map(Value::take)
+                    .unwrap_or_else(|| json!([]));
+                let errors = value.get_mut("errors").map(Value::take);
+
+                let mut entry = serde_json::Map::new();
+                entry.insert("items".to_string(), items);
+                entry.insert("path".to_string(), json!([key, start]));
+                incremental(entry, label, errors, self.has_next)
+            }
+        }
+    }
+}
+
+impl From<QueryResults> for IncrementalPayload {
+    fn from(results: QueryResults) -> Self {
+        IncrementalPayload::new(IncrementalPart::Initial(results), false)
+    }
+}
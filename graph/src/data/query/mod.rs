@@ -1,11 +1,17 @@
 mod cache_status;
 mod error;
+mod history;
+mod incremental;
 mod query;
 mod result;
+mod sql;
 mod trace;
 
 pub use self::cache_status::CacheStatus;
 pub use self::error::{QueryError, QueryExecutionError};
+pub use self::history::{EntityHistory, EntityHistoryQuery, EntityVersion};
+pub use self::incremental::{IncrementalPart, IncrementalPayload, IncrementalResults};
 pub use self::query::{Query, QueryTarget, QueryVariables};
 pub use self::result::{QueryResult, QueryResults};
+pub use self::sql::SqlQueryResult;
 pub use self::trace::Trace;
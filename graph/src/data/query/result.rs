@@ -2,7 +2,7 @@ use super::error::{QueryError, QueryExecutionError};
 use super::trace::{HttpTrace, TRACE_NONE};
 use crate::cheap_clone::CheapClone;
 use crate::components::server::query::ServerResponse;
-use crate::data::value::Object;
+use crate::data::value::{Object, Word};
 use crate::derive::CacheWeight;
 use crate::prelude::{r, CacheWeight, DeploymentHash};
 use http_body_util::Full;
@@ -105,6 +105,10 @@ impl Serialize for QueryResults {
         if has_errors {
             len += 1;
         }
+        let is_incomplete = self.results.iter().any(|r| !r.truncated.is_empty());
+        if is_incomplete {
+            len += 1;
+        }
         len += 1;
         let mut state = serializer.serialize_struct("QueryResults", len)?;
 
@@ -141,6 +145,20 @@ impl Serialize for QueryResults {
             state.serialize_field("errors", &SerError(self))?;
         }
 
+        // Tell clients which fields are missing from a partial result
+        if is_incomplete {
+            let truncated: Vec<_> = self
+                .results
+                .iter()
+                .flat_map(|r| r.truncated.iter())
+                .collect();
+            let extensions = serde_json::json!({
+                "incomplete": true,
+                "truncatedFields": truncated,
+            });
+            state.serialize_field("extensions", &extensions)?;
+        }
+
         if !self.trace.is_none() {
             let http = HttpTrace::new(start.elapsed(), self.results.weight());
             state.serialize_field("trace", &self.trace)?;
@@ -202,6 +220,35 @@ impl QueryResults {
         self.results.push(other);
     }
 
+    /// Combine all results into one result whose data is nested under
+    /// `key`. Federated queries use this to put the result of the query
+    /// against one subgraph into that subgraph's namespace
+    pub fn nest(self, key: Word) -> QueryResult {
+        let mut data: Option<Data> = None;
+        let mut errors = Vec::new();
+        let mut truncated = Vec::new();
+        for result in &self.results {
+            if let Some(d) = &result.data {
+                data.get_or_insert_with(Object::empty).append(d.clone());
+            }
+            errors.extend(result.errors.iter().cloned());
+            truncated.extend(
+                result
+                    .truncated
+                    .iter()
+                    .map(|field| format!("{}.{}", key, field)),
+            );
+        }
+        let value = data.map(r::Value::Object).unwrap_or(r::Value::Null);
+        QueryResult {
+            data: Some(Object::from_iter([(key, value)])),
+            errors,
+            deployment: self.deployment_hash().cloned(),
+            trace: TRACE_NONE.cheap_clone(),
+            truncated,
+        }
+    }
+
     pub fn as_http_response(&self) -> ServerResponse {
         let json = serde_json::to_string(&self).unwrap();
         let attestable = self.results.iter().all(|r| r.is_attestable());
@@ -232,6 +279,10 @@ pub struct QueryResult {
     pub deployment: Option<DeploymentHash>,
     #[serde(skip_serializing)]
     pub trace: Arc<Trace>,
+    /// The response keys of top-level fields that are missing from `data`
+    /// because the query timed out
+    #[serde(skip_serializing)]
+    truncated: Vec<String>,
 }
 
 impl QueryResult {
@@ -241,6 +292,7 @@ impl QueryResult {
             errors: Vec::new(),
             deployment: None,
             trace: TRACE_NONE.cheap_clone(),
+            truncated: Vec::new(),
         }
     }
 
@@ -253,6 +305,7 @@ impl QueryResult {
             errors: self.errors.clone(),
             deployment: self.deployment.clone(),
             trace: TRACE_NONE.cheap_clone(),
+            truncated: self.truncated.clone(),
         }
     }
 
@@ -300,6 +353,17 @@ impl QueryResult {
     pub fn data(&self) -> Option<&Data> {
         self.data.as_ref()
     }
+
+    /// Mark this result as incomplete because the query timed out before
+    /// the top-level fields `fields` could be resolved
+    pub fn mark_truncated(&mut self, fields: Vec<String>) {
+        self.errors.push(QueryExecutionError::Timeout.into());
+        self.truncated = fields;
+    }
+
+    pub fn truncated(&self) -> &[String] {
+        &self.truncated
+    }
 }
 
 impl From<QueryExecutionError> for QueryResult {
@@ -309,6 +373,7 @@ impl From<QueryExecutionError> for QueryResult {
             errors: vec![e.into()],
             deployment: None,
             trace: TRACE_NONE.cheap_clone(),
+            truncated: Vec::new(),
         }
     }
 }
@@ -320,6 +385,7 @@ impl From<QueryError> for QueryResult {
             errors: vec![e],
             deployment: None,
             trace: TRACE_NONE.cheap_clone(),
+            truncated: Vec::new(),
         }
     }
 }
@@ -331,6 +397,7 @@ impl From<Vec<QueryExecutionError>> for QueryResult {
             errors: e.into_iter().map(QueryError::from).collect(),
             deployment: None,
             trace: TRACE_NONE.cheap_clone(),
+            truncated: Vec::new(),
         }
     }
 }
@@ -396,3 +463,29 @@ fn multiple_data_items() {
     let actual = serde_json::to_string(&res).unwrap();
     assert_eq!(expected, actual)
 }
+
+// Check that partial results tell clients which fields are missing
+#[test]
+fn truncated_fields() {
+    use serde_json::json;
+
+    let obj = Object::from_iter([(
+        crate::data::value::Word::from("tokens"),
+        r::Value::List(vec![]),
+    )]);
+    let mut partial = QueryResult::new(obj);
+    partial.mark_truncated(vec!["pairs".to_string(), "swaps".to_string()]);
+    assert!(partial.has_errors());
+    assert!(!partial.is_attestable());
+
+    let mut res = QueryResults::empty(Trace::None);
+    res.append(Arc::new(partial), CacheStatus::default());
+
+    let expected = json!({
+        "data": { "tokens": [] },
+        "errors": [{ "message": "Query timed out" }],
+        "extensions": { "incomplete": true, "truncatedFields": ["pairs", "swaps"] }
+    });
+    let actual = serde_json::to_value(&res).unwrap();
+    assert_eq!(expected, actual)
+}
@@ -0,0 +1,9 @@
+use serde::Serialize;
+
+/// The result of running a query against the SQL endpoint. Each row has
+/// one value for each of the `columns`, in the same order
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SqlQueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
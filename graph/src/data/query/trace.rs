@@ -192,6 +192,27 @@ impl Trace {
         }
     }
 
+    /// Add the children of the block trace `other` to this trace. This is
+    /// needed when the fields of a block are fetched separately
+    pub fn merge(&mut self, other: Trace) {
+        if self.is_none() {
+            *self = other;
+            return;
+        }
+        match (self, other) {
+            (_, Trace::None) => { /* nothing to do */ }
+            (
+                Trace::Block { children, .. },
+                Trace::Block {
+                    children: other, ..
+                },
+            ) => children.extend(other),
+            (s, t) => {
+                unreachable!("can not merge self: {:#?} trace: {:#?}", s, t)
+            }
+        }
+    }
+
     pub fn is_none(&self) -> bool {
         match self {
             Trace::None => true,
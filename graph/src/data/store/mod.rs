@@ -141,6 +141,7 @@ pub const BIG_INT_SCALAR: &str = "BigInt";
 pub const BIG_DECIMAL_SCALAR: &str = "BigDecimal";
 pub const INT8_SCALAR: &str = "Int8";
 pub const TIMESTAMP_SCALAR: &str = "Timestamp";
+pub const GEO_POINT_SCALAR: &str = "GeoPoint";
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ValueType {
@@ -152,6 +153,7 @@ pub enum ValueType {
     Int8,
     String,
     Timestamp,
+    GeoPoint,
 }
 
 impl FromStr for ValueType {
@@ -166,6 +168,7 @@ impl FromStr for ValueType {
             "Int" => Ok(ValueType::Int),
             "Int8" => Ok(ValueType::Int8),
             "Timestamp" => Ok(ValueType::Timestamp),
+            "GeoPoint" => Ok(ValueType::GeoPoint),
             "String" | "ID" => Ok(ValueType::String),
             s => Err(anyhow!("Type not available in this context: {}", s)),
         }
@@ -181,9 +184,11 @@ impl ValueType {
     pub fn is_numeric(&self) -> bool {
         match self {
             ValueType::BigInt | ValueType::BigDecimal | ValueType::Int | ValueType::Int8 => true,
-            ValueType::Boolean | ValueType::Bytes | ValueType::String | ValueType::Timestamp => {
-                false
-            }
+            ValueType::Boolean
+            | ValueType::Bytes
+            | ValueType::String
+            | ValueType::Timestamp
+            | ValueType::GeoPoint => false,
         }
     }
 
@@ -196,6 +201,7 @@ impl ValueType {
             ValueType::Int => "Int",
             ValueType::Int8 => "Int8",
             ValueType::Timestamp => "Timestamp",
+            ValueType::GeoPoint => "GeoPoint",
             ValueType::String => "String",
         }
     }
@@ -230,6 +236,8 @@ impl PartialOrd for ValueType {
             | (Int8, Int) => Some(Greater),
             (Timestamp, _)
             | (_, Timestamp)
+            | (GeoPoint, _)
+            | (_, GeoPoint)
             | (Boolean, _)
             | (_, Boolean)
             | (Bytes, _)
@@ -262,6 +270,7 @@ pub enum Value {
     Null,
     Bytes(scalar::Bytes),
     BigInt(scalar::BigInt),
+    GeoPoint(scalar::GeoPoint),
 }
 
 pub const NULL: Value = Value::Null;
@@ -314,6 +323,9 @@ impl stable_hash_legacy::StableHash for Value {
             Timestamp(inner) => {
                 stable_hash_legacy::StableHash::stable_hash(inner, sequence_number, state)
             }
+            GeoPoint(inner) => {
+                stable_hash_legacy::StableHash::stable_hash(inner, sequence_number, state)
+            }
         }
     }
 }
@@ -365,6 +377,10 @@ impl StableHash for Value {
                 inner.stable_hash(field_address.child(0), state);
                 9
             }
+            GeoPoint(inner) => {
+                inner.stable_hash(field_address.child(0), state);
+                10
+            }
         };
 
         state.write(field_address, &[variant])
@@ -419,6 +435,14 @@ impl Value {
                             )
                         })?)
                     }
+                    GEO_POINT_SCALAR => {
+                        Value::GeoPoint(scalar::GeoPoint::parse(s).map_err(|e| {
+                            QueryExecutionError::ValueParseError(
+                                "GeoPoint".to_string(),
+                                e.to_string(),
+                            )
+                        })?)
+                    }
                     _ => Value::String(s.clone()),
                 }
             }
@@ -511,6 +535,29 @@ impl Value {
         }
     }
 
+    pub fn as_geo_point(&self) -> Option<scalar::GeoPoint> {
+        if let Value::GeoPoint(p) = self {
+            Some(*p)
+        } else {
+            None
+        }
+    }
+
+    /// Convert strings of the form `latitude,longitude`, also when they are
+    /// elements of a list, into `GeoPoint`s. All other values are returned
+    /// unchanged
+    pub fn into_geo_point(self) -> Result<Value, scalar::GeoPointError> {
+        match self {
+            Value::String(s) => scalar::GeoPoint::parse(&s).map(Value::GeoPoint),
+            Value::List(values) => values
+                .into_iter()
+                .map(Value::into_geo_point)
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::List),
+            value => Ok(value),
+        }
+    }
+
     /// Return the name of the type of this value for display to the user
     pub fn type_name(&self) -> String {
         match self {
@@ -521,6 +568,7 @@ impl Value {
             Value::Int(_) => "Int".to_owned(),
             Value::Int8(_) => "Int8".to_owned(),
             Value::Timestamp(_) => "Timestamp".to_owned(),
+            Value::GeoPoint(_) => "GeoPoint".to_owned(),
             Value::List(values) => {
                 if let Some(v) = values.first() {
                     format!("[{}]", v.type_name())
@@ -543,6 +591,7 @@ impl Value {
             | (Value::Int(_), ValueType::Int)
             | (Value::Int8(_), ValueType::Int8)
             | (Value::Timestamp(_), ValueType::Timestamp)
+            | (Value::GeoPoint(_), ValueType::GeoPoint)
             | (Value::Null, _) => true,
             (Value::List(values), _) if is_list => values
                 .iter()
@@ -573,6 +622,7 @@ impl fmt::Display for Value {
                     format!("[{}]", values.iter().map(ToString::to_string).join(", ")),
                 Value::Bytes(ref bytes) => bytes.to_string(),
                 Value::BigInt(ref number) => number.to_string(),
+                Value::GeoPoint(ref point) => point.to_string(),
             }
         )
     }
@@ -591,6 +641,7 @@ impl fmt::Debug for Value {
             Self::Null => write!(f, "Null"),
             Self::Bytes(bytes) => bytes.fmt(f),
             Self::BigInt(number) => number.fmt(f),
+            Self::GeoPoint(point) => f.debug_tuple("GeoPoint").field(&point.to_string()).finish(),
         }
     }
 }
@@ -610,6 +661,7 @@ impl From<Value> for q::Value {
             }
             Value::Bytes(bytes) => q::Value::String(bytes.to_string()),
             Value::BigInt(number) => q::Value::String(number.to_string()),
+            Value::GeoPoint(point) => q::Value::String(point.to_string()),
         }
     }
 }
@@ -629,6 +681,7 @@ impl From<Value> for r::Value {
             }
             Value::Bytes(bytes) => r::Value::String(bytes.to_string()),
             Value::BigInt(number) => r::Value::String(number.to_string()),
+            Value::GeoPoint(point) => r::Value::String(point.to_string()),
         }
     }
 }
@@ -663,6 +716,12 @@ impl From<scalar::Timestamp> for Value {
     }
 }
 
+impl From<scalar::GeoPoint> for Value {
+    fn from(value: scalar::GeoPoint) -> Value {
+        Value::GeoPoint(value)
+    }
+}
+
 impl From<bool> for Value {
     fn from(value: bool) -> Value {
         Value::Bool(value)
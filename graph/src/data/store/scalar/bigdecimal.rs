@@ -1,7 +1,8 @@
 use diesel::deserialize::FromSqlRow;
 use diesel::expression::AsExpression;
 use num_bigint::{self, ToBigInt};
-use num_traits::FromPrimitive;
+use num_integer::Integer;
+use num_traits::{FromPrimitive, One, Signed};
 use serde::{self, Deserialize, Serialize};
 use stable_hash::{FieldAddress, StableHash};
 use stable_hash_legacy::SequenceNumber;
@@ -17,6 +18,62 @@ pub use old_bigdecimal::ToPrimitive;
 
 use super::BigInt;
 
+/// How a `BigDecimal` is rounded when it has more significant digits than
+/// the precision allows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BigDecimalRounding {
+    /// The rounding that `BigDecimal` has always used. It is kept as the
+    /// default since changing it would change the PoI of existing
+    /// subgraphs
+    #[default]
+    Legacy,
+    /// Round to the nearest value, and away from zero on a tie
+    HalfUp,
+    /// Round to the nearest value, and to the even neighbor on a tie
+    HalfEven,
+    /// Drop the extra digits, i.e., round towards zero
+    Down,
+}
+
+/// The number of significant digits and the rounding that a deployment
+/// uses for `BigDecimal` values it creates in mappings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BigDecimalPrecision {
+    #[serde(default = "BigDecimalPrecision::default_digits")]
+    pub precision: u64,
+    #[serde(default)]
+    pub rounding: BigDecimalRounding,
+}
+
+impl BigDecimalPrecision {
+    fn default_digits() -> u64 {
+        BigDecimal::MAX_SIGNFICANT_DIGITS as u64
+    }
+
+    /// Check that the precision is within the range we support
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.precision == 0 || self.precision > BigDecimal::MAX_PRECISION {
+            return Err(anyhow!(
+                "the BigDecimal precision must be between 1 and {} but is {}",
+                BigDecimal::MAX_PRECISION,
+                self.precision
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for BigDecimalPrecision {
+    fn default() -> Self {
+        BigDecimalPrecision {
+            precision: Self::default_digits(),
+            rounding: BigDecimalRounding::Legacy,
+        }
+    }
+}
+
 /// All operations on `BigDecimal` return a normalized value.
 // Caveat: The exponent is currently an i64 and may overflow. See
 // https://github.com/akubera/bigdecimal-rs/issues/54.
@@ -40,12 +97,61 @@ impl BigDecimal {
     pub const MIN_EXP: i32 = -6143;
     pub const MAX_EXP: i32 = 6144;
     pub const MAX_SIGNFICANT_DIGITS: i32 = 34;
+    /// The largest precision a deployment can ask for. Division is
+    /// computed with 100 significant digits, and a larger precision would
+    /// therefore not lead to more accurate results
+    pub const MAX_PRECISION: u64 = 100;
 
     pub fn new(digits: BigInt, exp: i64) -> Self {
         // bigdecimal uses `scale` as the opposite of the power of ten, so negate `exp`.
         Self::from(OldBigDecimal::new(digits.inner(), -exp))
     }
 
+    /// Like `new`, but round to the given `precision` instead of the
+    /// default number of significant digits
+    pub fn new_with_precision(digits: BigInt, exp: i64, precision: &BigDecimalPrecision) -> Self {
+        BigDecimal(OldBigDecimal::new(digits.inner(), -exp)).round(precision)
+    }
+
+    /// Like `from_str`, but round to the given `precision` instead of the
+    /// default number of significant digits
+    pub fn from_str_with_precision(
+        s: &str,
+        precision: &BigDecimalPrecision,
+    ) -> Result<Self, <OldBigDecimal as FromStr>::Err> {
+        Ok(BigDecimal(OldBigDecimal::from_str(s)?).round(precision))
+    }
+
+    /// Parse `s` without rounding it to the default precision. This is
+    /// meant for values loaded from the store, which were already rounded
+    /// to the precision of the deployment when they were created
+    pub fn from_str_unrounded(s: &str) -> Result<Self, <OldBigDecimal as FromStr>::Err> {
+        let value = BigDecimal(OldBigDecimal::from_str(s)?);
+        if value == BigDecimal::zero() {
+            return Ok(BigDecimal::zero());
+        }
+        Ok(Self::strip_zeros(&value.0))
+    }
+
+    pub fn add_with_precision(&self, other: &BigDecimal, precision: &BigDecimalPrecision) -> Self {
+        BigDecimal(self.0.clone().add(other.0.clone())).round(precision)
+    }
+
+    pub fn sub_with_precision(&self, other: &BigDecimal, precision: &BigDecimalPrecision) -> Self {
+        BigDecimal(self.0.clone().sub(other.0.clone())).round(precision)
+    }
+
+    pub fn mul_with_precision(&self, other: &BigDecimal, precision: &BigDecimalPrecision) -> Self {
+        BigDecimal(self.0.clone().mul(other.0.clone())).round(precision)
+    }
+
+    pub fn div_with_precision(&self, other: &BigDecimal, precision: &BigDecimalPrecision) -> Self {
+        if other == &BigDecimal::zero() {
+            panic!("Cannot divide by zero-valued `BigDecimal`!")
+        }
+        BigDecimal(self.0.clone().div(other.0.clone())).round(precision)
+    }
+
     pub fn parse_bytes(bytes: &[u8]) -> Option<Self> {
         OldBigDecimal::parse_bytes(bytes, 10).map(Self)
     }
@@ -88,14 +194,28 @@ impl BigDecimal {
     // is included in a released version supported by Diesel.
     #[must_use]
     pub fn normalized(&self) -> BigDecimal {
+        self.round(&BigDecimalPrecision::default())
+    }
+
+    /// Round `self` to `precision` significant digits and strip trailing
+    /// zeros. With the default precision, this is the same as `normalized`
+    #[must_use]
+    pub fn round(&self, precision: &BigDecimalPrecision) -> BigDecimal {
         if self == &BigDecimal::zero() {
             return BigDecimal::zero();
         }
 
         // Round to the maximum significant digits.
-        let big_decimal = self.0.with_prec(Self::MAX_SIGNFICANT_DIGITS as u64);
+        let big_decimal = match precision.rounding {
+            BigDecimalRounding::Legacy => self.0.with_prec(precision.precision),
+            rounding => Self::round_digits(&self.0, precision.precision, rounding),
+        };
+
+        Self::strip_zeros(&big_decimal)
+    }
 
-        let (bigint, exp) = big_decimal.as_bigint_and_exponent();
+    fn strip_zeros(value: &OldBigDecimal) -> BigDecimal {
+        let (bigint, exp) = value.as_bigint_and_exponent();
         let (sign, mut digits) = bigint.to_radix_be(10);
         let trailing_count = digits.iter().rev().take_while(|i| **i == 0).count();
         digits.truncate(digits.len() - trailing_count);
@@ -104,6 +224,40 @@ impl BigDecimal {
 
         BigDecimal(OldBigDecimal::new(int_val, scale))
     }
+
+    fn round_digits(
+        value: &OldBigDecimal,
+        precision: u64,
+        rounding: BigDecimalRounding,
+    ) -> OldBigDecimal {
+        let digits = value.digits();
+        if digits <= precision {
+            return value.clone();
+        }
+        let (int_val, scale) = value.as_bigint_and_exponent();
+        let diff = digits - precision;
+        let divisor = num_traits::pow(num_bigint::BigInt::from(10), diff as usize);
+        // `div_rem` truncates towards zero, and the remainder has the same
+        // sign as `int_val`
+        let (mut quot, rem) = int_val.div_rem(&divisor);
+        let twice_rem = rem.abs() * num_bigint::BigInt::from(2);
+        let away_from_zero = match rounding {
+            BigDecimalRounding::Down => false,
+            BigDecimalRounding::HalfUp => twice_rem >= divisor,
+            BigDecimalRounding::HalfEven => {
+                twice_rem > divisor || (twice_rem == divisor && quot.is_odd())
+            }
+            BigDecimalRounding::Legacy => unreachable!("legacy rounding uses `with_prec`"),
+        };
+        if away_from_zero {
+            if int_val.is_negative() {
+                quot -= num_bigint::BigInt::one();
+            } else {
+                quot += num_bigint::BigInt::one();
+            }
+        }
+        OldBigDecimal::new(quot, scale - diff as i64)
+    }
 }
 
 impl Display for BigDecimal {
@@ -676,6 +830,66 @@ mod test {
         }
     }
 
+    #[test]
+    fn round_with_precision() {
+        use super::{BigDecimalPrecision, BigDecimalRounding};
+
+        let prec = |precision, rounding| BigDecimalPrecision {
+            precision,
+            rounding,
+        };
+        let round = |s: &str, precision: &BigDecimalPrecision| {
+            BigDecimal::from_str_with_precision(s, precision)
+                .unwrap()
+                .to_string()
+        };
+
+        // The default precision is the same as what `from_str` does
+        let long = "1.23456789012345678901234567890123456789";
+        assert_eq!(
+            BigDecimal::from_str(long).unwrap(),
+            BigDecimal::from_str_with_precision(long, &BigDecimalPrecision::default()).unwrap()
+        );
+        assert_eq!(long, round(long, &prec(50, BigDecimalRounding::Legacy)));
+        assert_eq!(
+            "1.2345678901",
+            round(long, &prec(11, BigDecimalRounding::HalfUp))
+        );
+
+        let cases = [
+            ("2.5", "3", "2", "2"),
+            ("3.5", "4", "4", "3"),
+            ("-2.5", "-3", "-2", "-2"),
+            ("2.49", "2", "2", "2"),
+            ("9.5", "10", "10", "9"),
+        ];
+        for (value, half_up, half_even, down) in cases {
+            assert_eq!(
+                half_up,
+                round(value, &prec(1, BigDecimalRounding::HalfUp)),
+                "{value}"
+            );
+            assert_eq!(
+                half_even,
+                round(value, &prec(1, BigDecimalRounding::HalfEven)),
+                "{value}"
+            );
+            assert_eq!(
+                down,
+                round(value, &prec(1, BigDecimalRounding::Down)),
+                "{value}"
+            );
+        }
+
+        let one = BigDecimal::from(1);
+        let three = BigDecimal::from(3);
+        let third = one.div_with_precision(&three, &prec(60, BigDecimalRounding::HalfEven));
+        assert_eq!(format!("0.{}", "3".repeat(60)), third.to_string());
+        assert!(BigDecimalPrecision::default().validate().is_ok());
+        assert!(prec(0, BigDecimalRounding::Legacy).validate().is_err());
+        assert!(prec(101, BigDecimalRounding::Legacy).validate().is_err());
+    }
+
     #[test]
     fn fmt_debug() {
         let bi = BigInt::from(-17);
@@ -0,0 +1,325 @@
+use serde::{self, Deserialize, Serialize};
+use stable_hash::{FieldAddress, StableHash};
+use thiserror::Error;
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::derive::CacheWeight;
+use crate::runtime::gas::{Gas, GasSizeOf, SaturatingInto};
+
+/// The number of decimal digits we keep for latitude and longitude. Seven
+/// digits give a resolution of about one centimeter, which is more than
+/// enough for anything that gets indexed from a chain
+const SCALE_DIGITS: u32 = 7;
+const SCALE: i64 = 10i64.pow(SCALE_DIGITS);
+
+/// The mean earth radius in meters, as used by PostGIS for spherical
+/// distance calculations
+pub const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum GeoPointError {
+    #[error("invalid GeoPoint `{0}`: expected `latitude,longitude`")]
+    InvalidFormat(String),
+    #[error("invalid GeoPoint `{0}`: `{1}` is not a decimal number")]
+    InvalidNumber(String, String),
+    #[error("invalid GeoPoint `{0}`: latitude must be between -90 and 90")]
+    LatitudeOutOfRange(String),
+    #[error("invalid GeoPoint `{0}`: longitude must be between -180 and 180")]
+    LongitudeOutOfRange(String),
+}
+
+/// A point on the earth's surface, given by its latitude and longitude in
+/// degrees (WGS 84). The string representation is `latitude,longitude`,
+/// for example `40.7128,-74.006`.
+///
+/// Coordinates are kept as fixed point numbers with `SCALE_DIGITS`
+/// decimal places so that parsing, formatting and hashing of points is
+/// deterministic and does not depend on floating point rounding
+#[derive(Clone, Copy, CacheWeight, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GeoPoint {
+    lat: i32,
+    lng: i32,
+}
+
+impl GeoPoint {
+    /// Parse a point from its `latitude,longitude` representation
+    pub fn parse(s: &str) -> Result<Self, GeoPointError> {
+        let (lat, lng) = s
+            .split_once(',')
+            .ok_or_else(|| GeoPointError::InvalidFormat(s.to_string()))?;
+        let lat = parse_fixed(lat.trim())
+            .ok_or_else(|| GeoPointError::InvalidNumber(s.to_string(), lat.trim().to_string()))?;
+        let lng = parse_fixed(lng.trim())
+            .ok_or_else(|| GeoPointError::InvalidNumber(s.to_string(), lng.trim().to_string()))?;
+        if lat.abs() > 90 * SCALE {
+            return Err(GeoPointError::LatitudeOutOfRange(s.to_string()));
+        }
+        if lng.abs() > 180 * SCALE {
+            return Err(GeoPointError::LongitudeOutOfRange(s.to_string()));
+        }
+        Ok(GeoPoint {
+            lat: lat as i32,
+            lng: lng as i32,
+        })
+    }
+
+    /// Parse a point from the text representation of a Postgres `point`,
+    /// which is `(x,y)`, i.e., `(longitude,latitude)`
+    pub fn from_pg_point(s: &str) -> Result<Self, GeoPointError> {
+        let inner = s
+            .trim()
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or_else(|| GeoPointError::InvalidFormat(s.to_string()))?;
+        let (lng, lat) = inner
+            .split_once(',')
+            .ok_or_else(|| GeoPointError::InvalidFormat(s.to_string()))?;
+        Self::parse(&format!("{},{}", lat, lng))
+    }
+
+    /// The text representation of this point as a Postgres `point`
+    pub fn to_pg_point(&self) -> String {
+        format!("({},{})", format_fixed(self.lng), format_fixed(self.lat))
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.lat as f64 / SCALE as f64
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.lng as f64 / SCALE as f64
+    }
+
+    /// The great-circle distance between `self` and `other` in meters,
+    /// using the haversine formula on a spherical earth
+    pub fn distance(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.latitude().to_radians(), other.latitude().to_radians());
+        let dlat = lat2 - lat1;
+        let dlng = (other.longitude() - self.longitude()).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlng / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_METERS * a.sqrt().asin()
+    }
+
+    /// Return the south-west and north-east corners of boxes that
+    /// together contain all points that are at most `meters` away from
+    /// `self`. That is one box, or two if the points cross the
+    /// antimeridian at ±180° longitude, one on each side of it. The boxes
+    /// are only used to narrow down candidates with an index, and are
+    /// therefore allowed to be larger than strictly necessary
+    pub fn bounding_boxes(&self, meters: f64) -> Vec<(GeoPoint, GeoPoint)> {
+        let dlat = (meters / EARTH_RADIUS_METERS).to_degrees();
+        let cos_lat = self.latitude().to_radians().cos();
+        let dlng = if cos_lat <= f64::EPSILON || self.latitude().abs() + dlat >= 90.0 {
+            // Close to the poles, every longitude is nearby
+            180.0
+        } else {
+            (dlat / cos_lat).min(180.0)
+        };
+        let fixed = |v: f64, max: f64| (v.clamp(-max, max) * SCALE as f64).round() as i32;
+        let south = fixed(self.latitude() - dlat, 90.0);
+        let north = fixed(self.latitude() + dlat, 90.0);
+        let (west, east) = (self.longitude() - dlng, self.longitude() + dlng);
+        let lngs = if dlng >= 180.0 {
+            vec![(-180.0, 180.0)]
+        } else if west < -180.0 {
+            vec![(west + 360.0, 180.0), (-180.0, east)]
+        } else if east > 180.0 {
+            vec![(west, 180.0), (-180.0, east - 360.0)]
+        } else {
+            vec![(west, east)]
+        };
+        lngs.into_iter()
+            .map(|(west, east)| {
+                let sw = GeoPoint {
+                    lat: south,
+                    lng: fixed(west, 180.0),
+                };
+                let ne = GeoPoint {
+                    lat: north,
+                    lng: fixed(east, 180.0),
+                };
+                (sw, ne)
+            })
+            .collect()
+    }
+
+    /// Return `true` if `self` lies within the box with the given
+    /// south-west and north-east corners
+    pub fn within_box(&self, sw: &GeoPoint, ne: &GeoPoint) -> bool {
+        sw.lat <= self.lat && self.lat <= ne.lat && sw.lng <= self.lng && self.lng <= ne.lng
+    }
+}
+
+/// Parse a decimal number into a fixed point number with `SCALE_DIGITS`
+/// decimal places. Additional digits are rounded half away from zero.
+/// Numbers in exponential notation, which Postgres uses when it prints
+/// very small values, go through `f64`
+fn parse_fixed(s: &str) -> Option<i64> {
+    if s.contains(['e', 'E']) {
+        let f: f64 = s.parse().ok()?;
+        return f.is_finite().then(|| (f * SCALE as f64).round() as i64);
+    }
+    let (neg, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    if (int.is_empty() && frac.is_empty())
+        || int.len() > 3
+        || !int.chars().all(|c| c.is_ascii_digit())
+        || !frac.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let mut value: i64 = if int.is_empty() { 0 } else { int.parse().ok()? };
+    let mut digits = frac.chars();
+    for _ in 0..SCALE_DIGITS {
+        let d = digits.next().and_then(|c| c.to_digit(10)).unwrap_or(0);
+        value = value * 10 + d as i64;
+    }
+    if digits.next().and_then(|c| c.to_digit(10)).unwrap_or(0) >= 5 {
+        value += 1;
+    }
+    Some(if neg { -value } else { value })
+}
+
+fn format_fixed(v: i32) -> String {
+    let sign = if v < 0 { "-" } else { "" };
+    let v = (v as i64).abs();
+    let int = v / SCALE;
+    let frac = v % SCALE;
+    if frac == 0 {
+        format!("{sign}{int}")
+    } else {
+        let frac = format!("{:0width$}", frac, width = SCALE_DIGITS as usize);
+        format!("{sign}{int}.{}", frac.trim_end_matches('0'))
+    }
+}
+
+impl FromStr for GeoPoint {
+    type Err = GeoPointError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for GeoPoint {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{},{}", format_fixed(self.lat), format_fixed(self.lng))
+    }
+}
+
+impl Serialize for GeoPoint {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GeoPoint {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let s = <String>::deserialize(deserializer)?;
+        GeoPoint::parse(&s).map_err(D::Error::custom)
+    }
+}
+
+impl StableHash for GeoPoint {
+    fn stable_hash<H: stable_hash::StableHasher>(&self, field_address: H::Addr, state: &mut H) {
+        self.lat.stable_hash(field_address.child(0), state);
+        self.lng.stable_hash(field_address.child(1), state);
+    }
+}
+
+impl stable_hash_legacy::StableHash for GeoPoint {
+    fn stable_hash<H: stable_hash_legacy::StableHasher>(
+        &self,
+        mut sequence_number: H::Seq,
+        state: &mut H,
+    ) {
+        use stable_hash_legacy::prelude::*;
+
+        stable_hash_legacy::StableHash::stable_hash(&self.lat, sequence_number.next_child(), state);
+        stable_hash_legacy::StableHash::stable_hash(&self.lng, sequence_number.next_child(), state);
+    }
+}
+
+impl GasSizeOf for GeoPoint {
+    fn const_gas_size_of() -> Option<Gas> {
+        Some(Gas::new(std::mem::size_of::<GeoPoint>().saturating_into()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GeoPoint, GeoPointError};
+
+    #[test]
+    fn parse_and_format() {
+        let p = GeoPoint::parse("40.7128,-74.006").unwrap();
+        assert_eq!("40.7128,-74.006", p.to_string());
+        assert_eq!("(-74.006,40.7128)", p.to_pg_point());
+        assert_eq!(p, GeoPoint::from_pg_point("(-74.006,40.7128)").unwrap());
+
+        let p = GeoPoint::parse(" -0.5 , 180 ").unwrap();
+        assert_eq!("-0.5,180", p.to_string());
+
+        // Digits beyond the seventh get rounded
+        let p = GeoPoint::parse("1.123456789,2.00000004").unwrap();
+        assert_eq!("1.1234568,2", p.to_string());
+
+        assert!(matches!(
+            GeoPoint::parse("40.7128"),
+            Err(GeoPointError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            GeoPoint::parse("north,1"),
+            Err(GeoPointError::InvalidNumber(_, _))
+        ));
+        assert!(matches!(
+            GeoPoint::parse("90.1,1"),
+            Err(GeoPointError::LatitudeOutOfRange(_))
+        ));
+        assert!(matches!(
+            GeoPoint::parse("1,-180.0000001"),
+            Err(GeoPointError::LongitudeOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn distance_and_box() {
+        let nyc = GeoPoint::parse("40.7128,-74.006").unwrap();
+        let london = GeoPoint::parse("51.5074,-0.1278").unwrap();
+        let d = nyc.distance(&london);
+        assert!((d - 5_570_000.0).abs() < 10_000.0, "distance was {d}");
+
+        let boxes = nyc.bounding_boxes(1000.0);
+        assert_eq!(1, boxes.len());
+        let (sw, ne) = &boxes[0];
+        assert!(nyc.within_box(&sw, &ne));
+        assert!(!london.within_box(&sw, &ne));
+        let nearby = GeoPoint::parse("40.7150,-74.0080").unwrap();
+        assert!(nearby.within_box(&sw, &ne));
+        assert!(nyc.distance(&nearby) < 1000.0);
+    }
+
+    #[test]
+    fn box_across_antimeridian() {
+        let east = GeoPoint::parse("0,179.9").unwrap();
+        let west = GeoPoint::parse("0,-179.9").unwrap();
+        let d = east.distance(&west);
+        assert!((d - 22_239.0).abs() < 10.0, "distance was {d}");
+
+        for (center, other) in [(&east, &west), (&west, &east)] {
+            let boxes = center.bounding_boxes(30_000.0);
+            assert_eq!(2, boxes.len());
+            assert!(boxes.iter().any(|(sw, ne)| center.within_box(sw, ne)));
+            assert!(boxes.iter().any(|(sw, ne)| other.within_box(sw, ne)));
+            let far = GeoPoint::parse("0,0").unwrap();
+            assert!(!boxes.iter().any(|(sw, ne)| far.within_box(sw, ne)));
+        }
+    }
+}
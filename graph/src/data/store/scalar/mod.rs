@@ -1,11 +1,13 @@
 mod bigdecimal;
 mod bigint;
 mod bytes;
+mod geo_point;
 mod timestamp;
 
-pub use bigdecimal::BigDecimal;
+pub use bigdecimal::{BigDecimal, BigDecimalPrecision, BigDecimalRounding};
 pub use bigint::{BigInt, BigIntSign};
 pub use bytes::Bytes;
+pub use geo_point::{GeoPoint, GeoPointError, EARTH_RADIUS_METERS};
 pub use old_bigdecimal::ToPrimitive;
 pub use timestamp::Timestamp;
 
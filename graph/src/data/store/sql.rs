@@ -67,6 +67,9 @@ impl ToSql<Text, Pg> for Value {
             Value::Bytes(h) => {
                 <String as ToSql<Text, Pg>>::to_sql(&h.to_string(), &mut out.reborrow())
             }
+            Value::GeoPoint(p) => {
+                <String as ToSql<Text, Pg>>::to_sql(&p.to_pg_point(), &mut out.reborrow())
+            }
             v => Err(anyhow!(
                 "Failed to convert attribute value to String or Bytes in SQL: {}",
                 v
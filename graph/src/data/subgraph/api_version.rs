@@ -21,6 +21,19 @@ pub const API_VERSION_0_0_8: Version = Version::new(0, 0, 8);
 /// Enables new host function `eth_get_balance`
 pub const API_VERSION_0_0_9: Version = Version::new(0, 0, 9);
 
+/// Supports mappings built with recent AssemblyScript releases and their incremental garbage
+/// collector. The host allocates objects through the exported runtime of the module instead of
+/// writing object headers itself.
+pub const API_VERSION_0_0_10: Version = Version::new(0, 0, 10);
+
+/// Adds withdrawals, the parent beacon block root and the blob gas fields to the Ethereum block,
+/// and the fields of L2 transaction types and the L1 fees of L2s to transactions and receipts.
+/// Transactions carry their access list, and receipts the transaction type and the effective gas
+/// price. Events carry the topics and data of their log, and data sources can have a fallback
+/// event handler for logs that no event handler decodes along with `ethereum.decodeEvent`.
+/// Enables the host function `ethereum.getProof` for verified reads of contract storage.
+pub const API_VERSION_0_0_11: Version = Version::new(0, 0, 11);
+
 /// Before this check was introduced, there were already subgraphs in the wild with spec version
 /// 0.0.3, due to confusion with the api version. To avoid breaking those, we accept 0.0.3 though it
 /// doesn't exist.
@@ -54,8 +67,17 @@ pub const SPEC_VERSION_1_1_0: Version = Version::new(1, 1, 0);
 // Enables eth call declarations and indexed arguments(topics) filtering in manifest
 pub const SPEC_VERSION_1_2_0: Version = Version::new(1, 2, 0);
 
+// Enables configuring the `bigDecimal` precision and rounding in the manifest
+pub const SPEC_VERSION_1_3_0: Version = Version::new(1, 3, 0);
+
+// Enables subgraph data sources
+pub const SPEC_VERSION_1_4_0: Version = Version::new(1, 4, 0);
+
+// Enables declaring the `source` pattern and the `context` of offchain data source templates
+pub const SPEC_VERSION_1_5_0: Version = Version::new(1, 5, 0);
+
 // The latest spec version available
-pub const LATEST_VERSION: &Version = &SPEC_VERSION_1_2_0;
+pub const LATEST_VERSION: &Version = &SPEC_VERSION_1_5_0;
 
 pub const MIN_SPEC_VERSION: Version = Version::new(0, 0, 2);
 
@@ -74,7 +96,7 @@ impl UnifiedMappingApiVersion {
         }
     }
 
-    pub(super) fn try_from_versions(
+    pub(crate) fn try_from_versions(
         versions: impl Iterator<Item = Version>,
     ) -> Result<Self, DifferentMappingApiVersions> {
         let unique_versions: BTreeSet<Version> = versions.collect();
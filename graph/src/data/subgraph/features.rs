@@ -13,6 +13,7 @@
 use crate::{
     blockchain::Blockchain,
     data::subgraph::SubgraphManifest,
+    env::ENV_VARS,
     prelude::{Deserialize, Serialize},
     schema::InputSchema,
 };
@@ -41,6 +42,53 @@ pub enum SubgraphFeature {
     IpfsOnEthereumContracts,
 }
 
+impl SubgraphFeature {
+    pub const ALL: [SubgraphFeature; 8] = [
+        SubgraphFeature::NonFatalErrors,
+        SubgraphFeature::Grafting,
+        SubgraphFeature::FullTextSearch,
+        SubgraphFeature::Aggregations,
+        SubgraphFeature::BytesAsIds,
+        SubgraphFeature::DeclaredEthCalls,
+        SubgraphFeature::ImmutableEntities,
+        SubgraphFeature::IpfsOnEthereumContracts,
+    ];
+
+    /// The environment variable that enables the feature on a node, for
+    /// features that are off unless the operator turns them on
+    pub fn config_flag(&self) -> Option<&'static str> {
+        match self {
+            SubgraphFeature::FullTextSearch => {
+                Some("GRAPH_ALLOW_NON_DETERMINISTIC_FULLTEXT_SEARCH")
+            }
+            SubgraphFeature::IpfsOnEthereumContracts => Some("GRAPH_ALLOW_NON_DETERMINISTIC_IPFS"),
+            SubgraphFeature::NonFatalErrors
+            | SubgraphFeature::Grafting
+            | SubgraphFeature::Aggregations
+            | SubgraphFeature::BytesAsIds
+            | SubgraphFeature::DeclaredEthCalls
+            | SubgraphFeature::ImmutableEntities => None,
+        }
+    }
+
+    /// Whether this node is configured to index subgraphs that use the
+    /// feature
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            SubgraphFeature::FullTextSearch => ENV_VARS.allow_non_deterministic_fulltext_search,
+            SubgraphFeature::IpfsOnEthereumContracts => {
+                ENV_VARS.mappings.allow_non_deterministic_ipfs
+            }
+            SubgraphFeature::NonFatalErrors
+            | SubgraphFeature::Grafting
+            | SubgraphFeature::Aggregations
+            | SubgraphFeature::BytesAsIds
+            | SubgraphFeature::DeclaredEthCalls
+            | SubgraphFeature::ImmutableEntities => true,
+        }
+    }
+}
+
 impl fmt::Display for SubgraphFeature {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         serde_plain::to_string(self)
@@ -182,4 +230,13 @@ mod tests {
             assert_eq!(SubgraphFeature::from_str(string).unwrap(), *variant)
         }
     }
+
+    #[test]
+    fn subgraph_feature_all() {
+        for string in STRING {
+            let feature = SubgraphFeature::from_str(string).unwrap();
+            assert!(SubgraphFeature::ALL.contains(&feature));
+        }
+        assert_eq!(SubgraphFeature::ALL.len(), STRING.len());
+    }
 }
@@ -39,11 +39,14 @@ use crate::{
         store::{StoreError, SubgraphStore},
     },
     data::{
-        graphql::TryFromValue, query::QueryExecutionError,
-        subgraph::features::validate_subgraph_features,
+        graphql::TryFromValue,
+        query::QueryExecutionError,
+        store::scalar::BigDecimalPrecision,
+        subgraph::features::{detect_features, validate_subgraph_features},
     },
     data_source::{
-        offchain::OFFCHAIN_KINDS, DataSource, DataSourceTemplate, UnresolvedDataSource,
+        offchain::{OffchainDataSourceKind, OFFCHAIN_KINDS},
+        subgraph, DataSource, DataSourceTemplate, UnresolvedDataSource,
         UnresolvedDataSourceTemplate,
     },
     derive::CacheWeight,
@@ -378,6 +381,10 @@ impl DataSourceContext {
         Self(HashMap::new())
     }
 
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(&Word::from(key))
+    }
+
     // This collects the entries into an ordered vector so that it can be iterated deterministically.
     pub fn sorted(self) -> Vec<(Word, Value)> {
         let mut v: Vec<_> = self.0.into_iter().collect();
@@ -464,6 +471,11 @@ pub fn calls_host_fn(runtime: &[u8], host_fn: &str) -> anyhow::Result<bool> {
 pub struct Graft {
     pub base: DeploymentHash,
     pub block: BlockNumber,
+    /// Take over the data of `base` instead of copying it. This is only
+    /// possible if the schema of the new deployment only adds to the
+    /// schema of `base`, and leaves `base` without any data
+    #[serde(default)]
+    pub in_place: bool,
 }
 
 impl Graft {
@@ -518,6 +530,40 @@ impl Graft {
     }
 }
 
+/// Check that the source of the subgraph data source `ds` is deployed on
+/// the same network and has the entity types that `ds` has handlers for
+fn validate_subgraph_source<S: SubgraphStore>(
+    ds: &subgraph::DataSource,
+    store: &S,
+) -> Result<(), Error> {
+    let address = &ds.source.address;
+    let source = store
+        .sourceable(address)
+        .map_err(|e| anyhow!("the source deployment `{}` can not be used: {}", address, e))?;
+
+    if source.network() != ds.network {
+        bail!(
+            "the source deployment `{}` indexes network `{}` but the data source uses network `{}`",
+            address,
+            source.network(),
+            ds.network
+        );
+    }
+
+    let schema = source.input_schema();
+    for entity_type in ds.entity_types() {
+        match schema.entity_type(entity_type) {
+            Ok(entity_type) if entity_type.is_object_type() => {}
+            _ => bail!(
+                "the source deployment `{}` has no entity type `{}`",
+                address,
+                entity_type
+            ),
+        }
+    }
+    Ok(())
+}
+
 #[derive(Clone, Debug)]
 pub struct DeploymentFeatures {
     pub id: String,
@@ -568,6 +614,13 @@ pub struct BaseSubgraphManifest<C, S, D, T> {
     #[serde(skip_serializing, default)]
     pub chain: PhantomData<C>,
     pub indexer_hints: Option<IndexerHints>,
+    /// The precision and rounding for `BigDecimal` values that mappings
+    /// create. Since this affects the PoI, it can only be set in the
+    /// manifest and not by the indexer
+    pub big_decimal: Option<BigDecimalPrecision>,
+    /// The fuel that each handler invocation may use. Like `big_decimal`,
+    /// this decides whether handlers fail and therefore the PoI
+    pub handler_fuel: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -704,6 +757,12 @@ impl<C: Blockchain> UnvalidatedSubgraphManifest<C> {
             }));
         }
 
+        for ds in self.0.data_sources.iter().filter_map(|ds| ds.as_onchain()) {
+            errors.extend(ds.validate_schema(&self.0.schema).into_iter().map(|e| {
+                SubgraphManifestValidationError::DataSourceValidation(ds.name().to_owned(), e)
+            }));
+        }
+
         // For API versions newer than 0.0.5, validate that all mappings uses the same api_version
         if let Err(different_api_versions) = self.0.unified_mapping_api_version() {
             errors.push(different_api_versions.into());
@@ -713,7 +772,7 @@ impl<C: Blockchain> UnvalidatedSubgraphManifest<C> {
             .0
             .data_sources
             .iter()
-            .filter_map(|d| Some(d.as_onchain()?.network()?.to_string()))
+            .filter_map(|d| Some(d.network()?.to_string()))
             .collect::<Vec<String>>();
         networks.sort();
         networks.dedup();
@@ -723,6 +782,15 @@ impl<C: Blockchain> UnvalidatedSubgraphManifest<C> {
             _ => errors.push(SubgraphManifestValidationError::MultipleEthereumNetworks),
         }
 
+        for ds in self.0.data_sources.iter().filter_map(|ds| ds.as_subgraph()) {
+            if let Err(e) = validate_subgraph_source(ds, store.as_ref()) {
+                errors.push(SubgraphManifestValidationError::DataSourceValidation(
+                    ds.name.clone(),
+                    e,
+                ));
+            }
+        }
+
         if let Some(graft) = &self.0.graft {
             if validate_graft_base {
                 if let Err(graft_err) = graft.validate(store).await {
@@ -747,6 +815,17 @@ impl<C: Blockchain> UnvalidatedSubgraphManifest<C> {
     pub fn spec_version(&self) -> &Version {
         &self.0.spec_version
     }
+
+    /// The features declared in the `features` section of the manifest
+    pub fn declared_features(&self) -> &BTreeSet<SubgraphFeature> {
+        &self.0.features
+    }
+
+    /// The features the subgraph uses. Mappings that are not valid WASM
+    /// modules are reported by `validate`
+    pub fn used_features(&self) -> BTreeSet<SubgraphFeature> {
+        detect_features(&self.0).unwrap_or_default()
+    }
 }
 
 impl<C: Blockchain> SubgraphManifest<C> {
@@ -778,7 +857,7 @@ impl<C: Blockchain> SubgraphManifest<C> {
     pub fn start_blocks(&self) -> Vec<BlockNumber> {
         self.data_sources
             .iter()
-            .filter_map(|d| Some(d.as_onchain()?.start_block()))
+            .filter_map(|d| d.start_block())
             .collect()
     }
 
@@ -789,6 +868,16 @@ impl<C: Blockchain> SubgraphManifest<C> {
         }
     }
 
+    pub fn big_decimal_precision(&self) -> BigDecimalPrecision {
+        self.big_decimal.unwrap_or_default()
+    }
+
+    /// The fuel budget for handlers that the manifest declares. Only this
+    /// budget makes running out of fuel a deterministic error
+    pub fn handler_fuel(&self) -> Option<u64> {
+        self.handler_fuel
+    }
+
     pub fn api_versions(&self) -> impl Iterator<Item = semver::Version> + '_ {
         self.templates
             .iter()
@@ -916,6 +1005,8 @@ impl<C: Blockchain> UnresolvedSubgraphManifest<C> {
             templates,
             chain,
             indexer_hints,
+            big_decimal,
+            handler_fuel,
         } = self;
 
         if !(MIN_SPEC_VERSION..=max_spec_version.clone()).contains(&spec_version) {
@@ -1003,6 +1094,52 @@ impl<C: Blockchain> UnresolvedSubgraphManifest<C> {
             );
         }
 
+        if let Some(big_decimal) = &big_decimal {
+            if spec_version < SPEC_VERSION_1_3_0 {
+                bail!(
+                    "Setting `bigDecimal` is not supported prior to {}",
+                    SPEC_VERSION_1_3_0
+                );
+            }
+            big_decimal.validate()?;
+        }
+
+        if let Some(handler_fuel) = handler_fuel {
+            if spec_version < SPEC_VERSION_1_5_0 {
+                bail!(
+                    "Setting `handlerFuel` is not supported prior to {}",
+                    SPEC_VERSION_1_5_0
+                );
+            }
+            if handler_fuel == 0 {
+                bail!("`handlerFuel` must be at least 1");
+            }
+        }
+
+        if spec_version < SPEC_VERSION_1_5_0
+            && templates
+                .iter()
+                .filter_map(|template| template.as_offchain())
+                .any(|template| template.is_parameterized())
+        {
+            bail!(
+                "Declaring `source` or `context` on offchain data source templates is not supported prior to {}",
+                SPEC_VERSION_1_5_0
+            );
+        }
+
+        if spec_version < SPEC_VERSION_1_5_0
+            && templates
+                .iter()
+                .filter_map(|template| template.as_offchain())
+                .any(|template| template.kind == OffchainDataSourceKind::Http)
+        {
+            bail!(
+                "`file/http` data source templates are not supported prior to {}",
+                SPEC_VERSION_1_5_0
+            );
+        }
+
         // Check the min_spec_version of each data source against the spec version of the subgraph
         let min_spec_version_mismatch = data_sources
             .iter()
@@ -1030,6 +1167,8 @@ impl<C: Blockchain> UnresolvedSubgraphManifest<C> {
             templates,
             chain,
             indexer_hints,
+            big_decimal,
+            handler_fuel,
         })
     }
 }
@@ -5,12 +5,13 @@ use chrono::{DateTime, Utc};
 use hex;
 use rand::rngs::OsRng;
 use rand::Rng;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
 use std::{fmt, fmt::Display};
 
 use super::DeploymentHash;
-use crate::blockchain::Blockchain;
+use crate::blockchain::{Blockchain, IngestionMode};
+use crate::components::subgraph::{FetchPolicy, HandlerLimits, RetryPolicy};
 use crate::data::graphql::TryFromValue;
 use crate::data::store::Value;
 use crate::data::subgraph::SubgraphManifest;
@@ -105,8 +106,15 @@ pub struct DeploymentCreate {
     pub start_block: Option<BlockPtr>,
     pub graft_base: Option<DeploymentHash>,
     pub graft_block: Option<BlockPtr>,
+    pub graft_in_place: bool,
     pub debug_fork: Option<DeploymentHash>,
     pub history_blocks_override: Option<i32>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub handler_limits: Option<HandlerLimits>,
+    pub mapping_env: Option<BTreeMap<String, String>>,
+    pub substreams_params: Option<BTreeMap<String, String>>,
+    pub ingestion: Option<IngestionMode>,
+    pub fetch_policy: Option<FetchPolicy>,
 }
 
 impl DeploymentCreate {
@@ -120,8 +128,15 @@ impl DeploymentCreate {
             start_block: start_block.cheap_clone(),
             graft_base: None,
             graft_block: None,
+            graft_in_place: false,
             debug_fork: None,
             history_blocks_override: None,
+            retry_policy: None,
+            handler_limits: None,
+            mapping_env: None,
+            substreams_params: None,
+            ingestion: None,
+            fetch_policy: None,
         }
     }
 
@@ -130,6 +145,31 @@ impl DeploymentCreate {
         self
     }
 
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    pub fn with_handler_limits(mut self, limits: HandlerLimits) -> Self {
+        self.handler_limits = Some(limits);
+        self
+    }
+
+    pub fn with_mapping_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.mapping_env = Some(env);
+        self
+    }
+
+    pub fn with_ingestion(mut self, ingestion: IngestionMode) -> Self {
+        self.ingestion = Some(ingestion);
+        self
+    }
+
+    pub fn with_fetch_policy(mut self, policy: FetchPolicy) -> Self {
+        self.fetch_policy = Some(policy);
+        self
+    }
+
     pub fn graft(mut self, base: Option<(DeploymentHash, BlockPtr)>) -> Self {
         if let Some((subgraph, ptr)) = base {
             self.graft_base = Some(subgraph);
@@ -138,6 +178,13 @@ impl DeploymentCreate {
         self
     }
 
+    /// Move the data of the graft base into the new deployment instead of
+    /// copying it
+    pub fn graft_in_place(mut self, in_place: bool) -> Self {
+        self.graft_in_place = in_place;
+        self
+    }
+
     pub fn debug(mut self, fork: Option<DeploymentHash>) -> Self {
         self.debug_fork = fork;
         self
@@ -170,6 +217,7 @@ pub struct SubgraphDeploymentEntity {
     pub latest_block: Option<BlockPtr>,
     pub graft_base: Option<DeploymentHash>,
     pub graft_block: Option<BlockPtr>,
+    pub graft_in_place: bool,
     pub debug_fork: Option<DeploymentHash>,
     pub reorg_count: i32,
     pub current_reorg_depth: i32,
@@ -91,6 +91,27 @@ impl IntoValue for ChainInfo {
     }
 }
 
+/// Progress of warming up the block and call caches for a deployment that
+/// started far behind the chain head
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WarmUpStatus {
+    pub start_block: BlockNumber,
+    pub end_block: BlockNumber,
+    /// The block up to which all blocks have been warmed up
+    pub latest_block: Option<BlockNumber>,
+}
+
+impl IntoValue for WarmUpStatus {
+    fn into_value(self) -> r::Value {
+        object! {
+            __typename: "WarmUpStatus",
+            startBlock: self.start_block,
+            endBlock: self.end_block,
+            latestBlock: self.latest_block,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Info {
     pub id: DeploymentId,
@@ -114,6 +135,10 @@ pub struct Info {
     pub node: Option<String>,
 
     pub history_blocks: i32,
+
+    /// The warm-up of the caches, if one is running. Only the node that
+    /// indexes the deployment knows about it
+    pub warm_up: Option<WarmUpStatus>,
 }
 
 impl IntoValue for Info {
@@ -130,6 +155,7 @@ impl IntoValue for Info {
             non_fatal_errors,
             synced,
             history_blocks,
+            warm_up,
         } = self;
 
         fn subgraph_error_to_value(subgraph_error: SubgraphError) -> r::Value {
@@ -173,6 +199,7 @@ impl IntoValue for Info {
             entityCount: format!("{}", entity_count),
             node: node,
             historyBlocks: history_blocks,
+            warmUp: warm_up,
         }
     }
 }
@@ -366,6 +366,10 @@ impl Value {
             ("ID", Value::String(s)) => Ok(Value::String(s)),
             ("ID", Value::Int(n)) => Ok(Value::String(n.to_string())),
             ("Bytes", Value::String(s)) => Ok(Value::String(s)),
+            ("GeoPoint", Value::String(s)) => match scalar::GeoPoint::parse(&s) {
+                Ok(_) => Ok(Value::String(s)),
+                Err(_) => Err(Value::String(s)),
+            },
             ("BigInt", Value::String(s)) => Ok(Value::String(s)),
             ("BigInt", Value::Int(n)) => Ok(Value::String(n.to_string())),
             ("JSONObject", Value::Object(obj)) => Ok(Value::Object(obj)),
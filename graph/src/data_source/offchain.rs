@@ -6,7 +6,11 @@ use crate::{
         store::{BlockNumber, StoredDynamicDataSource},
         subgraph::{InstanceDSTemplate, InstanceDSTemplateInfo},
     },
-    data::{store::scalar::Bytes, subgraph::SPEC_VERSION_0_0_7, value::Word},
+    data::{
+        store::{scalar::Bytes, ValueType},
+        subgraph::SPEC_VERSION_0_0_7,
+        value::Word,
+    },
     data_source,
     ipfs::ContentPath,
     prelude::{DataSourceContext, Link},
@@ -18,7 +22,7 @@ use lazy_static::lazy_static;
 use serde::Deserialize;
 use slog::{info, warn, Logger};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt,
     str::FromStr,
     sync::{atomic::AtomicI32, Arc},
@@ -30,6 +34,7 @@ lazy_static! {
     pub static ref OFFCHAIN_KINDS: HashMap<&'static str, OffchainDataSourceKind> = [
         ("file/ipfs", OffchainDataSourceKind::Ipfs),
         ("file/arweave", OffchainDataSourceKind::Arweave),
+        ("file/http", OffchainDataSourceKind::Http),
     ]
     .into_iter()
     .collect();
@@ -42,6 +47,7 @@ const NOT_DONE_VALUE: i32 = -1;
 pub enum OffchainDataSourceKind {
     Ipfs,
     Arweave,
+    Http,
 }
 impl OffchainDataSourceKind {
     pub fn try_parse_source(&self, bs: Bytes) -> Result<Source, anyhow::Error> {
@@ -54,6 +60,10 @@ impl OffchainDataSourceKind {
                 let base64 = Word::from(String::from_utf8(bs.to_vec())?);
                 Source::Arweave(base64)
             }
+            OffchainDataSourceKind::Http => {
+                let source = String::from_utf8(bs.to_vec())?.parse()?;
+                Source::Http(source)
+            }
         };
         Ok(source)
     }
@@ -178,10 +188,19 @@ impl DataSource {
                 bail!("Cannot create offchain data source from onchain template")
             }
         };
-        let source = info.params.into_iter().next().ok_or(anyhow::anyhow!(
-            "Failed to create data source from template `{}`: source parameter is missing",
-            template.name
-        ))?;
+        let source = match &template.source {
+            Some(pattern) => pattern.fill(&info.params).map_err(|e| {
+                anyhow!(
+                    "Failed to create data source from template `{}`: {}",
+                    template.name,
+                    e
+                )
+            })?,
+            None => info.params.into_iter().next().ok_or(anyhow::anyhow!(
+                "Failed to create data source from template `{}`: source parameter is missing",
+                template.name
+            ))?,
+        };
 
         let source = match template.kind {
             OffchainDataSourceKind::Ipfs => match source.parse() {
@@ -190,6 +209,12 @@ impl DataSource {
                 Err(e) => return Err(DataSourceCreationError::Ignore(source, e.into())),
             },
             OffchainDataSourceKind::Arweave => Source::Arweave(Word::from(source)),
+            OffchainDataSourceKind::Http => match source.parse() {
+                Ok(source) => Source::Http(source),
+                // Ignore data sources created with an invalid URL or hash, like
+                // those with an invalid CID.
+                Err(e) => return Err(DataSourceCreationError::Ignore(source, e)),
+            },
         };
 
         Ok(Self {
@@ -311,10 +336,64 @@ impl DataSource {
 
 pub type Base64 = Word;
 
+/// A file on an HTTP(S) server together with the SHA-256 hash of its
+/// contents, written as `https://example.com/1.json#sha256=<hex>`. Only
+/// contents with that hash are passed to handlers, which keeps indexing
+/// deterministic even though the server could change the file
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct HttpSource {
+    /// The URL without the hash
+    pub url: Word,
+    pub sha256: [u8; 32],
+}
+
+impl FromStr for HttpSource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut url =
+            url::Url::parse(s).map_err(|e| anyhow!("`{}` is not a valid URL: {}", s, e))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            bail!(
+                "`file/http` only supports http and https URLs but got `{}`",
+                s
+            );
+        }
+
+        let hash = url
+            .fragment()
+            .and_then(|fragment| fragment.strip_prefix("sha256="))
+            .ok_or_else(|| anyhow!("the URL `{}` must end with `#sha256=<hash>`", s))?;
+        let sha256 = hex::decode(hash)
+            .ok()
+            .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "the hash of `{}` must be 32 bytes in hexadecimal but is `{}`",
+                    s,
+                    hash
+                )
+            })?;
+
+        url.set_fragment(None);
+        Ok(HttpSource {
+            url: Word::from(url.as_str()),
+            sha256,
+        })
+    }
+}
+
+impl fmt::Display for HttpSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}#sha256={}", self.url, hex::encode(self.sha256))
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Source {
     Ipfs(ContentPath),
     Arweave(Base64),
+    Http(HttpSource),
 }
 
 impl Source {
@@ -328,6 +407,7 @@ impl Source {
         match self {
             Source::Ipfs(ref path) => Some(path.to_string().as_bytes().to_vec()),
             Source::Arweave(ref base64) => Some(base64.as_bytes().to_vec()),
+            Source::Http(ref source) => Some(source.to_string().into_bytes()),
         }
     }
 }
@@ -337,6 +417,7 @@ impl Into<Bytes> for Source {
         match self {
             Source::Ipfs(ref path) => Bytes::from(path.to_string().as_bytes().to_vec()),
             Source::Arweave(ref base64) => Bytes::from(base64.as_bytes()),
+            Source::Http(ref source) => Bytes::from(source.to_string().as_bytes()),
         }
     }
 }
@@ -444,18 +525,188 @@ pub struct UnresolvedDataSourceTemplate {
     pub kind: String,
     pub network: Option<String>,
     pub name: String,
+    #[serde(default)]
+    pub source: Option<UnresolvedTemplateSource>,
+    /// The fields that the context of data sources created from the
+    /// template must have, mapped to their type
+    #[serde(default)]
+    pub context: BTreeMap<String, String>,
     pub mapping: UnresolvedMapping,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct UnresolvedTemplateSource {
+    pub pattern: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct DataSourceTemplate {
     pub kind: OffchainDataSourceKind,
     pub network: Option<String>,
     pub name: String,
     pub manifest_idx: u32,
+    /// How to build the source from the parameters passed to `create`;
+    /// without a pattern, the first parameter is the source
+    pub source: Option<SourcePattern>,
+    pub context: BTreeMap<String, ContextField>,
     pub mapping: Mapping,
 }
 
+impl DataSourceTemplate {
+    /// Whether the template uses features that require spec version 1.5.0
+    pub fn is_parameterized(&self) -> bool {
+        self.source.is_some() || !self.context.is_empty()
+    }
+
+    /// Check that `params` and `context` are what the template declares.
+    /// Mappings call this when they create a data source from the
+    /// template so that mistakes are reported where they are made
+    pub fn check_creation(
+        &self,
+        params: &[String],
+        context: Option<&DataSourceContext>,
+    ) -> Result<(), Error> {
+        if let Some(pattern) = &self.source {
+            pattern.fill(params)?;
+        }
+
+        for (name, field) in &self.context {
+            match context.and_then(|context| context.get(name)) {
+                Some(value) if value.is_assignable(&field.value_type, field.is_list) => {}
+                Some(value) => bail!(
+                    "the context field `{}` of template `{}` must have type {} but has type {}",
+                    name,
+                    self.name,
+                    field,
+                    value.type_name()
+                ),
+                None => bail!(
+                    "the context of data sources created from template `{}` must have field `{}`",
+                    self.name,
+                    name
+                ),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A source with placeholders `{0}`, `{1}`, ... that are replaced with the
+/// parameters passed to `create`, for example `{0}/metadata/{1}.json`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourcePattern {
+    pattern: String,
+    /// The number of parameters the pattern needs: one more than its
+    /// largest placeholder
+    param_count: usize,
+}
+
+impl SourcePattern {
+    fn parts(pattern: &str) -> Result<Vec<PatternPart<'_>>, Error> {
+        let mut parts = Vec::new();
+        let mut rest = pattern;
+        while let Some(start) = rest.find(|c: char| c == '{' || c == '}') {
+            if rest[start..].starts_with('}') {
+                bail!("unmatched `}}` in source pattern `{}`", pattern);
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow!("unmatched `{{` in source pattern `{}`", pattern))?;
+            let placeholder = &rest[start + 1..start + end];
+            let index = placeholder.parse::<usize>().map_err(|_| {
+                anyhow!(
+                    "invalid placeholder `{{{}}}` in source pattern `{}`; placeholders are \
+                     parameter positions like `{{0}}`",
+                    placeholder,
+                    pattern
+                )
+            })?;
+            parts.push(PatternPart::Text(&rest[..start]));
+            parts.push(PatternPart::Param(index));
+            rest = &rest[start + end + 1..];
+        }
+        parts.push(PatternPart::Text(rest));
+        Ok(parts)
+    }
+
+    pub fn new(pattern: String) -> Result<Self, Error> {
+        let param_count = Self::parts(&pattern)?
+            .into_iter()
+            .filter_map(|part| match part {
+                PatternPart::Param(index) => Some(index + 1),
+                PatternPart::Text(_) => None,
+            })
+            .max()
+            .ok_or_else(|| anyhow!("source pattern `{}` has no placeholders", pattern))?;
+        Ok(Self {
+            pattern,
+            param_count,
+        })
+    }
+
+    /// Replace the placeholders in the pattern with `params`
+    pub fn fill(&self, params: &[String]) -> Result<String, Error> {
+        if params.len() < self.param_count {
+            bail!(
+                "source pattern `{}` needs {} parameters but only {} were passed",
+                self.pattern,
+                self.param_count,
+                params.len()
+            );
+        }
+        // The pattern was checked in `new`
+        let source = Self::parts(&self.pattern)
+            .unwrap()
+            .into_iter()
+            .map(|part| match part {
+                PatternPart::Text(text) => text,
+                PatternPart::Param(index) => params[index].as_str(),
+            })
+            .collect();
+        Ok(source)
+    }
+}
+
+enum PatternPart<'a> {
+    Text(&'a str),
+    Param(usize),
+}
+
+/// The type of a field in the context of data sources created from an
+/// offchain template, written like a GraphQL type, e.g., `BigInt` or
+/// `[String]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContextField {
+    pub value_type: ValueType,
+    pub is_list: bool,
+}
+
+impl FromStr for ContextField {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (inner, is_list) = match s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            Some(inner) => (inner, true),
+            None => (s, false),
+        };
+        let value_type = ValueType::from_str(inner.trim())?;
+        Ok(Self {
+            value_type,
+            is_list,
+        })
+    }
+}
+
+impl fmt::Display for ContextField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_list {
+            write!(f, "[{}]", self.value_type.to_str())
+        } else {
+            write!(f, "{}", self.value_type.to_str())
+        }
+    }
+}
+
 impl Into<DataSourceTemplateInfo> for DataSourceTemplate {
     fn into(self) -> DataSourceTemplateInfo {
         let DataSourceTemplate {
@@ -463,6 +714,8 @@ impl Into<DataSourceTemplateInfo> for DataSourceTemplate {
             network: _,
             name,
             manifest_idx,
+            source: _,
+            context: _,
             mapping,
         } = self;
 
@@ -486,6 +739,26 @@ impl UnresolvedDataSourceTemplate {
     ) -> Result<DataSourceTemplate, Error> {
         let kind = OffchainDataSourceKind::from_str(&self.kind)?;
 
+        let source = self
+            .source
+            .map(|source| SourcePattern::new(source.pattern))
+            .transpose()
+            .with_context(|| format!("invalid source of data source template {}", self.name))?;
+
+        let context = self
+            .context
+            .into_iter()
+            .map(|(name, field)| {
+                let field = field.parse().with_context(|| {
+                    format!(
+                        "invalid type of context field `{}` in data source template {}",
+                        name, self.name
+                    )
+                })?;
+                Ok((name, field))
+            })
+            .collect::<Result<_, Error>>()?;
+
         let mapping = self
             .mapping
             .resolve(resolver, schema, logger)
@@ -497,6 +770,8 @@ impl UnresolvedDataSourceTemplate {
             network: self.network,
             name: self.name,
             manifest_idx,
+            source,
+            context,
             mapping,
         })
     }
@@ -531,7 +806,7 @@ mod test {
         ipfs::ContentPath,
     };
 
-    use super::{OffchainDataSourceKind, Source};
+    use super::{ContextField, HttpSource, OffchainDataSourceKind, Source, SourcePattern};
 
     #[test]
     fn test_source_bytes_round_trip() {
@@ -549,5 +824,74 @@ mod test {
             .try_parse_source(arweave_source.into())
             .unwrap();
         assert! { matches!(s, Source::Arweave(b64) if b64.eq(&base64))};
+
+        let http: HttpSource = format!("https://example.com/1.json#sha256={}", "ab".repeat(32))
+            .parse()
+            .unwrap();
+        let http_source: Bytes = Source::Http(http.clone()).into();
+        let s = OffchainDataSourceKind::Http
+            .try_parse_source(http_source)
+            .unwrap();
+        assert! { matches!(s, Source::Http(source) if source.eq(&http))};
+    }
+
+    #[test]
+    fn test_http_source() {
+        let hash = "ab".repeat(32);
+        let source: HttpSource = format!("https://Example.com/a/1.json?v=2#sha256={hash}")
+            .parse()
+            .unwrap();
+        assert_eq!("https://example.com/a/1.json?v=2", source.url.as_str());
+        assert_eq!([0xab; 32], source.sha256);
+        assert_eq!(
+            format!("https://example.com/a/1.json?v=2#sha256={hash}"),
+            source.to_string()
+        );
+
+        for invalid in [
+            "https://example.com/1.json".to_string(),
+            format!("ftp://example.com/1.json#sha256={hash}"),
+            "https://example.com/1.json#sha256=abcd".to_string(),
+            format!("https://example.com/1.json#md5={hash}"),
+            format!("example.com/1.json#sha256={hash}"),
+        ] {
+            assert!(invalid.parse::<HttpSource>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn source_pattern() {
+        let params = |params: &[&str]| params.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        let pattern = SourcePattern::new("{0}/metadata/{1}.json".to_string()).unwrap();
+        assert_eq!(
+            "QmCid/metadata/7.json",
+            pattern.fill(&params(&["QmCid", "7"])).unwrap()
+        );
+        assert!(pattern.fill(&params(&["QmCid"])).is_err());
+
+        let pattern = SourcePattern::new("{1}{0}".to_string()).unwrap();
+        assert_eq!("ba", pattern.fill(&params(&["a", "b", "c"])).unwrap());
+
+        for invalid in ["QmCid", "{0", "0}", "{}", "{cid}/metadata.json"] {
+            assert!(
+                SourcePattern::new(invalid.to_string()).is_err(),
+                "pattern `{}` should be invalid",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn context_field() {
+        let field: ContextField = "BigInt".parse().unwrap();
+        assert!(!field.is_list);
+        assert_eq!("BigInt", field.to_string());
+
+        let field: ContextField = "[String]".parse().unwrap();
+        assert!(field.is_list);
+        assert_eq!("[String]", field.to_string());
+
+        assert!("Thing".parse::<ContextField>().is_err());
     }
 }
@@ -0,0 +1,239 @@
+use crate::{
+    blockchain::{Block, Blockchain},
+    components::{link_resolver::LinkResolver, store::BlockNumber},
+    data::{
+        store::Entity,
+        subgraph::{DeploymentHash, SPEC_VERSION_1_4_0},
+    },
+    data_source,
+    prelude::{DataSourceContext, Link},
+    schema::EntityKey,
+};
+use anyhow::{anyhow, Error};
+use serde::Deserialize;
+use slog::{info, Logger};
+use std::{collections::HashSet, fmt, sync::Arc};
+
+use super::TriggerWithHandler;
+
+pub const SUBGRAPH_DS_KIND: &str = "subgraph";
+
+const ENTITY_HANDLER_KIND: &str = "entity";
+
+/// A data source that triggers handlers when the entities of another
+/// deployment, the source, change. The source must index the same network
+/// as the subgraph that uses it, and handlers for a block only run once the
+/// source has processed that block
+#[derive(Debug, Clone)]
+pub struct DataSource {
+    pub kind: String,
+    pub name: String,
+    pub network: String,
+    pub manifest_idx: u32,
+    pub source: Source,
+    pub mapping: Mapping,
+    pub context: Arc<Option<DataSourceContext>>,
+}
+
+impl DataSource {
+    pub fn min_spec_version(&self) -> semver::Version {
+        SPEC_VERSION_1_4_0
+    }
+
+    pub fn handler_kind(&self) -> &str {
+        ENTITY_HANDLER_KIND
+    }
+
+    /// The address that `dataSource.address()` returns to mappings and that
+    /// is used to match triggers with hosts; it is the IPFS hash of the
+    /// source deployment
+    pub fn address(&self) -> Option<Vec<u8>> {
+        Some(self.source.address.as_bytes().to_vec())
+    }
+
+    /// The names of the entity types in the source for which this data
+    /// source has handlers
+    pub fn entity_types(&self) -> impl Iterator<Item = &str> {
+        self.mapping
+            .handlers
+            .iter()
+            .map(|handler| handler.entity.as_str())
+    }
+
+    pub fn validate(&self) -> Vec<Error> {
+        let mut errors = Vec::new();
+
+        if self.mapping.handlers.is_empty() {
+            errors.push(anyhow!("subgraph data sources need at least one handler"));
+        }
+        let mut entity_types = HashSet::new();
+        for entity_type in self.entity_types() {
+            if !entity_types.insert(entity_type) {
+                errors.push(anyhow!(
+                    "there is more than one handler for entity type `{}`",
+                    entity_type
+                ));
+            }
+        }
+
+        errors
+    }
+
+    pub fn match_and_decode<C: Blockchain>(
+        &self,
+        block: &Arc<C::Block>,
+        trigger: &TriggerData,
+    ) -> Option<TriggerWithHandler<super::MappingTrigger<C>>> {
+        if self.source.address != trigger.source || block.number() < self.source.start_block {
+            return None;
+        }
+
+        let handler = self
+            .mapping
+            .handlers
+            .iter()
+            .find(|handler| handler.entity == trigger.entity_type())?;
+
+        Some(TriggerWithHandler::new(
+            data_source::MappingTrigger::Subgraph(trigger.clone()),
+            handler.handler.clone(),
+            block.ptr(),
+            block.timestamp(),
+        ))
+    }
+
+    pub(super) fn is_duplicate_of(&self, b: &DataSource) -> bool {
+        let DataSource {
+            // Inferred from the manifest_idx
+            kind: _,
+            name: _,
+            network: _,
+            mapping: _,
+
+            manifest_idx,
+            source,
+            context,
+        } = self;
+
+        // See also: data-source-is-duplicate-of
+        manifest_idx == &b.manifest_idx && source == &b.source && context == &b.context
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Source {
+    /// The IPFS hash of the source deployment
+    pub address: DeploymentHash,
+    #[serde(default)]
+    pub start_block: BlockNumber,
+}
+
+#[derive(Clone, Debug)]
+pub struct Mapping {
+    pub language: String,
+    pub api_version: semver::Version,
+    pub entities: Vec<String>,
+    pub handlers: Vec<EntityHandler>,
+    pub runtime: Arc<Vec<u8>>,
+    pub link: Link,
+}
+
+/// A handler that is called with the new version of an entity of type
+/// `entity` in the source whenever the source creates or updates such an
+/// entity
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct EntityHandler {
+    pub handler: String,
+    pub entity: String,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct UnresolvedDataSource {
+    pub kind: String,
+    pub name: String,
+    pub network: String,
+    pub source: Source,
+    pub mapping: UnresolvedMapping,
+    pub context: Option<DataSourceContext>,
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedMapping {
+    pub api_version: String,
+    pub language: String,
+    pub file: Link,
+    pub handlers: Vec<EntityHandler>,
+    pub entities: Vec<String>,
+}
+
+impl UnresolvedDataSource {
+    pub(super) async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        manifest_idx: u32,
+    ) -> Result<DataSource, Error> {
+        info!(logger, "Resolve subgraph data source";
+            "name" => &self.name,
+            "kind" => &self.kind,
+            "source" => self.source.address.as_str(),
+        );
+
+        Ok(DataSource {
+            kind: self.kind,
+            name: self.name,
+            network: self.network,
+            manifest_idx,
+            source: self.source,
+            mapping: self.mapping.resolve(resolver, logger).await?,
+            context: Arc::new(self.context),
+        })
+    }
+}
+
+impl UnresolvedMapping {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+    ) -> Result<Mapping, Error> {
+        info!(logger, "Resolve subgraph mapping"; "link" => &self.file.link);
+
+        Ok(Mapping {
+            language: self.language,
+            api_version: semver::Version::parse(&self.api_version)?,
+            entities: self.entities,
+            handlers: self.handlers,
+            runtime: Arc::new(resolver.cat(logger, &self.file).await?),
+            link: self.file,
+        })
+    }
+}
+
+/// The creation or update of an entity in the source deployment
+#[derive(Clone)]
+pub struct TriggerData {
+    pub source: DeploymentHash,
+    pub key: EntityKey,
+    pub entity: Entity,
+}
+
+impl TriggerData {
+    pub fn entity_type(&self) -> &str {
+        self.key.entity_type.typename()
+    }
+}
+
+impl fmt::Debug for TriggerData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}] in {}",
+            self.entity_type(),
+            self.key.entity_id,
+            self.source
+        )
+    }
+}
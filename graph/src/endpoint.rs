@@ -2,16 +2,18 @@ use std::{
     collections::HashMap,
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
+    time::{Duration, Instant},
 };
 
-use prometheus::IntCounterVec;
+use prometheus::{IntCounter, IntCounterVec};
 use slog::{warn, Logger};
 
 use crate::{
     components::{adapter::ProviderName, metrics::MetricsRegistry},
     data::value::Word,
+    env::ENV_VARS,
 };
 
 /// ProviderCount is the underlying structure to keep the count,
@@ -19,6 +21,140 @@ use crate::{
 /// avoid locking since we don't need to modify the entire struture.
 type ProviderCount = Arc<HashMap<ProviderName, AtomicU64>>;
 
+/// The health of each provider for each `RequestClass`, indexed by the
+/// class. Like `ProviderCount`, all hosts are known ahead of time
+type ProviderHealth = Arc<HashMap<ProviderName, [Mutex<ClassHealth>; RequestClass::COUNT]>>;
+
+/// The kinds of requests that providers might serve well or badly
+/// independently of each other; some providers are fast for `eth_call` but
+/// slow for `eth_getLogs`, or limit how many traces they serve
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RequestClass {
+    Logs,
+    Calls,
+    Traces,
+    Blocks,
+    Other,
+}
+
+impl RequestClass {
+    const COUNT: usize = 5;
+
+    pub fn from_method(method: &str) -> Self {
+        match method {
+            "eth_getLogs" => RequestClass::Logs,
+            "eth_call" | "eth_getBalance" | "eth_getCode" | "eth_getStorageAt" => {
+                RequestClass::Calls
+            }
+            "trace_filter" | "trace_block" => RequestClass::Traces,
+            method if method.starts_with("debug_trace") => RequestClass::Traces,
+            "eth_getBlockByNumber"
+            | "eth_getBlockByHash"
+            | "eth_getBlockReceipts"
+            | "eth_getTransactionReceipt"
+            | "eth_blockNumber" => RequestClass::Blocks,
+            _ => RequestClass::Other,
+        }
+    }
+}
+
+/// How a request to a provider went
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestOutcome {
+    Success,
+    Failure,
+    /// The provider refused the request because we sent too many
+    RateLimited,
+    /// The provider does not have the state that the request needs, which
+    /// means it is not really an archive node
+    MissingState,
+}
+
+/// Moving averages of how well a provider served one class of requests.
+/// Errors, rate limiting and missing state are forgotten over time so that
+/// a provider that recovered gets traffic again
+#[derive(Debug, Default)]
+struct ClassHealth {
+    /// The average latency in milliseconds of the requests that took
+    /// measurably long, or `None` if we have not measured any yet
+    latency_ms: Option<f64>,
+    /// The share of failed requests as of `updated_at`
+    error_rate: f64,
+    updated_at: Option<Instant>,
+    rate_limited_at: Option<Instant>,
+    missing_state_at: Option<Instant>,
+}
+
+impl ClassHealth {
+    /// How much new observations change the moving averages
+    const WEIGHT: f64 = 0.2;
+    /// How much worse a provider whose requests all fail is than one whose
+    /// requests all succeed at the same latency
+    const ERROR_PENALTY: f64 = 10.0;
+    /// The same for a provider that just rate limited us or did not have
+    /// the state for a request; these are signs that it will keep failing
+    const LIMIT_PENALTY: f64 = 100.0;
+
+    /// The weight that an event at `then` still has at `now`
+    fn decay(then: Option<Instant>, now: Instant, half_life: Duration) -> f64 {
+        match then {
+            Some(then) => {
+                let elapsed = now.saturating_duration_since(then).as_secs_f64();
+                0.5f64.powf(elapsed / half_life.as_secs_f64())
+            }
+            None => 0.0,
+        }
+    }
+
+    fn observe(
+        &mut self,
+        latency: Option<Duration>,
+        outcome: RequestOutcome,
+        now: Instant,
+        half_life: Duration,
+    ) {
+        if let Some(latency) = latency {
+            let latency = latency.as_secs_f64() * 1000.0;
+            self.latency_ms = Some(match self.latency_ms {
+                Some(avg) => avg + Self::WEIGHT * (latency - avg),
+                None => latency,
+            });
+        }
+
+        let failed = if outcome == RequestOutcome::Success {
+            0.0
+        } else {
+            1.0
+        };
+        let error_rate = self.error_rate * Self::decay(self.updated_at, now, half_life);
+        self.error_rate = error_rate + Self::WEIGHT * (failed - error_rate);
+        self.updated_at = Some(now);
+
+        match outcome {
+            RequestOutcome::RateLimited => self.rate_limited_at = Some(now),
+            RequestOutcome::MissingState => self.missing_state_at = Some(now),
+            RequestOutcome::Success | RequestOutcome::Failure => {}
+        }
+    }
+
+    /// The expected cost of sending a request to the provider; lower is
+    /// better. Providers that we know nothing about score best so that
+    /// they get tried
+    fn score(&self, archive: bool, now: Instant, half_life: Duration) -> f64 {
+        let latency = 1.0 + self.latency_ms.unwrap_or(0.0);
+        let errors = 1.0
+            + Self::ERROR_PENALTY * self.error_rate * Self::decay(self.updated_at, now, half_life);
+        let rate_limited =
+            1.0 + Self::LIMIT_PENALTY * Self::decay(self.rate_limited_at, now, half_life);
+        let missing_state = if archive {
+            1.0 + Self::LIMIT_PENALTY * Self::decay(self.missing_state_at, now, half_life)
+        } else {
+            1.0
+        };
+        latency * errors * rate_limited * missing_state
+    }
+}
+
 /// This struct represents all the current labels except for the result
 /// which is added separately. If any new labels are necessary they should
 /// remain in the same order as added in [`EndpointMetrics::new`]
@@ -62,11 +198,15 @@ impl RequestLabels {
 }
 
 /// EndpointMetrics keeps track of calls success rate for specific calls,
-/// a success call to a host will clear the error count.
+/// a success call to a host will clear the error count. It also scores how
+/// well each host serves each `RequestClass` so that requests can go to
+/// the host that is best for them at the moment
 pub struct EndpointMetrics {
     logger: Logger,
     providers: ProviderCount,
+    health: ProviderHealth,
     counter: Box<IntCounterVec>,
+    received_bytes: Box<IntCounterVec>,
 }
 
 impl std::fmt::Debug for EndpointMetrics {
@@ -81,6 +221,11 @@ impl EndpointMetrics {
         providers: &[impl AsRef<str>],
         registry: Arc<MetricsRegistry>,
     ) -> Self {
+        let health = Arc::new(HashMap::from_iter(
+            providers
+                .iter()
+                .map(|h| (ProviderName::from(h.as_ref()), Default::default())),
+        ));
         let providers = Arc::new(HashMap::from_iter(
             providers
                 .iter()
@@ -95,10 +240,20 @@ impl EndpointMetrics {
             )
             .expect("unable to create endpoint_request counter_vec");
 
+        let received_bytes = registry
+            .new_int_counter_vec(
+                "endpoint_received_bytes",
+                "bytes received from the provider as sent over the wire, i.e., after compression",
+                &["conn_type", "provider"],
+            )
+            .expect("unable to create endpoint_received_bytes counter_vec");
+
         Self {
             logger,
             providers,
+            health,
             counter,
+            received_bytes,
         }
     }
 
@@ -130,21 +285,33 @@ impl EndpointMetrics {
     }
 
     pub fn success(&self, labels: &RequestLabels) {
-        match self.providers.get(&labels.provider) {
-            Some(count) => {
-                count.store(0, Ordering::Relaxed);
-            }
-            None => warn!(
-                &self.logger,
-                "metrics not available for host {}", labels.provider
-            ),
-        };
-
-        self.counter.with_label_values(&labels.to_slice(true)).inc();
+        self.record(labels, None, RequestOutcome::Success);
     }
 
     pub fn failure(&self, labels: &RequestLabels) {
+        self.record(labels, None, RequestOutcome::Failure);
+    }
+
+    /// The counter for the bytes that responses of the kind of connection
+    /// in `labels` received from its provider
+    pub fn received_bytes(&self, labels: &RequestLabels) -> IntCounter {
+        self.received_bytes
+            .with_label_values(&[(&labels.conn_type).into(), labels.provider.as_str()])
+    }
+
+    /// Count a request that took `latency`, if it was measured, and update
+    /// the score of the host for the class of the request
+    pub fn record(
+        &self,
+        labels: &RequestLabels,
+        latency: Option<Duration>,
+        outcome: RequestOutcome,
+    ) {
+        let is_success = outcome == RequestOutcome::Success;
         match self.providers.get(&labels.provider) {
+            Some(count) if is_success => {
+                count.store(0, Ordering::Relaxed);
+            }
             Some(count) => {
                 count.fetch_add(1, Ordering::Relaxed);
             }
@@ -154,11 +321,37 @@ impl EndpointMetrics {
             ),
         };
 
+        if let Some(health) = self.health.get(&labels.provider) {
+            let class = RequestClass::from_method(labels.req_type.as_str());
+            health[class as usize].lock().unwrap().observe(
+                latency,
+                outcome,
+                Instant::now(),
+                ENV_VARS.provider_score_half_life,
+            );
+        }
+
         self.counter
-            .with_label_values(&labels.to_slice(false))
+            .with_label_values(&labels.to_slice(is_success))
             .inc();
     }
 
+    /// The score of the host for requests of `class`, where lower is
+    /// better. With `archive`, a host that recently did not have the state
+    /// for a request scores a lot worse
+    pub fn score(&self, provider: &ProviderName, class: RequestClass, archive: bool) -> f64 {
+        self.health
+            .get(provider)
+            .map(|health| {
+                health[class as usize].lock().unwrap().score(
+                    archive,
+                    Instant::now(),
+                    ENV_VARS.provider_score_half_life,
+                )
+            })
+            .unwrap_or(1.0)
+    }
+
     /// Returns the current error count of a host or 0 if the host
     /// doesn't have a value on the map.
     pub fn get_count(&self, provider: &ProviderName) -> u64 {
@@ -172,12 +365,13 @@ impl EndpointMetrics {
 #[cfg(test)]
 mod test {
     use std::sync::Arc;
+    use std::time::{Duration, Instant};
 
     use slog::{o, Discard, Logger};
 
     use crate::{
         components::metrics::MetricsRegistry,
-        endpoint::{EndpointMetrics, ProviderName},
+        endpoint::{ClassHealth, EndpointMetrics, ProviderName, RequestOutcome},
     };
 
     #[tokio::test]
@@ -199,4 +393,30 @@ mod test {
         assert_eq!(metrics.get_count(&b), 2);
         assert_eq!(metrics.get_count(&c), 0);
     }
+
+    #[test]
+    fn scores_prefer_fast_providers_and_forget_problems() {
+        let half_life = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut fast = ClassHealth::default();
+        let mut slow = ClassHealth::default();
+        let latency = |ms| Some(Duration::from_millis(ms));
+
+        fast.observe(latency(10), RequestOutcome::Success, start, half_life);
+        slow.observe(latency(100), RequestOutcome::Success, start, half_life);
+        assert!(fast.score(false, start, half_life) < slow.score(false, start, half_life));
+
+        // Being rate limited makes the fast provider worse than the slow
+        // one until it has mostly been forgotten
+        fast.observe(None, RequestOutcome::RateLimited, start, half_life);
+        assert!(fast.score(false, start, half_life) > slow.score(false, start, half_life));
+        let later = start + half_life * 20;
+        assert!(fast.score(false, later, half_life) < slow.score(false, later, half_life));
+
+        // Missing state only matters for requests that need an archive
+        let mut pruned = ClassHealth::default();
+        pruned.observe(latency(10), RequestOutcome::MissingState, start, half_life);
+        assert!(pruned.score(false, start, half_life) < slow.score(false, start, half_life));
+        assert!(pruned.score(true, start, half_life) > slow.score(true, start, half_life));
+    }
 }
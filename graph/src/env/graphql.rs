@@ -62,6 +62,11 @@ pub struct EnvVarsGraphQl {
     /// Set by the environment variable `GRAPH_GRAPHQL_QUERY_TIMEOUT` (expressed in
     /// seconds). No default value is provided.
     pub query_timeout: Option<Duration>,
+    /// Return the data for the top-level fields that were resolved before a
+    /// query hit `query_timeout` instead of failing the whole query. Set by
+    /// the environment variable `GRAPH_GRAPHQL_PARTIAL_RESULTS_ON_TIMEOUT`.
+    /// Off by default.
+    pub partial_results_on_timeout: bool,
     /// Set by the environment variable `GRAPH_GRAPHQL_MAX_COMPLEXITY`. No
     /// default value is provided.
     pub max_complexity: Option<u64>,
@@ -74,6 +79,10 @@ pub struct EnvVarsGraphQl {
     /// Set by the environment variable `GRAPH_GRAPHQL_MAX_SKIP`. The default
     /// value is 4294967295 ([`u32::MAX`]).
     pub max_skip: u32,
+    /// How many items of a field marked with `@stream` are sent in each
+    /// part of an incremental response. Set by the environment variable
+    /// `GRAPH_GRAPHQL_STREAM_BATCH_SIZE`. The default value is 100.
+    pub stream_batch_size: u32,
     /// Allow skipping the check whether a deployment has changed while
     /// we were running a query. Once we are sure that the check mechanism
     /// is reliable, this variable should be removed.
@@ -103,6 +112,20 @@ pub struct EnvVarsGraphQl {
     /// Set by the env var `GRAPH_PARALLEL_BLOCK_CONSTRAINTS`
     /// Whether to run top-level queries with different block constraints in parallel
     pub parallel_block_constraints: bool,
+    /// Set by `GRAPH_SQL_ACCESS_TOKEN`. The bearer token that clients must
+    /// present to use the SQL endpoint. The SQL endpoint is disabled if
+    /// this is not set
+    pub sql_access_token: Option<String>,
+    /// Set by `GRAPH_SQL_MAX_ROWS`. The maximum number of rows a query
+    /// against the SQL endpoint may return. Defaults to 10,000
+    pub sql_max_rows: usize,
+    /// Set by `GRAPH_GRAPHQL_REQUIRE_API_KEY`. Whether requests to the query
+    /// port must carry an API key from `graphman api-key`. Defaults to
+    /// `false`
+    pub require_api_key: bool,
+    /// Set by `GRAPH_GRAPHQL_API_KEY_REFRESH_INTERVAL` (in seconds). How
+    /// often query nodes reload API keys from the database. Defaults to 30
+    pub api_key_refresh_interval: Duration,
 }
 
 // This does not print any values avoid accidentally leaking any sensitive env vars
@@ -137,10 +160,12 @@ impl From<InnerGraphQl> for EnvVarsGraphQl {
             query_cache_stale_period: x.query_cache_stale_period,
             query_cache_max_entry_ratio: x.query_cache_max_entry_ratio,
             query_timeout: x.query_timeout_in_secs.map(Duration::from_secs),
+            partial_results_on_timeout: x.partial_results_on_timeout.0,
             max_complexity: x.max_complexity.map(|x| x.0),
             max_depth: x.max_depth.0,
             max_first: x.max_first,
             max_skip: x.max_skip.0,
+            stream_batch_size: x.stream_batch_size,
             allow_deployment_change: x.allow_deployment_change.0,
             warn_result_size: x.warn_result_size.0 .0,
             error_result_size: x.error_result_size.0 .0,
@@ -149,6 +174,10 @@ impl From<InnerGraphQl> for EnvVarsGraphQl {
             disable_child_sorting: x.disable_child_sorting.0,
             query_trace_token: x.query_trace_token,
             parallel_block_constraints: x.parallel_block_constraints.0,
+            sql_access_token: x.sql_access_token,
+            sql_max_rows: x.sql_max_rows.0,
+            require_api_key: x.require_api_key.0,
+            api_key_refresh_interval: Duration::from_secs(x.api_key_refresh_interval_in_secs),
         }
     }
 }
@@ -178,6 +207,8 @@ pub struct InnerGraphQl {
     query_cache_max_entry_ratio: usize,
     #[envconfig(from = "GRAPH_GRAPHQL_QUERY_TIMEOUT")]
     query_timeout_in_secs: Option<u64>,
+    #[envconfig(from = "GRAPH_GRAPHQL_PARTIAL_RESULTS_ON_TIMEOUT", default = "false")]
+    partial_results_on_timeout: EnvVarBoolean,
     #[envconfig(from = "GRAPH_GRAPHQL_MAX_COMPLEXITY")]
     max_complexity: Option<NoUnderscores<u64>>,
     #[envconfig(from = "GRAPH_GRAPHQL_MAX_DEPTH", default = "")]
@@ -186,6 +217,8 @@ pub struct InnerGraphQl {
     max_first: u32,
     #[envconfig(from = "GRAPH_GRAPHQL_MAX_SKIP", default = "")]
     max_skip: WithDefaultUsize<u32, { u32::MAX as usize }>,
+    #[envconfig(from = "GRAPH_GRAPHQL_STREAM_BATCH_SIZE", default = "100")]
+    stream_batch_size: u32,
     #[envconfig(from = "GRAPHQL_ALLOW_DEPLOYMENT_CHANGE", default = "false")]
     allow_deployment_change: EnvVarBoolean,
     #[envconfig(from = "GRAPH_GRAPHQL_WARN_RESULT_SIZE", default = "")]
@@ -202,4 +235,12 @@ pub struct InnerGraphQl {
     query_trace_token: String,
     #[envconfig(from = "GRAPH_PARALLEL_BLOCK_CONSTRAINTS", default = "false")]
     pub parallel_block_constraints: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_SQL_ACCESS_TOKEN")]
+    sql_access_token: Option<String>,
+    #[envconfig(from = "GRAPH_SQL_MAX_ROWS", default = "10000")]
+    sql_max_rows: NoUnderscores<usize>,
+    #[envconfig(from = "GRAPH_GRAPHQL_REQUIRE_API_KEY", default = "false")]
+    require_api_key: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_GRAPHQL_API_KEY_REFRESH_INTERVAL", default = "30")]
+    api_key_refresh_interval_in_secs: u64,
 }
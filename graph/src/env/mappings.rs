@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::PathBuf;
 
 use super::*;
 
@@ -16,16 +17,49 @@ pub struct EnvVarsMapping {
     /// kilobytes). The default value is 10 megabytes.
     pub entity_cache_size: usize,
     /// Set by the environment variable `GRAPH_MAX_API_VERSION`. The default
-    /// value is `0.0.8`.
+    /// value is `0.0.11`.
     pub max_api_version: Version,
     /// Set by the environment variable `GRAPH_MAPPING_HANDLER_TIMEOUT`
     /// (expressed in seconds). No default is provided.
     pub timeout: Option<Duration>,
+    /// The fuel, roughly the number of WASM instructions, that a mapping
+    /// handler may use. Running out of it is non-deterministic; subgraphs
+    /// can set their own budget with `handlerFuel` in the manifest.
+    ///
+    /// Set by the environment variable `GRAPH_MAPPING_HANDLER_FUEL`. No
+    /// default is provided.
+    pub handler_fuel: Option<u64>,
     /// Maximum stack size for the WASM runtime.
     ///
     /// Set by the environment variable `GRAPH_RUNTIME_MAX_STACK_SIZE`
     /// (expressed in bytes). The default value is 512KiB.
     pub max_stack_size: usize,
+    /// Maximum size of the WASM memory of a mapping handler; handlers that
+    /// need more memory fail with a non-deterministic error.
+    ///
+    /// Set by the environment variable `GRAPH_RUNTIME_MAX_MEMORY_SIZE`
+    /// (expressed in bytes). No default is provided.
+    pub max_memory_size: Option<usize>,
+    /// A directory where compiled WASM modules are stored so that
+    /// deployments that use the same mappings, and restarts of the node,
+    /// do not have to compile them again.
+    ///
+    /// Set by the environment variable `GRAPH_WASM_MODULE_CACHE_DIR`. By
+    /// default, compiled modules are not cached.
+    pub module_cache_dir: Option<PathBuf>,
+    /// A directory where the contents of files that offchain data sources
+    /// fetched are stored, so that restarts, rewinds and copies of
+    /// deployments do not fetch them again.
+    ///
+    /// Set by the environment variable `GRAPH_OFFCHAIN_CACHE_DIR`. By
+    /// default, files are not cached on disk.
+    pub offchain_cache_dir: Option<PathBuf>,
+    /// How many bytes the files in `offchain_cache_dir` may take up; the
+    /// least recently used files are removed first.
+    ///
+    /// Set by the environment variable `GRAPH_OFFCHAIN_CACHE_MAX_BYTES`.
+    /// Defaults to 10 GiB.
+    pub offchain_cache_max_bytes: usize,
 
     /// Set by the environment variable `GRAPH_MAX_IPFS_CACHE_FILE_SIZE`
     /// (expressed in bytes). The default value is 1MiB.
@@ -54,6 +88,35 @@ pub struct EnvVarsMapping {
     /// Set by the environment variable `GRAPH_IPFS_REQUEST_LIMIT`. Defaults to 100.
     pub ipfs_request_limit: u16,
 
+    /// How long each of several IPFS servers may take to respond to a
+    /// request before the request counts as failed for that server.
+    ///
+    /// Set by the environment variable `GRAPH_IPFS_ENDPOINT_TIMEOUT`
+    /// (expressed in seconds). The default value is 60s.
+    pub ipfs_endpoint_timeout: Duration,
+    /// After how many consecutive failures an IPFS server is considered
+    /// unhealthy.
+    ///
+    /// Set by the environment variable `GRAPH_IPFS_UNHEALTHY_AFTER`.
+    /// Defaults to 3.
+    pub ipfs_unhealthy_after: u32,
+    /// How long an unhealthy IPFS server is only used when no healthy
+    /// server can provide the content.
+    ///
+    /// Set by the environment variable `GRAPH_IPFS_UNHEALTHY_COOLDOWN`
+    /// (expressed in seconds). The default value is 60s.
+    pub ipfs_unhealthy_cooldown: Duration,
+    /// The URL of an IPFS Pinning Service API endpoint that the CIDs that
+    /// deployments reference are pinned with.
+    ///
+    /// Set by the environment variable `GRAPH_IPFS_PINNING_SERVICE_URL`. By
+    /// default, nothing is pinned.
+    pub ipfs_pinning_service_url: Option<String>,
+    /// The access token for `ipfs_pinning_service_url`.
+    ///
+    /// Set by the environment variable `GRAPH_IPFS_PINNING_SERVICE_TOKEN`.
+    pub ipfs_pinning_service_token: Option<String>,
+
     /// Set by the flag `GRAPH_ALLOW_NON_DETERMINISTIC_IPFS`. Off by
     /// default.
     pub allow_non_deterministic_ipfs: bool,
@@ -62,6 +125,37 @@ pub struct EnvVarsMapping {
     /// eth calls before running triggers; instead eth calls happen when
     /// mappings call `ethereum.call`. Off by default.
     pub disable_declared_calls: bool,
+
+    /// The hosts that handlers of offchain data sources may fetch from
+    /// with `http.get`. `http.get` is disabled if the list is empty.
+    ///
+    /// Set by the environment variable `GRAPH_HTTP_GET_ALLOWED_HOSTS` as a
+    /// comma separated list. Empty by default.
+    pub http_get_allowed_hosts: Vec<String>,
+    /// The timeout for `http.get` requests.
+    ///
+    /// Set by the environment variable `GRAPH_HTTP_GET_TIMEOUT` (expressed
+    /// in seconds). The default value is 30s.
+    pub http_get_timeout: Duration,
+    /// The hosts that `file/http` data sources may fetch from, or `*` for
+    /// any host. `file/http` data sources are never fetched if the list is
+    /// empty.
+    ///
+    /// Set by the environment variable `GRAPH_FILE_HTTP_ALLOWED_HOSTS` as a
+    /// comma separated list. Empty by default.
+    pub file_http_allowed_hosts: Vec<String>,
+    /// Sets the size limit for responses to `http.get`.
+    ///
+    /// Set by the environment variable `GRAPH_MAX_HTTP_GET_BYTES`
+    /// (expressed in bytes). Defaults to 10 MiB.
+    pub max_http_get_bytes: usize,
+
+    /// How many of the messages that mappings log with `log.*` are kept
+    /// in the store for each deployment. Logs are not stored if this is 0.
+    ///
+    /// Set by the environment variable `GRAPH_DEPLOYMENT_LOG_RETENTION`.
+    /// The default value is 0.
+    pub deployment_log_retention: usize,
 }
 
 // This does not print any values avoid accidentally leaking any sensitive env vars
@@ -79,7 +173,12 @@ impl From<InnerMappingHandlers> for EnvVarsMapping {
 
             max_api_version: x.max_api_version,
             timeout: x.mapping_handler_timeout_in_secs.map(Duration::from_secs),
+            handler_fuel: x.mapping_handler_fuel,
             max_stack_size: x.runtime_max_stack_size.0 .0,
+            max_memory_size: x.runtime_max_memory_size,
+            module_cache_dir: x.wasm_module_cache_dir.map(PathBuf::from),
+            offchain_cache_dir: x.offchain_cache_dir.map(PathBuf::from),
+            offchain_cache_max_bytes: x.offchain_cache_max_bytes.0,
 
             max_ipfs_cache_file_size: x.max_ipfs_cache_file_size.0,
             max_ipfs_cache_size: x.max_ipfs_cache_size,
@@ -87,8 +186,28 @@ impl From<InnerMappingHandlers> for EnvVarsMapping {
             max_ipfs_map_file_size: x.max_ipfs_map_file_size.0,
             max_ipfs_file_bytes: x.max_ipfs_file_bytes.0,
             ipfs_request_limit: x.ipfs_request_limit,
+            ipfs_endpoint_timeout: Duration::from_secs(x.ipfs_endpoint_timeout_in_secs),
+            ipfs_unhealthy_after: x.ipfs_unhealthy_after,
+            ipfs_unhealthy_cooldown: Duration::from_secs(x.ipfs_unhealthy_cooldown_in_secs),
+            ipfs_pinning_service_url: x.ipfs_pinning_service_url,
+            ipfs_pinning_service_token: x.ipfs_pinning_service_token,
             allow_non_deterministic_ipfs: x.allow_non_deterministic_ipfs.0,
             disable_declared_calls: x.disable_declared_calls.0,
+            http_get_allowed_hosts: x
+                .http_get_allowed_hosts
+                .split(',')
+                .map(|host| host.trim().to_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect(),
+            http_get_timeout: Duration::from_secs(x.http_get_timeout_in_secs),
+            file_http_allowed_hosts: x
+                .file_http_allowed_hosts
+                .split(',')
+                .map(|host| host.trim().to_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect(),
+            max_http_get_bytes: x.max_http_get_bytes.0,
+            deployment_log_retention: x.deployment_log_retention,
         }
     }
 }
@@ -99,12 +218,22 @@ pub struct InnerMappingHandlers {
     entity_cache_dead_weight: EnvVarBoolean,
     #[envconfig(from = "GRAPH_ENTITY_CACHE_SIZE", default = "10000")]
     entity_cache_size_in_kb: usize,
-    #[envconfig(from = "GRAPH_MAX_API_VERSION", default = "0.0.9")]
+    #[envconfig(from = "GRAPH_MAX_API_VERSION", default = "0.0.11")]
     max_api_version: Version,
     #[envconfig(from = "GRAPH_MAPPING_HANDLER_TIMEOUT")]
     mapping_handler_timeout_in_secs: Option<u64>,
+    #[envconfig(from = "GRAPH_MAPPING_HANDLER_FUEL")]
+    mapping_handler_fuel: Option<u64>,
     #[envconfig(from = "GRAPH_RUNTIME_MAX_STACK_SIZE", default = "")]
     runtime_max_stack_size: WithDefaultUsize<NoUnderscores<usize>, { 512 * 1024 }>,
+    #[envconfig(from = "GRAPH_RUNTIME_MAX_MEMORY_SIZE")]
+    runtime_max_memory_size: Option<usize>,
+    #[envconfig(from = "GRAPH_WASM_MODULE_CACHE_DIR")]
+    wasm_module_cache_dir: Option<String>,
+    #[envconfig(from = "GRAPH_OFFCHAIN_CACHE_DIR")]
+    offchain_cache_dir: Option<String>,
+    #[envconfig(from = "GRAPH_OFFCHAIN_CACHE_MAX_BYTES", default = "")]
+    offchain_cache_max_bytes: WithDefaultUsize<usize, { 10 * 1024 * 1024 * 1024 }>,
 
     // IPFS.
     #[envconfig(from = "GRAPH_MAX_IPFS_CACHE_FILE_SIZE", default = "")]
@@ -119,8 +248,32 @@ pub struct InnerMappingHandlers {
     max_ipfs_file_bytes: WithDefaultUsize<usize, { 25 * 1024 * 1024 }>,
     #[envconfig(from = "GRAPH_IPFS_REQUEST_LIMIT", default = "100")]
     ipfs_request_limit: u16,
+    #[envconfig(from = "GRAPH_IPFS_ENDPOINT_TIMEOUT", default = "60")]
+    ipfs_endpoint_timeout_in_secs: u64,
+    #[envconfig(from = "GRAPH_IPFS_UNHEALTHY_AFTER", default = "3")]
+    ipfs_unhealthy_after: u32,
+    #[envconfig(from = "GRAPH_IPFS_UNHEALTHY_COOLDOWN", default = "60")]
+    ipfs_unhealthy_cooldown_in_secs: u64,
+    #[envconfig(from = "GRAPH_IPFS_PINNING_SERVICE_URL")]
+    ipfs_pinning_service_url: Option<String>,
+    #[envconfig(from = "GRAPH_IPFS_PINNING_SERVICE_TOKEN")]
+    ipfs_pinning_service_token: Option<String>,
     #[envconfig(from = "GRAPH_ALLOW_NON_DETERMINISTIC_IPFS", default = "false")]
     allow_non_deterministic_ipfs: EnvVarBoolean,
     #[envconfig(from = "GRAPH_DISABLE_DECLARED_CALLS", default = "false")]
     disable_declared_calls: EnvVarBoolean,
+
+    // HTTP.
+    #[envconfig(from = "GRAPH_HTTP_GET_ALLOWED_HOSTS", default = "")]
+    http_get_allowed_hosts: String,
+    #[envconfig(from = "GRAPH_HTTP_GET_TIMEOUT", default = "30")]
+    http_get_timeout_in_secs: u64,
+    #[envconfig(from = "GRAPH_FILE_HTTP_ALLOWED_HOSTS", default = "")]
+    file_http_allowed_hosts: String,
+    #[envconfig(from = "GRAPH_MAX_HTTP_GET_BYTES", default = "")]
+    max_http_get_bytes: WithDefaultUsize<usize, { 10 * 1024 * 1024 }>,
+
+    // Logs.
+    #[envconfig(from = "GRAPH_DEPLOYMENT_LOG_RETENTION", default = "0")]
+    deployment_log_retention: usize,
 }
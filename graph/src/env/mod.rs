@@ -186,11 +186,33 @@ pub struct EnvVars {
     /// The value is set by `ETHERUM_POLLING_INTERVAL` in millis and the
     /// default is 1000.
     pub ingestor_polling_interval: Duration,
+    /// How quickly errors, rate limiting and missing state are forgotten
+    /// when scoring JSON-RPC providers: their weight halves every half
+    /// life. Set by `GRAPH_PROVIDER_SCORE_HALF_LIFE` in seconds, the
+    /// default is 300.
+    pub provider_score_half_life: Duration,
     /// Set by the env var `GRAPH_EXPERIMENTAL_SUBGRAPH_SETTINGS` which should point
     /// to a file with subgraph-specific settings
     pub subgraph_settings: Option<String>,
     /// Whether to prefer substreams blocks streams over firehose when available.
     pub prefer_substreams_block_streams: bool,
+    /// Set by the flag `GRAPH_BLOCK_STREAM_PREFETCH`. Whether polling
+    /// block streams scan the next block range for triggers while the
+    /// blocks of the current range are still being processed. On by
+    /// default.
+    pub block_stream_prefetch: bool,
+    /// Set by the env var `GRAPH_BLOCK_STREAM_BUFFER_SIZE`. The number of
+    /// block stream events that are fetched ahead of the subgraph runner.
+    /// Defaults to a size that depends on the kind of block stream.
+    pub block_stream_buffer_size: Option<usize>,
+    /// Set by the env var `GRAPH_CACHE_WARM_UP_BLOCKS`. When a deployment
+    /// starts more than this many blocks behind the chain head, the blocks
+    /// and declared calls for that many blocks are fetched ahead of the
+    /// runner. The default is 100000, and 0 turns warming up off.
+    pub cache_warm_up_blocks: BlockNumber,
+    /// Set by the env var `GRAPH_CACHE_WARM_UP_PARALLELISM`. How many
+    /// block ranges a warm-up fetches at the same time. The default is 4.
+    pub cache_warm_up_parallelism: usize,
     /// Set by the flag `GRAPH_ENABLE_DIPS_METRICS`. Whether to enable
     /// gas metrics. Off by default.
     pub enable_dips_metrics: bool,
@@ -212,6 +234,16 @@ pub struct EnvVars {
     /// Set the maximum grpc decode size(in MB) for firehose BlockIngestor connections.
     /// Defaults to 25MB
     pub firehose_grpc_max_decode_size_mb: usize,
+    /// How long a Firehose or Substreams block stream waits for the next
+    /// response before it gives up on the endpoint and reconnects, possibly
+    /// to a different endpoint. Zero disables the timeout. Set by
+    /// `GRAPH_NODE_FIREHOSE_STALL_TIMEOUT`, in seconds; the default is 300
+    pub firehose_stall_timeout: Duration,
+    /// How often to check that the Firehose and Substreams endpoints of all
+    /// chains respond. Zero disables the checks. Set by
+    /// `GRAPH_NODE_FIREHOSE_HEALTH_CHECK_INTERVAL`, in seconds; the default
+    /// is 30
+    pub firehose_health_check_interval: Duration,
     /// Defined whether or not graph-node should refuse to perform genesis validation
     /// before using an adapter. Disabled by default for the moment, will be enabled
     /// on the next release. Disabling validation means the recorded genesis will be 0x00
@@ -221,6 +253,26 @@ pub struct EnvVars {
     /// How long do we wait for a response from the provider before considering that it is unavailable.
     /// Default is 30s.
     pub genesis_validation_timeout: Duration,
+    /// How often to validate the providers of all chains again while the
+    /// node is running; only used when genesis validation is enabled. Zero
+    /// disables revalidation. Set by
+    /// `GRAPH_NODE_GENESIS_REVALIDATION_INTERVAL`, in seconds; the default
+    /// is 300
+    pub genesis_revalidation_interval: Duration,
+    /// How often to check the configuration file for changes to the
+    /// providers of chains and apply them. Zero disables the check. Set by
+    /// `GRAPH_NODE_CONFIG_RELOAD_INTERVAL`, in seconds; the default is 30
+    pub config_reload_interval: Duration,
+    /// The OTLP endpoint that OpenTelemetry spans are exported to over
+    /// gRPC, like `http://localhost:4317`. Set by `GRAPH_OTLP_ENDPOINT`;
+    /// spans are not exported when it is not set
+    pub otlp_endpoint: Option<String>,
+    /// The `service.name` of the exported spans. Set by
+    /// `GRAPH_OTLP_SERVICE_NAME`; the default is `graph-node`
+    pub otlp_service_name: String,
+    /// The fraction of blocks and queries that are traced. Set by
+    /// `GRAPH_OTLP_SAMPLE_RATIO`; the default is 1, i.e., everything
+    pub otlp_sample_ratio: f64,
 
     /// Sets the token that is used to authenticate graphman GraphQL queries.
     ///
@@ -300,16 +352,32 @@ impl EnvVars {
             static_filters_threshold: inner.static_filters_threshold,
             reorg_threshold,
             ingestor_polling_interval: Duration::from_millis(inner.ingestor_polling_interval),
+            provider_score_half_life: Duration::from_secs(
+                inner.provider_score_half_life_in_secs.max(1),
+            ),
             subgraph_settings: inner.subgraph_settings,
             prefer_substreams_block_streams: inner.prefer_substreams_block_streams,
+            block_stream_prefetch: inner.block_stream_prefetch.0,
+            block_stream_buffer_size: inner.block_stream_buffer_size,
+            cache_warm_up_blocks: inner.cache_warm_up_blocks,
+            cache_warm_up_parallelism: inner.cache_warm_up_parallelism,
             enable_dips_metrics: inner.enable_dips_metrics.0,
             history_blocks_override: inner.history_blocks_override,
             min_history_blocks: inner.min_history_blocks.unwrap_or(2 * reorg_threshold),
             dips_metrics_object_store_url: inner.dips_metrics_object_store_url,
             section_map: inner.section_map,
             firehose_grpc_max_decode_size_mb: inner.firehose_grpc_max_decode_size_mb,
+            firehose_stall_timeout: Duration::from_secs(inner.firehose_stall_timeout),
+            firehose_health_check_interval: Duration::from_secs(
+                inner.firehose_health_check_interval,
+            ),
             genesis_validation_enabled: inner.genesis_validation_enabled.0,
             genesis_validation_timeout: Duration::from_secs(inner.genesis_validation_timeout),
+            genesis_revalidation_interval: Duration::from_secs(inner.genesis_revalidation_interval),
+            config_reload_interval: Duration::from_secs(inner.config_reload_interval),
+            otlp_endpoint: inner.otlp_endpoint,
+            otlp_service_name: inner.otlp_service_name,
+            otlp_sample_ratio: inner.otlp_sample_ratio.clamp(0.0, 1.0),
             graphman_server_auth_token: inner.graphman_server_auth_token,
         })
     }
@@ -363,7 +431,7 @@ struct Inner {
         default = "false"
     )]
     allow_non_deterministic_fulltext_search: EnvVarBoolean,
-    #[envconfig(from = "GRAPH_MAX_SPEC_VERSION", default = "1.2.0")]
+    #[envconfig(from = "GRAPH_MAX_SPEC_VERSION", default = "1.5.0")]
     max_spec_version: Version,
     #[envconfig(from = "GRAPH_LOAD_WINDOW_SIZE", default = "300")]
     load_window_size_in_secs: u64,
@@ -437,6 +505,8 @@ struct Inner {
     reorg_threshold: Option<BlockNumber>,
     #[envconfig(from = "ETHEREUM_POLLING_INTERVAL", default = "1000")]
     ingestor_polling_interval: u64,
+    #[envconfig(from = "GRAPH_PROVIDER_SCORE_HALF_LIFE", default = "300")]
+    provider_score_half_life_in_secs: u64,
     #[envconfig(from = "GRAPH_EXPERIMENTAL_SUBGRAPH_SETTINGS")]
     subgraph_settings: Option<String>,
     #[envconfig(
@@ -444,6 +514,14 @@ struct Inner {
         default = "false"
     )]
     prefer_substreams_block_streams: bool,
+    #[envconfig(from = "GRAPH_BLOCK_STREAM_PREFETCH", default = "true")]
+    block_stream_prefetch: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_BLOCK_STREAM_BUFFER_SIZE")]
+    block_stream_buffer_size: Option<usize>,
+    #[envconfig(from = "GRAPH_CACHE_WARM_UP_BLOCKS", default = "100000")]
+    cache_warm_up_blocks: BlockNumber,
+    #[envconfig(from = "GRAPH_CACHE_WARM_UP_PARALLELISM", default = "4")]
+    cache_warm_up_parallelism: usize,
     #[envconfig(from = "GRAPH_ENABLE_DIPS_METRICS", default = "false")]
     enable_dips_metrics: EnvVarBoolean,
     #[envconfig(from = "GRAPH_HISTORY_BLOCKS_OVERRIDE")]
@@ -456,10 +534,24 @@ struct Inner {
     section_map: Option<String>,
     #[envconfig(from = "GRAPH_NODE_FIREHOSE_MAX_DECODE_SIZE", default = "25")]
     firehose_grpc_max_decode_size_mb: usize,
+    #[envconfig(from = "GRAPH_NODE_FIREHOSE_STALL_TIMEOUT", default = "300")]
+    firehose_stall_timeout: u64,
+    #[envconfig(from = "GRAPH_NODE_FIREHOSE_HEALTH_CHECK_INTERVAL", default = "30")]
+    firehose_health_check_interval: u64,
     #[envconfig(from = "GRAPH_NODE_GENESIS_VALIDATION_ENABLED", default = "false")]
     genesis_validation_enabled: EnvVarBoolean,
     #[envconfig(from = "GRAPH_NODE_GENESIS_VALIDATION_TIMEOUT_SECONDS", default = "30")]
     genesis_validation_timeout: u64,
+    #[envconfig(from = "GRAPH_NODE_GENESIS_REVALIDATION_INTERVAL", default = "300")]
+    genesis_revalidation_interval: u64,
+    #[envconfig(from = "GRAPH_NODE_CONFIG_RELOAD_INTERVAL", default = "30")]
+    config_reload_interval: u64,
+    #[envconfig(from = "GRAPH_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<String>,
+    #[envconfig(from = "GRAPH_OTLP_SERVICE_NAME", default = "graph-node")]
+    otlp_service_name: String,
+    #[envconfig(from = "GRAPH_OTLP_SAMPLE_RATIO", default = "1")]
+    otlp_sample_ratio: f64,
     #[envconfig(from = "GRAPHMAN_SERVER_AUTH_TOKEN")]
     graphman_server_auth_token: Option<String>,
 }
@@ -13,7 +13,7 @@ use crate::{
     endpoint::{ConnectionType, EndpointMetrics, RequestLabels},
     env::ENV_VARS,
     firehose::decode_firehose_block,
-    prelude::{anyhow, debug, info, DeploymentHash},
+    prelude::{anyhow, debug, info, warn, DeploymentHash},
     substreams::Package,
     substreams_rpc::{self, response, BlockScopedData, Response},
 };
@@ -27,8 +27,14 @@ use itertools::Itertools;
 use prost::Message;
 use slog::Logger;
 use std::{
-    collections::HashMap, fmt::Display, marker::PhantomData, ops::ControlFlow, str::FromStr,
-    sync::Arc, time::Duration,
+    collections::HashMap,
+    fmt::Display,
+    marker::PhantomData,
+    ops::ControlFlow,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+    sync::Arc,
+    time::Duration,
 };
 use tonic::codegen::InterceptedService;
 use tonic::{
@@ -202,6 +208,8 @@ pub struct FirehoseEndpoint {
     genesis_decoder: Box<dyn GenesisDecoder>,
     endpoint_metrics: Arc<EndpointMetrics>,
     channel: Channel,
+    /// Whether the endpoint responded to the last health check
+    healthy: AtomicBool,
 }
 
 #[derive(Debug)]
@@ -377,9 +385,52 @@ impl FirehoseEndpoint {
             subgraph_limit,
             endpoint_metrics,
             genesis_decoder,
+            healthy: AtomicBool::new(true),
         }
     }
 
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::SeqCst)
+    }
+
+    fn set_healthy(&self, healthy: bool) -> bool {
+        self.healthy.swap(healthy, Ordering::SeqCst)
+    }
+
+    /// Check that the endpoint responds by asking it for the genesis block.
+    /// Block streams only use endpoints that fail the check if no other
+    /// endpoint of the chain passes it
+    pub async fn check_health(self: &Arc<Self>, logger: &Logger) -> bool {
+        let res = self
+            .net_identifiers_with_timeout(ENV_VARS.genesis_validation_timeout)
+            .await;
+        let healthy = res.is_ok();
+        let was_healthy = self.set_healthy(healthy);
+        match res {
+            Ok(_) if !was_healthy => {
+                info!(logger, "Firehose endpoint is healthy again"; "provider" => self.provider.as_str());
+            }
+            Err(e) if was_healthy => {
+                warn!(logger, "Firehose endpoint failed the health check";
+                    "provider" => self.provider.as_str(),
+                    "error" => format!("{:#}", e));
+            }
+            _ => {}
+        }
+        healthy
+    }
+
+    /// Count an error on an open stream against the endpoint, so that the
+    /// stream reconnects to a different endpoint if there is one with fewer
+    /// errors
+    pub fn report_stream_failure(&self, conn_type: ConnectionType) {
+        self.endpoint_metrics.failure(&RequestLabels {
+            provider: self.provider.clone(),
+            req_type: "stream".into(),
+            conn_type,
+        });
+    }
+
     pub fn current_error_count(&self) -> u64 {
         self.endpoint_metrics.get_count(&self.provider)
     }
@@ -409,7 +460,8 @@ impl FirehoseEndpoint {
         let mut client: FetchClient<
             InterceptedService<MetricsInterceptor<Channel>, AuthInterceptor>,
         > = FetchClient::with_interceptor(metrics, self.auth.clone())
-            .accept_compressed(CompressionEncoding::Gzip);
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd);
 
         if self.compression_enabled {
             client = client.send_compressed(CompressionEncoding::Gzip);
@@ -435,8 +487,12 @@ impl FirehoseEndpoint {
             },
         };
 
+        // Offer both encodings and let the provider pick the one it prefers
+        // for the responses; blocks compress well, and that saves a lot of
+        // bandwidth when the provider is far away
         let mut client = StreamClient::with_interceptor(metrics, self.auth.clone())
-            .accept_compressed(CompressionEncoding::Gzip);
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd);
 
         if self.compression_enabled {
             client = client.send_compressed(CompressionEncoding::Gzip);
@@ -466,7 +522,8 @@ impl FirehoseEndpoint {
             metrics,
             self.auth.clone(),
         )
-        .accept_compressed(CompressionEncoding::Gzip);
+        .accept_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Zstd);
 
         if self.compression_enabled {
             client = client.send_compressed(CompressionEncoding::Gzip);
@@ -663,14 +720,15 @@ impl FirehoseEndpoints {
     }
 
     /// This function will attempt to grab an endpoint based on the Lowest error count
-    //  with high capacity available. If an adapter cannot be found `endpoint` will
+    //  with high capacity available. Endpoints that failed their last health check are
+    // only used if all endpoints did. If an adapter cannot be found `endpoint` will
     // return an error.
     pub async fn endpoint(&self) -> anyhow::Result<Arc<FirehoseEndpoint>> {
-        let endpoint = self
-            .1
-            .get_all(&self.0)
-            .await?
-            .into_iter()
+        let endpoints = self.1.get_all(&self.0).await?;
+        let all_unhealthy = endpoints.iter().all(|endpoint| !endpoint.is_healthy());
+        let endpoint = endpoints
+            .iter()
+            .filter(|endpoint| all_unhealthy || endpoint.is_healthy())
             .sorted_by_key(|x| x.current_error_count())
             .try_fold(None, |acc, adapter| {
                 match adapter.get_capacity() {
@@ -879,6 +937,40 @@ mod test {
         assert_eq!(res.provider, high_error_adapter1.provider);
     }
 
+    #[tokio::test]
+    async fn firehose_endpoint_skips_unhealthy() {
+        let endpoint = |provider: &str| {
+            Arc::new(FirehoseEndpoint::new(
+                provider.to_string(),
+                "http://127.0.0.1".to_string(),
+                None,
+                None,
+                false,
+                false,
+                SubgraphLimit::Unlimited,
+                Arc::new(EndpointMetrics::mock()),
+                NoopGenesisDecoder::boxed(),
+            ))
+        };
+        let healthy = endpoint("healthy");
+        let unhealthy = endpoint("unhealthy");
+        unhealthy.set_healthy(false);
+
+        let endpoints = FirehoseEndpoints::for_testing(vec![unhealthy.clone(), healthy.clone()]);
+        let res = endpoints.endpoint().await.unwrap();
+        assert_eq!(res.provider, healthy.provider);
+        mem::drop(res);
+
+        // Without a healthy endpoint, the unhealthy ones are used
+        healthy.set_healthy(false);
+        endpoints.endpoint().await.unwrap();
+
+        // A successful health check makes the endpoint usable again
+        let logger = Logger::root(Discard, o!());
+        assert!(unhealthy.check_health(&logger).await);
+        assert!(unhealthy.is_healthy());
+    }
+
     #[test]
     fn subgraph_limit_calculates_availability() {
         #[derive(Debug)]
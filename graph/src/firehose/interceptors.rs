@@ -1,9 +1,12 @@
 use std::future::Future;
 use std::pin::Pin;
-use std::{fmt, sync::Arc};
+use std::sync::Arc;
+use std::task::{Context, Poll};
 
+use bytes::Buf;
+use prometheus::IntCounter;
 use tonic::{
-    codegen::Service,
+    codegen::{http, Body, Service},
     metadata::{Ascii, MetadataValue},
     service::Interceptor,
 };
@@ -46,17 +49,20 @@ pub struct MetricsInterceptor<S> {
     pub(crate) labels: RequestLabels,
 }
 
-impl<S, Request> Service<Request> for MetricsInterceptor<S>
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for MetricsInterceptor<S>
 where
-    S: Service<Request>,
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
     S::Future: Send + 'static,
-    Request: fmt::Debug,
+    S::Error: 'static,
+    ReqBody: 'static,
+    ResBody: 'static,
 {
-    type Response = S::Response;
+    type Response = http::Response<CountingBody<ResBody>>;
 
     type Error = S::Error;
 
-    type Future = Pin<Box<dyn Future<Output = <S::Future as Future>::Output> + Send + 'static>>;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
 
     fn poll_ready(
         &mut self,
@@ -65,7 +71,7 @@ where
         self.service.poll_ready(cx)
     }
 
-    fn call(&mut self, req: Request) -> Self::Future {
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
         let labels = self.labels.clone();
         let metrics = self.metrics.clone();
 
@@ -77,9 +83,49 @@ where
             } else {
                 metrics.failure(&labels);
             }
-            res
+            let received = metrics.received_bytes(&labels);
+            res.map(|res| res.map(|inner| CountingBody { inner, received }))
         };
 
         Box::pin(res)
     }
 }
+
+/// A response body that counts the bytes that we receive for it. These are
+/// the bytes on the wire, i.e., compressed if the provider compressed the
+/// response
+pub struct CountingBody<B> {
+    inner: B,
+    received: IntCounter,
+}
+
+impl<B> Body for CountingBody<B>
+where
+    B: Body + Unpin,
+{
+    type Data = B::Data;
+
+    type Error = B::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let res = Pin::new(&mut self.inner).poll_data(cx);
+        if let Poll::Ready(Some(Ok(data))) = &res {
+            self.received.inc_by(data.remaining() as u64);
+        }
+        res
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
@@ -14,6 +14,7 @@ use crate::ipfs::ContentPath;
 use crate::ipfs::IpfsError;
 use crate::ipfs::IpfsResult;
 use crate::ipfs::RetryPolicy;
+use crate::ipfs::ServerAddress;
 
 /// A read-only connection to an IPFS server.
 #[async_trait]
@@ -21,6 +22,11 @@ pub trait IpfsClient: Send + Sync + 'static {
     /// Returns the logger associated with the client.
     fn logger(&self) -> &Logger;
 
+    /// Returns the address of the IPFS server if the client connects to a single server.
+    fn server_address(&self) -> Option<&ServerAddress> {
+        None
+    }
+
     /// Sends a request to the IPFS server and returns a raw response.
     async fn call(self: Arc<Self>, req: IpfsRequest) -> IpfsResult<IpfsResponse>;
 
@@ -121,6 +127,16 @@ pub enum IpfsRequest {
     GetBlock(ContentPath),
 }
 
+impl IpfsRequest {
+    /// Returns the content path of the request.
+    pub fn path(&self) -> &ContentPath {
+        match self {
+            Self::Cat(path) => path,
+            Self::GetBlock(path) => path,
+        }
+    }
+}
+
 /// Contains a raw, successful IPFS response.
 #[derive(Debug)]
 pub struct IpfsResponse {
@@ -165,7 +181,7 @@ impl IpfsResponse {
     }
 }
 
-async fn run_with_optional_timeout<F, O>(
+pub(super) async fn run_with_optional_timeout<F, O>(
     path: &ContentPath,
     fut: F,
     timeout: Option<Duration>,
@@ -119,6 +119,10 @@ impl IpfsClient for IpfsGatewayClient {
         &self.logger
     }
 
+    fn server_address(&self) -> Option<&ServerAddress> {
+        Some(&self.server_address)
+    }
+
     async fn call(self: Arc<Self>, req: IpfsRequest) -> IpfsResult<IpfsResponse> {
         use IpfsRequest::*;
 
@@ -4,12 +4,15 @@ use anyhow::anyhow;
 use slog::info;
 use slog::Logger;
 
+use crate::components::metrics::MetricsRegistry;
+use crate::env::ENV_VARS;
 use crate::util::security::SafeDisplay;
 
 mod client;
 mod content_path;
 mod error;
 mod gateway_client;
+mod pinning;
 mod pool;
 mod retry_policy;
 mod rpc_client;
@@ -24,6 +27,8 @@ pub use self::content_path::ContentPath;
 pub use self::error::IpfsError;
 pub use self::error::RequestError;
 pub use self::gateway_client::IpfsGatewayClient;
+pub use self::pinning::PinStatus;
+pub use self::pinning::PinningService;
 pub use self::pool::IpfsClientPool;
 pub use self::retry_policy::RetryPolicy;
 pub use self::rpc_client::IpfsRpcClient;
@@ -35,9 +40,11 @@ pub type IpfsResult<T> = Result<T, IpfsError>;
 ///
 /// If multiple IPFS server addresses are specified, an IPFS client pool is created internally
 /// and for each IPFS request, the fastest client that can provide the content is
-/// automatically selected and the response is streamed from that client.
+/// automatically selected and the response is streamed from that client. Servers that
+/// repeatedly fail are only used when none of the other servers can provide the content.
 pub async fn new_ipfs_client<I, S>(
     server_addresses: I,
+    registry: &MetricsRegistry,
     logger: &Logger,
 ) -> IpfsResult<Arc<dyn IpfsClient>>
 where
@@ -100,9 +107,31 @@ where
         n => {
             info!(logger, "Creating a pool of {} IPFS clients", n);
 
-            let pool = IpfsClientPool::new(clients, logger);
+            let pool = IpfsClientPool::new(clients, registry, logger);
 
             Ok(Arc::new(pool))
         }
     }
 }
+
+/// Creates a client for the pinning service set with `GRAPH_IPFS_PINNING_SERVICE_URL`,
+/// or returns `None` if no pinning service is configured.
+pub fn new_pinning_service(
+    registry: &MetricsRegistry,
+    logger: &Logger,
+) -> anyhow::Result<Option<Arc<PinningService>>> {
+    let Some(url) = &ENV_VARS.mappings.ipfs_pinning_service_url else {
+        return Ok(None);
+    };
+    let token = ENV_VARS
+        .mappings
+        .ipfs_pinning_service_token
+        .clone()
+        .ok_or_else(|| {
+            anyhow!("GRAPH_IPFS_PINNING_SERVICE_TOKEN must be set to use a pinning service")
+        })?;
+
+    let service = PinningService::new(url, token, registry, logger)?;
+
+    Ok(Some(Arc::new(service)))
+}
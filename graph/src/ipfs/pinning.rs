@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use anyhow::Error;
+use prometheus::CounterVec;
+use serde::Deserialize;
+use slog::debug;
+use slog::info;
+use slog::warn;
+use slog::Logger;
+
+use crate::components::metrics::MetricsRegistry;
+use crate::ipfs::ContentPath;
+use crate::util::security::SafeDisplay;
+
+/// How often the status of a pin that is not finished yet is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the status of a pin is checked before giving up on it; pins
+/// that are not finished by then are requested again the next time the CID
+/// is referenced
+const MAX_POLLS: usize = 120;
+
+/// The status of a pin, as reported by the pinning service
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PinStatus {
+    Queued,
+    Pinning,
+    Pinned,
+    Failed,
+}
+
+impl PinStatus {
+    fn is_final(&self) -> bool {
+        matches!(self, PinStatus::Pinned | PinStatus::Failed)
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            PinStatus::Queued => "queued",
+            PinStatus::Pinning => "pinning",
+            PinStatus::Pinned => "pinned",
+            PinStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Pin {
+    requestid: String,
+    status: PinStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pins {
+    results: Vec<Pin>,
+}
+
+/// Pins the CIDs that deployments reference with a service that implements
+/// the [IPFS Pinning Service API](https://ipfs.github.io/pinning-services-api-spec/),
+/// like Pinata or web3.storage, so that the files stay retrievable even
+/// when nobody else keeps them around.
+///
+/// Every CID is only pinned once per process. Pinning happens in the
+/// background and never holds up or fails the request that referenced the
+/// CID; problems are logged, and the CID is pinned again the next time it
+/// is referenced.
+pub struct PinningService {
+    client: reqwest::Client,
+    url: reqwest::Url,
+    token: String,
+    pins: Mutex<HashMap<String, PinStatus>>,
+    results: CounterVec,
+    poll_interval: Duration,
+    logger: Logger,
+}
+
+impl PinningService {
+    /// Creates a client for the pinning service at `url` that authenticates
+    /// with the access `token`.
+    pub fn new(
+        url: &str,
+        token: String,
+        registry: &MetricsRegistry,
+        logger: &Logger,
+    ) -> Result<Self, Error> {
+        let mut url = reqwest::Url::parse(url)
+            .map_err(|e| anyhow!("invalid pinning service URL `{}`: {}", SafeDisplay(url), e))?;
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+
+        let results = registry.global_counter_vec(
+            "ipfs_pin_results",
+            "The number of CIDs that were sent to the pinning service, by how pinning them ended",
+            &["status"],
+        )?;
+
+        info!(
+            logger,
+            "Pinning referenced IPFS files with the pinning service at '{}'",
+            SafeDisplay(&url)
+        );
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url,
+            token,
+            pins: Mutex::new(HashMap::new()),
+            results,
+            poll_interval: POLL_INTERVAL,
+            logger: logger.to_owned(),
+        })
+    }
+
+    /// The status of the pin for the CID of `path`, if it was pinned by
+    /// this process.
+    pub fn status(&self, path: &ContentPath) -> Option<PinStatus> {
+        let cid = path.cid().to_string();
+        self.pins.lock().unwrap().get(&cid).copied()
+    }
+
+    /// Pins the CID of `path` in the background, unless it is already
+    /// pinned or being pinned.
+    pub fn pin(self: &Arc<Self>, path: &ContentPath) {
+        let cid = path.cid().to_string();
+        {
+            let mut pins = self.pins.lock().unwrap();
+            match pins.get(&cid) {
+                Some(PinStatus::Failed) | None => {
+                    pins.insert(cid.clone(), PinStatus::Queued);
+                }
+                Some(_) => return,
+            }
+        }
+
+        let this = self.clone();
+        crate::spawn(async move {
+            let status = match this.pin_and_wait(&cid).await {
+                Ok(status) => status,
+                Err(e) => {
+                    warn!(this.logger, "Failed to pin IPFS file";
+                        "cid" => &cid,
+                        "error" => format!("{:#}", e));
+                    this.pins.lock().unwrap().remove(&cid);
+                    this.results.with_label_values(&["error"]).inc();
+                    return;
+                }
+            };
+
+            if status.is_final() {
+                this.results.with_label_values(&[status.as_str()]).inc();
+                this.pins.lock().unwrap().insert(cid.clone(), status);
+            } else {
+                this.pins.lock().unwrap().remove(&cid);
+            }
+
+            if status == PinStatus::Failed {
+                warn!(this.logger, "The pinning service could not pin IPFS file"; "cid" => &cid);
+            } else {
+                debug!(this.logger, "Pinned IPFS file";
+                    "cid" => &cid,
+                    "status" => status.as_str());
+            }
+        });
+    }
+
+    /// Asks the service to pin `cid` unless it already has a pin for it,
+    /// and waits until pinning is finished.
+    async fn pin_and_wait(&self, cid: &str) -> Result<PinStatus, Error> {
+        let mut pin = match self.existing(cid).await? {
+            Some(pin) => pin,
+            None => self.request(cid).await?,
+        };
+        self.pins.lock().unwrap().insert(cid.to_owned(), pin.status);
+
+        for _ in 0..MAX_POLLS {
+            if pin.status.is_final() {
+                break;
+            }
+            tokio::time::sleep(self.poll_interval).await;
+            pin = self.get(&pin.requestid).await?;
+            self.pins.lock().unwrap().insert(cid.to_owned(), pin.status);
+        }
+
+        Ok(pin.status)
+    }
+
+    /// The pin for `cid` that the service already has, unless it failed
+    async fn existing(&self, cid: &str) -> Result<Option<Pin>, Error> {
+        let pins: Pins = self
+            .client
+            .get(self.url.join("pins")?)
+            .bearer_auth(&self.token)
+            .query(&[("cid", cid), ("status", "queued,pinning,pinned")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(pins.results.into_iter().next())
+    }
+
+    async fn request(&self, cid: &str) -> Result<Pin, Error> {
+        let pin = self
+            .client
+            .post(self.url.join("pins")?)
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({ "cid": cid }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(pin)
+    }
+
+    async fn get(&self, requestid: &str) -> Result<Pin, Error> {
+        let pin = self
+            .client
+            .get(self.url.join(&format!("pins/{requestid}"))?)
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(pin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::matchers as m;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::log::discard;
+
+    const CID: &str = "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn";
+
+    async fn make_service() -> (MockServer, Arc<PinningService>) {
+        let server = MockServer::start().await;
+        let registry = MetricsRegistry::mock();
+        let mut service = PinningService::new(
+            &format!("{}/psa", server.uri()),
+            "token".into(),
+            &registry,
+            &discard(),
+        )
+        .unwrap();
+        service.poll_interval = Duration::from_millis(10);
+
+        (server, Arc::new(service))
+    }
+
+    async fn wait_for(service: &PinningService, path: &ContentPath, status: PinStatus) {
+        for _ in 0..100 {
+            if service.status(path) == Some(status) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("the pin did not reach the status {:?}", status);
+    }
+
+    #[tokio::test]
+    async fn pin_new_cid() {
+        let (server, service) = make_service().await;
+        let path = ContentPath::new(format!("{CID}/file.json")).unwrap();
+
+        Mock::given(m::method("GET"))
+            .and(m::path("/psa/pins"))
+            .and(m::query_param("cid", CID))
+            .and(m::header("authorization", "Bearer token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 0,
+                "results": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(m::method("POST"))
+            .and(m::path("/psa/pins"))
+            .and(m::body_json(json!({ "cid": CID })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "requestid": "req1",
+                "status": "queued"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(m::method("GET"))
+            .and(m::path("/psa/pins/req1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "requestid": "req1",
+                "status": "pinned"
+            })))
+            .mount(&server)
+            .await;
+
+        service.pin(&path);
+        wait_for(&service, &path, PinStatus::Pinned).await;
+
+        // Pinning the CID again does not ask the service again
+        service.pin(&ContentPath::new(CID).unwrap());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    #[tokio::test]
+    async fn pin_existing_cid() {
+        let (server, service) = make_service().await;
+        let path = ContentPath::new(CID).unwrap();
+
+        Mock::given(m::method("GET"))
+            .and(m::path("/psa/pins"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{ "requestid": "req1", "status": "pinned" }]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(m::method("POST"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        service.pin(&path);
+        wait_for(&service, &path, PinStatus::Pinned).await;
+    }
+
+    #[tokio::test]
+    async fn errors_are_retried() {
+        let (server, service) = make_service().await;
+        let path = ContentPath::new(CID).unwrap();
+
+        Mock::given(m::method("GET"))
+            .and(m::path("/psa/pins"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        service.pin(&path);
+        for _ in 0..100 {
+            if service.status(&path).is_none() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // The failed attempt is forgotten so that the CID is pinned again
+        // the next time it is referenced
+        assert_eq!(None, service.status(&path));
+    }
+}
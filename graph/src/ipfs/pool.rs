@@ -1,35 +1,190 @@
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
 use futures03::stream::FuturesUnordered;
 use futures03::stream::StreamExt;
+use prometheus::CounterVec;
+use prometheus::GaugeVec;
+use slog::info;
+use slog::warn;
 use slog::Logger;
 
+use crate::components::metrics::MetricsRegistry;
+use crate::env::ENV_VARS;
+use crate::ipfs::client::run_with_optional_timeout;
 use crate::ipfs::IpfsClient;
 use crate::ipfs::IpfsError;
 use crate::ipfs::IpfsRequest;
 use crate::ipfs::IpfsResponse;
 use crate::ipfs::IpfsResult;
+use crate::util::security::SafeDisplay;
 
 /// Contains a list of IPFS clients and, for each read request, selects the fastest IPFS client
 /// that can provide the content and streams the response from that client.
 ///
 /// This can significantly improve performance when using multiple IPFS gateways,
 /// as some of them may already have the content cached.
+///
+/// Clients that repeatedly time out or can not be reached are considered unhealthy for a while,
+/// and are only used when none of the healthy clients can provide the content.
 pub struct IpfsClientPool {
-    clients: Vec<Arc<dyn IpfsClient>>,
+    endpoints: Vec<Endpoint>,
+    metrics: PoolMetrics,
+    timeout: Option<Duration>,
+    unhealthy_after: u32,
+    unhealthy_cooldown: Duration,
     logger: Logger,
 }
 
+struct Endpoint {
+    client: Arc<dyn IpfsClient>,
+    name: String,
+    health: Mutex<Health>,
+}
+
+#[derive(Default)]
+struct Health {
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+}
+
+struct PoolMetrics {
+    requests: CounterVec,
+    healthy: GaugeVec,
+}
+
+impl PoolMetrics {
+    fn new(registry: &MetricsRegistry) -> Self {
+        let requests = registry
+            .global_counter_vec(
+                "ipfs_endpoint_requests",
+                "The number of requests to each IPFS server, by whether they succeeded",
+                &["endpoint", "result"],
+            )
+            .unwrap();
+        let healthy = registry
+            .global_gauge_vec(
+                "ipfs_endpoint_healthy",
+                "Whether the IPFS server is considered healthy; 0 = no, 1 = yes",
+                &["endpoint"],
+            )
+            .unwrap();
+        Self { requests, healthy }
+    }
+}
+
 impl IpfsClientPool {
     /// Creates a new IPFS client pool from the specified clients.
-    pub fn new(clients: Vec<Arc<dyn IpfsClient>>, logger: &Logger) -> Self {
+    pub fn new(
+        clients: Vec<Arc<dyn IpfsClient>>,
+        registry: &MetricsRegistry,
+        logger: &Logger,
+    ) -> Self {
+        let metrics = PoolMetrics::new(registry);
+        let endpoints = clients
+            .into_iter()
+            .enumerate()
+            .map(|(i, client)| {
+                let name = match client.server_address() {
+                    Some(server_address) => SafeDisplay(server_address).to_string(),
+                    None => format!("client_{i}"),
+                };
+                metrics.healthy.with_label_values(&[&name]).set(1.0);
+
+                Endpoint {
+                    client,
+                    name,
+                    health: Mutex::new(Health::default()),
+                }
+            })
+            .collect();
+
         Self {
-            clients,
+            endpoints,
+            metrics,
+            timeout: Some(ENV_VARS.mappings.ipfs_endpoint_timeout),
+            unhealthy_after: ENV_VARS.mappings.ipfs_unhealthy_after,
+            unhealthy_cooldown: ENV_VARS.mappings.ipfs_unhealthy_cooldown,
             logger: logger.to_owned(),
         }
     }
+
+    fn is_healthy(&self, endpoint: &Endpoint) -> bool {
+        let health = endpoint.health.lock().unwrap();
+
+        match health.unhealthy_until {
+            Some(until) => until <= Instant::now(),
+            None => true,
+        }
+    }
+
+    async fn call_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        req: IpfsRequest,
+    ) -> IpfsResult<IpfsResponse> {
+        let path = req.path().to_owned();
+        let result =
+            run_with_optional_timeout(&path, endpoint.client.clone().call(req), self.timeout).await;
+
+        let outcome = if result.is_ok() { "success" } else { "failure" };
+        self.metrics
+            .requests
+            .with_label_values(&[&endpoint.name, outcome])
+            .inc();
+
+        match &result {
+            Ok(_) => self.record_success(endpoint),
+            // Content that is not available or too large says nothing about the health of the
+            // server, but a server that does not respond or can not be reached is unhealthy
+            Err(err) if err.is_timeout() || err.is_networking() => {
+                self.record_failure(endpoint, err)
+            }
+            Err(_) => {}
+        }
+
+        result
+    }
+
+    fn record_success(&self, endpoint: &Endpoint) {
+        let mut health = endpoint.health.lock().unwrap();
+
+        if health.unhealthy_until.is_some() {
+            info!(self.logger, "IPFS server is healthy again"; "server" => &endpoint.name);
+        }
+
+        *health = Health::default();
+        self.metrics
+            .healthy
+            .with_label_values(&[&endpoint.name])
+            .set(1.0);
+    }
+
+    fn record_failure(&self, endpoint: &Endpoint, err: &IpfsError) {
+        let mut health = endpoint.health.lock().unwrap();
+
+        health.consecutive_failures += 1;
+        if health.consecutive_failures < self.unhealthy_after {
+            return;
+        }
+
+        if health.unhealthy_until.is_none() {
+            warn!(self.logger, "IPFS server is unhealthy";
+                "server" => &endpoint.name,
+                "consecutive_failures" => health.consecutive_failures,
+                "error" => format!("{err:#}"));
+        }
+
+        health.unhealthy_until = Some(Instant::now() + self.unhealthy_cooldown);
+        self.metrics
+            .healthy
+            .with_label_values(&[&endpoint.name])
+            .set(0.0);
+    }
 }
 
 #[async_trait]
@@ -39,28 +194,31 @@ impl IpfsClient for IpfsClientPool {
     }
 
     async fn call(self: Arc<Self>, req: IpfsRequest) -> IpfsResult<IpfsResponse> {
-        let mut futs = self
-            .clients
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) = self
+            .endpoints
             .iter()
-            .map(|client| client.clone().call(req.clone()))
-            .collect::<FuturesUnordered<_>>();
+            .partition(|endpoint| self.is_healthy(endpoint));
 
         let mut last_err = None;
 
-        while let Some(result) = futs.next().await {
-            match result {
-                Ok(resp) => return Ok(resp),
-                Err(err) => last_err = Some(err),
-            };
+        // Fail over to the unhealthy clients only when none of the healthy ones could
+        // provide the content
+        for endpoints in [healthy, unhealthy] {
+            let mut futs = endpoints
+                .into_iter()
+                .map(|endpoint| self.call_endpoint(endpoint, req.clone()))
+                .collect::<FuturesUnordered<_>>();
+
+            while let Some(result) = futs.next().await {
+                match result {
+                    Ok(resp) => return Ok(resp),
+                    Err(err) => last_err = Some(err),
+                };
+            }
         }
 
-        let path = match req {
-            IpfsRequest::Cat(path) => path,
-            IpfsRequest::GetBlock(path) => path,
-        };
-
         let err = last_err.unwrap_or_else(|| IpfsError::ContentNotAvailable {
-            path,
+            path: req.path().to_owned(),
             reason: anyhow!("no clients can provide the content"),
         });
 
@@ -145,7 +303,11 @@ mod tests {
             .await;
 
         let clients: Vec<Arc<dyn IpfsClient>> = vec![client_1, client_2, client_3];
-        let pool = Arc::new(IpfsClientPool::new(clients, &discard()));
+        let pool = Arc::new(IpfsClientPool::new(
+            clients,
+            &MetricsRegistry::mock(),
+            &discard(),
+        ));
 
         let bytes = pool
             .cat_stream(&make_path(), None, RetryPolicy::None)
@@ -198,7 +360,11 @@ mod tests {
             .await;
 
         let clients: Vec<Arc<dyn IpfsClient>> = vec![client_1, client_2, client_3];
-        let pool = Arc::new(IpfsClientPool::new(clients, &discard()));
+        let pool = Arc::new(IpfsClientPool::new(
+            clients,
+            &MetricsRegistry::mock(),
+            &discard(),
+        ));
 
         let bytes = pool
             .cat(&make_path(), usize::MAX, None, RetryPolicy::None)
@@ -245,7 +411,11 @@ mod tests {
             .await;
 
         let clients: Vec<Arc<dyn IpfsClient>> = vec![client_1, client_2, client_3];
-        let pool = Arc::new(IpfsClientPool::new(clients, &discard()));
+        let pool = Arc::new(IpfsClientPool::new(
+            clients,
+            &MetricsRegistry::mock(),
+            &discard(),
+        ));
 
         let bytes = pool
             .get_block(&make_path(), None, RetryPolicy::None)
@@ -254,4 +424,118 @@ mod tests {
 
         assert_eq!(bytes.as_ref(), b"server_3")
     }
+
+    #[tokio::test]
+    async fn clients_that_time_out_become_unhealthy() {
+        let (server_1, client_1) = make_client().await;
+        let (server_2, client_2) = make_client().await;
+
+        mock_get()
+            .respond_with(ResponseTemplate::new(StatusCode::GATEWAY_TIMEOUT))
+            .expect(1)
+            .mount(&server_1)
+            .await;
+
+        mock_get()
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_bytes(b"server_2")
+                    .set_delay(ms(100)),
+            )
+            .expect(2)
+            .mount(&server_2)
+            .await;
+
+        let clients: Vec<Arc<dyn IpfsClient>> = vec![client_1, client_2];
+        let mut pool = IpfsClientPool::new(clients, &MetricsRegistry::mock(), &discard());
+        pool.unhealthy_after = 1;
+        let pool = Arc::new(pool);
+
+        // The second request is not sent to the first server anymore
+        for _ in 0..2 {
+            let bytes = pool
+                .clone()
+                .cat(&make_path(), usize::MAX, None, RetryPolicy::None)
+                .await
+                .unwrap();
+
+            assert_eq!(bytes.as_ref(), b"server_2");
+        }
+
+        assert!(!pool.is_healthy(&pool.endpoints[0]));
+        assert!(pool.is_healthy(&pool.endpoints[1]));
+    }
+
+    #[tokio::test]
+    async fn requests_time_out_for_each_client() {
+        let (server, client) = make_client().await;
+
+        mock_get()
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_bytes(b"server")
+                    .set_delay(ms(1000)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let clients: Vec<Arc<dyn IpfsClient>> = vec![client];
+        let mut pool = IpfsClientPool::new(clients, &MetricsRegistry::mock(), &discard());
+        pool.timeout = Some(ms(100));
+        let pool = Arc::new(pool);
+
+        let err = pool
+            .clone()
+            .cat(&make_path(), usize::MAX, None, RetryPolicy::None)
+            .await
+            .unwrap_err();
+
+        assert!(err.is_timeout());
+        assert_eq!(
+            1,
+            pool.endpoints[0]
+                .health
+                .lock()
+                .unwrap()
+                .consecutive_failures
+        );
+    }
+
+    #[tokio::test]
+    async fn unhealthy_clients_are_used_when_healthy_clients_fail() {
+        let (server_1, client_1) = make_client().await;
+        let (server_2, client_2) = make_client().await;
+
+        mock_get()
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_bytes(b"server_1"))
+            .expect(1)
+            .mount(&server_1)
+            .await;
+
+        mock_get()
+            .respond_with(ResponseTemplate::new(StatusCode::NOT_FOUND))
+            .expect(1)
+            .mount(&server_2)
+            .await;
+
+        let clients: Vec<Arc<dyn IpfsClient>> = vec![client_1, client_2];
+        let pool = Arc::new(IpfsClientPool::new(
+            clients,
+            &MetricsRegistry::mock(),
+            &discard(),
+        ));
+        pool.endpoints[0].health.lock().unwrap().unhealthy_until =
+            Some(Instant::now() + ms(60_000));
+
+        let bytes = pool
+            .clone()
+            .cat(&make_path(), usize::MAX, None, RetryPolicy::None)
+            .await
+            .unwrap();
+
+        assert_eq!(bytes.as_ref(), b"server_1");
+        // A successful request makes the client healthy again
+        assert!(pool.is_healthy(&pool.endpoints[0]));
+    }
 }
@@ -118,6 +118,10 @@ impl IpfsClient for IpfsRpcClient {
         &self.logger
     }
 
+    fn server_address(&self) -> Option<&ServerAddress> {
+        Some(&self.server_address)
+    }
+
     async fn call(self: Arc<Self>, req: IpfsRequest) -> IpfsResult<IpfsResponse> {
         use IpfsRequest::*;
 
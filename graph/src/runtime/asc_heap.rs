@@ -2,6 +2,8 @@ use std::mem::MaybeUninit;
 
 use semver::Version;
 
+use crate::data::store::scalar::BigDecimalPrecision;
+
 use super::{
     gas::GasCounter, AscIndexId, AscPtr, AscType, DeterministicHostError, HostExportError,
     IndexForAscTypeId,
@@ -30,7 +32,29 @@ pub trait AscHeap {
 
     fn api_version(&self) -> Version;
 
+    /// The precision and rounding for `BigDecimal` values that are read
+    /// from the heap
+    fn big_decimal_precision(&self) -> BigDecimalPrecision {
+        BigDecimalPrecision::default()
+    }
+
     fn asc_type_id(&mut self, type_id_index: IndexForAscTypeId) -> Result<u32, HostExportError>;
+
+    /// Allocate an object of the class with id `rt_id` through the runtime of the module, write
+    /// `bytes` as its content and return the address of the content. The runtime writes the
+    /// header and keeps track of the object for its garbage collector.
+    /// Only used for version >= 0.0.10.
+    fn managed_new(
+        &mut self,
+        _bytes: &[u8],
+        _rt_id: u32,
+        _gas: &GasCounter,
+    ) -> Result<u32, HostExportError> {
+        Err(DeterministicHostError::from(anyhow::anyhow!(
+            "allocating objects through the module runtime is not supported by this heap"
+        ))
+        .into())
+    }
 }
 
 /// Instantiate `rust_obj` as an Asc object of class `C`.
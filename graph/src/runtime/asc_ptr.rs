@@ -1,5 +1,6 @@
 use super::gas::GasCounter;
 use super::{padding_to_16, DeterministicHostError, HostExportError};
+use crate::data::subgraph::API_VERSION_0_0_10;
 
 use super::{AscHeap, AscIndexId, AscType, IndexForAscTypeId};
 use semver::Version;
@@ -95,6 +96,15 @@ impl<C: AscType> AscPtr<C> {
                 let heap_ptr = heap.raw_new(&asc_obj.to_asc_bytes()?, gas)?;
                 Ok(AscPtr::new(heap_ptr))
             }
+            version if version >= API_VERSION_0_0_10 => {
+                let bytes = asc_obj.to_asc_bytes()?;
+                // The runtime pads the allocation itself, so only the
+                // content needs to be written
+                let content_len = asc_obj.content_len(&bytes);
+                let rt_id = heap.asc_type_id(C::INDEX_ASC_TYPE_ID)?;
+                let heap_ptr = heap.managed_new(&bytes[..content_len], rt_id, gas)?;
+                Ok(AscPtr::new(heap_ptr))
+            }
             _ => {
                 let mut bytes = asc_obj.to_asc_bytes()?;
 
@@ -137,7 +147,7 @@ impl<C: AscType> AscPtr<C> {
     /// - gc_info2: usize -> second GC info (we don't free memory so it's irrelevant)
     /// - rt_id: u32 -> identifier for the class being allocated
     /// - rt_size: u32 -> content size
-    /// Only used for version >= 0.0.5.
+    /// Only used for versions >= 0.0.5 and < 0.0.10.
     fn generate_header<H: AscHeap + ?Sized>(
         heap: &mut H,
         type_id_index: IndexForAscTypeId,
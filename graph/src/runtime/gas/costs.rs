@@ -50,6 +50,13 @@ pub const BIG_MATH_GAS_OP: GasOp = GasOp {
     size_mult: BIG_MATH_GAS_PER_BYTE,
 };
 
+// Recovering or verifying a signature takes tens of microseconds, far
+// longer than what the base cost of other host exports accounts for
+pub const SIGNATURE_CHECK: GasOp = GasOp {
+    base_cost: DEFAULT_BASE_COST * 10,
+    size_mult: DEFAULT_GAS_PER_BYTE,
+};
+
 // Allow up to 100,000 data sources to be created
 pub const CREATE_DATA_SOURCE: Gas = Gas(CONST_MAX_GAS_PER_HANDLER / 100_000);
 
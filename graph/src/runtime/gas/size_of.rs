@@ -18,6 +18,7 @@ impl GasSizeOf for Value {
             Value::Int(int) => int.gas_size_of(),
             Value::Int8(int) => int.gas_size_of(),
             Value::Timestamp(ts) => ts.gas_size_of(),
+            Value::GeoPoint(point) => point.gas_size_of(),
             Value::Bytes(bytes) => bytes.gas_size_of(),
             Value::Bool(bool) => bool.gas_size_of(),
             Value::BigInt(big_int) => big_int.gas_size_of(),
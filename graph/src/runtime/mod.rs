@@ -247,11 +247,12 @@ pub enum IndexForAscTypeId {
     NearChunkHeader = 84,
     NearBlock = 85,
     NearReceiptWithOutcome = 86,
-    // Reserved discriminant space for more Near type IDs: [87, 999]:
+    NearFunctionCallWithOutcome = 87,
+    // Reserved discriminant space for more Near type IDs: [88, 999]:
     // Continue to add more Near type IDs here.
     // e.g.:
-    // NextNearType = 87,
-    // AnotherNearType = 88,
+    // NextNearType = 88,
+    // AnotherNearType = 89,
     // ...
     // LastNearType = 999,
 
@@ -261,10 +262,24 @@ pub enum IndexForAscTypeId {
     ArrayH256 = 1002,
     ArrayLog = 1003,
     ArrayTypedMapStringStoreValue = 1004,
+    ArraySmartContractCall = 1005,
+    ArrayArrayEthereumValue = 1006,
+    BlobTransaction = 1007,
+    ArrayBlobTransaction = 1008,
+    Withdrawal = 1009,
+    ArrayWithdrawal = 1010,
+    ProofRequest = 1011,
+    AccountProof = 1012,
+    StorageProof = 1013,
+    ArrayStorageProof = 1014,
+    AccessListItem = 1015,
+    ArrayAccessListItem = 1016,
+    EventDecodeRequest = 1017,
+    DecodedEvent = 1018,
     // Continue to add more Ethereum type IDs here.
     // e.g.:
-    // NextEthereumType = 1004,
-    // AnotherEthereumType = 1005,
+    // NextEthereumType = 1019,
+    // AnotherEthereumType = 1020,
     // ...
     // LastEthereumType = 1499,
 
@@ -333,10 +348,11 @@ pub enum IndexForAscTypeId {
     CosmosVersionParams = 1561,
     CosmosMessageData = 1562,
     CosmosTransactionContext = 1563,
+    CosmosWasmEventData = 1564,
     // Continue to add more Cosmos type IDs here.
     // e.g.:
-    // NextCosmosType = 1564,
-    // AnotherCosmosType = 1565,
+    // NextCosmosType = 1565,
+    // AnotherCosmosType = 1566,
     // ...
     // LastCosmosType = 2499,
 
@@ -368,7 +384,37 @@ pub enum IndexForAscTypeId {
     // ...
     // LastStarknetType = 4499,
 
-    // Reserved discriminant space for a future blockchain type IDs: [4,500, 5,499]
+    // Solana types
+    SolanaBlock = 4500,
+    SolanaTransaction = 4501,
+    SolanaInstruction = 4502,
+    SolanaAccountChange = 4503,
+    SolanaArrayBytes = 4504,
+    // Continue to add more Solana type IDs here.
+    // e.g.:
+    // NextSolanaType = 4505,
+    // AnotherSolanaType = 4506,
+    // ...
+    // LastSolanaType = 5499,
+
+    // Bitcoin types
+    BitcoinBlock = 5500,
+    BitcoinTransaction = 5501,
+    BitcoinInput = 5502,
+    BitcoinOutput = 5503,
+    BitcoinOutPoint = 5504,
+    BitcoinScript = 5505,
+    BitcoinArrayInput = 5506,
+    BitcoinArrayOutput = 5507,
+    BitcoinArrayBytes = 5508,
+    // Continue to add more Bitcoin type IDs here.
+    // e.g.:
+    // NextBitcoinType = 5509,
+    // AnotherBitcoinType = 5510,
+    // ...
+    // LastBitcoinType = 6499,
+
+    // Reserved discriminant space for a future blockchain type IDs: [6,500, 7,499]
     //
     // Generated with the following shell script:
     //
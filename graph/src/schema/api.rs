@@ -9,7 +9,7 @@ use thiserror::Error;
 
 use crate::cheap_clone::CheapClone;
 use crate::data::graphql::{ObjectOrInterface, ObjectTypeExt, TypeExt};
-use crate::data::store::IdType;
+use crate::data::store::{IdType, GEO_POINT_SCALAR};
 use crate::env::ENV_VARS;
 use crate::schema::{ast, META_FIELD_NAME, META_FIELD_TYPE, SCHEMA_TYPE_NAME};
 
@@ -19,7 +19,7 @@ use crate::data::graphql::ext::{
 use crate::derive::CheapClone;
 use crate::prelude::{q, r, s, DeploymentHash};
 
-use super::{Aggregation, Field, InputSchema, Schema, TypeKind};
+use super::{Aggregation, Field, InputSchema, ObjectType, Schema, TypeKind};
 
 #[derive(Error, Debug)]
 pub enum APISchemaError {
@@ -39,6 +39,8 @@ pub enum APISchemaError {
 const BLOCK_HEIGHT: &str = "Block_height";
 const CHANGE_BLOCK_FILTER_NAME: &str = "BlockChangedFilter";
 const ERROR_POLICY_TYPE: &str = "_SubgraphErrorPolicy_";
+const GEO_DISTANCE_FILTER_NAME: &str = "GeoDistanceFilter";
+const GEO_BOX_FILTER_NAME: &str = "GeoBoxFilter";
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, CheapClone)]
 pub enum ErrorPolicy {
@@ -530,6 +532,10 @@ fn field_enum_values(
 ) -> Result<Vec<s::EnumValue>, APISchemaError> {
     let mut enum_values = vec![];
     for field in fields {
+        if field.field_type.get_base_type() == GEO_POINT_SCALAR {
+            // Points have no natural order
+            continue;
+        }
         enum_values.push(s::EnumValue {
             position: Pos::default(),
             description: None,
@@ -546,9 +552,11 @@ fn enum_value_from_child_entity_field(
     parent_field_name: &str,
     field: &s::Field,
 ) -> Option<s::EnumValue> {
-    if ast::is_list_or_non_null_list_field(field) || ast::is_entity_type(schema, &field.field_type)
+    if ast::is_list_or_non_null_list_field(field)
+        || ast::is_entity_type(schema, &field.field_type)
+        || field.field_type.get_base_type() == GEO_POINT_SCALAR
     {
-        // Sorting on lists or entities is not supported.
+        // Sorting on lists, entities or points is not supported.
         None
     } else {
         Some(s::EnumValue {
@@ -865,6 +873,7 @@ fn field_filter_ops(set: FilterOpsSet<'_>) -> &'static [&'static str] {
             "not_contains",
         ],
         Object("ID") => &["", "not", "gt", "lt", "gte", "lte", "in", "not_in"],
+        Object("GeoPoint") => &["within_distance", "within_box"],
         Object("BigInt") | Object("BigDecimal") | Object("Int") | Object("Int8")
         | Object("Timestamp") => &["", "not", "gt", "lt", "gte", "lte", "in", "not_in"],
         Object("String") => &[
@@ -894,6 +903,7 @@ fn field_filter_ops(set: FilterOpsSet<'_>) -> &'static [&'static str] {
         | Aggregation("Int")
         | Aggregation("Int8")
         | Aggregation("Timestamp") => &["", "gt", "lt", "gte", "lte", "in"],
+        Aggregation("GeoPoint") => &[],
         Object(_) => &["", "not"],
         Aggregation(_) => &[""],
     }
@@ -913,6 +923,8 @@ fn field_scalar_filter_input_values(
                 "in" | "not_in" => {
                     s::Type::ListType(Box::new(s::Type::NonNullType(Box::new(field_type))))
                 }
+                "within_distance" => s::Type::NamedType(GEO_DISTANCE_FILTER_NAME.to_string()),
+                "within_box" => s::Type::NamedType(GEO_BOX_FILTER_NAME.to_string()),
                 _ => field_type,
             };
             input_value(&field.name, filter_type, value_type)
@@ -1058,8 +1070,14 @@ fn add_query_type(api: &mut s::Document, input_schema: &InputSchema) -> Result<(
         .iter()
         .filter_map(|fulltext| query_field_for_fulltext(fulltext))
         .collect();
+    let mut key_fields = input_schema
+        .object_types()
+        .filter(|(_, object_type)| !object_type.key.is_empty())
+        .map(|(name, object_type)| query_field_for_key(name, object_type))
+        .collect();
     fields.append(&mut agg_fields);
     fields.append(&mut fulltext_fields);
+    fields.append(&mut key_fields);
     fields.push(meta_field());
 
     let typedef = s::TypeDefinition::Object(s::ObjectType {
@@ -1262,6 +1280,41 @@ fn query_fields_for_type(type_name: &str, ops: FilterOps) -> Vec<s::Field> {
     ]
 }
 
+/// The name of the `Query` field that looks up entities of type
+/// `type_name` by the fields of their key (e.g. `swapByKey`)
+pub fn key_field_name(type_name: &str) -> String {
+    let (singular, _) = camel_cased_names(type_name);
+    format!("{singular}ByKey")
+}
+
+/// Generates the `Query` field for looking up an entity of a type that
+/// declares a key with `@entity(key: [..])`. The field has one argument
+/// for each field of the key
+fn query_field_for_key(type_name: &str, object_type: &ObjectType) -> s::Field {
+    let mut arguments: Vec<_> = object_type
+        .key_fields()
+        .map(|field| s::InputValue {
+            position: Pos::default(),
+            description: None,
+            name: field.name.to_string(),
+            value_type: field.field_type.clone(),
+            default_value: None,
+            directives: vec![],
+        })
+        .collect();
+    arguments.push(block_argument());
+    arguments.push(subgraph_error_argument());
+
+    s::Field {
+        position: Pos::default(),
+        description: Some(format!("Look up a `{}` by its key", type_name)),
+        name: key_field_name(type_name),
+        arguments,
+        field_type: s::Type::NamedType(type_name.to_owned()),
+        directives: vec![],
+    }
+}
+
 fn query_fields_for_agg_type(type_name: &str) -> Vec<s::Field> {
     let mut collection_arguments = FilterOps::Aggregation.collection_arguments(type_name);
     collection_arguments.push(block_argument());
@@ -1373,6 +1426,44 @@ mod tests {
         schema
             .get_named_type("Timestamp")
             .expect("Timestamp type is missing in API schema");
+        schema
+            .get_named_type("GeoPoint")
+            .expect("GeoPoint type is missing in API schema");
+    }
+
+    #[test]
+    fn api_schema_contains_geo_filters() {
+        let schema = parse("type Place @entity { id: ID!, location: GeoPoint! }");
+
+        let filter_type = schema
+            .get_named_type("Place_filter")
+            .expect("Place_filter type is missing in API schema");
+        let fields = match filter_type {
+            TypeDefinition::InputObject(t) => &t.fields,
+            _ => panic!("Place_filter must be an input object"),
+        };
+        let location_filters: Vec<_> = fields
+            .iter()
+            .filter(|f| f.name.starts_with("location"))
+            .map(|f| (f.name.as_str(), f.value_type.to_string()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("location_within_distance", "GeoDistanceFilter".to_string()),
+                ("location_within_box", "GeoBoxFilter".to_string())
+            ],
+            location_filters
+        );
+
+        let order_by = schema
+            .get_named_type("Place_orderBy")
+            .expect("Place_orderBy type is missing in API schema");
+        match order_by {
+            TypeDefinition::Enum(t) => {
+                assert!(t.values.iter().all(|v| v.name != "location"));
+            }
+            _ => panic!("Place_orderBy must be an enum"),
+        }
     }
 
     #[test]
@@ -2216,6 +2307,36 @@ type Gravatar @entity {
         query_field(&schema, "bands");
     }
 
+    #[test]
+    fn key_lookup_field() {
+        const SCHEMA: &str = r#"
+        type Swap @entity(immutable: true, key: ["pair", "blockNumber"]) {
+            id: Bytes!
+            pair: Bytes!
+            blockNumber: Int8!
+            amount: BigInt!
+        }
+        "#;
+        let schema = parse(SCHEMA);
+
+        let swap = query_field(&schema, "swapByKey");
+        let args: Vec<_> = swap
+            .arguments
+            .iter()
+            .map(|arg| (arg.name.as_str(), arg.value_type.to_string()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("pair", "Bytes!".to_string()),
+                ("blockNumber", "Int8!".to_string()),
+                ("block", "Block_height".to_string()),
+                ("subgraphError", "_SubgraphErrorPolicy_!".to_string()),
+            ],
+            args
+        );
+        assert_eq!("Swap", swap.field_type.to_string());
+    }
+
     #[test]
     fn aggregation() {
         const SCHEMA: &str = r#"
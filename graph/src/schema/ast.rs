@@ -36,11 +36,15 @@ pub enum FilterOp {
     Child,
     And,
     Or,
+    WithinDistance,
+    WithinBox,
 }
 
 /// Split a "name_eq" style name into an attribute ("name") and a filter op (`Equal`).
 pub fn parse_field_as_filter(key: &str) -> (String, FilterOp) {
     let (suffix, op) = match key {
+        k if k.ends_with("_within_distance") => ("_within_distance", FilterOp::WithinDistance),
+        k if k.ends_with("_within_box") => ("_within_box", FilterOp::WithinBox),
         k if k.ends_with("_not") => ("_not", FilterOp::Not),
         k if k.ends_with("_gt") => ("_gt", FilterOp::GreaterThan),
         k if k.ends_with("_lt") => ("_lt", FilterOp::LessThan),
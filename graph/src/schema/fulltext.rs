@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
 
 use crate::data::graphql::{DirectiveExt, ValueExt};
@@ -98,10 +98,55 @@ impl TryFrom<&str> for FulltextAlgorithm {
     }
 }
 
+/// The weight of a field in a fulltext index. Matches in fields with a
+/// higher weight rank higher; `A` is the highest weight
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FulltextWeight {
+    A,
+    B,
+    C,
+    D,
+}
+
+impl TryFrom<&str> for FulltextWeight {
+    type Error = String;
+    fn try_from(weight: &str) -> Result<Self, Self::Error> {
+        match weight {
+            "A" => Ok(FulltextWeight::A),
+            "B" => Ok(FulltextWeight::B),
+            "C" => Ok(FulltextWeight::C),
+            "D" => Ok(FulltextWeight::D),
+            invalid => Err(format!(
+                "The provided fulltext field weight {} is invalid. It must be one of: A, B, C, D",
+                invalid,
+            )),
+        }
+    }
+}
+
+impl FulltextWeight {
+    /// Return the weight as a valid SQL string that can be used verbatim
+    /// in a query
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::A => "'A'",
+            Self::B => "'B'",
+            Self::C => "'C'",
+            Self::D => "'D'",
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct FulltextConfig {
-    pub language: FulltextLanguage,
+    /// The languages with which the included fields are parsed. With more
+    /// than one language, the index contains the lexemes for each of them
+    /// and queries match if they match in any of them
+    pub languages: Vec<FulltextLanguage>,
     pub algorithm: FulltextAlgorithm,
+    /// The weights of the included fields that declare one. Postgres
+    /// treats fields without a weight as having weight `D`
+    pub weights: BTreeMap<String, FulltextWeight>,
 }
 
 pub struct FulltextDefinition {
@@ -121,32 +166,40 @@ impl From<&s::Directive> for FulltextDefinition {
         )
         .unwrap();
 
-        let language =
-            FulltextLanguage::try_from(directive.argument("language").unwrap().as_enum().unwrap())
-                .unwrap();
+        let languages = match directive.argument("languages") {
+            Some(languages) => languages
+                .as_list()
+                .unwrap()
+                .iter()
+                .map(|language| FulltextLanguage::try_from(language.as_enum().unwrap()).unwrap())
+                .collect(),
+            None => vec![FulltextLanguage::try_from(
+                directive.argument("language").unwrap().as_enum().unwrap(),
+            )
+            .unwrap()],
+        };
 
         let included_entity_list = directive.argument("include").unwrap().as_list().unwrap();
         // Currently fulltext query fields are limited to 1 entity, so we just take the first (and only) included Entity
         let included_entity = included_entity_list.first().unwrap().as_object().unwrap();
         let included_field_values = included_entity.get("fields").unwrap().as_list().unwrap();
-        let included_fields: HashSet<String> = included_field_values
-            .iter()
-            .map(|field| {
-                field
-                    .as_object()
-                    .unwrap()
-                    .get("name")
-                    .unwrap()
-                    .as_str()
-                    .unwrap()
-                    .into()
-            })
-            .collect();
+        let mut included_fields = HashSet::new();
+        let mut weights = BTreeMap::new();
+        for field in included_field_values {
+            let field = field.as_object().unwrap();
+            let name: String = field.get("name").unwrap().as_str().unwrap().into();
+            if let Some(weight) = field.get("weight") {
+                let weight = FulltextWeight::try_from(weight.as_enum().unwrap()).unwrap();
+                weights.insert(name.clone(), weight);
+            }
+            included_fields.insert(name);
+        }
 
         FulltextDefinition {
             config: FulltextConfig {
-                language,
+                languages,
                 algorithm,
+                weights,
             },
             included_fields,
             name: name.into(),
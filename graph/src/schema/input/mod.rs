@@ -40,6 +40,10 @@ pub mod kw {
     pub const ENTITY: &str = "entity";
     pub const IMMUTABLE: &str = "immutable";
     pub const TIMESERIES: &str = "timeseries";
+    pub const KEY: &str = "key";
+    pub const RETENTION: &str = "retention";
+    pub const INDEX: &str = "index";
+    pub const TYPE: &str = "type";
     pub const TIMESTAMP: &str = "timestamp";
     pub const AGGREGATE: &str = "aggregate";
     pub const AGGREGATION: &str = "aggregation";
@@ -49,6 +53,8 @@ pub mod kw {
     pub const INTERVALS: &str = "intervals";
     pub const INTERVAL: &str = "interval";
     pub const CUMULATIVE: &str = "cumulative";
+    pub const WEIGHT: &str = "weight";
+    pub const PERCENTILE: &str = "percentile";
 }
 
 /// The internal representation of a subgraph schema, i.e., the
@@ -219,6 +225,11 @@ pub struct Field {
     pub field_type: s::Type,
     pub value_type: ValueType,
     derived_from: Option<Word>,
+    /// The `key` of `@derivedFrom`, the field of the containing type that
+    /// `derived_from` joins with; without one, the join is with `id`
+    derived_key: Option<Word>,
+    /// The index declared for this field with `@index(type: ..)`
+    pub index: Option<IndexType>,
 }
 
 impl Field {
@@ -234,6 +245,8 @@ impl Field {
             field_type: field_type.clone(),
             value_type,
             derived_from,
+            derived_key: None,
+            index: None,
         }
     }
 
@@ -296,6 +309,15 @@ impl Field {
     pub fn is_derived(&self) -> bool {
         self.derived_from.is_some()
     }
+
+    /// For a derived field declared with `@derivedFrom(field: .., key: ..)`,
+    /// the name of the field in the type containing this field whose value
+    /// children store in their `derived_from` field. Derived fields without
+    /// a `key` join children with the `id` of their parent and return
+    /// `None`
+    pub fn derived_key(&self) -> Option<&str> {
+        self.derived_key.as_ref().map(|key| key.as_str())
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -413,6 +435,15 @@ pub struct ObjectType {
     /// is part of an aggregation
     aggregation: Option<Atom>,
     pub timeseries: bool,
+    /// The fields that together form a unique secondary key for entities
+    /// of this type, declared with `@entity(key: [..])`. The `id` remains
+    /// the identity of the entity. Empty if there is no such key
+    pub key: Box<[Word]>,
+    /// How long entity versions are kept after they stop being current,
+    /// or for immutable types how long entities are kept after they were
+    /// created, declared with `@entity(retention: "30d")`. `None` means
+    /// they are kept until the deployment is pruned
+    pub retention: Option<Duration>,
     interfaces: Box<[Word]>,
     shared_interfaces: Box<[Atom]>,
 }
@@ -430,7 +461,12 @@ impl ObjectType {
             .iter()
             .map(|field| {
                 let derived_from = field.derived_from().map(|name| Word::from(name));
-                Field::new(schema, &field.name, &field.field_type, derived_from)
+                let mut fld = Field::new(schema, &field.name, &field.field_type, derived_from);
+                fld.derived_key = field.derived_key().map(|name| Word::from(name));
+                fld.index = field.find_directive(kw::INDEX).map(|dir| {
+                    IndexType::from_directive(dir).expect("validations ensure we don't get here")
+                });
+                fld
             })
             .collect();
         let interfaces = object_type
@@ -452,6 +488,24 @@ impl ObjectType {
             None => timeseries,
             _ => unreachable!("validations ensure we don't get here"),
         };
+        let key: Box<[Word]> = match dir.argument(kw::KEY) {
+            Some(Value::List(fields)) => fields
+                .iter()
+                .map(|field| match field {
+                    Value::String(field) => Word::from(field.as_str()),
+                    _ => unreachable!("validations ensure we don't get here"),
+                })
+                .collect(),
+            None => Box::new([]),
+            _ => unreachable!("validations ensure we don't get here"),
+        };
+        let retention = match dir.argument(kw::RETENTION) {
+            Some(Value::String(retention)) => {
+                Some(parse_retention(retention).expect("validations ensure we don't get here"))
+            }
+            None => None,
+            _ => unreachable!("validations ensure we don't get here"),
+        };
         Self {
             name,
             fields,
@@ -459,6 +513,8 @@ impl ObjectType {
             immutable,
             aggregation: None,
             timeseries,
+            key,
+            retention,
             interfaces,
             shared_interfaces,
         }
@@ -471,12 +527,16 @@ impl ObjectType {
                 field_type: s::Type::NamedType("ID".to_string()),
                 value_type: ValueType::String,
                 derived_from: None,
+                derived_key: None,
+                index: None,
             },
             Field {
                 name: Word::from(POI_DIGEST),
                 field_type: s::Type::NamedType("String".to_string()),
                 value_type: ValueType::String,
                 derived_from: None,
+                derived_key: None,
+                index: None,
             },
         ]
         .into_boxed_slice();
@@ -490,6 +550,8 @@ impl ObjectType {
             immutable: false,
             aggregation: None,
             timeseries: false,
+            key: Box::new([]),
+            retention: None,
             fields,
             shared_interfaces: Box::new([]),
         }
@@ -503,6 +565,15 @@ impl ObjectType {
     pub fn is_aggregation(&self) -> bool {
         self.aggregation.is_some()
     }
+
+    /// The fields of the key declared with `@entity(key: [..])`, in the
+    /// order in which they were declared
+    pub fn key_fields(&self) -> impl Iterator<Item = &Field> {
+        self.key.iter().map(|name| {
+            self.field(name)
+                .expect("validations ensure key fields exist")
+        })
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -534,7 +605,9 @@ impl InterfaceType {
                 // derived fields on interfaces that it would for
                 // non-derived fields
                 let derived_from = field.derived_from().map(|name| Word::from(name));
-                Field::new(schema, &field.name, &field.field_type, derived_from)
+                let mut fld = Field::new(schema, &field.name, &field.field_type, derived_from);
+                fld.derived_key = field.derived_key().map(|name| Word::from(name));
+                fld
             })
             .collect();
         let name = pool
@@ -602,7 +675,7 @@ impl EnumMap {
     }
 }
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum AggregateFn {
     Sum,
     Max,
@@ -610,6 +683,10 @@ pub enum AggregateFn {
     Count,
     First,
     Last,
+    Avg,
+    Stddev,
+    Percentile,
+    WeightedAvg,
 }
 
 impl FromStr for AggregateFn {
@@ -623,6 +700,10 @@ impl FromStr for AggregateFn {
             "count" => Ok(AggregateFn::Count),
             "first" => Ok(AggregateFn::First),
             "last" => Ok(AggregateFn::Last),
+            "avg" => Ok(AggregateFn::Avg),
+            "stddev" => Ok(AggregateFn::Stddev),
+            "percentile" => Ok(AggregateFn::Percentile),
+            "weightedAvg" => Ok(AggregateFn::WeightedAvg),
             _ => Err(anyhow!("invalid aggregate function `{}`", s)),
         }
     }
@@ -632,11 +713,33 @@ impl AggregateFn {
     pub fn has_arg(&self) -> bool {
         use AggregateFn::*;
         match self {
-            Sum | Max | Min | First | Last => true,
+            Sum | Max | Min | First | Last | Avg | Stddev | Percentile | WeightedAvg => true,
             Count => false,
         }
     }
 
+    /// Whether the function can be used for cumulative aggregates. That
+    /// requires that the aggregate for a longer period can be computed
+    /// from the aggregate of an earlier period and the values in the
+    /// current bucket
+    pub fn can_be_cumulative(&self) -> bool {
+        use AggregateFn::*;
+        match self {
+            Sum | Max | Min | Count | First | Last => true,
+            Avg | Stddev | Percentile | WeightedAvg => false,
+        }
+    }
+
+    /// Whether the function produces fractional values even for integer
+    /// arguments, so that the aggregate must be a `BigDecimal`
+    pub fn is_fractional(&self) -> bool {
+        use AggregateFn::*;
+        match self {
+            Avg | Stddev | WeightedAvg => true,
+            Sum | Max | Min | Count | First | Last | Percentile => false,
+        }
+    }
+
     fn as_str(&self) -> &'static str {
         use AggregateFn::*;
         match self {
@@ -646,10 +749,113 @@ impl AggregateFn {
             Count => "count",
             First => "first",
             Last => "last",
+            Avg => "avg",
+            Stddev => "stddev",
+            Percentile => "percentile",
+            WeightedAvg => "weightedAvg",
         }
     }
 }
 
+/// The kinds of index that can be declared for a field with
+/// `@index(type: ..)`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IndexType {
+    BTree,
+    Gin,
+    Brin,
+}
+
+impl IndexType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IndexType::BTree => "btree",
+            IndexType::Gin => "gin",
+            IndexType::Brin => "brin",
+        }
+    }
+
+    /// Get the index type from an `@index` directive. The `type` argument
+    /// can be given as an enum value or a string and defaults to `btree`
+    fn from_directive(dir: &s::Directive) -> Result<Self, Error> {
+        match dir.argument(kw::TYPE) {
+            None => Ok(IndexType::BTree),
+            Some(Value::Enum(name)) | Some(Value::String(name)) => name.parse(),
+            Some(value) => Err(anyhow!("invalid index type `{}`", value)),
+        }
+    }
+}
+
+impl std::fmt::Display for IndexType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for IndexType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "btree" => Ok(IndexType::BTree),
+            "gin" => Ok(IndexType::Gin),
+            "brin" => Ok(IndexType::Brin),
+            _ => Err(anyhow!("invalid index type `{}`", s)),
+        }
+    }
+}
+
+/// Parse the value of the `retention` argument of `@entity`, a positive
+/// number followed by one of the units `s`, `m`, `h`, `d`, or `w` like
+/// `30d`
+fn parse_retention(value: &str) -> Result<Duration, Error> {
+    let value = value.trim();
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| anyhow!("retention `{}` has no unit", value))?;
+    let (amount, unit) = value.split_at(unit_start);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| anyhow!("retention `{}` does not start with a number", value))?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 24 * 3600,
+        "w" => 7 * 24 * 3600,
+        _ => return Err(anyhow!("retention `{}` has an unknown unit", value)),
+    };
+    if amount == 0 {
+        return Err(anyhow!("retention `{}` must be positive", value));
+    }
+    amount
+        .checked_mul(secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| anyhow!("retention `{}` is too large", value))
+}
+
+#[test]
+fn retention() {
+    assert_eq!(Duration::from_secs(90), parse_retention("90s").unwrap());
+    assert_eq!(
+        Duration::from_secs(30 * 60),
+        parse_retention("30m").unwrap()
+    );
+    assert_eq!(
+        Duration::from_secs(30 * 86400),
+        parse_retention("30d").unwrap()
+    );
+    assert_eq!(
+        Duration::from_secs(2 * 604800),
+        parse_retention("2w").unwrap()
+    );
+    assert!(parse_retention("0d").is_err());
+    assert!(parse_retention("30").is_err());
+    assert!(parse_retention("d").is_err());
+    assert!(parse_retention("30y").is_err());
+    assert!(parse_retention("-3d").is_err());
+}
+
 /// The supported intervals for timeseries in order of decreasing
 /// granularity. The intervals must all be divisible by the smallest
 /// interval
@@ -783,6 +989,10 @@ pub struct Aggregate {
     pub value_type: ValueType,
     /// Whether the aggregation is cumulative
     pub cumulative: bool,
+    /// The expression for the weights of a `weightedAvg`
+    pub weight: Option<Word>,
+    /// The percentile, between 0 and 1, that a `percentile` computes
+    pub percentile: Option<f64>,
 }
 
 impl Aggregate {
@@ -809,12 +1019,21 @@ impl Aggregate {
                 _ => unreachable!("validation ensures this is a boolean"),
             })
             .unwrap_or(false);
+        let weight = dir
+            .argument(kw::WEIGHT)
+            .map(|arg| Word::from(arg.as_str().unwrap()));
+        let percentile = dir.argument(kw::PERCENTILE).map(|arg| match arg {
+            Value::Float(p) => *p,
+            _ => unreachable!("validation ensures this is a float"),
+        });
 
         Aggregate {
             name: Word::from(name),
             func,
             arg,
             cumulative,
+            weight,
+            percentile,
             field_type: field_type.clone(),
             value_type: field_type.get_base_type().parse().unwrap(),
         }
@@ -828,6 +1047,8 @@ impl Aggregate {
             field_type: self.field_type.clone(),
             value_type: self.value_type,
             derived_from: None,
+            derived_key: None,
+            index: None,
         }
     }
 }
@@ -888,6 +1109,8 @@ impl Aggregation {
                     immutable: true,
                     aggregation: Some(name),
                     timeseries: false,
+                    key: Box::new([]),
+                    retention: None,
                     interfaces: Box::new([]),
                     shared_interfaces: Box::new([]),
                 }
@@ -1211,6 +1434,18 @@ impl InputSchema {
         })
     }
 
+    /// Return `true` if `object_type` is the name of an object type that
+    /// implements the interface with name `interface`
+    pub fn implements(&self, object_type: &str, interface: &str) -> bool {
+        let (Some(obj_type), Some(intf)) = (
+            self.inner.pool.lookup(object_type),
+            self.inner.pool.lookup(interface),
+        ) else {
+            return false;
+        };
+        self.is_object_type(obj_type) && self.interfaces(obj_type).any(|i| i.name == intf)
+    }
+
     fn implementers<'a>(
         &'a self,
         interface: &'a InterfaceType,
@@ -1379,6 +1614,21 @@ impl InputSchema {
             .any(|ti| matches!(ti, TypeInfo::Aggregation(_)))
     }
 
+    /// Return `true` if any entity type declares a `retention`
+    pub fn has_retention(&self) -> bool {
+        self.inner.type_infos.iter().any(|ti| match ti {
+            TypeInfo::Object(obj_type) => obj_type.retention.is_some(),
+            TypeInfo::Interface(_) | TypeInfo::Aggregation(_) => false,
+        })
+    }
+
+    /// Return `true` if the time of each block needs to be stored in the
+    /// PoI table, either for rolling up aggregations or for pruning
+    /// entities with a `retention`
+    pub fn needs_block_time(&self) -> bool {
+        self.has_aggregations() || self.has_retention()
+    }
+
     pub fn entity_fulltext_definitions(
         &self,
         entity: &str,
@@ -1675,14 +1925,14 @@ mod validations {
                 ext::{DirectiveFinder, FieldExt},
                 DirectiveExt, DocumentExt, ObjectTypeExt, TypeExt, ValueExt,
             },
-            store::{IdType, ValueType, ID},
+            store::{IdType, ValueType, GEO_POINT_SCALAR, ID},
             subgraph::SPEC_VERSION_1_1_0,
         },
         prelude::s,
         schema::{
             input::{kw, sqlexpr, AggregateFn, AggregationInterval},
-            FulltextAlgorithm, FulltextLanguage, Schema as BaseSchema, SchemaValidationError,
-            SchemaValidationError as Err, Strings, SCHEMA_TYPE_NAME,
+            FulltextAlgorithm, FulltextLanguage, FulltextWeight, Schema as BaseSchema,
+            SchemaValidationError, SchemaValidationError as Err, Strings, SCHEMA_TYPE_NAME,
         },
     };
 
@@ -1719,6 +1969,9 @@ mod validations {
 
         errors.append(&mut schema.validate_entity_directives());
         errors.append(&mut schema.validate_entity_type_ids());
+        errors.append(&mut schema.validate_entity_keys());
+        errors.append(&mut schema.validate_entity_retention());
+        errors.append(&mut schema.validate_index_directives());
         errors.append(&mut schema.validate_fields());
         errors.append(&mut schema.validate_fulltext_directives());
         errors.append(&mut schema.validate_aggregations());
@@ -1847,16 +2100,39 @@ mod validations {
             &self,
             fulltext: &s::Directive,
         ) -> Vec<SchemaValidationError> {
-            let language = match fulltext.argument("language") {
-                Some(s::Value::Enum(language)) => language,
+            let languages = match (
+                fulltext.argument("language"),
+                fulltext.argument("languages"),
+            ) {
+                (Some(s::Value::Enum(language)), None) => vec![language],
+                (None, Some(s::Value::List(languages))) if !languages.is_empty() => {
+                    let mut names = Vec::new();
+                    for language in languages {
+                        match language {
+                            s::Value::Enum(language) => names.push(language),
+                            _ => return vec![SchemaValidationError::FulltextLanguageUndefined],
+                        }
+                    }
+                    names
+                }
+                (Some(_), Some(_)) => return vec![SchemaValidationError::FulltextLanguageConflict],
                 _ => return vec![SchemaValidationError::FulltextLanguageUndefined],
             };
-            match FulltextLanguage::try_from(language.as_str()) {
-                Ok(_) => vec![],
-                Err(_) => vec![SchemaValidationError::FulltextLanguageInvalid(
-                    language.to_string(),
-                )],
+
+            let mut errors = vec![];
+            let mut seen = HashSet::new();
+            for language in languages {
+                if FulltextLanguage::try_from(language.as_str()).is_err() {
+                    errors.push(SchemaValidationError::FulltextLanguageInvalid(
+                        language.to_string(),
+                    ));
+                } else if !seen.insert(language) {
+                    errors.push(SchemaValidationError::FulltextLanguageDuplicate(
+                        language.to_string(),
+                    ));
+                }
             }
+            errors
         }
 
         fn validate_fulltext_directive_algorithm(
@@ -1911,9 +2187,9 @@ mod validations {
                         };
 
                         for field_value in fields {
-                            let field_name = match field_value {
+                            let (field_name, weight) = match field_value {
                             s::Value::Object(field_map) => match field_map.get("name") {
-                                Some(s::Value::String(name)) => name,
+                                Some(s::Value::String(name)) => (name, field_map.get("weight")),
                                 _ => return vec![SchemaValidationError::FulltextIncludedFieldMissingRequiredProperty],
                             },
                             _ => return vec![SchemaValidationError::FulltextIncludeEntityMissingOrIncorrectAttributes],
@@ -1932,6 +2208,21 @@ mod validations {
                                 field_name.clone(),
                             )];
                         };
+
+                            // Validate the optional weight of the field
+                            match weight {
+                                None => {}
+                                Some(s::Value::Enum(weight))
+                                    if FulltextWeight::try_from(weight.as_str()).is_ok() => {}
+                                Some(weight) => {
+                                    return vec![
+                                        SchemaValidationError::FulltextIncludedFieldWeightInvalid(
+                                            field_name.clone(),
+                                            weight.to_string(),
+                                        ),
+                                    ]
+                                }
+                            }
                         }
                     }
                 }
@@ -2039,6 +2330,153 @@ mod validations {
                 .collect()
         }
 
+        /// The `key` argument of `@entity` must be a non-empty list of
+        /// distinct fields of the type other than `id`, since it declares
+        /// a secondary key next to the `id`. Key fields must be
+        /// non-nullable scalars or enums so that every entity has a value
+        /// for each of them that can be compared with `=`
+        fn validate_entity_keys(&self) -> Vec<SchemaValidationError> {
+            let mut errors = Vec::new();
+            for object_type in &self.entity_types {
+                let dir = object_type.find_directive(kw::ENTITY).unwrap();
+                let Some(arg) = dir.argument(kw::KEY) else {
+                    continue;
+                };
+                let invalid = || SchemaValidationError::EntityKeyInvalid(object_type.name.clone());
+                let names = match arg {
+                    s::Value::List(names) if !names.is_empty() => names,
+                    _ => {
+                        errors.push(invalid());
+                        continue;
+                    }
+                };
+                let mut seen = HashSet::new();
+                for name in names {
+                    let s::Value::String(name) = name else {
+                        errors.push(invalid());
+                        continue;
+                    };
+                    if name == ID.as_str() || !seen.insert(name) {
+                        errors.push(invalid());
+                        continue;
+                    }
+                    let Some(field) = object_type.field(name) else {
+                        errors.push(SchemaValidationError::EntityKeyUnknownField(
+                            object_type.name.clone(),
+                            name.clone(),
+                        ));
+                        continue;
+                    };
+                    let scalar = match &field.field_type {
+                        s::Type::NonNullType(inner) => match inner.as_ref() {
+                            s::Type::NamedType(name) => {
+                                ValueType::is_scalar(name)
+                                    || self
+                                        .schema
+                                        .document
+                                        .get_enum_definitions()
+                                        .iter()
+                                        .any(|enum_type| &enum_type.name == name)
+                            }
+                            _ => false,
+                        },
+                        _ => false,
+                    };
+                    if !scalar || field.is_derived() {
+                        errors.push(SchemaValidationError::EntityKeyInvalidField(
+                            object_type.name.clone(),
+                            name.clone(),
+                            field.field_type.to_string(),
+                        ));
+                    }
+                }
+            }
+            errors
+        }
+
+        /// The `retention` argument of `@entity` must be a string that is a
+        /// valid duration like `30d`
+        fn validate_entity_retention(&self) -> Vec<SchemaValidationError> {
+            self.entity_types
+                .iter()
+                .filter_map(|object_type| {
+                    let dir = object_type.find_directive(kw::ENTITY).unwrap();
+                    let arg = dir.argument(kw::RETENTION)?;
+                    let valid = match arg {
+                        s::Value::String(retention) => super::parse_retention(retention).is_ok(),
+                        _ => false,
+                    };
+                    (!valid).then(|| {
+                        SchemaValidationError::EntityRetentionInvalid(
+                            object_type.name.clone(),
+                            arg.to_string(),
+                        )
+                    })
+                })
+                .collect()
+        }
+
+        /// The `@index` directive on a field must have a `type` that is one
+        /// of `btree`, `gin`, or `brin`, and the field must be stored in
+        /// the entity's table, i.e., not be derived. `gin` indexes can only
+        /// be used for list fields, and `btree` and `brin` indexes only for
+        /// fields that are not lists; `brin` indexes also can't be used for
+        /// `Boolean` or enum fields. `GeoPoint` fields are always indexed
+        /// and can not declare an index
+        fn validate_index_directives(&self) -> Vec<SchemaValidationError> {
+            let mut errors = Vec::new();
+            for object_type in &self.entity_types {
+                for field in &object_type.fields {
+                    let Some(dir) = field.find_directive(kw::INDEX) else {
+                        continue;
+                    };
+                    let invalid = |reason: &str| {
+                        SchemaValidationError::IndexDirectiveInvalid(
+                            object_type.name.clone(),
+                            field.name.clone(),
+                            reason.to_string(),
+                        )
+                    };
+                    let index_type = match super::IndexType::from_directive(dir) {
+                        Ok(index_type) => index_type,
+                        Err(e) => {
+                            errors.push(invalid(&e.to_string()));
+                            continue;
+                        }
+                    };
+                    let is_list = field.field_type.is_list();
+                    if field.is_derived() {
+                        errors.push(invalid("derived fields can not be indexed"));
+                    } else if field.field_type.get_base_type() == GEO_POINT_SCALAR {
+                        errors.push(invalid("GeoPoint fields are always indexed"));
+                    } else if index_type == super::IndexType::Gin && !is_list {
+                        errors.push(invalid("gin indexes can only be used for list fields"));
+                    } else if index_type != super::IndexType::Gin && is_list {
+                        errors.push(invalid(&format!(
+                            "{} indexes can not be used for list fields",
+                            index_type
+                        )));
+                    } else if index_type == super::IndexType::Brin {
+                        // Postgres has no `brin` operator classes for
+                        // booleans and enums
+                        let base_type = field.field_type.get_base_type();
+                        let is_enum = self
+                            .schema
+                            .document
+                            .get_enum_definitions()
+                            .iter()
+                            .any(|enum_type| enum_type.name == base_type);
+                        if base_type == "Boolean" || is_enum {
+                            errors.push(invalid(
+                                "brin indexes can not be used for Boolean or enum fields",
+                            ));
+                        }
+                    }
+                }
+            }
+            errors
+        }
+
         /// 1. All object types besides `_Schema_` must have an id field
         /// 2. The id field must be recognized by IdType
         fn validate_entity_type_ids(&self) -> Vec<SchemaValidationError> {
@@ -2173,7 +2611,7 @@ mod validations {
             // Iterate over all derived fields in all entity types; include the
             // interface types that the entity with the `@derivedFrom` implements
             // and the `field` argument of @derivedFrom directive
-            for (object_type, interface_types, field, target_field) in self
+            for (object_type, interface_types, field, target_field, key) in self
                 .entity_types
                 .iter()
                 .flat_map(|object_type| {
@@ -2206,6 +2644,7 @@ mod validations {
                                 .collect::<Vec<_>>(),
                             field,
                             directive.argument("field"),
+                            directive.argument("key"),
                         )
                     })
                 })
@@ -2254,6 +2693,66 @@ mod validations {
                         invalid(object_type, &field.name, &msg)
                     })?;
 
+                // With a `key`, the field we are deriving from has to hold
+                // the same kind of value as the field `key` of our type
+                if let Some(key) = key {
+                    let key = match key {
+                        s::Value::String(s) => s,
+                        _ => {
+                            return Err(invalid(
+                                object_type,
+                                &field.name,
+                                "the @derivedFrom `key` argument must be a string",
+                            ))
+                        }
+                    };
+                    let key_field = object_type
+                        .fields
+                        .iter()
+                        .find(|field| field.name.eq(key))
+                        .ok_or_else(|| {
+                            let msg = format!(
+                                "the key field `{}` does not exist on type `{}`",
+                                key, object_type.name
+                            );
+                            invalid(object_type, &field.name, &msg)
+                        })?;
+                    // The key types are the ones whose values we can pass
+                    // around as ids when querying
+                    let key_type = match key_field.field_type.get_base_type() {
+                        "ID" => "String",
+                        name => name,
+                    };
+                    if key_field.is_derived()
+                        || key_field.field_type.is_list()
+                        || !matches!(key_type, "String" | "Bytes" | "Int8")
+                    {
+                        let msg = format!(
+                            "the key field `{}` must be a field of type String, ID, Bytes, or Int8 \
+                             that is neither a list nor derived",
+                            key
+                        );
+                        return Err(invalid(object_type, &field.name, &msg));
+                    }
+                    let target_field_type = match target_field.field_type.get_base_type() {
+                        "ID" => "String",
+                        name => name,
+                    };
+                    if target_field.is_derived()
+                        || target_field.field_type.is_list()
+                        || target_field_type != key_type
+                    {
+                        let msg = format!(
+                            "field `{tf}` on type `{tt}` must have the same type as the key field `{key}` \
+                             and be neither a list nor derived",
+                            tf = target_field.name,
+                            tt = target_type_name,
+                        );
+                        return Err(invalid(object_type, &field.name, &msg));
+                    }
+                    continue;
+                }
+
                 // The field we are deriving from has to point back to us; as an
                 // exception, we allow deriving from the `id` of another type.
                 // For that, we will wind up comparing the `id`s of the two types
@@ -2489,7 +2988,17 @@ mod validations {
                                 }
                             };
                             match agg.argument(kw::CUMULATIVE) {
-                                Some(s::Value::Boolean(_)) | None => { /* ok */ }
+                                Some(s::Value::Boolean(false)) | None => { /* ok */ }
+                                Some(s::Value::Boolean(true)) => {
+                                    if !func.can_be_cumulative() {
+                                        errors.push(Err::AggregationCumulativeNotSupported(
+                                            agg_type.name.to_owned(),
+                                            field.name.to_owned(),
+                                            func.as_str().to_owned(),
+                                        ));
+                                        continue;
+                                    }
+                                }
                                 Some(_) => {
                                     errors.push(Err::AggregationInvalidCumulative(
                                         agg_type.name.to_owned(),
@@ -2498,6 +3007,50 @@ mod validations {
                                     continue;
                                 }
                             };
+                            let weight = match (func, agg.argument(kw::WEIGHT)) {
+                                (AggregateFn::WeightedAvg, Some(s::Value::String(weight))) => {
+                                    Some(weight)
+                                }
+                                (AggregateFn::WeightedAvg, _) => {
+                                    errors.push(Err::AggregationMissingWeight(
+                                        agg_type.name.to_owned(),
+                                        field.name.to_owned(),
+                                    ));
+                                    continue;
+                                }
+                                (_, Some(_)) => {
+                                    errors.push(Err::AggregationUnexpectedArg(
+                                        agg_type.name.to_owned(),
+                                        field.name.to_owned(),
+                                        kw::WEIGHT.to_owned(),
+                                        func.as_str().to_owned(),
+                                    ));
+                                    continue;
+                                }
+                                (_, None) => None,
+                            };
+                            match (func, agg.argument(kw::PERCENTILE)) {
+                                (AggregateFn::Percentile, Some(s::Value::Float(p)))
+                                    if (0.0..=1.0).contains(p) =>
+                                { /* ok */ }
+                                (AggregateFn::Percentile, _) => {
+                                    errors.push(Err::AggregationInvalidPercentile(
+                                        agg_type.name.to_owned(),
+                                        field.name.to_owned(),
+                                    ));
+                                    continue;
+                                }
+                                (_, Some(_)) => {
+                                    errors.push(Err::AggregationUnexpectedArg(
+                                        agg_type.name.to_owned(),
+                                        field.name.to_owned(),
+                                        kw::PERCENTILE.to_owned(),
+                                        func.as_str().to_owned(),
+                                    ));
+                                    continue;
+                                }
+                                (_, None) => { /* ok */ }
+                            }
                             let field_type = match field.field_type.value_type() {
                                 Ok(field_type) => field_type,
                                 Err(_) => {
@@ -2508,46 +3061,61 @@ mod validations {
                                     continue;
                                 }
                             };
+                            if func.is_fractional() && field_type != ValueType::BigDecimal {
+                                errors.push(Err::AggregationNonDecimalResult(
+                                    agg_type.name.to_owned(),
+                                    field.name.to_owned(),
+                                    func.as_str().to_owned(),
+                                ));
+                                continue;
+                            }
                             // It would be nicer to use a proper struct here
                             // and have that implement
                             // `sqlexpr::ExprVisitor` but we need access to
                             // a bunch of local variables that would make
                             // setting up that struct a bit awkward, so we
                             // use a closure instead
-                            let check_ident = |ident: &str| -> Result<(), SchemaValidationError> {
-                                let arg_type = match source.field(ident) {
-                                    Some(arg_field) => match arg_field.field_type.value_type() {
-                                        Ok(arg_type) if arg_type.is_numeric() => arg_type,
-                                        Ok(_) | Err(_) => {
-                                            return Err(Err::AggregationNonNumericArg(
+                            let check_ident =
+                                |arg: &str, ident: &str| -> Result<(), SchemaValidationError> {
+                                    let arg_type = match source.field(ident) {
+                                        Some(arg_field) => {
+                                            match arg_field.field_type.value_type() {
+                                                Ok(arg_type) if arg_type.is_numeric() => arg_type,
+                                                Ok(_) | Err(_) => {
+                                                    return Err(Err::AggregationNonNumericArg(
+                                                        agg_type.name.to_owned(),
+                                                        field.name.to_owned(),
+                                                        source.name.to_owned(),
+                                                        arg.to_owned(),
+                                                    ));
+                                                }
+                                            }
+                                        }
+                                        None => {
+                                            return Err(Err::AggregationUnknownArg(
                                                 agg_type.name.to_owned(),
                                                 field.name.to_owned(),
-                                                source.name.to_owned(),
                                                 arg.to_owned(),
                                             ));
                                         }
-                                    },
-                                    None => {
-                                        return Err(Err::AggregationUnknownArg(
+                                    };
+                                    if arg_type > field_type {
+                                        return Err(Err::AggregationNonMatchingArg(
                                             agg_type.name.to_owned(),
                                             field.name.to_owned(),
                                             arg.to_owned(),
+                                            arg_type.to_str().to_owned(),
+                                            field_type.to_str().to_owned(),
                                         ));
                                     }
+                                    Ok(())
                                 };
-                                if arg_type > field_type {
-                                    return Err(Err::AggregationNonMatchingArg(
-                                        agg_type.name.to_owned(),
-                                        field.name.to_owned(),
-                                        arg.to_owned(),
-                                        arg_type.to_str().to_owned(),
-                                        field_type.to_str().to_owned(),
-                                    ));
+                            for expr in std::iter::once(arg).chain(weight) {
+                                if let Err(mut errs) =
+                                    sqlexpr::parse(expr, |ident| check_ident(expr, ident))
+                                {
+                                    errors.append(&mut errs);
                                 }
-                                Ok(())
-                            };
-                            if let Err(mut errs) = sqlexpr::parse(arg, check_ident) {
-                                errors.append(&mut errs);
                             }
                         }
                         None => {
@@ -2564,7 +3132,16 @@ mod validations {
                                     continue;
                                 }
                             };
-                            if field.field_type.get_base_type()
+                            if field.field_type.get_base_type() == GEO_POINT_SCALAR {
+                                // Points can not be compared for equality
+                                // in the database and can therefore not be
+                                // grouped by
+                                errors.push(Err::AggregationInvalidDimensionType(
+                                    agg_type.name.to_owned(),
+                                    field.name.to_owned(),
+                                    GEO_POINT_SCALAR.to_owned(),
+                                ));
+                            } else if field.field_type.get_base_type()
                                 != src_field.field_type.get_base_type()
                             {
                                 errors.push(Err::AggregationNonMatchingType(
@@ -2757,6 +3334,8 @@ type E @entity { id: ID! }
 type F @entity { id: ID! }
 type G @entity { id: ID! a: BigInt }
 type H @entity { id: ID! a: A! }
+type K @entity { id: ID! pool: Bytes! }
+type L @entity { id: ID! pool: String! }
 # This sets up a situation where we need to allow `Transaction.from` to
 # point to an interface because of `Account.txn`
 type Transaction @entity { from: Address! }
@@ -2816,6 +3395,24 @@ type Account implements Address @entity { id: ID!, txn: Transaction! @derivedFro
                 "type must be an existing entity or interface",
             );
             validate("j: B @derivedFrom(field: \"id\")", "ok");
+            validate(
+                "address: Bytes!\n k: [K!]! @derivedFrom(field: \"pool\", key: \"address\")",
+                "ok",
+            );
+            validate(
+                "k: [K!]! @derivedFrom(field: \"pool\", key: \"address\")",
+                "the key field `address` does not exist on type `A`",
+            );
+            validate(
+                "address: [Bytes!]!\n k: [K!]! @derivedFrom(field: \"pool\", key: \"address\")",
+                "the key field `address` must be a field of type String, ID, Bytes, or Int8 \
+                 that is neither a list nor derived",
+            );
+            validate(
+                "address: Bytes!\n l: [L!]! @derivedFrom(field: \"pool\", key: \"address\")",
+                "field `pool` on type `L` must have the same type as the key field `address` \
+                 and be neither a list nor derived",
+            );
         }
 
         #[test]
@@ -2980,6 +3577,66 @@ type Gravatar @entity {
             assert_eq!(schema.validate_fulltext_directives(), vec![]);
         }
 
+        #[test]
+        fn test_fulltext_directive_languages_and_weights() {
+            fn validate(languages: &str, weight: &str) -> Vec<SchemaValidationError> {
+                let schema = format!(
+                    r#"
+type _Schema_ @fulltext(
+  name: "metadata"
+  {languages}
+  algorithm: rank
+  include: [
+    {{
+      entity: "Gravatar",
+      fields: [
+        {{ name: "displayName"{weight} }},
+        {{ name: "imageUrl"}},
+      ]
+    }}
+  ]
+)
+type Gravatar @entity {{
+  id: ID!
+  owner: Bytes!
+  displayName: String!
+  imageUrl: String!
+}}"#
+                );
+                let document =
+                    graphql_parser::parse_schema(&schema).expect("Failed to parse schema");
+                let schema =
+                    BaseSchema::new(DeploymentHash::new("id1").unwrap(), document).unwrap();
+                let schema = Schema::new(LATEST_VERSION, &schema);
+                schema.validate_fulltext_directives()
+            }
+
+            assert_eq!(validate("languages: [en, fr]", ", weight: A"), vec![]);
+            assert_eq!(
+                validate("language: en languages: [fr]", ""),
+                vec![Err::FulltextLanguageConflict]
+            );
+            assert_eq!(
+                validate("languages: []", ""),
+                vec![Err::FulltextLanguageUndefined]
+            );
+            assert_eq!(
+                validate("languages: [en, en]", ""),
+                vec![Err::FulltextLanguageDuplicate("en".to_string())]
+            );
+            assert_eq!(
+                validate("languages: [en, xx]", ""),
+                vec![Err::FulltextLanguageInvalid("xx".to_string())]
+            );
+            assert_eq!(
+                validate("language: en", ", weight: E"),
+                vec![Err::FulltextIncludedFieldWeightInvalid(
+                    "displayName".to_string(),
+                    "E".to_string()
+                )]
+            );
+        }
+
         #[test]
         fn agg() {
             fn parse_annotation(file_name: &str, line: &str) -> (bool, Version, String) {
@@ -23,17 +23,19 @@ mod entity_type;
 mod fulltext;
 mod input;
 
-pub use api::{is_introspection_field, APISchemaError, INTROSPECTION_QUERY_TYPE};
+pub use api::{is_introspection_field, key_field_name, APISchemaError, INTROSPECTION_QUERY_TYPE};
 
 pub use api::{ApiSchema, ErrorPolicy};
 pub use entity_key::EntityKey;
 pub use entity_type::{AsEntityTypeName, EntityType};
-pub use fulltext::{FulltextAlgorithm, FulltextConfig, FulltextDefinition, FulltextLanguage};
+pub use fulltext::{
+    FulltextAlgorithm, FulltextConfig, FulltextDefinition, FulltextLanguage, FulltextWeight,
+};
 pub use input::sqlexpr::{ExprVisitor, VisitExpr};
 pub(crate) use input::POI_OBJECT;
 pub use input::{
     kw, Aggregate, AggregateFn, Aggregation, AggregationInterval, AggregationMapping, Field,
-    InputSchema, InterfaceType, ObjectOrInterface, ObjectType, TypeKind,
+    IndexType, InputSchema, InterfaceType, ObjectOrInterface, ObjectType, TypeKind,
 };
 
 pub const SCHEMA_TYPE_NAME: &str = "_Schema_";
@@ -95,6 +97,10 @@ pub enum SchemaValidationError {
     FulltextLanguageUndefined,
     #[error("Fulltext language is invalid: {0}")]
     FulltextLanguageInvalid(String),
+    #[error("Fulltext directive can only have one of 'language' and 'languages'")]
+    FulltextLanguageConflict,
+    #[error("Fulltext language is listed more than once: {0}")]
+    FulltextLanguageDuplicate(String),
     #[error("Fulltext algorithm is undefined")]
     FulltextAlgorithmUndefined,
     #[error("Fulltext algorithm is invalid: {0}")]
@@ -115,6 +121,8 @@ pub enum SchemaValidationError {
     FulltextIncludedFieldMissingRequiredProperty,
     #[error("Fulltext entity field, {0}, not found or not a string")]
     FulltextIncludedFieldInvalid(String),
+    #[error("Fulltext entity field, {0}, has invalid weight {1}; it must be one of A, B, C, D")]
+    FulltextIncludedFieldWeightInvalid(String, String),
     #[error("Type {0} is missing an `id` field")]
     IdFieldMissing(String),
     #[error("{0}")]
@@ -157,6 +165,8 @@ pub enum SchemaValidationError {
     AggregationUnknownField(String, String, String),
     #[error("Field {1} in aggregation {0} has type {2} but its type in the source is {3}")]
     AggregationNonMatchingType(String, String, String, String),
+    #[error("Field {1} in aggregation {0} has type {2} which can not be used as a dimension")]
+    AggregationInvalidDimensionType(String, String, String),
     #[error("Field {1} in aggregation {0} has an invalid argument for `arg`: it must be a string")]
     AggregationInvalidArg(String, String),
     #[error("Field {1} in aggregation {0} uses the unknown aggregation function `{2}`")]
@@ -177,10 +187,32 @@ pub enum SchemaValidationError {
     AggregationNonNumericArg(String, String, String, String),
     #[error("Field {1} in aggregation {0} has an invalid value for `cumulative`. It needs to be a boolean")]
     AggregationInvalidCumulative(String, String),
+    #[error("Field {1} in aggregation {0} can not be cumulative since the function `{2}` can not be computed from the aggregate for an earlier period")]
+    AggregationCumulativeNotSupported(String, String, String),
+    #[error("Field {1} in aggregation {0} uses the function `{2}` which produces fractional values; the field must be of type BigDecimal")]
+    AggregationNonDecimalResult(String, String, String),
+    #[error("Field {1} in aggregation {0} is missing the `weight` argument which the function weightedAvg requires; it must be a string")]
+    AggregationMissingWeight(String, String),
+    #[error("Field {1} in aggregation {0} needs a `percentile` argument that is a float between 0 and 1")]
+    AggregationInvalidPercentile(String, String),
+    #[error("Field {1} in aggregation {0} has an argument `{2}` but the function `{3}` does not take it")]
+    AggregationUnexpectedArg(String, String, String, String),
     #[error("Aggregations are not supported with spec version {0}; please migrate the subgraph to the latest version")]
     AggregationsNotSupported(Version),
     #[error("Using Int8 as the type for the `id` field is not supported with spec version {0}; please migrate the subgraph to the latest version")]
     IdTypeInt8NotSupported(Version),
+    #[error(
+        "The `key` of entity {0} must be a non-empty list of distinct field names other than `id`"
+    )]
+    EntityKeyInvalid(String),
+    #[error("The `key` of entity {0} contains {1} but the entity has no such field")]
+    EntityKeyUnknownField(String, String),
+    #[error("The `key` of entity {0} contains {1} of type {2}, but key fields must be non-nullable scalars or enums")]
+    EntityKeyInvalidField(String, String, String),
+    #[error("The `retention` of entity {0} must be a duration like \"30d\" with one of the units s, m, h, d, or w, but is {1}")]
+    EntityRetentionInvalid(String, String),
+    #[error("The `@index` directive on field {1} of entity {0} is invalid: {2}")]
+    IndexDirectiveInvalid(String, String, String),
     #[error("{0}")]
     ExprNotSupported(String),
     #[error("Expressions can't us the function {0}")]
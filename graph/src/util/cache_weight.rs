@@ -158,9 +158,12 @@ impl CacheWeight for Value {
             Value::List(values) => values.indirect_weight(),
             Value::Bytes(bytes) => bytes.indirect_weight(),
             Value::BigInt(n) => n.indirect_weight(),
-            Value::Timestamp(_) | Value::Int8(_) | Value::Int(_) | Value::Bool(_) | Value::Null => {
-                0
-            }
+            Value::Timestamp(_)
+            | Value::GeoPoint(_)
+            | Value::Int8(_)
+            | Value::Int(_)
+            | Value::Bool(_)
+            | Value::Null => 0,
         }
     }
 }
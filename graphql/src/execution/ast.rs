@@ -232,7 +232,9 @@ impl Directive {
             .map(|(_, v)| v)
     }
 
-    fn eval_if(&self) -> bool {
+    /// Evaluate the `if` argument of the directive, which defaults to
+    /// `true` if it is not given
+    pub(crate) fn eval_if(&self) -> bool {
         match self.argument_value("if") {
             None => true,
             Some(r::Value::Boolean(b)) => *b,
@@ -326,6 +328,16 @@ impl Field {
             })
             .collect();
 
+        // Derived fields with a `key` find their children through the value
+        // of the key, which we therefore need to select, too
+        column_names.extend(
+            self.selection_set
+                .fields_for_name(entity_type.typename())?
+                .filter_map(|field| entity_type.field(&field.name))
+                .filter_map(|field| field.derived_key())
+                .map(|key| key.to_string()),
+        );
+
         // We need to also select the `orderBy` field if there is one
         use EntityOrder::*;
         let order_field = match order {
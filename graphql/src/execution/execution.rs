@@ -16,6 +16,7 @@ use parking_lot::MutexGuard;
 use std::time::Instant;
 use std::{borrow::ToOwned, collections::HashSet};
 
+use graph::components::tracing::TraceContext;
 use graph::data::graphql::*;
 use graph::data::query::CacheStatus;
 use graph::env::CachedSubgraphIds;
@@ -220,6 +221,10 @@ where
     /// Time at which the query times out.
     pub deadline: Option<Instant>,
 
+    /// Whether to return the top-level fields that were resolved before
+    /// `deadline` instead of failing the query when it times out
+    pub partial_results: bool,
+
     /// Max value for `first`.
     pub max_first: u32,
 
@@ -258,6 +263,7 @@ where
             resolver: introspection_resolver,
             query: self.query.cheap_clone(),
             deadline: self.deadline,
+            partial_results: false,
             max_first: std::u32::MAX,
             max_skip: std::u32::MAX,
 
@@ -268,11 +274,15 @@ where
     }
 }
 
+/// Execute the root selection set without looking at the query cache.
+/// Besides the result, return the response keys of the top-level fields
+/// that were left out because the query timed out; that list can only be
+/// non-empty if `ctx.partial_results` is set
 pub(crate) async fn execute_root_selection_set_uncached(
     ctx: &ExecutionContext<impl Resolver>,
     selection_set: &a::SelectionSet,
     root_type: &sast::ObjectType,
-) -> Result<(Object, Trace), Vec<QueryExecutionError>> {
+) -> Result<(Object, Trace, Vec<String>), Vec<QueryExecutionError>> {
     // Split the top-level fields into introspection fields and
     // regular data fields
     let mut data_set = a::SelectionSet::empty_from(selection_set);
@@ -293,8 +303,11 @@ pub(crate) async fn execute_root_selection_set_uncached(
     }
 
     // If we are getting regular data, prefetch it from the database
+    let mut truncated = Vec::new();
     let (mut values, trace) = if data_set.is_empty() && meta_items.is_empty() {
         (Object::default(), Trace::None)
+    } else if ctx.partial_results && ctx.deadline.is_some() {
+        execute_fields_until_timeout(ctx, &data_set, meta_items, root_type, &mut truncated).await?
     } else {
         let (initial_data, trace) = ctx.resolver.prefetch(ctx, &data_set)?;
         data_set.push_fields(meta_items)?;
@@ -314,6 +327,63 @@ pub(crate) async fn execute_root_selection_set_uncached(
         );
     }
 
+    Ok((values, trace, truncated))
+}
+
+/// Prefetch and execute the top-level fields in `data_set` and `meta_items`
+/// one at a time. Once a field runs into the query timeout, stop and add
+/// the response keys of that field and of all the fields after it to
+/// `truncated` so that the fields that did finish can still be returned
+async fn execute_fields_until_timeout(
+    ctx: &ExecutionContext<impl Resolver>,
+    data_set: &a::SelectionSet,
+    meta_items: Vec<&a::Field>,
+    root_type: &sast::ObjectType,
+    truncated: &mut Vec<String>,
+) -> Result<(Object, Trace), Vec<QueryExecutionError>> {
+    let mut values = Object::default();
+    let mut trace = Trace::None;
+
+    let data_fields = data_set.fields_for(root_type)?.map(|field| (field, false));
+    let meta_fields = meta_items.into_iter().map(|field| (field, true));
+    for (field, is_meta) in data_fields.chain(meta_fields) {
+        if !truncated.is_empty() {
+            truncated.push(field.response_key().to_string());
+            continue;
+        }
+
+        // Meta fields are not prefetched, but we still need a trace for
+        // the block if this is the only field
+        let mut field_set = a::SelectionSet::empty_from(data_set);
+        if !is_meta {
+            field_set.push(field)?;
+        }
+        let res = async {
+            let (initial_data, field_trace) = ctx.resolver.prefetch(ctx, &field_set)?;
+            if is_meta {
+                field_set.push(field)?;
+            }
+            let obj =
+                execute_selection_set_to_map(ctx, &field_set, root_type, initial_data).await?;
+            Ok::<_, Vec<QueryExecutionError>>((obj, field_trace))
+        }
+        .await;
+
+        match res {
+            Ok((obj, field_trace)) => {
+                values.append(obj);
+                trace.merge(field_trace);
+            }
+            Err(errors)
+                if errors
+                    .iter()
+                    .all(|e| matches!(e, QueryExecutionError::Timeout)) =>
+            {
+                truncated.push(field.response_key().to_string())
+            }
+            Err(errors) => return Err(errors),
+        }
+    }
     Ok((values, trace))
 }
 
@@ -377,18 +447,27 @@ pub(crate) async fn execute_root_selection_set<R: Resolver>(
         let logger = execute_ctx.logger.clone();
         let query_text = execute_ctx.query.query_text.cheap_clone();
         let variables_text = execute_ctx.query.variables_text.cheap_clone();
+        // Store queries happen on the blocking thread and belong to the
+        // span of this query
+        let trace_cx = TraceContext::current();
         match graph::spawn_blocking_allow_panic(move || {
+            let _trace_guard = trace_cx.attach();
+            let mut truncated = Vec::new();
             let mut query_res = QueryResult::from(
                 graph::block_on(execute_root_selection_set_uncached(
                     &execute_ctx,
                     &execute_selection_set,
                     &execute_root_type,
                 ))
-                .map(|(obj, mut trace)| {
+                .map(|(obj, mut trace, fields)| {
                     trace.query_done(query_start.elapsed(), &_permit);
+                    truncated = fields;
                     (obj, trace)
                 }),
             );
+            if !truncated.is_empty() {
+                query_res.mark_truncated(truncated);
+            }
 
             // Unwrap: In practice should never fail, but if it does we will catch the panic.
             execute_ctx.resolver.post_process(&mut query_res).unwrap();
@@ -994,12 +994,10 @@ impl Transform {
                     }
                 }
                 q::Selection::FragmentSpread(spread) => {
-                    // TODO: we ignore the directives here (and so did the
-                    // old implementation), but that seems wrong
                     let q::FragmentSpread {
                         position: _,
                         fragment_name,
-                        directives: _,
+                        directives: spread_directives,
                     } = spread;
                     let frag = self.fragments.get(&fragment_name).unwrap();
                     if visited_fragments.insert(fragment_name) {
@@ -1010,8 +1008,14 @@ impl Transform {
                             directives,
                             selection_set,
                         } = frag;
+                        // Directives on the spread, like `@defer`, apply
+                        // in addition to those on the fragment definition
+                        let directives = spread_directives
+                            .into_iter()
+                            .chain(directives.iter().cloned())
+                            .collect();
                         self.expand_fragment(
-                            directives.clone(),
+                            directives,
                             Some(type_condition),
                             type_set,
                             selection_set.clone(),
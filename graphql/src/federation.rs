@@ -0,0 +1,394 @@
+//! Splitting of federated queries.
+//!
+//! A federated query selects from several subgraphs at once. Each
+//! top-level field of such a query is the namespace of a subgraph, and the
+//! selection set of that field is an ordinary query against the subgraph:
+//!
+//! ```graphql
+//! query($first: Int) {
+//!   uniswap { pools(first: $first) { id } }
+//!   blocks: ens { domains { id name } }
+//! }
+//! ```
+//!
+//! The query is split into one query per namespace which are then run
+//! independently and have their results nested under the namespace
+
+use std::collections::{HashMap, HashSet};
+
+use graph::data::query::QueryTarget;
+use graph::prelude::{q, BlockNumber, Query, QueryExecutionError};
+
+/// The query for one namespace of a federated query
+#[derive(Clone, Debug)]
+pub struct Part {
+    /// The key under which the result of this part is returned
+    pub key: String,
+    pub target: QueryTarget,
+    name: Option<String>,
+    variable_definitions: Vec<q::VariableDefinition>,
+    fragments: Vec<q::FragmentDefinition>,
+    selection_set: q::SelectionSet,
+}
+
+impl Part {
+    /// The query document for this part where every top-level field that
+    /// does not have a `block` argument yet is constrained to `block`
+    pub fn document(&self, block: BlockNumber) -> q::Document {
+        let mut selection_set = self.selection_set.clone();
+        pin_block(&mut selection_set, block);
+
+        let mut variables = HashSet::new();
+        let mut fragments = HashSet::new();
+        let by_name: HashMap<_, _> = self
+            .fragments
+            .iter()
+            .map(|frag| (frag.name.as_str(), frag))
+            .collect();
+        collect_selection_set(&selection_set, &by_name, &mut variables, &mut fragments);
+
+        let query = q::Query {
+            position: selection_set.span.0,
+            name: self.name.clone(),
+            variable_definitions: self
+                .variable_definitions
+                .iter()
+                .filter(|def| variables.contains(&def.name))
+                .cloned()
+                .collect(),
+            directives: vec![],
+            selection_set,
+        };
+        let mut definitions = vec![q::Definition::Operation(q::OperationDefinition::Query(
+            query,
+        ))];
+        definitions.extend(
+            self.fragments
+                .iter()
+                .filter(|frag| fragments.contains(&frag.name))
+                .cloned()
+                .map(q::Definition::Fragment),
+        );
+        q::Document { definitions }
+    }
+}
+
+/// Split the federated `query` into one part for each of its top-level
+/// fields. The name of each top-level field must be one of `namespaces`
+pub fn split(
+    query: &Query,
+    namespaces: &[(String, QueryTarget)],
+) -> Result<Vec<Part>, QueryExecutionError> {
+    let mut operations = vec![];
+    let mut fragments = vec![];
+    for def in &query.document.definitions {
+        match def {
+            q::Definition::Operation(op) => operations.push(op),
+            q::Definition::Fragment(frag) => fragments.push(frag.clone()),
+        }
+    }
+
+    let (name, variable_definitions, selection_set) = match operations.as_slice() {
+        [q::OperationDefinition::SelectionSet(set)] => (None, vec![], set),
+        [q::OperationDefinition::Query(query)] => {
+            if !query.directives.is_empty() {
+                return Err(QueryExecutionError::NotSupported(
+                    "directives on federated queries".to_string(),
+                ));
+            }
+            (
+                query.name.clone(),
+                query.variable_definitions.clone(),
+                &query.selection_set,
+            )
+        }
+        [q::OperationDefinition::Mutation(_)] | [q::OperationDefinition::Subscription(_)] => {
+            return Err(QueryExecutionError::NotSupported(
+                "only queries can be federated".to_string(),
+            ))
+        }
+        _ => {
+            return Err(QueryExecutionError::ValidationError(
+                None,
+                "a federated query must contain exactly one operation".to_string(),
+            ))
+        }
+    };
+
+    let by_name: HashMap<_, _> = fragments
+        .iter()
+        .map(|frag| (frag.name.as_str(), frag))
+        .collect();
+
+    let mut keys = HashSet::new();
+    let mut parts = vec![];
+    for sel in &selection_set.items {
+        let field = match sel {
+            q::Selection::Field(field) => field,
+            q::Selection::FragmentSpread(q::FragmentSpread { position, .. })
+            | q::Selection::InlineFragment(q::InlineFragment { position, .. }) => {
+                return Err(QueryExecutionError::ValidationError(
+                    Some(*position),
+                    "fragments can not be used at the top level of a federated query".to_string(),
+                ))
+            }
+        };
+        let target = namespaces
+            .iter()
+            .find(|(namespace, _)| namespace == &field.name)
+            .map(|(_, target)| target.clone())
+            .ok_or_else(|| {
+                QueryExecutionError::ValidationError(
+                    Some(field.position),
+                    format!("`{}` is not a subgraph namespace", field.name),
+                )
+            })?;
+        if !field.arguments.is_empty() || !field.directives.is_empty() {
+            return Err(QueryExecutionError::ValidationError(
+                Some(field.position),
+                format!(
+                    "the namespace `{}` does not accept arguments or directives",
+                    field.name
+                ),
+            ));
+        }
+        let key = field.alias.clone().unwrap_or_else(|| field.name.clone());
+        if !keys.insert(key.clone()) {
+            return Err(QueryExecutionError::ValidationError(
+                Some(field.position),
+                format!("the namespace `{}` is selected more than once", key),
+            ));
+        }
+        if field.selection_set.items.is_empty() {
+            return Err(QueryExecutionError::EmptySelectionSet(key));
+        }
+
+        let selection_set = inline_fragments(&field.selection_set, &by_name, &mut vec![])?;
+        parts.push(Part {
+            key,
+            target,
+            name: name.clone(),
+            variable_definitions: variable_definitions.clone(),
+            fragments: fragments.clone(),
+            selection_set,
+        });
+    }
+    Ok(parts)
+}
+
+/// Replace fragment spreads in `set` with equivalent inline fragments so
+/// that `pin_block` can get to all top-level fields without having to
+/// change fragment definitions that might also be used further down
+fn inline_fragments<'a>(
+    set: &q::SelectionSet,
+    fragments: &HashMap<&'a str, &'a q::FragmentDefinition>,
+    visiting: &mut Vec<&'a str>,
+) -> Result<q::SelectionSet, QueryExecutionError> {
+    let mut items = Vec::with_capacity(set.items.len());
+    for sel in &set.items {
+        let sel = match sel {
+            q::Selection::Field(_) => sel.clone(),
+            q::Selection::InlineFragment(frag) => q::Selection::InlineFragment(q::InlineFragment {
+                selection_set: inline_fragments(&frag.selection_set, fragments, visiting)?,
+                ..frag.clone()
+            }),
+            q::Selection::FragmentSpread(spread) => {
+                let (name, frag) = fragments
+                    .get_key_value(spread.fragment_name.as_str())
+                    .ok_or_else(|| {
+                        QueryExecutionError::UndefinedFragment(spread.fragment_name.clone())
+                    })?;
+                if visiting.contains(name) {
+                    return Err(QueryExecutionError::CyclicalFragment(name.to_string()));
+                }
+                visiting.push(*name);
+                let selection_set = inline_fragments(&frag.selection_set, fragments, visiting)?;
+                visiting.pop();
+                q::Selection::InlineFragment(q::InlineFragment {
+                    position: spread.position,
+                    type_condition: Some(frag.type_condition.clone()),
+                    directives: spread.directives.clone(),
+                    selection_set,
+                })
+            }
+        };
+        items.push(sel);
+    }
+    Ok(q::SelectionSet {
+        span: set.span,
+        items,
+    })
+}
+
+/// Add `block: { number: <block> }` to all top-level fields in `set`
+/// that do not have a `block` argument. Introspection fields are left
+/// alone since they do not take a block
+fn pin_block(set: &mut q::SelectionSet, block: BlockNumber) {
+    for sel in &mut set.items {
+        match sel {
+            q::Selection::Field(field) => {
+                if field.name.starts_with("__")
+                    || field.arguments.iter().any(|(name, _)| name == "block")
+                {
+                    continue;
+                }
+                let number = q::Value::Object(
+                    [("number".to_string(), q::Value::Int(block.into()))]
+                        .into_iter()
+                        .collect(),
+                );
+                field.arguments.push(("block".to_string(), number));
+            }
+            q::Selection::InlineFragment(frag) => pin_block(&mut frag.selection_set, block),
+            // `inline_fragments` removed all fragment spreads
+            q::Selection::FragmentSpread(_) => {}
+        }
+    }
+}
+
+/// Collect the names of all variables and fragments that `set` uses,
+/// directly or through other fragments
+fn collect_selection_set(
+    set: &q::SelectionSet,
+    fragments: &HashMap<&str, &q::FragmentDefinition>,
+    variables: &mut HashSet<String>,
+    used: &mut HashSet<String>,
+) {
+    for sel in &set.items {
+        match sel {
+            q::Selection::Field(field) => {
+                for (_, value) in &field.arguments {
+                    collect_value(value, variables);
+                }
+                collect_directives(&field.directives, variables);
+                collect_selection_set(&field.selection_set, fragments, variables, used);
+            }
+            q::Selection::FragmentSpread(spread) => {
+                collect_directives(&spread.directives, variables);
+                if used.insert(spread.fragment_name.clone()) {
+                    if let Some(frag) = fragments.get(spread.fragment_name.as_str()) {
+                        collect_directives(&frag.directives, variables);
+                        collect_selection_set(&frag.selection_set, fragments, variables, used);
+                    }
+                }
+            }
+            q::Selection::InlineFragment(frag) => {
+                collect_directives(&frag.directives, variables);
+                collect_selection_set(&frag.selection_set, fragments, variables, used);
+            }
+        }
+    }
+}
+
+fn collect_directives(directives: &[q::Directive], variables: &mut HashSet<String>) {
+    for dir in directives {
+        for (_, value) in &dir.arguments {
+            collect_value(value, variables);
+        }
+    }
+}
+
+fn collect_value(value: &q::Value, variables: &mut HashSet<String>) {
+    match value {
+        q::Value::Variable(name) => {
+            variables.insert(name.clone());
+        }
+        q::Value::List(values) => values.iter().for_each(|v| collect_value(v, variables)),
+        q::Value::Object(map) => map.values().for_each(|v| collect_value(v, variables)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::data::query::QueryTarget;
+    use graph::prelude::{q, ApiVersion, DeploymentHash, Query, QueryExecutionError};
+
+    use super::split;
+
+    fn namespaces() -> Vec<(String, QueryTarget)> {
+        ["uniswap", "ens"]
+            .into_iter()
+            .map(|ns| {
+                let id = DeploymentHash::new(ns).unwrap();
+                (
+                    ns.to_string(),
+                    QueryTarget::Deployment(id, ApiVersion::default()),
+                )
+            })
+            .collect()
+    }
+
+    fn parse(text: &str) -> q::Document {
+        q::parse_query(text).unwrap().into_static()
+    }
+
+    fn query(text: &str) -> Query {
+        Query::new(parse(text), None, false)
+    }
+
+    #[test]
+    fn split_federated_query() {
+        let query = query(
+            "query q($first: Int, $name: String) { \
+               uniswap { pools(first: $first) { ...PoolFields } ...Meta } \
+               names: ens { domains(where: { name: $name }) { id } \
+                            _meta(block: { number: 7 }) { deployment } __typename } \
+             } \
+             fragment PoolFields on Pool { id token { id } } \
+             fragment Meta on Query { _meta { hasIndexingErrors } }",
+        );
+        let parts = split(&query, &namespaces()).unwrap();
+        assert_eq!(2, parts.len());
+
+        assert_eq!("uniswap", parts[0].key);
+        let expected = parse(
+            "query q($first: Int) { \
+               pools(first: $first, block: { number: 10 }) { ...PoolFields } \
+               ... on Query { _meta(block: { number: 10 }) { hasIndexingErrors } } \
+             } \
+             fragment PoolFields on Pool { id token { id } }",
+        );
+        assert_eq!(expected.to_string(), parts[0].document(10).to_string());
+
+        assert_eq!("names", parts[1].key);
+        let expected = parse(
+            "query q($name: String) { \
+               domains(where: { name: $name }, block: { number: 5 }) { id } \
+               _meta(block: { number: 7 }) { deployment } __typename \
+             }",
+        );
+        assert_eq!(expected.to_string(), parts[1].document(5).to_string());
+    }
+
+    #[test]
+    fn reject_invalid_federated_queries() {
+        let namespaces = namespaces();
+        let err = |text: &str| split(&query(text), &namespaces).unwrap_err();
+
+        assert!(matches!(
+            err("{ aave { reserves { id } } }"),
+            QueryExecutionError::ValidationError(_, _)
+        ));
+        assert!(matches!(
+            err("{ uniswap(block: { number: 1 }) { pools { id } } }"),
+            QueryExecutionError::ValidationError(_, _)
+        ));
+        assert!(matches!(
+            err("{ uniswap { pools { id } } uniswap { tokens { id } } }"),
+            QueryExecutionError::ValidationError(_, _)
+        ));
+        assert!(matches!(
+            err("{ ...Root } fragment Root on Query { uniswap { pools { id } } }"),
+            QueryExecutionError::ValidationError(_, _)
+        ));
+        assert!(matches!(
+            err("subscription { uniswap { pools { id } } }"),
+            QueryExecutionError::NotSupported(_)
+        ));
+        assert!(matches!(
+            err("{ uniswap { ...Missing } }"),
+            QueryExecutionError::UndefinedFragment(_)
+        ));
+    }
+}
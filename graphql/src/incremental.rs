@@ -0,0 +1,177 @@
+//! Splitting of queries that use `@defer` and `@stream`.
+//!
+//! Only top-level fields are delivered incrementally. Fields that come from
+//! a fragment marked with `@defer` are left out of the initial result and
+//! sent in a later part, one part per label. Top-level fields marked with
+//! `@stream` are fetched in batches by rewriting their `first` and `skip`
+//! arguments so that we never hold more than one batch of them in memory.
+//!
+//! Batches are pages with an offset, not cursors: every batch runs the
+//! query of the field again with `skip` moved forward, and the database
+//! has to step over all the items before that offset. All batches are read
+//! at the same block, so they page through the same list. Since that
+//! offset is subject to `GRAPH_GRAPHQL_MAX_SKIP` like any other `skip`,
+//! fields whose last batch would start past it are not streamed but
+//! returned in full in the initial result.
+//!
+//! The incremental delivery proposal allows servers to ignore these
+//! directives, and we do that for nested fields whose data is always
+//! returned as part of their parent
+
+use graph::components::store::ChildMultiplicity;
+use graph::prelude::{r, QueryExecutionError};
+
+use crate::execution::ast as a;
+use crate::store::build_range;
+
+const DEFER: &str = "defer";
+const STREAM: &str = "stream";
+
+/// Return the string argument `name` of `dir`
+fn string_argument(dir: &a::Directive, name: &str) -> Option<String> {
+    match dir.argument_value(name) {
+        Some(r::Value::String(s)) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// Return the integer argument `name` of `dir`, or `default` if the
+/// argument is not set
+fn int_argument(dir: &a::Directive, name: &str, default: u32) -> u32 {
+    match dir.argument_value(name) {
+        Some(r::Value::Int(n)) => (*n).max(0) as u32,
+        _ => default,
+    }
+}
+
+fn set_argument(field: &mut a::Field, name: &str, value: u32) {
+    let value = r::Value::Int(value as i64);
+    match field.arguments.iter_mut().find(|(n, _)| n == name) {
+        Some((_, v)) => *v = value,
+        None => field.arguments.push((name.to_string(), value)),
+    }
+}
+
+/// The fields of a fragment marked with `@defer`
+pub struct Deferred {
+    pub label: Option<String>,
+    pub selection_set: a::SelectionSet,
+}
+
+/// A top-level field marked with `@stream`
+#[derive(Clone)]
+pub struct Stream {
+    pub label: Option<String>,
+    field: a::Field,
+    /// The number of items that are part of the initial result
+    pub initial_count: u32,
+    /// The `first` and `skip` arguments of the field
+    first: u32,
+    skip: u32,
+}
+
+impl Stream {
+    pub fn key(&self) -> &str {
+        self.field.response_key()
+    }
+
+    /// The field restricted to at most `count` items starting with the
+    /// item at index `start` of the original field. Returns `None` if
+    /// there are no items left
+    pub fn window(&self, start: u32, count: u32) -> Option<a::Field> {
+        let count = count.min(self.first.saturating_sub(start));
+        if count == 0 {
+            return None;
+        }
+        let mut field = self.field.clone();
+        set_argument(&mut field, "first", count);
+        set_argument(&mut field, "skip", self.skip.saturating_add(start));
+        Some(field)
+    }
+}
+
+/// How to deliver the result of a query incrementally
+pub struct Plan {
+    /// The fields that are part of the initial result
+    pub initial: a::SelectionSet,
+    pub deferred: Vec<Deferred>,
+    pub streams: Vec<Stream>,
+}
+
+impl Plan {
+    /// Split `selection_set` into parts. The `first` and `skip` arguments
+    /// of streamed fields are checked against `max_first` and `max_skip`
+    /// before they are split into batches
+    pub fn new(
+        selection_set: &a::SelectionSet,
+        max_first: u32,
+        max_skip: u32,
+    ) -> Result<Self, QueryExecutionError> {
+        let mut initial = a::SelectionSet::empty_from(selection_set);
+        let mut deferred: Vec<Deferred> = Vec::new();
+        let mut streams = Vec::new();
+
+        for (_, fields) in selection_set.fields() {
+            for field in fields {
+                let active = |name: &str| {
+                    field
+                        .directives
+                        .iter()
+                        .find(|dir| dir.name == name && dir.eval_if())
+                };
+
+                if let Some(dir) = active(DEFER) {
+                    let label = string_argument(dir, "label");
+                    match deferred.iter_mut().find(|part| part.label == label) {
+                        Some(part) => part.selection_set.push(field)?,
+                        None => {
+                            let mut part = Deferred {
+                                label,
+                                selection_set: a::SelectionSet::empty_from(selection_set),
+                            };
+                            part.selection_set.push(field)?;
+                            deferred.push(part);
+                        }
+                    }
+                    continue;
+                }
+
+                // Only collections can be streamed since we page through
+                // them with `first` and `skip`
+                let dir = match active(STREAM) {
+                    Some(dir) if field.multiplicity == ChildMultiplicity::Many => dir,
+                    _ => {
+                        initial.push(field)?;
+                        continue;
+                    }
+                };
+                let range = build_range(field, max_first, max_skip)?;
+                let first = range.first.unwrap_or(max_first);
+                let skip = range.skip;
+                // The later batches start past `skip` and can't be sent
+                // if they would have to skip more than `max_skip` items
+                if skip.saturating_add(first - 1) > max_skip {
+                    initial.push(field)?;
+                    continue;
+                }
+                let stream = Stream {
+                    label: string_argument(dir, "label"),
+                    field: field.clone(),
+                    initial_count: int_argument(dir, "initialCount", 0).min(first),
+                    first,
+                    skip,
+                };
+                if let Some(field) = stream.window(0, stream.initial_count) {
+                    initial.push(&field)?;
+                }
+                streams.push(stream);
+            }
+        }
+
+        Ok(Plan {
+            initial,
+            deferred,
+            streams,
+        })
+    }
+}
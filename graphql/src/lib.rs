@@ -16,6 +16,13 @@ mod values;
 /// Utilities for querying `Store` components.
 mod store;
 
+/// Utilities for splitting federated queries into queries per subgraph
+mod federation;
+
+/// Utilities for delivering query results incrementally with `@defer`
+/// and `@stream`
+mod incremental;
+
 /// The external interface for actually running queries
 mod runner;
 
@@ -21,6 +21,9 @@ pub struct QueryExecutionOptions<R> {
     /// Time at which the query times out.
     pub deadline: Option<Instant>,
 
+    /// Return partial results when the query runs into `deadline`
+    pub partial_results: bool,
+
     /// Maximum value for the `first` argument.
     pub max_first: u32,
 
@@ -48,6 +51,7 @@ where
         resolver: options.resolver,
         query: query.clone(),
         deadline: options.deadline,
+        partial_results: options.partial_results,
         max_first: options.max_first,
         max_skip: options.max_skip,
         cache_status: Default::default(),
@@ -1,26 +1,44 @@
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::time::Instant;
 
 use crate::metrics::GraphQLMetrics;
-use crate::prelude::{QueryExecutionOptions, StoreResolver, SubscriptionExecutionOptions};
+use crate::prelude::{a, QueryExecutionOptions, StoreResolver, SubscriptionExecutionOptions};
 use crate::query::execute_query;
 use crate::subscription::execute_prepared_subscription;
+use crate::{federation, incremental};
+use graph::components::tracing::{self, KeyValue, TraceContext, TraceFutureExt as _};
 use graph::futures03::future;
+use graph::futures03::stream::{self, StreamExt};
 use graph::prelude::MetricsRegistry;
 use graph::{
     components::store::SubscriptionManager,
     prelude::{
-        async_trait, o, CheapClone, DeploymentState, GraphQLMetrics as GraphQLMetricsTrait,
-        GraphQlRunner as GraphQlRunnerTrait, Logger, Query, QueryExecutionError, Subscription,
-        SubscriptionError, SubscriptionResult, ENV_VARS,
+        async_trait, o, r, BlockNumber, CheapClone, DeploymentState,
+        GraphQLMetrics as GraphQLMetricsTrait, GraphQlRunner as GraphQlRunnerTrait, Logger, Query,
+        QueryExecutionError, Subscription, SubscriptionError, SubscriptionResult, ENV_VARS,
     },
 };
 use graph::{data::graphql::load_manager::LoadManager, prelude::QueryStoreManager};
 use graph::{
-    data::query::{QueryResults, QueryTarget},
+    data::query::{
+        CacheStatus, EntityHistory, EntityHistoryQuery, IncrementalPart, IncrementalPayload,
+        IncrementalResults, QueryResult, QueryResults, QueryTarget, SqlQueryResult, Trace,
+    },
+    data::value::{Object, Word},
     prelude::QueryStore,
 };
 
+/// A part of an incremental response that still needs to be computed
+enum Pending {
+    Deferred(incremental::Deferred),
+    /// The batch of items of `stream` that starts at index `start`
+    Stream {
+        stream: incremental::Stream,
+        start: u32,
+    },
+}
+
 /// GraphQL runner implementation for The Graph.
 pub struct GraphQlRunner<S, SM> {
     logger: Logger,
@@ -90,18 +108,24 @@ where
         Ok(())
     }
 
-    async fn execute(
+    /// Set up the store for running `query` against `target` and prepare
+    /// the query for execution
+    async fn prepare(
         &self,
         query: Query,
         target: QueryTarget,
         max_complexity: Option<u64>,
         max_depth: Option<u8>,
-        max_first: Option<u32>,
-        max_skip: Option<u32>,
-        metrics: Arc<GraphQLMetrics>,
-    ) -> Result<QueryResults, QueryResults> {
-        let execute_start = Instant::now();
-
+        metrics: &Arc<GraphQLMetrics>,
+    ) -> Result<
+        (
+            Arc<dyn QueryStore + Send + Sync>,
+            DeploymentState,
+            Arc<crate::execution::Query>,
+            bool,
+        ),
+        QueryResults,
+    > {
         // We need to use the same `QueryStore` for the entire query to ensure
         // we have a consistent view if the world, even when replicas, which
         // are eventually consistent, are in use. If we run different parts
@@ -145,6 +169,32 @@ where
                 query.query_text.as_ref(),
             )
             .to_result()?;
+        Ok((store, state, query, do_trace))
+    }
+
+    async fn execute(
+        &self,
+        query: Query,
+        target: QueryTarget,
+        max_complexity: Option<u64>,
+        max_depth: Option<u8>,
+        max_first: Option<u32>,
+        max_skip: Option<u32>,
+        metrics: Arc<GraphQLMetrics>,
+    ) -> Result<QueryResults, QueryResults> {
+        let execute_start = Instant::now();
+
+        let (store, state, query, do_trace) = self
+            .prepare(query, target, max_complexity, max_depth, &metrics)
+            .with_context(tracing::child_span("graphql.prepare", vec![]))
+            .await?;
+        tracing::set_attributes(
+            &TraceContext::current(),
+            vec![
+                KeyValue::new("deployment", query.schema.id().to_string()),
+                KeyValue::new("query.shape_hash", format!("{:x}", query.shape_hash)),
+            ],
+        );
         let by_block_constraint =
             StoreResolver::locate_blocks(store.as_ref(), &state, &query).await?;
         let mut max_block = 0;
@@ -167,18 +217,29 @@ where
             )
             .await?;
             max_block = max_block.max(resolver.block_number());
-            query_res_futures.push(execute_query(
-                query.clone(),
-                Some(selection_set),
-                resolver.block_ptr.clone(),
-                QueryExecutionOptions {
-                    resolver,
-                    deadline: ENV_VARS.graphql.query_timeout.map(|t| Instant::now() + t),
-                    max_first: max_first.unwrap_or(ENV_VARS.graphql.max_first),
-                    max_skip: max_skip.unwrap_or(ENV_VARS.graphql.max_skip),
-                    trace: do_trace,
-                },
-            ));
+            let execute_cx = tracing::child_span(
+                "graphql.execute",
+                vec![KeyValue::new(
+                    "block.number",
+                    resolver.block_number() as i64,
+                )],
+            );
+            query_res_futures.push(
+                execute_query(
+                    query.clone(),
+                    Some(selection_set),
+                    resolver.block_ptr.clone(),
+                    QueryExecutionOptions {
+                        resolver,
+                        deadline: ENV_VARS.graphql.query_timeout.map(|t| Instant::now() + t),
+                        partial_results: ENV_VARS.graphql.partial_results_on_timeout,
+                        max_first: max_first.unwrap_or(ENV_VARS.graphql.max_first),
+                        max_skip: max_skip.unwrap_or(ENV_VARS.graphql.max_skip),
+                        trace: do_trace,
+                    },
+                )
+                .with_context(execute_cx),
+            );
         }
 
         let results: Vec<_> = if ENV_VARS.graphql.parallel_block_constraints {
@@ -202,6 +263,223 @@ where
             .map_err(QueryResults::from)
             .map(|()| result)
     }
+
+    /// Split a federated query into a query per namespace and run them
+    /// all. To give clients a consistent view across subgraphs, all
+    /// subgraphs that index the same network are queried at the latest
+    /// block that every one of them has processed
+    async fn execute_federated(
+        self: Arc<Self>,
+        query: Query,
+        namespaces: Vec<(String, QueryTarget)>,
+    ) -> Result<QueryResults, QueryResults> {
+        let parts = federation::split(&query, &namespaces)?;
+
+        let mut networks = Vec::with_capacity(parts.len());
+        let mut blocks: HashMap<String, BlockNumber> = HashMap::new();
+        for part in &parts {
+            let store = self.store.query_store(part.target.clone(), false).await?;
+            let latest = store.deployment_state().await?.latest_block.number;
+            let network = store.network_name().to_string();
+            blocks
+                .entry(network.clone())
+                .and_modify(|block| *block = (*block).min(latest))
+                .or_insert(latest);
+            networks.push(network);
+        }
+
+        let futures = parts.into_iter().zip(networks).map(|(part, network)| {
+            let query = Query::new(
+                part.document(blocks[&network]),
+                query.variables.clone(),
+                false,
+            );
+            let runner = self.cheap_clone();
+            async move {
+                let results = runner.run_query(query, part.target).await;
+                results.nest(Word::from(part.key))
+            }
+        });
+
+        let mut result = QueryResults::empty(Trace::None);
+        for part_result in future::join_all(futures).await {
+            result.append(Arc::new(part_result), CacheStatus::default());
+        }
+        Ok(result)
+    }
+
+    /// Run a query whose top-level fields may use `@defer` or `@stream`.
+    /// Only the initial part of the result is computed right away;
+    /// deferred fragments and further batches of streamed fields are
+    /// computed when the returned stream is polled for them, so that we
+    /// never get ahead of the client by more than one part
+    async fn execute_incremental(
+        &self,
+        query: Query,
+        target: QueryTarget,
+    ) -> Result<IncrementalResults, QueryResults> {
+        fn options(
+            resolver: StoreResolver,
+            deadline: Option<Instant>,
+            trace: bool,
+        ) -> QueryExecutionOptions<StoreResolver> {
+            QueryExecutionOptions {
+                resolver,
+                deadline,
+                partial_results: false,
+                max_first: ENV_VARS.graphql.max_first,
+                max_skip: ENV_VARS.graphql.max_skip,
+                trace,
+            }
+        }
+
+        // The timeout applies to the whole query, not to each part
+        let deadline = ENV_VARS.graphql.query_timeout.map(|t| Instant::now() + t);
+
+        let (store, state, query, do_trace) = self
+            .prepare(
+                query,
+                target,
+                ENV_VARS.graphql.max_complexity,
+                Some(ENV_VARS.graphql.max_depth),
+                &self.graphql_metrics,
+            )
+            .await?;
+        let by_block_constraint =
+            StoreResolver::locate_blocks(store.as_ref(), &state, &query).await?;
+        let mut max_block = 0;
+        let mut result = QueryResults::empty(query.root_trace(do_trace));
+        let mut pending = VecDeque::new();
+
+        for (ptr, (selection_set, error_policy)) in by_block_constraint {
+            let resolver = StoreResolver::at_block(
+                &self.logger,
+                store.cheap_clone(),
+                &state,
+                self.subscription_manager.cheap_clone(),
+                ptr,
+                error_policy,
+                query.schema.id().clone(),
+                self.graphql_metrics.cheap_clone(),
+                self.load_manager.cheap_clone(),
+            )
+            .await?;
+            max_block = max_block.max(resolver.block_number());
+
+            let plan = incremental::Plan::new(
+                &selection_set,
+                ENV_VARS.graphql.max_first,
+                ENV_VARS.graphql.max_skip,
+            )?;
+            let (query_res, cache_status) = execute_query(
+                query.clone(),
+                Some(plan.initial),
+                resolver.block_ptr.clone(),
+                options(resolver.cheap_clone(), deadline, do_trace),
+            )
+            .await;
+            result.append(query_res, cache_status);
+
+            for part in plan.deferred {
+                pending.push_back((resolver.cheap_clone(), Pending::Deferred(part)));
+            }
+            for stream in plan.streams {
+                let start = stream.initial_count;
+                if start == 0 {
+                    // The initial result needs to contain the list, even
+                    // if all its items are streamed
+                    let empty =
+                        Object::from_iter([(Word::from(stream.key()), r::Value::List(vec![]))]);
+                    result.append(Arc::new(QueryResult::new(empty)), CacheStatus::default());
+                }
+                if stream.window(start, 1).is_some() {
+                    pending.push_back((resolver.cheap_clone(), Pending::Stream { stream, start }));
+                }
+            }
+        }
+
+        query.log_execution(max_block);
+        self.deployment_changed(store.as_ref(), state, max_block as u64)
+            .await
+            .map_err(QueryResults::from)?;
+
+        let initial =
+            IncrementalPayload::new(IncrementalPart::Initial(result), !pending.is_empty());
+        let rest = stream::unfold(pending, move |mut pending| {
+            let query = query.cheap_clone();
+            async move {
+                let (resolver, next) = pending.pop_front()?;
+                let block_ptr = resolver.block_ptr.clone();
+                let part = match next {
+                    Pending::Deferred(part) => {
+                        let (query_res, _) = execute_query(
+                            query,
+                            Some(part.selection_set),
+                            block_ptr,
+                            options(resolver, deadline, false),
+                        )
+                        .await;
+                        IncrementalPart::Deferred {
+                            label: part.label,
+                            results: query_res.into(),
+                        }
+                    }
+                    Pending::Stream { stream, start } => {
+                        let batch_size = ENV_VARS.graphql.stream_batch_size.max(1);
+                        let field = stream
+                            .window(start, batch_size)
+                            .expect("only streams with items left are pending");
+                        let mut selection_set = a::SelectionSet::empty_from(&query.selection_set);
+                        let query_res = match selection_set.push(&field) {
+                            Ok(()) => {
+                                execute_query(
+                                    query,
+                                    Some(selection_set),
+                                    block_ptr,
+                                    options(resolver.cheap_clone(), deadline, false),
+                                )
+                                .await
+                                .0
+                            }
+                            Err(e) => Arc::new(QueryResult::from(e)),
+                        };
+
+                        // A batch that is not full is the last one
+                        let count = query_res
+                            .data()
+                            .and_then(|data| data.get(stream.key()))
+                            .map(|items| match items {
+                                r::Value::List(items) => items.len() as u32,
+                                _ => 0,
+                            })
+                            .unwrap_or(0);
+                        let next = start + batch_size;
+                        if !query_res.has_errors()
+                            && count == batch_size
+                            && stream.window(next, 1).is_some()
+                        {
+                            pending.push_back((
+                                resolver,
+                                Pending::Stream {
+                                    stream: stream.clone(),
+                                    start: next,
+                                },
+                            ));
+                        }
+                        IncrementalPart::Items {
+                            label: stream.label.clone(),
+                            key: stream.key().to_string(),
+                            start: start as usize,
+                            results: query_res.into(),
+                        }
+                    }
+                };
+                let has_next = !pending.is_empty();
+                Some((IncrementalPayload::new(part, has_next), pending))
+            }
+        });
+        Ok(stream::once(future::ready(initial)).chain(rest).boxed())
+    }
 }
 
 #[async_trait]
@@ -231,17 +509,91 @@ where
         max_first: Option<u32>,
         max_skip: Option<u32>,
     ) -> QueryResults {
-        self.execute(
-            query,
-            target,
-            max_complexity,
-            max_depth,
-            max_first,
-            max_skip,
-            self.graphql_metrics.clone(),
-        )
-        .await
-        .unwrap_or_else(|e| e)
+        let cx = tracing::child_span(
+            "graphql.query",
+            vec![KeyValue::new("target", format!("{:?}", target))],
+        );
+        let results = self
+            .execute(
+                query,
+                target,
+                max_complexity,
+                max_depth,
+                max_first,
+                max_skip,
+                self.graphql_metrics.clone(),
+            )
+            .with_context(cx.clone())
+            .await
+            .unwrap_or_else(|e| e);
+        if let Some(error) = results.errors().first() {
+            tracing::record_error(&cx, error);
+        }
+        results
+    }
+
+    async fn run_incremental_query(
+        self: Arc<Self>,
+        query: Query,
+        target: QueryTarget,
+    ) -> IncrementalResults {
+        self.execute_incremental(query, target)
+            .await
+            .unwrap_or_else(|e| stream::once(future::ready(e.into())).boxed())
+    }
+
+    async fn run_federated_query(
+        self: Arc<Self>,
+        query: Query,
+        namespaces: Vec<(String, QueryTarget)>,
+    ) -> QueryResults {
+        self.execute_federated(query, namespaces)
+            .await
+            .unwrap_or_else(|e| e)
+    }
+
+    async fn run_sql_query(
+        self: Arc<Self>,
+        sql: String,
+        block: Option<BlockNumber>,
+        target: QueryTarget,
+    ) -> Result<SqlQueryResult, QueryExecutionError> {
+        let store = self.store.query_store(target, false).await?;
+        let state = store.deployment_state().await?;
+        let block = block.unwrap_or(state.latest_block.number);
+        state
+            .block_queryable(block)
+            .map_err(|msg| QueryExecutionError::ValueParseError("block".to_owned(), msg))?;
+
+        let _permit = store.query_permit().await?;
+        graph::spawn_blocking_allow_panic(move || store.execute_sql(&sql, block))
+            .await
+            .map_err(|e| QueryExecutionError::Panic(e.to_string()))
+            .and_then(|res| res)
+    }
+
+    async fn run_history_query(
+        self: Arc<Self>,
+        query: EntityHistoryQuery,
+        target: QueryTarget,
+    ) -> Result<EntityHistory, QueryExecutionError> {
+        query
+            .validate(ENV_VARS.graphql.max_first)
+            .map_err(|msg| QueryExecutionError::ValueParseError("query".to_owned(), msg))?;
+
+        let store = self.store.query_store(target, false).await?;
+        let state = store.deployment_state().await?;
+        for block in [query.from_block, query.to_block] {
+            state
+                .block_queryable(block)
+                .map_err(|msg| QueryExecutionError::ValueParseError("block".to_owned(), msg))?;
+        }
+
+        let _permit = store.query_permit().await?;
+        graph::spawn_blocking_allow_panic(move || store.entity_history(&query))
+            .await
+            .map_err(|e| QueryExecutionError::Panic(e.to_string()))
+            .and_then(|res| res)
     }
 
     async fn run_subscription(
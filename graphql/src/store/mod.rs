@@ -2,4 +2,5 @@ mod prefetch;
 mod query;
 mod resolver;
 
+pub(crate) use self::query::build_range;
 pub use self::resolver::StoreResolver;
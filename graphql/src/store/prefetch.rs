@@ -261,6 +261,9 @@ enum JoinRelation {
     Direct(JoinField),
     // Name of the field in the parent type containing child ids
     Derived(JoinField),
+    // Name of field in which child stores the value of the parent field
+    // `key`, and the type of those values
+    Keyed(JoinField, Word, IdType),
 }
 
 #[derive(Debug)]
@@ -282,7 +285,16 @@ impl JoinCond {
         field: &Field,
     ) -> Self {
         let relation = if let Some(derived_from_field) = field.derived_from(schema) {
-            JoinRelation::Direct(JoinField::new(derived_from_field))
+            match field.derived_key() {
+                Some(key) => JoinRelation::Keyed(
+                    JoinField::new(derived_from_field),
+                    Word::from(key),
+                    // Validation makes sure that keys have a type that
+                    // can be used for ids
+                    IdType::try_from(&derived_from_field.field_type).unwrap(),
+                ),
+                None => JoinRelation::Direct(JoinField::new(derived_from_field)),
+            }
         } else {
             JoinRelation::Derived(JoinField::new(field))
         };
@@ -310,6 +322,24 @@ impl JoinCond {
                     EntityLink::Direct(field.window_attribute(), multiplicity),
                 ))
             }
+            JoinRelation::Keyed(field, key, key_type) => {
+                // the parents store the value that children reference in
+                // `key`; parents without a value can not have children
+                let (ids, keys): (Vec<_>, Vec<_>) = parents_by_id
+                    .into_iter()
+                    .filter_map(|(id, node)| {
+                        node.get(key)
+                            .and_then(|value| value.as_id(*key_type))
+                            .map(|key| (id, key))
+                    })
+                    .unzip();
+                let ids = IdList::try_from_iter(self.parent_type.id_type()?, ids.into_iter())?;
+                let keys = IdList::try_from_iter(*key_type, keys.into_iter())?;
+                Ok((
+                    ids,
+                    EntityLink::Key(field.window_attribute(), keys, multiplicity),
+                ))
+            }
             JoinRelation::Derived(field) => {
                 let (ids, parent_link) = match field {
                     JoinField::Scalar(child_field) => {
@@ -8,12 +8,13 @@ use graph::components::store::{
 };
 use graph::data::graphql::TypeExt as _;
 use graph::data::query::QueryExecutionError;
+use graph::data::store::scalar::GeoPoint;
 use graph::data::store::{Attribute, SubscriptionFilter, Value, ValueType};
 use graph::data::value::Object;
 use graph::data::value::Value as DataValue;
 use graph::prelude::{r, s, TryFromValue, ENV_VARS};
 use graph::schema::ast::{self as sast, FilterOp};
-use graph::schema::{ApiSchema, EntityType, InputSchema, ObjectOrInterface};
+use graph::schema::{key_field_name, ApiSchema, EntityType, InputSchema, ObjectOrInterface};
 
 use crate::execution::ast as a;
 
@@ -49,12 +50,15 @@ pub(crate) fn build_query<'a>(
     if let Some(filter) = build_filter(entity, field, schema)? {
         query = query.filter(filter);
     }
+    if let Some(filter) = build_key_filter(entity, field)? {
+        query.filter = Some(filter.and_maybe(query.filter));
+    }
     query = query.order(order);
     Ok(query)
 }
 
 /// Parses GraphQL arguments into a EntityRange, if present.
-fn build_range(
+pub(crate) fn build_range(
     field: &a::Field,
     max_first: u32,
     max_skip: u32,
@@ -95,6 +99,35 @@ fn build_range(
     })
 }
 
+/// For lookups with the `<type>ByKey` field of a type that declares a key
+/// with `@entity(key: [..])`, the filter that selects the entity whose key
+/// fields have the values given in the arguments
+fn build_key_filter(
+    entity: &ObjectOrInterface,
+    field: &a::Field,
+) -> Result<Option<EntityFilter>, QueryExecutionError> {
+    let ObjectOrInterface::Object(_, object_type) = entity else {
+        return Ok(None);
+    };
+    if object_type.key.is_empty() || field.name != key_field_name(entity.typename()) {
+        return Ok(None);
+    }
+    let filters = object_type
+        .key_fields()
+        .map(|key_field| {
+            let value = field.argument_value(&key_field.name).ok_or_else(|| {
+                QueryExecutionError::MissingArgumentError(
+                    field.position,
+                    key_field.name.to_string(),
+                )
+            })?;
+            let value = Value::from_query_value(value, &key_field.field_type)?;
+            Ok(EntityFilter::Equal(key_field.name.to_string(), value))
+        })
+        .collect::<Result<Vec<_>, QueryExecutionError>>()?;
+    Ok(Some(EntityFilter::And(filters)))
+}
+
 /// Parses GraphQL arguments into an EntityFilter, if present.
 fn build_filter(
     entity: &ObjectOrInterface,
@@ -153,6 +186,51 @@ fn parse_change_block_filter(value: &r::Value) -> Result<BlockNumber, QueryExecu
     }
 }
 
+fn parse_geo_point(object: &Object, key: &str) -> Result<GeoPoint, QueryExecutionError> {
+    match object.get(key) {
+        Some(r::Value::String(s)) => GeoPoint::parse(s).map_err(|e| {
+            QueryExecutionError::ValueParseError("GeoPoint".to_string(), e.to_string())
+        }),
+        _ => Err(QueryExecutionError::InvalidFilterError),
+    }
+}
+
+/// Parses the value of a `<field>_within_distance` or `<field>_within_box`
+/// filter
+fn build_geo_filter(
+    field_name: String,
+    operation: FilterOp,
+    value: &r::Value,
+) -> Result<EntityFilter, QueryExecutionError> {
+    let object = match value {
+        r::Value::Object(object) => object,
+        _ => return Err(QueryExecutionError::InvalidFilterError),
+    };
+    match operation {
+        FilterOp::WithinDistance => {
+            let center = parse_geo_point(object, "center")?;
+            let meters = match object.get("meters") {
+                Some(r::Value::String(s)) => s.parse::<f64>().ok(),
+                Some(r::Value::Int(i)) => Some(*i as f64),
+                Some(r::Value::Float(f)) => Some(*f),
+                _ => None,
+            }
+            .filter(|meters| meters.is_finite() && *meters >= 0.0)
+            .ok_or(QueryExecutionError::InvalidFilterError)?;
+            Ok(EntityFilter::WithinDistance(field_name, center, meters))
+        }
+        FilterOp::WithinBox => {
+            let sw = parse_geo_point(object, "southWest")?;
+            let ne = parse_geo_point(object, "northEast")?;
+            if sw.latitude() > ne.latitude() || sw.longitude() > ne.longitude() {
+                return Err(QueryExecutionError::InvalidFilterError);
+            }
+            Ok(EntityFilter::WithinBox(field_name, sw, ne))
+        }
+        _ => unreachable!("only called for geo filters"),
+    }
+}
+
 /// Parses a GraphQL Filter Value into an EntityFilter.
 fn build_entity_filter(
     field_name: String,
@@ -296,6 +374,16 @@ fn build_filter_from_object<'a>(
                         ));
                     }
                 },
+                WithinDistance | WithinBox => {
+                    // Make sure the field exists
+                    entity.field(&field_name).ok_or_else(|| {
+                        QueryExecutionError::EntityFieldError(
+                            entity.typename().to_owned(),
+                            field_name.clone(),
+                        )
+                    })?;
+                    build_geo_filter(field_name, op, value)?
+                }
                 _ => {
                     let field = entity.field(&field_name).ok_or_else(|| {
                         QueryExecutionError::EntityFieldError(
@@ -331,6 +419,7 @@ fn build_child_filter_from_object(
         schema,
     )?));
     let derived = field.is_derived();
+    let key = field.derived_key().map(|key| key.to_string());
     let attr = match field.derived_from(schema) {
         Some(field) => field.name.to_string(),
         None => field_name.clone(),
@@ -347,6 +436,7 @@ fn build_child_filter_from_object(
                         entity_type,
                         filter: filter.clone(),
                         derived,
+                        key: key.clone(),
                     })
                 })
                 .collect(),
@@ -361,6 +451,7 @@ fn build_child_filter_from_object(
                         .field(&field_name)
                         .ok_or(QueryExecutionError::InvalidFilterError)?;
                     let derived = field.is_derived();
+                    let key = field.derived_key().map(|key| key.to_string());
 
                     let attr = match field.derived_from(schema) {
                         Some(derived_from) => derived_from.name.to_string(),
@@ -372,6 +463,7 @@ fn build_child_filter_from_object(
                         entity_type: child_entity.entity_type(),
                         filter: filter.clone(),
                         derived,
+                        key,
                     }))
                 })
                 .collect::<Result<Vec<EntityFilter>, QueryExecutionError>>()?,
@@ -382,6 +474,7 @@ fn build_child_filter_from_object(
             entity_type: schema.entity_type(*type_name)?,
             filter,
             derived,
+            key,
         }))
     }
 }
@@ -569,6 +662,12 @@ fn build_order_by(
                             parent_field_name.clone(),
                         )
                     })?;
+                if field.derived_key().is_some() {
+                    return Err(QueryExecutionError::NotSupported(format!(
+                        "Sorting by attributes of `{}`, which is derived with a `key`",
+                        parent_field_name
+                    )));
+                }
                 let derived_from = field.derived_from(schema);
                 let base_type = field.field_type.get_base_type();
 
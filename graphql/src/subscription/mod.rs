@@ -96,6 +96,7 @@ fn create_source_event_stream(
         resolver,
         query,
         deadline: None,
+        partial_results: false,
         max_first: options.max_first,
         max_skip: options.max_skip,
         cache_status: Default::default(),
@@ -235,6 +236,7 @@ async fn execute_subscription_event(
         resolver,
         query,
         deadline: timeout.map(|t| Instant::now() + t),
+        partial_results: false,
         max_first,
         max_skip,
         cache_status: Default::default(),
@@ -1,4 +1,4 @@
-use graph::data::store::scalar::Timestamp;
+use graph::data::store::scalar::{GeoPoint, Timestamp};
 use graph::prelude::s::{EnumType, InputValue, ScalarType, Type, TypeDefinition};
 use graph::prelude::{q, r, QueryExecutionError};
 use graph::schema;
@@ -57,6 +57,10 @@ impl MaybeCoercible<ScalarType> for q::Value {
                 n.as_i64().ok_or(q::Value::Int(n))?.to_string(),
             )),
             ("Bytes", q::Value::String(s)) => Ok(r::Value::String(s)),
+            ("GeoPoint", q::Value::String(s)) => match GeoPoint::parse(&s) {
+                Ok(_) => Ok(r::Value::String(s)),
+                Err(_) => Err(q::Value::String(s)),
+            },
             ("BigInt", q::Value::String(s)) => Ok(r::Value::String(s)),
             ("BigInt", q::Value::Int(n)) => Ok(r::Value::String(
                 n.as_i64().ok_or(q::Value::Int(n))?.to_string(),
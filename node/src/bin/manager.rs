@@ -5,10 +5,11 @@ use graph::bail;
 use graph::blockchain::BlockHash;
 use graph::cheap_clone::CheapClone;
 use graph::components::adapter::ChainId;
+use graph::components::subgraph::OnExhaustion;
 use graph::endpoint::EndpointMetrics;
 use graph::env::ENV_VARS;
 use graph::log::logger_with_levels;
-use graph::prelude::{MetricsRegistry, BLOCK_NUMBER_MAX};
+use graph::prelude::{BlockNumber, MetricsRegistry, BLOCK_NUMBER_MAX};
 use graph::{data::graphql::load_manager::LoadManager, prelude::chrono, prometheus::Registry};
 use graph::{
     prelude::{
@@ -324,6 +325,120 @@ pub enum Command {
         once: bool,
     },
 
+    /// Show the messages that the mappings of a deployment logged
+    ///
+    /// Logs are only stored if GRAPH_DEPLOYMENT_LOG_RETENTION is set
+    Logs {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// Only show logs that are at least this severe: critical, error,
+        /// warning, info or debug
+        #[clap(long, short)]
+        level: Option<String>,
+        /// Only show logs from this handler
+        #[clap(long)]
+        handler: Option<String>,
+        /// Only show logs from this block on
+        #[clap(long)]
+        from_block: Option<BlockNumber>,
+        /// Only show logs up to and including this block
+        #[clap(long)]
+        to_block: Option<BlockNumber>,
+        /// Show at most this many of the most recent logs
+        #[clap(long, short, default_value = "100")]
+        first: usize,
+    },
+
+    /// Show or change how a deployment retries non-deterministic errors
+    ///
+    /// Without any options, show the current retry policy. Changes take
+    /// effect the next time the deployment is started
+    RetryPolicy {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// Give up after this many retries
+        #[clap(long, conflicts_with = "forever")]
+        max_retries: Option<u32>,
+        /// Retry without a limit
+        #[clap(long)]
+        forever: bool,
+        /// The delay before the first retry in seconds
+        #[clap(long)]
+        initial_delay: Option<u64>,
+        /// The delay doubles with every retry up to this many seconds
+        #[clap(long)]
+        max_delay: Option<u64>,
+        /// Randomize delays by up to this fraction, between 0 and 1
+        #[clap(long)]
+        jitter: Option<f64>,
+        /// What to do once all retries are used up, `fail` or `pause`
+        #[clap(long)]
+        on_exhaustion: Option<OnExhaustion>,
+        /// Remove the retry policy so that the deployment uses the defaults
+        #[clap(
+            long,
+            conflicts_with_all = ["max_retries", "forever", "initial_delay", "max_delay", "jitter", "on_exhaustion"]
+        )]
+        reset: bool,
+    },
+
+    /// Show or reset the Firehose cursor of a deployment
+    ///
+    /// Block streams resume from the cursor after a restart. Resetting it
+    /// makes them start after the block pointer of the deployment instead,
+    /// for example when the provider does not accept the cursor
+    Cursor {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// Remove the cursor; the deployment must be paused or unassigned
+        #[clap(long)]
+        reset: bool,
+    },
+
+    /// Show or change the params of the substreams modules of a deployment
+    ///
+    /// The params set here override the params from the manifest. Without
+    /// any options, show the current params. Changes take effect the next
+    /// time the deployment is started
+    SubstreamsParams {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// Set the params of a module, as `<MODULE>=<PARAMS>`
+        #[clap(long, value_name = "MODULE=PARAMS")]
+        set: Vec<String>,
+        /// Use the params from the manifest for a module again
+        #[clap(long, value_name = "MODULE")]
+        unset: Vec<String>,
+        /// Remove all params so that the deployment uses the params from
+        /// the manifest
+        #[clap(long, conflicts_with_all = ["set", "unset"])]
+        reset: bool,
+    },
+
+    /// Show or retry the offchain files that a deployment gave up on
+    ///
+    /// Deployments give up on files once fetching them failed as often as
+    /// the `fetch` policy of their deployment rule allows, or when the
+    /// files are not acceptable under it. Without any options, show those
+    /// files. Retrying a file takes effect the next time the deployment is
+    /// started
+    OffchainFailures {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// Retry the file with this source, like a CID or a URL
+        #[clap(long, value_name = "SOURCE")]
+        retry: Vec<String>,
+        /// Retry all files
+        #[clap(long, conflicts_with = "retry")]
+        retry_all: bool,
+    },
+
+    /// Manage the API keys for the query port
+    ///
+    /// Keys are only checked when `GRAPH_GRAPHQL_REQUIRE_API_KEY` is set
+    #[clap(subcommand)]
+    ApiKey(ApiKeyCommand),
+
     /// General database management
     #[clap(subcommand)]
     Database(DatabaseCommand),
@@ -440,8 +555,12 @@ pub enum ConfigCommand {
         network: String,
     },
 
-    /// Compare the NetIdentifier of all defined adapters with the existing
-    /// identifiers on the ChainStore.
+    /// Validate all providers and show the outcome for each of them
+    ///
+    /// Compares the NetIdentifier of all defined adapters with the existing
+    /// identifiers on the ChainStore, or the `genesis_hash` of the chain if
+    /// the configuration sets one, and their chain id with the `chain_id`
+    /// that the configuration sets for them. Does not change the store.
     CheckProviders {},
 
     /// Show subgraph-specific settings
@@ -680,6 +799,32 @@ pub enum StatsCommand {
     },
 }
 
+#[derive(Clone, Debug, Subcommand)]
+pub enum ApiKeyCommand {
+    /// Create an API key and print its secret
+    ///
+    /// The secret is only shown once; revoke the key and create a new one
+    /// if it is lost
+    Create {
+        /// The name of the key, used in metrics and to revoke it
+        name: String,
+        /// Only allow querying this deployment (see `help info`). Can be
+        /// given several times; without it, the key can query everything
+        #[clap(long = "deployment", short)]
+        deployments: Vec<DeploymentSearch>,
+        /// The maximum number of requests per minute on each query node
+        #[clap(long)]
+        rate_limit: Option<u32>,
+    },
+    /// List all API keys that have not been revoked
+    List,
+    /// Revoke an API key
+    Revoke {
+        /// The name of the key
+        name: String,
+    },
+}
+
 #[derive(Clone, Debug, Subcommand)]
 pub enum IndexCommand {
     /// Creates a new database index.
@@ -1169,8 +1314,10 @@ async fn main() -> anyhow::Result<()> {
             match cmd {
                 CheckProviders {} => {
                     let store = ctx.store().block_store();
-                    let networks = ctx.networks(store.cheap_clone()).await?;
-                    Ok(commands::config::check_provider_genesis(&networks, store).await)
+                    let logger = ctx.logger.clone();
+                    let registry = ctx.registry.clone();
+                    commands::config::check_provider_genesis(logger, &ctx.config, registry, store)
+                        .await
                 }
                 Place { name, network } => {
                     commands::config::place(&ctx.config.deployment, &name, &network)
@@ -1562,6 +1709,19 @@ async fn main() -> anyhow::Result<()> {
                 }
             }
         }
+        ApiKey(cmd) => {
+            use ApiKeyCommand::*;
+            let primary_pool = ctx.primary_pool();
+            match cmd {
+                Create {
+                    name,
+                    deployments,
+                    rate_limit,
+                } => commands::api_key::create(primary_pool, name, deployments, rate_limit),
+                List => commands::api_key::list(primary_pool),
+                Revoke { name } => commands::api_key::revoke(primary_pool, name),
+            }
+        }
         Database(cmd) => {
             match cmd {
                 DatabaseCommand::Migrate => {
@@ -1600,6 +1760,85 @@ async fn main() -> anyhow::Result<()> {
             )
             .await
         }
+        Logs {
+            deployment,
+            level,
+            handler,
+            from_block,
+            to_block,
+            first,
+        } => {
+            let (store, primary_pool) = ctx.store_and_primary();
+            let filter = commands::logs::Filter {
+                level,
+                handler,
+                from_block,
+                to_block,
+                first,
+            };
+            commands::logs::run(store.subgraph_store(), primary_pool, deployment, filter)
+        }
+        RetryPolicy {
+            deployment,
+            max_retries,
+            forever,
+            initial_delay,
+            max_delay,
+            jitter,
+            on_exhaustion,
+            reset,
+        } => {
+            let (store, primary_pool) = ctx.store_and_primary();
+            let changes = commands::retry_policy::Changes {
+                max_retries,
+                forever,
+                initial_delay_secs: initial_delay,
+                max_delay_secs: max_delay,
+                jitter,
+                on_exhaustion,
+            };
+            commands::retry_policy::run(
+                store.subgraph_store(),
+                primary_pool,
+                deployment,
+                changes,
+                reset,
+            )
+        }
+        SubstreamsParams {
+            deployment,
+            set,
+            unset,
+            reset,
+        } => {
+            let (store, primary_pool) = ctx.store_and_primary();
+            commands::substreams_params::run(
+                store.subgraph_store(),
+                primary_pool,
+                deployment,
+                set,
+                unset,
+                reset,
+            )
+        }
+        OffchainFailures {
+            deployment,
+            retry,
+            retry_all,
+        } => {
+            let (store, primary_pool) = ctx.store_and_primary();
+            commands::offchain_failures::run(
+                store.subgraph_store(),
+                primary_pool,
+                deployment,
+                retry,
+                retry_all,
+            )
+        }
+        Cursor { deployment, reset } => {
+            let (store, primary_pool) = ctx.store_and_primary();
+            commands::cursor::run(store.subgraph_store(), primary_pool, deployment, reset).await
+        }
         Drop {
             deployment,
             current,
@@ -1,7 +1,10 @@
 use graph::{
     anyhow::Error,
-    blockchain::BlockchainKind,
-    components::adapter::ChainId,
+    blockchain::{BlockchainKind, IngestionMode},
+    components::{
+        adapter::ChainId,
+        subgraph::{FetchPolicy, HandlerLimits, RetryPolicy},
+    },
     env::ENV_VARS,
     firehose::{SubgraphLimit, SUBGRAPHS_PER_CONN},
     itertools::Itertools,
@@ -13,7 +16,9 @@ use graph::{
             de::{self, value, SeqAccess, Visitor},
             Deserialize, Deserializer,
         },
-        serde_json, serde_regex, toml, Logger, NodeId, StoreError,
+        serde_json, serde_regex, toml,
+        web3::types::{Address, H256},
+        Logger, NodeId, StoreError,
     },
 };
 use graph_chain_ethereum as ethereum;
@@ -416,9 +421,52 @@ impl ChainSection {
         for (_, chain) in self.chains.iter_mut() {
             chain.validate()?
         }
+
+        // Aliases must refer to exactly one chain and chain ids must be
+        // unique so that manifests resolve to the same chain no matter
+        // which name they use for it
+        let mut names: BTreeMap<&str, &str> = BTreeMap::new();
+        for (name, chain) in &self.chains {
+            for alias in chain.aliases.iter().chain(std::iter::once(name)) {
+                if let Some(other) = names.insert(alias.as_str(), name.as_str()) {
+                    bail!(
+                        "network name `{}` is used by chains `{}` and `{}`",
+                        alias,
+                        other,
+                        name
+                    );
+                }
+            }
+        }
+        let mut chain_ids: BTreeMap<u64, &str> = BTreeMap::new();
+        for (name, chain) in &self.chains {
+            if let Some(chain_id) = chain.chain_id {
+                if let Some(other) = chain_ids.insert(chain_id, name.as_str()) {
+                    bail!(
+                        "chains `{}` and `{}` both have chain_id {}",
+                        other,
+                        name,
+                        chain_id
+                    );
+                }
+            }
+        }
         Ok(())
     }
 
+    /// The aliases of all chains, mapped to the name of their chain
+    pub fn aliases(&self) -> BTreeMap<ChainId, ChainId> {
+        self.chains
+            .iter()
+            .flat_map(|(name, chain)| {
+                chain
+                    .aliases
+                    .iter()
+                    .map(move |alias| (alias.as_str().into(), name.as_str().into()))
+            })
+            .collect()
+    }
+
     fn from_opt(opt: &Opt) -> Result<Self> {
         // If we are not the block ingestor, set the node name
         // to something that is definitely not our node_id
@@ -502,6 +550,7 @@ impl ChainSection {
                         features,
                         headers: Default::default(),
                         rules: vec![],
+                        chain_id: None,
                     }),
                 };
                 let entry = chains.entry(name.to_string()).or_insert_with(|| Chain {
@@ -509,6 +558,14 @@ impl ChainSection {
                     protocol: BlockchainKind::Ethereum,
                     polling_interval: default_polling_interval(),
                     providers: vec![],
+                    multicall: None,
+                    blobs: None,
+                    head: ethereum::HeadBlock::Latest,
+                    genesis_hash: None,
+                    firehose_backfill: false,
+                    chain_id: None,
+                    aliases: vec![],
+                    capabilities: BTreeSet::new(),
                 });
                 entry.providers.push(provider);
             }
@@ -529,8 +586,42 @@ pub struct Chain {
     pub polling_interval: Duration,
     #[serde(rename = "provider")]
     pub providers: Vec<Provider>,
+    /// The address of a Multicall3 contract through which `eth_call`s for
+    /// the same block are batched into one request
+    #[serde(default)]
+    pub multicall: Option<Address>,
+    /// Where to get the contents of EIP-4844 blobs from for
+    /// `ethereum.getBlob`
+    #[serde(default)]
+    pub blobs: Option<ethereum::BlobSource>,
+    /// Which block to treat as the chain head; subgraphs never index past
+    /// it
+    #[serde(default)]
+    pub head: ethereum::HeadBlock,
+    /// The genesis block hash that providers are validated against instead
+    /// of the one in the store, for chains that went through a regenesis
+    #[serde(default)]
+    pub genesis_hash: Option<H256>,
+    /// Ingest the chain head over JSON-RPC even though the chain has
+    /// Firehose providers and only use those to backfill deployments whose
+    /// deployment rule sets `ingestion = "hybrid"`
+    #[serde(default)]
+    pub firehose_backfill: bool,
+    /// The EVM chain id of the chain; providers that do not set their own
+    /// `chain_id` must report it for `eth_chainId`
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+    /// Other network names that subgraph manifests may use for the chain
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// What sets the chain apart from Ethereum mainnet, one of
+    /// `CHAIN_CAPABILITIES`
+    #[serde(default)]
+    pub capabilities: BTreeSet<String>,
 }
 
+const CHAIN_CAPABILITIES: [&str; 2] = ["final_blocks", "no_eth_call_gas"];
+
 fn default_blockchain_kind() -> BlockchainKind {
     BlockchainKind::Ethereum
 }
@@ -573,8 +664,51 @@ impl Chain {
             }
         }
 
+        if !matches!(self.protocol, BlockchainKind::Ethereum)
+            && (self.chain_id.is_some() || !self.capabilities.is_empty())
+        {
+            bail!(
+                "`chain_id` and `capabilities` can only be set for ethereum chains, not for {}",
+                self.protocol
+            );
+        }
+
+        if let Some(capability) = self
+            .capabilities
+            .iter()
+            .find(|capability| !CHAIN_CAPABILITIES.contains(&capability.as_str()))
+        {
+            bail!(
+                "unknown chain capability `{}`, allowed capabilities are {}",
+                capability,
+                CHAIN_CAPABILITIES.join(", ")
+            );
+        }
+
+        if let Some(chain_id) = self.chain_id {
+            for provider in &self.providers {
+                if let ProviderDetails::Web3(web3) = &provider.details {
+                    if web3.chain_id.map_or(false, |id| id != chain_id) {
+                        bail!(
+                            "provider {} has chain_id {} but its chain has chain_id {}",
+                            provider.label,
+                            web3.chain_id.unwrap(),
+                            chain_id
+                        );
+                    }
+                }
+            }
+        }
+
         Ok(())
     }
+
+    pub fn capabilities(&self) -> ethereum::ChainCapabilities {
+        ethereum::ChainCapabilities {
+            final_blocks: self.capabilities.contains("final_blocks"),
+            no_eth_call_gas: self.capabilities.contains("no_eth_call_gas"),
+        }
+    }
 }
 
 fn deserialize_http_headers<'de, D>(deserializer: D) -> Result<HeaderMap, D::Error>
@@ -705,13 +839,17 @@ pub struct Web3Provider {
 
     #[serde(default, rename = "match")]
     rules: Vec<Web3Rule>,
+
+    /// The chain id that the provider must report for `eth_chainId`
+    #[serde(default)]
+    pub chain_id: Option<u64>,
 }
 
 impl Web3Provider {
     pub fn node_capabilities(&self) -> NodeCapabilities {
         NodeCapabilities {
             archive: self.features.contains("archive"),
-            traces: self.features.contains("traces"),
+            traces: self.features.contains("traces") || self.features.contains("debug_traces"),
         }
     }
 
@@ -720,7 +858,7 @@ impl Web3Provider {
     }
 }
 
-const PROVIDER_FEATURES: [&str; 3] = ["traces", "archive", "no_eip1898"];
+const PROVIDER_FEATURES: [&str; 4] = ["traces", "debug_traces", "archive", "no_eip1898"];
 const DEFAULT_PROVIDER_FEATURES: [&str; 2] = ["traces", "archive"];
 
 impl Provider {
@@ -826,6 +964,7 @@ impl<'de> Deserialize<'de> for Provider {
                 let mut features = None;
                 let mut headers = None;
                 let mut nodes = Vec::new();
+                let mut chain_id = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -870,6 +1009,12 @@ impl<'de> Deserialize<'de> for Provider {
                         ProviderField::Match => {
                             nodes = map.next_value()?;
                         }
+                        ProviderField::ChainId => {
+                            if chain_id.is_some() {
+                                return Err(serde::de::Error::duplicate_field("chain_id"));
+                            }
+                            chain_id = Some(map.next_value()?);
+                        }
                     }
                 }
 
@@ -880,8 +1025,9 @@ impl<'de> Deserialize<'de> for Provider {
                             || transport.is_some()
                             || features.is_some()
                             || headers.is_some()
+                            || chain_id.is_some()
                         {
-                            return Err(serde::de::Error::custom("when `details` field is provided, deprecated `url`, `transport`, `features` and `headers` cannot be specified"));
+                            return Err(serde::de::Error::custom("when `details` field is provided, deprecated `url`, `transport`, `features`, `headers` and `chain_id` cannot be specified"));
                         }
 
                         match v {
@@ -901,6 +1047,7 @@ impl<'de> Deserialize<'de> for Provider {
                             .ok_or_else(|| serde::de::Error::missing_field("features"))?,
                         headers: headers.unwrap_or_else(HeaderMap::new),
                         rules: nodes,
+                        chain_id,
                     }),
                 };
 
@@ -915,6 +1062,7 @@ impl<'de> Deserialize<'de> for Provider {
             "url",
             "features",
             "headers",
+            "chain_id",
         ];
         deserializer.deserialize_struct("Provider", FIELDS, ProviderVisitor)
     }
@@ -932,6 +1080,8 @@ enum ProviderField {
     Transport,
     Features,
     Headers,
+    #[serde(rename = "chain_id")]
+    ChainId,
 }
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -1023,8 +1173,48 @@ impl DeploymentPlacer for Deployment {
         };
         Ok(placement)
     }
+
+    fn retry_policy(&self, name: &str, network: &str) -> Option<RetryPolicy> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(name, network))
+            .and_then(|rule| rule.retry.clone())
+    }
+
+    fn handler_limits(&self, name: &str, network: &str) -> Option<HandlerLimits> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(name, network))
+            .and_then(|rule| rule.limits.clone())
+    }
+
+    fn mapping_env(&self, name: &str, network: &str) -> Option<BTreeMap<String, String>> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(name, network))
+            .and_then(|rule| rule.env.clone())
+    }
+
+    fn ingestion(&self, name: &str, network: &str) -> Option<IngestionMode> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(name, network))
+            .and_then(|rule| rule.ingestion)
+    }
+
+    fn fetch_policy(&self, name: &str, network: &str) -> Option<FetchPolicy> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(name, network))
+            .and_then(|rule| rule.fetch.clone())
+    }
 }
 
+/// The most constants a rule may make available to mappings with
+/// `dataSource.env`, and how long each of them may be
+const MAX_MAPPING_ENV_ENTRIES: usize = 64;
+const MAX_MAPPING_ENV_VALUE_LEN: usize = 1024;
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 struct Rule {
     #[serde(rename = "match", default)]
@@ -1037,6 +1227,16 @@ struct Rule {
     )]
     shards: Vec<String>,
     indexers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry: Option<RetryPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limits: Option<HandlerLimits>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    env: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ingestion: Option<IngestionMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fetch: Option<FetchPolicy>,
 }
 
 impl Rule {
@@ -1064,6 +1264,42 @@ impl Rule {
             NodeId::new(indexer).map_err(|()| anyhow!("invalid node id {}", &indexer))?;
         }
         self.shard_names().map_err(Error::from)?;
+        if let Some(retry) = &self.retry {
+            retry
+                .validate()
+                .map_err(|e| anyhow!("invalid retry policy: {}", e))?;
+        }
+        if let Some(limits) = &self.limits {
+            limits
+                .validate()
+                .map_err(|e| anyhow!("invalid handler limits: {}", e))?;
+        }
+        if let Some(fetch) = &self.fetch {
+            fetch
+                .validate()
+                .map_err(|e| anyhow!("invalid fetch policy: {}", e))?;
+        }
+        if let Some(env) = &self.env {
+            if env.len() > MAX_MAPPING_ENV_ENTRIES {
+                return Err(anyhow!(
+                    "a rule can set at most {} env entries but sets {}",
+                    MAX_MAPPING_ENV_ENTRIES,
+                    env.len()
+                ));
+            }
+            for (key, value) in env {
+                if key.is_empty() {
+                    return Err(anyhow!("env keys can not be empty"));
+                }
+                if value.len() > MAX_MAPPING_ENV_VALUE_LEN {
+                    return Err(anyhow!(
+                        "the value of env entry `{}` is longer than {} bytes",
+                        key,
+                        MAX_MAPPING_ENV_VALUE_LEN
+                    ));
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -1213,16 +1449,20 @@ where
 #[cfg(test)]
 mod tests {
 
-    use crate::config::{default_polling_interval, ChainSection, Web3Rule};
+    use crate::config::{default_polling_interval, ChainSection, Deployment, Web3Rule};
 
     use super::{
         Chain, Config, FirehoseProvider, Provider, ProviderDetails, Transport, Web3Provider,
     };
-    use graph::blockchain::BlockchainKind;
+    use graph::blockchain::{BlockchainKind, IngestionMode};
+    use graph::components::adapter::ChainId;
+    use graph::components::subgraph::OnExhaustion;
     use graph::firehose::SubgraphLimit;
     use graph::http::{HeaderMap, HeaderValue};
     use graph::prelude::regex::Regex;
+    use graph::prelude::web3::types::{Address, H256};
     use graph::prelude::{toml, NodeId};
+    use graph_chain_ethereum as ethereum;
     use std::collections::BTreeSet;
     use std::fs::read_to_string;
     use std::path::{Path, PathBuf};
@@ -1259,6 +1499,14 @@ mod tests {
                 protocol: BlockchainKind::Ethereum,
                 polling_interval: default_polling_interval(),
                 providers: vec![],
+                multicall: None,
+                blobs: None,
+                head: ethereum::HeadBlock::Latest,
+                genesis_hash: None,
+                firehose_backfill: false,
+                chain_id: None,
+                aliases: vec![],
+                capabilities: BTreeSet::new(),
             },
             actual
         );
@@ -1281,6 +1529,14 @@ mod tests {
                 protocol: BlockchainKind::Near,
                 polling_interval: default_polling_interval(),
                 providers: vec![],
+                multicall: None,
+                blobs: None,
+                head: ethereum::HeadBlock::Latest,
+                genesis_hash: None,
+                firehose_backfill: false,
+                chain_id: None,
+                aliases: vec![],
+                capabilities: BTreeSet::new(),
             },
             actual
         );
@@ -1307,6 +1563,7 @@ mod tests {
                     features: BTreeSet::new(),
                     headers: HeaderMap::new(),
                     rules: Vec::new(),
+                    chain_id: None,
                 }),
             },
             actual
@@ -1333,12 +1590,36 @@ mod tests {
                     features: BTreeSet::new(),
                     headers: HeaderMap::new(),
                     rules: Vec::new(),
+                    chain_id: None,
                 }),
             },
             actual
         );
     }
 
+    #[test]
+    fn it_works_on_provider_with_debug_traces() {
+        let mut actual = toml::from_str::<Provider>(
+            r#"
+            label = "geth"
+            details = { type = "web3", url = "http://localhost:8545", features = ["archive", "debug_traces"] }
+        "#,
+        )
+        .unwrap();
+        actual.validate().unwrap();
+
+        match actual.details {
+            ProviderDetails::Web3(web3) => assert_eq!(
+                NodeCapabilities {
+                    archive: true,
+                    traces: true,
+                },
+                web3.node_capabilities()
+            ),
+            _ => panic!("expected a web3 provider"),
+        }
+    }
+
     #[test]
     fn it_errors_on_deprecated_provider_missing_url_from_toml() {
         let actual = toml::from_str::<Provider>(
@@ -1440,6 +1721,7 @@ mod tests {
                     features,
                     headers,
                     rules: Vec::new(),
+                    chain_id: None,
                 }),
             },
             actual
@@ -1465,6 +1747,7 @@ mod tests {
                     features: BTreeSet::new(),
                     headers: HeaderMap::new(),
                     rules: Vec::new(),
+                    chain_id: None,
                 }),
             },
             actual
@@ -1834,6 +2117,7 @@ mod tests {
                     features: BTreeSet::new(),
                     headers: HeaderMap::new(),
                     rules: Vec::new(),
+                    chain_id: None,
                 }),
             },
             actual
@@ -1937,4 +2221,358 @@ mod tests {
             actual.chains.get("mainnet").unwrap().polling_interval
         );
     }
+
+    #[test]
+    fn chain_registry() {
+        let mut actual = toml::from_str::<ChainSection>(
+            r#"
+            ingestor = "block_ingestor_node"
+            [arbitrum]
+            shard = "primary"
+            provider = []
+            chain_id = 42161
+            aliases = ["arbitrum-one", "arb1"]
+            [celo]
+            shard = "primary"
+            provider = []
+            chain_id = 42220
+            capabilities = ["final_blocks"]"#,
+        )
+        .unwrap();
+        actual.validate().unwrap();
+
+        let aliases = actual.aliases();
+        assert_eq!(2, aliases.len());
+        assert_eq!("arbitrum", aliases[&ChainId::from("arbitrum-one")].as_str());
+        assert_eq!("arbitrum", aliases[&ChainId::from("arb1")].as_str());
+        assert_eq!(
+            ethereum::ChainCapabilities {
+                final_blocks: true,
+                no_eth_call_gas: false
+            },
+            actual.chains.get("celo").unwrap().capabilities()
+        );
+
+        // An alias can not refer to more than one chain
+        let mut actual = toml::from_str::<ChainSection>(
+            r#"
+            ingestor = "block_ingestor_node"
+            [arbitrum]
+            shard = "primary"
+            provider = []
+            aliases = ["arb"]
+            [arbitrum-nova]
+            shard = "primary"
+            provider = []
+            aliases = ["arb"]"#,
+        )
+        .unwrap();
+        assert!(actual.validate().is_err());
+
+        // Nor can two chains have the same chain id
+        let mut actual = toml::from_str::<ChainSection>(
+            r#"
+            ingestor = "block_ingestor_node"
+            [arbitrum]
+            shard = "primary"
+            provider = []
+            chain_id = 42161
+            [arbitrum-one]
+            shard = "primary"
+            provider = []
+            chain_id = 42161"#,
+        )
+        .unwrap();
+        assert!(actual.validate().is_err());
+
+        let mut actual = toml::from_str::<Chain>(
+            r#"
+            shard = "primary"
+            provider = []
+            capabilities = ["instant"]"#,
+        )
+        .unwrap();
+        assert!(actual.validate().is_err());
+    }
+
+    #[test]
+    fn multicall_address() {
+        let actual = toml::from_str::<ChainSection>(
+            r#"
+            ingestor = "block_ingestor_node"
+            [mainnet]
+            shard = "vip"
+            provider = []
+            multicall = "0xcA11bde05977b3631167028862bE2a173976CA11"
+            [sepolia]
+            shard = "primary"
+            provider = []"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(
+                "ca11bde05977b3631167028862be2a173976ca11"
+                    .parse::<Address>()
+                    .unwrap()
+            ),
+            actual.chains.get("mainnet").unwrap().multicall
+        );
+        assert_eq!(None, actual.chains.get("sepolia").unwrap().multicall);
+
+        assert!(toml::from_str::<Chain>(
+            r#"
+            shard = "primary"
+            provider = []
+            multicall = "not an address""#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn validation_overrides() {
+        let actual = toml::from_str::<Chain>(
+            r#"
+            shard = "primary"
+            genesis_hash = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            provider = [
+              { label = "top", url = "http://localhost:8545", features = [], chain_id = 10 },
+              { label = "details", details = { type = "web3", url = "http://localhost:8545", features = [], chain_id = 11 } },
+              { label = "unset", url = "http://localhost:8545", features = [] }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some(H256::from_low_u64_be(1)), actual.genesis_hash);
+        let chain_ids: Vec<_> = actual
+            .providers
+            .iter()
+            .map(|provider| match &provider.details {
+                ProviderDetails::Web3(web3) => web3.chain_id,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(vec![Some(10), Some(11), None], chain_ids);
+
+        assert!(toml::from_str::<Provider>(
+            r#"
+            label = "both"
+            chain_id = 10
+            details = { type = "web3", url = "http://localhost:8545", features = [] }"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn blob_source() {
+        let actual = toml::from_str::<Chain>(
+            r#"
+            shard = "primary"
+            provider = []
+            blobs = { type = "blobscan", url = "https://api.blobscan.com" }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(ethereum::BlobSource::Blobscan {
+                url: "https://api.blobscan.com".to_string()
+            }),
+            actual.blobs
+        );
+    }
+
+    #[test]
+    fn head_block() {
+        let actual = toml::from_str::<ChainSection>(
+            r#"
+            ingestor = "block_ingestor_node"
+            [mainnet]
+            shard = "primary"
+            provider = []
+            head = "finalized"
+            [sepolia]
+            shard = "primary"
+            provider = []"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ethereum::HeadBlock::Finalized,
+            actual.chains.get("mainnet").unwrap().head
+        );
+        assert_eq!(
+            ethereum::HeadBlock::Latest,
+            actual.chains.get("sepolia").unwrap().head
+        );
+    }
+
+    #[test]
+    fn deployment_rules_with_retry_policy() {
+        use graph_store_postgres::DeploymentPlacer;
+
+        let deployment: Deployment = toml::from_str(
+            r#"
+            [[rule]]
+            match = { network = "mainnet" }
+            indexers = [ "index_node_0" ]
+            retry = { max_retries = 5, on_exhaustion = "pause" }
+            [[rule]]
+            indexers = [ "index_node_1" ]
+        "#,
+        )
+        .unwrap();
+        assert!(deployment.validate().is_ok());
+
+        let policy = deployment.retry_policy("sub/graph", "mainnet").unwrap();
+        assert_eq!(Some(5), policy.max_retries);
+        assert_eq!(OnExhaustion::Pause, policy.on_exhaustion);
+        assert_eq!(None, deployment.retry_policy("sub/graph", "gnosis"));
+
+        let deployment: Deployment = toml::from_str(
+            r#"
+            [[rule]]
+            indexers = [ "index_node_0" ]
+            retry = { initial_delay_secs = 100, max_delay_secs = 10 }
+        "#,
+        )
+        .unwrap();
+        assert!(deployment.validate().is_err());
+    }
+
+    #[test]
+    fn deployment_rules_with_handler_limits() {
+        use graph_store_postgres::DeploymentPlacer;
+
+        let deployment: Deployment = toml::from_str(
+            r#"
+            [[rule]]
+            match = { name = "heavy/.*" }
+            indexers = [ "index_node_0" ]
+            limits = { timeout_secs = 60, fuel = 10000000000, max_memory_bytes = 1073741824 }
+            [[rule]]
+            indexers = [ "index_node_1" ]
+        "#,
+        )
+        .unwrap();
+        assert!(deployment.validate().is_ok());
+
+        let limits = deployment.handler_limits("heavy/graph", "mainnet").unwrap();
+        assert_eq!(Some(60), limits.timeout_secs);
+        assert_eq!(Some(10_000_000_000), limits.fuel);
+        assert_eq!(Some(1 << 30), limits.max_memory_bytes);
+        assert_eq!(None, deployment.handler_limits("sub/graph", "mainnet"));
+
+        let deployment: Deployment = toml::from_str(
+            r#"
+            [[rule]]
+            indexers = [ "index_node_0" ]
+            limits = { fuel = 0 }
+        "#,
+        )
+        .unwrap();
+        assert!(deployment.validate().is_err());
+    }
+
+    #[test]
+    fn deployment_rules_with_mapping_env() {
+        use graph_store_postgres::DeploymentPlacer;
+
+        let deployment: Deployment = toml::from_str(
+            r#"
+            [[rule]]
+            match = { network = "mainnet" }
+            indexers = [ "index_node_0" ]
+            env = { aggregator = "0x5f4ec3df9cbd43714fe2740f5e3616155c5b8419", fees = "on" }
+            [[rule]]
+            indexers = [ "index_node_1" ]
+        "#,
+        )
+        .unwrap();
+        assert!(deployment.validate().is_ok());
+
+        let env = deployment.mapping_env("sub/graph", "mainnet").unwrap();
+        assert_eq!(
+            Some("0x5f4ec3df9cbd43714fe2740f5e3616155c5b8419"),
+            env.get("aggregator").map(String::as_str)
+        );
+        assert_eq!(Some("on"), env.get("fees").map(String::as_str));
+        assert_eq!(None, deployment.mapping_env("sub/graph", "sepolia"));
+
+        let deployment: Deployment = toml::from_str(
+            r#"
+            [[rule]]
+            indexers = [ "index_node_0" ]
+            env = { "" = "empty" }
+        "#,
+        )
+        .unwrap();
+        assert!(deployment.validate().is_err());
+    }
+
+    #[test]
+    fn deployment_rules_with_ingestion() {
+        use graph_store_postgres::DeploymentPlacer;
+
+        let deployment: Deployment = toml::from_str(
+            r#"
+            [[rule]]
+            match = { network = "mainnet" }
+            indexers = [ "index_node_0" ]
+            ingestion = "hybrid"
+            [[rule]]
+            indexers = [ "index_node_1" ]
+        "#,
+        )
+        .unwrap();
+        assert!(deployment.validate().is_ok());
+
+        assert_eq!(
+            Some(IngestionMode::Hybrid),
+            deployment.ingestion("sub/graph", "mainnet")
+        );
+        assert_eq!(None, deployment.ingestion("sub/graph", "sepolia"));
+
+        let res: Result<Deployment, _> = toml::from_str(
+            r#"
+            [[rule]]
+            indexers = [ "index_node_0" ]
+            ingestion = "sideways"
+        "#,
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn deployment_rules_with_fetch_policy() {
+        use graph_store_postgres::DeploymentPlacer;
+
+        let deployment: Deployment = toml::from_str(
+            r#"
+            [[rule]]
+            match = { name = "nft/.*" }
+            indexers = [ "index_node_0" ]
+            fetch = { max_attempts = 20, max_in_flight = 4, content_types = [ "application/json" ] }
+            [[rule]]
+            indexers = [ "index_node_1" ]
+        "#,
+        )
+        .unwrap();
+        assert!(deployment.validate().is_ok());
+
+        let policy = deployment.fetch_policy("nft/graph", "mainnet").unwrap();
+        assert_eq!(Some(20), policy.max_attempts);
+        assert_eq!(Some(4), policy.max_in_flight);
+        assert_eq!(Some(vec!["application/json".to_string()]), policy.content_types);
+        assert_eq!(None, deployment.fetch_policy("sub/graph", "mainnet"));
+
+        let deployment: Deployment = toml::from_str(
+            r#"
+            [[rule]]
+            indexers = [ "index_node_0" ]
+            fetch = { max_in_flight = 0 }
+        "#,
+        )
+        .unwrap();
+        assert!(deployment.validate().is_err());
+    }
 }
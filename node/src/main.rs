@@ -15,7 +15,7 @@ use graph::log::logger;
 use graph::prelude::*;
 use graph::prometheus::Registry;
 use graph::url::Url;
-use graph_core::polling_monitor::{arweave_service, ipfs_service};
+use graph_core::polling_monitor::{arweave_service, http_service, ipfs_service, OffchainCache};
 use graph_core::{
     SubgraphAssignmentProvider as IpfsSubgraphAssignmentProvider, SubgraphInstanceManager,
     SubgraphRegistrar as IpfsSubgraphRegistrar,
@@ -25,11 +25,12 @@ use graph_node::config::Config;
 use graph_node::network_setup::Networks;
 use graph_node::opt;
 use graph_node::store_builder::StoreBuilder;
-use graph_server_http::GraphQLServer as GraphQLQueryServer;
+use graph_server_http::{ApiKeyGuard, GraphQLServer as GraphQLQueryServer};
 use graph_server_index_node::IndexNodeServer;
 use graph_server_json_rpc::JsonRpcServer;
 use graph_server_metrics::PrometheusMetricsServer;
 use graph_server_websocket::SubscriptionServer as GraphQLSubscriptionServer;
+use graph_store_postgres::api_keys::ApiKeyStorage;
 use graph_store_postgres::connection_pool::ConnectionPool;
 use graph_store_postgres::Store;
 use graph_store_postgres::{register_jobs as register_store_jobs, NotificationSender};
@@ -97,6 +98,11 @@ async fn main() {
         render_testament!(TESTAMENT)
     );
 
+    if let Err(e) = graph::components::tracing::init(&logger) {
+        crit!(logger, "Failed to set up OpenTelemetry tracing"; "error" => format!("{:#}", e));
+        std::process::exit(1);
+    }
+
     if !graph_server_index_node::PoiProtection::from_env(&ENV_VARS).is_active() {
         warn!(
             logger,
@@ -201,22 +207,42 @@ async fn main() {
     let logger_factory =
         LoggerFactory::new(logger.clone(), elastic_config, metrics_registry.clone());
 
-    let ipfs_client = graph::ipfs::new_ipfs_client(&opt.ipfs, &logger)
+    let ipfs_client = graph::ipfs::new_ipfs_client(&opt.ipfs, &metrics_registry, &logger)
         .await
         .unwrap_or_else(|err| panic!("Failed to create IPFS client: {err:#}"));
 
+    let pinning_service = graph::ipfs::new_pinning_service(&metrics_registry, &logger)
+        .unwrap_or_else(|err| panic!("Failed to create IPFS pinning service: {err:#}"));
+
+    let offchain_cache = ENV_VARS.mappings.offchain_cache_dir.clone().map(|dir| {
+        Arc::new(
+            OffchainCache::new(
+                logger.clone(),
+                dir,
+                ENV_VARS.mappings.offchain_cache_max_bytes,
+            )
+            .expect("Failed to create offchain file cache"),
+        )
+    });
+
     let ipfs_service = ipfs_service(
         ipfs_client.cheap_clone(),
         ENV_VARS.mappings.max_ipfs_file_bytes,
         ENV_VARS.mappings.ipfs_timeout,
         ENV_VARS.mappings.ipfs_request_limit,
+        offchain_cache.clone(),
+        pinning_service.clone(),
     );
 
     let arweave_resolver = Arc::new(ArweaveClient::new(
         logger.cheap_clone(),
         opt.arweave
-            .parse()
-            .expect("unable to parse arweave gateway address"),
+            .iter()
+            .map(|url| {
+                url.parse()
+                    .expect("unable to parse arweave gateway address")
+            })
+            .collect(),
     ));
 
     let arweave_service = arweave_service(
@@ -226,11 +252,22 @@ async fn main() {
             0 => FileSizeLimit::Unlimited,
             n => FileSizeLimit::MaxBytes(n as u64),
         },
+        env_vars.mappings.ipfs_timeout,
+        offchain_cache.clone(),
+    );
+    let http_service = http_service(
+        env_vars.mappings.file_http_allowed_hosts.clone(),
+        env_vars.mappings.max_ipfs_file_bytes,
+        env_vars.mappings.ipfs_timeout,
+        env_vars.mappings.ipfs_request_limit,
+        offchain_cache,
     );
 
     // Convert the clients into a link resolver. Since we want to get past
     // possible temporary DNS failures, make the resolver retry
-    let link_resolver = Arc::new(IpfsResolver::new(ipfs_client, env_vars.cheap_clone()));
+    let link_resolver = Arc::new(
+        IpfsResolver::new(ipfs_client, env_vars.cheap_clone()).with_pinning(pinning_service),
+    );
     let metrics_server = PrometheusMetricsServer::new(&logger_factory, prometheus_registry.clone());
 
     let endpoint_metrics = Arc::new(EndpointMetrics::new(
@@ -272,6 +309,7 @@ async fn main() {
 
     start_graphman_server(opt.graphman_port, graphman_server_config).await;
 
+    let config_path = opt.config.clone();
     let launch_services = |logger: Logger, env_vars: Arc<EnvVars>| async move {
         let block_store = network_store.block_store();
 
@@ -338,6 +376,46 @@ async fn main() {
 
         let blockchain_map = Arc::new(blockchain_map);
 
+        // Validate providers again while we run
+        graph::spawn(network_adapters.revalidate_providers(env_vars.genesis_revalidation_interval));
+
+        // Keep track of which Firehose endpoints are healthy
+        graph::spawn(network_adapters.check_firehose_health(
+            logger.new(o!("component" => "FirehoseHealthCheck")),
+            env_vars.firehose_health_check_interval,
+        ));
+
+        // Apply changes to providers in the configuration file while we run
+        let reload_interval = env_vars.config_reload_interval;
+        if let Some(path) = config_path {
+            if !reload_interval.is_zero() {
+                graph::spawn(network_adapters.watch_config(
+                    logger.new(o!("component" => "ConfigWatcher")),
+                    path,
+                    config.clone(),
+                    reload_interval,
+                ));
+            }
+        }
+
+        // Cross-check the JSON-RPC providers of each chain against each other
+        for chain in blockchain_map
+            .get_all_by_kind::<graph_chain_ethereum::Chain>(BlockchainKind::Ethereum)
+            .unwrap_or_default()
+        {
+            if let Ok(adapters) = chain.chain_client().rpc() {
+                let monitor = graph_chain_ethereum::DivergenceMonitor::new(
+                    logger.new(
+                        o!("component" => "DivergenceMonitor", "network" => chain.name.to_string()),
+                    ),
+                    chain.name.clone(),
+                    adapters.clone(),
+                    &metrics_registry,
+                );
+                graph::spawn(monitor.run());
+            }
+        }
+
         let shards: Vec<_> = config.stores.keys().cloned().collect();
         let load_manager = Arc::new(LoadManager::new(
             &logger,
@@ -352,9 +430,27 @@ async fn main() {
             load_manager,
             graphql_metrics_registry,
         ));
-        let graphql_server = GraphQLQueryServer::new(&logger_factory, graphql_runner.clone());
-        let subscription_server =
-            GraphQLSubscriptionServer::new(&logger, graphql_runner.clone(), network_store.clone());
+        let mut graphql_server = GraphQLQueryServer::new(&logger_factory, graphql_runner.clone());
+        let api_keys = if ENV_VARS.graphql.require_api_key {
+            let api_keys = ApiKeyGuard::new(
+                logger.clone(),
+                Arc::new(ApiKeyStorage::new(primary_pool.clone())),
+                &metrics_registry,
+            )
+            .expect("failed to load API keys");
+            let api_keys = Arc::new(api_keys);
+            api_keys.refresh_periodically();
+            graphql_server = graphql_server.with_api_keys(api_keys.clone());
+            Some(api_keys)
+        } else {
+            None
+        };
+        let subscription_server = GraphQLSubscriptionServer::new(
+            &logger,
+            graphql_runner.clone(),
+            network_store.clone(),
+            api_keys,
+        );
 
         let index_node_server = IndexNodeServer::new(
             &logger_factory,
@@ -400,6 +496,7 @@ async fn main() {
             link_resolver.clone(),
             ipfs_service,
             arweave_service,
+            http_service,
             static_filters,
         );
 
@@ -0,0 +1,84 @@
+use graph::prelude::{anyhow::anyhow, Error};
+use graph_store_postgres::{api_keys::ApiKeyStorage, connection_pool::ConnectionPool};
+
+use crate::manager::deployment::DeploymentSearch;
+
+pub fn create(
+    primary_pool: ConnectionPool,
+    name: String,
+    deployments: Vec<DeploymentSearch>,
+    rate_limit: Option<u32>,
+) -> Result<(), Error> {
+    if rate_limit == Some(0) {
+        return Err(anyhow!("the rate limit must be at least 1"));
+    }
+    let deployments = if deployments.is_empty() {
+        None
+    } else {
+        let hashes = deployments
+            .iter()
+            .map(|search| search.locate_unique(&primary_pool).map(|loc| loc.hash))
+            .collect::<Result<Vec<_>, _>>()?;
+        Some(hashes)
+    };
+
+    let storage = ApiKeyStorage::new(primary_pool);
+    let secret = storage.create(&name, deployments, rate_limit)?;
+    println!("created API key `{name}`; clients send it in the `X-Api-Key` header");
+    println!("this is the only time the key is shown:");
+    println!("{secret}");
+    Ok(())
+}
+
+pub fn list(primary_pool: ConnectionPool) -> Result<(), Error> {
+    let storage = ApiKeyStorage::new(primary_pool);
+    let keys = storage.list()?;
+    if keys.is_empty() {
+        println!("no API keys");
+        return Ok(());
+    }
+
+    println!(
+        "{:<20} | {:<10} | {:<25} | deployments",
+        "name", "rate limit", "created at"
+    );
+    println!("{:-<20}-+-{:-<10}-+-{:-<25}-+-{:-<11}", "", "", "", "");
+    for info in keys {
+        let rate_limit = info
+            .key
+            .rate_limit
+            .map(|limit| format!("{limit}/min"))
+            .unwrap_or_else(|| "none".to_string());
+        let deployments = info
+            .key
+            .deployments
+            .map(|deployments| {
+                deployments
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_else(|| "all".to_string());
+        println!(
+            "{:<20} | {:<10} | {:<25} | {}",
+            info.key.name,
+            rate_limit,
+            info.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            deployments
+        );
+    }
+    Ok(())
+}
+
+pub fn revoke(primary_pool: ConnectionPool, name: String) -> Result<(), Error> {
+    let storage = ApiKeyStorage::new(primary_pool);
+    if !storage.revoke(&name)? {
+        return Err(anyhow!("there is no API key with name `{name}`"));
+    }
+    println!(
+        "revoked API key `{name}`; query nodes stop accepting it once they reload \
+         keys, see GRAPH_GRAPHQL_API_KEY_REFRESH_INTERVAL"
+    );
+    Ok(())
+}
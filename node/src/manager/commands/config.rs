@@ -2,8 +2,9 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use graph::{
     anyhow::{bail, Context},
+    blockchain::ChainIdentifier,
     components::{
-        adapter::{ChainId, IdentValidator, IdentValidatorError, NoopIdentValidator, ProviderName},
+        adapter::{ChainId, IdentValidator, IdentValidatorError, NoopIdentValidator},
         subgraph::{Setting, Settings},
     },
     endpoint::EndpointMetrics,
@@ -20,34 +21,73 @@ use graph_store_postgres::{BlockStore, DeploymentPlacer};
 
 use crate::{config::Config, network_setup::Networks};
 
-/// Compare the NetIdentifier of all defined adapters with the existing
-/// identifiers on the ChainStore. If a ChainStore doesn't exist it will be show
-/// as an error. It's intended to be run again an environment that has already
-/// been setup by graph-node.
-pub async fn check_provider_genesis(networks: &Networks, store: Arc<BlockStore>) {
+/// Checks identifiers against the store like the block store does, but
+/// never changes the identifier in the store
+struct ReadOnlyValidator(Arc<BlockStore>);
+
+impl IdentValidator for ReadOnlyValidator {
+    fn check_ident(
+        &self,
+        chain_id: &ChainId,
+        ident: &ChainIdentifier,
+    ) -> Result<(), IdentValidatorError> {
+        self.0.check_ident(chain_id, ident)
+    }
+
+    fn update_ident(&self, _chain_id: &ChainId, _ident: &ChainIdentifier) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Validate all providers the way graph-node does, comparing their chain
+/// identifier with the one in the ChainStore, or the genesis hash that the
+/// configuration sets for the chain, and their chain id with the one that the
+/// configuration sets for them, and print the outcome for each provider. If
+/// a ChainStore doesn't exist it will be show as an error. It's intended to
+/// be run again an environment that has already been setup by graph-node.
+pub async fn check_provider_genesis(
+    logger: Logger,
+    config: &Config,
+    registry: Arc<MetricsRegistry>,
+    store: Arc<BlockStore>,
+) -> Result<(), Error> {
+    let networks = Networks::from_config(
+        logger,
+        config,
+        registry,
+        Arc::new(EndpointMetrics::mock()),
+        Arc::new(ReadOnlyValidator(store)),
+        true,
+    )
+    .await?;
+
     println!("Checking providers");
-    for (chain_id, ids) in networks.all_chain_identifiers().await.into_iter() {
-        let (_oks, errs): (Vec<_>, Vec<_>) = ids
-            .into_iter()
-            .map(|(provider, id)| {
-                id.map_err(IdentValidatorError::from)
-                    .and_then(|id| store.check_ident(chain_id, &id))
-                    .map_err(|e| (provider, e))
-            })
-            .partition_result();
-        let errs = errs
-            .into_iter()
-            .dedup_by(|e1, e2| e1.eq(e2))
-            .collect::<Vec<(ProviderName, IdentValidatorError)>>();
-
-        if errs.is_empty() {
-            println!("chain_id: {}: status: OK", chain_id);
-            continue;
+    for (chain_id, providers) in networks.validation_status().await {
+        let ok = providers.iter().all(|provider| provider.status == "valid");
+        println!(
+            "chain_id: {}: status: {}",
+            chain_id,
+            if ok { "OK" } else { "NOK" }
+        );
+        for provider in providers {
+            let quarantined = if provider.quarantined {
+                " (quarantined)"
+            } else {
+                ""
+            };
+            match provider.error {
+                Some(error) => println!(
+                    "  {}: {}{}: {}",
+                    provider.provider, provider.status, quarantined, error
+                ),
+                None => println!(
+                    "  {}: {}{}",
+                    provider.provider, provider.status, quarantined
+                ),
+            }
         }
-
-        println!("chain_id: {}: status: NOK", chain_id);
-        println!("errors: {:?}", errs);
     }
+    Ok(())
 }
 
 pub fn place(placer: &dyn DeploymentPlacer, name: &str, network: &str) -> Result<(), Error> {
@@ -190,6 +230,7 @@ pub async fn provider(
         caps,
         config.node.as_str(),
         adapters
+            .iter()
             .map(|adapter| adapter.provider().to_string())
             .join(", ")
     );
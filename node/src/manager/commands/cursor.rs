@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use graph::prelude::{anyhow::anyhow, Error, SubgraphStore as _};
+use graph_store_postgres::{connection_pool::ConnectionPool, SubgraphStore};
+
+use crate::manager::deployment::DeploymentSearch;
+
+pub async fn run(
+    store: Arc<SubgraphStore>,
+    primary_pool: ConnectionPool,
+    search: DeploymentSearch,
+    reset: bool,
+) -> Result<(), Error> {
+    let deployment = search.locate_unique(&primary_pool)?;
+
+    if reset {
+        // A running deployment keeps its cursor in memory and would write
+        // it back with the next block
+        if let Some((node, false)) = store.assignment_status(&deployment)? {
+            return Err(anyhow!(
+                "deployment {deployment} is running on node {node}; pause or unassign it first"
+            ));
+        }
+        store.reset_firehose_cursor(&deployment)?;
+        println!("reset the Firehose cursor of {deployment}");
+        println!("it will resume streaming after its block pointer");
+        return Ok(());
+    }
+
+    let (head, cursor) = store.head_and_cursor(&deployment).await?;
+    let head = head
+        .map(|ptr| ptr.to_string())
+        .unwrap_or_else(|| "none".to_string());
+    let cursor = match cursor.is_none() {
+        true => "none".to_string(),
+        false => cursor.to_string(),
+    };
+    println!("block:  {head}");
+    println!("cursor: {cursor}");
+    Ok(())
+}
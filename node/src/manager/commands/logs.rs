@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use graph::{
+    components::store::{log_level_name, parse_log_level, DeploymentLogFilter},
+    prelude::{anyhow::anyhow, BlockNumber, Error, SubgraphStore as _},
+};
+use graph_store_postgres::{connection_pool::ConnectionPool, SubgraphStore};
+
+use crate::manager::deployment::DeploymentSearch;
+
+/// Which logs to show, as given on the command line
+pub struct Filter {
+    pub level: Option<String>,
+    pub handler: Option<String>,
+    pub from_block: Option<BlockNumber>,
+    pub to_block: Option<BlockNumber>,
+    pub first: usize,
+}
+
+pub fn run(
+    store: Arc<SubgraphStore>,
+    primary_pool: ConnectionPool,
+    search: DeploymentSearch,
+    filter: Filter,
+) -> Result<(), Error> {
+    let deployment = search.locate_unique(&primary_pool)?;
+    let level = filter
+        .level
+        .map(|level| {
+            parse_log_level(&level).ok_or_else(|| {
+                anyhow!(
+                    "invalid level `{level}`, use one of critical, error, warning, info or debug"
+                )
+            })
+        })
+        .transpose()?;
+
+    let filter = DeploymentLogFilter {
+        level,
+        handler: filter.handler,
+        from_block: filter.from_block,
+        to_block: filter.to_block,
+        first: filter.first,
+    };
+    let mut logs = store.deployment_logs().find(&deployment.hash, &filter)?;
+    if logs.is_empty() {
+        println!("no logs found for {deployment}");
+        return Ok(());
+    }
+
+    // Print the oldest log first, like a log file
+    logs.reverse();
+    for log in logs {
+        println!(
+            "{} {:<8} block {} {}: {}",
+            log.timestamp.to_rfc3339(),
+            log_level_name(log.level),
+            log.block_number,
+            log.handler.as_deref().unwrap_or("-"),
+            log.message
+        );
+    }
+    Ok(())
+}
@@ -1,20 +1,26 @@
+pub mod api_key;
 pub mod assign;
 pub mod chain;
 pub mod check_blocks;
 pub mod config;
 pub mod copy;
 pub mod create;
+pub mod cursor;
 pub mod database;
 pub mod deploy;
 pub mod deployment;
 pub mod drop;
 pub mod index;
 pub mod listen;
+pub mod logs;
+pub mod offchain_failures;
 pub mod prune;
 pub mod query;
 pub mod remove;
+pub mod retry_policy;
 pub mod rewind;
 pub mod run;
 pub mod stats;
+pub mod substreams_params;
 pub mod txn_speed;
 pub mod unused_deployments;
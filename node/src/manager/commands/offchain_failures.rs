@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use graph::prelude::{Error, SubgraphStore as _};
+use graph_store_postgres::{connection_pool::ConnectionPool, SubgraphStore};
+
+use crate::manager::deployment::DeploymentSearch;
+
+pub fn run(
+    store: Arc<SubgraphStore>,
+    primary_pool: ConnectionPool,
+    search: DeploymentSearch,
+    retry: Vec<String>,
+    retry_all: bool,
+) -> Result<(), Error> {
+    let deployment = search.locate_unique(&primary_pool)?;
+
+    if retry.is_empty() && !retry_all {
+        let failures = store.offchain_failures(&deployment)?;
+        if failures.is_empty() {
+            println!("no offchain files were given up on");
+        }
+        for failure in failures {
+            println!("{} {}: {}", failure.kind, failure.source, failure.error);
+        }
+        return Ok(());
+    }
+
+    let count = store.clear_offchain_failures(&deployment, &retry)?;
+    println!(
+        "cleared {count} offchain failures for {deployment}; restart it for this to take effect"
+    );
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use graph::{
+    components::subgraph::{OnExhaustion, RetryPolicy},
+    prelude::{anyhow::anyhow, Error, SubgraphStore as _},
+};
+use graph_store_postgres::{connection_pool::ConnectionPool, SubgraphStore};
+
+use crate::manager::deployment::DeploymentSearch;
+
+/// Changes to the retry policy of a deployment; fields that are `None`
+/// are left unchanged
+pub struct Changes {
+    pub max_retries: Option<u32>,
+    pub forever: bool,
+    pub initial_delay_secs: Option<u64>,
+    pub max_delay_secs: Option<u64>,
+    pub jitter: Option<f64>,
+    pub on_exhaustion: Option<OnExhaustion>,
+}
+
+impl Changes {
+    fn is_empty(&self) -> bool {
+        self.max_retries.is_none()
+            && !self.forever
+            && self.initial_delay_secs.is_none()
+            && self.max_delay_secs.is_none()
+            && self.jitter.is_none()
+            && self.on_exhaustion.is_none()
+    }
+
+    fn apply(self, mut policy: RetryPolicy) -> RetryPolicy {
+        if self.forever {
+            policy.max_retries = None;
+        }
+        if let Some(max_retries) = self.max_retries {
+            policy.max_retries = Some(max_retries);
+        }
+        if let Some(initial_delay_secs) = self.initial_delay_secs {
+            policy.initial_delay_secs = initial_delay_secs;
+        }
+        if let Some(max_delay_secs) = self.max_delay_secs {
+            policy.max_delay_secs = max_delay_secs;
+        }
+        if let Some(jitter) = self.jitter {
+            policy.jitter = jitter;
+        }
+        if let Some(on_exhaustion) = self.on_exhaustion {
+            policy.on_exhaustion = on_exhaustion;
+        }
+        policy
+    }
+}
+
+fn show(policy: &RetryPolicy, is_default: bool) {
+    let max_retries = policy
+        .max_retries
+        .map(|max| max.to_string())
+        .unwrap_or_else(|| "forever".to_string());
+    if is_default {
+        println!("no retry policy set, using the defaults");
+    }
+    println!("max retries:   {max_retries}");
+    println!("initial delay: {}s", policy.initial_delay_secs);
+    println!("max delay:     {}s", policy.max_delay_secs);
+    println!("jitter:        {}", policy.jitter);
+    println!("on exhaustion: {}", policy.on_exhaustion.as_str());
+}
+
+pub fn run(
+    store: Arc<SubgraphStore>,
+    primary_pool: ConnectionPool,
+    search: DeploymentSearch,
+    changes: Changes,
+    reset: bool,
+) -> Result<(), Error> {
+    let deployment = search.locate_unique(&primary_pool)?;
+    let current = store.retry_policy(&deployment)?;
+
+    if reset {
+        store.set_retry_policy(&deployment, None)?;
+        println!("reset retry policy for {deployment}; restart it for this to take effect");
+        show(&RetryPolicy::default(), true);
+        return Ok(());
+    }
+
+    if changes.is_empty() {
+        let is_default = current.is_none();
+        show(&current.unwrap_or_default(), is_default);
+        return Ok(());
+    }
+
+    let policy = changes.apply(current.unwrap_or_default());
+    policy.validate().map_err(|e| anyhow!("{e}"))?;
+    store.set_retry_policy(&deployment, Some(policy.clone()))?;
+    println!("updated retry policy for {deployment}; restart it for this to take effect");
+    show(&policy, false);
+    Ok(())
+}
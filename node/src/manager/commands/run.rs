@@ -21,7 +21,7 @@ use graph::prelude::{
     SubgraphStore, SubgraphVersionSwitchingMode, ENV_VARS,
 };
 use graph::slog::{debug, info, Logger};
-use graph_core::polling_monitor::{arweave_service, ipfs_service};
+use graph_core::polling_monitor::{arweave_service, http_service, ipfs_service, OffchainCache};
 use graph_core::{
     SubgraphAssignmentProvider as IpfsSubgraphAssignmentProvider, SubgraphInstanceManager,
     SubgraphRegistrar as IpfsSubgraphRegistrar,
@@ -58,18 +58,32 @@ pub async fn run(
     let logger_factory = LoggerFactory::new(logger.clone(), None, metrics_ctx.registry.clone());
 
     // FIXME: Hard-coded IPFS config, take it from config file instead?
-    let ipfs_client = graph::ipfs::new_ipfs_client(&ipfs_url, &logger).await?;
+    let ipfs_client = graph::ipfs::new_ipfs_client(&ipfs_url, &metrics_registry, &logger).await?;
+
+    let pinning_service = graph::ipfs::new_pinning_service(&metrics_registry, &logger)?;
+
+    let offchain_cache = env_vars
+        .mappings
+        .offchain_cache_dir
+        .clone()
+        .map(|dir| {
+            OffchainCache::new(logger.clone(), dir, env_vars.mappings.offchain_cache_max_bytes)
+                .map(Arc::new)
+        })
+        .transpose()?;
 
     let ipfs_service = ipfs_service(
         ipfs_client.cheap_clone(),
         env_vars.mappings.max_ipfs_file_bytes,
         env_vars.mappings.ipfs_timeout,
         env_vars.mappings.ipfs_request_limit,
+        offchain_cache.clone(),
+        pinning_service.clone(),
     );
 
     let arweave_resolver = Arc::new(ArweaveClient::new(
         logger.cheap_clone(),
-        arweave_url.parse().expect("invalid arweave url"),
+        vec![arweave_url.parse().expect("invalid arweave url")],
     ));
     let arweave_service = arweave_service(
         arweave_resolver.cheap_clone(),
@@ -78,6 +92,15 @@ pub async fn run(
             0 => FileSizeLimit::Unlimited,
             n => FileSizeLimit::MaxBytes(n as u64),
         },
+        env_vars.mappings.ipfs_timeout,
+        offchain_cache.clone(),
+    );
+    let http_service = http_service(
+        env_vars.mappings.file_http_allowed_hosts.clone(),
+        env_vars.mappings.max_ipfs_file_bytes,
+        env_vars.mappings.ipfs_timeout,
+        env_vars.mappings.ipfs_request_limit,
+        offchain_cache,
     );
 
     let endpoint_metrics = Arc::new(EndpointMetrics::new(
@@ -88,7 +111,9 @@ pub async fn run(
 
     // Convert the clients into a link resolver. Since we want to get past
     // possible temporary DNS failures, make the resolver retry
-    let link_resolver = Arc::new(IpfsResolver::new(ipfs_client, env_vars.cheap_clone()));
+    let link_resolver = Arc::new(
+        IpfsResolver::new(ipfs_client, env_vars.cheap_clone()).with_pinning(pinning_service),
+    );
 
     let chain_head_update_listener = store_builder.chain_head_update_listener();
     let network_store = store_builder.network_store(config.chain_ids());
@@ -135,6 +160,7 @@ pub async fn run(
         link_resolver.cheap_clone(),
         ipfs_service,
         arweave_service,
+        http_service,
         static_filters,
     );
 
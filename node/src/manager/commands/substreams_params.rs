@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use graph::prelude::{anyhow::anyhow, Error, SubgraphStore as _};
+use graph_store_postgres::{connection_pool::ConnectionPool, SubgraphStore};
+
+use crate::manager::deployment::DeploymentSearch;
+
+fn show(params: &BTreeMap<String, String>) {
+    if params.is_empty() {
+        println!("no params set, using the params from the manifest");
+    }
+    for (module, params) in params {
+        println!("{module}: {params}");
+    }
+}
+
+pub fn run(
+    store: Arc<SubgraphStore>,
+    primary_pool: ConnectionPool,
+    search: DeploymentSearch,
+    set: Vec<String>,
+    unset: Vec<String>,
+    reset: bool,
+) -> Result<(), Error> {
+    let deployment = search.locate_unique(&primary_pool)?;
+
+    if reset {
+        store.set_substreams_params(&deployment, None)?;
+        println!("reset substreams params for {deployment}; restart it for this to take effect");
+        return Ok(());
+    }
+
+    let mut params = store.substreams_params(&deployment)?.unwrap_or_default();
+    if set.is_empty() && unset.is_empty() {
+        show(&params);
+        return Ok(());
+    }
+
+    for module in unset {
+        params.remove(&module);
+    }
+    for setting in set {
+        let (module, value) = setting
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected `<MODULE>=<PARAMS>` but got `{setting}`"))?;
+        params.insert(module.to_string(), value.to_string());
+    }
+
+    let params = (!params.is_empty()).then_some(params);
+    store.set_substreams_params(&deployment, params.clone())?;
+    println!("updated substreams params for {deployment}; restart it for this to take effect");
+    show(&params.unwrap_or_default());
+    Ok(())
+}
@@ -1,39 +1,49 @@
 use ethereum::{
     network::{EthereumNetworkAdapter, EthereumNetworkAdapters},
-    BlockIngestor,
+    BlobSource, BlockIngestor, ChainCapabilities, HeadBlock, ProviderEthRpcMetrics,
 };
 use graph::{
     anyhow::{self, bail},
-    blockchain::{Blockchain, BlockchainKind, BlockchainMap, ChainIdentifier},
+    blockchain::{BlockHash, Blockchain, BlockchainKind, BlockchainMap, ChainIdentifier},
     cheap_clone::CheapClone,
     components::{
         adapter::{
-            ChainId, IdentValidator, NetIdentifiable, NoopIdentValidator, ProviderManager,
-            ProviderName,
+            ChainId, GenesisOverrideValidator, IdentValidator, NetIdentifiable, NoopIdentValidator,
+            ProviderManager, ProviderName, ProviderValidation,
         },
         metrics::MetricsRegistry,
     },
     endpoint::EndpointMetrics,
     env::{EnvVars, ENV_VARS},
     firehose::{FirehoseEndpoint, FirehoseEndpoints},
-    futures03::future::TryFutureExt,
+    futures03::future::{join_all, TryFutureExt},
     itertools::Itertools,
     log::factory::LoggerFactory,
     prelude::{
         anyhow::{anyhow, Result},
-        info, Logger, NodeId,
+        error, info, Logger, NodeId,
     },
     slog::{o, warn, Discard},
+    tokio,
 };
 use graph_chain_ethereum as ethereum;
+use graph_chain_starknet::StarknetRpcClients;
 use graph_store_postgres::{BlockStore, ChainHeadUpdateListener};
 
-use std::{any::Any, cmp::Ordering, sync::Arc, time::Duration};
+use std::{
+    any::Any,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
 
 use crate::chain::{
-    create_all_ethereum_networks, create_firehose_networks, create_substreams_networks,
-    networks_as_chains,
+    create_all_ethereum_networks, create_firehose_networks, create_starknet_rpc_networks,
+    create_substreams_networks, networks_as_chains,
 };
+use crate::config::{Chain, Config, ProviderDetails};
 
 #[derive(Debug, Clone)]
 pub struct EthAdapterConfig {
@@ -43,6 +53,13 @@ pub struct EthAdapterConfig {
     // polling interval is set per chain so if set all adapter configuration will have
     // the same value.
     pub polling_interval: Option<Duration>,
+    // Like the polling interval, the blob source is set per chain
+    pub blob_source: Option<BlobSource>,
+    pub head: HeadBlock,
+    // Whether the Firehose providers of the chain only backfill hybrid
+    // deployments
+    pub firehose_backfill: bool,
+    pub capabilities: ChainCapabilities,
 }
 
 #[derive(Debug, Clone)]
@@ -52,11 +69,20 @@ pub struct FirehoseAdapterConfig {
     pub adapters: Vec<Arc<FirehoseEndpoint>>,
 }
 
+/// The JSON-RPC providers of a Starknet chain
+#[derive(Debug, Clone)]
+pub struct StarknetRpcAdapterConfig {
+    pub chain_id: ChainId,
+    pub clients: StarknetRpcClients,
+    pub polling_interval: Duration,
+}
+
 #[derive(Debug, Clone)]
 pub enum AdapterConfiguration {
     Rpc(EthAdapterConfig),
     Firehose(FirehoseAdapterConfig),
     Substreams(FirehoseAdapterConfig),
+    StarknetRpc(StarknetRpcAdapterConfig),
 }
 
 impl AdapterConfiguration {
@@ -64,13 +90,17 @@ impl AdapterConfiguration {
         match self {
             AdapterConfiguration::Rpc(_) => &BlockchainKind::Ethereum,
             AdapterConfiguration::Firehose(fh) | AdapterConfiguration::Substreams(fh) => &fh.kind,
+            AdapterConfiguration::StarknetRpc(_) => &BlockchainKind::Starknet,
         }
     }
     pub fn chain_id(&self) -> &ChainId {
         match self {
             AdapterConfiguration::Rpc(EthAdapterConfig { chain_id, .. })
             | AdapterConfiguration::Firehose(FirehoseAdapterConfig { chain_id, .. })
-            | AdapterConfiguration::Substreams(FirehoseAdapterConfig { chain_id, .. }) => chain_id,
+            | AdapterConfiguration::Substreams(FirehoseAdapterConfig { chain_id, .. })
+            | AdapterConfiguration::StarknetRpc(StarknetRpcAdapterConfig { chain_id, .. }) => {
+                chain_id
+            }
         }
     }
 
@@ -102,13 +132,29 @@ impl AdapterConfiguration {
     pub fn is_substreams(&self) -> bool {
         self.as_substreams().is_none()
     }
+
+    pub fn as_starknet_rpc(&self) -> Option<&StarknetRpcAdapterConfig> {
+        match self {
+            AdapterConfiguration::StarknetRpc(rpc) => Some(rpc),
+            _ => None,
+        }
+    }
 }
 
 pub struct Networks {
+    /// The adapters that the chains were created with; later updates of
+    /// the providers do not change them
     pub adapters: Vec<AdapterConfiguration>,
     rpc_provider_manager: ProviderManager<EthereumNetworkAdapter>,
+    call_only_provider_manager: ProviderManager<EthereumNetworkAdapter>,
     firehose_provider_manager: ProviderManager<Arc<FirehoseEndpoint>>,
     substreams_provider_manager: ProviderManager<Arc<FirehoseEndpoint>>,
+    eth_rpc_metrics: Arc<ProviderEthRpcMetrics>,
+    endpoint_metrics: Arc<EndpointMetrics>,
+    genesis_validation_enabled: bool,
+    /// Other names under which the chains are known, mapped to the name
+    /// of the chain
+    aliases: BTreeMap<ChainId, ChainId>,
 }
 
 impl Networks {
@@ -116,11 +162,17 @@ impl Networks {
     fn noop() -> Self {
         Self {
             adapters: vec![],
+            aliases: BTreeMap::new(),
             rpc_provider_manager: ProviderManager::new(
                 Logger::root(Discard, o!()),
                 vec![].into_iter(),
                 Arc::new(NoopIdentValidator),
             ),
+            call_only_provider_manager: ProviderManager::new(
+                Logger::root(Discard, o!()),
+                vec![].into_iter(),
+                Arc::new(NoopIdentValidator),
+            ),
             firehose_provider_manager: ProviderManager::new(
                 Logger::root(Discard, o!()),
                 vec![].into_iter(),
@@ -131,6 +183,11 @@ impl Networks {
                 vec![].into_iter(),
                 Arc::new(NoopIdentValidator),
             ),
+            eth_rpc_metrics: Arc::new(ProviderEthRpcMetrics::new(
+                Arc::new(MetricsRegistry::mock()),
+            )),
+            endpoint_metrics: Arc::new(EndpointMetrics::mock()),
+            genesis_validation_enabled: false,
         }
     }
 
@@ -150,13 +207,21 @@ impl Networks {
         let mut out = vec![];
         for chain_id in self.adapters.iter().map(|a| a.chain_id()).sorted().dedup() {
             let mut inner = vec![];
-            for adapter in self.rpc_provider_manager.get_all_unverified(chain_id) {
+            for adapter in self
+                .rpc_provider_manager
+                .get_all_unverified(chain_id)
+                .iter()
+            {
                 inner.push((
                     adapter.provider_name(),
                     adapter.net_identifiers_with_timeout(timeout).await,
                 ));
             }
-            for adapter in self.firehose_provider_manager.get_all_unverified(chain_id) {
+            for adapter in self
+                .firehose_provider_manager
+                .get_all_unverified(chain_id)
+                .iter()
+            {
                 inner.push((
                     adapter.provider_name(),
                     adapter.net_identifiers_with_timeout(timeout).await,
@@ -165,6 +230,7 @@ impl Networks {
             for adapter in self
                 .substreams_provider_manager
                 .get_all_unverified(chain_id)
+                .iter()
             {
                 inner.push((
                     adapter.provider_name(),
@@ -178,6 +244,96 @@ impl Networks {
         out
     }
 
+    /// The outcome of validating each provider of every chain. Providers
+    /// that have not been checked yet are validated first
+    pub async fn validation_status(&self) -> Vec<(&ChainId, Vec<ProviderValidation>)> {
+        let mut out = vec![];
+        for chain_id in self.adapters.iter().map(|a| a.chain_id()).sorted().dedup() {
+            // Only done for the validation; errors show up in the status
+            let _ = self.rpc_provider_manager.get_all(chain_id).await;
+            let _ = self.firehose_provider_manager.get_all(chain_id).await;
+            let _ = self.substreams_provider_manager.get_all(chain_id).await;
+
+            let mut inner = self.rpc_provider_manager.validation_status(chain_id).await;
+            inner.extend(
+                self.firehose_provider_manager
+                    .validation_status(chain_id)
+                    .await,
+            );
+            inner.extend(
+                self.substreams_provider_manager
+                    .validation_status(chain_id)
+                    .await,
+            );
+            out.push((chain_id, inner));
+        }
+        out
+    }
+
+    /// Validate the providers of all chains again every `interval` so that
+    /// a provider that starts serving a different chain is not used anymore.
+    /// Does nothing if genesis validation is disabled
+    pub fn revalidate_providers(
+        &self,
+        interval: Duration,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let enabled = self.genesis_validation_enabled && !interval.is_zero();
+        let rpc = self.rpc_provider_manager.cheap_clone();
+        let firehose = self.firehose_provider_manager.cheap_clone();
+        let substreams = self.substreams_provider_manager.cheap_clone();
+        async move {
+            if !enabled {
+                return;
+            }
+            loop {
+                tokio::time::sleep(interval).await;
+                rpc.revalidate().await;
+                firehose.revalidate().await;
+                substreams.revalidate().await;
+            }
+        }
+    }
+
+    /// Periodically check that the Firehose and Substreams endpoints of all
+    /// chains respond so that block streams avoid endpoints that are down
+    pub fn check_firehose_health(
+        &self,
+        logger: Logger,
+        interval: Duration,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let chain_ids: Vec<ChainId> = self
+            .adapters
+            .iter()
+            .map(|a| a.chain_id().clone())
+            .sorted()
+            .dedup()
+            .collect();
+        let firehose = self.firehose_provider_manager.cheap_clone();
+        let substreams = self.substreams_provider_manager.cheap_clone();
+        async move {
+            if interval.is_zero() {
+                return;
+            }
+            loop {
+                tokio::time::sleep(interval).await;
+                for chain_id in &chain_ids {
+                    let endpoints = firehose
+                        .get_all_including_quarantined(chain_id)
+                        .iter()
+                        .chain(substreams.get_all_including_quarantined(chain_id).iter())
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    join_all(
+                        endpoints
+                            .iter()
+                            .map(|endpoint| endpoint.check_health(&logger)),
+                    )
+                    .await;
+                }
+            }
+        }
+    }
+
     pub async fn chain_identifier(
         &self,
         logger: &Logger,
@@ -189,7 +345,7 @@ impl Networks {
             chain_id: &ChainId,
             provider_type: &str,
         ) -> Result<ChainIdentifier> {
-            for adapter in pm.get_all_unverified(chain_id) {
+            for adapter in pm.get_all_unverified(chain_id).iter() {
                 match adapter.net_identifiers().await {
                     Ok(ident) => return Ok(ident),
                     Err(err) => {
@@ -235,7 +391,7 @@ impl Networks {
 
     pub async fn from_config(
         logger: Logger,
-        config: &crate::config::Config,
+        config: &Config,
         registry: Arc<MetricsRegistry>,
         endpoint_metrics: Arc<EndpointMetrics>,
         store: Arc<dyn IdentValidator>,
@@ -245,9 +401,30 @@ impl Networks {
             return Ok(Networks::noop());
         }
 
+        let genesis_hashes: HashMap<ChainId, BlockHash> = config
+            .chains
+            .chains
+            .iter()
+            .filter_map(|(name, chain)| {
+                chain
+                    .genesis_hash
+                    .map(|hash| (name.as_str().into(), BlockHash::from(hash)))
+            })
+            .collect();
+        let store: Arc<dyn IdentValidator> = if genesis_hashes.is_empty() {
+            store
+        } else {
+            Arc::new(GenesisOverrideValidator::new(
+                logger.cheap_clone(),
+                store,
+                genesis_hashes,
+            ))
+        };
+
+        let eth_rpc_metrics = Arc::new(ProviderEthRpcMetrics::new(registry));
         let eth = create_all_ethereum_networks(
             logger.cheap_clone(),
-            registry,
+            eth_rpc_metrics.cheap_clone(),
             &config,
             endpoint_metrics.cheap_clone(),
         )
@@ -257,20 +434,29 @@ impl Networks {
             &config,
             endpoint_metrics.cheap_clone(),
         );
-        let substreams =
-            create_substreams_networks(logger.cheap_clone(), &config, endpoint_metrics);
+        let substreams = create_substreams_networks(
+            logger.cheap_clone(),
+            &config,
+            endpoint_metrics.cheap_clone(),
+        );
+        let starknet_rpc = create_starknet_rpc_networks(&logger, &config)?;
         let adapters: Vec<_> = eth
             .into_iter()
             .chain(firehose.into_iter())
             .chain(substreams.into_iter())
+            .chain(starknet_rpc.into_iter())
             .collect();
 
-        Ok(Networks::new(
+        let mut networks = Networks::new(
             &logger,
             adapters,
             store,
             genesis_validation_enabled,
-        ))
+            eth_rpc_metrics,
+            endpoint_metrics,
+        );
+        networks.aliases = config.chains.aliases();
+        Ok(networks)
     }
 
     fn new(
@@ -278,6 +464,8 @@ impl Networks {
         adapters: Vec<AdapterConfiguration>,
         validator: Arc<dyn IdentValidator>,
         genesis_validation_enabled: bool,
+        eth_rpc_metrics: Arc<ProviderEthRpcMetrics>,
+        endpoint_metrics: Arc<EndpointMetrics>,
     ) -> Self {
         let adapters2 = adapters.clone();
         let eth_adapters = adapters.iter().flat_map(|a| a.as_rpc()).cloned().map(
@@ -286,6 +474,10 @@ impl Networks {
                  mut adapters,
                  call_only: _,
                  polling_interval: _,
+                 blob_source: _,
+                 head: _,
+                 firehose_backfill: _,
+                 capabilities: _,
              }| {
                 adapters.sort_by(|a, b| {
                     a.capabilities
@@ -297,6 +489,12 @@ impl Networks {
             },
         );
 
+        let call_only_adapters = adapters
+            .iter()
+            .flat_map(|a| a.as_rpc())
+            .map(|eth| (eth.chain_id.clone(), eth.call_only.clone()))
+            .collect_vec();
+
         let firehose_adapters = adapters
             .iter()
             .flat_map(|a| a.as_firehose())
@@ -325,11 +523,17 @@ impl Networks {
 
         let s = Self {
             adapters: adapters2,
+            aliases: BTreeMap::new(),
             rpc_provider_manager: ProviderManager::new(
                 logger.clone(),
                 eth_adapters,
                 validator.cheap_clone(),
             ),
+            call_only_provider_manager: ProviderManager::new(
+                logger.clone(),
+                call_only_adapters.into_iter(),
+                validator.cheap_clone(),
+            ),
             firehose_provider_manager: ProviderManager::new(
                 logger.clone(),
                 firehose_adapters
@@ -344,6 +548,9 @@ impl Networks {
                     .map(|(chain_id, endpoints)| (chain_id, endpoints)),
                 validator.cheap_clone(),
             ),
+            eth_rpc_metrics,
+            endpoint_metrics,
+            genesis_validation_enabled,
         };
 
         if !genesis_validation_enabled {
@@ -416,6 +623,13 @@ impl Networks {
                     block_ingestor::<graph_chain_starknet::Chain>(logger, id, chain, &mut res)
                         .await?
                 }
+                BlockchainKind::Solana => {
+                    block_ingestor::<graph_chain_solana::Chain>(logger, id, chain, &mut res).await?
+                }
+                BlockchainKind::Bitcoin => {
+                    block_ingestor::<graph_chain_bitcoin::Chain>(logger, id, chain, &mut res)
+                        .await?
+                }
             }
         }
 
@@ -458,6 +672,10 @@ impl Networks {
         )
         .await;
 
+        for (alias, network) in &self.aliases {
+            bm.insert_alias(alias.clone(), network.clone());
+        }
+
         bm
     }
 
@@ -470,19 +688,255 @@ impl Networks {
     }
 
     pub fn ethereum_rpcs(&self, chain_id: ChainId) -> EthereumNetworkAdapters {
-        let eth_adapters = self
-            .adapters
-            .iter()
-            .filter(|a| a.chain_id().eq(&chain_id))
-            .flat_map(|a| a.as_rpc())
-            .flat_map(|eth_c| eth_c.call_only.clone())
-            .collect_vec();
-
         EthereumNetworkAdapters::new(
             chain_id,
             self.rpc_provider_manager.cheap_clone(),
-            eth_adapters,
+            self.call_only_provider_manager.cheap_clone(),
             None,
         )
     }
+
+    /// Apply the changes to the providers of chains between `previous`,
+    /// the configuration that these networks were created from or last
+    /// updated to, and `config`. Chains that exist already use the new
+    /// providers right away; providers that did not change keep their
+    /// connections. Adding or removing chains, changing which kinds of
+    /// providers a chain has and any other changes to the configuration
+    /// only take effect after a restart.
+    ///
+    /// If creating any of the new providers fails, none of the changes
+    /// are applied
+    pub async fn update_providers(
+        &self,
+        logger: &Logger,
+        previous: &Config,
+        config: &Config,
+    ) -> Result<()> {
+        // The kinds of providers of a chain, without call-only providers,
+        // which decide what kind of client the chain uses
+        fn provider_kinds(chain: &Chain) -> (bool, bool, bool) {
+            let has =
+                |f: fn(&ProviderDetails) -> bool| chain.providers.iter().any(|p| f(&p.details));
+            (
+                has(|details| matches!(details, ProviderDetails::Web3(_))),
+                has(|details| matches!(details, ProviderDetails::Firehose(_))),
+                has(|details| matches!(details, ProviderDetails::Substreams(_))),
+            )
+        }
+
+        // Put the adapters of unchanged providers and the `created`
+        // adapters for changed providers in the order of the providers in
+        // `chain`
+        fn updated<T: NetIdentifiable + Clone + 'static>(
+            manager: &ProviderManager<T>,
+            chain_id: &ChainId,
+            chain: &Chain,
+            unchanged: &[ProviderName],
+            created: Vec<T>,
+        ) -> Vec<T> {
+            let mut adapters = manager
+                .get_all_including_quarantined(chain_id)
+                .iter()
+                .filter(|adapter| unchanged.contains(&adapter.provider_name()))
+                .cloned()
+                .chain(created)
+                .collect_vec();
+            adapters.sort_by_key(|adapter| {
+                let name = adapter.provider_name();
+                chain
+                    .providers
+                    .iter()
+                    .position(|p| p.label == name.as_str())
+            });
+            adapters
+        }
+
+        // For each chain that changed, the chain with only the providers
+        // that changed, and the labels of the providers that did not
+        let mut changes: BTreeMap<String, (Chain, Vec<ProviderName>)> = BTreeMap::new();
+        for (name, chain) in &config.chains.chains {
+            let Some(current) = previous.chains.chains.get(name) else {
+                warn!(logger, "Ignoring new chain, adding a chain requires a restart";
+                      "network" => name);
+                continue;
+            };
+            if current.providers == chain.providers {
+                continue;
+            }
+            if current.protocol != chain.protocol
+                || provider_kinds(current) != provider_kinds(chain)
+            {
+                warn!(logger, "Ignoring changes to the providers of chain, changing its protocol \
+                               or the kinds of providers it has requires a restart";
+                      "network" => name);
+                continue;
+            }
+
+            let unchanged = chain
+                .providers
+                .iter()
+                .filter(|provider| current.providers.contains(provider))
+                .map(|provider| ProviderName::from(provider.label.as_str()))
+                .collect_vec();
+            let mut chain = chain.clone();
+            chain
+                .providers
+                .retain(|provider| !unchanged.contains(&provider.label.as_str().into()));
+            changes.insert(name.clone(), (chain, unchanged));
+        }
+        for name in previous.chains.chains.keys() {
+            if !config.chains.chains.contains_key(name) {
+                warn!(logger, "Ignoring removed chain, removing a chain requires a restart";
+                      "network" => name);
+            }
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut changed = config.clone();
+        changed.chains.chains = changes
+            .iter()
+            .map(|(name, (chain, _))| (name.clone(), chain.clone()))
+            .collect();
+        let created: Vec<AdapterConfiguration> = create_all_ethereum_networks(
+            logger.cheap_clone(),
+            self.eth_rpc_metrics.cheap_clone(),
+            &changed,
+            self.endpoint_metrics.cheap_clone(),
+        )
+        .await?
+        .into_iter()
+        .chain(create_firehose_networks(
+            logger.cheap_clone(),
+            &changed,
+            self.endpoint_metrics.cheap_clone(),
+        ))
+        .chain(create_substreams_networks(
+            logger.cheap_clone(),
+            &changed,
+            self.endpoint_metrics.cheap_clone(),
+        ))
+        .collect();
+
+        for (name, (_, unchanged)) in changes {
+            let chain_id: ChainId = name.as_str().into();
+            let chain = &config.chains.chains[&name];
+            let created = created.iter().filter(|a| a.chain_id() == &chain_id);
+
+            let rpc = created.clone().flat_map(|a| a.as_rpc());
+            let mut adapters = updated(
+                &self.rpc_provider_manager,
+                &chain_id,
+                chain,
+                &unchanged,
+                rpc.clone().flat_map(|eth| eth.adapters.clone()).collect(),
+            );
+            adapters.sort_by(|a, b| {
+                a.capabilities
+                    .partial_cmp(&b.capabilities)
+                    .unwrap_or(Ordering::Equal)
+            });
+            let call_only = updated(
+                &self.call_only_provider_manager,
+                &chain_id,
+                chain,
+                &unchanged,
+                rpc.flat_map(|eth| eth.call_only.clone()).collect(),
+            );
+            let firehose = updated(
+                &self.firehose_provider_manager,
+                &chain_id,
+                chain,
+                &unchanged,
+                created
+                    .clone()
+                    .flat_map(|a| a.as_firehose())
+                    .flat_map(|fh| fh.adapters.clone())
+                    .collect(),
+            );
+            let substreams = updated(
+                &self.substreams_provider_manager,
+                &chain_id,
+                chain,
+                &unchanged,
+                created
+                    .flat_map(|a| a.as_substreams())
+                    .flat_map(|fh| fh.adapters.clone())
+                    .collect(),
+            );
+
+            self.rpc_provider_manager
+                .update(&chain_id, adapters, &unchanged);
+            self.call_only_provider_manager
+                .update(&chain_id, call_only, &unchanged);
+            self.firehose_provider_manager
+                .update(&chain_id, firehose, &unchanged);
+            self.substreams_provider_manager
+                .update(&chain_id, substreams, &unchanged);
+            info!(logger, "Updated providers";
+                  "network" => &name,
+                  "providers" => chain.providers.iter().map(|p| &p.label).join(", "),
+                  "unchanged" => unchanged.iter().join(", "));
+        }
+
+        if !self.genesis_validation_enabled {
+            self.rpc_provider_manager.mark_all_valid().await;
+            self.firehose_provider_manager.mark_all_valid().await;
+            self.substreams_provider_manager.mark_all_valid().await;
+        }
+
+        Ok(())
+    }
+
+    /// Check the configuration file at `path` for changes every `interval`
+    /// and apply changes to providers with `update_providers`. `config` is
+    /// the configuration that these networks were created from
+    pub async fn watch_config(
+        self,
+        logger: Logger,
+        path: String,
+        mut config: Config,
+        interval: Duration,
+    ) {
+        let mut contents: Option<String> = None;
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let new_contents = match std::fs::read_to_string(&path) {
+                Ok(new_contents) => new_contents,
+                Err(e) => {
+                    warn!(logger, "Failed to read configuration file";
+                          "path" => &path, "error" => e.to_string());
+                    continue;
+                }
+            };
+            if contents.as_ref() == Some(&new_contents) {
+                continue;
+            }
+
+            // Remember the contents even if they can not be applied so that
+            // an invalid file is only reported once, and tried again when
+            // it changes
+            let new_config = Config::from_str(&new_contents, config.node.as_str());
+            contents = Some(new_contents);
+            let new_config = match new_config {
+                Ok(new_config) => new_config,
+                Err(e) => {
+                    error!(logger, "Invalid configuration file, not applying it";
+                           "path" => &path, "error" => format!("{:#}", e));
+                    continue;
+                }
+            };
+            match self.update_providers(&logger, &config, &new_config).await {
+                Ok(()) => {
+                    config = new_config;
+                }
+                Err(e) => {
+                    error!(logger, "Failed to update providers from configuration file";
+                           "path" => &path, "error" => format!("{:#}", e));
+                }
+            }
+        }
+    }
 }
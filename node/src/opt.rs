@@ -111,12 +111,14 @@ pub struct Opt {
     pub ipfs: Vec<String>,
     #[clap(
         long,
-        value_name = "{HOST:PORT|URL}",
+        value_name = "{HOST:PORT|URL},",
+        use_value_delimiter = true,
         default_value = "https://arweave.net",
         env = "GRAPH_NODE_ARWEAVE_URL",
-        help = "HTTP base URL for arweave gateway"
+        help = "Comma-separated list of HTTP base URLs of arweave gateways, which are \
+           tried in order until one of them has the file"
     )]
-    pub arweave: String,
+    pub arweave: Vec<String>,
     #[clap(
         long,
         default_value = "8000",
@@ -0,0 +1,268 @@
+//! Runs subgraph mappings in unit tests with the host implementation that
+//! `graph-node` uses for indexing, but with in-memory stand-ins for the
+//! store, IPFS and the chain. See the README for an example
+mod services;
+mod store;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use graph::blockchain::{BlockTime, Blockchain, HostFn};
+use graph::components::metrics::gas::GasMetrics;
+use graph::components::store::ReadStore;
+use graph::components::subgraph::{HandlerLimits, MappingError};
+use graph::data::store::scalar::BigDecimalPrecision;
+use graph::data::subgraph::schema::SubgraphError;
+use graph::data_source::{DataSource, DataSourceTemplate, MappingTrigger, TriggerWithHandler};
+use graph::prelude::{
+    anyhow, o, BlockNumber, BlockPtr, BlockState, CheapClone, DeploymentHash, EntityModification,
+    Error, HostMetrics, InstanceDSTemplateInfo, Logger, MetricsRegistry, StopwatchMetrics,
+};
+use graph::runtime::gas::Gas;
+use graph::schema::InputSchema;
+use graph_runtime_wasm::host_exports::DataSourceDetails;
+use graph_runtime_wasm::module::ToAscPtr;
+use graph_runtime_wasm::{
+    ExperimentalFeatures, HostExports, MappingContext, ValidModule, WasmInstance,
+};
+
+pub use services::{MockDeploymentLogs, MockEnsLookup, MockHttpCache, MockLinkResolver};
+pub use store::MockStore;
+
+/// The block that a handler runs in
+#[derive(Clone, Debug)]
+pub struct BlockFixture {
+    pub ptr: BlockPtr,
+    pub timestamp: BlockTime,
+}
+
+impl BlockFixture {
+    /// A block with the given number, a zero hash and the number as its
+    /// timestamp in seconds
+    pub fn new(number: BlockNumber) -> Self {
+        Self {
+            ptr: BlockPtr::new(Default::default(), number),
+            timestamp: BlockTime::since_epoch(number as i64, 0),
+        }
+    }
+}
+
+/// What a handler did besides changing entities, which are applied to the
+/// `MockStore`
+#[derive(Debug)]
+pub struct HandlerOutcome {
+    /// The entity changes of the handler, in the form in which they would
+    /// be written to the store
+    pub modifications: Vec<EntityModification>,
+    /// Errors that the handler reported without failing, for subgraphs
+    /// with the `nonFatalErrors` feature
+    pub deterministic_errors: Vec<SubgraphError>,
+    pub created_data_sources: Vec<InstanceDSTemplateInfo>,
+    pub gas: Gas,
+}
+
+/// A mapping of one data source, ready to run handlers. The stores and
+/// services are shared between all handlers that run
+pub struct MappingTest<C: Blockchain> {
+    logger: Logger,
+    deployment: DeploymentHash,
+    data_source: DataSource<C>,
+    templates: Vec<DataSourceTemplate<C>>,
+    valid_module: Arc<ValidModule>,
+    host_metrics: Arc<HostMetrics>,
+    host_fns: Vec<HostFn>,
+    mapping_env: BTreeMap<String, String>,
+    allow_non_deterministic_ipfs: bool,
+    store: Arc<MockStore>,
+    ipfs: Arc<MockLinkResolver>,
+    ens: Arc<MockEnsLookup>,
+    http: Arc<MockHttpCache>,
+    logs: Arc<MockDeploymentLogs>,
+}
+
+impl<C: Blockchain> MappingTest<C> {
+    /// Compile the mapping of `data_source` for a deployment with the
+    /// given `schema`. Handlers run without limits on time, fuel and memory
+    pub fn new(deployment: &str, schema: &str, data_source: DataSource<C>) -> Result<Self, Error> {
+        let logger = Logger::root(graph::slog::Discard, o!());
+        let deployment = DeploymentHash::new(deployment)
+            .map_err(|id| anyhow!("`{}` is not a valid deployment id", id))?;
+        let schema = InputSchema::parse_latest(schema, deployment.clone())?;
+        let raw_module = data_source
+            .runtime()
+            .ok_or_else(|| anyhow!("data source `{}` has no mapping", data_source.name()))?;
+        let valid_module = Arc::new(ValidModule::new(
+            &logger,
+            &raw_module,
+            &HandlerLimits::default(),
+        )?);
+
+        let registry = Arc::new(MetricsRegistry::mock());
+        let stopwatch = StopwatchMetrics::new(
+            logger.clone(),
+            deployment.clone(),
+            "test",
+            registry.clone(),
+            "test_shard".to_string(),
+        );
+        let gas_metrics = GasMetrics::new(deployment.clone(), registry.clone());
+        let host_metrics = Arc::new(HostMetrics::new(
+            registry,
+            deployment.as_str(),
+            stopwatch,
+            gas_metrics,
+        ));
+
+        Ok(Self {
+            logger,
+            deployment,
+            data_source,
+            templates: Vec::new(),
+            valid_module,
+            host_metrics,
+            host_fns: Vec::new(),
+            mapping_env: BTreeMap::new(),
+            allow_non_deterministic_ipfs: false,
+            store: Arc::new(MockStore::new(schema)),
+            ipfs: Arc::new(MockLinkResolver::default()),
+            ens: Arc::new(MockEnsLookup::default()),
+            http: Arc::new(MockHttpCache::default()),
+            logs: Arc::new(MockDeploymentLogs::default()),
+        })
+    }
+
+    /// Recompile the mapping so that handlers run with `limits`
+    pub fn with_limits(mut self, limits: HandlerLimits) -> Result<Self, Error> {
+        let raw_module = self
+            .data_source
+            .runtime()
+            .expect("the mapping was compiled");
+        self.valid_module = Arc::new(ValidModule::new(&self.logger, &raw_module, &limits)?);
+        Ok(self)
+    }
+
+    /// The templates that handlers can create data sources from
+    pub fn with_templates(mut self, templates: Vec<DataSourceTemplate<C>>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Replace a host function that needs a chain client, for example
+    /// `ethereum.call`, with a stub
+    pub fn with_host_fn(mut self, host_fn: HostFn) -> Self {
+        self.host_fns.push(host_fn);
+        self
+    }
+
+    /// Set a constant that the mapping reads with `dataSource.env`
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.mapping_env.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Allow `ipfs.cat` and `ipfs.map` in handlers of onchain data sources
+    pub fn with_non_deterministic_ipfs(mut self) -> Self {
+        self.allow_non_deterministic_ipfs = true;
+        self
+    }
+
+    pub fn deployment(&self) -> &DeploymentHash {
+        &self.deployment
+    }
+
+    pub fn store(&self) -> &MockStore {
+        &self.store
+    }
+
+    pub fn ipfs(&self) -> &MockLinkResolver {
+        &self.ipfs
+    }
+
+    pub fn ens(&self) -> &MockEnsLookup {
+        &self.ens
+    }
+
+    pub fn http(&self) -> &MockHttpCache {
+        &self.http
+    }
+
+    pub fn logs(&self) -> &MockDeploymentLogs {
+        &self.logs
+    }
+
+    fn host_exports(&self) -> HostExports {
+        let ds_details = DataSourceDetails::from_data_source(
+            &self.data_source,
+            Arc::new(self.templates.iter().map(|t| t.into()).collect()),
+        );
+        HostExports::new(
+            self.deployment.clone(),
+            self.data_source.network().unwrap_or_default().to_string(),
+            ds_details,
+            self.ipfs.cheap_clone(),
+            self.ens.cheap_clone(),
+            self.http.cheap_clone(),
+            self.logs.cheap_clone(),
+            None,
+            BigDecimalPrecision::default(),
+            ReadStore::input_schema(&self.store).has_retention(),
+            Arc::new(self.mapping_env.clone()),
+        )
+    }
+
+    /// Run the handler for `trigger` in `block` and apply its entity
+    /// changes to the store. Entity changes of a handler that fails are
+    /// discarded, just like when indexing.
+    ///
+    /// Like when indexing, host functions block on futures, for example
+    /// for `ipfs.cat`. This must therefore be called from a thread that
+    /// entered a tokio runtime without running inside of it, for example
+    /// with `tokio::task::spawn_blocking`
+    pub fn run(
+        &self,
+        block: &BlockFixture,
+        trigger: TriggerWithHandler<MappingTrigger<C>>,
+    ) -> Result<HandlerOutcome, MappingError>
+    where
+        <C as Blockchain>::MappingTrigger: ToAscPtr,
+    {
+        let ctx = MappingContext {
+            logger: self.logger.clone(),
+            host_exports: Arc::new(self.host_exports()),
+            block_ptr: block.ptr.clone(),
+            timestamp: block.timestamp,
+            state: BlockState::new(self.store.cheap_clone(), Default::default()),
+            proof_of_indexing: None,
+            host_fns: Arc::new(self.host_fns.clone()),
+            debug_fork: None,
+            mapping_logger: self.logger.clone(),
+            instrument: false,
+        };
+        let experimental_features = ExperimentalFeatures {
+            allow_non_deterministic_ipfs: self.allow_non_deterministic_ipfs,
+        };
+        let instance = WasmInstance::from_valid_module_with_ctx(
+            self.valid_module.cheap_clone(),
+            ctx,
+            self.host_metrics.cheap_clone(),
+            experimental_features,
+        )
+        .map_err(MappingError::Unknown)?;
+
+        let (mut state, gas) = instance.handle_trigger(trigger)?;
+        let created_data_sources = state.drain_created_data_sources();
+        let modifications = state
+            .entity_cache
+            .as_modifications(block.ptr.number)
+            .map_err(|e| MappingError::Unknown(e.into()))?
+            .modifications;
+        self.store.apply(&modifications);
+
+        Ok(HandlerOutcome {
+            modifications,
+            deterministic_errors: state.deterministic_errors,
+            created_data_sources,
+            gas,
+        })
+    }
+}
@@ -0,0 +1,200 @@
+//! Stand-ins for IPFS and the services of the store that the host exports
+//! use besides the entity store
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use graph::components::link_resolver::{JsonStreamValue, JsonValueStream};
+use graph::components::store::{
+    DeploymentLog, DeploymentLogFilter, DeploymentLogs, EnsLookup, HttpCache,
+};
+use graph::data::subgraph::Link;
+use graph::futures03::stream;
+use graph::prelude::{
+    anyhow, async_trait, serde_json, DeploymentHash, Error, LinkResolver, Logger, StoreError,
+};
+
+/// Serves the files that were added to it in place of IPFS. Links can be
+/// given with or without a leading `/ipfs/`
+#[derive(Clone, Debug, Default)]
+pub struct MockLinkResolver {
+    files: Arc<RwLock<HashMap<String, Vec<u8>>>>,
+}
+
+impl MockLinkResolver {
+    fn path(link: &str) -> &str {
+        link.trim_start_matches("/ipfs/")
+    }
+
+    pub fn add(&self, path: &str, content: Vec<u8>) {
+        self.files
+            .write()
+            .unwrap()
+            .insert(Self::path(path).to_string(), content);
+    }
+
+    fn file(&self, link: &Link) -> Result<Vec<u8>, Error> {
+        self.files
+            .read()
+            .unwrap()
+            .get(Self::path(&link.link))
+            .cloned()
+            .ok_or_else(|| anyhow!("file `{}` was not added to the test IPFS", link.link))
+    }
+}
+
+#[async_trait]
+impl LinkResolver for MockLinkResolver {
+    fn with_timeout(&self, _timeout: Duration) -> Box<dyn LinkResolver> {
+        Box::new(self.clone())
+    }
+
+    fn with_retries(&self) -> Box<dyn LinkResolver> {
+        Box::new(self.clone())
+    }
+
+    async fn cat(&self, _logger: &Logger, link: &Link) -> Result<Vec<u8>, Error> {
+        self.file(link)
+    }
+
+    async fn get_block(&self, _logger: &Logger, link: &Link) -> Result<Vec<u8>, Error> {
+        self.file(link)
+    }
+
+    async fn json_stream(&self, _logger: &Logger, link: &Link) -> Result<JsonValueStream, Error> {
+        let file = String::from_utf8(self.file(link)?)?;
+        // Like IPFS, count lines from 1 and skip empty lines
+        let values = file
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .map(|value| JsonStreamValue { value, line: i + 1 })
+                    .map_err(|e| anyhow!("{} in line {}", e, i + 1))
+            })
+            .collect::<Vec<_>>();
+        Ok(Box::pin(stream::iter(values)))
+    }
+}
+
+/// Resolves the ENS names that were added to it
+#[derive(Default)]
+pub struct MockEnsLookup {
+    names: RwLock<HashMap<String, String>>,
+}
+
+impl MockEnsLookup {
+    /// Make `hash`, the keccak256 hash of `name`, resolve to `name`
+    pub fn add(&self, hash: &str, name: &str) {
+        self.names
+            .write()
+            .unwrap()
+            .insert(hash.to_string(), name.to_string());
+    }
+}
+
+impl EnsLookup for MockEnsLookup {
+    fn find_name(&self, hash: &str) -> Result<Option<String>, StoreError> {
+        Ok(self.names.read().unwrap().get(hash).cloned())
+    }
+
+    fn is_table_empty(&self) -> Result<bool, StoreError> {
+        Ok(self.names.read().unwrap().is_empty())
+    }
+}
+
+/// Keeps responses to `http.get` in memory. Tests can add responses up
+/// front so that handlers never go to the network
+#[derive(Default)]
+pub struct MockHttpCache {
+    responses: RwLock<HashMap<(DeploymentHash, String, String), Vec<u8>>>,
+}
+
+impl MockHttpCache {
+    pub fn add(&self, deployment: &DeploymentHash, source: &str, url: &str, body: Vec<u8>) {
+        self.responses.write().unwrap().insert(
+            (deployment.clone(), source.to_string(), url.to_string()),
+            body,
+        );
+    }
+}
+
+impl HttpCache for MockHttpCache {
+    fn get(
+        &self,
+        deployment: &DeploymentHash,
+        source: &str,
+        url: &str,
+    ) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self
+            .responses
+            .read()
+            .unwrap()
+            .get(&(deployment.clone(), source.to_string(), url.to_string()))
+            .cloned())
+    }
+
+    fn insert(
+        &self,
+        deployment: &DeploymentHash,
+        source: &str,
+        url: &str,
+        body: Vec<u8>,
+    ) -> Result<Vec<u8>, StoreError> {
+        Ok(self
+            .responses
+            .write()
+            .unwrap()
+            .entry((deployment.clone(), source.to_string(), url.to_string()))
+            .or_insert(body)
+            .clone())
+    }
+}
+
+/// Keeps everything that mappings logged, oldest first
+#[derive(Default)]
+pub struct MockDeploymentLogs {
+    logs: Mutex<Vec<DeploymentLog>>,
+}
+
+impl MockDeploymentLogs {
+    pub fn logs(&self) -> Vec<DeploymentLog> {
+        self.logs.lock().unwrap().clone()
+    }
+}
+
+impl DeploymentLogs for MockDeploymentLogs {
+    fn append(&self, _deployment: &DeploymentHash, log: DeploymentLog) -> Result<(), StoreError> {
+        self.logs.lock().unwrap().push(log);
+        Ok(())
+    }
+
+    fn find(
+        &self,
+        _deployment: &DeploymentHash,
+        filter: &DeploymentLogFilter,
+    ) -> Result<Vec<DeploymentLog>, StoreError> {
+        Ok(self
+            .logs
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|log| {
+                filter
+                    .level
+                    .map_or(true, |level| log.level.is_at_least(level))
+            })
+            .filter(|log| filter.handler.is_none() || log.handler == filter.handler)
+            .filter(|log| {
+                filter
+                    .from_block
+                    .map_or(true, |from| log.block_number >= from)
+            })
+            .filter(|log| filter.to_block.map_or(true, |to| log.block_number <= to))
+            .take(filter.first)
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,174 @@
+//! An in-memory entity store for the deployment under test
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::RwLock;
+
+use graph::blockchain::block_stream::FirehoseCursor;
+use graph::components::store::{DeploymentCursorTracker, DerivedEntityQuery, ReadStore};
+use graph::prelude::{BlockNumber, BlockPtr, Entity, EntityModification, Error, StoreError};
+use graph::schema::{EntityKey, InputSchema};
+
+/// Holds the entities of a deployment. Entities have no history; a handler
+/// always sees the latest version of every entity
+pub struct MockStore {
+    schema: InputSchema,
+    entities: RwLock<BTreeMap<EntityKey, Entity>>,
+}
+
+impl MockStore {
+    pub fn new(schema: InputSchema) -> Self {
+        Self {
+            schema,
+            entities: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn schema(&self) -> &InputSchema {
+        &self.schema
+    }
+
+    fn key(&self, entity_type: &str, id: &str) -> Result<EntityKey, Error> {
+        self.schema.entity_type(entity_type)?.parse_key(id)
+    }
+
+    /// Add an entity as a fixture, or replace it if it exists already
+    pub fn set(&self, entity_type: &str, entity: Entity) -> Result<(), Error> {
+        let key = self.schema.entity_type(entity_type)?.key(entity.id());
+        self.entities.write().unwrap().insert(key, entity);
+        Ok(())
+    }
+
+    /// The current version of the entity with the given type and id
+    pub fn get(&self, entity_type: &str, id: &str) -> Result<Option<Entity>, Error> {
+        let key = self.key(entity_type, id)?;
+        Ok(self.entities.read().unwrap().get(&key).cloned())
+    }
+
+    /// All entities of the given type, ordered by id
+    pub fn all(&self, entity_type: &str) -> Result<Vec<Entity>, Error> {
+        let entity_type = self.schema.entity_type(entity_type)?;
+        Ok(self
+            .entities
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.entity_type == entity_type)
+            .map(|(_, entity)| entity.clone())
+            .collect())
+    }
+
+    /// Apply the changes that a handler made
+    pub(crate) fn apply(&self, modifications: &[EntityModification]) {
+        let mut entities = self.entities.write().unwrap();
+        for modification in modifications {
+            match modification {
+                EntityModification::Insert { key, data, .. }
+                | EntityModification::Overwrite { key, data, .. } => {
+                    entities.insert(key.clone(), data.as_ref().clone());
+                }
+                EntityModification::Remove { key, .. } => {
+                    entities.remove(key);
+                }
+            }
+        }
+    }
+}
+
+impl ReadStore for MockStore {
+    fn get(&self, key: &EntityKey) -> Result<Option<Entity>, StoreError> {
+        Ok(self.entities.read().unwrap().get(key).cloned())
+    }
+
+    fn get_at_block(
+        &self,
+        key: &EntityKey,
+        _block: BlockNumber,
+    ) -> Result<Option<Entity>, StoreError> {
+        ReadStore::get(self, key)
+    }
+
+    fn get_many(
+        &self,
+        keys: BTreeSet<EntityKey>,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        let entities = self.entities.read().unwrap();
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| entities.get(&key).cloned().map(|entity| (key, entity)))
+            .collect())
+    }
+
+    fn get_derived(
+        &self,
+        query: &DerivedEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        let entities = self.entities.read().unwrap();
+        Ok(entities
+            .iter()
+            .filter(|(key, entity)| query.matches(key, entity) && query.filter.matches(entity))
+            .map(|(key, entity)| (key.clone(), entity.clone()))
+            .collect())
+    }
+
+    fn input_schema(&self) -> InputSchema {
+        self.schema.clone()
+    }
+}
+
+impl DeploymentCursorTracker for MockStore {
+    fn input_schema(&self) -> InputSchema {
+        self.schema.clone()
+    }
+
+    fn block_ptr(&self) -> Option<BlockPtr> {
+        None
+    }
+
+    fn firehose_cursor(&self) -> FirehoseCursor {
+        FirehoseCursor::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::data::store::Id;
+    use graph::data_source::CausalityRegion;
+    use graph::entity;
+    use graph::prelude::DeploymentHash;
+
+    use super::*;
+
+    #[test]
+    fn derived_lookups_and_modifications() {
+        let schema = InputSchema::parse_latest(
+            "type User @entity { id: ID!, name: String!, posts: [Post!]! @derivedFrom(field: \"author\") }
+             type Post @entity { id: ID!, author: User! }",
+            DeploymentHash::new("harness").unwrap(),
+        )
+        .unwrap();
+        let store = MockStore::new(schema.clone());
+        store
+            .set("User", entity! { schema => id: "u1", name: "one" })
+            .unwrap();
+        store
+            .set("Post", entity! { schema => id: "p1", author: "u1" })
+            .unwrap();
+        store
+            .set("Post", entity! { schema => id: "p2", author: "u2" })
+            .unwrap();
+
+        let query = DerivedEntityQuery {
+            entity_type: schema.entity_type("Post").unwrap(),
+            entity_field: "author".into(),
+            value: Id::String("u1".into()),
+            causality_region: CausalityRegion::ONCHAIN,
+            filter: Default::default(),
+        };
+        let posts = store.get_derived(&query).unwrap();
+        assert_eq!(1, posts.len());
+
+        let key = schema.entity_type("User").unwrap().parse_key("u1").unwrap();
+        store.apply(&[EntityModification::Remove { key, block: 1 }]);
+        assert_eq!(None, store.get("User", "u1").unwrap());
+        assert_eq!(2, store.all("Post").unwrap().len());
+    }
+}
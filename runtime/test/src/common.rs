@@ -1,6 +1,7 @@
 use ethabi::Contract;
 use graph::blockchain::BlockTime;
 use graph::components::store::DeploymentLocator;
+use graph::data::store::scalar::BigDecimalPrecision;
 use graph::data::subgraph::*;
 use graph::data_source;
 use graph::env::EnvVars;
@@ -14,6 +15,7 @@ use graph_chain_ethereum::{
 use graph_runtime_wasm::host_exports::DataSourceDetails;
 use graph_runtime_wasm::{HostExports, MappingContext};
 use semver::Version;
+use std::collections::BTreeMap;
 use std::env;
 use std::str::FromStr;
 use web3::types::Address;
@@ -47,6 +49,7 @@ fn mock_host_exports(
                 entities: vec![],
                 abis: vec![],
                 event_handlers: vec![],
+                fallback_event_handler: None,
                 call_handlers: vec![],
                 block_handlers: vec![],
                 link: Link {
@@ -59,6 +62,8 @@ fn mock_host_exports(
 
     let network = data_source.network.clone().unwrap();
     let ens_lookup = store.ens_lookup();
+    let http_cache = store.http_cache();
+    let deployment_logs = store.deployment_logs();
 
     let ds_details = DataSourceDetails::from_data_source(
         &graph::data_source::DataSource::Onchain::<Chain>(data_source),
@@ -66,6 +71,7 @@ fn mock_host_exports(
     );
 
     let client = IpfsRpcClient::new_unchecked(ServerAddress::local_rpc_api(), &LOGGER).unwrap();
+    let prunes_history = store.input_schema(&subgraph_id).unwrap().has_retention();
 
     HostExports::new(
         subgraph_id,
@@ -76,6 +82,12 @@ fn mock_host_exports(
             Arc::new(EnvVars::default()),
         )),
         ens_lookup,
+        http_cache,
+        deployment_logs,
+        None,
+        BigDecimalPrecision::default(),
+        prunes_history,
+        Arc::new(BTreeMap::new()),
     )
 }
 
@@ -154,6 +166,7 @@ pub fn mock_data_source(path: &str, api_version: Version) -> DataSource {
             entities: vec![],
             abis: vec![],
             event_handlers: vec![],
+            fallback_event_handler: None,
             call_handlers: vec![],
             block_handlers: vec![],
             link: Link {
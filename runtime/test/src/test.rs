@@ -1,6 +1,7 @@
 use graph::blockchain::BlockTime;
 use graph::components::metrics::gas::GasMetrics;
 use graph::components::store::*;
+use graph::components::subgraph::HandlerLimits;
 use graph::data::store::{scalar, Id, IdType};
 use graph::data::subgraph::*;
 use graph::data::value::Word;
@@ -57,6 +58,19 @@ async fn test_valid_module_and_store_with_timeout(
     data_source: DataSource,
     api_version: Version,
     timeout: Option<Duration>,
+) -> (WasmInstance, Arc<impl SubgraphStore>, DeploymentLocator) {
+    let limits = HandlerLimits {
+        timeout_secs: timeout.map(|timeout| timeout.as_secs()),
+        ..Default::default()
+    };
+    test_valid_module_and_store_with_limits(subgraph_id, data_source, api_version, limits).await
+}
+
+async fn test_valid_module_and_store_with_limits(
+    subgraph_id: &str,
+    data_source: DataSource,
+    api_version: Version,
+    limits: HandlerLimits,
 ) -> (WasmInstance, Arc<impl SubgraphStore>, DeploymentLocator) {
     let logger = Logger::root(slog::Discard, o!());
     let subgraph_id_with_api_version =
@@ -102,7 +116,7 @@ async fn test_valid_module_and_store_with_timeout(
     };
 
     let module = WasmInstance::from_valid_module_with_ctx(
-        Arc::new(ValidModule::new(&logger, data_source.mapping.runtime.as_ref(), timeout).unwrap()),
+        Arc::new(ValidModule::new(&logger, data_source.mapping.runtime.as_ref(), &limits).unwrap()),
         mock_context(
             deployment.clone(),
             data_source,
@@ -1355,6 +1369,54 @@ impl Host {
             &self.gas,
         )
     }
+
+    fn store_get_many(
+        &mut self,
+        entity_type: &str,
+        ids: &[&str],
+    ) -> Result<Vec<Option<Arc<Entity>>>, anyhow::Error> {
+        let ids = ids.iter().map(|id| id.to_string()).collect();
+        self.host_exports.store_get_many(
+            &mut self.ctx.state,
+            entity_type.to_string(),
+            ids,
+            &self.gas,
+        )
+    }
+
+    fn store_get_at_block(
+        &mut self,
+        entity_type: &str,
+        id: &str,
+        block: BlockNumber,
+        current_block: BlockNumber,
+    ) -> Result<Option<Arc<Entity>>, HostExportError> {
+        self.host_exports.store_get_at_block(
+            &mut self.ctx.state,
+            entity_type.to_string(),
+            id.to_string(),
+            block,
+            current_block,
+            &self.gas,
+        )
+    }
+
+    fn store_load_related_filtered(
+        &mut self,
+        entity_type: &str,
+        id: &str,
+        field: &str,
+        filter: DerivedEntityFilter,
+    ) -> Result<Vec<Entity>, anyhow::Error> {
+        self.host_exports.store_load_related_filtered(
+            &mut self.ctx.state,
+            entity_type.to_string(),
+            id.to_string(),
+            field.to_string(),
+            filter,
+            &self.gas,
+        )
+    }
 }
 
 #[track_caller]
@@ -1635,6 +1697,192 @@ async fn test_store_intf() {
         .expect_err("store_get with interface does not work");
 }
 
+#[tokio::test]
+async fn test_store_get_many() {
+    const USER: &str = "User";
+    const PERSON: &str = "Person";
+
+    let schema = "type User implements Person @entity {
+        id: String!,
+        name: String,
+    }
+
+    interface Person {
+        id: String!,
+        name: String,
+    }";
+
+    let mut host = Host::new(schema, "hostStoreGetMany", "boolean.wasm", None).await;
+
+    host.store_set(USER, "u1", vec![("id", "u1"), ("name", "user1")])
+        .expect("storing user works");
+    host.store_set(USER, "u2", vec![("id", "u2"), ("name", "user2")])
+        .expect("storing user works");
+
+    let users = host
+        .store_get_many(USER, &["u2", "nope", "u1", "u2"])
+        .unwrap();
+    let names: Vec<_> = users
+        .iter()
+        .map(|user| {
+            user.as_ref()
+                .map(|user| user.get("name").unwrap().as_str().unwrap().to_string())
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            Some("user2".to_string()),
+            None,
+            Some("user1".to_string()),
+            Some("user2".to_string())
+        ],
+        names,
+        "store_get_many returns entities in the order of the ids"
+    );
+
+    assert!(host.store_get_many(USER, &[]).unwrap().is_empty());
+
+    let err = host
+        .store_get_many(PERSON, &["u1"])
+        .expect_err("store_get_many with interface does not work");
+    err_says(
+        err,
+        "Cannot get entity of type `Person`. The type must be an @entity type",
+    );
+}
+
+#[tokio::test]
+async fn test_store_load_related_filtered() {
+    const PARENT: &str = "Parent";
+    const CHILD: &str = "Child";
+
+    let schema = "type Parent @entity {
+        id: String!,
+        children: [Child!]! @derivedFrom(field: \"parent\")
+    }
+
+    type Child @entity {
+        id: String!,
+        parent: Parent!,
+        kind: String!,
+        rank: Int!
+    }";
+
+    let mut host = Host::new(schema, "hostStoreLoadRelatedFiltered", "boolean.wasm", None).await;
+
+    host.store_set(PARENT, "p1", vec![("id", "p1")]).unwrap();
+    for (id, parent, kind, rank) in [
+        ("c1", "p1", "a", 3),
+        ("c2", "p1", "b", 5),
+        ("c3", "p1", "a", 7),
+        ("c4", "p1", "a", 1),
+        ("c5", "p2", "a", 9),
+    ] {
+        let data = vec![
+            ("id", Value::from(id)),
+            ("parent", Value::from(parent)),
+            ("kind", Value::from(kind)),
+            ("rank", Value::from(rank)),
+        ];
+        host.store_setv(CHILD, id, data).unwrap();
+    }
+
+    let ids = |entities: Vec<Entity>| -> Vec<String> {
+        entities
+            .into_iter()
+            .map(|entity| entity.id().to_string())
+            .collect()
+    };
+
+    let filter = DerivedEntityFilter {
+        values: vec![(Word::from("kind"), Value::from("a"))],
+        order_by: Some(Word::from("rank")),
+        descending: true,
+        first: Some(2),
+    };
+    let children = host
+        .store_load_related_filtered(PARENT, "p1", "children", filter)
+        .unwrap();
+    assert_eq!(vec!["c3", "c1"], ids(children));
+
+    let children = host
+        .store_load_related_filtered(PARENT, "p1", "children", DerivedEntityFilter::default())
+        .unwrap();
+    assert_eq!(vec!["c1", "c2", "c3", "c4"], ids(children));
+
+    let filter = DerivedEntityFilter {
+        order_by: Some(Word::from("children")),
+        ..Default::default()
+    };
+    let err = host
+        .store_load_related_filtered(PARENT, "p1", "children", filter)
+        .expect_err("ordering by an unknown attribute does not work");
+    err_says(err, "since it is not an attribute of that type");
+}
+
+#[tokio::test]
+async fn test_store_get_at_block() {
+    const USER: &str = "User";
+    const CURRENT_BLOCK: BlockNumber = 12;
+
+    let schema = "type User @entity {
+        id: String!,
+        name: String,
+    }";
+
+    let mut host = Host::new(schema, "hostStoreGetAtBlock", "boolean.wasm", None).await;
+
+    host.store_set(USER, "u1", vec![("id", "u1"), ("name", "user1")])
+        .expect("storing user works");
+
+    let user = host
+        .store_get_at_block(USER, "u1", CURRENT_BLOCK, CURRENT_BLOCK)
+        .unwrap()
+        .expect("the current block sees changes made by the handler");
+    assert_eq!("user1", user.get("name").unwrap().as_str().unwrap());
+
+    let user = host
+        .store_get_at_block(USER, "u1", CURRENT_BLOCK - 1, CURRENT_BLOCK)
+        .unwrap();
+    assert!(
+        user.is_none(),
+        "earlier blocks do not see changes made by the handler"
+    );
+
+    for block in [CURRENT_BLOCK + 1, -1] {
+        let err = host
+            .store_get_at_block(USER, "u1", block, CURRENT_BLOCK)
+            .expect_err("only blocks up to the current block can be read");
+        assert!(matches!(err, HostExportError::Deterministic(_)));
+        err_says(err, "store.getAtBlock can only get entities as of blocks");
+    }
+}
+
+#[tokio::test]
+async fn test_store_get_at_block_with_retention() {
+    let schema = r#"type User @entity(retention: "1h") {
+        id: String!,
+        name: String,
+    }"#;
+
+    let mut host = Host::new(
+        schema,
+        "hostStoreGetAtBlockWithRetention",
+        "boolean.wasm",
+        None,
+    )
+    .await;
+
+    let err = host
+        .store_get_at_block("User", "u1", 11, 12)
+        .expect_err("subgraphs that prune their history can not use getAtBlock");
+    assert!(matches!(err, HostExportError::Deterministic(_)));
+    err_says(
+        err,
+        "store.getAtBlock can not be used by subgraphs that prune",
+    );
+}
+
 #[tokio::test]
 async fn test_store_ts() {
     const DATA: &str = "Data";
@@ -1698,3 +1946,58 @@ async fn test_store_ts() {
         "Cannot get entity of type `Stats`. The type must be an @entity type",
     );
 }
+
+/// A module with a memory and a single function that takes `params` and
+/// whose code, without locals and the final `end`, is `code`
+fn module_with_code(params: &[u8], code: &[u8]) -> Vec<u8> {
+    fn section(id: u8, content: &[u8]) -> Vec<u8> {
+        // All sections here are shorter than 128 bytes, so their length
+        // is a single byte in LEB128
+        assert!(content.len() < 128);
+        [&[id, content.len() as u8][..], content].concat()
+    }
+
+    let ty = [&[0x01, 0x60, params.len() as u8][..], params, &[0x00]].concat();
+    let body = [&[0x00][..], code, &[0x0b]].concat();
+    let body = [&[body.len() as u8][..], &body].concat();
+    [
+        &b"\0asm\x01\0\0\0"[..],
+        &section(1, &ty),
+        &section(3, &[0x01, 0x00]),
+        &section(5, &[0x01, 0x00, 0x01]),
+        &section(10, &[&[0x01][..], &body].concat()),
+    ]
+    .concat()
+}
+
+#[test]
+fn simd_and_bulk_memory_modules() {
+    let logger = Logger::root(slog::Discard, o!());
+    let limits = HandlerLimits::default();
+    const I32: u8 = 0x7f;
+
+    // (func (param i32 i32 i32)
+    //   local.get 0 local.get 1 local.get 2 memory.copy
+    //   local.get 0 local.get 1 local.get 2 memory.fill
+    //   local.get 0 v128.load drop)
+    let code = [
+        0x20, 0x00, 0x20, 0x01, 0x20, 0x02, 0xfc, 0x0a, 0x00, 0x00, // memory.copy
+        0x20, 0x00, 0x20, 0x01, 0x20, 0x02, 0xfc, 0x0b, 0x00, // memory.fill
+        0x20, 0x00, 0xfd, 0x00, 0x04, 0x00, 0x1a, // v128.load
+    ];
+    let module = module_with_code(&[I32, I32, I32], &code);
+    ValidModule::new(&logger, &module, &limits).expect("SIMD and bulk memory are allowed");
+
+    // (func (param i32)
+    //   local.get 0 v128.load local.get 0 v128.load i8x16.relaxed_swizzle drop)
+    let code = [
+        0x20, 0x00, 0xfd, 0x00, 0x04, 0x00, // v128.load
+        0x20, 0x00, 0xfd, 0x00, 0x04, 0x00, // v128.load
+        0xfd, 0x80, 0x02, 0x1a, // i8x16.relaxed_swizzle
+    ];
+    let module = module_with_code(&[I32], &code);
+    assert!(
+        ValidModule::new(&logger, &module, &limits).is_err(),
+        "relaxed SIMD is not deterministic and must be rejected"
+    );
+}
@@ -41,6 +41,40 @@ async fn unbounded_loop_v0_0_5() {
     test_unbounded_loop(API_VERSION_0_0_5).await;
 }
 
+async fn test_out_of_fuel(api_version: Version) {
+    let mut instance = test_valid_module_and_store_with_limits(
+        "outOfFuel",
+        mock_data_source(
+            &wasm_file_path("non_terminating.wasm", api_version.clone()),
+            api_version.clone(),
+        ),
+        api_version,
+        HandlerLimits {
+            fuel: Some(1_000_000),
+            ..Default::default()
+        },
+    )
+    .await
+    .0;
+    let res: Result<(), _> = instance
+        .get_func("loop")
+        .typed(&mut instance.store.as_context_mut())
+        .unwrap()
+        .call(&mut instance.store.as_context_mut(), ());
+    let err = res.unwrap_err();
+    assert!(format!("{err:?}").contains("all fuel consumed"), "{}", err);
+}
+
+#[tokio::test]
+async fn out_of_fuel_v0_0_4() {
+    test_out_of_fuel(API_VERSION_0_0_4).await;
+}
+
+#[tokio::test]
+async fn out_of_fuel_v0_0_5() {
+    test_out_of_fuel(API_VERSION_0_0_5).await;
+}
+
 async fn test_unbounded_recursion(api_version: Version) {
     let mut instance = test_module(
         "unboundedRecursion",
@@ -567,7 +567,7 @@ impl StoreValueKind {
         use self::store::Value;
 
         match value {
-            Value::String(_) => StoreValueKind::String,
+            Value::String(_) | Value::GeoPoint(_) => StoreValueKind::String,
             Value::Int(_) => StoreValueKind::Int,
             Value::Int8(_) => StoreValueKind::Int8,
             Value::Timestamp(_) => StoreValueKind::Timestamp,
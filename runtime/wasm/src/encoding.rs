@@ -0,0 +1,275 @@
+//! Address encodings that are common on chains other than Ethereum. The
+//! decoding functions return `None` for malformed input so that mappings
+//! can deal with addresses they can not parse
+use anyhow::{anyhow, Error};
+use blake2::{Blake2b512, Digest};
+use sha2::Sha256;
+
+const BASE58_CHECKSUM_LEN: usize = 4;
+
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+const BECH32_CHECKSUM_LEN: usize = 6;
+
+const SS58_PREFIX: &[u8] = b"SS58PRE";
+const SS58_CHECKSUM_LEN: usize = 2;
+/// The largest network prefix that SS58 can encode
+pub const SS58_MAX_PREFIX: u16 = 0b0011_1111_1111_1111;
+
+pub fn base58_decode(s: &str) -> Option<Vec<u8>> {
+    bs58::decode(s).into_vec().ok()
+}
+
+fn double_sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(Sha256::digest(data)).to_vec()
+}
+
+/// Base58 with a checksum of 4 bytes of the double SHA-256 of `data`, as
+/// used for Bitcoin addresses
+pub fn base58check_encode(data: &[u8]) -> String {
+    let mut bytes = data.to_vec();
+    bytes.extend_from_slice(&double_sha256(data)[..BASE58_CHECKSUM_LEN]);
+    bs58::encode(bytes).into_string()
+}
+
+pub fn base58check_decode(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = base58_decode(s)?;
+    if bytes.len() < BASE58_CHECKSUM_LEN {
+        return None;
+    }
+    let checksum = bytes.split_off(bytes.len() - BASE58_CHECKSUM_LEN);
+    (double_sha256(&bytes)[..BASE58_CHECKSUM_LEN] == checksum[..]).then_some(bytes)
+}
+
+fn bech32_polymod(values: impl Iterator<Item = u8>) -> u32 {
+    let mut chk: u32 = 1;
+    for value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in BECH32_GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+fn bech32_hrp_expand(hrp: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    hrp.iter()
+        .map(|c| c >> 5)
+        .chain(std::iter::once(0))
+        .chain(hrp.iter().map(|c| c & 31))
+}
+
+/// Regroup the `from` bit values in `data` into `to` bit values
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1 << to) - 1;
+    let max_acc = (1 << (from + to - 1)) - 1;
+    let mut result = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for value in data {
+        let value = *value as u32;
+        if value >> from != 0 {
+            return None;
+        }
+        acc = ((acc << from) | value) & max_acc;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return None;
+    }
+    Some(result)
+}
+
+fn check_hrp(hrp: &str) -> Result<(), Error> {
+    if hrp.is_empty() || hrp.len() > 83 || hrp.bytes().any(|c| !(33..=126).contains(&c)) {
+        return Err(anyhow!("`{}` is not a valid bech32 prefix", hrp));
+    }
+    Ok(())
+}
+
+/// Encode `data` with the BIP-173 bech32 encoding and the human readable
+/// part `hrp`, as used for Cosmos addresses
+pub fn bech32_encode(hrp: &str, data: &[u8]) -> Result<String, Error> {
+    check_hrp(hrp)?;
+    let hrp = hrp.to_lowercase();
+    let data = convert_bits(data, 8, 5, true).expect("bytes can be regrouped");
+
+    let values = bech32_hrp_expand(hrp.as_bytes())
+        .chain(data.iter().copied())
+        .chain([0; BECH32_CHECKSUM_LEN]);
+    let polymod = bech32_polymod(values) ^ 1;
+    let checksum = (0..BECH32_CHECKSUM_LEN).map(|i| ((polymod >> (5 * (5 - i))) & 31) as u8);
+
+    let mut s = hrp;
+    s.push('1');
+    s.extend(
+        data.iter()
+            .copied()
+            .chain(checksum)
+            .map(|value| BECH32_CHARSET[value as usize] as char),
+    );
+    Ok(s)
+}
+
+/// Decode a bech32 string whose human readable part must be `hrp`
+pub fn bech32_decode(s: &str, hrp: &str) -> Option<Vec<u8>> {
+    if s.chars().any(|c| c.is_lowercase()) && s.chars().any(|c| c.is_uppercase()) {
+        return None;
+    }
+    let s = s.to_lowercase();
+    let (actual_hrp, data) = s.rsplit_once('1')?;
+    if actual_hrp != hrp.to_lowercase() || check_hrp(actual_hrp).is_err() {
+        return None;
+    }
+    if data.len() < BECH32_CHECKSUM_LEN {
+        return None;
+    }
+    let values = data
+        .bytes()
+        .map(|c| {
+            BECH32_CHARSET
+                .iter()
+                .position(|d| *d == c)
+                .map(|pos| pos as u8)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let polymod =
+        bech32_polymod(bech32_hrp_expand(actual_hrp.as_bytes()).chain(values.iter().copied()));
+    if polymod != 1 {
+        return None;
+    }
+    convert_bits(&values[..values.len() - BECH32_CHECKSUM_LEN], 5, 8, false)
+}
+
+fn ss58_checksum(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Blake2b512::new();
+    hasher.update(SS58_PREFIX);
+    hasher.update(data);
+    hasher.finalize()[..SS58_CHECKSUM_LEN].to_vec()
+}
+
+/// Encode the 32 or 33 byte `account` as an SS58 address for the network
+/// with the given `prefix`, as used by Substrate based chains
+pub fn ss58_encode(account: &[u8], prefix: u16) -> Result<String, Error> {
+    if account.len() != 32 && account.len() != 33 {
+        return Err(anyhow!(
+            "SS58 addresses must be for 32 or 33 bytes but got {} bytes",
+            account.len()
+        ));
+    }
+    let mut bytes = match prefix {
+        0..=63 => vec![prefix as u8],
+        64..=SS58_MAX_PREFIX => {
+            let first = ((prefix & 0b0000_0000_1111_1100) as u8) >> 2;
+            let second = ((prefix >> 8) as u8) | ((prefix & 0b0000_0000_0000_0011) as u8) << 6;
+            vec![first | 0b0100_0000, second]
+        }
+        _ => {
+            return Err(anyhow!(
+                "the SS58 prefix {} is larger than the maximum {}",
+                prefix,
+                SS58_MAX_PREFIX
+            ))
+        }
+    };
+    bytes.extend_from_slice(account);
+    let checksum = ss58_checksum(&bytes);
+    bytes.extend(checksum);
+    Ok(bs58::encode(bytes).into_string())
+}
+
+/// Decode an SS58 address into the network prefix and the account
+pub fn ss58_decode(s: &str) -> Option<(u16, Vec<u8>)> {
+    let bytes = base58_decode(s)?;
+    let (prefix, prefix_len) = match *bytes.first()? {
+        first @ 0..=63 => (first as u16, 1),
+        first @ 64..=127 => {
+            let second = *bytes.get(1)?;
+            let lower = (first << 2) | (second >> 6);
+            let upper = second & 0b0011_1111;
+            (lower as u16 | (upper as u16) << 8, 2)
+        }
+        _ => return None,
+    };
+    let account_len = bytes.len().checked_sub(prefix_len + SS58_CHECKSUM_LEN)?;
+    if account_len != 32 && account_len != 33 {
+        return None;
+    }
+    let (data, checksum) = bytes.split_at(prefix_len + account_len);
+    if ss58_checksum(data) != checksum {
+        return None;
+    }
+    Some((prefix, data[prefix_len..].to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base58check() {
+        let address = base58check_encode(&[0; 21]);
+        assert_eq!("1111111111111111111114oLvT2", address);
+        assert_eq!(Some(vec![0; 21]), base58check_decode(&address));
+        assert_eq!(None, base58check_decode("1111111111111111111114oLvT3"));
+        assert_eq!(None, base58check_decode("0OIl"));
+    }
+
+    #[test]
+    fn bech32() {
+        assert_eq!("a12uel5l", bech32_encode("a", &[]).unwrap());
+        assert_eq!(Some(vec![]), bech32_decode("A12UEL5L", "a"));
+        assert_eq!(None, bech32_decode("a12uel5l", "b"));
+        assert_eq!(None, bech32_decode("a12ueL5l", "a"));
+
+        let data: Vec<u8> = (0..20).collect();
+        let address = bech32_encode("cosmos", &data).unwrap();
+        assert!(address.starts_with("cosmos1"));
+        assert_eq!(Some(data), bech32_decode(&address, "cosmos"));
+
+        let mut corrupt = address.into_bytes();
+        let last = corrupt.len() - 1;
+        corrupt[last] = if corrupt[last] == b'q' { b'p' } else { b'q' };
+        assert_eq!(
+            None,
+            bech32_decode(&String::from_utf8(corrupt).unwrap(), "cosmos")
+        );
+
+        assert!(bech32_encode("", &[]).is_err());
+    }
+
+    #[test]
+    fn ss58() {
+        let alice = hex::decode("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d")
+            .unwrap();
+
+        let generic = ss58_encode(&alice, 42).unwrap();
+        assert_eq!("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", generic);
+        assert_eq!(Some((42, alice.clone())), ss58_decode(&generic));
+
+        let polkadot = ss58_encode(&alice, 0).unwrap();
+        assert_eq!("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5", polkadot);
+
+        // Two byte prefixes
+        let address = ss58_encode(&alice, 1284).unwrap();
+        assert_eq!(Some((1284, alice.clone())), ss58_decode(&address));
+
+        assert!(ss58_encode(&alice[..31], 0).is_err());
+        assert!(ss58_encode(&alice, SS58_MAX_PREFIX + 1).is_err());
+        assert_eq!(
+            None,
+            ss58_decode("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQZ")
+        );
+    }
+}
@@ -1,7 +1,10 @@
 use std::{convert::TryInto, num::NonZeroU32};
 
+use anyhow::{anyhow, Error};
 use graph::runtime::gas::CONST_MAX_GAS_PER_HANDLER;
-use parity_wasm::elements::Instruction;
+use parity_wasm::elements::{
+    BulkInstruction, External, Instruction, Local, Module, SimdInstruction, Type, ValueType,
+};
 use wasm_instrument::gas_metering::{MemoryGrowCost, Rules};
 
 pub const GAS_COST_STORE: u32 = 2263;
@@ -145,6 +148,13 @@ impl Rules for GasRules {
             Return => 100,
             Drop => 100,
             SignExt(_) => 100,
+            // Bulk instructions that work on any number of bytes or table elements are charged
+            // for their length at runtime, see `meter_bulk`
+            Bulk(_) => GAS_COST_LOAD + GAS_COST_STORE,
+            // SIMD instructions work on 128 bits, so charge them like two 64 bit instructions
+            Simd(SimdInstruction::V128Load(_)) => 2 * GAS_COST_LOAD,
+            Simd(SimdInstruction::V128Store(_)) => 2 * GAS_COST_STORE,
+            Simd(_) => 2 * 100,
             Nop => 1,
             Unreachable => 1,
         };
@@ -166,3 +176,117 @@ impl Rules for GasRules {
         MemoryGrowCost::Linear(gas_per_page)
     }
 }
+
+/// The gas that a bulk instruction costs for each byte or table element it works on, or `None`
+/// if its cost does not depend on a length.
+fn bulk_cost_per_unit(instruction: &BulkInstruction) -> Option<u32> {
+    use BulkInstruction::*;
+    match instruction {
+        // A 64 bit load and store move 8 bytes
+        MemoryCopy | MemoryInit(_) => Some((GAS_COST_LOAD + GAS_COST_STORE) / 8),
+        MemoryFill => Some(GAS_COST_STORE / 8),
+        TableCopy | TableInit(_) => Some(GAS_COST_LOAD + GAS_COST_STORE),
+        MemoryDrop(_) | TableDrop(_) => None,
+    }
+}
+
+/// Charge bulk instructions for the number of bytes or table elements they work on. The gas
+/// injected for `GasRules` is computed before the module runs, so it can not depend on the
+/// length, which is only known at runtime.
+///
+/// This must run after `wasm_instrument::gas_metering::inject` since it passes the gas to the
+/// `gas` import that adds. Before each of these instructions, the length on top of the stack is
+/// copied into a new local, multiplied by the cost per unit and passed to `gas`. The gas is
+/// capped at `u32::MAX`, which is more than copying all of a 32 bit memory costs.
+pub fn meter_bulk(mut module: Module) -> Result<Module, Error> {
+    let is_metered = |instruction: &Instruction| match instruction {
+        Instruction::Bulk(bulk) => bulk_cost_per_unit(bulk).is_some(),
+        _ => false,
+    };
+    let uses_bulk = module.code_section().map_or(false, |code| {
+        code.bodies()
+            .iter()
+            .any(|body| body.code().elements().iter().any(is_metered))
+    });
+    if !uses_bulk {
+        return Ok(module);
+    }
+
+    // Module name "gas" must match. See also e3f03e62-40e4-4f8c-b4a1-d0375cca0b76
+    let gas_func = module
+        .import_section()
+        .and_then(|imports| {
+            imports
+                .entries()
+                .iter()
+                .filter(|entry| matches!(entry.external(), External::Function(_)))
+                .position(|entry| entry.module() == "gas" && entry.field() == "gas")
+        })
+        .ok_or_else(|| anyhow!("the `gas` import is missing"))?;
+    let gas_func = u32::try_from(gas_func)?;
+
+    let params = {
+        let types = module.type_section().map_or(&[][..], |types| types.types());
+        let funcs = module
+            .function_section()
+            .map_or(&[][..], |funcs| funcs.entries());
+        funcs
+            .iter()
+            .map(|func| match types.get(func.type_ref() as usize) {
+                Some(Type::Function(ty)) => Ok(ty.params().len()),
+                None => Err(anyhow!("function type {} does not exist", func.type_ref())),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    // Unwrap: `uses_bulk` found code
+    let bodies = module.code_section_mut().unwrap().bodies_mut();
+    for (body, params) in bodies.iter_mut().zip(params) {
+        if !body.code().elements().iter().any(is_metered) {
+            continue;
+        }
+
+        let locals = body
+            .locals()
+            .iter()
+            .map(|local| local.count() as usize)
+            .sum::<usize>();
+        let len_local = u32::try_from(params + locals)?;
+        let gas_local = len_local
+            .checked_add(1)
+            .ok_or_else(|| anyhow!("too many locals"))?;
+        body.locals_mut().push(Local::new(1, ValueType::I32));
+        body.locals_mut().push(Local::new(1, ValueType::I64));
+
+        let code = std::mem::take(body.code_mut().elements_mut());
+        let mut metered = Vec::with_capacity(code.len());
+        for instruction in code {
+            if let Instruction::Bulk(bulk) = &instruction {
+                if let Some(cost) = bulk_cost_per_unit(bulk) {
+                    use Instruction::*;
+                    let max = u32::MAX as i64;
+                    metered.extend([
+                        TeeLocal(len_local),
+                        GetLocal(len_local),
+                        I64ExtendUI32,
+                        I64Const(cost as i64),
+                        I64Mul,
+                        // min(gas, u32::MAX)
+                        TeeLocal(gas_local),
+                        I64Const(max),
+                        GetLocal(gas_local),
+                        I64Const(max),
+                        I64LtU,
+                        Select,
+                        I32WrapI64,
+                        Call(gas_func),
+                    ]);
+                }
+            }
+            metered.push(instruction);
+        }
+        *body.code_mut().elements_mut() = metered;
+    }
+
+    Ok(module)
+}
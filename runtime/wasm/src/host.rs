@@ -1,4 +1,5 @@
 use std::cmp::PartialEq;
+use std::collections::BTreeMap;
 use std::time::Instant;
 
 use async_trait::async_trait;
@@ -6,8 +7,12 @@ use graph::futures01::sync::mpsc::Sender;
 use graph::futures03::channel::oneshot::channel;
 
 use graph::blockchain::{BlockTime, Blockchain, HostFn, RuntimeAdapter};
-use graph::components::store::{EnsLookup, SubgraphFork};
-use graph::components::subgraph::{MappingError, SharedProofOfIndexing};
+use graph::components::store::{
+    DeploymentLogs, EnsLookup, HttpCache, SourceableStore, SubgraphFork,
+};
+use graph::components::subgraph::{HandlerLimits, MappingError, SharedProofOfIndexing};
+use graph::components::tracing::{self, KeyValue, TraceContext};
+use graph::data::store::scalar::BigDecimalPrecision;
 use graph::data_source::{
     DataSource, DataSourceTemplate, MappingTrigger, TriggerData, TriggerWithHandler,
 };
@@ -28,6 +33,10 @@ pub struct RuntimeHostBuilder<C: Blockchain> {
     runtime_adapter: Arc<dyn RuntimeAdapter<C>>,
     link_resolver: Arc<dyn LinkResolver>,
     ens_lookup: Arc<dyn EnsLookup>,
+    http_cache: Arc<dyn HttpCache>,
+    deployment_logs: Arc<dyn DeploymentLogs>,
+    /// The deployments that subgraph data sources use as their source
+    source_stores: Arc<BTreeMap<DeploymentHash, Arc<dyn SourceableStore>>>,
 }
 
 impl<C: Blockchain> Clone for RuntimeHostBuilder<C> {
@@ -36,6 +45,9 @@ impl<C: Blockchain> Clone for RuntimeHostBuilder<C> {
             runtime_adapter: self.runtime_adapter.cheap_clone(),
             link_resolver: self.link_resolver.cheap_clone(),
             ens_lookup: self.ens_lookup.cheap_clone(),
+            http_cache: self.http_cache.cheap_clone(),
+            deployment_logs: self.deployment_logs.cheap_clone(),
+            source_stores: self.source_stores.cheap_clone(),
         }
     }
 }
@@ -45,11 +57,17 @@ impl<C: Blockchain> RuntimeHostBuilder<C> {
         runtime_adapter: Arc<dyn RuntimeAdapter<C>>,
         link_resolver: Arc<dyn LinkResolver>,
         ens_lookup: Arc<dyn EnsLookup>,
+        http_cache: Arc<dyn HttpCache>,
+        deployment_logs: Arc<dyn DeploymentLogs>,
+        source_stores: Arc<BTreeMap<DeploymentHash, Arc<dyn SourceableStore>>>,
     ) -> Self {
         RuntimeHostBuilder {
             runtime_adapter,
             link_resolver,
             ens_lookup,
+            http_cache,
+            deployment_logs,
+            source_stores,
         }
     }
 }
@@ -66,6 +84,7 @@ where
         logger: Logger,
         subgraph_id: DeploymentHash,
         metrics: Arc<HostMetrics>,
+        limits: &HandlerLimits,
     ) -> Result<Sender<Self::Req>, Error> {
         let experimental_features = ExperimentalFeatures {
             allow_non_deterministic_ipfs: ENV_VARS.mappings.allow_non_deterministic_ipfs,
@@ -76,7 +95,7 @@ where
             subgraph_id,
             metrics,
             tokio::runtime::Handle::current(),
-            ENV_VARS.mappings.timeout,
+            limits,
             experimental_features,
         )
     }
@@ -89,7 +108,26 @@ where
         templates: Arc<Vec<DataSourceTemplate<C>>>,
         mapping_request_sender: Sender<WasmRequest<C>>,
         metrics: Arc<HostMetrics>,
+        big_decimal_precision: BigDecimalPrecision,
+        prunes_history: bool,
+        mapping_env: Arc<BTreeMap<String, String>>,
     ) -> Result<Self::Host, Error> {
+        let source_store = match &data_source {
+            DataSource::Subgraph(ds) => Some(
+                self.source_stores
+                    .get(&ds.source.address)
+                    .cloned()
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "the source deployment `{}` of data source `{}` is not available",
+                            ds.source.address,
+                            ds.name
+                        )
+                    })?,
+            ),
+            DataSource::Onchain(_) | DataSource::Offchain(_) => None,
+        };
+
         RuntimeHost::new(
             self.runtime_adapter.cheap_clone(),
             self.link_resolver.clone(),
@@ -100,6 +138,12 @@ where
             mapping_request_sender,
             metrics,
             self.ens_lookup.cheap_clone(),
+            self.http_cache.cheap_clone(),
+            self.deployment_logs.cheap_clone(),
+            source_store,
+            big_decimal_precision,
+            prunes_history,
+            mapping_env,
         )
     }
 }
@@ -126,6 +170,12 @@ where
         mapping_request_sender: Sender<WasmRequest<C>>,
         metrics: Arc<HostMetrics>,
         ens_lookup: Arc<dyn EnsLookup>,
+        http_cache: Arc<dyn HttpCache>,
+        deployment_logs: Arc<dyn DeploymentLogs>,
+        source_store: Option<Arc<dyn SourceableStore>>,
+        big_decimal_precision: BigDecimalPrecision,
+        prunes_history: bool,
+        mapping_env: Arc<BTreeMap<String, String>>,
     ) -> Result<Self, Error> {
         let ds_details = DataSourceDetails::from_data_source(
             &data_source,
@@ -140,6 +190,12 @@ where
             ds_details,
             link_resolver,
             ens_lookup,
+            http_cache,
+            deployment_logs,
+            source_store,
+            big_decimal_precision,
+            prunes_history,
+            mapping_env,
         ));
 
         let host_fns = data_source
@@ -178,6 +234,7 @@ where
             "data_source" => &self.data_source.name(),
         );
 
+        let handler_cx = handler_span(&handler, self.data_source.name());
         let (result_sender, result_receiver) = channel();
         let start_time = Instant::now();
         let metrics = self.metrics.clone();
@@ -222,6 +279,8 @@ where
             "gas_used" => gas_used.to_string(),
         );
 
+        end_handler_span(&handler_cx, &result);
+
         // Discard the gas value
         result.map(|(block_state, _)| block_state)
     }
@@ -245,6 +304,7 @@ where
             "data_source" => &self.data_source.name(),
         );
 
+        let handler_cx = handler_span(&handler, self.data_source.name());
         let (result_sender, result_receiver) = channel();
         let start_time = Instant::now();
         let metrics = self.metrics.clone();
@@ -290,11 +350,36 @@ where
             "gas_used" => gas_used.to_string(),
         );
 
+        end_handler_span(&handler_cx, &result);
+
         // Discard the gas value
         result.map(|(block_state, _)| block_state)
     }
 }
 
+/// The span for running `handler` in the WASM module; the module runs on a
+/// thread of its own, so the span covers waiting for the result
+fn handler_span(handler: &str, data_source: &str) -> TraceContext {
+    tracing::child_span(
+        "handler",
+        vec![
+            KeyValue::new("handler", handler.to_string()),
+            KeyValue::new("data_source", data_source.to_string()),
+        ],
+    )
+}
+
+fn end_handler_span(cx: &TraceContext, result: &Result<(BlockState, Gas), MappingError>) {
+    match result {
+        Ok((_, gas)) => {
+            tracing::set_attributes(cx, vec![KeyValue::new("gas_used", gas.to_string())])
+        }
+        Err(MappingError::PossibleReorg(e) | MappingError::Unknown(e)) => {
+            tracing::record_error(cx, e)
+        }
+    }
+}
+
 #[async_trait]
 impl<C: Blockchain> RuntimeHostTrait<C> for RuntimeHost<C> {
     fn data_source(&self) -> &DataSource<C> {
@@ -364,14 +449,14 @@ impl<C: Blockchain> RuntimeHostTrait<C> for RuntimeHost<C> {
     /// trigger has been processed.
     fn done_at(&self) -> Option<BlockNumber> {
         match self.data_source() {
-            DataSource::Onchain(_) => None,
+            DataSource::Onchain(_) | DataSource::Subgraph(_) => None,
             DataSource::Offchain(ds) => ds.done_at(),
         }
     }
 
     fn set_done_at(&self, block: Option<BlockNumber>) {
         match self.data_source() {
-            DataSource::Onchain(_) => {}
+            DataSource::Onchain(_) | DataSource::Subgraph(_) => {}
             DataSource::Offchain(ds) => ds.set_done_at(block),
         }
     }
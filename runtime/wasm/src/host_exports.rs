@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::Deref;
 use std::str::FromStr;
 use std::time::{Duration, Instant};
@@ -7,32 +7,45 @@ use graph::data::subgraph::API_VERSION_0_0_8;
 use graph::data::value::Word;
 
 use graph::futures03::stream::StreamExt;
+use graph::ipfs::IpfsError;
 use graph::schema::EntityType;
+use graph::url::Url;
 use never::Never;
 use semver::Version;
 use web3::types::H160;
 
 use graph::blockchain::BlockTime;
 use graph::blockchain::Blockchain;
-use graph::components::store::{EnsLookup, GetScope, LoadRelatedRequest};
+use graph::components::store::{
+    DeploymentLog, DeploymentLogs, DerivedEntityFilter, EnsLookup, GetScope, HttpCache,
+    LoadRelatedRequest, SourceableStore,
+};
 use graph::components::subgraph::{
     InstanceDSTemplate, PoICausalityRegion, ProofOfIndexingEvent, SharedProofOfIndexing,
 };
+use graph::data::store::scalar::BigDecimalPrecision;
 use graph::data::store::{self};
 use graph::data_source::{CausalityRegion, DataSource, EntityTypeAccess};
 use graph::ensure;
 use graph::prelude::ethabi::param_type::Reader;
-use graph::prelude::ethabi::{decode, encode, Token};
+use graph::prelude::ethabi::{decode, encode, ParamType, Token};
 use graph::prelude::serde_json;
 use graph::prelude::{slog::b, slog::record_static, *};
 use graph::runtime::gas::{self, complexity, Gas, GasCounter};
 pub use graph::runtime::{DeterministicHostError, HostExportError};
 
+use crate::encoding;
 use crate::module::WasmInstance;
 use crate::{error::DeterminismLevel, module::IntoTrap};
 
 use super::module::WasmInstanceData;
 
+/// Whether `e` is a network error that happened while talking to IPFS
+fn is_networking_error(e: &Error) -> bool {
+    e.downcast_ref::<IpfsError>()
+        .map_or(false, |e| e.is_networking() || e.is_timeout())
+}
+
 fn write_poi_event(
     proof_of_indexing: &SharedProofOfIndexing,
     poi_event: &ProofOfIndexingEvent,
@@ -67,6 +80,23 @@ pub struct HostExports {
     poi_causality_region: String,
     pub(crate) link_resolver: Arc<dyn LinkResolver>,
     ens_lookup: Arc<dyn EnsLookup>,
+    /// The responses to `http.get` requests that were made so far
+    http_cache: Arc<dyn HttpCache>,
+    /// Where the messages that mappings log are kept for developers
+    deployment_logs: Arc<dyn DeploymentLogs>,
+    /// The deployment that a subgraph data source uses as its source;
+    /// `None` for all other data sources
+    source_store: Option<Arc<dyn SourceableStore>>,
+    /// The precision and rounding the subgraph manifest asks for when
+    /// creating `BigDecimal` values
+    pub(crate) big_decimal_precision: BigDecimalPrecision,
+    /// Whether the manifest lets the store prune the history of the
+    /// deployment, either with `indexerHints.prune` or with a `retention`
+    /// for some entity type
+    prunes_history: bool,
+    /// The constants that the operator set for the deployment and that
+    /// mappings read with `dataSource.env`
+    mapping_env: Arc<BTreeMap<String, String>>,
 }
 
 pub struct DataSourceDetails {
@@ -77,6 +107,7 @@ pub struct DataSourceDetails {
     pub entity_type_access: EntityTypeAccess,
     pub templates: Arc<Vec<InstanceDSTemplate>>,
     pub causality_region: CausalityRegion,
+    pub is_offchain: bool,
 }
 
 impl DataSourceDetails {
@@ -92,6 +123,7 @@ impl DataSourceDetails {
             entity_type_access: ds.entities(),
             templates,
             causality_region: ds.causality_region(),
+            is_offchain: ds.is_offchain(),
         }
     }
 }
@@ -103,6 +135,12 @@ impl HostExports {
         data_source_details: DataSourceDetails,
         link_resolver: Arc<dyn LinkResolver>,
         ens_lookup: Arc<dyn EnsLookup>,
+        http_cache: Arc<dyn HttpCache>,
+        deployment_logs: Arc<dyn DeploymentLogs>,
+        source_store: Option<Arc<dyn SourceableStore>>,
+        big_decimal_precision: BigDecimalPrecision,
+        prunes_history: bool,
+        mapping_env: Arc<BTreeMap<String, String>>,
     ) -> Self {
         Self {
             subgraph_id,
@@ -111,6 +149,12 @@ impl HostExports {
             subgraph_network,
             link_resolver,
             ens_lookup,
+            http_cache,
+            deployment_logs,
+            source_store,
+            big_decimal_precision,
+            prunes_history,
+            mapping_env,
         }
     }
 
@@ -322,6 +366,25 @@ impl HostExports {
             &key.entity_type,
         )?;
 
+        // Mappings have no native representation for points and set
+        // `GeoPoint` fields to strings of the form `latitude,longitude`
+        for field in entity_type.object_type()?.fields.iter() {
+            if field.value_type != ValueType::GeoPoint {
+                continue;
+            }
+            if let Some(value) = data.remove(&field.name) {
+                let value = value.into_geo_point().map_err(|e| {
+                    HostExportError::Deterministic(anyhow!(
+                        "Invalid value for `{}.{}`: {}",
+                        key.entity_type,
+                        field.name,
+                        e
+                    ))
+                })?;
+                data.insert(field.name.clone(), value);
+            }
+        }
+
         // Filter out fields that are not in the schema
         let filtered_entity_data = data.into_iter().filter(|(field_name, _)| {
             state
@@ -424,6 +487,144 @@ impl HostExports {
         Ok(result)
     }
 
+    /// Get the entity with `entity_id` as it was once `block` had been
+    /// processed. `block` must not be after the block that is currently
+    /// being processed. Subgraphs whose manifest asks for pruning can not
+    /// use this at all, since whether the history for `block` is still
+    /// there would depend on when the store pruned it
+    pub(crate) fn store_get_at_block(
+        &self,
+        state: &mut BlockState,
+        entity_type: String,
+        entity_id: String,
+        block: BlockNumber,
+        current_block: BlockNumber,
+        gas: &GasCounter,
+    ) -> Result<Option<Arc<Entity>>, HostExportError> {
+        if block < 0 || block > current_block {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "store.getAtBlock can only get entities as of blocks between 0 and the \
+                 current block {}, but was asked for block {}",
+                current_block,
+                block
+            )));
+        }
+        if self.prunes_history {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "store.getAtBlock can not be used by subgraphs that prune their history \
+                 with `indexerHints.prune` or a `retention` of an entity type"
+            )));
+        }
+        if block == current_block {
+            return self
+                .store_get(state, entity_type, entity_id, gas, GetScope::Store)
+                .map_err(HostExportError::from);
+        }
+
+        let entity_type = state.entity_cache.schema.entity_type(&entity_type)?;
+        Self::expect_object_type(&entity_type, "get")?;
+
+        let store_key = entity_type.parse_key_in(entity_id, self.data_source.causality_region)?;
+        self.check_entity_type_access(&store_key.entity_type)?;
+
+        let result = state
+            .entity_cache
+            .store
+            .get_at_block(&store_key, block)
+            .map_err(|e| HostExportError::Unknown(e.into()))?
+            .map(Arc::new);
+
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::STORE_GET.with_args(
+                complexity::Linear,
+                (&store_key, result.as_ref().map(|e| e.as_ref())),
+            ),
+            "store_get_at_block",
+        )?;
+
+        if let Some(ref entity) = result {
+            state.metrics.track_entity_read(&entity_type, &entity)
+        }
+
+        Ok(result)
+    }
+
+    pub(crate) fn store_get_many(
+        &self,
+        state: &mut BlockState,
+        entity_type: String,
+        entity_ids: Vec<String>,
+        gas: &GasCounter,
+    ) -> Result<Vec<Option<Arc<Entity>>>, anyhow::Error> {
+        let entity_type = state.entity_cache.schema.entity_type(&entity_type)?;
+        Self::expect_object_type(&entity_type, "get")?;
+        self.check_entity_type_access(&entity_type)?;
+
+        let keys = entity_ids
+            .into_iter()
+            .map(|id| entity_type.parse_key_in(id, self.data_source.causality_region))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let result = state.entity_cache.get_many(&keys)?;
+
+        for (key, entity) in keys.iter().zip(result.iter()) {
+            Self::track_gas_and_ops(
+                gas,
+                state,
+                gas::STORE_GET.with_args(
+                    complexity::Linear,
+                    (key, entity.as_ref().map(|e| e.as_ref())),
+                ),
+                "store_get_many",
+            )?;
+
+            if let Some(entity) = entity {
+                state.metrics.track_entity_read(&entity_type, entity)
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Look up an entity in the source of a subgraph data source as of
+    /// `block`, the block that is being processed
+    pub(crate) fn store_get_in_source(
+        &self,
+        state: &mut BlockState,
+        entity_type: String,
+        entity_id: String,
+        block: BlockNumber,
+        gas: &GasCounter,
+    ) -> Result<Option<Entity>, HostExportError> {
+        let source = self.source_store.as_ref().ok_or_else(|| {
+            HostExportError::Deterministic(anyhow!(
+                "`store.get_in_source` can only be used in handlers of subgraph data sources"
+            ))
+        })?;
+
+        let entity_type = source
+            .input_schema()
+            .entity_type(&entity_type)
+            .map_err(HostExportError::Deterministic)?;
+        Self::expect_object_type(&entity_type, "get_in_source")?;
+        let key = entity_type
+            .parse_key_in(entity_id, CausalityRegion::ONCHAIN)
+            .map_err(HostExportError::Deterministic)?;
+
+        let result = source.get(&key, block).map_err(anyhow::Error::from)?;
+
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::STORE_GET.with_args(complexity::Linear, (&key, result.as_ref())),
+            "store_get_in_source",
+        )?;
+
+        Ok(result)
+    }
+
     pub(crate) fn store_load_related(
         &self,
         state: &mut BlockState,
@@ -456,6 +657,72 @@ impl HostExports {
         Ok(result)
     }
 
+    /// Like `store_load_related`, but only return the entities that match
+    /// `filter`, in its order
+    pub(crate) fn store_load_related_filtered(
+        &self,
+        state: &mut BlockState,
+        entity_type: String,
+        entity_id: String,
+        entity_field: String,
+        filter: DerivedEntityFilter,
+        gas: &GasCounter,
+    ) -> Result<Vec<Entity>, anyhow::Error> {
+        let entity_type = state.entity_cache.schema.entity_type(&entity_type)?;
+        let key = entity_type.parse_key_in(entity_id, self.data_source.causality_region)?;
+        let store_key = LoadRelatedRequest {
+            entity_type: key.entity_type,
+            entity_id: key.entity_id,
+            entity_field: entity_field.into(),
+            causality_region: self.data_source.causality_region,
+        };
+        self.check_entity_type_access(&store_key.entity_type)?;
+
+        let schema = &state.entity_cache.schema;
+        let (related_type, _) = schema.get_field_related(&store_key)?;
+        let related_field = |attr: &str| -> Result<_, anyhow::Error> {
+            match related_type.field(attr) {
+                Some(field) if !field.is_derived() => Ok(field),
+                _ => Err(anyhow!(
+                    "Can not filter related entities of type `{}` by `{}` since it is not an attribute of that type",
+                    related_type,
+                    attr
+                )),
+            }
+        };
+        for (attr, _) in &filter.values {
+            related_field(attr)?;
+        }
+        if let Some(attr) = &filter.order_by {
+            let field = related_field(attr)?;
+            if field.is_list()
+                || field.value_type == ValueType::GeoPoint
+                || schema.is_enum_type(field.field_type.get_base_type())
+            {
+                return Err(anyhow!(
+                    "Can not order related entities of type `{}` by `{}` since it is a list, enum or geo point attribute",
+                    related_type,
+                    attr
+                ));
+            }
+        }
+
+        let result = state
+            .entity_cache
+            .load_related_filtered(&store_key, &filter)?;
+
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::STORE_GET.with_args(complexity::Linear, (&store_key, &result)),
+            "store_load_related_filtered",
+        )?;
+
+        state.metrics.track_entity_read_batch(&entity_type, &result);
+
+        Ok(result)
+    }
+
     /// Prints the module of `n` in hex.
     /// Integers are encoded using the least amount of digits (no leading zero digits).
     /// Their encoding may be of uneven length. The number zero encodes as "0x0".
@@ -503,13 +770,73 @@ impl HostExports {
         graph::block_on(self.link_resolver.get_block(logger, &Link { link }))
     }
 
-    // Read the IPFS file `link`, split it into JSON objects, and invoke the
-    // exported function `callback` on each JSON object. The successful return
-    // value contains the block state produced by each callback invocation. Each
-    // invocation of `callback` happens in its own instance of a WASM module,
-    // which is identical to `module` when it was first started. The signature
-    // of the callback must be `callback(JSONValue, Value)`, and the `userData`
-    // parameter is passed to the callback without any changes
+    /// Fetch `url` for a handler of an offchain data source. The host of
+    /// `url` must be on `GRAPH_HTTP_GET_ALLOWED_HOSTS`. The first response
+    /// for a URL is stored, and all later requests for that URL from the
+    /// same deployment and for the same content of the data source return
+    /// the stored response
+    pub(crate) fn http_get(
+        &self,
+        logger: &Logger,
+        url: String,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Vec<u8>, HostExportError> {
+        if !self.data_source.is_offchain {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "`http.get` can only be used in handlers of offchain data sources, \
+                 but data source `{}` is not one",
+                self.data_source.name
+            )));
+        }
+        let url = check_http_get_url(&url, &ENV_VARS.mappings.http_get_allowed_hosts)?;
+        // The content hash, or other source, of the offchain data source
+        let source = String::from_utf8_lossy(&self.data_source.address);
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, url.as_str()),
+            "http_get",
+        )?;
+
+        let cached = self
+            .http_cache
+            .get(&self.subgraph_id, &source, url.as_str())
+            .map_err(|e| HostExportError::Unknown(e.into()))?;
+        let body = match cached {
+            Some(body) => body,
+            None => {
+                debug!(logger, "Fetching URL for http.get"; "url" => url.as_str());
+                let body = graph::block_on(http_get_fetch(url.clone()))?;
+                self.http_cache
+                    .insert(&self.subgraph_id, &source, url.as_str(), body)
+                    .map_err(|e| HostExportError::Unknown(e.into()))?
+            }
+        };
+
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &body),
+            "http_get",
+        )?;
+        Ok(body)
+    }
+
+    // Read the IPFS file `link`, split it into JSON objects, or into the
+    // blocks of a CAR file with the `car` flag, and invoke the exported
+    // function `callback` on each of them. The successful return value
+    // contains the block state produced by all callback invocations and the
+    // number of invocations. Each invocation of `callback` happens in its own
+    // instance of a WASM module, which is identical to `module` when it was
+    // first started. The signature of the callback must be
+    // `callback(JSONValue, Value)`, and the `userData` parameter is passed to
+    // the callback without any changes.
+    //
+    // The file is streamed and the state of each invocation is merged as soon
+    // as it finishes, so that memory use does not grow with the size of the
+    // file. If reading the file fails because of a network error, reading
+    // resumes where it left off
     pub(crate) fn ipfs_map(
         link_resolver: &Arc<dyn LinkResolver>,
         wasm_ctx: &WasmInstanceData,
@@ -517,15 +844,20 @@ impl HostExports {
         callback: &str,
         user_data: store::Value,
         flags: Vec<String>,
-    ) -> Result<Vec<BlockState>, anyhow::Error> {
+    ) -> Result<(BlockState, usize), anyhow::Error> {
         // Does not consume gas because this is not a part of deterministic APIs.
         // Ideally we would consume gas the same as ipfs_cat and then share
         // gas across the spawned modules for callbacks.
 
         const JSON_FLAG: &str = "json";
+        const CAR_FLAG: &str = "car";
+        // How often reading the file may be resumed after network errors
+        const MAX_RESUMES: usize = 10;
+
+        let car = flags.iter().any(|flag| flag == CAR_FLAG);
         ensure!(
-            flags.contains(&JSON_FLAG.to_string()),
-            "Flags must contain 'json'"
+            car || flags.iter().any(|flag| flag == JSON_FLAG),
+            "Flags must contain 'json' or 'car'"
         );
 
         let host_metrics = wasm_ctx.host_metrics.clone();
@@ -541,13 +873,41 @@ impl HostExports {
         let start = Instant::now();
         let mut last_log = start;
         let logger = ctx.logger.new(o!("ipfs_map" => link.clone()));
+        let link = Link { link };
+
+        let open = || {
+            let stream = match car {
+                true => link_resolver.car_stream(&logger, &link),
+                false => link_resolver.json_stream(&logger, &link),
+            };
+            graph::block_on(stream)
+        };
+
+        let mut run = || -> Result<(BlockState, usize), Error> {
+            let mut state = ctx.derive_with_empty_block_state().state;
+            let mut calls = 0;
+            let mut last_line = 0;
+            let mut resumes = 0;
 
-        let result = {
-            let mut stream: JsonValueStream =
-                graph::block_on(link_resolver.json_stream(&logger, &Link { link }))?;
-            let mut v = Vec::new();
+            let mut stream: JsonValueStream = open()?;
             while let Some(sv) = graph::block_on(stream.next()) {
-                let sv = sv?;
+                let sv = match sv {
+                    Ok(sv) => sv,
+                    Err(e) if is_networking_error(&e) && resumes < MAX_RESUMES => {
+                        resumes += 1;
+                        warn!(logger, "Reading the file failed, resuming";
+                            "line" => last_line,
+                            "error" => e.to_string());
+                        stream = open()?;
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+                // After resuming, skip what was processed already
+                if sv.line <= last_line {
+                    continue;
+                }
+
                 let module = WasmInstance::from_valid_module_with_ctx(
                     valid_module.clone(),
                     ctx.derive_with_empty_block_state(),
@@ -555,6 +915,10 @@ impl HostExports {
                     wasm_ctx.experimental_features,
                 )?;
                 let result = module.handle_json_callback(&callback, &sv.value, &user_data)?;
+                state.extend(result);
+                calls += 1;
+                last_line = sv.line;
+
                 // Log progress every 15s
                 if last_log.elapsed() > Duration::from_secs(15) {
                     debug!(
@@ -565,11 +929,10 @@ impl HostExports {
                     );
                     last_log = Instant::now();
                 }
-                v.push(result)
             }
-            Ok(v)
+            Ok((state, calls))
         };
-        result.map_err(move |e: Error| anyhow::anyhow!("{}: {}", errmsg, e.to_string()))
+        run().map_err(move |e: Error| anyhow::anyhow!("{}: {}", errmsg, e.to_string()))
     }
 
     /// Expects a decimal string.
@@ -664,6 +1027,95 @@ impl HostExports {
         Ok(tiny_keccak::keccak256(data))
     }
 
+    pub(crate) fn crypto_sha512(
+        &self,
+        input: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        use sha2::Digest;
+
+        let data = &input[..];
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, data),
+            "crypto_sha512",
+        )?;
+        Ok(sha2::Sha512::digest(data).to_vec())
+    }
+
+    pub(crate) fn crypto_blake2b256(
+        &self,
+        input: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        use blake2::Digest;
+
+        let data = &input[..];
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, data),
+            "crypto_blake2b256",
+        )?;
+        Ok(blake2::Blake2b::<blake2::digest::consts::U32>::digest(data).to_vec())
+    }
+
+    pub(crate) fn crypto_blake2b512(
+        &self,
+        input: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        use blake2::Digest;
+
+        let data = &input[..];
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, data),
+            "crypto_blake2b512",
+        )?;
+        Ok(blake2::Blake2b512::digest(data).to_vec())
+    }
+
+    pub(crate) fn crypto_secp256k1_recover(
+        &self,
+        hash: Vec<u8>,
+        signature: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<[u8; 65]>, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::SIGNATURE_CHECK.with_args(complexity::Size, &signature[..]),
+            "crypto_secp256k1_recover",
+        )?;
+
+        secp256k1_recover(&hash, &signature)
+    }
+
+    pub(crate) fn crypto_ed25519_verify(
+        &self,
+        public_key: Vec<u8>,
+        message: Vec<u8>,
+        signature: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<bool, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::SIGNATURE_CHECK.with_args(complexity::Size, &message[..]),
+            "crypto_ed25519_verify",
+        )?;
+
+        Ok(ed25519_verify(&public_key, &message, &signature))
+    }
+
     pub(crate) fn big_int_plus(
         &self,
         x: BigInt,
@@ -871,6 +1323,131 @@ impl HostExports {
         Ok(::bs58::encode(&bytes).into_string())
     }
 
+    /// Returns `None` if `string` is not valid base58
+    pub(crate) fn base58_to_bytes(
+        &self,
+        string: String,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<Vec<u8>>, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &string),
+            "base58_to_bytes",
+        )?;
+        Ok(encoding::base58_decode(&string))
+    }
+
+    pub(crate) fn bytes_to_base58_check(
+        &self,
+        bytes: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<String, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes),
+            "bytes_to_base58_check",
+        )?;
+        Ok(encoding::base58check_encode(&bytes))
+    }
+
+    /// Returns `None` if `string` is not valid base58 or has the wrong
+    /// checksum
+    pub(crate) fn base58_check_to_bytes(
+        &self,
+        string: String,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<Vec<u8>>, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &string),
+            "base58_check_to_bytes",
+        )?;
+        Ok(encoding::base58check_decode(&string))
+    }
+
+    pub(crate) fn bytes_to_bech32(
+        &self,
+        hrp: String,
+        bytes: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<String, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes),
+            "bytes_to_bech32",
+        )?;
+        encoding::bech32_encode(&hrp, &bytes).map_err(DeterministicHostError::from)
+    }
+
+    /// Returns `None` if `string` is not valid bech32 or does not start
+    /// with the human readable part `hrp`
+    pub(crate) fn bech32_to_bytes(
+        &self,
+        string: String,
+        hrp: String,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<Vec<u8>>, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &string),
+            "bech32_to_bytes",
+        )?;
+        Ok(encoding::bech32_decode(&string, &hrp))
+    }
+
+    pub(crate) fn bytes_to_ss58(
+        &self,
+        bytes: Vec<u8>,
+        prefix: u32,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<String, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes),
+            "bytes_to_ss58",
+        )?;
+        let prefix = u16::try_from(prefix)
+            .ok()
+            .filter(|prefix| *prefix <= encoding::SS58_MAX_PREFIX)
+            .ok_or_else(|| {
+                anyhow!(
+                    "the SS58 prefix {} is larger than the maximum {}",
+                    prefix,
+                    encoding::SS58_MAX_PREFIX
+                )
+            })?;
+        encoding::ss58_encode(&bytes, prefix).map_err(DeterministicHostError::from)
+    }
+
+    /// Returns the account for an SS58 address regardless of its network
+    /// prefix, or `None` if `string` is not a valid SS58 address
+    pub(crate) fn ss58_to_bytes(
+        &self,
+        string: String,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<Vec<u8>>, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &string),
+            "ss58_to_bytes",
+        )?;
+        Ok(encoding::ss58_decode(&string).map(|(_, account)| account))
+    }
+
     pub(crate) fn big_decimal_plus(
         &self,
         x: BigDecimal,
@@ -884,7 +1461,7 @@ impl HostExports {
             gas::BIG_MATH_GAS_OP.with_args(complexity::Linear, (&x, &y)),
             "big_decimal_plus",
         )?;
-        Ok(x + y)
+        Ok(x.add_with_precision(&y, &self.big_decimal_precision))
     }
 
     pub(crate) fn big_decimal_minus(
@@ -900,7 +1477,7 @@ impl HostExports {
             gas::BIG_MATH_GAS_OP.with_args(complexity::Linear, (&x, &y)),
             "big_decimal_minus",
         )?;
-        Ok(x - y)
+        Ok(x.sub_with_precision(&y, &self.big_decimal_precision))
     }
 
     pub(crate) fn big_decimal_times(
@@ -916,7 +1493,7 @@ impl HostExports {
             gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&x, &y)),
             "big_decimal_times",
         )?;
-        Ok(x * y)
+        Ok(x.mul_with_precision(&y, &self.big_decimal_precision))
     }
 
     /// Maximum precision of 100 decimal digits.
@@ -939,7 +1516,7 @@ impl HostExports {
                 x
             )));
         }
-        Ok(x / y)
+        Ok(x.div_with_precision(&y, &self.big_decimal_precision))
     }
 
     pub(crate) fn big_decimal_equals(
@@ -985,7 +1562,7 @@ impl HostExports {
             gas::DEFAULT_GAS_OP.with_args(complexity::Size, &s),
             "big_decimal_from_string",
         )?;
-        BigDecimal::from_str(&s)
+        BigDecimal::from_str_with_precision(&s, &self.big_decimal_precision)
             .with_context(|| format!("string  is not a BigDecimal: '{}'", s))
             .map_err(DeterministicHostError::from)
     }
@@ -1032,6 +1609,13 @@ impl HostExports {
             .map_err(DeterministicHostError::from)?
             .clone();
 
+        if let InstanceDSTemplate::Offchain(template) = &template {
+            template
+                .check_creation(&params, context.as_ref())
+                .with_context(|| format!("Failed to create data source from name `{}`", name))
+                .map_err(DeterministicHostError::from)?;
+        }
+
         // Remember that we need to create this data source
         state.push_created_data_source(InstanceDSTemplateInfo {
             template,
@@ -1062,6 +1646,8 @@ impl HostExports {
         logger: &Logger,
         level: slog::Level,
         msg: String,
+        block_number: BlockNumber,
+        handler: Option<&str>,
         gas: &GasCounter,
         state: &mut BlockState,
     ) -> Result<(), DeterministicHostError> {
@@ -1072,6 +1658,19 @@ impl HostExports {
             "log_log",
         )?;
 
+        // The store decides whether logs are kept at all. Storing logs is a
+        // convenience for developers and must never make a handler fail
+        let log = DeploymentLog {
+            level,
+            message: msg.clone(),
+            handler: handler.map(str::to_string),
+            block_number,
+            timestamp: chrono::Utc::now(),
+        };
+        if let Err(e) = self.deployment_logs.append(&self.subgraph_id, log) {
+            warn!(logger, "Failed to store mapping log"; "error" => e.to_string());
+        }
+
         let rs = record_static!(level, self.data_source.name.as_str());
 
         logger.log(&slog::Record::new(
@@ -1130,6 +1729,36 @@ impl HostExports {
         Ok(self.data_source.context.as_ref().clone())
     }
 
+    /// Look up the constant `key` that the operator set for the deployment.
+    /// Every read goes into the PoI since indexers might set different
+    /// values
+    pub(crate) fn data_source_env(
+        &self,
+        logger: &Logger,
+        proof_of_indexing: &SharedProofOfIndexing,
+        key: String,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<String>, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            Gas::new(gas::DEFAULT_BASE_COST),
+            "data_source_env",
+        )?;
+        let value = self.mapping_env.get(&key).cloned();
+        write_poi_event(
+            proof_of_indexing,
+            &ProofOfIndexingEvent::ReadEnv {
+                key: &key,
+                value: value.as_deref(),
+            },
+            &self.poi_causality_region,
+            logger,
+        );
+        Ok(value)
+    }
+
     pub(crate) fn json_from_bytes(
         &self,
         bytes: &Vec<u8>,
@@ -1156,6 +1785,21 @@ impl HostExports {
             .map_err(|e| DeterministicHostError::from(Error::from(e)))
     }
 
+    /// Like `json_from_bytes`, but numbers whose value is an integer are
+    /// written as plain decimal digits, e.g. `1.5e3` becomes `1500`, so
+    /// that mappings can convert them to a `BigInt` without going through
+    /// a float; other numbers are kept as they appear in `bytes`
+    pub(crate) fn json_from_bytes_preserve_numbers(
+        &self,
+        bytes: &Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<serde_json::Value, DeterministicHostError> {
+        let mut value = self.json_from_bytes(bytes, gas, state)?;
+        normalize_json_integers(&mut value);
+        Ok(value)
+    }
+
     pub(crate) fn string_to_h160(
         &self,
         string: &str,
@@ -1230,6 +1874,77 @@ impl HostExports {
             .map(|mut tokens| tokens.pop().unwrap())
             .context("Failed to decode")
     }
+
+    /// Encode `tokens` as the parameters of a function call with the
+    /// parameter `types`. Returns `None` if the tokens do not match them
+    pub(crate) fn ethereum_encode_tuple(
+        &self,
+        types: String,
+        tokens: Vec<Token>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<Vec<u8>>, DeterministicHostError> {
+        let param_types = match read_tuple_types(&types) {
+            Ok(param_types) => param_types,
+            Err(_) => return Ok(None),
+        };
+        if param_types.len() != tokens.len()
+            || !tokens
+                .iter()
+                .zip(&param_types)
+                .all(|(token, param_type)| token.type_check(param_type))
+        {
+            return Ok(None);
+        }
+
+        let encoded = encode(&tokens);
+
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &encoded),
+            "ethereum_encode_tuple",
+        )?;
+
+        Ok(Some(encoded))
+    }
+
+    /// Decode `data` as the parameters of a function call with the
+    /// parameter `types`. Returns `None` if `data` does not match them
+    pub(crate) fn ethereum_decode_tuple(
+        &self,
+        types: String,
+        data: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<Vec<Token>>, DeterministicHostError> {
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &data),
+            "ethereum_decode_tuple",
+        )?;
+
+        Ok(read_tuple_types(&types)
+            .ok()
+            .and_then(|param_types| decode(&param_types, &data).ok()))
+    }
+}
+
+/// Parse a comma separated list of ABI types like `address,(uint256,bytes)[]`.
+/// Unlike the type that `ethereum.decode` takes, the list is not a tuple
+/// itself; a list with a single tuple has to be given as `(address,bytes)`
+fn read_tuple_types(types: &str) -> Result<Vec<ParamType>, Error> {
+    let types = types.trim();
+    if types.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match Reader::read(&format!("({})", types)) {
+        Ok(ParamType::Tuple(param_types)) => Ok(param_types),
+        Ok(_) => Err(anyhow!("`{}` is not a list of types", types)),
+        Err(e) => Err(anyhow!("Failed to read types: {}", e)),
+    }
 }
 
 fn string_to_h160(string: &str) -> Result<H160, DeterministicHostError> {
@@ -1259,6 +1974,202 @@ fn bytes_to_string(logger: &Logger, bytes: Vec<u8>) -> String {
     s.trim_end_matches('\u{0000}').to_string()
 }
 
+/// The plain decimal digits of the JSON number `number` if its value is an
+/// integer, e.g. `-12` for `-1.20e1`. Returns `None` if the value is not an
+/// integer or if writing it out would take an unreasonable number of digits
+fn json_integer(number: &str) -> Option<String> {
+    const MAX_DIGITS: i64 = 1_000;
+
+    let (negative, number) = match number.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, number),
+    };
+    let (mantissa, exp) = match number.find(['e', 'E']) {
+        Some(pos) => (&number[..pos], number[pos + 1..].parse::<i64>().ok()?),
+        None => (number, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    // Huge exponents can not produce a reasonable number of digits, and
+    // would make the arithmetic below overflow
+    if exp > MAX_DIGITS + frac.len() as i64 {
+        return None;
+    }
+
+    // The value is `digits * 10^scale`
+    let mut digits = format!("{}{}", int, frac);
+    let scale = exp.checked_sub(frac.len() as i64)?;
+    if scale >= 0 {
+        if (digits.len() as i64).checked_add(scale)? > MAX_DIGITS {
+            return None;
+        }
+        digits.extend(std::iter::repeat('0').take(scale as usize));
+    } else {
+        let keep = digits.len().saturating_sub(scale.unsigned_abs() as usize);
+        if digits[keep..].bytes().any(|digit| digit != b'0') {
+            return None;
+        }
+        digits.truncate(keep);
+    }
+
+    let digits = match digits.trim_start_matches('0') {
+        "" => "0",
+        digits => digits,
+    };
+    if negative && digits != "0" {
+        Some(format!("-{}", digits))
+    } else {
+        Some(digits.to_string())
+    }
+}
+
+fn normalize_json_integers(value: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    match value {
+        Value::Number(number) => {
+            let integer = json_integer(&number.to_string())
+                .and_then(|integer| serde_json::Number::from_str(&integer).ok());
+            if let Some(integer) = integer {
+                *number = integer;
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(normalize_json_integers),
+        Value::Object(map) => map.values_mut().for_each(normalize_json_integers),
+        Value::Null | Value::Bool(_) | Value::String(_) => {}
+    }
+}
+
+/// Recover the uncompressed public key, 65 bytes starting with `0x04`, that
+/// produced `signature` for the 32 byte `hash`. The signature consists of
+/// `r`, `s` and the recovery id `v`, which can be 0, 1, 27 or 28. Returns
+/// `None` if the signature is invalid
+fn secp256k1_recover(
+    hash: &[u8],
+    signature: &[u8],
+) -> Result<Option<[u8; 65]>, DeterministicHostError> {
+    use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+
+    let message = secp256k1::Message::from_slice(hash).map_err(|_| {
+        DeterministicHostError::from(anyhow!(
+            "secp256k1Recover: the hash must be 32 bytes long but is {} bytes long",
+            hash.len()
+        ))
+    })?;
+    if signature.len() != 65 {
+        return Ok(None);
+    }
+    let v = match signature[64] {
+        v @ 0..=1 => v,
+        v @ 27..=28 => v - 27,
+        _ => return Ok(None),
+    };
+    let signature = RecoveryId::from_i32(v as i32)
+        .and_then(|id| RecoverableSignature::from_compact(&signature[..64], id));
+    let Ok(signature) = signature else {
+        return Ok(None);
+    };
+    let key = secp256k1::Secp256k1::verification_only()
+        .recover_ecdsa(&message, &signature)
+        .ok()
+        .map(|key| key.serialize_uncompressed());
+    Ok(key)
+}
+
+/// Check that `signature` is a valid ed25519 signature of `message` for the
+/// 32 byte `public_key`. Malleable signatures are rejected
+fn ed25519_verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let (Ok(public_key), Ok(signature)) = (
+        <[u8; 32]>::try_from(public_key),
+        <[u8; 64]>::try_from(signature),
+    ) else {
+        return false;
+    };
+    let Ok(public_key) = ed25519_dalek::VerifyingKey::from_bytes(&public_key) else {
+        return false;
+    };
+    let signature = ed25519_dalek::Signature::from_bytes(&signature);
+    public_key.verify_strict(message, &signature).is_ok()
+}
+
+/// The most redirects that `http.get` follows
+const HTTP_GET_MAX_REDIRECTS: usize = 10;
+
+/// Check that `url` is an HTTP(S) URL for one of the `allowed_hosts` and
+/// return it in its normalized form. Since the allowed hosts differ between
+/// indexers, a host that is not allowed is a non-deterministic error
+fn check_http_get_url(url: &str, allowed_hosts: &[String]) -> Result<Url, HostExportError> {
+    let url = Url::parse(url).map_err(|e| {
+        HostExportError::Deterministic(anyhow!("`{}` is not a valid URL: {}", url, e))
+    })?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "`http.get` only supports http and https URLs but got `{}`",
+            url
+        )));
+    }
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    if !allowed_hosts.contains(&host) {
+        return Err(HostExportError::Unknown(anyhow!(
+            "the host `{}` is not allowed for `http.get`. \
+             Hint: add it to GRAPH_HTTP_GET_ALLOWED_HOSTS",
+            host
+        )));
+    }
+    Ok(url)
+}
+
+async fn http_get_fetch(url: Url) -> Result<Vec<u8>, HostExportError> {
+    let max_bytes = ENV_VARS.mappings.max_http_get_bytes;
+    // The limit differs between indexers
+    let too_large = |url: &Url| {
+        HostExportError::Unknown(anyhow!(
+            "the response for `{}` is larger than the limit of {} bytes",
+            url,
+            max_bytes
+        ))
+    };
+    let fetch_failed = |url: &Url, e: reqwest::Error| {
+        HostExportError::Unknown(anyhow!("failed to fetch `{}`: {}", url, e))
+    };
+
+    // Every hop of a redirect has to go to an allowed host, too
+    let redirect = reqwest::redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() >= HTTP_GET_MAX_REDIRECTS {
+            return attempt.error(anyhow!("too many redirects"));
+        }
+        match check_http_get_url(
+            attempt.url().as_str(),
+            &ENV_VARS.mappings.http_get_allowed_hosts,
+        ) {
+            Ok(_) => attempt.follow(),
+            Err(e) => attempt.error(e),
+        }
+    });
+    let client = reqwest::Client::builder()
+        .timeout(ENV_VARS.mappings.http_get_timeout)
+        .redirect(redirect)
+        .build()
+        .map_err(|e| HostExportError::Unknown(e.into()))?;
+    let mut response = client
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| fetch_failed(&url, e))?;
+    if response.content_length().unwrap_or(0) > max_bytes as u64 {
+        return Err(too_large(&url));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| fetch_failed(&url, e))? {
+        body.extend_from_slice(&chunk);
+        if body.len() > max_bytes {
+            return Err(too_large(&url));
+        }
+    }
+    Ok(body)
+}
+
 /// Expose some host functions for testing only
 #[cfg(debug_assertions)]
 pub mod test_support {
@@ -1267,7 +2178,7 @@ pub mod test_support {
     use graph::{
         blockchain::BlockTime,
         components::{
-            store::{BlockNumber, GetScope},
+            store::{BlockNumber, DerivedEntityFilter, GetScope},
             subgraph::SharedProofOfIndexing,
         },
         data::value::Word,
@@ -1327,6 +2238,55 @@ pub mod test_support {
             self.host_exports
                 .store_get(state, entity_type, entity_id, gas, GetScope::Store)
         }
+
+        pub fn store_get_at_block(
+            &self,
+            state: &mut BlockState,
+            entity_type: String,
+            entity_id: String,
+            block: BlockNumber,
+            current_block: BlockNumber,
+            gas: &GasCounter,
+        ) -> Result<Option<Arc<Entity>>, HostExportError> {
+            self.host_exports.store_get_at_block(
+                state,
+                entity_type,
+                entity_id,
+                block,
+                current_block,
+                gas,
+            )
+        }
+
+        pub fn store_get_many(
+            &self,
+            state: &mut BlockState,
+            entity_type: String,
+            entity_ids: Vec<String>,
+            gas: &GasCounter,
+        ) -> Result<Vec<Option<Arc<Entity>>>, anyhow::Error> {
+            self.host_exports
+                .store_get_many(state, entity_type, entity_ids, gas)
+        }
+
+        pub fn store_load_related_filtered(
+            &self,
+            state: &mut BlockState,
+            entity_type: String,
+            entity_id: String,
+            entity_field: String,
+            filter: DerivedEntityFilter,
+            gas: &GasCounter,
+        ) -> Result<Vec<Entity>, anyhow::Error> {
+            self.host_exports.store_load_related_filtered(
+                state,
+                entity_type,
+                entity_id,
+                entity_field,
+                filter,
+                gas,
+            )
+        }
     }
 }
 #[test]
@@ -1358,3 +2318,121 @@ fn bytes_to_string_is_lossy() {
         )
     )
 }
+
+#[test]
+fn secp256k1_recover_finds_signer() {
+    use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&[7u8; 32]).unwrap();
+    let hash = tiny_keccak::keccak256(b"hello");
+    let (id, rs) = secp
+        .sign_ecdsa_recoverable(&Message::from_slice(&hash).unwrap(), &secret)
+        .serialize_compact();
+    let expected = PublicKey::from_secret_key(&secp, &secret).serialize_uncompressed();
+
+    let mut signature = rs.to_vec();
+    signature.push(id.to_i32() as u8);
+    assert_eq!(
+        Some(expected),
+        secp256k1_recover(&hash, &signature).unwrap()
+    );
+
+    // Ethereum style recovery ids
+    signature[64] += 27;
+    assert_eq!(
+        Some(expected),
+        secp256k1_recover(&hash, &signature).unwrap()
+    );
+
+    signature[64] = 5;
+    assert_eq!(None, secp256k1_recover(&hash, &signature).unwrap());
+    assert_eq!(None, secp256k1_recover(&hash, &signature[..64]).unwrap());
+    assert!(secp256k1_recover(&hash[..31], &signature).is_err());
+}
+
+#[test]
+fn ed25519_verify_checks_signature() {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let public_key = key.verifying_key().to_bytes();
+    let signature = key.sign(b"hello").to_bytes();
+
+    assert!(ed25519_verify(&public_key, b"hello", &signature));
+    assert!(!ed25519_verify(&public_key, b"hellO", &signature));
+    assert!(!ed25519_verify(&public_key[..31], b"hello", &signature));
+    assert!(!ed25519_verify(&public_key, b"hello", &signature[..63]));
+}
+
+#[test]
+fn json_integers_are_normalized() {
+    assert_eq!(Some("0".to_string()), json_integer("0"));
+    assert_eq!(Some("0".to_string()), json_integer("-0.0e-5"));
+    assert_eq!(Some("1500".to_string()), json_integer("1.5e3"));
+    assert_eq!(Some("1500".to_string()), json_integer("1.5E+3"));
+    assert_eq!(Some("-12".to_string()), json_integer("-1.20e1"));
+    assert_eq!(Some("5".to_string()), json_integer("5.000"));
+    assert_eq!(Some("12".to_string()), json_integer("1200e-2"));
+    assert_eq!(
+        Some("1000000000000000000000".to_string()),
+        json_integer("1e21")
+    );
+    let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+    assert_eq!(Some(max.to_string()), json_integer(max));
+    assert_eq!(None, json_integer("1.5"));
+    assert_eq!(None, json_integer("15e-1"));
+    assert_eq!(None, json_integer("1e100000"));
+    assert_eq!(None, json_integer("1e9223372036854775807"));
+    assert_eq!(None, json_integer("1.5e-9223372036854775808"));
+
+    let mut value: serde_json::Value =
+        serde_json::from_str(&format!(r#"{{"a": [1e3, 0.5], "b": {}}}"#, max)).unwrap();
+    normalize_json_integers(&mut value);
+    assert_eq!(
+        format!(r#"{{"a":[1000,0.5],"b":{}}}"#, max),
+        value.to_string()
+    );
+}
+
+#[test]
+fn http_get_urls_are_checked() {
+    let allowed = ["api.example.com".to_string()];
+
+    let url = check_http_get_url("https://API.example.com/prices?id=1", &allowed).unwrap();
+    assert_eq!("https://api.example.com/prices?id=1", url.as_str());
+    assert!(check_http_get_url("http://api.example.com:8080/", &allowed).is_ok());
+
+    assert!(check_http_get_url("https://example.com/", &allowed).is_err());
+    assert!(check_http_get_url("https://api.example.com.evil.com/", &allowed).is_err());
+    assert!(check_http_get_url("ftp://api.example.com/", &allowed).is_err());
+    assert!(check_http_get_url("not a url", &allowed).is_err());
+    assert!(check_http_get_url("https://api.example.com/", &[]).is_err());
+}
+
+#[test]
+fn read_tuple_types_of_parameter_lists() {
+    use web3::types::U256;
+    use ParamType::*;
+
+    assert_eq!(
+        vec![Address, Array(Box::new(Tuple(vec![Uint(256), Bytes])))],
+        read_tuple_types("address,(uint256,bytes)[]").unwrap()
+    );
+    assert_eq!(
+        vec![Tuple(vec![Address, Bytes])],
+        read_tuple_types("(address,bytes)").unwrap()
+    );
+    assert_eq!(Vec::<ParamType>::new(), read_tuple_types("").unwrap());
+    assert!(read_tuple_types("address,nonsense").is_err());
+
+    // Dynamic parameters are encoded without the offset that a dynamic
+    // tuple would get, which is how calldata is laid out
+    let tokens = vec![Token::Uint(1.into()), Token::Bytes(vec![0xbe, 0xef])];
+    let encoded = encode(&tokens);
+    assert_eq!(U256::from(1), U256::from_big_endian(&encoded[..32]));
+    assert_eq!(
+        tokens,
+        decode(&read_tuple_types("uint256,bytes").unwrap(), &encoded).unwrap()
+    );
+}
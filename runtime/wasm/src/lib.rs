@@ -14,8 +14,11 @@ pub mod module;
 /// Runtime-agnostic implementation of exports to WASM.
 pub mod host_exports;
 
+/// Address encodings for the `typeConversion` host exports.
+mod encoding;
 pub mod error;
 mod gas_rules;
+mod module_cache;
 
 pub use host::RuntimeHostBuilder;
 pub use host_exports::HostExports;
@@ -1,8 +1,8 @@
-use crate::gas_rules::GasRules;
+use crate::gas_rules::{meter_bulk, GasRules};
 use crate::module::{ExperimentalFeatures, ToAscPtr, WasmInstance};
 use graph::blockchain::{BlockTime, Blockchain, HostFn};
 use graph::components::store::SubgraphFork;
-use graph::components::subgraph::{MappingError, SharedProofOfIndexing};
+use graph::components::subgraph::{HandlerLimits, MappingError, SharedProofOfIndexing};
 use graph::data_source::{MappingTrigger, TriggerWithHandler};
 use graph::futures01::sync::mpsc;
 use graph::futures01::{Future as _, Stream as _};
@@ -22,13 +22,13 @@ pub fn spawn_module<C: Blockchain>(
     subgraph_id: DeploymentHash,
     host_metrics: Arc<HostMetrics>,
     runtime: tokio::runtime::Handle,
-    timeout: Option<Duration>,
+    limits: &HandlerLimits,
     experimental_features: ExperimentalFeatures,
 ) -> Result<mpsc::Sender<WasmRequest<C>>, anyhow::Error>
 where
     <C as Blockchain>::MappingTrigger: ToAscPtr,
 {
-    let valid_module = Arc::new(ValidModule::new(&logger, raw_module, timeout)?);
+    let valid_module = Arc::new(ValidModule::new(&logger, raw_module, limits)?);
 
     // Create channel for event handling requests
     let (mapping_request_sender, mapping_request_receiver) = mpsc::channel(100);
@@ -250,6 +250,15 @@ pub struct ValidModule {
     // The timeout for the module.
     pub timeout: Option<Duration>,
 
+    // The fuel that each instance of the module may use, if fuel is metered.
+    pub fuel: Option<u64>,
+
+    // Whether the manifest declared `fuel`, which makes running out of it deterministic.
+    pub manifest_fuel: bool,
+
+    // How large the memory of each instance of the module may grow, in bytes.
+    pub max_memory: Option<usize>,
+
     // Used as a guard to terminate this task dependency.
     epoch_counter_abort_handle: Option<tokio::task::AbortHandle>,
 }
@@ -259,8 +268,15 @@ impl ValidModule {
     pub fn new(
         logger: &Logger,
         raw_module: &[u8],
-        timeout: Option<Duration>,
+        limits: &HandlerLimits,
     ) -> Result<Self, anyhow::Error> {
+        let timeout = limits.timeout();
+        let fuel = limits.fuel;
+        let manifest_fuel = limits.manifest_fuel;
+        let max_memory = limits
+            .max_memory_bytes
+            .map(|bytes| usize::try_from(bytes).unwrap_or(usize::MAX));
+
         // Add the gas calls here. Module name "gas" must match. See also
         // e3f03e62-40e4-4f8c-b4a1-d0375cca0b76. We do this by round-tripping the module through
         // parity - injecting gas then serializing again.
@@ -298,6 +314,7 @@ impl ValidModule {
         });
         let parity_module = wasm_instrument::gas_metering::inject(parity_module, &GasRules, "gas")
             .map_err(|_| anyhow!("Failed to inject gas counter"))?;
+        let parity_module = meter_bulk(parity_module)?;
         let raw_module = parity_module.into_bytes()?;
 
         // We currently use Cranelift as a compilation engine. Cranelift is an optimizing compiler,
@@ -306,12 +323,31 @@ impl ValidModule {
         let mut config = wasmtime::Config::new();
         config.strategy(wasmtime::Strategy::Cranelift);
         config.epoch_interruption(true);
+        // Fuel is consumed per WASM instruction, so unlike the timeout, running out of fuel
+        // happens at the same point on every machine with the same budget.
+        // See also: runtime-fuel
+        config.consume_fuel(fuel.is_some());
         config.cranelift_nan_canonicalization(true); // For NaN determinism.
+
+        // Bulk memory operations and SIMD instructions have exactly specified results, and NaN
+        // canonicalization also applies to SIMD floats. Relaxed SIMD instructions on the other
+        // hand may produce different results on different hardware, so they stay disabled and
+        // modules that use them fail validation.
+        config.wasm_bulk_memory(true);
+        config.wasm_simd(true);
+        config.wasm_relaxed_simd(false);
+        config.wasm_threads(false);
         config.cranelift_opt_level(wasmtime::OptLevel::None);
         config.max_wasm_stack(ENV_VARS.mappings.max_stack_size);
 
         let engine = &wasmtime::Engine::new(&config)?;
-        let module = wasmtime::Module::from_binary(engine, &raw_module)?;
+        let module = crate::module_cache::load_or_compile(
+            logger,
+            ENV_VARS.mappings.module_cache_dir.as_deref(),
+            engine,
+            &raw_module,
+            fuel.is_some(),
+        )?;
 
         let mut import_name_to_modules: BTreeMap<String, Vec<String>> = BTreeMap::new();
 
@@ -349,6 +385,9 @@ impl ValidModule {
             import_name_to_modules,
             start_function,
             timeout,
+            fuel,
+            manifest_fuel,
+            max_memory,
             epoch_counter_abort_handle,
         })
     }
@@ -10,7 +10,7 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use anyhow::Error;
-use graph::components::store::GetScope;
+use graph::components::store::{DerivedEntityFilter, GetScope};
 use never::Never;
 
 use crate::asc_abi::class::*;
@@ -94,11 +94,62 @@ pub struct WasmInstanceData {
 
     pub(crate) experimental_features: ExperimentalFeatures,
 
+    pub(crate) memory_limiter: MemoryLimiter,
+
+    /// The handler that is running; `None` while the module is started
+    pub(crate) handler: Option<String>,
+
     // This option is needed to break the cyclic dependency between, instance, store, and context.
     // during execution it should always be populated.
     asc_heap: Option<AscHeapCtx>,
 }
 
+/// Refuses to grow the memory of an instance beyond `max_memory` bytes.
+/// Mappings see a failed `memory.grow` and abort; `exceeded` remembers
+/// that the limit was the cause so that the error is non-deterministic
+pub(crate) struct MemoryLimiter {
+    max_memory: Option<usize>,
+    pub(crate) exceeded: bool,
+}
+
+impl MemoryLimiter {
+    fn new(max_memory: Option<usize>) -> Self {
+        MemoryLimiter {
+            max_memory,
+            exceeded: false,
+        }
+    }
+}
+
+impl wasmtime::ResourceLimiter for MemoryLimiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool, anyhow::Error> {
+        if maximum.map_or(false, |maximum| desired > maximum) {
+            return Ok(false);
+        }
+        match self.max_memory {
+            Some(max_memory) if desired > max_memory => {
+                self.exceeded = true;
+                Ok(false)
+            }
+            _ => Ok(true),
+        }
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: u32,
+        desired: u32,
+        maximum: Option<u32>,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(maximum.map_or(true, |maximum| desired <= maximum))
+    }
+}
+
 impl WasmInstanceData {
     pub fn from_instance(
         ctx: MappingContext,
@@ -109,11 +160,13 @@ impl WasmInstanceData {
         WasmInstanceData {
             asc_heap: None,
             ctx,
-            valid_module,
             host_metrics,
             possible_reorg: false,
             deterministic_host_trap: false,
+            memory_limiter: MemoryLimiter::new(valid_module.max_memory),
+            handler: None,
             experimental_features,
+            valid_module,
         }
     }
 
@@ -336,6 +389,148 @@ impl WasmInstanceContext<'_> {
         self.store_get_scoped(gas, entity_ptr, id_ptr, GetScope::InBlock)
     }
 
+    /// function store.getAtBlock(entity: string, id: string, blockNumber: i32): Entity | null
+    pub fn store_get_at_block(
+        &mut self,
+        gas: &GasCounter,
+        entity_ptr: AscPtr<AscString>,
+        id_ptr: AscPtr<AscString>,
+        block_number: u32,
+    ) -> Result<AscPtr<AscEntity>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let _timer = self
+            .as_ref()
+            .host_metrics
+            .cheap_clone()
+            .time_host_fn_execution_region("store_get_at_block");
+
+        let entity_type: String = asc_get(self, entity_ptr, gas)?;
+        let id: String = asc_get(self, id_ptr, gas)?;
+        // The block number is an `i32` in AssemblyScript
+        let block = block_number as i32;
+        let current_block = self.as_ref().ctx.block_ptr.number;
+        let entity = host_exports.store_get_at_block(
+            &mut self.as_mut().ctx.state,
+            entity_type.clone(),
+            id.clone(),
+            block,
+            current_block,
+            gas,
+        )?;
+
+        if self.as_ref().ctx.instrument {
+            debug!(self.as_ref().ctx.logger, "store_get_at_block";
+                    "type" => &entity_type,
+                    "id" => &id,
+                    "block" => block,
+                    "found" => entity.is_some());
+        }
+
+        match entity {
+            Some(entity) => asc_new(self, &entity.sorted_ref(), gas).map_err(Into::into),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function store.getMany(entity: string, ids: Array<string>): Array<Entity | null>
+    pub fn store_get_many(
+        &mut self,
+        gas: &GasCounter,
+        entity_ptr: AscPtr<AscString>,
+        ids_ptr: AscPtr<Array<AscPtr<AscString>>>,
+    ) -> Result<AscPtr<Array<AscPtr<AscEntity>>>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let _timer = self
+            .as_ref()
+            .host_metrics
+            .cheap_clone()
+            .time_host_fn_execution_region("store_get_many");
+
+        let entity_type: String = asc_get(self, entity_ptr, gas)?;
+        let ids: Vec<String> = asc_get(self, ids_ptr, gas)?;
+        let entities = host_exports.store_get_many(
+            &mut self.as_mut().ctx.state,
+            entity_type.clone(),
+            ids.clone(),
+            gas,
+        )?;
+
+        if self.as_ref().ctx.instrument {
+            debug!(self.as_ref().ctx.logger, "store_get_many";
+                    "type" => &entity_type,
+                    "ids" => ids.len(),
+                    "found" => entities.iter().filter(|entity| entity.is_some()).count());
+        }
+        let host_metrics = self.as_ref().host_metrics.cheap_clone();
+        let debug_fork = self.as_ref().ctx.debug_fork.cheap_clone();
+
+        let mut ptrs = Vec::with_capacity(entities.len());
+        for (id, entity) in ids.into_iter().zip(entities) {
+            let ptr = match (entity, &debug_fork) {
+                (Some(entity), _) => {
+                    let _section = host_metrics.stopwatch.start_section("store_get_asc_new");
+                    asc_new(self, &entity.sorted_ref(), gas)?
+                }
+                (None, Some(fork)) => {
+                    let entity_option =
+                        fork.fetch(entity_type.clone(), id.clone()).map_err(|e| {
+                            HostExportError::Unknown(anyhow!(
+                                "store_get_many: failed to fetch entity from the debug fork: {}",
+                                e
+                            ))
+                        })?;
+                    match entity_option {
+                        Some(entity) => {
+                            let _section =
+                                host_metrics.stopwatch.start_section("store_get_asc_new");
+                            let entity = asc_new(self, &entity.sorted(), gas)?;
+                            let id_ptr = asc_new(self, id.as_str(), gas)?;
+                            self.store_set(gas, entity_ptr, id_ptr, entity)?;
+                            entity
+                        }
+                        None => AscPtr::null(),
+                    }
+                }
+                (None, None) => AscPtr::null(),
+            };
+            ptrs.push(ptr);
+        }
+
+        let array = Array::new(&ptrs, self, gas)?;
+        AscPtr::alloc_obj(array, self, gas)
+    }
+
+    /// function store.get_in_source(entity: string, id: string): Entity | null
+    pub fn store_get_in_source(
+        &mut self,
+        gas: &GasCounter,
+        entity_ptr: AscPtr<AscString>,
+        id_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<AscEntity>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let _timer = self
+            .as_ref()
+            .host_metrics
+            .cheap_clone()
+            .time_host_fn_execution_region("store_get_in_source");
+
+        let entity_type: String = asc_get(self, entity_ptr, gas)?;
+        let id: String = asc_get(self, id_ptr, gas)?;
+        let block = self.as_ref().ctx.block_ptr.number;
+        let entity_option = host_exports.store_get_in_source(
+            &mut self.as_mut().ctx.state,
+            entity_type,
+            id,
+            block,
+            gas,
+        )?;
+
+        match entity_option {
+            Some(entity) => asc_new(self, &entity.sorted_ref(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
     /// function store.loadRelated(entity_type: string, id: string, field: string): Array<Entity>
     pub fn store_load_related(
         &mut self,
@@ -363,6 +558,59 @@ impl WasmInstanceContext<'_> {
         Ok(ret)
     }
 
+    /// function store.loadRelatedFiltered(
+    ///     entity: string, id: string, field: string, filter: Entity,
+    ///     orderBy: string | null, descending: bool, first: u32
+    /// ): Array<Entity>
+    ///
+    /// Only returns related entities whose attributes have the values in
+    /// `filter`, and at most `first` of them unless `first` is 0
+    pub fn store_load_related_filtered(
+        &mut self,
+
+        gas: &GasCounter,
+        entity_type_ptr: AscPtr<AscString>,
+        id_ptr: AscPtr<AscString>,
+        field_ptr: AscPtr<AscString>,
+        filter_ptr: AscPtr<AscEntity>,
+        order_by_ptr: AscPtr<AscString>,
+        descending: u32,
+        first: u32,
+    ) -> Result<AscPtr<Array<AscPtr<AscEntity>>>, HostExportError> {
+        let entity_type: String = asc_get(self, entity_type_ptr, gas)?;
+        let id: String = asc_get(self, id_ptr, gas)?;
+        let field: String = asc_get(self, field_ptr, gas)?;
+        let values: HashMap<Word, Value> = asc_get(self, filter_ptr, gas)?;
+        let order_by: Option<String> = match order_by_ptr.is_null() {
+            false => Some(asc_get(self, order_by_ptr, gas)?),
+            true => None,
+        };
+
+        let mut values: Vec<_> = values.into_iter().collect();
+        values.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        let filter = DerivedEntityFilter {
+            values,
+            order_by: order_by.map(Word::from),
+            descending: descending != 0,
+            first: (first > 0).then_some(first as usize),
+        };
+
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let entities = host_exports.store_load_related_filtered(
+            &mut self.as_mut().ctx.state,
+            entity_type,
+            id,
+            field,
+            filter,
+            gas,
+        )?;
+
+        let entities: Vec<Vec<(Word, Value)>> =
+            entities.into_iter().map(|entity| entity.sorted()).collect();
+        let ret = asc_new(self, &entities, gas)?;
+        Ok(ret)
+    }
+
     /// function typeConversion.bytesToString(bytes: Bytes): string
     pub fn bytes_to_string(
         &mut self,
@@ -479,6 +727,27 @@ impl WasmInstanceContext<'_> {
         asc_new(self, &result, gas)
     }
 
+    /// function json.fromBytesPreserveNumbers(bytes: Bytes): JSONValue
+    pub fn json_from_bytes_preserve_numbers(
+        &mut self,
+        gas: &GasCounter,
+        bytes_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<AscEnum<JsonValueKind>>, HostExportError> {
+        let bytes: Vec<u8> = asc_get(self, bytes_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let result = host_exports
+            .json_from_bytes_preserve_numbers(&bytes, gas, &mut ctx.state)
+            .with_context(|| {
+                format!(
+                    "Failed to parse JSON from byte array. Bytes (truncated to 1024 chars): `{:?}`",
+                    &bytes[..bytes.len().min(1024)],
+                )
+            })
+            .map_err(DeterministicHostError::from)?;
+        asc_new(self, &result, gas)
+    }
+
     /// function json.try_fromBytes(bytes: Bytes): Result<JSONValue, boolean>
     pub fn json_try_from_bytes(
         &mut self,
@@ -577,6 +846,19 @@ impl WasmInstanceContext<'_> {
         }
     }
 
+    /// function http.get(url: String): Bytes
+    pub fn http_get(
+        &mut self,
+        gas: &GasCounter,
+        url_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let url: String = asc_get(self, url_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let body = host_exports.http_get(&ctx.logger, url, gas, &mut ctx.state)?;
+        asc_new(self, &*body, gas).map_err(Into::into)
+    }
+
     /// function ipfs.map(link: String, callback: String, flags: String[]): void
     pub fn ipfs_map(
         &mut self,
@@ -609,7 +891,7 @@ impl WasmInstanceContext<'_> {
         // Pause the timeout while running ipfs_map, and resume it when done.
         self.suspend_timeout();
         let start_time = Instant::now();
-        let output_states = HostExports::ipfs_map(
+        let (output_state, n_calls) = HostExports::ipfs_map(
             &self.as_ref().ctx.host_exports.link_resolver.cheap_clone(),
             self.as_ref(),
             link.clone(),
@@ -624,12 +906,10 @@ impl WasmInstanceContext<'_> {
             "Successfully processed file with ipfs.map";
             "link" => &link,
             "callback" => &*callback,
-            "n_calls" => output_states.len(),
+            "n_calls" => n_calls,
             "time" => format!("{}ms", start_time.elapsed().as_millis())
         );
-        for output_state in output_states {
-            self.as_mut().ctx.state.extend(output_state);
-        }
+        self.as_mut().ctx.state.extend(output_state);
 
         Ok(())
     }
@@ -704,6 +984,95 @@ impl WasmInstanceContext<'_> {
         asc_new(self, input.as_ref(), gas)
     }
 
+    /// function crypto.sha512(input: Bytes): Bytes
+    pub fn crypto_sha512(
+        &mut self,
+
+        gas: &GasCounter,
+        input_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let input = asc_get(self, input_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        let input = host_exports.crypto_sha512(input, gas, &mut ctx.state)?;
+        asc_new(self, input.as_slice(), gas)
+    }
+
+    /// function crypto.blake2b256(input: Bytes): Bytes
+    pub fn crypto_blake2b256(
+        &mut self,
+
+        gas: &GasCounter,
+        input_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let input = asc_get(self, input_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        let input = host_exports.crypto_blake2b256(input, gas, &mut ctx.state)?;
+        asc_new(self, input.as_slice(), gas)
+    }
+
+    /// function crypto.blake2b512(input: Bytes): Bytes
+    pub fn crypto_blake2b512(
+        &mut self,
+
+        gas: &GasCounter,
+        input_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let input = asc_get(self, input_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        let input = host_exports.crypto_blake2b512(input, gas, &mut ctx.state)?;
+        asc_new(self, input.as_slice(), gas)
+    }
+
+    /// function crypto.secp256k1Recover(hash: Bytes, signature: Bytes): Bytes | null
+    pub fn crypto_secp256k1_recover(
+        &mut self,
+
+        gas: &GasCounter,
+        hash_ptr: AscPtr<Uint8Array>,
+        signature_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let hash = asc_get(self, hash_ptr, gas)?;
+        let signature = asc_get(self, signature_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        match host_exports.crypto_secp256k1_recover(hash, signature, gas, &mut ctx.state)? {
+            Some(public_key) => asc_new(self, public_key.as_ref(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function crypto.ed25519Verify(publicKey: Bytes, message: Bytes, signature: Bytes): bool
+    pub fn crypto_ed25519_verify(
+        &mut self,
+
+        gas: &GasCounter,
+        public_key_ptr: AscPtr<Uint8Array>,
+        message_ptr: AscPtr<Uint8Array>,
+        signature_ptr: AscPtr<Uint8Array>,
+    ) -> Result<bool, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let public_key = asc_get(self, public_key_ptr, gas)?;
+        let message = asc_get(self, message_ptr, gas)?;
+        let signature = asc_get(self, signature_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        let valid = host_exports.crypto_ed25519_verify(
+            public_key,
+            message,
+            signature,
+            gas,
+            &mut ctx.state,
+        )?;
+        Ok(valid)
+    }
+
     /// function bigInt.plus(x: BigInt, y: BigInt): BigInt
     pub fn big_int_plus(
         &mut self,
@@ -780,10 +1149,14 @@ impl WasmInstanceContext<'_> {
         x_ptr: AscPtr<AscBigInt>,
         y_ptr: AscPtr<AscBigDecimal>,
     ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
-        let x = BigDecimal::new(asc_get(self, x_ptr, gas)?, 0);
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let x = BigDecimal::new_with_precision(
+            asc_get(self, x_ptr, gas)?,
+            0,
+            &host_exports.big_decimal_precision,
+        );
 
         let y = asc_get(self, y_ptr, gas)?;
-        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
         let ctx = &mut self.as_mut().ctx;
 
         let result = host_exports.big_decimal_divided_by(x, y, gas, &mut ctx.state)?;
@@ -905,6 +1278,117 @@ impl WasmInstanceContext<'_> {
         asc_new(self, &result, gas)
     }
 
+    /// function typeConversion.base58ToBytes(s: string): Bytes | null
+    pub fn base58_to_bytes(
+        &mut self,
+
+        gas: &GasCounter,
+        string_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let string = asc_get(self, string_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        match host_exports.base58_to_bytes(string, gas, &mut ctx.state)? {
+            Some(bytes) => asc_new(self, bytes.as_slice(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function typeConversion.bytesToBase58Check(bytes: Bytes): string
+    pub fn bytes_to_base58_check(
+        &mut self,
+
+        gas: &GasCounter,
+        bytes_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let bytes = asc_get(self, bytes_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let result = host_exports.bytes_to_base58_check(bytes, gas, &mut ctx.state)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function typeConversion.base58CheckToBytes(s: string): Bytes | null
+    pub fn base58_check_to_bytes(
+        &mut self,
+
+        gas: &GasCounter,
+        string_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let string = asc_get(self, string_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        match host_exports.base58_check_to_bytes(string, gas, &mut ctx.state)? {
+            Some(bytes) => asc_new(self, bytes.as_slice(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function typeConversion.bytesToBech32(hrp: string, bytes: Bytes): string
+    pub fn bytes_to_bech32(
+        &mut self,
+
+        gas: &GasCounter,
+        hrp_ptr: AscPtr<AscString>,
+        bytes_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let hrp = asc_get(self, hrp_ptr, gas)?;
+        let bytes = asc_get(self, bytes_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let result = host_exports.bytes_to_bech32(hrp, bytes, gas, &mut ctx.state)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function typeConversion.bech32ToBytes(s: string, hrp: string): Bytes | null
+    pub fn bech32_to_bytes(
+        &mut self,
+
+        gas: &GasCounter,
+        string_ptr: AscPtr<AscString>,
+        hrp_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let string = asc_get(self, string_ptr, gas)?;
+        let hrp = asc_get(self, hrp_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        match host_exports.bech32_to_bytes(string, hrp, gas, &mut ctx.state)? {
+            Some(bytes) => asc_new(self, bytes.as_slice(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function typeConversion.bytesToSs58(bytes: Bytes, prefix: u32): string
+    pub fn bytes_to_ss58(
+        &mut self,
+
+        gas: &GasCounter,
+        bytes_ptr: AscPtr<Uint8Array>,
+        prefix: u32,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let bytes = asc_get(self, bytes_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let result = host_exports.bytes_to_ss58(bytes, prefix, gas, &mut ctx.state)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function typeConversion.ss58ToBytes(s: string): Bytes | null
+    pub fn ss58_to_bytes(
+        &mut self,
+
+        gas: &GasCounter,
+        string_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let string = asc_get(self, string_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        match host_exports.ss58_to_bytes(string, gas, &mut ctx.state)? {
+            Some(bytes) => asc_new(self, bytes.as_slice(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
     /// function bigDecimal.toString(x: BigDecimal): string
     pub fn big_decimal_to_string(
         &mut self,
@@ -1099,6 +1583,30 @@ impl WasmInstanceContext<'_> {
         asc_new(self, &ds_ctx, gas)
     }
 
+    /// function dataSource.env(key: string): string | null
+    pub fn data_source_env(
+        &mut self,
+        gas: &GasCounter,
+        key_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let key: String = asc_get(self, key_ptr, gas)?;
+        let logger = self.as_ref().ctx.logger.cheap_clone();
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let value = host_exports.data_source_env(
+            &logger,
+            &ctx.proof_of_indexing,
+            key,
+            gas,
+            &mut ctx.state,
+        )?;
+
+        // map `None` to `null`, and `Some(s)` to a runtime string
+        value
+            .map(|value| asc_new(self, value.as_str(), gas).map_err(Into::into))
+            .unwrap_or(Ok(AscPtr::null()))
+    }
+
     pub fn ens_name_by_hash(
         &mut self,
         gas: &GasCounter,
@@ -1129,8 +1637,17 @@ impl WasmInstanceContext<'_> {
         let level = LogLevel::from(level).into();
         let msg: String = asc_get(self, msg, gas)?;
         let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
-        let ctx = &mut self.as_mut().ctx;
-        host_exports.log_log(&ctx.mapping_logger, level, msg, gas, &mut ctx.state)
+        let data = self.as_mut();
+        let ctx = &mut data.ctx;
+        host_exports.log_log(
+            &ctx.mapping_logger,
+            level,
+            msg,
+            ctx.block_ptr.number,
+            data.handler.as_deref(),
+            gas,
+            &mut ctx.state,
+        )
     }
 
     /// function encode(token: ethereum.Value): Bytes | null
@@ -1167,6 +1684,41 @@ impl WasmInstanceContext<'_> {
             .unwrap_or(Ok(AscPtr::null()))
     }
 
+    /// function encodeTuple(types: string, values: Array<ethereum.Value>): Bytes | null
+    pub fn ethereum_encode_tuple(
+        &mut self,
+        gas: &GasCounter,
+        types_ptr: AscPtr<AscString>,
+        values_ptr: AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let types = asc_get(self, types_ptr, gas)?;
+        let tokens = asc_get(self, values_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let data = host_exports.ethereum_encode_tuple(types, tokens, gas, &mut ctx.state)?;
+        // return `null` if the values do not match the types
+        data.map(|bytes| asc_new(self, &*bytes, gas))
+            .unwrap_or(Ok(AscPtr::null()))
+    }
+
+    /// function decodeTuple(types: string, data: Bytes): Array<ethereum.Value> | null
+    pub fn ethereum_decode_tuple(
+        &mut self,
+        gas: &GasCounter,
+        types_ptr: AscPtr<AscString>,
+        data_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>, HostExportError> {
+        let types = asc_get(self, types_ptr, gas)?;
+        let data = asc_get(self, data_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let tokens = host_exports.ethereum_decode_tuple(types, data, gas, &mut ctx.state)?;
+        // return `null` if the data does not match the types
+        tokens
+            .map(|tokens| asc_new(self, tokens.as_slice(), gas))
+            .unwrap_or(Ok(AscPtr::null()))
+    }
+
     /// function arweave.transactionData(txId: string): Bytes | null
     pub fn arweave_transaction_data(
         &self,
@@ -120,7 +120,7 @@ impl WasmInstance {
         self.invoke_handler(handler_name, obj, Arc::new(o!()), None)
     }
 
-    pub(crate) fn handle_trigger<C: Blockchain>(
+    pub fn handle_trigger<C: Blockchain>(
         mut self,
         trigger: TriggerWithHandler<MappingTrigger<C>>,
     ) -> Result<(BlockState, Gas), MappingError>
@@ -173,6 +173,8 @@ impl WasmInstance {
             .typed(self.store.as_context_mut())
             .context("wasm function has incorrect signature")?;
 
+        self.instance_ctx().as_mut().handler = Some(handler.to_string());
+
         // Caution: Make sure all exit paths from this function call `exit_handler`.
         self.instance_ctx().as_mut().ctx.state.enter_handler();
 
@@ -204,6 +206,44 @@ impl WasmInstance {
                         self.instance_ctx().as_ref().valid_module.timeout.unwrap().as_secs()
                     ))));
                 }
+                // Running out of fuel is deterministic since fuel is counted per instruction, but
+                // only if the budget is the one from the manifest; a budget from the environment
+                // or the deployment rules can differ between indexers.
+                // See also: runtime-fuel
+                Err(trap)
+                    if trap
+                        .chain()
+                        .any(|e| e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel)) =>
+                {
+                    let valid_module = &self.instance_ctx().as_ref().valid_module;
+                    let manifest_fuel = valid_module.manifest_fuel;
+                    let trap = trap.context(format!(
+                        "Handler '{}' used up its fuel budget of {}",
+                        handler,
+                        valid_module.fuel.unwrap_or_default()
+                    ));
+                    if !manifest_fuel {
+                        self.instance_ctx().as_mut().ctx.state.exit_handler();
+                        return Err(MappingError::Unknown(trap));
+                    }
+                    Some(trap)
+                }
+                // The memory limit comes from the environment or the deployment rules and can
+                // differ between indexers, so whatever the mapping did after it failed to grow
+                // its memory, the error is non-deterministic.
+                Err(trap) if self.instance_ctx().as_ref().memory_limiter.exceeded => {
+                    let trap = trap.context(format!(
+                        "Handler '{}' exceeded the memory limit of {} bytes",
+                        handler,
+                        self.instance_ctx()
+                            .as_ref()
+                            .valid_module
+                            .max_memory
+                            .unwrap_or_default()
+                    ));
+                    self.instance_ctx().as_mut().ctx.state.exit_handler();
+                    return Err(MappingError::Unknown(trap));
+                }
                 Err(trap) => {
                     let trap_is_deterministic = is_trap_deterministic(&trap)
                         || self.instance_ctx().as_ref().deterministic_host_trap;
@@ -278,6 +318,7 @@ impl WasmInstance {
             experimental_features,
         );
         let mut store = Store::new(engine, wasm_ctx);
+        store.limiter(|data| &mut data.memory_limiter);
 
         // The epoch on the engine will only ever be incremeted if increment_epoch() is explicitly
         // called, we only do so if a timeout has been set, it will run forever. When a timeout is
@@ -289,6 +330,12 @@ impl WasmInstance {
         // See also: runtime-timeouts
         store.set_epoch_deadline(2);
 
+        // Every instance handles a single trigger, so this budget is per handler invocation.
+        // See also: runtime-fuel
+        if let Some(fuel) = valid_module.fuel {
+            store.add_fuel(fuel)?;
+        }
+
         // Because `gas` and `deterministic_host_trap` need to be accessed from the gas
         // host fn, they need to be separate from the rest of the context.
         let gas = GasCounter::new(host_metrics.gas_metrics.clone());
@@ -399,6 +446,18 @@ impl WasmInstance {
 
         link!("ethereum.encode", ethereum_encode, params_ptr);
         link!("ethereum.decode", ethereum_decode, params_ptr, data_ptr);
+        link!(
+            "ethereum.encodeTuple",
+            ethereum_encode_tuple,
+            types_ptr,
+            values_ptr
+        );
+        link!(
+            "ethereum.decodeTuple",
+            ethereum_decode_tuple,
+            types_ptr,
+            data_ptr
+        );
 
         link!("abort", abort, message_ptr, file_name_ptr, line, column);
 
@@ -411,6 +470,33 @@ impl WasmInstance {
             id,
             field
         );
+        link!(
+            "store.loadRelatedFiltered",
+            store_load_related_filtered,
+            "host_export_store_load_related_filtered",
+            entity,
+            id,
+            field,
+            filter,
+            order_by,
+            descending,
+            first
+        );
+        link!(
+            "store.getMany",
+            store_get_many,
+            "host_export_store_get_many",
+            entity,
+            ids
+        );
+        link!(
+            "store.getAtBlock",
+            store_get_at_block,
+            "host_export_store_get_at_block",
+            entity,
+            id,
+            block_number
+        );
         link!(
             "store.get_in_block",
             store_get_in_block,
@@ -418,6 +504,13 @@ impl WasmInstance {
             entity,
             id
         );
+        link!(
+            "store.get_in_source",
+            store_get_in_source,
+            "host_export_store_get_in_source",
+            entity,
+            id
+        );
         link!(
             "store.set",
             store_set,
@@ -452,6 +545,8 @@ impl WasmInstance {
             );
         }
 
+        link!("http.get", http_get, url_ptr);
+
         link!("store.remove", store_remove, entity_ptr, id_ptr);
 
         link!("typeConversion.bytesToString", bytes_to_string, ptr);
@@ -460,15 +555,61 @@ impl WasmInstance {
         link!("typeConversion.bigIntToHex", big_int_to_hex, ptr);
         link!("typeConversion.stringToH160", string_to_h160, ptr);
         link!("typeConversion.bytesToBase58", bytes_to_base58, ptr);
+        link!("typeConversion.base58ToBytes", base58_to_bytes, ptr);
+        link!(
+            "typeConversion.bytesToBase58Check",
+            bytes_to_base58_check,
+            ptr
+        );
+        link!(
+            "typeConversion.base58CheckToBytes",
+            base58_check_to_bytes,
+            ptr
+        );
+        link!(
+            "typeConversion.bytesToBech32",
+            bytes_to_bech32,
+            hrp_ptr,
+            ptr
+        );
+        link!(
+            "typeConversion.bech32ToBytes",
+            bech32_to_bytes,
+            ptr,
+            hrp_ptr
+        );
+        link!("typeConversion.bytesToSs58", bytes_to_ss58, ptr, prefix);
+        link!("typeConversion.ss58ToBytes", ss58_to_bytes, ptr);
 
         link!("json.fromBytes", json_from_bytes, ptr);
         link!("json.try_fromBytes", json_try_from_bytes, ptr);
+        link!(
+            "json.fromBytesPreserveNumbers",
+            json_from_bytes_preserve_numbers,
+            ptr
+        );
         link!("json.toI64", json_to_i64, ptr);
         link!("json.toU64", json_to_u64, ptr);
         link!("json.toF64", json_to_f64, ptr);
         link!("json.toBigInt", json_to_big_int, ptr);
 
         link!("crypto.keccak256", crypto_keccak_256, ptr);
+        link!("crypto.sha512", crypto_sha512, ptr);
+        link!("crypto.blake2b256", crypto_blake2b256, ptr);
+        link!("crypto.blake2b512", crypto_blake2b512, ptr);
+        link!(
+            "crypto.secp256k1Recover",
+            crypto_secp256k1_recover,
+            hash_ptr,
+            signature_ptr
+        );
+        link!(
+            "crypto.ed25519Verify",
+            crypto_ed25519_verify,
+            public_key_ptr,
+            message_ptr,
+            signature_ptr
+        );
 
         link!("bigInt.plus", big_int_plus, x_ptr, y_ptr);
         link!("bigInt.minus", big_int_minus, x_ptr, y_ptr);
@@ -502,6 +643,7 @@ impl WasmInstance {
         link!("dataSource.address", data_source_address,);
         link!("dataSource.network", data_source_network,);
         link!("dataSource.context", data_source_context,);
+        link!("dataSource.env", data_source_env, key_ptr);
 
         link!("ens.nameByHash", ens_name_by_hash, ptr);
 
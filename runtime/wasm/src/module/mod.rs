@@ -4,13 +4,15 @@ use std::mem::MaybeUninit;
 use anyhow::anyhow;
 use anyhow::Error;
 use graph::blockchain::Blockchain;
+use graph::data::store::scalar::BigDecimalPrecision;
+use graph::data::subgraph::API_VERSION_0_0_10;
 use graph::util::mem::init_slice;
 use semver::Version;
 use wasmtime::AsContext;
 use wasmtime::AsContextMut;
 use wasmtime::Memory;
 
-use graph::data_source::{offchain, MappingTrigger, TriggerWithHandler};
+use graph::data_source::{offchain, subgraph, MappingTrigger, TriggerWithHandler};
 use graph::prelude::*;
 use graph::runtime::AscPtr;
 use graph::runtime::{
@@ -69,6 +71,16 @@ impl ToAscPtr for offchain::TriggerData {
     }
 }
 
+impl ToAscPtr for subgraph::TriggerData {
+    fn to_asc_ptr<H: AscHeap>(
+        self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<()>, HostExportError> {
+        asc_new(heap, &self.entity.sorted_ref(), gas).map(|ptr| ptr.erase())
+    }
+}
+
 impl<C: Blockchain> ToAscPtr for MappingTrigger<C>
 where
     C::MappingTrigger: ToAscPtr,
@@ -81,6 +93,7 @@ where
         match self {
             MappingTrigger::Onchain(trigger) => trigger.to_asc_ptr(heap, gas),
             MappingTrigger::Offchain(trigger) => trigger.to_asc_ptr(heap, gas),
+            MappingTrigger::Subgraph(trigger) => trigger.to_asc_ptr(heap, gas),
         }
     }
 }
@@ -116,6 +129,8 @@ fn is_trap_deterministic(trap: &Error) -> bool {
         | UnreachableCodeReached => true,
 
         // `Interrupt`: Can be a timeout, at least as wasmtime currently implements it.
+        // `OutOfFuel`: Only deterministic with the fuel budget from the manifest, which
+        // `WasmInstance::invoke_handler` checks.
         // `StackOverflow`: We may want to have a configurable stack size.
         // `None`: A host trap, so we need to check the `deterministic_host_trap` flag in the context.
         Interrupt | StackOverflow | _ => false,
@@ -132,8 +147,13 @@ pub struct AscHeapCtx {
     id_of_type: Option<wasmtime::TypedFunc<u32, u32>>,
 
     // Function exported by the wasm module that will allocate the request number of bytes and
-    // return a pointer to the first byte of allocated space.
-    memory_allocate: wasmtime::TypedFunc<i32, i32>,
+    // return a pointer to the first byte of allocated space. Only used for version < 0.0.10.
+    memory_allocate: Option<wasmtime::TypedFunc<i32, i32>>,
+
+    // The `__new` and `__pin` functions of the AssemblyScript runtime, which the module exports
+    // when it is compiled with `--exportRuntime`. Only used for version >= 0.0.10.
+    runtime_new: Option<wasmtime::TypedFunc<(u32, u32), u32>>,
+    runtime_pin: Option<wasmtime::TypedFunc<u32, u32>>,
 
     api_version: semver::Version,
 
@@ -161,15 +181,39 @@ impl AscHeapCtx {
             .context("Failed to find memory export in the WASM module")?;
 
         let memory_allocate = match &api_version {
-            version if *version <= Version::new(0, 0, 4) => instance
-                .get_func(ctx.as_context_mut(), "memory.allocate")
-                .context("`memory.allocate` function not found"),
-            _ => instance
-                .get_func(ctx.as_context_mut(), "allocate")
-                .context("`allocate` function not found"),
-        }?
-        .typed(ctx.as_context())?
-        .clone();
+            version if *version <= Version::new(0, 0, 4) => Some(
+                instance
+                    .get_func(ctx.as_context_mut(), "memory.allocate")
+                    .context("`memory.allocate` function not found")?
+                    .typed(ctx.as_context())?,
+            ),
+            version if *version < API_VERSION_0_0_10 => Some(
+                instance
+                    .get_func(ctx.as_context_mut(), "allocate")
+                    .context("`allocate` function not found")?
+                    .typed(ctx.as_context())?,
+            ),
+            _ => None,
+        };
+
+        let (runtime_new, runtime_pin) = match &api_version {
+            version if *version < API_VERSION_0_0_10 => (None, None),
+            _ => {
+                let new = instance
+                    .get_func(ctx.as_context_mut(), "__new")
+                    .context(
+                        "`__new` function not found, compile the mappings with `--exportRuntime`",
+                    )?
+                    .typed(ctx.as_context())?;
+                let pin = instance
+                    .get_func(ctx.as_context_mut(), "__pin")
+                    .context(
+                        "`__pin` function not found, compile the mappings with `--exportRuntime`",
+                    )?
+                    .typed(ctx.as_context())?;
+                (Some(new), Some(pin))
+            }
+        };
 
         let id_of_type = match &api_version {
             version if *version <= Version::new(0, 0, 4) => None,
@@ -184,6 +228,8 @@ impl AscHeapCtx {
 
         Ok(AscHeapCtx {
             memory_allocate,
+            runtime_new,
+            runtime_pin,
             memory,
             arena_start_ptr: 0,
             arena_free_size: 0,
@@ -225,7 +271,12 @@ impl AscHeap for WasmInstanceContext<'_> {
             // Unwrap: This may panic if more memory needs to be requested from the OS and that
             // fails. This error is not deterministic since it depends on the operating conditions
             // of the node.
-            let memory_allocate = self.asc_heap_ref().memory_allocate;
+            let memory_allocate = self.asc_heap_ref().memory_allocate.ok_or_else(|| {
+                DeterministicHostError::from(anyhow!(
+                    "raw allocations are not supported for apiVersion {}",
+                    self.asc_heap_ref().api_version
+                ))
+            })?;
             self.asc_heap_mut().arena_start_ptr = memory_allocate
                 .call(self.as_context_mut(), arena_size)
                 .unwrap();
@@ -308,6 +359,10 @@ impl AscHeap for WasmInstanceContext<'_> {
         self.asc_heap_ref().api_version.clone()
     }
 
+    fn big_decimal_precision(&self) -> BigDecimalPrecision {
+        self.as_ref().ctx.host_exports.big_decimal_precision
+    }
+
     fn asc_type_id(&mut self, type_id_index: IndexForAscTypeId) -> Result<u32, HostExportError> {
         let func = self.asc_heap_ref().id_of_type.unwrap();
 
@@ -320,4 +375,48 @@ impl AscHeap for WasmInstanceContext<'_> {
                 )
             })
     }
+
+    fn managed_new(
+        &mut self,
+        bytes: &[u8],
+        rt_id: u32,
+        gas: &GasCounter,
+    ) -> Result<u32, HostExportError> {
+        gas.consume_host_fn_with_metrics(
+            Gas::new(GAS_COST_STORE as u64 * bytes.len() as u64),
+            "managed_new",
+        )?;
+
+        // Unwrap ok because it's only called on correct apiVersion, look for AscPtr::alloc_obj
+        let runtime_new = self.asc_heap_ref().runtime_new.unwrap();
+        let runtime_pin = self.asc_heap_ref().runtime_pin.unwrap();
+
+        let size = u32::try_from(bytes.len()).unwrap();
+        let ptr = runtime_new
+            .call(self.as_context_mut(), (size, rt_id))
+            .map_err(|trap| {
+                host_export_error_from_trap(trap, format!("Failed to call '__new' with {size}"))
+            })?;
+
+        // Nothing in the module references the object yet, and writes from the host bypass the
+        // write barrier of the incremental garbage collector. Pinning the object keeps the
+        // collector from freeing it; since every instance only handles a single trigger, the
+        // object never needs to be unpinned.
+        runtime_pin
+            .call(self.as_context_mut(), ptr)
+            .map_err(|trap| host_export_error_from_trap(trap, "Failed to call '__pin'".into()))?;
+
+        let memory = self.asc_heap_ref().memory;
+        memory
+            .write(self.as_context_mut(), ptr as usize, bytes)
+            .map_err(|_| {
+                DeterministicHostError::from(anyhow!(
+                    "Heap access out of bounds. Offset: {} Size: {}",
+                    ptr,
+                    bytes.len()
+                ))
+            })?;
+
+        Ok(ptr)
+    }
 }
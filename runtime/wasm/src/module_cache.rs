@@ -0,0 +1,100 @@
+//! A cache of compiled WASM modules on disk. Compiling mappings with
+//! Cranelift is expensive, and deployments that are created from the same
+//! code, as well as restarts of the node, would otherwise compile the same
+//! mappings over and over again
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Error;
+use graph::prelude::{warn, Logger};
+use sha2::{Digest, Sha256};
+
+fn cache_path(dir: &Path, raw_module: &[u8], fuel: bool) -> PathBuf {
+    // Modules compiled with fuel metering contain different code than
+    // modules compiled without it
+    let suffix = if fuel { "-fuel" } else { "" };
+    let hash = hex::encode(Sha256::digest(raw_module));
+    dir.join(format!("{hash}{suffix}.cwasm"))
+}
+
+/// Compile the instrumented `raw_module`, or load it from `dir` if it was
+/// compiled before. Problems with the cache are logged but never fail the
+/// compilation; in the worst case, the module is compiled again
+pub(crate) fn load_or_compile(
+    logger: &Logger,
+    dir: Option<&Path>,
+    engine: &wasmtime::Engine,
+    raw_module: &[u8],
+    fuel: bool,
+) -> Result<wasmtime::Module, Error> {
+    let dir = match dir {
+        Some(dir) => dir,
+        None => return wasmtime::Module::from_binary(engine, raw_module),
+    };
+    let path = cache_path(dir, raw_module, fuel);
+
+    if path.exists() {
+        // Safety: the cache directory is only written by graph-node, and
+        // wasmtime refuses to load modules that were compiled by a
+        // different version or with an incompatible configuration
+        match unsafe { wasmtime::Module::deserialize_file(engine, &path) } {
+            Ok(module) => return Ok(module),
+            Err(e) => {
+                warn!(logger, "Failed to load compiled module from cache, compiling it again";
+                    "path" => path.display().to_string(),
+                    "error" => e.to_string())
+            }
+        }
+    }
+
+    let module = wasmtime::Module::from_binary(engine, raw_module)?;
+    if let Err(e) = store(dir, &path, &module) {
+        warn!(logger, "Failed to store compiled module in cache";
+            "path" => path.display().to_string(),
+            "error" => e.to_string());
+    }
+    Ok(module)
+}
+
+fn store(dir: &Path, path: &Path, module: &wasmtime::Module) -> Result<(), Error> {
+    fs::create_dir_all(dir)?;
+    // Several mappings might compile the same module at the same time;
+    // writing to a temporary file and renaming it makes sure that nobody
+    // ever loads a partially written module
+    let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+    fs::write(&tmp, module.serialize()?)?;
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::prelude::o;
+
+    use super::*;
+
+    // The smallest valid WASM module
+    const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    #[test]
+    fn compiled_modules_are_cached() {
+        let logger = Logger::root(graph::slog::Discard, o!());
+        let dir = std::env::temp_dir().join(format!("module-cache-{}", uuid::Uuid::new_v4()));
+        let engine = wasmtime::Engine::default();
+
+        load_or_compile(&logger, Some(&dir), &engine, EMPTY_MODULE, false).unwrap();
+        let path = cache_path(&dir, EMPTY_MODULE, false);
+        assert!(path.exists());
+        assert!(!cache_path(&dir, EMPTY_MODULE, true).exists());
+
+        // A corrupt cache entry is replaced
+        fs::write(&path, b"garbage").unwrap();
+        load_or_compile(&logger, Some(&dir), &engine, EMPTY_MODULE, false).unwrap();
+        assert!(unsafe { wasmtime::Module::deserialize_file(&engine, &path) }.is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
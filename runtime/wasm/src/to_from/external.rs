@@ -131,7 +131,11 @@ impl FromAscObj<AscBigDecimal> for BigDecimal {
         let bytes = exp.to_signed_bytes_le();
         let mut byte_array = if exp >= 0.into() { [0; 8] } else { [255; 8] };
         byte_array[..bytes.len()].copy_from_slice(&bytes);
-        let big_decimal = BigDecimal::new(digits, i64::from_le_bytes(byte_array));
+        let big_decimal = BigDecimal::new_with_precision(
+            digits,
+            i64::from_le_bytes(byte_array),
+            &heap.big_decimal_precision(),
+        );
 
         // Validate the exponent.
         let exp = -big_decimal.as_bigint_and_exponent().1;
@@ -312,6 +316,8 @@ impl ToAscObj<AscEnum<StoreValueKind>> for store::Value {
             Value::Int(n) => EnumPayload::from(*n),
             Value::Int8(n) => EnumPayload::from(*n),
             Value::Timestamp(n) => EnumPayload::from(n),
+            // Mappings see points as strings of the form `latitude,longitude`
+            Value::GeoPoint(point) => asc_new(heap, point.to_string().as_str(), gas)?.into(),
             Value::BigDecimal(n) => asc_new(heap, n, gas)?.into(),
             Value::Bool(b) => EnumPayload::from(*b),
             Value::List(array) => asc_new(heap, array.as_slice(), gas)?.into(),
@@ -9,6 +9,7 @@ mod deployment_version_selector;
 mod empty_response;
 mod execution;
 mod execution_id;
+mod stream_status;
 mod subgraph_health;
 
 pub use self::block_hash::BlockHash;
@@ -22,4 +23,5 @@ pub use self::deployment_version_selector::DeploymentVersionSelector;
 pub use self::empty_response::EmptyResponse;
 pub use self::execution::Execution;
 pub use self::execution_id::ExecutionId;
+pub use self::stream_status::StreamStatus;
 pub use self::subgraph_health::SubgraphHealth;
@@ -0,0 +1,51 @@
+use async_graphql::SimpleObject;
+
+use crate::entities::BlockNumber;
+
+/// The state of the Firehose or Substreams block stream of a deployment
+#[derive(Clone, Debug, SimpleObject)]
+pub struct StreamStatus {
+    pub hash: String,
+    /// Either `firehose` or `substreams`.
+    pub kind: String,
+    pub provider: String,
+    pub is_connected: bool,
+    pub reconnects: u64,
+    pub undos: u64,
+    pub latest_block_number: Option<BlockNumber>,
+    /// Seconds since the stream last advanced its cursor.
+    pub cursor_age: Option<f64>,
+    /// Averaged over the current connection.
+    pub blocks_per_second: f64,
+    /// Averaged over the current connection.
+    pub bytes_per_second: f64,
+}
+
+impl StreamStatus {
+    pub fn new(hash: String, status: graph::components::subgraph::StreamStatus) -> Self {
+        let graph::components::subgraph::StreamStatus {
+            kind,
+            provider,
+            connected,
+            reconnects,
+            undos,
+            latest_block,
+            cursor_age,
+            blocks_per_second,
+            bytes_per_second,
+        } = status;
+
+        Self {
+            hash,
+            kind: kind.to_string(),
+            provider,
+            is_connected: connected,
+            reconnects,
+            undos,
+            latest_block_number: latest_block.map(Into::into),
+            cursor_age: cursor_age.map(|age| age.as_secs_f64()),
+            blocks_per_second,
+            bytes_per_second,
+        }
+    }
+}
@@ -5,8 +5,10 @@ use async_graphql::Result;
 use crate::entities::DeploymentInfo;
 use crate::entities::DeploymentSelector;
 use crate::entities::DeploymentVersionSelector;
+use crate::entities::StreamStatus;
 
 mod info;
+mod stream_status;
 
 pub struct DeploymentQuery;
 
@@ -26,4 +28,19 @@ impl DeploymentQuery {
     ) -> Result<Vec<DeploymentInfo>> {
         info::run(ctx, deployment, version)
     }
+
+    /// Returns the state of the Firehose or Substreams block streams of one, multiple,
+    /// or all deployments.
+    ///
+    /// Only streams of deployments that are indexed by the graph-node instance
+    /// that serves the request are included.
+    pub async fn stream_status(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "A selector for one or multiple deployments.
+                          When not provided, it matches all deployments.")]
+        deployment: Option<DeploymentSelector>,
+    ) -> Result<Vec<StreamStatus>> {
+        stream_status::run(ctx, deployment)
+    }
 }
@@ -0,0 +1,41 @@
+use std::collections::HashSet;
+
+use async_graphql::Context;
+use async_graphql::Result;
+use graph::components::subgraph::STREAM_STATUSES;
+use graph::prelude::DeploymentHash;
+
+use crate::entities::DeploymentSelector;
+use crate::entities::StreamStatus;
+use crate::resolvers::context::GraphmanContext;
+
+pub fn run(ctx: &Context<'_>, deployment: Option<DeploymentSelector>) -> Result<Vec<StreamStatus>> {
+    let ctx = GraphmanContext::new(ctx)?;
+
+    let deployment = deployment
+        .map(TryInto::try_into)
+        .transpose()?
+        .unwrap_or(graphman::deployment::DeploymentSelector::All);
+
+    let deployments = graphman::commands::deployment::info::load_deployments(
+        ctx.primary_pool.clone(),
+        &deployment,
+        &graphman::deployment::DeploymentVersionSelector::All,
+    )?;
+
+    // A deployment is listed once for every subgraph name that uses it
+    let mut seen = HashSet::new();
+
+    let resp = deployments
+        .into_iter()
+        .filter(|deployment| seen.insert(deployment.hash.clone()))
+        .filter_map(|deployment| {
+            let hash = DeploymentHash::new(deployment.hash.clone()).ok()?;
+            let status = STREAM_STATUSES.status(&hash)?;
+
+            Some(StreamStatus::new(deployment.hash, status))
+        })
+        .collect();
+
+    Ok(resp)
+}
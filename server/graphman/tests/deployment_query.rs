@@ -1,6 +1,7 @@
 pub mod util;
 
 use graph::components::store::{QueryStoreManager, SubgraphStore};
+use graph::components::subgraph::STREAM_STATUSES;
 use graph::data::subgraph::DeploymentHash;
 use graph::prelude::QueryTarget;
 
@@ -249,3 +250,60 @@ fn graphql_returns_empty_deployment_info_when_there_are_no_deployments() {
         assert_eq!(resp, expected_resp);
     });
 }
+
+#[test]
+fn graphql_returns_stream_status() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new("subgraph_1").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let deployment_hash = DeploymentHash::new("subgraph_2").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        STREAM_STATUSES.connect(&deployment_hash, "firehose", "provider_1", false);
+        STREAM_STATUSES.connect(&deployment_hash, "firehose", "provider_2", true);
+        STREAM_STATUSES.advance(&deployment_hash, 10, true);
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"{
+                    deployment {
+                        streamStatus {
+                            hash
+                            kind
+                            provider
+                            isConnected
+                            reconnects
+                            undos
+                            latestBlockNumber
+                        }
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        STREAM_STATUSES.remove(&deployment_hash);
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "streamStatus": [
+                        {
+                            "hash": "subgraph_2",
+                            "kind": "firehose",
+                            "provider": "provider_2",
+                            "isConnected": true,
+                            "reconnects": 1,
+                            "undos": 1,
+                            "latestBlockNumber": "10"
+                        }
+                    ]
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+    });
+}
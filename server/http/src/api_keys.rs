@@ -0,0 +1,240 @@
+//! Checks API keys for requests to the query port. Keys are loaded from
+//! the store periodically so that keys that are created or revoked with
+//! `graphman` take effect without a restart.
+//!
+//! Rate limits use a fixed window of one minute per key and are enforced
+//! by each query node on its own; with several query nodes behind a load
+//! balancer, a key can make up to its limit against each of them
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use graph::components::server::api_key::{self, ApiKey, ApiKeyStore, ApiKeys, API_KEY_HEADER};
+use graph::components::server::query::ServerResponse;
+use graph::data::query::QueryTarget;
+use graph::env::ENV_VARS;
+use graph::hyper::{HeaderMap, StatusCode};
+use graph::prelude::{MetricsRegistry, StoreError};
+use graph::prometheus::CounterVec;
+use graph::slog::{error, Logger};
+use graph::tokio;
+
+use crate::sql::error_response;
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Why a request was rejected; used as the `reason` label of the
+/// `query_api_key_rejections` metric
+#[derive(Clone, Copy)]
+enum Rejection {
+    Missing,
+    Unknown,
+    Deployment,
+    RateLimit,
+}
+
+impl Rejection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Rejection::Missing => "missing",
+            Rejection::Unknown => "unknown",
+            Rejection::Deployment => "deployment",
+            Rejection::RateLimit => "rate_limit",
+        }
+    }
+
+    fn status_and_message(&self) -> (StatusCode, String) {
+        match self {
+            Rejection::Missing => (
+                StatusCode::UNAUTHORIZED,
+                format!("an API key is required in the `{}` header", API_KEY_HEADER),
+            ),
+            Rejection::Unknown => (StatusCode::UNAUTHORIZED, "invalid API key".to_string()),
+            Rejection::Deployment => (
+                StatusCode::FORBIDDEN,
+                "the API key does not allow querying this subgraph".to_string(),
+            ),
+            Rejection::RateLimit => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "the rate limit for the API key has been exceeded".to_string(),
+            ),
+        }
+    }
+}
+
+/// The requests a key has made in the current rate limit window
+struct Window {
+    start: Instant,
+    requests: u32,
+}
+
+pub struct ApiKeyGuard {
+    logger: Logger,
+    store: Arc<dyn ApiKeyStore>,
+    keys: RwLock<Arc<ApiKeys>>,
+    windows: Mutex<HashMap<String, Window>>,
+    requests: Box<CounterVec>,
+    rejections: Box<CounterVec>,
+}
+
+impl fmt::Debug for ApiKeyGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKeyGuard {{ }}")
+    }
+}
+
+impl ApiKeyGuard {
+    /// Create a guard and load the current keys from `store`
+    pub fn new(
+        logger: Logger,
+        store: Arc<dyn ApiKeyStore>,
+        registry: &MetricsRegistry,
+    ) -> Result<Self, StoreError> {
+        let requests = registry
+            .new_counter_vec(
+                "query_api_key_requests",
+                "the number of requests accepted for each API key",
+                vec![String::from("key")],
+            )
+            .expect("failed to create `query_api_key_requests` counter");
+        let rejections = registry
+            .new_counter_vec(
+                "query_api_key_rejections",
+                "the number of requests rejected for each API key and reason",
+                vec![String::from("key"), String::from("reason")],
+            )
+            .expect("failed to create `query_api_key_rejections` counter");
+        let keys = store.active_keys()?;
+
+        Ok(ApiKeyGuard {
+            logger,
+            store,
+            keys: RwLock::new(Arc::new(keys)),
+            windows: Mutex::new(HashMap::new()),
+            requests,
+            rejections,
+        })
+    }
+
+    /// Reload keys from the store every
+    /// `GRAPH_GRAPHQL_API_KEY_REFRESH_INTERVAL`
+    pub fn refresh_periodically(self: &Arc<Self>) {
+        let guard = self.clone();
+        graph::spawn(async move {
+            let mut interval = tokio::time::interval(ENV_VARS.graphql.api_key_refresh_interval);
+            // The first tick completes immediately, and we already loaded
+            // the keys in `new`
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let store = guard.store.clone();
+                match graph::spawn_blocking_allow_panic(move || store.active_keys()).await {
+                    Ok(Ok(keys)) => guard.set_keys(keys),
+                    Ok(Err(e)) => {
+                        error!(guard.logger, "Failed to load API keys"; "error" => e.to_string())
+                    }
+                    Err(e) => {
+                        error!(guard.logger, "Failed to load API keys"; "error" => e.to_string())
+                    }
+                }
+            }
+        });
+    }
+
+    fn set_keys(&self, keys: ApiKeys) {
+        let mut windows = self.windows.lock().unwrap();
+        windows.retain(|name, _| keys.keys.values().any(|key| &key.name == name));
+        *self.keys.write().unwrap() = Arc::new(keys);
+    }
+
+    fn allows(keys: &ApiKeys, key: &ApiKey, target: &QueryTarget) -> bool {
+        if key.deployments.is_none() {
+            return true;
+        }
+        match target {
+            QueryTarget::Deployment(id, _) => key.allows(id),
+            QueryTarget::Name(name, _) => keys
+                .names
+                .get(name.as_str())
+                .map(|id| key.allows(id))
+                .unwrap_or(false),
+        }
+    }
+
+    /// Count a request against the rate limit of `key` and return `false`
+    /// if the key has exceeded it
+    fn within_rate_limit(&self, key: &ApiKey) -> bool {
+        let Some(limit) = key.rate_limit else {
+            return true;
+        };
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+        let window = windows.entry(key.name.clone()).or_insert(Window {
+            start: now,
+            requests: 0,
+        });
+        if now.duration_since(window.start) >= RATE_LIMIT_WINDOW {
+            window.start = now;
+            window.requests = 0;
+        }
+        if window.requests >= limit {
+            return false;
+        }
+        window.requests += 1;
+        true
+    }
+
+    fn reject(&self, key: &str, rejection: Rejection) -> (StatusCode, String) {
+        self.rejections
+            .with_label_values(&[key, rejection.as_str()])
+            .inc();
+        rejection.status_and_message()
+    }
+
+    /// Check that the request with `headers` carries an API key that may
+    /// query all of `targets` and has not exceeded its rate limit. Returns
+    /// the response to send instead if it does not
+    pub fn check<'a>(
+        &self,
+        headers: &HeaderMap,
+        targets: impl IntoIterator<Item = &'a QueryTarget>,
+    ) -> Result<(), ServerResponse> {
+        self.check_headers(headers, targets)
+            .map_err(|(status, message)| error_response(status, message))
+    }
+
+    /// Like `check`, but for servers that build their own responses, like
+    /// the websocket server; returns the status and the message for
+    /// the rejection
+    pub fn check_headers<'a>(
+        &self,
+        headers: &HeaderMap,
+        targets: impl IntoIterator<Item = &'a QueryTarget>,
+    ) -> Result<(), (StatusCode, String)> {
+        let Some(secret) = headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return Err(self.reject("", Rejection::Missing));
+        };
+
+        let keys = self.keys.read().unwrap().clone();
+        let Some(key) = keys.keys.get(&api_key::hash_secret(secret.trim())) else {
+            return Err(self.reject("", Rejection::Unknown));
+        };
+
+        if !targets
+            .into_iter()
+            .all(|target| Self::allows(&keys, key, target))
+        {
+            return Err(self.reject(&key.name, Rejection::Deployment));
+        }
+        if !self.within_rate_limit(key) {
+            return Err(self.reject(&key.name, Rejection::RateLimit));
+        }
+
+        self.requests.with_label_values(&[&key.name]).inc();
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! Support for incremental delivery of the results of queries that use
+//! `@defer` or `@stream`. Clients opt into it by accepting
+//! `multipart/mixed` responses; each payload is then sent as a JSON part
+//! of a multipart response as soon as it has been computed
+use std::convert::Infallible;
+
+use graph::data::query::IncrementalResults;
+use graph::futures03::stream::{self, StreamExt};
+use graph::http_body_util::combinators::UnsyncBoxBody;
+use graph::http_body_util::{BodyExt, StreamBody};
+use graph::hyper::body::{Bytes, Frame};
+use graph::hyper::header::{ACCEPT, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE};
+use graph::hyper::{HeaderMap, Response, StatusCode};
+
+pub const MULTIPART_MIXED: &str = "multipart/mixed";
+
+const BOUNDARY: &str = "graphql";
+
+/// A response whose body may be produced while it is being sent
+pub type StreamingResponse = Response<UnsyncBoxBody<Bytes, Infallible>>;
+
+/// Return `true` if the client accepts a `multipart/mixed` response
+pub fn wants_multipart(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .map(|accept| {
+            accept
+                .split(',')
+                .any(|mime| mime.trim().starts_with(MULTIPART_MIXED))
+        })
+        .unwrap_or(false)
+}
+
+fn part(json: &str) -> Bytes {
+    Bytes::from(format!(
+        "--{BOUNDARY}\r\ncontent-type: application/json; charset=utf-8\r\n\r\n{json}\r\n"
+    ))
+}
+
+/// A multipart response that sends each of the `payloads` as its own part
+pub fn response(payloads: IncrementalResults) -> StreamingResponse {
+    let parts = payloads
+        .map(|payload| part(&payload.to_json().to_string()))
+        .chain(stream::once(async {
+            Bytes::from(format!("--{BOUNDARY}--\r\n"))
+        }))
+        .map(|bytes| Ok::<_, Infallible>(Frame::data(bytes)));
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(
+            CONTENT_TYPE,
+            format!("{MULTIPART_MIXED}; boundary=\"{BOUNDARY}\"; deferSpec=20220824"),
+        )
+        .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(StreamBody::new(parts).boxed_unsync())
+        .unwrap()
+}
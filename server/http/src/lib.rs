@@ -2,10 +2,14 @@ extern crate graph;
 extern crate graph_graphql;
 extern crate serde;
 
+mod api_keys;
+mod incremental;
 mod request;
 mod server;
 mod service;
+mod sql;
 
+pub use self::api_keys::ApiKeyGuard;
 pub use self::server::GraphQLServer;
 pub use self::service::GraphQLService;
 
@@ -6,6 +6,7 @@ use graph::components::server::server::{start, ServerHandle};
 use graph::log::factory::{ComponentLoggerConfig, ElasticComponentLoggerConfig};
 use graph::slog::info;
 
+use crate::api_keys::ApiKeyGuard;
 use crate::service::GraphQLService;
 use graph::prelude::{GraphQlRunner, Logger, LoggerFactory};
 
@@ -13,6 +14,7 @@ use graph::prelude::{GraphQlRunner, Logger, LoggerFactory};
 pub struct GraphQLServer<Q> {
     logger: Logger,
     graphql_runner: Arc<Q>,
+    api_keys: Option<Arc<ApiKeyGuard>>,
 }
 
 impl<Q: GraphQlRunner> GraphQLServer<Q> {
@@ -29,9 +31,16 @@ impl<Q: GraphQlRunner> GraphQLServer<Q> {
         GraphQLServer {
             logger,
             graphql_runner,
+            api_keys: None,
         }
     }
 
+    /// Require an API key for all queries
+    pub fn with_api_keys(mut self, api_keys: Arc<ApiKeyGuard>) -> Self {
+        self.api_keys = Some(api_keys);
+        self
+    }
+
     pub async fn start(&self, port: u16, ws_port: u16) -> Result<ServerHandle, anyhow::Error> {
         let logger = self.logger.clone();
 
@@ -42,11 +51,15 @@ impl<Q: GraphQlRunner> GraphQLServer<Q> {
 
         let graphql_runner = self.graphql_runner.clone();
 
-        let service = Arc::new(GraphQLService::new(logger.clone(), graphql_runner, ws_port));
+        let mut service = GraphQLService::new(logger.clone(), graphql_runner, ws_port);
+        if let Some(api_keys) = &self.api_keys {
+            service = service.with_api_keys(api_keys.cheap_clone());
+        }
+        let service = Arc::new(service);
 
         start(logger, port, move |req| {
             let service = service.cheap_clone();
-            async move { Ok::<_, _>(service.cheap_clone().call(req).await) }
+            async move { Ok::<_, _>(service.cheap_clone().call_streaming(req).await) }
         })
         .await
     }
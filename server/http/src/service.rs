@@ -8,7 +8,7 @@ use graph::components::graphql::GraphQlRunner;
 use graph::components::server::query::ServerResponse;
 use graph::components::server::query::ServerResult;
 use graph::components::versions::ApiVersion;
-use graph::data::query::QueryResult;
+use graph::data::query::{EntityHistoryQuery, QueryExecutionError, QueryResult, QueryResults};
 use graph::data::subgraph::DeploymentHash;
 use graph::data::subgraph::SubgraphName;
 use graph::env::ENV_VARS;
@@ -27,7 +27,10 @@ use graph::slog::Logger;
 use graph::url::form_urlencoded;
 use graph::{components::server::query::ServerError, data::query::QueryTarget};
 
+use crate::api_keys::ApiKeyGuard;
+use crate::incremental::{self, StreamingResponse};
 use crate::request::parse_graphql_request;
+use crate::sql::{self, SqlRequest};
 
 fn client_error(msg: impl Into<String>) -> ServerResponse {
     let response_obj = json!({
@@ -43,12 +46,31 @@ fn client_error(msg: impl Into<String>) -> ServerResponse {
         .unwrap()
 }
 
+/// Filter out empty strings from path segments
+fn filter_and_join_segments(segments: &[&str]) -> String {
+    segments
+        .iter()
+        .filter(|&&segment| !segment.is_empty())
+        .map(|&segment| segment)
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// What a GraphQL request should be run against
+enum RequestTarget {
+    /// A single subgraph
+    Single(QueryTarget),
+    /// Several subgraphs, each under its own namespace
+    Federated(Vec<(String, QueryTarget)>),
+}
+
 /// A Hyper Service that serves GraphQL over a POST / endpoint.
 #[derive(Debug)]
 pub struct GraphQLService<Q> {
     logger: Logger,
     graphql_runner: Arc<Q>,
     ws_port: u16,
+    api_keys: Option<Arc<ApiKeyGuard>>,
 }
 
 impl<Q> GraphQLService<Q>
@@ -61,6 +83,26 @@ where
             logger,
             graphql_runner,
             ws_port,
+            api_keys: None,
+        }
+    }
+
+    /// Require an API key for all queries
+    pub fn with_api_keys(mut self, api_keys: Arc<ApiKeyGuard>) -> Self {
+        self.api_keys = Some(api_keys);
+        self
+    }
+
+    /// Check that `request` has an API key that may query all of
+    /// `targets` if API keys are required
+    fn check_api_key<'a, T>(
+        &self,
+        request: &Request<T>,
+        targets: impl IntoIterator<Item = &'a QueryTarget>,
+    ) -> Result<(), ServerResponse> {
+        match &self.api_keys {
+            Some(api_keys) => api_keys.check(request.headers(), targets),
+            None => Ok(()),
         }
     }
 
@@ -123,17 +165,38 @@ where
         Ok(version)
     }
 
-    async fn handle_graphql_query_by_name<T: Body>(
+    fn target_by_name<T>(
         &self,
         subgraph_name: String,
-        request: Request<T>,
-    ) -> ServerResult {
-        let version = self.resolve_api_version(&request)?;
+        request: &Request<T>,
+    ) -> Result<QueryTarget, ServerError> {
+        let version = self.resolve_api_version(request)?;
         let subgraph_name = SubgraphName::new(subgraph_name.as_str()).map_err(|()| {
             ServerError::ClientError(format!("Invalid subgraph name {:?}", subgraph_name))
         })?;
 
-        self.handle_graphql_query(QueryTarget::Name(subgraph_name, version), request)
+        Ok(QueryTarget::Name(subgraph_name, version))
+    }
+
+    fn target_by_id<T>(
+        &self,
+        id: String,
+        request: &Request<T>,
+    ) -> Result<QueryTarget, ServerError> {
+        let id = DeploymentHash::new(id)
+            .map_err(|id| ServerError::ClientError(format!("Invalid subgraph id `{}`", id)))?;
+        let version = self.resolve_api_version(request)?;
+
+        Ok(QueryTarget::Deployment(id, version))
+    }
+
+    async fn handle_graphql_query_by_name<T: Body>(
+        &self,
+        subgraph_name: String,
+        request: Request<T>,
+    ) -> ServerResult {
+        let target = self.target_by_name(subgraph_name, &request)?;
+        self.handle_graphql_query(RequestTarget::Single(target), request)
             .await
     }
 
@@ -142,19 +205,156 @@ where
         id: String,
         request: Request<T>,
     ) -> ServerResult {
+        let target = self.target_by_id(id, &request)?;
+        self.handle_graphql_query(RequestTarget::Single(target), request)
+            .await
+    }
+
+    /// Parse the namespaces of a federated query from the query string of
+    /// the request. Each parameter `<namespace>=<subgraph>` makes the
+    /// subgraph available under `namespace`; the subgraph is either a
+    /// subgraph name or `id:<deployment id>`
+    fn resolve_namespaces<T>(
+        &self,
+        request: &Request<T>,
+    ) -> Result<Vec<(String, QueryTarget)>, ServerError> {
+        let version = self.resolve_api_version(request)?;
+        let query = request.uri().query().unwrap_or("");
+
+        let mut namespaces = Vec::new();
+        for (namespace, subgraph) in form_urlencoded::parse(query.as_bytes()) {
+            if namespace == "api-version" {
+                continue;
+            }
+            let target = match subgraph.strip_prefix("id:") {
+                Some(id) => {
+                    let id = DeploymentHash::new(id).map_err(|id| {
+                        ServerError::ClientError(format!("Invalid subgraph id `{}`", id))
+                    })?;
+                    QueryTarget::Deployment(id, version.clone())
+                }
+                None => {
+                    let name = SubgraphName::new(subgraph.as_ref()).map_err(|()| {
+                        ServerError::ClientError(format!("Invalid subgraph name {:?}", subgraph))
+                    })?;
+                    QueryTarget::Name(name, version.clone())
+                }
+            };
+            namespaces.push((namespace.into_owned(), target));
+        }
+
+        if namespaces.is_empty() {
+            return Err(ServerError::ClientError(
+                "Federated queries need at least one namespace, \
+                 e.g. /subgraphs/federated?<namespace>=<subgraph name>"
+                    .to_string(),
+            ));
+        }
+        Ok(namespaces)
+    }
+
+    async fn handle_federated_query<T: Body>(&self, request: Request<T>) -> ServerResult {
+        let namespaces = self.resolve_namespaces(&request)?;
+
+        self.handle_graphql_query(RequestTarget::Federated(namespaces), request)
+            .await
+    }
+
+    async fn handle_sql_query<T: Body>(&self, id: String, request: Request<T>) -> ServerResult {
+        let Some(token) = &ENV_VARS.graphql.sql_access_token else {
+            return self.handle_not_found();
+        };
+        if !sql::is_authorized(request.headers(), token) {
+            return Ok(sql::unauthorized());
+        }
+        let id = DeploymentHash::new(id)
+            .map_err(|id| ServerError::ClientError(format!("Invalid subgraph id `{}`", id)))?;
+        let version = self.resolve_api_version(&request)?;
+        let target = QueryTarget::Deployment(id, version);
+        if let Err(response) = self.check_api_key(&request, [&target]) {
+            return Ok(response);
+        }
+        let arrow = sql::wants_arrow(request.headers());
+
+        let body = request
+            .collect()
+            .await
+            .map_err(|_| ServerError::InternalError("Failed to read request body".into()))?
+            .to_bytes();
+        let SqlRequest { query, block } = SqlRequest::parse(&body)?;
+
+        match self
+            .graphql_runner
+            .cheap_clone()
+            .run_sql_query(query, block, target)
+            .await
+        {
+            Ok(result) => sql::response(&result, arrow),
+            Err(e @ QueryExecutionError::InvalidSqlQuery(_))
+            | Err(e @ QueryExecutionError::ValueParseError(_, _))
+            | Err(e @ QueryExecutionError::DeploymentNotFound(_)) => {
+                Ok(client_error(e.to_string()))
+            }
+            Err(e) => Err(ServerError::InternalError(e.to_string())),
+        }
+    }
+
+    async fn handle_history_query<T: Body>(&self, id: String, request: Request<T>) -> ServerResult {
         let id = DeploymentHash::new(id)
             .map_err(|id| ServerError::ClientError(format!("Invalid subgraph id `{}`", id)))?;
         let version = self.resolve_api_version(&request)?;
+        let target = QueryTarget::Deployment(id, version);
+        if let Err(response) = self.check_api_key(&request, [&target]) {
+            return Ok(response);
+        }
+
+        let body = request
+            .collect()
+            .await
+            .map_err(|_| ServerError::InternalError("Failed to read request body".into()))?
+            .to_bytes();
+        let query: EntityHistoryQuery =
+            serde_json::from_slice(&body).map_err(|e| ServerError::ClientError(e.to_string()))?;
 
-        self.handle_graphql_query(QueryTarget::Deployment(id, version), request)
+        match self
+            .graphql_runner
+            .cheap_clone()
+            .run_history_query(query, target)
             .await
+        {
+            Ok(history) => {
+                let body = serde_json::to_string(&history)
+                    .map_err(|e| ServerError::InternalError(e.to_string()))?;
+                Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                    .body(Full::from(body))
+                    .unwrap())
+            }
+            Err(e @ QueryExecutionError::ValueParseError(_, _))
+            | Err(e @ QueryExecutionError::DeploymentNotFound(_)) => {
+                Ok(client_error(e.to_string()))
+            }
+            Err(e) => Err(ServerError::InternalError(e.to_string())),
+        }
     }
 
     async fn handle_graphql_query<T: Body>(
         &self,
-        target: QueryTarget,
+        target: RequestTarget,
         request: Request<T>,
     ) -> ServerResult {
+        let targets: Vec<&QueryTarget> = match &target {
+            RequestTarget::Single(target) => vec![target],
+            RequestTarget::Federated(namespaces) => {
+                namespaces.iter().map(|(_, target)| target).collect()
+            }
+        };
+        if let Err(response) = self.check_api_key(&request, targets) {
+            return Ok(response);
+        }
+
         let start = Instant::now();
         let trace = {
             !ENV_VARS.graphql.query_trace_token.is_empty()
@@ -177,12 +377,20 @@ where
         let query_parsing_time = start.elapsed();
 
         let mut result = match query {
-            Ok(query) => {
-                self.graphql_runner
-                    .cheap_clone()
-                    .run_query(query, target)
-                    .await
-            }
+            Ok(query) => match target {
+                RequestTarget::Single(target) => {
+                    self.graphql_runner
+                        .cheap_clone()
+                        .run_query(query, target)
+                        .await
+                }
+                RequestTarget::Federated(namespaces) => {
+                    self.graphql_runner
+                        .cheap_clone()
+                        .run_federated_query(query, namespaces)
+                        .await
+                }
+            },
             Err(ServerError::QueryError(e)) => QueryResult::from(e).into(),
             Err(e) => return Err(e),
         };
@@ -198,12 +406,46 @@ where
         Ok(result.as_http_response())
     }
 
+    /// Run a query against `target` and send its result incrementally if
+    /// it uses `@defer` or `@stream`
+    async fn handle_incremental_query<T: Body>(
+        &self,
+        target: QueryTarget,
+        request: Request<T>,
+    ) -> Result<StreamingResponse, ServerError> {
+        if let Err(response) = self.check_api_key(&request, [&target]) {
+            return Ok(response.map(BodyExt::boxed_unsync));
+        }
+        let body = request
+            .collect()
+            .await
+            .map_err(|_| ServerError::InternalError("Failed to read request body".into()))?
+            .to_bytes();
+        let query = match parse_graphql_request(&body, false) {
+            Ok(query) => query,
+            Err(ServerError::QueryError(e)) => {
+                let result: QueryResults = QueryResult::from(e).into();
+                return Ok(result.as_http_response().map(BodyExt::boxed_unsync));
+            }
+            Err(e) => return Err(e),
+        };
+        let payloads = self
+            .graphql_runner
+            .cheap_clone()
+            .run_incremental_query(query, target)
+            .await;
+        Ok(incremental::response(payloads))
+    }
+
     // Handles OPTIONS requests
     fn handle_graphql_options<T>(&self, _request: Request<T>) -> ServerResult {
         Ok(Response::builder()
             .status(200)
             .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
-            .header(ACCESS_CONTROL_ALLOW_HEADERS, "Content-Type, User-Agent")
+            .header(
+                ACCESS_CONTROL_ALLOW_HEADERS,
+                "Content-Type, User-Agent, X-Api-Key",
+            )
             .header(ACCESS_CONTROL_ALLOW_METHODS, "GET, OPTIONS, POST")
             .header(CONTENT_TYPE, "text/html; charset=utf-8")
             .body(Full::from(""))
@@ -291,16 +533,6 @@ where
             }
         }
 
-        // Filter out empty strings from path segments
-        fn filter_and_join_segments(segments: &[&str]) -> String {
-            segments
-                .iter()
-                .filter(|&&segment| !segment.is_empty())
-                .map(|&segment| segment)
-                .collect::<Vec<&str>>()
-                .join("/")
-        }
-
         let is_mutation = req
             .uri()
             .query()
@@ -336,6 +568,16 @@ where
                     .await
             }
             (Method::OPTIONS, ["subgraphs", "id", _]) => self.handle_graphql_options(req),
+            (Method::POST, &["subgraphs", "id", subgraph_id, "sql"]) => {
+                self.handle_sql_query(subgraph_id.to_owned(), req).await
+            }
+            (Method::OPTIONS, ["subgraphs", "id", _, "sql"]) => self.handle_graphql_options(req),
+            (Method::POST, &["subgraphs", "id", subgraph_id, "history"]) => {
+                self.handle_history_query(subgraph_id.to_owned(), req).await
+            }
+            (Method::OPTIONS, ["subgraphs", "id", _, "history"]) => {
+                self.handle_graphql_options(req)
+            }
             (Method::POST, path @ ["subgraphs", "name", ..]) => {
                 let subgraph_name = filter_and_join_segments(&path[2..]);
                 self.handle_graphql_query_by_name(subgraph_name, req).await
@@ -343,18 +585,54 @@ where
 
             (Method::OPTIONS, ["subgraphs", "name", ..]) => self.handle_graphql_options(req),
 
+            (Method::POST, &["subgraphs", "federated"]) => self.handle_federated_query(req).await,
+            (Method::OPTIONS, ["subgraphs", "federated"]) => self.handle_graphql_options(req),
+
             _ => self.handle_not_found(),
         }
     }
 
+    /// Like `call`, but sends the result of GraphQL queries against a
+    /// single subgraph incrementally when the client accepts a
+    /// `multipart/mixed` response
+    pub async fn call_streaming<T: Body + std::fmt::Debug>(
+        &self,
+        req: Request<T>,
+    ) -> StreamingResponse {
+        if req.method() == Method::POST && incremental::wants_multipart(req.headers()) {
+            let path = req.uri().path().to_owned();
+            let segments: Vec<_> = path.split('/').skip(1).collect();
+            let target = match segments.as_slice() {
+                &["subgraphs", "id", id] => Some(self.target_by_id(id.to_owned(), &req)),
+                ["subgraphs", "name", name @ ..] => {
+                    Some(self.target_by_name(filter_and_join_segments(name), &req))
+                }
+                _ => None,
+            };
+            if let Some(target) = target {
+                let response = match target {
+                    Ok(target) => self.handle_incremental_query(target, req).await,
+                    Err(e) => Err(e),
+                };
+                return response
+                    .unwrap_or_else(|e| self.error_response(e).map(BodyExt::boxed_unsync));
+            }
+        }
+        self.call(req).await.map(BodyExt::boxed_unsync)
+    }
+
     pub async fn call<T: Body + std::fmt::Debug>(&self, req: Request<T>) -> ServerResponse {
         // Returning Err here will prevent the client from receiving any response.
         // Instead, we generate a Response with an error code and return Ok
-        let result = self.handle_call(req).await;
-
-        match result {
+        match self.handle_call(req).await {
             Ok(response) => response,
-            Err(err @ ServerError::ClientError(_)) => {
+            Err(err) => self.error_response(err),
+        }
+    }
+
+    fn error_response(&self, err: ServerError) -> ServerResponse {
+        match err {
+            err @ ServerError::ClientError(_) => {
                 let response_obj = json!({
                     "error": err.to_string()
                 });
@@ -367,7 +645,7 @@ where
                     .body(Full::from(response_str))
                     .unwrap()
             }
-            Err(err @ ServerError::QueryError(_)) => {
+            err @ ServerError::QueryError(_) => {
                 error!(self.logger, "GraphQLService call failed: {}", err);
 
                 let response_obj = json!({
@@ -382,7 +660,7 @@ where
                     .body(Full::from(response_str))
                     .unwrap()
             }
-            Err(err @ ServerError::InternalError(_)) => {
+            err @ ServerError::InternalError(_) => {
                 error!(self.logger, "GraphQLService call failed: {}", err);
 
                 Response::builder()
@@ -401,13 +679,22 @@ mod tests {
     use graph::data::value::{Object, Word};
     use graph::http_body_util::{BodyExt, Full};
     use graph::hyper::body::Bytes;
-    use graph::hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+    use graph::hyper::header::{ACCEPT, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE};
     use graph::hyper::{Method, Request, StatusCode};
     use graph::prelude::serde_json::json;
 
-    use graph::data::query::{QueryResults, QueryTarget};
+    use graph::data::query::{
+        CacheStatus, EntityHistory, EntityHistoryQuery, EntityVersion, IncrementalPart,
+        IncrementalPayload, IncrementalResults, QueryResults, QueryTarget, SqlQueryResult, Trace,
+    };
+    use graph::futures03::stream::{self, StreamExt};
     use graph::prelude::*;
 
+    use std::collections::HashMap;
+
+    use graph::components::server::api_key::{hash_secret, ApiKey, ApiKeyStore, ApiKeys};
+
+    use crate::api_keys::ApiKeyGuard;
     use crate::test_utils;
 
     use super::GraphQLService;
@@ -449,6 +736,79 @@ mod tests {
             ))
         }
 
+        async fn run_incremental_query(
+            self: Arc<Self>,
+            query: Query,
+            target: QueryTarget,
+        ) -> IncrementalResults {
+            let initial = self.run_query(query, target).await;
+            let deferred =
+                QueryResults::from(Object::from_iter([(Word::from("age"), r::Value::Int(42))]));
+            stream::iter(vec![
+                IncrementalPayload::new(IncrementalPart::Initial(initial), true),
+                IncrementalPayload::new(
+                    IncrementalPart::Deferred {
+                        label: Some("details".to_string()),
+                        results: deferred,
+                    },
+                    false,
+                ),
+            ])
+            .boxed()
+        }
+
+        async fn run_federated_query(
+            self: Arc<Self>,
+            query: Query,
+            namespaces: Vec<(String, QueryTarget)>,
+        ) -> QueryResults {
+            let mut results = QueryResults::empty(Trace::None);
+            for (namespace, target) in namespaces {
+                let result = self.cheap_clone().run_query(query.clone(), target).await;
+                results.append(
+                    Arc::new(result.nest(Word::from(namespace))),
+                    CacheStatus::default(),
+                );
+            }
+            results
+        }
+
+        async fn run_sql_query(
+            self: Arc<Self>,
+            _sql: String,
+            _block: Option<BlockNumber>,
+            _target: QueryTarget,
+        ) -> Result<SqlQueryResult, QueryExecutionError> {
+            Ok(SqlQueryResult {
+                columns: vec!["name".to_string()],
+                rows: vec![vec![json!("Jordi")]],
+            })
+        }
+
+        async fn run_history_query(
+            self: Arc<Self>,
+            query: EntityHistoryQuery,
+            _target: QueryTarget,
+        ) -> Result<EntityHistory, QueryExecutionError> {
+            let version = |block_from, block_to, name: &str| EntityVersion {
+                block_from,
+                block_to,
+                data: r::Value::Object(Object::from_iter([
+                    (
+                        Word::from("id"),
+                        r::Value::String(query.id.clone().unwrap()),
+                    ),
+                    (Word::from("name"), r::Value::String(name.to_string())),
+                ])),
+            };
+            Ok(EntityHistory {
+                versions: vec![
+                    version(query.from_block, Some(query.to_block), "Jordi"),
+                    version(query.to_block, None, "Jordan"),
+                ],
+            })
+        }
+
         async fn run_subscription(
             self: Arc<Self>,
             _subscription: Subscription,
@@ -557,4 +917,241 @@ mod tests {
             .expect("Query result field \"name\" is not a string");
         assert_eq!(name, "Jordi".to_string());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn posting_federated_queries_yields_namespaced_response() {
+        let logger = Logger::root(slog::Discard, o!());
+        let graphql_runner = Arc::new(TestGraphQlRunner);
+
+        let service = GraphQLService::new(logger, graphql_runner, 8001);
+
+        let request: Request<Full<Bytes>> = Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+            .header(CONTENT_LENGTH, 100)
+            .uri(format!(
+                "http://localhost:8000/subgraphs/federated?users=id:{}&people=people/main",
+                USERS.as_str()
+            ))
+            .body(Full::from(
+                "{\"query\": \"{ users { name } people { name } }\"}",
+            ))
+            .unwrap();
+
+        let response = service.call(request).await;
+
+        let data = test_utils::assert_successful_response(response).await;
+        for namespace in ["users", "people"] {
+            let name = data
+                .get(namespace)
+                .and_then(|ns| ns.get("name"))
+                .and_then(|name| name.as_str());
+            assert_eq!(name, Some("Jordi"), "namespace {namespace}");
+        }
+    }
+
+    #[tokio::test]
+    async fn posting_federated_queries_without_namespaces_yields_error_response() {
+        let logger = Logger::root(slog::Discard, o!());
+        let graphql_runner = Arc::new(TestGraphQlRunner);
+
+        let service = GraphQLService::new(logger, graphql_runner, 8001);
+
+        let request: Request<Full<Bytes>> = Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+            .header(CONTENT_LENGTH, 100)
+            .uri("http://localhost:8000/subgraphs/federated")
+            .body(Full::from("{\"query\": \"{ users { name } }\"}"))
+            .unwrap();
+
+        let response = service.call(request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn posting_sql_queries_without_access_token_is_not_found() {
+        let logger = Logger::root(slog::Discard, o!());
+        let graphql_runner = Arc::new(TestGraphQlRunner);
+
+        let service = GraphQLService::new(logger, graphql_runner, 8001);
+
+        let request: Request<Full<Bytes>> = Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_LENGTH, 100)
+            .header(AUTHORIZATION, "Bearer secret")
+            .uri("http://localhost:8000/subgraphs/id/QmWmyoMoctfbAaiEs2G46gpeUmhqFRDW6KWo64y5r581Vz/sql")
+            .body(Full::from("{\"query\": \"select name from users\"}"))
+            .unwrap();
+
+        // `GRAPH_SQL_ACCESS_TOKEN` is not set in tests, which disables
+        // the endpoint
+        let response = service.call(request).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn posting_incremental_queries_yields_multipart_response() {
+        let logger = Logger::root(slog::Discard, o!());
+        let graphql_runner = Arc::new(TestGraphQlRunner);
+
+        let service = GraphQLService::new(logger, graphql_runner, 8001);
+
+        let request: Request<Full<Bytes>> = Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/json")
+            .header(
+                ACCEPT,
+                "multipart/mixed; deferSpec=20220824, application/json",
+            )
+            .uri(format!(
+                "http://localhost:8000/subgraphs/id/{}",
+                USERS.clone()
+            ))
+            .body(Full::from(
+                "{\"query\": \"{ name ... @defer(label: \\\"details\\\") { age } }\"}",
+            ))
+            .unwrap();
+
+        let response = service.call_streaming(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers().get(CONTENT_TYPE).unwrap();
+        assert!(content_type
+            .to_str()
+            .unwrap()
+            .starts_with("multipart/mixed; boundary=\"graphql\""));
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let parts: Vec<serde_json::Value> = body
+            .split("--graphql")
+            .filter_map(|part| part.split("\r\n\r\n").nth(1))
+            .map(|json| serde_json::from_str(json.trim()).unwrap())
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                json!({ "data": { "name": "Jordi" }, "hasNext": true }),
+                json!({
+                    "incremental": [{ "data": { "age": 42 }, "path": [], "label": "details" }],
+                    "hasNext": false
+                }),
+            ]
+        );
+        assert!(body.ends_with("--graphql--\r\n"));
+    }
+
+    struct TestApiKeyStore;
+
+    impl ApiKeyStore for TestApiKeyStore {
+        fn active_keys(&self) -> Result<ApiKeys, StoreError> {
+            let key = ApiKey {
+                name: "users-only".to_string(),
+                deployments: Some(vec![USERS.clone()]),
+                rate_limit: Some(2),
+            };
+            Ok(ApiKeys {
+                keys: HashMap::from([(hash_secret("secret"), key)]),
+                names: HashMap::new(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn queries_require_an_allowed_api_key() {
+        let logger = Logger::root(slog::Discard, o!());
+        let graphql_runner = Arc::new(TestGraphQlRunner);
+        let api_keys = ApiKeyGuard::new(
+            logger.clone(),
+            Arc::new(TestApiKeyStore),
+            &MetricsRegistry::mock(),
+        )
+        .unwrap();
+
+        let service =
+            GraphQLService::new(logger, graphql_runner, 8001).with_api_keys(Arc::new(api_keys));
+
+        let request = |id: &str, key: Option<&str>| {
+            let mut builder = Request::builder()
+                .method(Method::POST)
+                .header(CONTENT_TYPE, "application/json")
+                .header(CONTENT_LENGTH, 100)
+                .uri(format!("http://localhost:8000/subgraphs/id/{}", id));
+            if let Some(key) = key {
+                builder = builder.header("X-Api-Key", key);
+            }
+            builder
+                .body(Full::<Bytes>::from("{\"query\": \"{ name }\"}"))
+                .unwrap()
+        };
+
+        let response = service.call(request(USERS.as_str(), None)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = service.call(request(USERS.as_str(), Some("guess"))).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = service.call(request("QmOther", Some("secret"))).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // The key allows two requests per minute
+        for _ in 0..2 {
+            let response = service.call(request(USERS.as_str(), Some("secret"))).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = service.call(request(USERS.as_str(), Some("secret"))).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn posting_history_queries_returns_versions() {
+        let logger = Logger::root(slog::Discard, o!());
+        let graphql_runner = Arc::new(TestGraphQlRunner);
+
+        let service = GraphQLService::new(logger, graphql_runner, 8001);
+
+        let request: Request<Full<Bytes>> = Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/json")
+            .uri(format!(
+                "http://localhost:8000/subgraphs/id/{}/history",
+                USERS.clone()
+            ))
+            .body(Full::from(
+                "{\"entity\": \"User\", \"id\": \"u1\", \"fromBlock\": 10, \"toBlock\": 20}",
+            ))
+            .unwrap();
+
+        let response = service.call(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body_bytes = response.body().clone().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "versions": [
+                    { "blockFrom": 10, "blockTo": 20, "data": { "id": "u1", "name": "Jordi" } },
+                    { "blockFrom": 20, "blockTo": null, "data": { "id": "u1", "name": "Jordan" } },
+                ]
+            })
+        );
+
+        // Requests with unknown fields are rejected
+        let request: Request<Full<Bytes>> = Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/json")
+            .uri(format!(
+                "http://localhost:8000/subgraphs/id/{}/history",
+                USERS.clone()
+            ))
+            .body(Full::from(
+                "{\"entity\": \"User\", \"from\": 10, \"toBlock\": 20}",
+            ))
+            .unwrap();
+
+        let response = service.call(request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
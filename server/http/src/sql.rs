@@ -0,0 +1,192 @@
+//! Support for the SQL endpoint at `/subgraphs/id/<ID>/sql`. Requests are
+//! JSON objects `{ "query": "select ..", "block": 123 }` where `block` is
+//! optional. Results are returned as JSON unless the client asks for an
+//! Arrow IPC stream with an `Accept` header
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray, Int64Array, StringArray};
+use arrow::datatypes::{Field, Schema};
+use arrow::error::ArrowError;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use graph::components::server::query::{ServerError, ServerResponse};
+use graph::data::query::SqlQueryResult;
+use graph::http_body_util::Full;
+use graph::hyper::header::{ACCEPT, ACCESS_CONTROL_ALLOW_ORIGIN, AUTHORIZATION, CONTENT_TYPE};
+use graph::hyper::{HeaderMap, Response, StatusCode};
+use graph::prelude::{serde_json, BlockNumber};
+use serde::Deserialize;
+
+pub const ARROW_STREAM: &str = "application/vnd.apache.arrow.stream";
+
+#[derive(Deserialize)]
+pub struct SqlRequest {
+    pub query: String,
+    pub block: Option<BlockNumber>,
+}
+
+impl SqlRequest {
+    pub fn parse(body: &[u8]) -> Result<Self, ServerError> {
+        serde_json::from_slice(body).map_err(|e| ServerError::ClientError(e.to_string()))
+    }
+}
+
+/// Check that the request carries the bearer token `token`.
+pub fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    let Some(bearer) = headers
+        .get(AUTHORIZATION)
+        .and_then(|header| header.as_bytes().strip_prefix(b"Bearer "))
+    else {
+        return false;
+    };
+    // Compare hashes rather than the tokens themselves so that the time
+    // the comparison takes does not reveal anything about the token
+    blake3::hash(bearer) == blake3::hash(token.as_bytes())
+}
+
+/// Return `true` if the client prefers an Arrow IPC stream over JSON
+pub fn wants_arrow(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .map(|accept| accept.split(',').any(|mime| mime.trim() == ARROW_STREAM))
+        .unwrap_or(false)
+}
+
+pub fn unauthorized() -> ServerResponse {
+    error_response(
+        StatusCode::UNAUTHORIZED,
+        "a valid bearer token is required to run SQL queries",
+    )
+}
+
+pub fn error_response(status: StatusCode, msg: impl Into<String>) -> ServerResponse {
+    let body = serde_json::json!({ "error": msg.into() }).to_string();
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Full::from(body))
+        .unwrap()
+}
+
+pub fn response(result: &SqlQueryResult, arrow: bool) -> Result<ServerResponse, ServerError> {
+    let (content_type, body) = if arrow {
+        let body = to_arrow(result).map_err(|e| ServerError::InternalError(e.to_string()))?;
+        (ARROW_STREAM, body)
+    } else {
+        let body =
+            serde_json::to_vec(result).map_err(|e| ServerError::InternalError(e.to_string()))?;
+        ("application/json", body)
+    };
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, content_type)
+        .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Full::from(body))
+        .unwrap())
+}
+
+/// Encode `result` as an Arrow IPC stream with a single record batch.
+/// Columns whose values are all booleans or all fit into an `i64` get
+/// those types; everything else, in particular `BigInt` and `BigDecimal`
+/// values, is turned into strings so that no precision is lost
+fn to_arrow(result: &SqlQueryResult) -> Result<Vec<u8>, ArrowError> {
+    use serde_json::Value;
+
+    let mut fields = Vec::with_capacity(result.columns.len());
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(result.columns.len());
+    for (idx, name) in result.columns.iter().enumerate() {
+        let values = result
+            .rows
+            .iter()
+            .map(move |row| row.get(idx).unwrap_or(&Value::Null));
+        let mut non_null = values.clone().filter(|value| !value.is_null());
+
+        let array: ArrayRef = if non_null.clone().all(Value::is_boolean) {
+            Arc::new(values.map(Value::as_bool).collect::<BooleanArray>())
+        } else if non_null.all(|value| value.as_i64().is_some()) {
+            Arc::new(values.map(Value::as_i64).collect::<Int64Array>())
+        } else {
+            Arc::new(
+                values
+                    .map(|value| match value {
+                        Value::Null => None,
+                        Value::String(s) => Some(s.clone()),
+                        value => Some(value.to_string()),
+                    })
+                    .collect::<StringArray>(),
+            )
+        };
+        fields.push(Field::new(name, array.data_type().clone(), true));
+        arrays.push(array);
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    let batch = if arrays.is_empty() {
+        RecordBatch::new_empty(schema.clone())
+    } else {
+        RecordBatch::try_new(schema.clone(), arrays)?
+    };
+    let mut writer = StreamWriter::try_new(Vec::new(), &schema)?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    writer.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Array, BooleanArray, Int64Array, StringArray};
+    use arrow::ipc::reader::StreamReader;
+    use graph::data::query::SqlQueryResult;
+    use graph::hyper::header::{ACCEPT, AUTHORIZATION};
+    use graph::hyper::HeaderMap;
+    use graph::prelude::serde_json::json;
+
+    use super::{is_authorized, to_arrow, wants_arrow, ARROW_STREAM};
+
+    #[test]
+    fn authorization() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_authorized(&headers, "secret"));
+        headers.insert(AUTHORIZATION, "Bearer wrong".parse().unwrap());
+        assert!(!is_authorized(&headers, "secret"));
+        headers.insert(AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert!(is_authorized(&headers, "secret"));
+
+        assert!(!wants_arrow(&headers));
+        headers.insert(
+            ACCEPT,
+            format!("application/json, {ARROW_STREAM}").parse().unwrap(),
+        );
+        assert!(wants_arrow(&headers));
+    }
+
+    #[test]
+    fn arrow_encoding() {
+        let result = SqlQueryResult {
+            columns: vec!["symbol".into(), "count".into(), "big".into(), "flag".into()],
+            rows: vec![
+                vec![json!("GRT"), json!(3), json!(1), json!(true)],
+                vec![json!("ETH"), json!(null), json!(1e30), json!(null)],
+            ],
+        };
+        let bytes = to_arrow(&result).unwrap();
+
+        let mut reader = StreamReader::try_new(bytes.as_slice(), None).unwrap();
+        let batch = reader.next().unwrap().unwrap();
+        assert_eq!(2, batch.num_rows());
+        let symbols = batch.column(0).as_any().downcast_ref::<StringArray>();
+        assert_eq!(Some("ETH"), symbols.map(|symbols| symbols.value(1)));
+        let counts = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(3, counts.value(0));
+        assert!(counts.is_null(1));
+        // Numbers that do not fit into an `i64` are turned into strings
+        assert!(batch.column(2).as_any().is::<StringArray>());
+        assert!(batch.column(3).as_any().is::<BooleanArray>());
+    }
+}
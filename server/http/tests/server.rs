@@ -2,7 +2,10 @@ use graph::http::StatusCode;
 use std::time::Duration;
 
 use graph::data::{
-    query::{QueryResults, QueryTarget},
+    query::{
+        EntityHistory, EntityHistoryQuery, IncrementalResults, QueryResults, QueryTarget,
+        SqlQueryResult,
+    },
     value::{Object, Word},
 };
 use graph::prelude::*;
@@ -63,6 +66,39 @@ impl GraphQlRunner for TestGraphQlRunner {
         .into()
     }
 
+    async fn run_incremental_query(
+        self: Arc<Self>,
+        _query: Query,
+        _target: QueryTarget,
+    ) -> IncrementalResults {
+        unimplemented!();
+    }
+
+    async fn run_federated_query(
+        self: Arc<Self>,
+        _query: Query,
+        _namespaces: Vec<(String, QueryTarget)>,
+    ) -> QueryResults {
+        unimplemented!();
+    }
+
+    async fn run_sql_query(
+        self: Arc<Self>,
+        _sql: String,
+        _block: Option<BlockNumber>,
+        _target: QueryTarget,
+    ) -> Result<SqlQueryResult, QueryExecutionError> {
+        unimplemented!();
+    }
+
+    async fn run_history_query(
+        self: Arc<Self>,
+        _query: EntityHistoryQuery,
+        _target: QueryTarget,
+    ) -> Result<EntityHistory, QueryExecutionError> {
+        unimplemented!();
+    }
+
     async fn run_subscription(
         self: Arc<Self>,
         _subscription: Subscription,
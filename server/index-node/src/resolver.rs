@@ -8,10 +8,14 @@ use web3::types::Address;
 
 use git_testament::{git_testament, CommitKind};
 use graph::blockchain::{Blockchain, BlockchainKind, BlockchainMap};
-use graph::components::store::{BlockPtrForNumber, BlockStore, QueryPermit, Store};
+use graph::components::store::{
+    log_level_name, parse_log_level, BlockPtrForNumber, BlockStore, DeploymentLogFilter,
+    QueryPermit, Store,
+};
+use graph::components::subgraph::WARM_UPS;
 use graph::components::versions::VERSIONS;
 use graph::data::graphql::{object, IntoValue, ObjectOrInterface, ValueMap};
-use graph::data::subgraph::{status, DeploymentFeatures};
+use graph::data::subgraph::{status, DeploymentFeatures, SubgraphFeature};
 use graph::data::value::Object;
 use graph::futures03::TryFutureExt;
 use graph::prelude::*;
@@ -22,6 +26,9 @@ use crate::auth::PoiProtection;
 /// Timeout for calls to fetch the block from JSON-RPC or Firehose.
 const BLOCK_HASH_FROM_NUMBER_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// The most logs that one `deploymentLogs` query returns
+const MAX_DEPLOYMENT_LOGS: i32 = 1000;
+
 git_testament!(TESTAMENT);
 
 lazy_static! {
@@ -122,6 +129,16 @@ impl<S: Store> IndexNodeResolver<S> {
         }
     }
 
+    /// The statuses from the store together with the warm-ups that are
+    /// running in this node
+    fn statuses(&self, filter: status::Filter) -> Result<Vec<status::Info>, QueryExecutionError> {
+        let mut infos = self.store.status(filter)?;
+        for info in &mut infos {
+            info.warm_up = WARM_UPS.status(info.id);
+        }
+        Ok(infos)
+    }
+
     fn resolve_indexing_statuses(&self, field: &a::Field) -> Result<r::Value, QueryExecutionError> {
         let deployments = field
             .argument_value("subgraphs")
@@ -137,9 +154,7 @@ impl<S: Store> IndexNodeResolver<S> {
             })
             .unwrap_or_else(Vec::new);
 
-        let infos = self
-            .store
-            .status(status::Filter::Deployments(deployments))?;
+        let infos = self.statuses(status::Filter::Deployments(deployments))?;
         Ok(infos.into_value())
     }
 
@@ -160,9 +175,7 @@ impl<S: Store> IndexNodeResolver<S> {
             "name" => &subgraph_name
         );
 
-        let infos = self
-            .store
-            .status(status::Filter::SubgraphName(subgraph_name))?;
+        let infos = self.statuses(status::Filter::SubgraphName(subgraph_name))?;
 
         Ok(infos.into_value())
     }
@@ -457,7 +470,7 @@ impl<S: Store> IndexNodeResolver<S> {
             "current_version" => current_version,
         );
 
-        let infos = self.store.status(status::Filter::SubgraphVersion(
+        let infos = self.statuses(status::Filter::SubgraphVersion(
             subgraph_name,
             current_version,
         ))?;
@@ -608,11 +621,169 @@ impl<S: Store> IndexNodeResolver<S> {
                 )
                 .await?
             }
+            BlockchainKind::Solana => {
+                let unvalidated_subgraph_manifest =
+                    UnvalidatedSubgraphManifest::<graph_chain_solana::Chain>::resolve(
+                        deployment_hash.clone(),
+                        raw_yaml,
+                        &self.link_resolver,
+                        &self.logger,
+                        max_spec_version,
+                    )
+                    .await?;
+
+                Self::validate_and_extract_features(
+                    &self.store.subgraph_store(),
+                    unvalidated_subgraph_manifest,
+                )
+                .await?
+            }
+            BlockchainKind::Bitcoin => {
+                let unvalidated_subgraph_manifest =
+                    UnvalidatedSubgraphManifest::<graph_chain_bitcoin::Chain>::resolve(
+                        deployment_hash.clone(),
+                        raw_yaml,
+                        &self.link_resolver,
+                        &self.logger,
+                        max_spec_version,
+                    )
+                    .await?;
+
+                Self::validate_and_extract_features(
+                    &self.store.subgraph_store(),
+                    unvalidated_subgraph_manifest,
+                )
+                .await?
+            }
         };
 
         Ok(result)
     }
 
+    async fn validate_manifest<C: Blockchain>(
+        &self,
+        deployment_hash: &DeploymentHash,
+        raw_yaml: serde_yaml::Mapping,
+    ) -> r::Value {
+        let unvalidated = match UnvalidatedSubgraphManifest::<C>::resolve(
+            deployment_hash.clone(),
+            raw_yaml,
+            &self.link_resolver,
+            &self.logger,
+            ENV_VARS.max_spec_version.clone(),
+        )
+        .await
+        {
+            Ok(unvalidated) => unvalidated,
+            Err(e) => return manifest_validation(None, None, &[e.to_string()], vec![]),
+        };
+
+        let spec_version = unvalidated.spec_version().to_string();
+        let declared = unvalidated.declared_features().clone();
+        let used = unvalidated.used_features();
+        let features = SubgraphFeature::ALL
+            .iter()
+            .filter(|feature| declared.contains(*feature) || used.contains(*feature))
+            .map(|feature| {
+                object! {
+                    __typename: "FeatureSupport",
+                    feature: feature.to_string(),
+                    declared: declared.contains(feature),
+                    used: used.contains(feature),
+                    enabled: feature.is_enabled(),
+                    configFlag: feature.config_flag(),
+                }
+            })
+            .collect();
+
+        // Validate the graft base the way deploying a new subgraph does
+        let mut errors = Vec::new();
+        let network = match unvalidated
+            .validate(self.store.subgraph_store(), true)
+            .await
+        {
+            Ok(manifest) => {
+                let network = manifest.network_name();
+                if let Err(e) = self.blockchain_map.get::<C>(network.as_str().into()) {
+                    errors.push(SubgraphRegistrarError::NetworkNotSupported(e).to_string());
+                }
+                Some(network)
+            }
+            Err(validation_errors) => {
+                errors.extend(validation_errors.into_iter().map(|e| e.to_string()));
+                None
+            }
+        };
+
+        manifest_validation(Some(spec_version), network, &errors, features)
+    }
+
+    /// Check whether this node would accept a deployment of the manifest
+    /// with IPFS hash `subgraphId` without deploying it
+    async fn resolve_validate_manifest(
+        &self,
+        field: &a::Field,
+    ) -> Result<r::Value, QueryExecutionError> {
+        // We can safely unwrap because the argument is non-nullable and has been validated.
+        let subgraph_id = field.get_required::<String>("subgraphId").unwrap();
+
+        let deployment_hash = DeploymentHash::new(subgraph_id).map_err(|invalid_qm_hash| {
+            QueryExecutionError::SubgraphDeploymentIdError(invalid_qm_hash)
+        })?;
+
+        let raw_yaml: serde_yaml::Mapping = {
+            let file_bytes = self
+                .link_resolver
+                .cat(&self.logger, &deployment_hash.to_ipfs_link())
+                .await
+                .map_err(SubgraphManifestResolveError::ResolveError)?;
+
+            serde_yaml::from_slice(&file_bytes).map_err(SubgraphManifestResolveError::ParseError)?
+        };
+
+        let kind = match BlockchainKind::from_manifest(&raw_yaml) {
+            Ok(kind) => kind,
+            Err(e) => return Ok(manifest_validation(None, None, &[e.to_string()], vec![])),
+        };
+
+        let validation = match kind {
+            BlockchainKind::Ethereum => {
+                self.validate_manifest::<graph_chain_ethereum::Chain>(&deployment_hash, raw_yaml)
+                    .await
+            }
+            BlockchainKind::Cosmos => {
+                self.validate_manifest::<graph_chain_cosmos::Chain>(&deployment_hash, raw_yaml)
+                    .await
+            }
+            BlockchainKind::Near => {
+                self.validate_manifest::<graph_chain_near::Chain>(&deployment_hash, raw_yaml)
+                    .await
+            }
+            BlockchainKind::Arweave => {
+                self.validate_manifest::<graph_chain_arweave::Chain>(&deployment_hash, raw_yaml)
+                    .await
+            }
+            BlockchainKind::Substreams => {
+                self.validate_manifest::<graph_chain_substreams::Chain>(&deployment_hash, raw_yaml)
+                    .await
+            }
+            BlockchainKind::Starknet => {
+                self.validate_manifest::<graph_chain_starknet::Chain>(&deployment_hash, raw_yaml)
+                    .await
+            }
+            BlockchainKind::Solana => {
+                self.validate_manifest::<graph_chain_solana::Chain>(&deployment_hash, raw_yaml)
+                    .await
+            }
+            BlockchainKind::Bitcoin => {
+                self.validate_manifest::<graph_chain_bitcoin::Chain>(&deployment_hash, raw_yaml)
+                    .await
+            }
+        };
+
+        Ok(validation)
+    }
+
     async fn resolve_subgraph_features(
         &self,
         field: &a::Field,
@@ -665,6 +836,64 @@ impl<S: Store> IndexNodeResolver<S> {
         ))
     }
 
+    fn resolve_deployment_logs(&self, field: &a::Field) -> Result<r::Value, QueryExecutionError> {
+        let subgraph_id = field
+            .get_required::<DeploymentHash>("subgraphId")
+            .expect("Valid subgraphId required");
+
+        let level = field
+            .get_optional::<String>("level")
+            .expect("Valid level required")
+            .map(|level| parse_log_level(&level).expect("Valid level required"));
+
+        let first = field
+            .get_optional::<i32>("first")
+            .expect("Valid first required")
+            .unwrap_or(100);
+        if !(0..=MAX_DEPLOYMENT_LOGS).contains(&first) {
+            return Err(QueryExecutionError::RangeArgumentsError(
+                "first",
+                MAX_DEPLOYMENT_LOGS as u32,
+                first as i64,
+            ));
+        }
+
+        let filter = DeploymentLogFilter {
+            level,
+            handler: field
+                .get_optional::<String>("handler")
+                .expect("Valid handler required"),
+            from_block: field
+                .get_optional::<BlockNumber>("fromBlock")
+                .expect("Valid fromBlock required"),
+            to_block: field
+                .get_optional::<BlockNumber>("toBlock")
+                .expect("Valid toBlock required"),
+            first: first as usize,
+        };
+
+        let logs = self
+            .store
+            .subgraph_store()
+            .deployment_logs()
+            .find(&subgraph_id, &filter)?;
+
+        Ok(r::Value::List(
+            logs.into_iter()
+                .map(|log| {
+                    object! {
+                        __typename: "DeploymentLog",
+                        level: r::Value::Enum(log_level_name(log.level).to_string()),
+                        message: log.message,
+                        handler: log.handler,
+                        blockNumber: log.block_number,
+                        timestamp: log.timestamp.to_rfc3339(),
+                    }
+                })
+                .collect(),
+        ))
+    }
+
     fn version(&self) -> Result<r::Value, QueryExecutionError> {
         Ok(VERSION.clone().into_value())
     }
@@ -718,6 +947,8 @@ impl<S: Store> IndexNodeResolver<S> {
         try_resolve_for_chain!(graph_chain_cosmos::Chain);
         try_resolve_for_chain!(graph_chain_near::Chain);
         try_resolve_for_chain!(graph_chain_starknet::Chain);
+        try_resolve_for_chain!(graph_chain_solana::Chain);
+        try_resolve_for_chain!(graph_chain_bitcoin::Chain);
 
         // If you're adding support for a new chain and this `match` clause just
         // gave you a compiler error, then this message is for you! You need to
@@ -730,7 +961,9 @@ impl<S: Store> IndexNodeResolver<S> {
             | BlockchainKind::Ethereum
             | BlockchainKind::Cosmos
             | BlockchainKind::Near
-            | BlockchainKind::Starknet => (),
+            | BlockchainKind::Starknet
+            | BlockchainKind::Solana
+            | BlockchainKind::Bitcoin => (),
         }
 
         // The given network does not exist.
@@ -751,6 +984,22 @@ impl<S: Store> BlockPtrForNumber for IndexNodeResolver<S> {
     }
 }
 
+fn manifest_validation(
+    spec_version: Option<String>,
+    network: Option<String>,
+    errors: &[String],
+    features: Vec<r::Value>,
+) -> r::Value {
+    object! {
+        __typename: "ManifestValidation",
+        accepted: errors.is_empty(),
+        errors: errors.to_vec(),
+        specVersion: spec_version,
+        network: network,
+        features: features,
+    }
+}
+
 fn entity_changes_to_graphql(entity_changes: Vec<EntityOperation>) -> r::Value {
     // Results are sorted first alphabetically by entity type, then by entity
     // ID, and then aphabetically by field name.
@@ -869,6 +1118,7 @@ impl<S: Store> Resolver for IndexNodeResolver<S> {
             (None, "SubgraphIndexingStatus", "indexingStatusesForSubgraphName") => {
                 self.resolve_indexing_statuses_for_subgraph_name(field)
             }
+            (None, "DeploymentLog", "deploymentLogs") => self.resolve_deployment_logs(field),
             (None, "CachedEthereumCall", "cachedEthereumCalls") => {
                 self.resolve_cached_ethereum_calls(field).await
             }
@@ -899,6 +1149,7 @@ impl<S: Store> Resolver for IndexNodeResolver<S> {
                 self.resolve_indexing_status_for_version(field, false)
             }
             (None, "subgraphFeatures") => self.resolve_subgraph_features(field).await,
+            (None, "validateManifest") => self.resolve_validate_manifest(field).await,
             (None, "entityChangesInBlock") => self.resolve_entity_changes_in_block(field),
             // The top-level `subgraphVersions` field
             (None, "apiVersions") => self.resolve_api_versions(field),
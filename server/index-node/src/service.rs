@@ -144,6 +144,7 @@ where
             let options = QueryExecutionOptions {
                 resolver,
                 deadline: None,
+                partial_results: false,
                 max_first: std::u32::MAX,
                 max_skip: std::u32::MAX,
                 trace: false,
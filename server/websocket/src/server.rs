@@ -6,6 +6,7 @@ use graph::{
     data::query::QueryTarget,
     prelude::{SubscriptionServer as SubscriptionServerTrait, *},
 };
+use graph_server_http::ApiKeyGuard;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Mutex;
 use tokio::net::TcpListener;
@@ -20,6 +21,7 @@ pub struct SubscriptionServer<Q, S> {
     logger: Logger,
     graphql_runner: Arc<Q>,
     store: Arc<S>,
+    api_keys: Option<Arc<ApiKeyGuard>>,
 }
 
 impl<Q, S> SubscriptionServer<Q, S>
@@ -27,11 +29,19 @@ where
     Q: GraphQlRunner,
     S: QueryStoreManager,
 {
-    pub fn new(logger: &Logger, graphql_runner: Arc<Q>, store: Arc<S>) -> Self {
+    /// Create a server; if `api_keys` is set, connections need an API key
+    /// that may query the subgraph they subscribe to, just like queries
+    pub fn new(
+        logger: &Logger,
+        graphql_runner: Arc<Q>,
+        store: Arc<S>,
+        api_keys: Option<Arc<ApiKeyGuard>>,
+    ) -> Self {
         SubscriptionServer {
             logger: logger.new(o!("component" => "SubscriptionServer")),
             graphql_runner,
             store,
+            api_keys,
         }
     }
 
@@ -125,6 +135,7 @@ where
             let logger2 = self.logger.clone();
             let graphql_runner = self.graphql_runner.clone();
             let store = self.store.clone();
+            let api_keys = self.api_keys.clone();
 
             // Subgraph that the request is resolved to (if any)
             let subgraph_id = Arc::new(Mutex::new(None));
@@ -182,6 +193,20 @@ where
                             .unwrap());
                     }
 
+                if let Some(api_keys) = &api_keys {
+                    let target = QueryTarget::Deployment(state.id.clone(), ApiVersion::default());
+                    if let Err((status, message)) =
+                        api_keys.check_headers(request.headers(), [&target])
+                    {
+                        return Err(Response::builder()
+                            .status(status)
+                            .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                            .header(CONTENT_TYPE, "text/plain")
+                            .body(Some(message))
+                            .unwrap());
+                    }
+                }
+
                 *accept_subgraph_id.lock().unwrap() = Some(state.id);
                 response.headers_mut().insert(
                     "Sec-WebSocket-Protocol",
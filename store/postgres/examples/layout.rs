@@ -55,7 +55,10 @@ fn print_diesel_tables(layout: &Layout) {
             ColumnType::Int => "Integer",
             ColumnType::Int8 => "Int8",
             ColumnType::Timestamp => "Timestamp",
-            ColumnType::String | ColumnType::Enum(_) | ColumnType::TSVector(_) => "Text",
+            ColumnType::String
+            | ColumnType::Enum(_)
+            | ColumnType::TSVector(_)
+            | ColumnType::GeoPoint => "Text",
         }
         .to_owned();
 
@@ -75,7 +78,10 @@ fn print_diesel_tables(layout: &Layout) {
             ColumnType::Bytes => "Vec<u8>",
             ColumnType::Int => "i32",
             ColumnType::Int8 => "i64",
-            ColumnType::String | ColumnType::Enum(_) | ColumnType::TSVector(_) => "String",
+            ColumnType::String
+            | ColumnType::Enum(_)
+            | ColumnType::TSVector(_)
+            | ColumnType::GeoPoint => "String",
             ColumnType::Timestamp => "Timestamp",
         }
         .to_owned();
@@ -0,0 +1 @@
+ALTER TABLE subgraphs.subgraph_manifest DROP COLUMN retry_policy;
@@ -0,0 +1,3 @@
+-- The retry policy for non-deterministic errors; null means that the
+-- defaults from the environment are used
+ALTER TABLE subgraphs.subgraph_manifest ADD COLUMN retry_policy JSONB;
@@ -0,0 +1 @@
+drop table public.api_keys;
@@ -0,0 +1,17 @@
+create table public.api_keys
+(
+    id          serial primary key,
+    name        text                     not null,
+    -- hex-encoded blake3 hash of the secret clients send
+    key_hash    text                     not null unique,
+    -- the deployments the key can query; null allows all deployments
+    deployments text[]                   default null,
+    -- requests per minute; null means unlimited
+    rate_limit  int                      default null check (rate_limit > 0),
+    created_at  timestamp with time zone not null,
+    revoked_at  timestamp with time zone default null
+);
+
+-- names can be reused once the key that had them has been revoked
+create unique index api_keys_name_active
+    on public.api_keys(name) where revoked_at is null;
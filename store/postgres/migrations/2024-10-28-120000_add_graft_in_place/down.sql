@@ -0,0 +1 @@
+ALTER TABLE subgraphs.subgraph_deployment DROP COLUMN graft_in_place;
@@ -0,0 +1,4 @@
+-- Whether a graft moves the data of its base into the new deployment
+-- instead of copying it
+ALTER TABLE subgraphs.subgraph_deployment
+  ADD COLUMN graft_in_place BOOLEAN NOT NULL DEFAULT false;
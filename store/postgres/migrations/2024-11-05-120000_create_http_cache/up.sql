@@ -0,0 +1,10 @@
+create table public.http_cache
+(
+    deployment   text                     not null,
+    url          text                     not null,
+    -- blake3 hash of the body
+    content_hash bytea                    not null,
+    body         bytea                    not null,
+    created_at   timestamp with time zone not null,
+    primary key (deployment, url)
+);
@@ -0,0 +1,3 @@
+-- Limits for the mapping handlers of the deployment; null means that the
+-- defaults from the manifest and the environment are used
+ALTER TABLE subgraphs.subgraph_manifest ADD COLUMN handler_limits JSONB;
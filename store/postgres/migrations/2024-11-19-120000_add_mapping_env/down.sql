@@ -0,0 +1 @@
+ALTER TABLE subgraphs.subgraph_manifest DROP COLUMN mapping_env;
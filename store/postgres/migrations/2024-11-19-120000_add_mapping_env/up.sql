@@ -0,0 +1,3 @@
+-- Named constants that mappings of the deployment can read with
+-- `dataSource.env`, as a JSON object of strings; null means none
+ALTER TABLE subgraphs.subgraph_manifest ADD COLUMN mapping_env JSONB;
@@ -0,0 +1,14 @@
+create table public.deployment_logs
+(
+    id           bigserial primary key,
+    deployment   text                     not null,
+    -- the slog level, from 1 (critical) to 6 (trace)
+    level        int                      not null,
+    handler      text,
+    block_number int                      not null,
+    message      text                     not null,
+    created_at   timestamp with time zone not null
+);
+
+create index deployment_logs_deployment_id
+    on public.deployment_logs(deployment, id);
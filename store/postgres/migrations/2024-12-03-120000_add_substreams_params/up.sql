@@ -0,0 +1,4 @@
+-- Params for the substreams modules of the deployment that override the
+-- params from the manifest, as a JSON object from module name to params;
+-- null means no overrides
+ALTER TABLE subgraphs.subgraph_manifest ADD COLUMN substreams_params JSONB;
@@ -0,0 +1,3 @@
+-- How the deployment gets its blocks, 'default' or 'hybrid'; null means
+-- 'default'
+ALTER TABLE subgraphs.subgraph_manifest ADD COLUMN ingestion TEXT;
@@ -0,0 +1,2 @@
+drop table subgraphs.offchain_failure;
+ALTER TABLE subgraphs.subgraph_manifest DROP COLUMN fetch_policy;
@@ -0,0 +1,16 @@
+-- The `FetchPolicy` for file data sources as JSON; null means the
+-- defaults
+ALTER TABLE subgraphs.subgraph_manifest ADD COLUMN fetch_policy JSONB;
+
+-- Offchain files that a deployment gave up on fetching, so that it does
+-- not fetch them again when it is restarted
+create table subgraphs.offchain_failure
+(
+    id        int4                     not null
+              references subgraphs.subgraph_deployment(id) on delete cascade,
+    kind      text                     not null,
+    source    text                     not null,
+    error     text                     not null,
+    failed_at timestamp with time zone not null,
+    primary key (id, kind, source)
+);
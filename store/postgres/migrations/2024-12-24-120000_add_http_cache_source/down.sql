@@ -0,0 +1,8 @@
+delete from public.http_cache c
+ where exists (select 1 from public.http_cache d
+                where d.deployment = c.deployment
+                  and d.url = c.url
+                  and d.source < c.source);
+alter table public.http_cache drop constraint http_cache_pkey;
+alter table public.http_cache drop column source;
+alter table public.http_cache add primary key (deployment, url);
@@ -0,0 +1,6 @@
+-- Responses are stored separately for each content hash, or other source,
+-- of the offchain data source whose handler fetched them
+alter table public.http_cache add column source text not null default '';
+alter table public.http_cache alter column source drop default;
+alter table public.http_cache drop constraint http_cache_pkey;
+alter table public.http_cache add primary key (deployment, source, url);
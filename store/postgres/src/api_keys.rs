@@ -0,0 +1,196 @@
+//! Storage for the API keys of the query port. Keys live in the primary
+//! and are managed with `graphman api-key`
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+use graph::components::server::api_key::{self, ApiKey, ApiKeyStore, ApiKeys};
+use graph::components::store::StoreError;
+use graph::constraint_violation;
+use graph::data::subgraph::DeploymentHash;
+use rand::rngs::OsRng;
+use rand::Rng;
+
+use crate::connection_pool::ConnectionPool;
+use crate::primary::{subgraph as s, subgraph_version as v};
+
+diesel::table! {
+    public.api_keys {
+        id -> Integer,
+        name -> Text,
+        key_hash -> Text,
+        deployments -> Nullable<Array<Text>>,
+        rate_limit -> Nullable<Integer>,
+        created_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+    }
+}
+
+use self::api_keys as k;
+
+/// An API key as shown to operators
+#[derive(Clone, Debug)]
+pub struct ApiKeyInfo {
+    pub key: ApiKey,
+    pub created_at: DateTime<Utc>,
+}
+
+fn parse_deployments(
+    deployments: Option<Vec<String>>,
+) -> Result<Option<Vec<DeploymentHash>>, StoreError> {
+    deployments
+        .map(|deployments| {
+            deployments
+                .into_iter()
+                .map(|id| {
+                    DeploymentHash::new(id)
+                        .map_err(|id| constraint_violation!("illegal deployment id: {}", id))
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+#[derive(Clone)]
+pub struct ApiKeyStorage {
+    primary_pool: ConnectionPool,
+}
+
+impl ApiKeyStorage {
+    pub fn new(primary_pool: ConnectionPool) -> Self {
+        Self { primary_pool }
+    }
+
+    /// Create a new API key and return its secret. The secret is not
+    /// stored and can not be recovered later
+    pub fn create(
+        &self,
+        name: &str,
+        deployments: Option<Vec<DeploymentHash>>,
+        rate_limit: Option<u32>,
+    ) -> Result<String, StoreError> {
+        let mut conn = self.primary_pool.get()?;
+
+        conn.transaction(|conn| {
+            let exists = k::table
+                .filter(k::name.eq(name))
+                .filter(k::revoked_at.is_null())
+                .select(count_star())
+                .get_result::<i64>(conn)?
+                > 0;
+            if exists {
+                return Err(constraint_violation!(
+                    "an API key with name `{}` already exists",
+                    name
+                ));
+            }
+
+            // 256 random bits
+            let secret = hex::encode(OsRng.gen::<[u8; 32]>());
+            let deployments = deployments.map(|deployments| {
+                deployments
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+            });
+            let rate_limit = rate_limit
+                .map(|limit| i32::try_from(limit).unwrap_or(i32::MAX))
+                .filter(|limit| *limit > 0);
+
+            diesel::insert_into(k::table)
+                .values((
+                    k::name.eq(name),
+                    k::key_hash.eq(api_key::hash_secret(&secret)),
+                    k::deployments.eq(deployments),
+                    k::rate_limit.eq(rate_limit),
+                    k::created_at.eq(Utc::now()),
+                ))
+                .execute(conn)?;
+            Ok(secret)
+        })
+    }
+
+    /// Revoke the API key called `name`. Return `false` if there is no
+    /// such key
+    pub fn revoke(&self, name: &str) -> Result<bool, StoreError> {
+        let mut conn = self.primary_pool.get()?;
+
+        let count = diesel::update(k::table)
+            .set(k::revoked_at.eq(Utc::now()))
+            .filter(k::name.eq(name))
+            .filter(k::revoked_at.is_null())
+            .execute(&mut conn)?;
+        Ok(count > 0)
+    }
+
+    /// List all API keys that have not been revoked
+    pub fn list(&self) -> Result<Vec<ApiKeyInfo>, StoreError> {
+        let mut conn = self.primary_pool.get()?;
+
+        k::table
+            .filter(k::revoked_at.is_null())
+            .select((k::name, k::deployments, k::rate_limit, k::created_at))
+            .order_by(k::name)
+            .load::<(String, Option<Vec<String>>, Option<i32>, DateTime<Utc>)>(&mut conn)?
+            .into_iter()
+            .map(|(name, deployments, rate_limit, created_at)| {
+                Ok(ApiKeyInfo {
+                    key: ApiKey {
+                        name,
+                        deployments: parse_deployments(deployments)?,
+                        rate_limit: rate_limit.map(|limit| limit as u32),
+                    },
+                    created_at,
+                })
+            })
+            .collect()
+    }
+}
+
+impl ApiKeyStore for ApiKeyStorage {
+    fn active_keys(&self) -> Result<ApiKeys, StoreError> {
+        let mut conn = self.primary_pool.get()?;
+
+        let rows = k::table
+            .filter(k::revoked_at.is_null())
+            .select((k::key_hash, k::name, k::deployments, k::rate_limit))
+            .load::<(String, String, Option<Vec<String>>, Option<i32>)>(&mut conn)?;
+
+        let allowed: Vec<&String> = rows
+            .iter()
+            .filter_map(|(_, _, deployments, _)| deployments.as_ref())
+            .flatten()
+            .collect();
+        let names = if allowed.is_empty() {
+            HashMap::new()
+        } else {
+            s::table
+                .inner_join(v::table.on(s::current_version.eq(v::id.nullable())))
+                .filter(v::deployment.eq_any(allowed))
+                .select((s::name, v::deployment))
+                .load::<(String, String)>(&mut conn)?
+                .into_iter()
+                .map(|(name, deployment)| {
+                    DeploymentHash::new(deployment)
+                        .map(|deployment| (name, deployment))
+                        .map_err(|id| constraint_violation!("illegal deployment id: {}", id))
+                })
+                .collect::<Result<_, _>>()?
+        };
+
+        let keys = rows
+            .into_iter()
+            .map(|(hash, name, deployments, rate_limit)| {
+                let key = ApiKey {
+                    name,
+                    deployments: parse_deployments(deployments)?,
+                    rate_limit: rate_limit.map(|limit| limit as u32),
+                };
+                Ok((hash, key))
+            })
+            .collect::<Result<_, StoreError>>()?;
+
+        Ok(ApiKeys { keys, names })
+    }
+}
@@ -186,6 +186,11 @@ pub struct Catalog {
     /// Whether the database supports `int4_minmax_multi_ops` etc.
     /// See the [Postgres docs](https://www.postgresql.org/docs/15/brin-builtin-opclasses.html)
     has_minmax_multi_ops: bool,
+
+    /// Whether the PostGIS extension is installed in the database. When
+    /// it is, distance filters on `GeoPoint` columns use it for their
+    /// calculations
+    pub has_postgis: bool,
 }
 
 impl Catalog {
@@ -199,6 +204,7 @@ impl Catalog {
         let text_columns = get_text_columns(conn, &site.namespace)?;
         let use_poi = supports_proof_of_indexing(conn, &site.namespace)?;
         let has_minmax_multi_ops = has_minmax_multi_ops(conn)?;
+        let has_postgis = has_postgis(conn)?;
 
         Ok(Catalog {
             site,
@@ -207,6 +213,7 @@ impl Catalog {
             use_bytea_prefix,
             entities_with_causality_region: entities_with_causality_region.into_iter().collect(),
             has_minmax_multi_ops,
+            has_postgis,
         })
     }
 
@@ -217,6 +224,7 @@ impl Catalog {
         entities_with_causality_region: BTreeSet<EntityType>,
     ) -> Result<Self, StoreError> {
         let has_minmax_multi_ops = has_minmax_multi_ops(conn)?;
+        let has_postgis = has_postgis(conn)?;
 
         Ok(Catalog {
             site,
@@ -228,6 +236,7 @@ impl Catalog {
             use_bytea_prefix: true,
             entities_with_causality_region,
             has_minmax_multi_ops,
+            has_postgis,
         })
     }
 
@@ -245,6 +254,7 @@ impl Catalog {
             use_bytea_prefix: true,
             entities_with_causality_region,
             has_minmax_multi_ops: false,
+            has_postgis: false,
         })
     }
 
@@ -912,3 +922,19 @@ fn has_minmax_multi_ops(conn: &mut PgConnection) -> Result<bool, StoreError> {
 
     Ok(sql_query(QUERY).get_result::<Ops>(conn)?.has_ops)
 }
+
+/// Check whether the PostGIS extension is installed in the database for
+/// `conn`
+fn has_postgis(conn: &mut PgConnection) -> Result<bool, StoreError> {
+    const QUERY: &str = "select count(*) > 0 as has_postgis \
+                           from pg_extension \
+                          where extname = 'postgis'";
+
+    #[derive(Queryable, QueryableByName)]
+    struct Ext {
+        #[diesel(sql_type = Bool)]
+        has_postgis: bool,
+    }
+
+    Ok(sql_query(QUERY).get_result::<Ext>(conn)?.has_postgis)
+}
@@ -14,7 +14,8 @@ use diesel::{
     sql_types::{Nullable, Text},
 };
 use graph::{
-    blockchain::block_stream::FirehoseCursor,
+    blockchain::{block_stream::FirehoseCursor, IngestionMode},
+    components::subgraph::{FetchPolicy, HandlerLimits, OffchainFailure, RetryPolicy},
     data::subgraph::schema::SubgraphError,
     env::ENV_VARS,
     schema::EntityType,
@@ -23,8 +24,8 @@ use graph::{
 use graph::{
     data::store::scalar::ToPrimitive,
     prelude::{
-        anyhow, hex, web3::types::H256, BigDecimal, BlockNumber, BlockPtr, DeploymentHash,
-        DeploymentState, StoreError,
+        anyhow, hex, serde_json, web3::types::H256, BigDecimal, BlockNumber, BlockPtr,
+        DeploymentHash, DeploymentState, StoreError,
     },
     schema::InputSchema,
 };
@@ -36,7 +37,12 @@ use graph::{
     util::backoff::ExponentialBackoff,
 };
 use stable_hash_legacy::crypto::SetHasher;
-use std::{collections::BTreeSet, convert::TryFrom, ops::Bound, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    ops::Bound,
+    time::Duration,
+};
 use std::{str::FromStr, sync::Arc};
 
 use crate::connection_pool::ForeignServer;
@@ -148,6 +154,7 @@ table! {
         graft_base -> Nullable<Text>,
         graft_block_hash -> Nullable<Binary>,
         graft_block_number -> Nullable<Numeric>,
+        graft_in_place -> Bool,
         debug_fork -> Nullable<Text>,
         reorg_count -> Integer,
         current_reorg_depth -> Integer,
@@ -191,6 +198,35 @@ table! {
         // How many blocks of history to keep, defaults to `i32::max` for
         // unlimited history
         history_blocks -> Integer,
+        // The `RetryPolicy` for non-deterministic errors as JSON; `null`
+        // means the defaults from the environment
+        retry_policy -> Nullable<Jsonb>,
+        // The `HandlerLimits` as JSON; `null` means the defaults from the
+        // manifest and the environment
+        handler_limits -> Nullable<Jsonb>,
+        // The constants for `dataSource.env` as a JSON object; `null` means
+        // that there are none
+        mapping_env -> Nullable<Jsonb>,
+        // Params for substreams modules by module name that override the
+        // params from the manifest; `null` means that there are none
+        substreams_params -> Nullable<Jsonb>,
+        // How the deployment gets its blocks; `null` means
+        // `IngestionMode::Default`
+        ingestion -> Nullable<Text>,
+        // The `FetchPolicy` for file data sources as JSON; `null` means
+        // the defaults
+        fetch_policy -> Nullable<Jsonb>,
+    }
+}
+
+table! {
+    // Offchain files that a deployment gave up on fetching
+    subgraphs.offchain_failure (id, kind, source) {
+        id -> Integer,
+        kind -> Text,
+        source -> Text,
+        error -> Text,
+        failed_at -> Timestamptz,
     }
 }
 
@@ -278,6 +314,19 @@ pub fn graft_point(
     graft(conn, id, false)
 }
 
+/// Return `true` if the deployment `id` takes over the data of its graft
+/// base rather than copying it
+pub fn graft_in_place(conn: &mut PgConnection, id: &DeploymentHash) -> Result<bool, StoreError> {
+    use subgraph_deployment as sd;
+
+    Ok(sd::table
+        .select(sd::graft_in_place)
+        .filter(sd::deployment.eq(id.as_str()))
+        .first::<bool>(conn)
+        .optional()?
+        .unwrap_or(false))
+}
+
 /// Look up the debug fork for the given subgraph in the database and
 /// return it. Returns `None` if the deployment does not have
 /// a debug fork.
@@ -353,6 +402,18 @@ impl ManifestInfo {
     }
 }
 
+/// Return the earliest block for which the deployment still has the
+/// history of its entities
+pub fn earliest_block(conn: &mut PgConnection, site: &Site) -> Result<BlockNumber, StoreError> {
+    use subgraph_deployment as d;
+
+    d::table
+        .filter(d::id.eq(site.id))
+        .select(d::earliest_block_number)
+        .first::<BlockNumber>(conn)
+        .map_err(StoreError::from)
+}
+
 // Return how many blocks of history this subgraph should keep
 pub fn history_blocks(conn: &mut PgConnection, site: &Site) -> Result<BlockNumber, StoreError> {
     use subgraph_manifest as sm;
@@ -377,6 +438,231 @@ pub fn set_history_blocks(
         .map_err(StoreError::from)
 }
 
+/// Return the retry policy that was configured for this deployment, if any
+pub fn retry_policy(
+    conn: &mut PgConnection,
+    site: &Site,
+) -> Result<Option<RetryPolicy>, StoreError> {
+    use subgraph_manifest as sm;
+
+    let policy = sm::table
+        .select(sm::retry_policy)
+        .filter(sm::id.eq(site.id))
+        .first::<Option<serde_json::Value>>(conn)?;
+    policy
+        .map(|policy| {
+            serde_json::from_value(policy).map_err(|e| {
+                constraint_violation!("invalid retry policy for sgd{}: {}", site.id, e)
+            })
+        })
+        .transpose()
+}
+
+pub fn set_retry_policy(
+    conn: &mut PgConnection,
+    site: &Site,
+    policy: Option<&RetryPolicy>,
+) -> Result<(), StoreError> {
+    use subgraph_manifest as sm;
+
+    let policy = policy
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| StoreError::Unknown(e.into()))?;
+    update(sm::table.filter(sm::id.eq(site.id)))
+        .set(sm::retry_policy.eq(policy))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(StoreError::from)
+}
+
+/// Return the handler limits that were configured for this deployment, if
+/// any
+pub fn handler_limits(
+    conn: &mut PgConnection,
+    site: &Site,
+) -> Result<Option<HandlerLimits>, StoreError> {
+    use subgraph_manifest as sm;
+
+    let limits = sm::table
+        .select(sm::handler_limits)
+        .filter(sm::id.eq(site.id))
+        .first::<Option<serde_json::Value>>(conn)?;
+    limits
+        .map(|limits| {
+            serde_json::from_value(limits).map_err(|e| {
+                constraint_violation!("invalid handler limits for sgd{}: {}", site.id, e)
+            })
+        })
+        .transpose()
+}
+
+/// Return the constants that mappings of this deployment can read with
+/// `dataSource.env`, if any were configured
+pub fn mapping_env(
+    conn: &mut PgConnection,
+    site: &Site,
+) -> Result<Option<BTreeMap<String, String>>, StoreError> {
+    use subgraph_manifest as sm;
+
+    let env = sm::table
+        .select(sm::mapping_env)
+        .filter(sm::id.eq(site.id))
+        .first::<Option<serde_json::Value>>(conn)?;
+    env.map(|env| {
+        serde_json::from_value(env)
+            .map_err(|e| constraint_violation!("invalid mapping env for sgd{}: {}", site.id, e))
+    })
+    .transpose()
+}
+
+/// Return the params for substreams modules that override the params from
+/// the manifest of this deployment, if any were set
+pub fn substreams_params(
+    conn: &mut PgConnection,
+    site: &Site,
+) -> Result<Option<BTreeMap<String, String>>, StoreError> {
+    use subgraph_manifest as sm;
+
+    let params = sm::table
+        .select(sm::substreams_params)
+        .filter(sm::id.eq(site.id))
+        .first::<Option<serde_json::Value>>(conn)?;
+    params
+        .map(|params| {
+            serde_json::from_value(params).map_err(|e| {
+                constraint_violation!("invalid substreams params for sgd{}: {}", site.id, e)
+            })
+        })
+        .transpose()
+}
+
+pub fn set_substreams_params(
+    conn: &mut PgConnection,
+    site: &Site,
+    params: Option<&BTreeMap<String, String>>,
+) -> Result<(), StoreError> {
+    use subgraph_manifest as sm;
+
+    let params = params
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| StoreError::Unknown(e.into()))?;
+    update(sm::table.filter(sm::id.eq(site.id)))
+        .set(sm::substreams_params.eq(params))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(StoreError::from)
+}
+
+/// Return how the file data sources of this deployment fetch their files,
+/// if that was configured
+pub fn fetch_policy(
+    conn: &mut PgConnection,
+    site: &Site,
+) -> Result<Option<FetchPolicy>, StoreError> {
+    use subgraph_manifest as sm;
+
+    let policy = sm::table
+        .select(sm::fetch_policy)
+        .filter(sm::id.eq(site.id))
+        .first::<Option<serde_json::Value>>(conn)?;
+    policy
+        .map(|policy| {
+            serde_json::from_value(policy).map_err(|e| {
+                constraint_violation!("invalid fetch policy for sgd{}: {}", site.id, e)
+            })
+        })
+        .transpose()
+}
+
+/// Return the offchain files that this deployment gave up on fetching
+pub fn offchain_failures(
+    conn: &mut PgConnection,
+    site: &Site,
+) -> Result<Vec<OffchainFailure>, StoreError> {
+    use offchain_failure as f;
+
+    let failures = f::table
+        .select((f::kind, f::source, f::error))
+        .filter(f::id.eq(site.id))
+        .order_by((f::kind, f::source))
+        .load::<(String, String, String)>(conn)?;
+    Ok(failures
+        .into_iter()
+        .map(|(kind, source, error)| OffchainFailure {
+            kind,
+            source,
+            error,
+        })
+        .collect())
+}
+
+pub fn record_offchain_failure(
+    conn: &mut PgConnection,
+    site: &Site,
+    failure: &OffchainFailure,
+) -> Result<(), StoreError> {
+    use offchain_failure as f;
+
+    insert_into(f::table)
+        .values((
+            f::id.eq(site.id),
+            f::kind.eq(&failure.kind),
+            f::source.eq(&failure.source),
+            f::error.eq(&failure.error),
+            f::failed_at.eq(now),
+        ))
+        .on_conflict((f::id, f::kind, f::source))
+        .do_update()
+        .set((f::error.eq(&failure.error), f::failed_at.eq(now)))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Forget that this deployment gave up on the offchain files from
+/// `sources`, or on all files if `sources` is empty. Returns how many
+/// files were forgotten
+pub fn clear_offchain_failures(
+    conn: &mut PgConnection,
+    site: &Site,
+    sources: &[String],
+) -> Result<usize, StoreError> {
+    use offchain_failure as f;
+
+    let count = if sources.is_empty() {
+        delete(f::table.filter(f::id.eq(site.id))).execute(conn)?
+    } else {
+        delete(
+            f::table
+                .filter(f::id.eq(site.id))
+                .filter(f::source.eq_any(sources)),
+        )
+        .execute(conn)?
+    };
+    Ok(count)
+}
+
+/// Return how the deployment gets its blocks, if that was configured
+pub fn ingestion(
+    conn: &mut PgConnection,
+    site: &Site,
+) -> Result<Option<IngestionMode>, StoreError> {
+    use subgraph_manifest as sm;
+
+    let ingestion = sm::table
+        .select(sm::ingestion)
+        .filter(sm::id.eq(site.id))
+        .first::<Option<String>>(conn)?;
+    ingestion
+        .map(|ingestion| {
+            IngestionMode::from_str(&ingestion).map_err(|e| {
+                constraint_violation!("invalid ingestion mode for sgd{}: {}", site.id, e)
+            })
+        })
+        .transpose()
+}
+
 #[allow(dead_code)]
 pub fn features(
     conn: &mut PgConnection,
@@ -532,6 +818,18 @@ pub fn get_subgraph_firehose_cursor(
     res
 }
 
+/// Remove the Firehose cursor of a deployment so that the next block stream
+/// starts from its block pointer and checks that it is still on the chain
+pub fn reset_firehose_cursor(conn: &mut PgConnection, site: &Site) -> Result<(), StoreError> {
+    use subgraph_deployment as d;
+
+    update(d::table.filter(d::id.eq(site.id)))
+        .set(d::firehose_cursor.eq(None::<String>))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(StoreError::from)
+}
+
 pub fn revert_block_ptr(
     conn: &mut PgConnection,
     id: &DeploymentHash,
@@ -1157,9 +1455,41 @@ pub fn create_deployment(
         start_block,
         graft_base,
         graft_block,
+        graft_in_place,
         debug_fork,
         history_blocks_override,
+        retry_policy,
+        handler_limits,
+        mapping_env,
+        substreams_params,
+        ingestion,
+        fetch_policy,
     } = deployment;
+    let retry_policy = retry_policy
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| StoreError::Unknown(e.into()))?;
+    let handler_limits = handler_limits
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| StoreError::Unknown(e.into()))?;
+    let mapping_env = mapping_env
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| StoreError::Unknown(e.into()))?;
+    let substreams_params = substreams_params
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| StoreError::Unknown(e.into()))?;
+    let fetch_policy = fetch_policy
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| StoreError::Unknown(e.into()))?;
     let earliest_block_number = start_block.as_ref().map(|ptr| ptr.number).unwrap_or(0);
     let entities_with_causality_region = Vec::from_iter(
         entities_with_causality_region
@@ -1181,6 +1511,7 @@ pub fn create_deployment(
         d::graft_base.eq(graft_base.as_ref().map(|s| s.as_str())),
         d::graft_block_hash.eq(b(&graft_block)),
         d::graft_block_number.eq(n(&graft_block)),
+        d::graft_in_place.eq(graft_in_place),
         d::debug_fork.eq(debug_fork.as_ref().map(|s| s.as_str())),
     );
 
@@ -1202,6 +1533,12 @@ pub fn create_deployment(
         m::raw_yaml.eq(raw_yaml),
         m::entities_with_causality_region.eq(entities_with_causality_region),
         m::history_blocks.eq(history_blocks_override.unwrap_or(history_blocks)),
+        m::retry_policy.eq(retry_policy),
+        m::handler_limits.eq(handler_limits),
+        m::mapping_env.eq(mapping_env),
+        m::substreams_params.eq(substreams_params),
+        m::ingestion.eq(ingestion.map(|ingestion| ingestion.to_string())),
+        m::fetch_policy.eq(fetch_policy),
     );
 
     if exists && replace {
@@ -1304,6 +1641,38 @@ pub fn copy_earliest_block(
     Ok(())
 }
 
+/// Copy the Firehose cursor of `src` to `dst` if `src` is still at
+/// `block`. A cursor for a later block would make `dst` skip blocks; in that
+/// case, `dst` is left without a cursor. Returns `true` if a cursor was
+/// copied
+pub fn copy_firehose_cursor(
+    conn: &mut PgConnection,
+    src: &Site,
+    dst: &Site,
+    block: &BlockPtr,
+) -> Result<bool, StoreError> {
+    use subgraph_deployment as d;
+
+    let src_nsp = ForeignServer::metadata_schema_in(&src.shard, &dst.shard);
+
+    let query = format!(
+        "(select firehose_cursor from {src_nsp}.subgraph_deployment \
+           where id = {} \
+             and latest_ethereum_block_number = {} \
+             and latest_ethereum_block_hash = decode('{}', 'hex'))",
+        src.id,
+        block.number,
+        block.hash_hex()
+    );
+
+    let cursor = update(d::table.filter(d::id.eq(dst.id)))
+        .set(d::firehose_cursor.eq(sql::<Nullable<Text>>(&query)))
+        .returning(d::firehose_cursor)
+        .get_result::<Option<String>>(conn)?;
+
+    Ok(cursor.is_some())
+}
+
 pub fn on_sync(conn: &mut PgConnection, id: impl Into<DeploymentId>) -> Result<OnSync, StoreError> {
     use subgraph_manifest as m;
 
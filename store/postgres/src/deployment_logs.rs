@@ -0,0 +1,141 @@
+//! Storage for the messages that mappings log with `log.*`. Logs live in
+//! the primary, and only the most recent `retention` messages of each
+//! deployment are kept
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use graph::components::store::{DeploymentLog, DeploymentLogFilter, DeploymentLogs, StoreError};
+use graph::constraint_violation;
+use graph::data::subgraph::DeploymentHash;
+use graph::prelude::BlockNumber;
+use graph::slog::Level;
+
+use crate::connection_pool::ConnectionPool;
+
+diesel::table! {
+    public.deployment_logs (id) {
+        id -> BigInt,
+        deployment -> Text,
+        level -> Integer,
+        handler -> Nullable<Text>,
+        block_number -> Integer,
+        message -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+use self::deployment_logs as l;
+
+/// Removing old logs after every insert would be wasteful; instead, we
+/// prune a deployment's logs whenever this many logs have been inserted
+/// across all deployments
+const PRUNE_INTERVAL: i64 = 100;
+
+#[derive(Clone)]
+pub struct DeploymentLogStorage {
+    primary_pool: ConnectionPool,
+    retention: usize,
+}
+
+impl DeploymentLogStorage {
+    pub fn new(primary_pool: ConnectionPool, retention: usize) -> Self {
+        Self {
+            primary_pool,
+            retention,
+        }
+    }
+
+    fn prune(&self, conn: &mut PgConnection, deployment: &str) -> Result<(), StoreError> {
+        let oldest_kept = l::table
+            .filter(l::deployment.eq(deployment))
+            .order(l::id.desc())
+            .offset(self.retention as i64 - 1)
+            .select(l::id)
+            .first::<i64>(conn)
+            .optional()?;
+        if let Some(oldest_kept) = oldest_kept {
+            diesel::delete(
+                l::table
+                    .filter(l::deployment.eq(deployment))
+                    .filter(l::id.lt(oldest_kept)),
+            )
+            .execute(conn)?;
+        }
+        Ok(())
+    }
+}
+
+impl DeploymentLogs for DeploymentLogStorage {
+    fn append(&self, deployment: &DeploymentHash, log: DeploymentLog) -> Result<(), StoreError> {
+        if self.retention == 0 {
+            return Ok(());
+        }
+        let mut conn = self.primary_pool.get()?;
+
+        let id = diesel::insert_into(l::table)
+            .values((
+                l::deployment.eq(deployment.as_str()),
+                l::level.eq(log.level.as_usize() as i32),
+                l::handler.eq(log.handler.as_deref()),
+                l::block_number.eq(log.block_number),
+                l::message.eq(&log.message),
+                l::created_at.eq(log.timestamp),
+            ))
+            .returning(l::id)
+            .get_result::<i64>(&mut conn)?;
+
+        if id % PRUNE_INTERVAL == 0 {
+            self.prune(&mut conn, deployment.as_str())?;
+        }
+        Ok(())
+    }
+
+    fn find(
+        &self,
+        deployment: &DeploymentHash,
+        filter: &DeploymentLogFilter,
+    ) -> Result<Vec<DeploymentLog>, StoreError> {
+        let mut conn = self.primary_pool.get()?;
+
+        let mut query = l::table
+            .filter(l::deployment.eq(deployment.as_str()))
+            .into_boxed();
+        if let Some(level) = filter.level {
+            // More severe levels have smaller numbers
+            query = query.filter(l::level.le(level.as_usize() as i32));
+        }
+        if let Some(handler) = &filter.handler {
+            query = query.filter(l::handler.eq(handler));
+        }
+        if let Some(from_block) = filter.from_block {
+            query = query.filter(l::block_number.ge(from_block));
+        }
+        if let Some(to_block) = filter.to_block {
+            query = query.filter(l::block_number.le(to_block));
+        }
+
+        query
+            .order(l::id.desc())
+            .limit(filter.first as i64)
+            .select((
+                l::level,
+                l::message,
+                l::handler,
+                l::block_number,
+                l::created_at,
+            ))
+            .load::<(i32, String, Option<String>, BlockNumber, DateTime<Utc>)>(&mut conn)?
+            .into_iter()
+            .map(|(level, message, handler, block_number, timestamp)| {
+                let level = Level::from_usize(level as usize)
+                    .ok_or_else(|| constraint_violation!("invalid log level {}", level))?;
+                Ok(DeploymentLog {
+                    level,
+                    message,
+                    handler,
+                    block_number,
+                    timestamp,
+                })
+            })
+            .collect()
+    }
+}
@@ -12,7 +12,7 @@ use graph::components::store::{
     PruningStrategy, QueryPermit, StoredDynamicDataSource, VersionStats,
 };
 use graph::components::versions::VERSIONS;
-use graph::data::query::Trace;
+use graph::data::query::{EntityHistory, EntityHistoryQuery, SqlQueryResult, Trace};
 use graph::data::store::IdList;
 use graph::data::subgraph::{status, SPEC_VERSION_0_0_6};
 use graph::data_source::CausalityRegion;
@@ -35,8 +35,12 @@ use std::str::FromStr;
 use std::sync::{atomic::AtomicUsize, Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use graph::blockchain::IngestionMode;
 use graph::components::store::EntityCollection;
-use graph::components::subgraph::{ProofOfIndexingFinisher, ProofOfIndexingVersion};
+use graph::components::subgraph::{
+    FetchPolicy, HandlerLimits, OffchainFailure, ProofOfIndexingFinisher, ProofOfIndexingVersion,
+    RetryPolicy,
+};
 use graph::constraint_violation;
 use graph::data::subgraph::schema::{DeploymentCreate, SubgraphError};
 use graph::prelude::{
@@ -194,6 +198,7 @@ impl DeploymentStore {
             // Create (or update) the metadata. Update only happens in tests
             let entities_with_causality_region =
                 deployment.manifest.entities_with_causality_region.clone();
+            let graft_in_place = deployment.graft_in_place;
 
             // If `GRAPH_HISTORY_BLOCKS_OVERRIDE` is set, override the history_blocks
             // setting with the value of the environment variable.
@@ -222,7 +227,11 @@ impl DeploymentStore {
                 )?;
                 // See if we are grafting and check that the graft is permissible
                 if let Some(base) = graft_base {
-                    let errors = layout.can_copy_from(&base);
+                    let errors = if graft_in_place {
+                        layout.can_alter_in_place(&base)
+                    } else {
+                        layout.can_copy_from(&base)
+                    };
                     if !errors.is_empty() {
                         return Err(StoreError::Unknown(anyhow!(
                             "The subgraph `{}` cannot be used as the graft base \
@@ -286,6 +295,29 @@ impl DeploymentStore {
         layout.query(&logger, conn, query)
     }
 
+    pub(crate) fn execute_sql(
+        &self,
+        conn: &mut PgConnection,
+        site: Arc<Site>,
+        sql: &str,
+        block: BlockNumber,
+    ) -> Result<SqlQueryResult, QueryExecutionError> {
+        let layout = self.layout(conn, site)?;
+        layout.query_sql(conn, sql, block)
+    }
+
+    pub(crate) fn entity_history(
+        &self,
+        conn: &mut PgConnection,
+        site: Arc<Site>,
+        query: &EntityHistoryQuery,
+    ) -> Result<EntityHistory, QueryExecutionError> {
+        let layout = self.layout(conn, site)?;
+        layout
+            .entity_history(conn, query)
+            .map_err(QueryExecutionError::from)
+    }
+
     fn check_intf_uniqueness(
         &self,
         conn: &mut PgConnection,
@@ -818,6 +850,133 @@ impl DeploymentStore {
         deployment::set_history_blocks(&mut conn, site, history_blocks)
     }
 
+    pub(crate) fn retry_policy(&self, site: &Site) -> Result<Option<RetryPolicy>, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::retry_policy(&mut conn, site)
+    }
+
+    pub(crate) fn handler_limits(&self, site: &Site) -> Result<Option<HandlerLimits>, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::handler_limits(&mut conn, site)
+    }
+
+    pub(crate) fn mapping_env(
+        &self,
+        site: &Site,
+    ) -> Result<Option<BTreeMap<String, String>>, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::mapping_env(&mut conn, site)
+    }
+
+    pub(crate) fn substreams_params(
+        &self,
+        site: &Site,
+    ) -> Result<Option<BTreeMap<String, String>>, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::substreams_params(&mut conn, site)
+    }
+
+    pub(crate) fn set_substreams_params(
+        &self,
+        site: &Site,
+        params: Option<&BTreeMap<String, String>>,
+    ) -> Result<(), StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::set_substreams_params(&mut conn, site, params)
+    }
+
+    pub(crate) fn ingestion(&self, site: &Site) -> Result<Option<IngestionMode>, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::ingestion(&mut conn, site)
+    }
+
+    pub(crate) fn fetch_policy(&self, site: &Site) -> Result<Option<FetchPolicy>, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::fetch_policy(&mut conn, site)
+    }
+
+    pub(crate) fn offchain_failures(
+        &self,
+        site: &Site,
+    ) -> Result<Vec<OffchainFailure>, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::offchain_failures(&mut conn, site)
+    }
+
+    pub(crate) fn record_offchain_failure(
+        &self,
+        site: &Site,
+        failure: &OffchainFailure,
+    ) -> Result<(), StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::record_offchain_failure(&mut conn, site, failure)
+    }
+
+    pub(crate) fn clear_offchain_failures(
+        &self,
+        site: &Site,
+        sources: &[String],
+    ) -> Result<usize, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::clear_offchain_failures(&mut conn, site, sources)
+    }
+
+    pub(crate) fn set_retry_policy(
+        &self,
+        site: &Site,
+        policy: Option<&RetryPolicy>,
+    ) -> Result<(), StoreError> {
+        if let Some(policy) = policy {
+            policy.validate().map_err(|msg| {
+                constraint_violation!("invalid retry policy for sgd{}: {}", site.id, msg)
+            })?;
+        }
+        let mut conn = self.get_conn()?;
+        deployment::set_retry_policy(&mut conn, site, policy)
+    }
+
+    pub(crate) fn reset_block_cursor(&self, site: &Site) -> Result<(), StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::reset_firehose_cursor(&mut conn, site)
+    }
+
+    /// Remove data for entity types with a `retention` that has fallen
+    /// out of their retention window; see `Layout::prune_by_retention`
+    pub(crate) async fn prune_by_retention(
+        self: &Arc<Self>,
+        mut reporter: Box<dyn PruneReporter>,
+        site: Arc<Site>,
+        final_block: BlockNumber,
+    ) -> Result<Box<dyn PruneReporter>, StoreError> {
+        let store = self.clone();
+        self.with_conn(move |conn, cancel| {
+            // See `prune` for why we take this lock
+            if advisory_lock::try_lock_pruning(conn, &site)? {
+                let res = deployment::earliest_block(conn, &site)
+                    .and_then(|earliest_block| {
+                        store
+                            .layout(conn, site.cheap_clone())
+                            .map(|layout| (layout, earliest_block))
+                    })
+                    .map_err(CancelableError::from)
+                    .and_then(|(layout, earliest_block)| {
+                        layout.prune_by_retention(
+                            reporter.as_mut(),
+                            conn,
+                            earliest_block,
+                            final_block,
+                            cancel,
+                        )
+                    });
+                advisory_lock::unlock_pruning(conn, &site)?;
+                res.map(|()| reporter)
+            } else {
+                Ok(reporter)
+            }
+        })
+        .await
+    }
+
     pub(crate) async fn prune(
         self: &Arc<Self>,
         reporter: Box<dyn PruneReporter>,
@@ -1047,6 +1206,27 @@ impl DeploymentStore {
         layout.find(&mut conn, key, block)
     }
 
+    /// Like `get`, but fail if `block` is before the earliest block for
+    /// which the deployment has the history of its entities
+    pub(crate) fn get_at_block(
+        &self,
+        site: Arc<Site>,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<Entity>, StoreError> {
+        let mut conn = self.get_conn()?;
+        let earliest_block = deployment::earliest_block(&mut conn, &site)?;
+        if block < earliest_block {
+            return Err(StoreError::QueryExecutionError(format!(
+                "can not get `{}` as of block {} since the history of the deployment \
+                 before block {} has been pruned",
+                key, block, earliest_block
+            )));
+        }
+        let layout = self.layout(&mut conn, site)?;
+        layout.find(&mut conn, key, block)
+    }
+
     /// Retrieve all the entities matching `ids_for_type`, both the type and causality region, from
     /// the deployment `site`. Only consider entities as of the given `block`
     pub(crate) fn get_many(
@@ -1192,11 +1372,58 @@ impl DeploymentStore {
                 earliest_block,
                 batch.block_ptr.number,
             )?;
+        } else if layout.input_schema.has_retention() && Self::retention_prune_due(batch) {
+            let _section = stopwatch.start_section("transact_blocks_prune");
+
+            self.spawn_retention_prune(logger, site, batch.block_ptr.number)?;
         }
 
         Ok(event)
     }
 
+    /// Return `true` if `batch` contains a block whose number is a
+    /// multiple of `GRAPH_STORE_RETENTION_PRUNE_INTERVAL`. Deciding this by
+    /// block number rather than by time makes sure that which entity
+    /// versions are removed only depends on the blocks that have been
+    /// processed
+    fn retention_prune_due(batch: &Batch) -> bool {
+        let interval = ENV_VARS.store.retention_prune_interval.max(1);
+        batch.block_ptr.number.div_euclid(interval) > (batch.first_block - 1).div_euclid(interval)
+    }
+
+    /// Return `true` if a previous prune for `site` is still running. If
+    /// a previous prune has finished, return its error if it failed
+    fn prune_in_progress(&self, site: &Site) -> Result<bool, StoreError> {
+        let finished = self
+            .prune_handles
+            .lock()
+            .unwrap()
+            .get(&site.id)
+            .map(|handle| handle.is_finished());
+        match finished {
+            Some(true) => {
+                // A previous prune has finished
+                let handle = self.prune_handles.lock().unwrap().remove(&site.id).unwrap();
+                match FutureExt::now_or_never(handle) {
+                    Some(Ok(Ok(()))) => Ok(false),
+                    Some(Ok(Err(err))) => Err(StoreError::PruneFailure(err.to_string())),
+                    Some(Err(join_err)) => Err(StoreError::PruneFailure(join_err.to_string())),
+                    None => Err(constraint_violation!(
+                        "prune handle is finished but not ready"
+                    )),
+                }
+            }
+            Some(false) => {
+                // A previous prune is still in progress
+                Ok(true)
+            }
+            None => {
+                // There is no prune in progress
+                Ok(false)
+            }
+        }
+    }
+
     fn spawn_prune(
         self: &Arc<Self>,
         logger: &Logger,
@@ -1205,42 +1432,6 @@ impl DeploymentStore {
         earliest_block: BlockNumber,
         latest_block: BlockNumber,
     ) -> Result<(), StoreError> {
-        fn prune_in_progress(store: &DeploymentStore, site: &Site) -> Result<bool, StoreError> {
-            let finished = store
-                .prune_handles
-                .lock()
-                .unwrap()
-                .get(&site.id)
-                .map(|handle| handle.is_finished());
-            match finished {
-                Some(true) => {
-                    // A previous prune has finished
-                    let handle = store
-                        .prune_handles
-                        .lock()
-                        .unwrap()
-                        .remove(&site.id)
-                        .unwrap();
-                    match FutureExt::now_or_never(handle) {
-                        Some(Ok(Ok(()))) => Ok(false),
-                        Some(Ok(Err(err))) => Err(StoreError::PruneFailure(err.to_string())),
-                        Some(Err(join_err)) => Err(StoreError::PruneFailure(join_err.to_string())),
-                        None => Err(constraint_violation!(
-                            "prune handle is finished but not ready"
-                        )),
-                    }
-                }
-                Some(false) => {
-                    // A previous prune is still in progress
-                    Ok(true)
-                }
-                None => {
-                    // There is no prune in progress
-                    Ok(false)
-                }
-            }
-        }
-
         async fn run(
             logger: Logger,
             store: Arc<DeploymentStore>,
@@ -1257,7 +1448,7 @@ impl DeploymentStore {
             .await
         }
 
-        if !prune_in_progress(&self, &site)? {
+        if !self.prune_in_progress(&site)? {
             let req = PruneRequest::new(
                 &site.as_ref().into(),
                 history_blocks,
@@ -1276,6 +1467,51 @@ impl DeploymentStore {
         Ok(())
     }
 
+    fn spawn_retention_prune(
+        self: &Arc<Self>,
+        logger: &Logger,
+        site: Arc<Site>,
+        latest_block: BlockNumber,
+    ) -> Result<(), StoreError> {
+        async fn run(
+            logger: Logger,
+            store: Arc<DeploymentStore>,
+            site: Arc<Site>,
+            final_block: BlockNumber,
+        ) -> Result<(), StoreError> {
+            info!(logger, "Start pruning entities past their retention";
+                  "final_block" => final_block);
+            let logger2 = logger.cheap_clone();
+            retry::forever_async(&logger2, "prune_by_retention", move || {
+                let store = store.cheap_clone();
+                let reporter = OngoingPruneReporter::new(logger.cheap_clone());
+                let site = site.cheap_clone();
+                async move {
+                    store
+                        .prune_by_retention(reporter, site, final_block)
+                        .await
+                        .map(|_| ())
+                }
+            })
+            .await
+        }
+
+        if !self.prune_in_progress(&site)? {
+            let final_block = latest_block - ENV_VARS.reorg_threshold;
+            if final_block <= 0 {
+                return Ok(());
+            }
+
+            let deployment_id = site.id;
+            let handle = graph::spawn(run(logger.cheap_clone(), self.clone(), site, final_block));
+            self.prune_handles
+                .lock()
+                .unwrap()
+                .insert(deployment_id, handle);
+        }
+        Ok(())
+    }
+
     fn rewind_or_truncate_with_conn(
         &self,
         conn: &mut PgConnection,
@@ -1499,6 +1735,11 @@ impl DeploymentStore {
         deployment::graft_pending(&mut conn, id)
     }
 
+    pub(crate) fn graft_in_place(&self, id: &DeploymentHash) -> Result<bool, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::graft_in_place(&mut conn, id)
+    }
+
     /// Bring the subgraph into a state where we can start or resume
     /// indexing.
     ///
@@ -1519,36 +1760,68 @@ impl DeploymentStore {
 
         // If `graft_src` is `Some`, then there is a pending graft.
         if let Some((src, block, src_deployment, index_list)) = graft_src {
-            info!(
-                logger,
-                "Initializing graft by copying data from {} to {}",
-                src.catalog.site.namespace,
-                dst.catalog.site.namespace
-            );
-
             let src_manifest_idx_and_name = src_deployment.manifest.template_idx_and_name()?;
             let dst_manifest_idx_and_name = self
                 .load_deployment(dst.site.clone())?
                 .manifest
                 .template_idx_and_name()?;
 
-            // Copy subgraph data
-            // We allow both not copying tables at all from the source, as well
-            // as adding new tables in `self`; we only need to check that tables
-            // that actually need to be copied from the source are compatible
-            // with the corresponding tables in `self`
-            let mut copy_conn = crate::copy::Connection::new(
-                logger,
-                self.pool.clone(),
-                src.clone(),
-                dst.clone(),
-                block.clone(),
-                src_manifest_idx_and_name,
-                dst_manifest_idx_and_name,
-            )?;
-            let status = copy_conn.copy_data(index_list)?;
-            if status == crate::copy::Status::Cancelled {
-                return Err(StoreError::Canceled);
+            let in_place = self.graft_in_place(&site.deployment)?;
+            if in_place {
+                info!(
+                    logger,
+                    "Initializing graft by moving data from {} to {}",
+                    src.catalog.site.namespace,
+                    dst.catalog.site.namespace
+                );
+
+                let start = Instant::now();
+                let mut conn = self.get_conn()?;
+                // Keep anybody from writing to `src` while we move it
+                deployment::with_lock(&mut conn, &src.site, |conn| {
+                    conn.transaction(|conn| {
+                        dst.alter_in_place_from(
+                            conn,
+                            &src,
+                            block.number,
+                            &src_manifest_idx_and_name,
+                            &dst_manifest_idx_and_name,
+                        )
+                    })
+                })?;
+                info!(logger, "Moved data and altered it in place";
+                      "time_ms" => start.elapsed().as_millis());
+                // Make sure the layout for `dst` gets loaded from the
+                // altered tables, and that nothing uses the emptied
+                // layout of `src`
+                self.layout_cache.remove(&site);
+                self.layout_cache.remove(&src.site);
+            } else {
+                info!(
+                    logger,
+                    "Initializing graft by copying data from {} to {}",
+                    src.catalog.site.namespace,
+                    dst.catalog.site.namespace
+                );
+
+                // Copy subgraph data
+                // We allow both not copying tables at all from the source, as well
+                // as adding new tables in `self`; we only need to check that tables
+                // that actually need to be copied from the source are compatible
+                // with the corresponding tables in `self`
+                let mut copy_conn = crate::copy::Connection::new(
+                    logger,
+                    self.pool.clone(),
+                    src.clone(),
+                    dst.clone(),
+                    block.clone(),
+                    src_manifest_idx_and_name,
+                    dst_manifest_idx_and_name,
+                )?;
+                let status = copy_conn.copy_data(index_list)?;
+                if status == crate::copy::Status::Cancelled {
+                    return Err(StoreError::Canceled);
+                }
             }
 
             let mut conn = self.get_conn()?;
@@ -1613,6 +1886,13 @@ impl DeploymentStore {
                 // Set the block ptr to the graft point to signal that we successfully
                 // performed the graft
                 crate::deployment::forward_block_ptr(conn, &dst.site.deployment, &block)?;
+
+                // If `src` has not moved past the graft point, like when a
+                // paused deployment is moved to another shard, `dst` can
+                // resume from its cursor instead of starting without one
+                if deployment::copy_firehose_cursor(conn, &src.site, &dst.site, &block)? {
+                    info!(logger, "Copied the Firehose cursor");
+                }
                 info!(logger, "Subgraph successfully initialized";
                     "time_ms" => start.elapsed().as_millis());
                 Ok(())
@@ -63,6 +63,7 @@ pub struct DeploymentDetail {
     graft_base: Option<String>,
     graft_block_hash: Option<Bytes>,
     graft_block_number: Option<BigDecimal>,
+    graft_in_place: bool,
     debug_fork: Option<String>,
     reorg_count: i32,
     current_reorg_depth: i32,
@@ -251,6 +252,7 @@ pub(crate) fn info_from_details(
         entity_count,
         node: None,
         history_blocks: subgraph_history_blocks,
+        warm_up: None,
     })
 }
 
@@ -17,6 +17,7 @@ use graph::{
 };
 
 use crate::primary::Namespace;
+use crate::relational::SqlName;
 
 type DynTable = diesel_dynamic_schema::Table<String, Namespace>;
 type DynColumn<ST> = diesel_dynamic_schema::Column<DynTable, &'static str, ST>;
@@ -313,6 +314,74 @@ impl DataSourcesTable {
         Ok(count)
     }
 
+    /// Return `true` if the table exists in the database
+    pub(crate) fn exists(&self, conn: &mut PgConnection) -> Result<bool, StoreError> {
+        crate::catalog::table_exists(
+            conn,
+            self.namespace.as_str(),
+            &SqlName::verbatim(Self::TABLE_NAME.to_string()),
+        )
+    }
+
+    /// Change the manifest indexes of the data sources in this table from
+    /// the ones in `src_manifest_idx_and_name` to the ones with the same
+    /// template name in `dst_manifest_idx_and_name`. This is needed when
+    /// the table has been moved to a deployment with a different manifest
+    pub(crate) fn remap_manifest_idx(
+        &self,
+        conn: &mut PgConnection,
+        src_manifest_idx_and_name: &[(i32, String)],
+        dst_manifest_idx_and_name: &[(i32, String)],
+    ) -> Result<usize, StoreError> {
+        let src_idxs = self
+            .table
+            .clone()
+            .select(&self.manifest_idx)
+            .distinct()
+            .load::<i32>(conn)?;
+
+        let mut mapping = Vec::new();
+        for src_idx in src_idxs {
+            let name = &src_manifest_idx_and_name
+                .iter()
+                .find(|(idx, _)| idx == &src_idx)
+                .with_context(|| {
+                    anyhow!(
+                        "the source of {} does not have a template with index {}",
+                        self.namespace,
+                        src_idx
+                    )
+                })?
+                .1;
+            let dst_idx = dst_manifest_idx_and_name
+                .iter()
+                .find(|(_, n)| n == name)
+                .with_context(|| {
+                    anyhow!(
+                        "the manifest for {} is missing a template with name {}",
+                        self.namespace,
+                        name
+                    )
+                })?
+                .0;
+            if src_idx != dst_idx {
+                mapping.push(format!("when {src_idx} then {dst_idx}"));
+            }
+        }
+        if mapping.is_empty() {
+            return Ok(0);
+        }
+
+        // Use a single statement so that swapping indexes does not
+        // clobber data sources that were already remapped
+        let query = format!(
+            "update {} set manifest_idx = case manifest_idx {} else manifest_idx end",
+            self.qname,
+            mapping.join(" ")
+        );
+        Ok(sql_query(query).execute(conn)?)
+    }
+
     // Remove offchain data sources by checking the causality region, which currently uniquely
     // identifies an offchain data source.
     pub(super) fn update_offchain_status(
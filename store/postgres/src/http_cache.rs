@@ -0,0 +1,99 @@
+//! Storage for the responses to `http.get` requests from offchain data
+//! sources. Responses live in the primary and are never updated so that
+//! handlers that run again, for example after a restart or a reorg, see
+//! the same response as the first time
+use chrono::Utc;
+use diesel::prelude::*;
+use graph::components::store::{HttpCache, StoreError};
+use graph::constraint_violation;
+use graph::data::subgraph::DeploymentHash;
+
+use crate::connection_pool::ConnectionPool;
+
+diesel::table! {
+    public.http_cache (deployment, source, url) {
+        deployment -> Text,
+        source -> Text,
+        url -> Text,
+        content_hash -> Binary,
+        body -> Binary,
+        created_at -> Timestamptz,
+    }
+}
+
+use self::http_cache as h;
+
+#[derive(Clone)]
+pub struct HttpCacheStorage {
+    primary_pool: ConnectionPool,
+}
+
+impl HttpCacheStorage {
+    pub fn new(primary_pool: ConnectionPool) -> Self {
+        Self { primary_pool }
+    }
+
+    fn check_hash(url: &str, hash: Vec<u8>, body: Vec<u8>) -> Result<Vec<u8>, StoreError> {
+        if blake3::hash(&body).as_bytes()[..] != hash[..] {
+            return Err(constraint_violation!(
+                "the stored response for `{}` does not match its content hash",
+                url
+            ));
+        }
+        Ok(body)
+    }
+}
+
+impl HttpCache for HttpCacheStorage {
+    fn get(
+        &self,
+        deployment: &DeploymentHash,
+        source: &str,
+        url: &str,
+    ) -> Result<Option<Vec<u8>>, StoreError> {
+        let mut conn = self.primary_pool.get()?;
+
+        h::table
+            .filter(h::deployment.eq(deployment.as_str()))
+            .filter(h::source.eq(source))
+            .filter(h::url.eq(url))
+            .select((h::content_hash, h::body))
+            .get_result::<(Vec<u8>, Vec<u8>)>(&mut conn)
+            .optional()?
+            .map(|(hash, body)| Self::check_hash(url, hash, body))
+            .transpose()
+    }
+
+    fn insert(
+        &self,
+        deployment: &DeploymentHash,
+        source: &str,
+        url: &str,
+        body: Vec<u8>,
+    ) -> Result<Vec<u8>, StoreError> {
+        let mut conn = self.primary_pool.get()?;
+
+        let content_hash = blake3::hash(&body).as_bytes().to_vec();
+        // Another node or an earlier attempt might have stored a response
+        // already; the one that got there first wins
+        diesel::insert_into(h::table)
+            .values((
+                h::deployment.eq(deployment.as_str()),
+                h::source.eq(source),
+                h::url.eq(url),
+                h::content_hash.eq(&content_hash),
+                h::body.eq(&body),
+                h::created_at.eq(Utc::now()),
+            ))
+            .on_conflict_do_nothing()
+            .execute(&mut conn)?;
+
+        let (hash, body) = h::table
+            .filter(h::deployment.eq(deployment.as_str()))
+            .filter(h::source.eq(source))
+            .filter(h::url.eq(url))
+            .select((h::content_hash, h::body))
+            .get_result::<(Vec<u8>, Vec<u8>)>(&mut conn)?;
+        Self::check_hash(url, hash, body)
+    }
+}
@@ -38,7 +38,10 @@ mod subgraph_store;
 pub mod transaction_receipt;
 mod writable;
 
+pub mod api_keys;
+pub mod deployment_logs;
 pub mod graphman;
+pub mod http_cache;
 
 #[cfg(debug_assertions)]
 pub mod layout_for_tests {
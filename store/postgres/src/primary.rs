@@ -1408,6 +1408,18 @@ impl<'a> Connection<'a> {
             .map(|_| ())
     }
 
+    /// Mark `site` as not active so that nothing uses it anymore, for
+    /// example because its data was moved to another deployment
+    pub(crate) fn deactivate(&mut self, site: &Site) -> Result<(), StoreError> {
+        use deployment_schemas as ds;
+
+        update(ds::table.filter(ds::id.eq(site.id)))
+            .set(ds::active.eq(false))
+            .execute(self.conn.as_mut())
+            .map_err(|e| e.into())
+            .map(|_| ())
+    }
+
     /// Remove all subgraph versions, the entry in `deployment_schemas` and the entry in
     /// `subgraph_features` for subgraph `id` in a transaction
     pub fn drop_site(&mut self, site: &Site) -> Result<(), StoreError> {
@@ -1776,6 +1788,28 @@ impl<'a> Connection<'a> {
             .load(self.conn.as_mut())?)
     }
 
+    /// The names of the subgraphs whose current or pending version is the
+    /// deployment `site`
+    pub fn subgraphs_with_current_or_pending(
+        &mut self,
+        site: &Site,
+    ) -> Result<Vec<String>, StoreError> {
+        use subgraph as s;
+        use subgraph_version as v;
+
+        Ok(s::table
+            .inner_join(
+                v::table.on(v::id
+                    .nullable()
+                    .eq(s::current_version)
+                    .or(v::id.nullable().eq(s::pending_version))),
+            )
+            .filter(v::deployment.eq(site.deployment.as_str()))
+            .select(s::name)
+            .distinct()
+            .load(self.conn.as_mut())?)
+    }
+
     pub fn find_ens_name(&mut self, hash: &str) -> Result<Option<String>, StoreError> {
         use ens_names as dsl;
 
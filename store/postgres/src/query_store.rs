@@ -3,7 +3,8 @@ use std::time::Instant;
 
 use crate::deployment_store::{DeploymentStore, ReplicaId};
 use graph::components::store::{DeploymentId, QueryPermit, QueryStore as QueryStoreTrait};
-use graph::data::query::Trace;
+use graph::components::tracing::{self, KeyValue};
+use graph::data::query::{EntityHistory, EntityHistoryQuery, SqlQueryResult, Trace};
 use graph::data::store::QueryObject;
 use graph::prelude::*;
 use graph::schema::{ApiSchema, InputSchema};
@@ -43,18 +44,59 @@ impl QueryStoreTrait for QueryStore {
         query: EntityQuery,
     ) -> Result<(Vec<QueryObject>, Trace), graph::prelude::QueryExecutionError> {
         assert_eq!(&self.site.deployment, &query.subgraph_id);
+        let mut attributes = tracing::deployment_attributes(&self.site.deployment);
+        attributes.push(KeyValue::new("block.number", query.block as i64));
+        let cx = tracing::child_span("store.query", attributes);
+
         let start = Instant::now();
         let mut conn = self
             .store
             .get_replica_conn(self.replica_id)
             .map_err(|e| QueryExecutionError::StoreError(e.into()))?;
         let wait = start.elapsed();
-        self.store
+        let res = self
+            .store
             .execute_query(&mut conn, self.site.clone(), query)
             .map(|(entities, mut trace)| {
                 trace.conn_wait(wait);
                 (entities, trace)
-            })
+            });
+        match &res {
+            Ok((entities, _)) => tracing::set_attributes(
+                &cx,
+                vec![
+                    KeyValue::new("conn_wait_ms", wait.as_millis() as i64),
+                    KeyValue::new("entities", entities.len() as i64),
+                ],
+            ),
+            Err(e) => tracing::record_error(&cx, e),
+        }
+        res
+    }
+
+    fn execute_sql(
+        &self,
+        sql: &str,
+        block: BlockNumber,
+    ) -> Result<SqlQueryResult, QueryExecutionError> {
+        let mut conn = self
+            .store
+            .get_replica_conn(self.replica_id)
+            .map_err(|e| QueryExecutionError::StoreError(e.into()))?;
+        self.store
+            .execute_sql(&mut conn, self.site.clone(), sql, block)
+    }
+
+    fn entity_history(
+        &self,
+        query: &EntityHistoryQuery,
+    ) -> Result<EntityHistory, QueryExecutionError> {
+        let mut conn = self
+            .store
+            .get_replica_conn(self.replica_id)
+            .map_err(|e| QueryExecutionError::StoreError(e.into()))?;
+        self.store
+            .entity_history(&mut conn, self.site.clone(), query)
     }
 
     /// Return true if the deployment with the given id is fully synced,
@@ -18,6 +18,7 @@ pub(crate) mod dsl;
 pub(crate) mod index;
 mod prune;
 mod rollup;
+mod sql;
 pub(crate) mod value;
 
 use diesel::deserialize::FromSql;
@@ -34,10 +35,10 @@ use graph::components::store::write::{RowGroup, WriteChunk};
 use graph::components::subgraph::PoICausalityRegion;
 use graph::constraint_violation;
 use graph::data::graphql::TypeExt as _;
-use graph::data::query::Trace;
+use graph::data::query::{EntityHistory, EntityHistoryQuery, EntityVersion, SqlQueryResult, Trace};
 use graph::data::value::Word;
 use graph::data_source::CausalityRegion;
-use graph::prelude::{q, EntityQuery, StopwatchMetrics, ENV_VARS};
+use graph::prelude::{q, r, EntityQuery, StopwatchMetrics, ENV_VARS};
 use graph::schema::{
     EntityKey, EntityType, Field, FulltextConfig, FulltextDefinition, InputSchema,
 };
@@ -56,8 +57,8 @@ use std::time::{Duration, Instant};
 
 use crate::relational::value::{FromOidRow, OidRow};
 use crate::relational_queries::{
-    ConflictingEntitiesData, ConflictingEntitiesQuery, FindChangesQuery, FindDerivedQuery,
-    FindPossibleDeletionsQuery, ReturnedEntityData,
+    ConflictingEntitiesData, ConflictingEntitiesQuery, EntityVersionData, FindChangesQuery,
+    FindDerivedQuery, FindHistoryQuery, FindPossibleDeletionsQuery, ReturnedEntityData,
 };
 use crate::{
     primary::{Namespace, Site},
@@ -67,7 +68,7 @@ use crate::{
     },
 };
 use graph::components::store::{AttributeNames, DerivedEntityQuery};
-use graph::data::store::{Id, IdList, IdType, BYTES_SCALAR};
+use graph::data::store::{Id, IdList, IdType, QueryObject, BYTES_SCALAR};
 use graph::data::subgraph::schema::POI_TABLE;
 use graph::prelude::{
     anyhow, info, BlockNumber, DeploymentHash, Entity, EntityChange, EntityOperation, Logger,
@@ -77,6 +78,7 @@ use graph::prelude::{
 use crate::block_range::{BLOCK_COLUMN, BLOCK_RANGE_COLUMN};
 pub use crate::catalog::Catalog;
 use crate::connection_pool::ForeignServer;
+use crate::dynds::DataSourcesTable;
 use crate::{catalog, deployment};
 
 use self::rollup::Rollup;
@@ -258,7 +260,6 @@ impl Layout {
         // since we handle that specially
         let entity_tables = schema.entity_types();
         let ts_tables = schema.ts_entity_types();
-        let has_ts_tables = !ts_tables.is_empty();
 
         let mut tables = entity_tables
             .iter()
@@ -283,7 +284,7 @@ impl Layout {
             tables.push(Self::make_poi_table(
                 &schema,
                 &catalog,
-                has_ts_tables,
+                schema.needs_block_time(),
                 tables.len(),
             ))
         }
@@ -330,7 +331,7 @@ impl Layout {
     fn make_poi_table(
         schema: &InputSchema,
         catalog: &Catalog,
-        has_ts_tables: bool,
+        has_block_time: bool,
         position: usize,
     ) -> Table {
         let poi_type = schema.poi_type();
@@ -360,9 +361,9 @@ impl Layout {
             },
         ];
 
-        // If the subgraph uses timeseries, store the block time in the PoI
-        // table
-        if has_ts_tables {
+        // If the subgraph uses timeseries or entities with a retention,
+        // store the block time in the PoI table
+        if has_block_time {
             // FIXME: Use `Timestamp` as the field type when that's
             // available
             let ts_column = Column {
@@ -432,6 +433,100 @@ impl Layout {
             .collect()
     }
 
+    /// Determine if the data of `base` can be moved into `self` and
+    /// brought up to date with our schema by only adding to the database
+    /// schema of `base`. That is possible if our schema only adds nullable
+    /// fields, entity types, or enum values to the schema of `base`.
+    /// Returns a list of errors if that is not possible. An empty vector
+    /// indicates that `self` can take over the data of `base`
+    pub fn can_alter_in_place(&self, base: &Layout) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.site.shard != base.site.shard {
+            errors.push(format!(
+                "the base is in shard {} but the deployment is in shard {}",
+                base.site.shard, self.site.shard
+            ));
+        }
+        if !base.site.schema_version.private_data_sources() {
+            errors.push("the base uses an old storage scheme for data sources".to_string());
+        }
+        if !base.catalog.use_bytea_prefix {
+            errors.push("the base uses an old storage scheme for indexes".to_string());
+        }
+        for name in base.input_schema.enum_types() {
+            // Enums that are not used by any columns anymore are harmless,
+            // and changes to them are caught when we check the columns
+            let (Some(src), Some(dst)) = (
+                base.input_schema.enum_values(name),
+                self.input_schema.enum_values(name),
+            ) else {
+                continue;
+            };
+            let removed = src.difference(&dst).collect::<Vec<_>>();
+            if !removed.is_empty() {
+                errors.push(format!(
+                    "the enum {} does not contain the values {} anymore",
+                    name,
+                    removed.iter().join(", ")
+                ));
+            }
+        }
+        for src in base.tables.values() {
+            match self.table(&src.name) {
+                Some(dst) => errors.extend(dst.can_alter_in_place_from(src, &self.input_schema)),
+                None => errors.push(format!("the entity type {} was removed", src.object)),
+            }
+        }
+        errors
+    }
+
+    /// Take over the data of `src` by moving its database schema into
+    /// `self` and adding what our schema adds to the schema of `src`. The
+    /// caller must have checked that that is possible with
+    /// `can_alter_in_place`. The namespace of `src` is left empty.
+    ///
+    /// The data sources of `src` are reverted to `block` and changed to
+    /// refer to the templates in our manifest; entities are not changed
+    /// and need to be reverted by the caller
+    pub(crate) fn alter_in_place_from(
+        &self,
+        conn: &mut PgConnection,
+        src: &Layout,
+        block: BlockNumber,
+        src_manifest_idx_and_name: &[(i32, String)],
+        dst_manifest_idx_and_name: &[(i32, String)],
+    ) -> Result<(), StoreError> {
+        let src_nsp = &src.site.namespace;
+        let dst_nsp = &self.site.namespace;
+
+        // If the data sources of `src` are gone, an earlier attempt at
+        // this already moved them
+        if !DataSourcesTable::new(src_nsp.clone()).exists(conn)? {
+            return Ok(());
+        }
+
+        let ddl = self.as_additive_ddl(src).map_err(|_| {
+            StoreError::Unknown(anyhow!(
+                "failed to generate DDL to alter {} in place",
+                src_nsp
+            ))
+        })?;
+
+        // The schema for `self` was created when the deployment was
+        // created, but it does not contain any data
+        catalog::drop_schema(conn, dst_nsp.as_str())?;
+        conn.batch_execute(&format!(
+            "alter schema {src_nsp} rename to {dst_nsp};\
+             create schema {src_nsp};"
+        ))?;
+        conn.batch_execute(&ddl)?;
+
+        let dds = DataSourcesTable::new(dst_nsp.clone());
+        dds.revert(conn, block + 1)?;
+        dds.remap_manifest_idx(conn, src_manifest_idx_and_name, dst_manifest_idx_and_name)?;
+        Ok(())
+    }
+
     /// Import the database schema for this layout from its own database
     /// shard (in `self.site.shard`) into the database represented by `conn`
     /// if the schema for this layout does not exist yet
@@ -551,7 +646,15 @@ impl Layout {
         let table = self.table_for_entity(&derived_query.entity_type)?;
         let ids = excluded_keys.iter().map(|key| &key.entity_id).cloned();
         let excluded_keys = IdList::try_from_iter(derived_query.entity_type.id_type()?, ids)?;
-        let query = FindDerivedQuery::new(table, derived_query, block, excluded_keys);
+        let filter = derived_query.filter.entity_filter();
+        let query = FindDerivedQuery::new(
+            self,
+            table,
+            derived_query,
+            filter.as_ref(),
+            block,
+            excluded_keys,
+        )?;
 
         let mut entities = BTreeMap::new();
 
@@ -785,6 +888,138 @@ impl Layout {
             .map(|values| (values, trace))
     }
 
+    /// Run the SQL query `sql` from the SQL endpoint against the tables
+    /// in this layout as they were at `block`. The query is validated and
+    /// rewritten by `sql::rewrite` and runs in a read-only transaction
+    pub fn query_sql(
+        &self,
+        conn: &mut PgConnection,
+        sql: &str,
+        block: BlockNumber,
+    ) -> Result<SqlQueryResult, QueryExecutionError> {
+        #[derive(QueryableByName)]
+        struct Row {
+            #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Json>)]
+            cols: Option<serde_json::Value>,
+            #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Json>)]
+            vals: Option<serde_json::Value>,
+        }
+
+        let query = sql::rewrite(self, sql, block)?;
+        let max_rows = ENV_VARS.graphql.sql_max_rows;
+        // Turn each row into a JSON array of column names and one of
+        // values; `json_each` keeps the columns in the order in which the
+        // query produces them
+        let query = format!(
+            "select (select json_agg(e.key order by e.n) \
+                       from json_each(t.r) with ordinality as e(key, value, n)) as cols, \
+                    (select json_agg(e.value order by e.n) \
+                       from json_each(t.r) with ordinality as e(key, value, n)) as vals \
+               from (select row_to_json(q) as r from ({query}) q limit {limit}) t",
+            limit = max_rows + 1
+        );
+
+        let rows = conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                if let Some(ref timeout_sql) = *STATEMENT_TIMEOUT {
+                    conn.batch_execute(timeout_sql)?;
+                }
+                sql_query(query).load::<Row>(conn)
+            })
+            .map_err(|e| QueryExecutionError::InvalidSqlQuery(e.to_string()))?;
+        if rows.len() > max_rows {
+            return Err(QueryExecutionError::InvalidSqlQuery(format!(
+                "the query returns more than the maximum of {max_rows} rows"
+            )));
+        }
+
+        let mut result = SqlQueryResult::default();
+        for row in rows {
+            if let (true, Some(serde_json::Value::Array(cols))) =
+                (result.columns.is_empty(), row.cols)
+            {
+                result.columns = cols
+                    .into_iter()
+                    .map(|col| match col {
+                        serde_json::Value::String(col) => col,
+                        col => col.to_string(),
+                    })
+                    .collect();
+            }
+            match row.vals {
+                Some(serde_json::Value::Array(vals)) => result.rows.push(vals),
+                _ => result.rows.push(vec![]),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Find the versions of entities that were written or removed in the
+    /// block range of `query`. The end of a version's block range is only
+    /// reported if it falls into the requested range so that the result
+    /// does not depend on what happened after `query.to_block`
+    pub fn entity_history(
+        &self,
+        conn: &mut PgConnection,
+        query: &EntityHistoryQuery,
+    ) -> Result<EntityHistory, QueryExecutionError> {
+        let unknown_type = || {
+            QueryExecutionError::ValueParseError(
+                "entity".to_string(),
+                format!("unknown entity type `{}`", query.entity),
+            )
+        };
+        let entity_type = self
+            .input_schema
+            .entity_type(&query.entity)
+            .map_err(|_| unknown_type())?;
+        let table = self
+            .table_for_entity(&entity_type)
+            .map_err(|_| unknown_type())?;
+        let ids = query
+            .id
+            .as_ref()
+            .map(|id| {
+                entity_type.parse_ids(vec![id.clone()]).map_err(|e| {
+                    QueryExecutionError::ValueParseError("id".to_string(), e.to_string())
+                })
+            })
+            .transpose()?;
+
+        // Without an `id`, the query has to scan the versions of all
+        // entities of the type and is subject to the same timeout as
+        // GraphQL queries
+        let rows = conn
+            .transaction(|conn| {
+                if let Some(ref timeout_sql) = *STATEMENT_TIMEOUT {
+                    conn.batch_execute(timeout_sql)?;
+                }
+                FindHistoryQuery::new(
+                    table,
+                    ids,
+                    query.from_block,
+                    query.to_block,
+                    query.first,
+                    query.skip,
+                )
+                .load::<EntityVersionData>(conn)
+            })
+            .map_err(StoreError::from)?;
+
+        let mut history = EntityHistory::default();
+        for row in rows {
+            let object: QueryObject = row.data.deserialize_with_layout(self, None)?;
+            history.versions.push(EntityVersion {
+                block_from: row.block_from,
+                block_to: row.block_to.filter(|block| *block <= query.to_block),
+                data: r::Value::Object(object.entity),
+            });
+        }
+        Ok(history)
+    }
+
     pub fn update<'a>(
         &'a self,
         conn: &mut PgConnection,
@@ -1181,12 +1416,15 @@ pub struct EnumType {
 
 impl EnumType {
     fn is_assignable_from(&self, source: &Self) -> Option<String> {
-        if source.values.is_subset(self.values.as_ref()) {
+        let missing = source.values.difference(&self.values).collect::<Vec<_>>();
+        if missing.is_empty() {
             None
         } else {
             Some(format!(
-                "the enum type {} contains values not present in {}",
-                source.name, self.name
+                "the enum type {} contains the values {} that are not present in {}",
+                source.name,
+                missing.iter().join(", "),
+                self.name
             ))
         }
     }
@@ -1204,6 +1442,9 @@ pub enum ColumnType {
     Int,
     Int8,
     Timestamp,
+    /// Stored as a Postgres `point` with the longitude as `x` and the
+    /// latitude as `y`
+    GeoPoint,
     String,
     TSVector(FulltextConfig),
     Enum(EnumType),
@@ -1229,6 +1470,7 @@ impl std::fmt::Display for ColumnType {
             ColumnType::Int => write!(f, "Int"),
             ColumnType::Int8 => write!(f, "Int8"),
             ColumnType::Timestamp => write!(f, "Timestamp"),
+            ColumnType::GeoPoint => write!(f, "GeoPoint"),
             ColumnType::String => write!(f, "String"),
             ColumnType::TSVector(_) => write!(f, "TSVector"),
             ColumnType::Enum(enum_type) => write!(f, "Enum({})", enum_type.name),
@@ -1285,6 +1527,7 @@ impl ColumnType {
             ValueType::Int => Ok(ColumnType::Int),
             ValueType::Int8 => Ok(ColumnType::Int8),
             ValueType::Timestamp => Ok(ColumnType::Timestamp),
+            ValueType::GeoPoint => Ok(ColumnType::GeoPoint),
             ValueType::String => Ok(ColumnType::String),
         }
     }
@@ -1298,6 +1541,7 @@ impl ColumnType {
             ColumnType::Int => "int4",
             ColumnType::Int8 => "int8",
             ColumnType::Timestamp => "timestamptz",
+            ColumnType::GeoPoint => "point",
             ColumnType::String => "text",
             ColumnType::TSVector(_) => "tsvector",
             ColumnType::Enum(enum_type) => enum_type.name.as_str(),
@@ -1461,7 +1705,13 @@ impl Column {
                 object, self.field
             ))
         } else if let ColumnType::Enum(self_enum_type) = &self.column_type {
-            if let ColumnType::Enum(source_enum_type) = &source.column_type {
+            if self.is_list() != source.is_list() {
+                Some(format!(
+                    "The attribute {}.{} has type {}, \
+                                 but its type in the source is {}",
+                    object, self.field, self.field_type, source.field_type
+                ))
+            } else if let ColumnType::Enum(source_enum_type) = &source.column_type {
                 self_enum_type.is_assignable_from(source_enum_type)
             } else {
                 Some(format!(
@@ -1544,13 +1794,22 @@ impl Table {
         })?;
 
         let table_name = SqlName::from(defn.as_str());
-        let columns = object_type
+        let mut columns = object_type
             .fields
             .iter()
             .filter(|field| !field.is_derived())
             .map(|field| Column::new(schema, &table_name, field, catalog))
             .chain(fulltexts.iter().map(Column::new_fulltext))
             .collect::<Result<Vec<Column>, StoreError>>()?;
+        // The index for the key declared with `@entity(key: [..])` covers
+        // the entire values of the key columns, and lookups by key need to
+        // compare them in their entirety to use it
+        for column in columns
+            .iter_mut()
+            .filter(|column| object_type.key.contains(&column.field))
+        {
+            column.use_prefix_comparison = false;
+        }
         let qualified_name = SqlName::qualified_name(&catalog.site.namespace, &table_name);
         let immutable = defn.is_immutable();
         let nsp = catalog.site.namespace.clone();
@@ -1630,6 +1889,122 @@ impl Table {
             .collect()
     }
 
+    /// Check that `self` can take over the data of `source` where the only
+    /// change to `source` is that nullable columns are added to it, or that
+    /// references now point to an interface that the type they pointed to
+    /// implements in `schema`, our schema
+    fn can_alter_in_place_from(&self, source: &Self, schema: &InputSchema) -> Vec<String> {
+        /// Return the names of the base types of `src` and `dst` if the
+        /// two types only differ in their base type
+        fn base_types<'a>(src: &'a q::Type, dst: &'a q::Type) -> Option<(&'a str, &'a str)> {
+            use q::Type::*;
+            match (src, dst) {
+                (NamedType(src), NamedType(dst)) => Some((src.as_str(), dst.as_str())),
+                (ListType(src), ListType(dst)) | (NonNullType(src), NonNullType(dst)) => {
+                    base_types(src, dst)
+                }
+                _ => None,
+            }
+        }
+
+        fn key(table: &Table) -> Option<Box<[Word]>> {
+            table
+                .object
+                .object_type()
+                .ok()
+                .map(|object_type| object_type.key.clone())
+        }
+
+        let mut errors = Vec::new();
+        if self.immutable != source.immutable {
+            errors.push(format!(
+                "the entity type {} changed whether it is immutable",
+                self.object
+            ));
+        }
+        if self.has_causality_region != source.has_causality_region {
+            errors.push(format!(
+                "the entity type {} changed whether it can be written by offchain data sources",
+                self.object
+            ));
+        }
+        if key(self) != key(source) {
+            errors.push(format!(
+                "the key of the entity type {} changed",
+                self.object
+            ));
+        }
+        for scol in &source.columns {
+            let Some(dcol) = self.column(&scol.name) else {
+                errors.push(format!(
+                    "the attribute {}.{} was removed",
+                    self.object, scol.field
+                ));
+                continue;
+            };
+            let same_type = match (&dcol.column_type, &scol.column_type) {
+                // Enums are qualified with their namespace, but they
+                // only differ in their values which we check separately
+                (ColumnType::Enum(_), ColumnType::Enum(_)) => true,
+                (dtype, stype) => dtype == stype && dcol.fulltext_fields == scol.fulltext_fields,
+            };
+            if !same_type {
+                errors.push(format!(
+                    "the attribute {}.{} has type {}, but its type in the base is {}",
+                    self.object, dcol.field, dcol.field_type, scol.field_type
+                ));
+            } else if dcol.field_type != scol.field_type {
+                // The values are stored the same way; that is fine if a
+                // reference now points to an interface of what it pointed
+                // to before
+                match base_types(&scol.field_type, &dcol.field_type) {
+                    Some((src_type, dst_type)) if schema.implements(src_type, dst_type) => {}
+                    Some((src_type, _)) if schema.is_reference(src_type) => errors.push(format!(
+                        "the attribute {}.{} has type {}, but its type in the base is {}; \
+                             references can only change to an interface that {} implements",
+                        self.object, dcol.field, dcol.field_type, scol.field_type, src_type
+                    )),
+                    _ => errors.push(format!(
+                        "the attribute {}.{} has type {}, but its type in the base is {}",
+                        self.object, dcol.field, dcol.field_type, scol.field_type
+                    )),
+                }
+            }
+            if self
+                .object
+                .field(dcol.field.as_str())
+                .and_then(|field| field.index)
+                != source
+                    .object
+                    .field(scol.field.as_str())
+                    .and_then(|field| field.index)
+            {
+                errors.push(format!(
+                    "the index declared for the attribute {}.{} changed",
+                    self.object, dcol.field
+                ));
+            }
+        }
+        for dcol in &self.columns {
+            if source.column(&dcol.name).is_some() {
+                continue;
+            }
+            if !dcol.is_nullable() {
+                errors.push(format!(
+                    "the attribute {}.{} is non-nullable, but there is no such attribute in the base",
+                    self.object, dcol.field
+                ));
+            }
+            if dcol.is_fulltext() {
+                errors.push(format!(
+                    "the fulltext search {} can not be added since existing entities would not be searchable",
+                    dcol.field
+                ));
+            }
+        }
+        errors
+    }
+
     pub fn primary_key(&self) -> &Column {
         self.columns
             .iter()
@@ -1,14 +1,17 @@
 use std::{
+    collections::BTreeSet,
     fmt::{self, Write},
     iter,
+    ops::Bound::{Excluded, Unbounded},
 };
 
 use graph::{
     prelude::{BLOCK_NUMBER_MAX, ENV_VARS},
-    schema::InputSchema,
+    schema::{IndexType, InputSchema},
 };
 
 use crate::block_range::CAUSALITY_REGION_COLUMN;
+use crate::primary::Namespace;
 use crate::relational::{
     ColumnType, BLOCK_COLUMN, BLOCK_RANGE_COLUMN, BYTE_ARRAY_PREFIX_SIZE, STRING_PREFIX_SIZE,
     VID_COLUMN,
@@ -23,6 +26,9 @@ const CREATE_EXCLUSION_CONSTRAINT: bool = true;
 #[cfg(not(debug_assertions))]
 const CREATE_EXCLUSION_CONSTRAINT: bool = false;
 
+/// The maximum length in bytes of identifiers in Postgres
+const MAX_IDENTIFIER_LEN: usize = 63;
+
 impl Layout {
     /// Generate the DDL for the entire layout, i.e., all `create table`
     /// and `create index` etc. statements needed in the database schema
@@ -52,27 +58,79 @@ impl Layout {
         Ok(out)
     }
 
-    pub(crate) fn write_enum_ddl(&self, out: &mut dyn Write) -> Result<(), fmt::Error> {
+    /// Generate the DDL that turns the database schema for `base` into
+    /// the one for `self` after it has been moved into our namespace. This
+    /// only covers additions, i.e., enum values, tables, and columns that
+    /// `base` does not have; `can_alter_in_place` checks that there are no
+    /// other changes.
+    ///
+    /// Enum values are added in the position they would have if the enum
+    /// had been created with them so that sorting by them is not affected
+    pub fn as_additive_ddl(&self, base: &Layout) -> Result<String, fmt::Error> {
+        let mut out = String::new();
+
         for name in self.input_schema.enum_types() {
             let values = self.input_schema.enum_values(name).unwrap();
-            let mut sep = "";
+            let Some(base_values) = base.input_schema.enum_values(name) else {
+                write_enum(&mut out, &self.catalog.site.namespace, name, &values)?;
+                continue;
+            };
             let name = SqlName::from(name);
-            write!(
-                out,
-                "create type {}.{}\n    as enum (",
-                self.catalog.site.namespace,
-                name.quoted()
-            )?;
-            for value in values.iter() {
-                write!(out, "{}'{}'", sep, value)?;
-                sep = ", "
+            for value in values.difference(&base_values) {
+                let next = base_values
+                    .range::<String, _>((Excluded(value), Unbounded))
+                    .next();
+                write!(
+                    out,
+                    "alter type {}.{} add value '{}'",
+                    self.catalog.site.namespace,
+                    name.quoted(),
+                    value
+                )?;
+                match next {
+                    Some(next) => writeln!(out, " before '{}';", next)?,
+                    None => writeln!(out, ";")?,
+                }
+            }
+        }
+
+        let mut tables = self.tables.values().collect::<Vec<_>>();
+        tables.sort_by_key(|table| table.position);
+        for table in tables {
+            match base.table(&table.name) {
+                Some(base_table) => table.add_columns_ddl(base_table, &mut out)?,
+                None => table.as_ddl(&self.input_schema, &self.catalog, None, &mut out)?,
             }
-            writeln!(out, ");")?;
+        }
+
+        Ok(out)
+    }
+
+    pub(crate) fn write_enum_ddl(&self, out: &mut dyn Write) -> Result<(), fmt::Error> {
+        for name in self.input_schema.enum_types() {
+            let values = self.input_schema.enum_values(name).unwrap();
+            write_enum(out, &self.catalog.site.namespace, name, &values)?;
         }
         Ok(())
     }
 }
 
+fn write_enum(
+    out: &mut dyn Write,
+    nsp: &Namespace,
+    name: &str,
+    values: &BTreeSet<String>,
+) -> fmt::Result {
+    let mut sep = "";
+    let name = SqlName::from(name);
+    write!(out, "create type {}.{}\n    as enum (", nsp, name.quoted())?;
+    for value in values.iter() {
+        write!(out, "{}'{}'", sep, value)?;
+        sep = ", "
+    }
+    writeln!(out, ");")
+}
+
 impl Table {
     /// Return an iterator over all the column names of this table
     ///
@@ -254,6 +312,10 @@ impl Table {
 
         let method = if column.is_list() || column.is_fulltext() {
             "gin".to_string()
+        } else if column.column_type == ColumnType::GeoPoint {
+            // Points have no btree operator class; the GiST index
+            // supports the `<@ box(..)` checks for geospatial filters
+            "gist".to_string()
         } else {
             "btree".to_string()
         };
@@ -271,6 +333,7 @@ impl Table {
             if !column.is_list()
                 && method == "btree"
                 && column.name.as_str() != "id"
+                && self.declared_index(column).is_none()
                 && !skip_colums.contains(&column.name.to_string())
             {
                 let sql = format!(
@@ -290,28 +353,43 @@ impl Table {
         let columns = self.columns_to_index();
 
         for (column_index, column) in columns.enumerate() {
-            let (method, index_expr) =
-                Self::calculate_attr_index_method_and_expression(self.immutable, column);
-
-            // If `create_gin_indexes` is set to false, we don't create
-            // indexes on array attributes. Experience has shown that these
-            // indexes are very expensive to update and can have a very bad
-            // impact on the write performance of the database, but are
-            // hardly ever used or needed by queries.
-            if !column.is_list() || ENV_VARS.store.create_gin_indexes {
-                write!(
-                    out,
-                    "create index attr_{table_index}_{column_index}_{table_name}_{column_name}\n    on {qname} using {method}({index_expr});\n",
-                    table_index = self.position,
-                    table_name = self.name,
-                    column_name = column.name,
-                    qname = self.qualified_name,
-                )?;
-            }
+            self.create_attribute_index(column_index, column, out)?;
         }
         writeln!(out)
     }
 
+    fn create_attribute_index(
+        &self,
+        column_index: usize,
+        column: &Column,
+        out: &mut String,
+    ) -> fmt::Result {
+        // Columns with a declared index get that index instead
+        if self.declared_index(column).is_some() {
+            return Ok(());
+        }
+
+        let (method, index_expr) =
+            Self::calculate_attr_index_method_and_expression(self.immutable, column);
+
+        // If `create_gin_indexes` is set to false, we don't create
+        // indexes on array attributes. Experience has shown that these
+        // indexes are very expensive to update and can have a very bad
+        // impact on the write performance of the database, but are
+        // hardly ever used or needed by queries.
+        if !column.is_list() || ENV_VARS.store.create_gin_indexes {
+            write!(
+                out,
+                "create index attr_{table_index}_{column_index}_{table_name}_{column_name}\n    on {qname} using {method}({index_expr});\n",
+                table_index = self.position,
+                table_name = self.name,
+                column_name = column.name,
+                qname = self.qualified_name,
+            )?;
+        }
+        Ok(())
+    }
+
     fn columns_to_index(&self) -> impl Iterator<Item = &Column> {
         // Skip columns whose type is an array of enum, since there is no
         // good way to index them with Postgres 9.6. Once we move to
@@ -330,12 +408,18 @@ impl Table {
                 && [ColumnType::BigDecimal, ColumnType::BigInt, ColumnType::Int]
                     .contains(&col.column_type))
         };
+        // Arrays of points can not be indexed since `point` has no
+        // equality operator
+        let not_point_list =
+            |col: &&Column| !(col.is_list() && col.column_type == ColumnType::GeoPoint);
+
         let columns = self
             .columns
             .iter()
             .filter(not_enum_list)
             .filter(not_immutable_pk)
-            .filter(not_numeric_list);
+            .filter(not_numeric_list)
+            .filter(not_point_list);
         columns
     }
 
@@ -378,6 +462,152 @@ impl Table {
         Ok(())
     }
 
+    /// If the object type declares a key with `@entity(key: [..])`, make
+    /// sure that there is only ever one entity for each combination of
+    /// values of the key's columns and support lookups by them. For
+    /// immutable entities a unique index does that; mutable entities need
+    /// an exclusion constraint since different versions of the same entity
+    /// share its key, and a BTree index for range scans on a prefix of the
+    /// key since the GiST index of the constraint doesn't help with those.
+    /// The key is a secondary key; the table is still keyed by `id`
+    fn create_key_indexes(&self, out: &mut String) -> fmt::Result {
+        let object_type = self.object.object_type().map_err(|_| fmt::Error)?;
+        if object_type.key.is_empty() {
+            return Ok(());
+        }
+
+        let key_cols: Vec<_> = object_type
+            .key
+            .iter()
+            .map(|field| {
+                self.column_for_field(field.as_str())
+                    .map(|col| col.name.quoted())
+                    .map_err(|_| fmt::Error)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let unique = if self.immutable { "unique " } else { "" };
+        writeln!(
+            out,
+            "create {unique}index {table_name}_key\n    on {qname}({cols});",
+            table_name = self.name,
+            qname = self.qualified_name,
+            cols = key_cols.join(", ")
+        )?;
+        if !self.immutable {
+            let cols = key_cols
+                .iter()
+                .map(|col| format!("{col} with ="))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                out,
+                "alter table {qname}\n    add constraint {table_name}_key_excl exclude using gist ({cols}, {block_range} with &&);",
+                table_name = self.name,
+                qname = self.qualified_name,
+                block_range = BLOCK_RANGE_COLUMN
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The index declared for `column` with `@index`, if any
+    fn declared_index(&self, column: &Column) -> Option<IndexType> {
+        self.object
+            .field(column.field.as_str())
+            .and_then(|field| field.index)
+    }
+
+    /// The name of the index declared for `column`. Postgres truncates
+    /// names that are longer than `MAX_IDENTIFIER_LEN`, which would make
+    /// the names of columns that only differ at the end collide. We
+    /// shorten such names ourselves and end them with a hash of the full
+    /// name instead
+    fn declared_index_name(&self, column: &Column, index_type: IndexType) -> String {
+        let name = format!("{}_{}_{}", self.name, column.name, index_type);
+        if name.len() <= MAX_IDENTIFIER_LEN {
+            return name;
+        }
+        let hash = hex::encode(&blake3::hash(name.as_bytes()).as_bytes()[..8]);
+        // SQL names are ASCII, and slicing by bytes is safe
+        let prefix = &name[..MAX_IDENTIFIER_LEN - hash.len() - 1];
+        format!("{prefix}_{hash}")
+    }
+
+    /// The names of the indexes and constraints that are created from
+    /// declarations in the schema, i.e., the ones for `@index` and for the
+    /// key from `@entity(key: [..])`
+    fn declared_index_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .columns
+            .iter()
+            .filter_map(|column| {
+                self.declared_index(column)
+                    .map(|index_type| self.declared_index_name(column, index_type))
+            })
+            .collect();
+        let has_key = self
+            .object
+            .object_type()
+            .map(|object_type| !object_type.key.is_empty())
+            .unwrap_or(false);
+        if has_key {
+            names.push(format!("{}_key", self.name));
+            names.push(format!("{}_key_excl", self.name));
+        }
+        names
+    }
+
+    /// Create the indexes that fields declare with `@index(type: ..)`.
+    /// Unlike the attribute indexes we create by default, declared indexes
+    /// are always created right away, and `gin` indexes are created
+    /// regardless of `GRAPH_STORE_CREATE_GIN_INDEXES`
+    fn create_declared_indexes(&self, out: &mut String) -> fmt::Result {
+        for column in &self.columns {
+            self.create_declared_index(column, out)?;
+        }
+        Ok(())
+    }
+
+    fn create_declared_index(&self, column: &Column, out: &mut String) -> fmt::Result {
+        let Some(index_type) = self.declared_index(column) else {
+            return Ok(());
+        };
+        let (_, index_expr) = Self::calculate_index_method_and_expression(column);
+        writeln!(
+            out,
+            "create index {name}\n    on {qname} using {index_type}({index_expr});",
+            name = self.declared_index_name(column, index_type),
+            qname = self.qualified_name,
+        )
+    }
+
+    /// Generate the DDL that adds the columns in `self` that `base` does
+    /// not have to the table for `base`, together with their indexes. The
+    /// table for `base` must already have been moved into our namespace
+    fn add_columns_ddl(&self, base: &Table, out: &mut String) -> fmt::Result {
+        for column in &self.columns {
+            if base.column(&column.name).is_some() {
+                continue;
+            }
+            let mut column_ddl = String::new();
+            column.as_ddl(&mut column_ddl)?;
+            writeln!(
+                out,
+                "alter table {qname} add column {column_ddl};",
+                qname = self.qualified_name
+            )?;
+            if let Some(column_index) = self
+                .columns_to_index()
+                .position(|col| col.name == column.name)
+            {
+                self.create_attribute_index(column_index, column, out)?;
+            }
+            self.create_declared_index(column, out)?;
+        }
+        Ok(())
+    }
+
     /// Generate the DDL for one table, i.e. one `create table` statement
     /// and all `create index` statements for the table's columns
     ///
@@ -403,12 +633,20 @@ impl Table {
                     false,
                 )
                 .map_err(|_| fmt::Error)?;
-            for (_, sql) in arr {
+            // Indexes declared in the schema are created below, and we
+            // must not create them twice
+            let declared = self.declared_index_names();
+            for (name, sql) in arr {
+                if name.map(|name| declared.contains(&name)).unwrap_or(false) {
+                    continue;
+                }
                 writeln!(out, "{};", sql).expect("properly formated index statements")
             }
         } else {
             self.create_attribute_indexes(out)?;
         }
+        self.create_declared_indexes(out)?;
+        self.create_key_indexes(out)?;
         self.create_aggregate_indexes(schema, out)
     }
 
@@ -2,6 +2,8 @@ use index::CreateIndex;
 use itertools::Itertools;
 use pretty_assertions::assert_eq;
 
+use graph::components::store::DeploymentSchemaVersion;
+
 use super::*;
 
 use crate::{deployment_store::generate_index_creation_sql, layout_for_tests::make_dummy_site};
@@ -246,6 +248,35 @@ fn generate_ddl() {
     let layout = test_layout(LIFETIME_GQL);
     let sql = layout.as_ddl(None).expect("Failed to generate DDL");
     check_eqv(LIFETIME_SQL, &sql);
+
+    let layout = test_layout(KEY_GQL);
+    let sql = layout.as_ddl(None).expect("Failed to generate DDL");
+    check_eqv(KEY_SQL, &sql);
+
+    let layout = test_layout(INDEX_GQL);
+    let sql = layout.as_ddl(None).expect("Failed to generate DDL");
+    check_eqv(INDEX_SQL, &sql);
+}
+
+#[test]
+fn long_declared_index_names() {
+    const GQL: &str = r#"
+    type Event @entity(immutable: true) {
+        id: Bytes!
+        priceOfTheTokenThatWasTransferredInTheEventBeforeFees: BigInt! @index
+        priceOfTheTokenThatWasTransferredInTheEventAfterFees: BigInt! @index
+    }"#;
+
+    let layout = test_layout(GQL);
+    let sql = layout.as_ddl(None).expect("Failed to generate DDL");
+    let names: Vec<_> = sql
+        .lines()
+        .filter_map(|line| line.strip_prefix("create index "))
+        .map(|name| name.trim())
+        .collect();
+    assert_eq!(3, names.len(), "unexpected indexes in {sql}");
+    assert!(names.iter().all(|name| name.len() <= 63), "{names:?}");
+    assert!(names.iter().all_unique(), "{names:?}");
 }
 
 #[test]
@@ -350,6 +381,119 @@ fn can_copy_from() {
         ],
         dest.can_copy_from(&source)
     );
+
+    // We allow adding values to enums, but not removing them
+    let source = test_layout("enum Color { red } type Scalar @entity { id: ID, color: Color }");
+    let dest = test_layout("enum Color { blue, red } type Scalar @entity { id: ID, color: Color }");
+    assert!(dest.can_copy_from(&source).is_empty());
+    let errors = source.can_copy_from(&dest);
+    assert_eq!(1, errors.len());
+    assert!(errors[0].contains("contains the values blue that are not present in"));
+    // We can not change an enum field to an array of enums
+    let dest =
+        test_layout("enum Color { blue, red } type Scalar @entity { id: ID, color: [Color] }");
+    assert_eq!(
+        vec![
+            "The attribute Scalar.color has type [Color], but \
+                 its type in the source is Color"
+        ],
+        dest.can_copy_from(&source)
+    );
+}
+
+/// A layout for a deployment that can serve as the base for an in-place
+/// graft, except that all layouts in these tests share the same namespace
+fn in_place_layout(gql: &str) -> Layout {
+    let layout = test_layout(gql);
+    let mut site = make_dummy_site(
+        layout.site.deployment.clone(),
+        layout.site.namespace.clone(),
+        layout.site.network.clone(),
+    );
+    site.schema_version = DeploymentSchemaVersion::LATEST;
+    let site = Arc::new(site);
+    let catalog = Catalog::for_tests(site.clone(), BTreeSet::new()).unwrap();
+    Layout::new(site, &layout.input_schema, catalog).unwrap()
+}
+
+#[test]
+fn can_alter_in_place() {
+    let base = in_place_layout(ALTER_BASE_GQL);
+    assert!(base.can_alter_in_place(&base).is_empty());
+
+    // Adding nullable fields, entity types and enum values is fine
+    let dest = in_place_layout(ALTER_DEST_GQL);
+    assert!(dest.can_alter_in_place(&base).is_empty());
+
+    // Anything else is not
+    let dest = in_place_layout(
+        "enum Color { red }
+         type Thing @entity(immutable: true) { id: ID!, size: Int! }",
+    );
+    assert_eq!(
+        vec![
+            "the enum Color does not contain the values yellow anymore",
+            "the entity type Thing changed whether it is immutable",
+            "the attribute Thing.name was removed",
+            "the attribute Thing.size is non-nullable, but there is no such attribute in the base",
+        ],
+        dest.can_alter_in_place(&base)
+    );
+    let dest = in_place_layout(
+        "enum Color { red, yellow }
+         type Thing @entity { id: ID!, name: String }",
+    );
+    assert_eq!(
+        vec!["the attribute Thing.name has type String, but its type in the base is String!"],
+        dest.can_alter_in_place(&base)
+    );
+    let dest = in_place_layout("type Other @entity { id: ID! }");
+    assert_eq!(
+        vec!["the entity type Thing was removed"],
+        dest.can_alter_in_place(&base)
+    );
+
+    // References can change to an interface that the type they referenced
+    // implements, but not to other types
+    let base = in_place_layout(
+        "type Owner @entity { id: ID! }
+         type Thing @entity { id: ID!, owner: Owner! }",
+    );
+    let dest = in_place_layout(
+        "interface Holder { id: ID! }
+         type Owner implements Holder @entity { id: ID! }
+         type Thing @entity { id: ID!, owner: Holder! }",
+    );
+    assert!(dest.can_alter_in_place(&base).is_empty());
+    let dest = in_place_layout(
+        "type Owner @entity { id: ID! }
+         type Other @entity { id: ID! }
+         type Thing @entity { id: ID!, owner: Other! }",
+    );
+    assert_eq!(
+        vec![
+            "the attribute Thing.owner has type Other!, but its type in the base is Owner!; \
+             references can only change to an interface that Owner implements"
+        ],
+        dest.can_alter_in_place(&base)
+    );
+
+    // Deployments with an old storage scheme can not be altered in place
+    let base = test_layout(ALTER_BASE_GQL);
+    let dest = in_place_layout(ALTER_BASE_GQL);
+    assert_eq!(
+        vec!["the base uses an old storage scheme for data sources"],
+        dest.can_alter_in_place(&base)
+    );
+}
+
+#[test]
+fn additive_ddl() {
+    let base = in_place_layout(ALTER_BASE_GQL);
+    let dest = in_place_layout(ALTER_DEST_GQL);
+
+    check_eqv("", &base.as_additive_ddl(&base).unwrap());
+    check_eqv(ALTER_SQL, &dest.as_additive_ddl(&base).unwrap());
 }
 
 const THING_GQL: &str = r#"
@@ -1109,3 +1253,147 @@ on "sgd0815"."stats_3_day" using btree("volume");
 create index stats_3_day_dims
 on "sgd0815"."stats_3_day"(group_2, group_1, timestamp);
 "#;
+
+const KEY_GQL: &str = r#"
+type Swap @entity(immutable: true, key: ["pair", "blockNumber"]) {
+    id: Bytes!
+    pair: Bytes!
+    blockNumber: Int8!
+}
+
+type Holding @entity(key: ["owner", "pool"]) {
+    id: ID!
+    owner: Bytes!
+    pool: String!
+    liquidity: BigInt!
+}
+"#;
+
+const KEY_SQL: &str = r#"
+create table "sgd0815"."swap" (
+    vid                  bigserial primary key,
+    block$               int not null,
+    "id"                 bytea not null,
+    "pair"               bytea not null,
+    "block_number"       int8 not null,
+    unique(id)
+);
+create index swap_block
+    on "sgd0815"."swap"(block$);
+create index attr_0_0_swap_pair
+    on "sgd0815"."swap" using btree("pair");
+create index attr_0_1_swap_block_number
+    on "sgd0815"."swap" using btree("block_number");
+create unique index swap_key
+    on "sgd0815"."swap"("pair", "block_number");
+
+create table "sgd0815"."holding" (
+    vid                  bigserial primary key,
+    block_range          int4range not null,
+    "id"                 text not null,
+    "owner"              bytea not null,
+    "pool"               text not null,
+    "liquidity"          numeric not null
+);
+alter table "sgd0815"."holding"
+  add constraint holding_id_block_range_excl exclude using gist (id with =, block_range with &&);
+create index brin_holding
+    on "sgd0815"."holding"
+ using brin(lower(block_range) int4_minmax_ops, coalesce(upper(block_range), 2147483647) int4_minmax_ops, vid int8_minmax_ops);
+create index holding_block_range_closed
+    on "sgd0815"."holding"(coalesce(upper(block_range), 2147483647))
+ where coalesce(upper(block_range), 2147483647) < 2147483647;
+create index attr_1_0_holding_id
+    on "sgd0815"."holding" using btree("id");
+create index attr_1_1_holding_owner
+    on "sgd0815"."holding" using btree("owner");
+create index attr_1_2_holding_pool
+    on "sgd0815"."holding" using btree("pool");
+create index attr_1_3_holding_liquidity
+    on "sgd0815"."holding" using btree("liquidity");
+create index holding_key
+    on "sgd0815"."holding"("owner", "pool");
+alter table "sgd0815"."holding"
+    add constraint holding_key_excl exclude using gist ("owner" with =, "pool" with =, block_range with &&);
+"#;
+
+const INDEX_GQL: &str = r#"
+type Event @entity(immutable: true) {
+    id: Bytes!
+    blockNumber: Int8! @index(type: brin)
+    name: String! @index
+    topics: [Bytes!]! @index(type: "gin")
+    value: BigInt!
+}
+"#;
+
+const INDEX_SQL: &str = r#"
+create table "sgd0815"."event" (
+    vid                  bigserial primary key,
+    block$               int not null,
+    "id"                 bytea not null,
+    "block_number"       int8 not null,
+    "name"               text not null,
+    "topics"             bytea[] not null,
+    "value"              numeric not null,
+    unique(id)
+);
+create index event_block
+    on "sgd0815"."event"(block$);
+create index attr_0_3_event_value
+    on "sgd0815"."event" using btree("value");
+create index event_block_number_brin
+    on "sgd0815"."event" using brin("block_number");
+create index event_name_btree
+    on "sgd0815"."event" using btree(left("name", 256));
+create index event_topics_gin
+    on "sgd0815"."event" using gin("topics");
+"#;
+
+const ALTER_BASE_GQL: &str = r#"
+    enum Color { red, yellow }
+
+    type Thing @entity {
+        id: ID!
+        name: String!
+    }
+"#;
+
+const ALTER_DEST_GQL: &str = r#"
+    enum Color { blue, red, white, yellow }
+
+    type Thing @entity {
+        id: ID!
+        name: String!
+        color: Color
+        nick: String
+    }
+
+    type Tag @entity(immutable: true) {
+        id: ID!
+        label: String
+    }
+"#;
+
+const ALTER_SQL: &str = r#"
+alter type sgd0815."color" add value 'blue' before 'red';
+alter type sgd0815."color" add value 'white' before 'yellow';
+alter table "sgd0815"."thing" add column "color" "sgd0815"."color";
+create index attr_0_2_thing_color
+    on "sgd0815"."thing" using btree("color");
+alter table "sgd0815"."thing" add column "nick" text;
+create index attr_0_3_thing_nick
+    on "sgd0815"."thing" using btree(left("nick", 256));
+
+create table "sgd0815"."tag" (
+    vid                  bigserial primary key,
+    block$                int not null,
+    "id"                 text not null,
+    "label"              text,
+    unique(id)
+);
+create index tag_block
+    on "sgd0815"."tag"(block$);
+create index attr_1_0_tag_label
+    on "sgd0815"."tag" using btree(left("label", 256));
+"#;
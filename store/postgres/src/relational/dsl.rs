@@ -332,6 +332,24 @@ impl<'a> Table<'a> {
             }
         }
 
+        /// Diesel has no support for `point`, and we read points as text
+        fn add_point_field<'b>(
+            select: &mut SelectClause<'b>,
+            table: &'b Table<'b>,
+            column: &'b RelColumn,
+        ) {
+            let name = format!("{}.{}", table.alias.as_str(), &column.name);
+
+            match (column.is_list(), column.is_nullable()) {
+                (true, true) => {
+                    select.add_field(sql::<Nullable<Array<Text>>>(&format!("{name}::text[]")))
+                }
+                (true, false) => select.add_field(sql::<Array<Text>>(&format!("{name}::text[]"))),
+                (false, true) => select.add_field(sql::<Nullable<Text>>(&format!("{name}::text"))),
+                (false, false) => select.add_field(sql::<Text>(&format!("{name}::text"))),
+            }
+        }
+
         let mut selection = DynamicSelectClause::new();
         for column in columns {
             if column.name == TYPENAME_COL.name {
@@ -355,6 +373,7 @@ impl<'a> Table<'a> {
                     // meant to be directly queried or returned
                 }
                 ColumnType::Enum(_) => add_enum_field(&mut selection, self, column),
+                ColumnType::GeoPoint => add_point_field(&mut selection, self, column),
             };
         }
         <Self as SelectDsl<SelectClause<'a>>>::select(*self, selection).into_boxed()
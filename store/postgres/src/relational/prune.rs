@@ -1,9 +1,13 @@
-use std::{fmt::Write, sync::Arc, time::Instant};
+use std::{
+    fmt::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use diesel::{
     connection::SimpleConnection,
     sql_query,
-    sql_types::{BigInt, Integer},
+    sql_types::{BigInt, Bool, Integer, Nullable},
     Connection, PgConnection, RunQueryDsl,
 };
 use graph::{
@@ -18,6 +22,7 @@ use graph::{
 use itertools::Itertools;
 
 use crate::{
+    block_range::BLOCK_COLUMN,
     catalog,
     copy::AdaptiveBatchSize,
     deployment,
@@ -61,6 +66,111 @@ impl Table {
         .get_result::<VidRange>(conn)?;
         Ok((min_vid, max_vid))
     }
+
+    /// The retention that the entity type for this table declares
+    fn retention(&self) -> Option<Duration> {
+        self.object
+            .object_type()
+            .ok()
+            .and_then(|obj_type| obj_type.retention)
+    }
+
+    /// Delete all entity versions whose range was closed before
+    /// `earliest_block` in batches
+    fn delete_versions_before(
+        &self,
+        conn: &mut PgConnection,
+        reporter: &mut dyn PruneReporter,
+        earliest_block: BlockNumber,
+    ) -> Result<(), StoreError> {
+        let (min_vid, max_vid) = self.vid_range(conn, 0, earliest_block)?;
+        let mut batch_size = AdaptiveBatchSize::new(self);
+        let mut next_vid = min_vid;
+        while next_vid <= max_vid {
+            let start = Instant::now();
+            let rows = sql_query(format!(
+                "/* controller=prune,phase=delete,start_vid={next_vid},batch_size={batch_size} */ \
+                 delete from {qname} \
+                              where coalesce(upper(block_range), 2147483647) <= $1 \
+                                and vid >= $2 and vid < $2 + $3",
+                qname = self.qualified_name,
+                batch_size = batch_size.size
+            ))
+            .bind::<Integer, _>(earliest_block)
+            .bind::<BigInt, _>(next_vid)
+            .bind::<BigInt, _>(&batch_size)
+            .execute(conn)?;
+
+            next_vid += batch_size.size;
+
+            batch_size.adapt(start.elapsed());
+
+            reporter.prune_batch(
+                self.name.as_str(),
+                rows as usize,
+                PrunePhase::Delete,
+                next_vid > max_vid,
+            );
+        }
+        Ok(())
+    }
+
+    /// Delete all entities of an immutable table that were created before
+    /// `earliest_block` in batches
+    fn delete_entities_before(
+        &self,
+        conn: &mut PgConnection,
+        reporter: &mut dyn PruneReporter,
+        earliest_block: BlockNumber,
+    ) -> Result<(), StoreError> {
+        #[derive(QueryableByName)]
+        struct VidRange {
+            #[diesel(sql_type = BigInt)]
+            min_vid: i64,
+            #[diesel(sql_type = BigInt)]
+            max_vid: i64,
+        }
+
+        let VidRange { min_vid, max_vid } = sql_query(format!(
+            "/* controller=prune,phase=retention,last={earliest_block} */ \
+             select coalesce(min(vid), 0) as min_vid, \
+                    coalesce(max(vid), -1) as max_vid from {src} \
+              where {BLOCK_COLUMN} < $1",
+            src = self.qualified_name,
+        ))
+        .bind::<Integer, _>(earliest_block)
+        .get_result::<VidRange>(conn)?;
+
+        let mut batch_size = AdaptiveBatchSize::new(self);
+        let mut next_vid = min_vid;
+        while next_vid <= max_vid {
+            let start = Instant::now();
+            let rows = sql_query(format!(
+                "/* controller=prune,phase=retention,start_vid={next_vid},batch_size={batch_size} */ \
+                 delete from {qname} \
+                  where {BLOCK_COLUMN} < $1 \
+                    and vid >= $2 and vid < $2 + $3",
+                qname = self.qualified_name,
+                batch_size = batch_size.size
+            ))
+            .bind::<Integer, _>(earliest_block)
+            .bind::<BigInt, _>(next_vid)
+            .bind::<BigInt, _>(&batch_size)
+            .execute(conn)?;
+
+            next_vid += batch_size.size;
+
+            batch_size.adapt(start.elapsed());
+
+            reporter.prune_batch(
+                self.name.as_str(),
+                rows as usize,
+                PrunePhase::Delete,
+                next_vid > max_vid,
+            );
+        }
+        Ok(())
+    }
 }
 
 /// Utility to copy relevant data out of a source table and into a new
@@ -458,35 +568,7 @@ impl Layout {
                 PruningStrategy::Delete => {
                     // Delete all entity versions whose range was closed
                     // before `req.earliest_block`
-                    let (min_vid, max_vid) = table.vid_range(conn, 0, req.earliest_block)?;
-                    let mut batch_size = AdaptiveBatchSize::new(&table);
-                    let mut next_vid = min_vid;
-                    while next_vid <= max_vid {
-                        let start = Instant::now();
-                        let rows = sql_query(format!(
-                            "/* controller=prune,phase=delete,start_vid={next_vid},batch_size={batch_size} */ \
-                             delete from {qname} \
-                                          where coalesce(upper(block_range), 2147483647) <= $1 \
-                                            and vid >= $2 and vid < $2 + $3",
-                            qname = table.qualified_name,
-                            batch_size = batch_size.size
-                        ))
-                        .bind::<Integer, _>(req.earliest_block)
-                        .bind::<BigInt, _>(next_vid)
-                        .bind::<BigInt, _>(&batch_size)
-                        .execute(conn)?;
-
-                        next_vid += batch_size.size;
-
-                        batch_size.adapt(start.elapsed());
-
-                        reporter.prune_batch(
-                            table.name.as_str(),
-                            rows as usize,
-                            PrunePhase::Delete,
-                            next_vid > max_vid,
-                        );
-                    }
+                    table.delete_versions_before(conn, reporter, req.earliest_block)?;
                 }
             }
             reporter.finish_table(table.name.as_str());
@@ -508,4 +590,111 @@ impl Layout {
 
         Ok(())
     }
+
+    /// Return the first block whose block time is no more than `retention`
+    /// before the time of `final_block`, using the block times recorded in
+    /// the PoI table. Since only block times up to `final_block` are
+    /// considered, the result only depends on `final_block` and not on
+    /// when we compute it. Return `None` if that can not be determined,
+    /// e.g., because the deployment has no PoI table or because the oldest
+    /// block time that is still recorded is within `retention`
+    fn retention_block(
+        &self,
+        conn: &mut PgConnection,
+        final_block: BlockNumber,
+        retention: Duration,
+    ) -> Result<Option<BlockNumber>, StoreError> {
+        #[derive(QueryableByName)]
+        struct RetentionBlock {
+            #[diesel(sql_type = Nullable<Integer>)]
+            block: Option<BlockNumber>,
+            #[diesel(sql_type = Nullable<Bool>)]
+            has_older: Option<bool>,
+        }
+
+        let Some(poi) = self.tables.get(&self.input_schema.poi_type()) else {
+            return Ok(None);
+        };
+        let Ok(block_time) = poi.column_for_field(self.input_schema.poi_block_time().as_str())
+        else {
+            return Ok(None);
+        };
+
+        let RetentionBlock { block, has_older } = sql_query(format!(
+            "/* controller=prune,phase=retention */ \
+             with head as (select max(\"{block_time}\") as time from {poi} \
+                            where lower(block_range) <= $2) \
+             select min(lower(p.block_range)) filter (where p.\"{block_time}\" >= h.time - $1) as block, \
+                    bool_or(p.\"{block_time}\" < h.time - $1) as has_older \
+               from {poi} p, head h \
+              where lower(p.block_range) <= $2",
+            block_time = block_time.name,
+            poi = poi.qualified_name,
+        ))
+        .bind::<BigInt, _>(retention.as_secs() as i64)
+        .bind::<Integer, _>(final_block)
+        .get_result::<RetentionBlock>(conn)?;
+
+        Ok(block.filter(|_| has_older.unwrap_or(false)))
+    }
+
+    /// Remove data for entity types that declare a `retention` once it is
+    /// older than that. For mutable types, only versions that were closed
+    /// before the retention window are removed so that none of the data
+    /// that mappings can read changes. Entities of immutable types never
+    /// stop being current, so they are removed once they were created
+    /// before the retention window and before `earliest_block`, i.e., when
+    /// time-travel queries can not reach the block in which they were
+    /// created anymore. The retention window is measured backwards from the time of
+    /// `final_block` rather than the current time so that what is removed
+    /// only depends on block numbers, and so that deployments that are
+    /// still syncing keep the data they will need once they are synced.
+    ///
+    /// Nothing at or after `final_block` is removed so that pruning can't
+    /// conflict with reverts, and versions of mutable entities before
+    /// `earliest_block` are left to the normal pruning logic
+    pub fn prune_by_retention(
+        &self,
+        reporter: &mut dyn PruneReporter,
+        conn: &mut PgConnection,
+        earliest_block: BlockNumber,
+        final_block: BlockNumber,
+        cancel: &CancelHandle,
+    ) -> Result<(), CancelableError<StoreError>> {
+        let mut tables: Vec<_> = self
+            .tables
+            .values()
+            .filter_map(|table| table.retention().map(|retention| (table, retention)))
+            .collect();
+        tables.sort_by(|(a, _), (b, _)| a.name.as_str().cmp(b.name.as_str()));
+
+        let mut pruned = Vec::new();
+        for (table, retention) in tables {
+            let Some(block) = self.retention_block(conn, final_block, retention)? else {
+                continue;
+            };
+            let block = block.min(final_block);
+            if table.immutable {
+                let block = block.min(earliest_block);
+                reporter.start_table(table.name.as_str());
+                table.delete_entities_before(conn, reporter, block)?;
+            } else {
+                if block <= earliest_block {
+                    continue;
+                }
+                reporter.start_table(table.name.as_str());
+                table.delete_versions_before(conn, reporter, block)?;
+                catalog::set_last_pruned_block(conn, &self.site, &table.name, block)?;
+            }
+            reporter.finish_table(table.name.as_str());
+            pruned.push(table);
+            cancel.check_cancel()?;
+        }
+
+        self.analyze_tables(conn, reporter, pruned, cancel)?;
+
+        reporter.finish();
+
+        Ok(())
+    }
 }
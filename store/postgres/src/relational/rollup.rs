@@ -145,6 +145,8 @@ pub(crate) struct Agg<'a> {
     aggregate: &'a Aggregate,
     src_columns: Vec<&'a str>,
     expr: String,
+    /// The rewritten weight expression for `weightedAvg`
+    weight: Option<String>,
     agg_column: &'a Column,
 }
 
@@ -154,12 +156,23 @@ impl<'a> Agg<'a> {
         src_table: &'a Table,
         agg_table: &'a Table,
     ) -> Result<Self, StoreError> {
-        let (expr, src_columns) = rewrite(src_table, &aggregate.arg)?;
+        let (expr, mut src_columns) = rewrite(src_table, &aggregate.arg)?;
+        let weight = match &aggregate.weight {
+            Some(weight) => {
+                let (weight, weight_columns) = rewrite(src_table, weight)?;
+                src_columns.extend(weight_columns);
+                src_columns.sort();
+                src_columns.dedup();
+                Some(weight)
+            }
+            None => None,
+        };
         let agg_column = agg_table.column_for_field(&aggregate.name)?;
         Ok(Self {
             aggregate,
             src_columns,
             expr,
+            weight,
             agg_column,
         })
     }
@@ -180,6 +193,26 @@ impl<'a> Agg<'a> {
                 write!(w, "arg_max_{}(({}, {time}))", sql_type, src)?
             }
             Count => write!(w, "count(*)")?,
+            Avg => write!(w, "avg({})", src)?,
+            // The values in a bucket are all the values there are for
+            // that period, not a sample of them
+            Stddev => write!(w, "stddev_pop({})", src)?,
+            // Use `percentile_disc` so that the percentile is one of the
+            // values and has their type
+            Percentile => write!(
+                w,
+                "percentile_disc({}) within group (order by {})",
+                self.aggregate.percentile.unwrap_or(0.5),
+                src
+            )?,
+            // When the weights add up to 0, fall back to the plain average
+            WeightedAvg => {
+                let weight = self.weight.as_deref().unwrap_or("1");
+                write!(
+                    w,
+                    "coalesce(sum(({src}) * ({weight})) / nullif(sum({weight}), 0), avg({src}))"
+                )?
+            }
         }
         write!(w, " as \"{}\"", self.agg_column.name)
     }
@@ -207,6 +240,12 @@ impl<'a> Agg<'a> {
                 return self.aggregate_over(&name, time, w);
             }
             Count => write!(w, "sum(\"{}\")", self.agg_column.name)?,
+            Avg | Stddev | Percentile | WeightedAvg => {
+                // These can not be cumulative and the previous value is
+                // always `null`; we just need to pick the value from the
+                // current bucket
+                write!(w, "max(\"{}\")", self.agg_column.name)?
+            }
         }
         write!(w, " as \"{}\"", self.agg_column.name)
     }
@@ -624,6 +663,16 @@ mod tests {
         timestamp: Timestamp!
         count: Int8! @aggregate(fn: "count")
       }
+
+      type Statistics @aggregation(intervals: ["day"], source: "Data") {
+        id: Int8!
+        timestamp: Timestamp!
+        avg: BigDecimal! @aggregate(fn: "avg", arg: "price")
+        stddev: BigDecimal! @aggregate(fn: "stddev", arg: "price")
+        median: Int! @aggregate(fn: "percentile", arg: "amount", percentile: 0.5)
+        p95: Int! @aggregate(fn: "percentile", arg: "amount", percentile: 0.95)
+        vwap: BigDecimal! @aggregate(fn: "weightedAvg", arg: "price", weight: "amount")
+      }
       "#;
 
         const STATS_HOUR_SQL: &str = r#"\
@@ -704,6 +753,20 @@ mod tests {
              order by "sgd007"."data".timestamp) data \
         group by timestamp"#;
 
+        const STATISTICS_SQL: &str = r#"\
+        insert into "sgd007"."statistics_day"(id, timestamp, block$, "avg", "stddev", "median", "p95", "vwap") \
+        select max(id) as id, timestamp, $3, \
+               avg("price") as "avg", \
+               stddev_pop("price") as "stddev", \
+               percentile_disc(0.5) within group (order by "amount") as "median", \
+               percentile_disc(0.95) within group (order by "amount") as "p95", \
+               coalesce(sum(("price") * ("amount")) / nullif(sum("amount"), 0), avg("price")) as "vwap" \
+          from (select id, date_bin('86400s', timestamp, 'epoch'::timestamptz) as timestamp, "amount", "price" \
+                  from "sgd007"."data" \
+                 where "sgd007"."data".timestamp >= $1 and "sgd007"."data".timestamp < $2 \
+                 order by "sgd007"."data".timestamp) data \
+         group by timestamp"#;
+
         #[track_caller]
         fn rollup_for<'a>(layout: &'a Layout, table_name: &str) -> &'a Rollup {
             layout
@@ -719,7 +782,7 @@ mod tests {
         let site = Arc::new(make_dummy_site(hash, nsp, "rollup".to_string()));
         let catalog = Catalog::for_tests(site.clone(), BTreeSet::new()).unwrap();
         let layout = Layout::new(site, &schema, catalog).unwrap();
-        assert_eq!(6, layout.rollups.len());
+        assert_eq!(7, layout.rollups.len());
 
         // Intervals are non-decreasing
         assert!(layout.rollups[0].interval <= layout.rollups[1].interval);
@@ -741,5 +804,8 @@ mod tests {
 
         let count_only = rollup_for(&layout, "count_only_day");
         check_eqv(COUNT_ONLY_SQL, &count_only.insert_sql);
+
+        let statistics = rollup_for(&layout, "statistics_day");
+        check_eqv(STATISTICS_SQL, &statistics.insert_sql);
     }
 }
@@ -0,0 +1,569 @@
+//! Validation and rewriting of queries for the SQL endpoint.
+//!
+//! Users write queries against the entity tables of a deployment using
+//! the table and column names from the database schema, e.g. `select
+//! count(*) from token`. Only a restricted subset of `select` statements
+//! is allowed: queries can not name tables outside of the deployment, call
+//! functions that are not whitelisted, or use constructs that we do not
+//! know to be harmless.
+//!
+//! Every reference to an entity table is replaced with a subquery that
+//! only returns the entity versions that were current at the query block,
+//! so that queries see the same data that a GraphQL query for that block
+//! would see
+use graph::prelude::{BlockNumber, QueryExecutionError};
+use graph::sqlparser::ast as p;
+use graph::sqlparser::dialect::PostgreSqlDialect;
+use graph::sqlparser::parser::Parser;
+use itertools::Itertools;
+
+use graph::data::subgraph::schema::POI_TABLE;
+
+use crate::block_range::{BLOCK_COLUMN, BLOCK_RANGE_COLUMN};
+
+use super::{Layout, Table};
+
+/// Functions that queries may call. Besides aggregates, these are
+/// functions that can not cause side effects or access data outside of
+/// the tables they are given
+const FN_WHITELIST: [&str; 52] = [
+    // Aggregates
+    "avg", "count", "max", "min", "sum", "bool_and", "bool_or", "every", "stddev",
+    "stddev_pop", "stddev_samp", "variance", "var_pop", "var_samp", "array_agg", "string_agg",
+    // Math
+    "abs", "ceil", "ceiling", "div", "exp", "floor", "gcd", "lcm", "ln", "log", "mod", "power",
+    "round", "sign", "sqrt", "trunc",
+    // Conditionals
+    "coalesce", "nullif", "greatest", "least",
+    // Strings and bytes
+    "concat", "encode", "decode", "left", "length", "lower", "replace", "right", "starts_with",
+    "substr", "upper",
+    // Arrays
+    "array_length", "cardinality",
+    // Dates and times
+    "date_part", "date_trunc", "to_timestamp",
+];
+
+fn invalid(msg: impl Into<String>) -> QueryExecutionError {
+    QueryExecutionError::InvalidSqlQuery(msg.into())
+}
+
+/// Parse and validate `sql` and return the SQL for a query that runs it
+/// against the tables in `layout` as of `block`
+pub(crate) fn rewrite(
+    layout: &Layout,
+    sql: &str,
+    block: BlockNumber,
+) -> Result<String, QueryExecutionError> {
+    let dialect = PostgreSqlDialect {};
+    let mut stmts = Parser::parse_sql(&dialect, sql).map_err(|e| invalid(e.to_string()))?;
+    if stmts.len() != 1 {
+        return Err(invalid("the query must consist of exactly one statement"));
+    }
+    let mut query = match stmts.pop().unwrap() {
+        p::Statement::Query(query) => query,
+        stmt => {
+            return Err(invalid(format!(
+                "only SELECT statements are allowed but got `{stmt}`"
+            )))
+        }
+    };
+
+    let mut rewriter = Rewriter {
+        layout,
+        block,
+        ctes: Vec::new(),
+    };
+    rewriter.visit_query(&mut query)?;
+    Ok(query.to_string())
+}
+
+/// The name of an identifier the way Postgres sees it: unquoted
+/// identifiers are case-insensitive
+fn normalize(ident: &p::Ident) -> String {
+    match ident.quote_style {
+        Some(_) => ident.value.clone(),
+        None => ident.value.to_lowercase(),
+    }
+}
+
+struct Rewriter<'a> {
+    layout: &'a Layout,
+    block: BlockNumber,
+    /// The names of the common table expressions that are visible at the
+    /// current point in the query. References to them must not be
+    /// rewritten
+    ctes: Vec<String>,
+}
+
+impl<'a> Rewriter<'a> {
+    fn table(&self, name: &str) -> Result<&'a Table, QueryExecutionError> {
+        self.layout
+            .tables
+            .values()
+            .find(|table| table.name.as_str() == name && table.name.as_str() != POI_TABLE)
+            .map(|table| table.as_ref())
+            .ok_or_else(|| invalid(format!("unknown table `{name}`")))
+    }
+
+    /// A query that returns the versions of the entities in `table` that
+    /// are visible at `self.block`
+    fn table_query(&self, table: &Table) -> Result<Box<p::Query>, QueryExecutionError> {
+        let columns = table.columns.iter().map(|col| col.name.quoted()).join(", ");
+        let filter = if table.immutable {
+            format!("\"{}\" <= {}", BLOCK_COLUMN, self.block)
+        } else {
+            format!("\"{}\" @> {}", BLOCK_RANGE_COLUMN, self.block)
+        };
+        let sql = format!(
+            "select {columns} from {} where {filter}",
+            table.qualified_name
+        );
+        match Parser::parse_sql(&PostgreSqlDialect {}, &sql)
+            .map_err(|e| invalid(e.to_string()))?
+            .pop()
+        {
+            Some(p::Statement::Query(query)) => Ok(query),
+            _ => Err(QueryExecutionError::ConstraintViolation(format!(
+                "could not generate query for table {}",
+                table.qualified_name
+            ))),
+        }
+    }
+
+    fn visit_query(&mut self, query: &mut p::Query) -> Result<(), QueryExecutionError> {
+        let depth = self.ctes.len();
+
+        if let Some(with) = &mut query.with {
+            if with.recursive {
+                return Err(invalid("recursive queries are not supported"));
+            }
+            for cte in &mut with.cte_tables {
+                self.visit_query(&mut cte.query)?;
+                self.ctes.push(normalize(&cte.alias.name));
+            }
+        }
+        if !query.locks.is_empty() {
+            return Err(invalid("locking clauses are not supported"));
+        }
+        self.visit_set_expr(&mut query.body)?;
+        for order_by in &mut query.order_by {
+            self.visit_expr(&mut order_by.expr)?;
+        }
+        if let Some(limit) = &mut query.limit {
+            self.visit_expr(limit)?;
+        }
+        if let Some(offset) = &mut query.offset {
+            self.visit_expr(&mut offset.value)?;
+        }
+        if let Some(quantity) = query.fetch.as_mut().and_then(|fetch| fetch.quantity.as_mut()) {
+            self.visit_expr(quantity)?;
+        }
+
+        self.ctes.truncate(depth);
+        Ok(())
+    }
+
+    fn visit_set_expr(&mut self, expr: &mut p::SetExpr) -> Result<(), QueryExecutionError> {
+        match expr {
+            p::SetExpr::Select(select) => self.visit_select(select),
+            p::SetExpr::Query(query) => self.visit_query(query),
+            p::SetExpr::SetOperation { left, right, .. } => {
+                self.visit_set_expr(left)?;
+                self.visit_set_expr(right)
+            }
+            _ => Err(invalid(format!("`{expr}` is not supported"))),
+        }
+    }
+
+    fn visit_select(&mut self, select: &mut p::Select) -> Result<(), QueryExecutionError> {
+        if select.into.is_some() {
+            return Err(invalid("SELECT INTO is not supported"));
+        }
+        if !select.lateral_views.is_empty() {
+            return Err(invalid("LATERAL VIEW is not supported"));
+        }
+
+        for from in &mut select.from {
+            self.visit_table_with_joins(from)?;
+        }
+        if let Some(p::Distinct::On(exprs)) = &mut select.distinct {
+            for expr in exprs {
+                self.visit_expr(expr)?;
+            }
+        }
+        for item in &mut select.projection {
+            match item {
+                p::SelectItem::UnnamedExpr(expr) | p::SelectItem::ExprWithAlias { expr, .. } => {
+                    self.visit_expr(expr)?
+                }
+                p::SelectItem::QualifiedWildcard(_, _) | p::SelectItem::Wildcard(_) => {}
+            }
+        }
+        if let Some(selection) = &mut select.selection {
+            self.visit_expr(selection)?;
+        }
+        if let p::GroupByExpr::Expressions(exprs, ..) = &mut select.group_by {
+            for expr in exprs {
+                self.visit_expr(expr)?;
+            }
+        }
+        if let Some(having) = &mut select.having {
+            self.visit_expr(having)?;
+        }
+        if let Some(qualify) = &mut select.qualify {
+            self.visit_expr(qualify)?;
+        }
+        Ok(())
+    }
+
+    fn visit_table_with_joins(
+        &mut self,
+        from: &mut p::TableWithJoins,
+    ) -> Result<(), QueryExecutionError> {
+        self.visit_table_factor(&mut from.relation)?;
+        for join in &mut from.joins {
+            self.visit_table_factor(&mut join.relation)?;
+
+            use p::JoinOperator::*;
+            match &mut join.join_operator {
+                Inner(constraint)
+                | LeftOuter(constraint)
+                | RightOuter(constraint)
+                | FullOuter(constraint) => {
+                    if let p::JoinConstraint::On(expr) = constraint {
+                        self.visit_expr(expr)?;
+                    }
+                }
+                CrossJoin => {}
+                op => return Err(invalid(format!("join operator {op:?} is not supported"))),
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_table_factor(&mut self, factor: &mut p::TableFactor) -> Result<(), QueryExecutionError> {
+        let replacement = match factor {
+            p::TableFactor::Table {
+                name, alias, args, ..
+            } => {
+                if args.is_some() {
+                    return Err(invalid(format!("table function `{name}` is not supported")));
+                }
+                let [ident] = name.0.as_slice() else {
+                    return Err(invalid(format!(
+                        "table name `{name}` must not be qualified with a schema"
+                    )));
+                };
+                let table_name = normalize(ident);
+                if self.ctes.contains(&table_name) {
+                    return Ok(());
+                }
+                let table = self.table(&table_name)?;
+                let alias = alias.take().unwrap_or_else(|| p::TableAlias {
+                    name: ident.clone(),
+                    columns: vec![],
+                });
+                p::TableFactor::Derived {
+                    lateral: false,
+                    subquery: self.table_query(table)?,
+                    alias: Some(alias),
+                }
+            }
+            p::TableFactor::Derived { subquery, .. } => return self.visit_query(subquery),
+            p::TableFactor::NestedJoin {
+                table_with_joins, ..
+            } => return self.visit_table_with_joins(table_with_joins),
+            _ => return Err(invalid(format!("`{factor}` is not supported in FROM"))),
+        };
+        *factor = replacement;
+        Ok(())
+    }
+
+    fn visit_expr(&mut self, expr: &mut p::Expr) -> Result<(), QueryExecutionError> {
+        use p::Expr::*;
+
+        match expr {
+            Identifier(_) | CompoundIdentifier(_) | TypedString { .. } => Ok(()),
+            Value(p::Value::Placeholder(_)) => Err(invalid("query parameters are not supported")),
+            Value(_) => Ok(()),
+            Nested(expr) | IsFalse(expr) | IsNotFalse(expr) | IsTrue(expr) | IsNotTrue(expr)
+            | IsNull(expr) | IsNotNull(expr) | IsUnknown(expr) | IsNotUnknown(expr) => {
+                self.visit_expr(expr)
+            }
+            IsDistinctFrom(expr1, expr2) | IsNotDistinctFrom(expr1, expr2) => {
+                self.visit_expr(expr1)?;
+                self.visit_expr(expr2)
+            }
+            BinaryOp { left, op, right } => {
+                check_binary_op(op)?;
+                self.visit_expr(left)?;
+                self.visit_expr(right)
+            }
+            UnaryOp { op, expr } => {
+                check_unary_op(op)?;
+                self.visit_expr(expr)
+            }
+            Cast { expr, .. } | Extract { expr, .. } | Ceil { expr, .. } | Floor { expr, .. } => {
+                self.visit_expr(expr)
+            }
+            Between {
+                expr, low, high, ..
+            } => {
+                self.visit_expr(expr)?;
+                self.visit_expr(low)?;
+                self.visit_expr(high)
+            }
+            Like { expr, pattern, .. } | ILike { expr, pattern, .. } => {
+                self.visit_expr(expr)?;
+                self.visit_expr(pattern)
+            }
+            InList { expr, list, .. } => {
+                self.visit_expr(expr)?;
+                for expr in list {
+                    self.visit_expr(expr)?;
+                }
+                Ok(())
+            }
+            InSubquery { expr, subquery, .. } => {
+                self.visit_expr(expr)?;
+                self.visit_query(subquery)
+            }
+            Exists { subquery, .. } => self.visit_query(subquery),
+            Subquery(query) => self.visit_query(query),
+            Position { expr, r#in } => {
+                self.visit_expr(expr)?;
+                self.visit_expr(r#in)
+            }
+            Substring {
+                expr,
+                substring_from,
+                substring_for,
+                ..
+            } => {
+                self.visit_expr(expr)?;
+                if let Some(from) = substring_from {
+                    self.visit_expr(from)?;
+                }
+                if let Some(len) = substring_for {
+                    self.visit_expr(len)?;
+                }
+                Ok(())
+            }
+            Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                if let Some(operand) = operand {
+                    self.visit_expr(operand)?;
+                }
+                for expr in conditions.iter_mut().chain(results.iter_mut()) {
+                    self.visit_expr(expr)?;
+                }
+                if let Some(else_result) = else_result {
+                    self.visit_expr(else_result)?;
+                }
+                Ok(())
+            }
+            Tuple(exprs) => {
+                for expr in exprs {
+                    self.visit_expr(expr)?;
+                }
+                Ok(())
+            }
+            Interval(interval) => self.visit_expr(&mut interval.value),
+            Function(func) => self.visit_func(func),
+            _ => Err(invalid(format!("the expression `{expr}` is not supported"))),
+        }
+    }
+
+    fn visit_func(&mut self, func: &mut p::Function) -> Result<(), QueryExecutionError> {
+        let p::Function {
+            name,
+            args: pargs,
+            filter,
+            null_treatment,
+            over,
+            within_group,
+        } = func;
+
+        if over.is_some() {
+            return Err(invalid(format!(
+                "call to {name} uses a window, which is not supported"
+            )));
+        }
+        if null_treatment.is_some() || !within_group.is_empty() {
+            return Err(invalid(format!("call to {name} uses an illegal feature")));
+        }
+        let [ident] = name.0.as_slice() else {
+            return Err(invalid(format!(
+                "function name {name} uses a qualified name with '.'"
+            )));
+        };
+        let whitelisted = match ident.quote_style {
+            Some(_) => FN_WHITELIST.contains(&ident.value.as_str()),
+            None => FN_WHITELIST
+                .iter()
+                .any(|fn_name| fn_name.eq_ignore_ascii_case(&ident.value)),
+        };
+        if !whitelisted {
+            return Err(invalid(format!("function {name} is not supported")));
+        }
+        if let Some(filter) = filter {
+            self.visit_expr(filter)?;
+        }
+
+        match pargs {
+            p::FunctionArguments::None => Ok(()),
+            p::FunctionArguments::Subquery(_) => Err(invalid(format!(
+                "call to {name} uses a subquery argument"
+            ))),
+            p::FunctionArguments::List(list) => {
+                if !list.clauses.is_empty() {
+                    return Err(invalid(format!("call to {name} uses a clause")));
+                }
+                for arg in &mut list.args {
+                    match arg {
+                        p::FunctionArg::Named { .. } => {
+                            return Err(invalid(format!("call to {name} uses a named argument")))
+                        }
+                        p::FunctionArg::Unnamed(p::FunctionArgExpr::Expr(expr)) => {
+                            self.visit_expr(expr)?
+                        }
+                        // Needed for `count(*)`
+                        p::FunctionArg::Unnamed(p::FunctionArgExpr::Wildcard) => {}
+                        p::FunctionArg::Unnamed(p::FunctionArgExpr::QualifiedWildcard(_)) => {
+                            return Err(invalid(format!(
+                                "call to {name} uses a qualified wildcard argument"
+                            )))
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn check_binary_op(op: &p::BinaryOperator) -> Result<(), QueryExecutionError> {
+    use p::BinaryOperator::*;
+    match op {
+        Plus | Minus | Multiply | Divide | Modulo | PGExp | Gt | Lt | GtEq | LtEq | Eq | NotEq
+        | And | Or | StringConcat | PGLikeMatch | PGILikeMatch | PGNotLikeMatch
+        | PGNotILikeMatch => Ok(()),
+        _ => Err(invalid(format!("binary operator {op} is not supported"))),
+    }
+}
+
+fn check_unary_op(op: &p::UnaryOperator) -> Result<(), QueryExecutionError> {
+    use p::UnaryOperator::*;
+    match op {
+        Plus | Minus | Not => Ok(()),
+        _ => Err(invalid(format!("unary operator {op} is not supported"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+
+    use graph::prelude::{DeploymentHash, QueryExecutionError};
+    use graph::schema::InputSchema;
+
+    use crate::layout_for_tests::{make_dummy_site, Namespace};
+    use crate::relational::{Catalog, Layout};
+
+    use super::rewrite;
+
+    const SCHEMA: &str = "
+        type Token @entity {
+            id: ID!,
+            symbol: String!,
+            decimals: Int!
+        }
+
+        type Transfer @entity(immutable: true) {
+            id: ID!,
+            token: Token!,
+            amount: BigInt!
+        }";
+
+    fn test_layout() -> Layout {
+        let subgraph = DeploymentHash::new("subgraph").unwrap();
+        let schema = InputSchema::parse_latest(SCHEMA, subgraph.clone()).unwrap();
+        let namespace = Namespace::new("sgd0815".to_owned()).unwrap();
+        let site = Arc::new(make_dummy_site(subgraph, namespace, "anet".to_string()));
+        let catalog = Catalog::for_tests(site.clone(), BTreeSet::new()).unwrap();
+        Layout::new(site, &schema, catalog).unwrap()
+    }
+
+    #[track_caller]
+    fn check_rewrite(sql: &str, expected: &[&str]) {
+        let layout = test_layout();
+        let rewritten = rewrite(&layout, sql, 42).unwrap();
+        for exp in expected {
+            assert!(
+                rewritten.contains(exp),
+                "expected `{rewritten}` to contain `{exp}`"
+            );
+        }
+    }
+
+    #[test]
+    fn rewrites_table_references() {
+        check_rewrite(
+            "select symbol, count(*) from token group by symbol",
+            &[
+                r#"FROM "sgd0815"."token" WHERE "block_range" @> 42) AS token"#,
+                "GROUP BY symbol",
+            ],
+        );
+        check_rewrite(
+            "select t.symbol, sum(x.amount) from Token t join transfer x on x.token = t.id \
+             where t.id in (select token from transfer)",
+            &[
+                r#"FROM "sgd0815"."token" WHERE "block_range" @> 42) AS t"#,
+                r#"FROM "sgd0815"."transfer" WHERE "block$" <= 42) AS x"#,
+                r#"FROM "sgd0815"."transfer" WHERE "block$" <= 42) AS transfer"#,
+            ],
+        );
+        // References to common table expressions are left alone
+        check_rewrite(
+            "with big as (select * from transfer where amount > 100) select count(*) from big",
+            &[
+                r#"FROM "sgd0815"."transfer" WHERE "block$" <= 42) AS transfer"#,
+                "FROM big",
+            ],
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        let layout = test_layout();
+        let invalid = |sql: &str| {
+            assert!(
+                matches!(
+                    rewrite(&layout, sql, 42),
+                    Err(QueryExecutionError::InvalidSqlQuery(_))
+                ),
+                "query `{sql}` should be rejected"
+            )
+        };
+
+        invalid("delete from token");
+        invalid("select 1; select 2");
+        invalid("select * from pg_catalog.pg_user");
+        invalid(r#"select * from "sgd0815"."token""#);
+        invalid("select * from nonexistent");
+        invalid("select * from poi2$");
+        invalid("select pg_sleep(10)");
+        invalid("select * from token where symbol = $1");
+        invalid("select * from generate_series(1, 10)");
+        invalid("select * from token for update");
+        invalid("with recursive r as (select 1) select * from r");
+        invalid("select count(*) over () from token");
+    }
+}
@@ -10,7 +10,7 @@ use graph::{
     components::store::StoreError,
     data::{
         store::{
-            scalar::{BigDecimal, Bytes, Timestamp},
+            scalar::{BigDecimal, Bytes, GeoPoint, Timestamp},
             Entity, QueryObject,
         },
         value::{Object, Word},
@@ -118,8 +118,14 @@ pub trait FromOidValue: Sized {
     fn from_oid_value(value: OidValue, column_type: &ColumnType) -> Result<Self, StoreError>;
 }
 
+/// Points are read from the database as the text representation of a
+/// Postgres `point`
+fn geo_point(s: &str) -> Result<GeoPoint, StoreError> {
+    GeoPoint::from_pg_point(s).map_err(|e| StoreError::Unknown(e.into()))
+}
+
 impl FromOidValue for r::Value {
-    fn from_oid_value(value: OidValue, _: &ColumnType) -> Result<Self, StoreError> {
+    fn from_oid_value(value: OidValue, column_type: &ColumnType) -> Result<Self, StoreError> {
         fn as_list<T, F>(values: Vec<T>, f: F) -> r::Value
         where
             F: Fn(T) -> r::Value,
@@ -129,6 +135,14 @@ impl FromOidValue for r::Value {
 
         use OidValue as O;
         let value = match value {
+            O::String(s) if column_type == &ColumnType::GeoPoint => {
+                Self::String(geo_point(&s)?.to_string())
+            }
+            O::StringArray(s) if column_type == &ColumnType::GeoPoint => Self::List(
+                s.iter()
+                    .map(|s| geo_point(s).map(|p| Self::String(p.to_string())))
+                    .collect::<Result<_, _>>()?,
+            ),
             O::String(s) => Self::String(s),
             O::StringArray(s) => as_list(s, Self::String),
             O::Bytes(b) => Self::String(b.to_string()),
@@ -171,6 +185,10 @@ impl FromOidValue for graph::prelude::Value {
 
         use OidValue as O;
         let value = match value {
+            O::String(s) if column_type == &ColumnType::GeoPoint => Self::GeoPoint(geo_point(&s)?),
+            O::StringArray(s) if column_type == &ColumnType::GeoPoint => {
+                as_list_err(s, |s| geo_point(&s).map(Self::GeoPoint))?
+            }
             O::String(s) => Self::String(s),
             O::StringArray(s) => as_list(s, Self::String),
             O::Bytes(b) => Self::Bytes(b),
@@ -10,7 +10,9 @@ use diesel::query_builder::{AstPass, Query, QueryFragment, QueryId};
 use diesel::query_dsl::RunQueryDsl;
 use diesel::result::{Error as DieselError, QueryResult};
 use diesel::sql_types::Untyped;
-use diesel::sql_types::{Array, BigInt, Binary, Bool, Int8, Integer, Jsonb, Text, Timestamptz};
+use diesel::sql_types::{
+    Array, BigInt, Binary, Bool, Double, Int8, Integer, Jsonb, Text, Timestamptz,
+};
 use diesel::QuerySource as _;
 use graph::components::store::write::{EntityWrite, RowGroup, WriteChunk};
 use graph::components::store::{Child as StoreChild, DerivedEntityQuery};
@@ -23,7 +25,7 @@ use graph::prelude::{
     EntityLink, EntityOrder, EntityOrderByChild, EntityOrderByChildInfo, EntityRange, EntityWindow,
     ParentLink, QueryExecutionError, StoreError, Value, ENV_VARS,
 };
-use graph::schema::{EntityType, FulltextAlgorithm, FulltextConfig, InputSchema};
+use graph::schema::{EntityType, FulltextAlgorithm, FulltextConfig, FulltextWeight, InputSchema};
 use graph::{components::store::AttributeNames, data::store::scalar};
 use inflector::Inflector;
 use itertools::Itertools;
@@ -212,6 +214,9 @@ pub trait FromColumnValue: Sized + std::fmt::Debug {
 
     fn from_timestamp(i: &str) -> Result<Self, StoreError>;
 
+    // The text representation of a Postgres `point`, i.e., `(lng,lat)`
+    fn from_geo_point(p: &str) -> Result<Self, StoreError>;
+
     fn from_vec(v: Vec<Self>) -> Self;
 
     fn from_column_value(
@@ -243,7 +248,7 @@ pub trait FromColumnValue: Sized + std::fmt::Debug {
             },
             (j::Number(number), ColumnType::BigDecimal) => {
                 let s = number.to_string();
-                scalar::BigDecimal::from_str(s.as_str())
+                scalar::BigDecimal::from_str_unrounded(s.as_str())
                     .map(Self::from_big_decimal)
                     .map_err(|e| {
                         StoreError::Unknown(anyhow!(
@@ -264,6 +269,7 @@ pub trait FromColumnValue: Sized + std::fmt::Debug {
             }
             (j::String(s), ColumnType::Bytes) => Self::from_bytes(s.trim_start_matches("\\x")),
             (j::String(s), ColumnType::Timestamp) => Self::from_timestamp(&s),
+            (j::String(s), ColumnType::GeoPoint) => Self::from_geo_point(&s),
             (j::String(s), column_type) => Err(StoreError::Unknown(anyhow!(
                 "can not convert string {} to {:?}",
                 s,
@@ -334,6 +340,12 @@ impl FromColumnValue for r::Value {
             })
     }
 
+    fn from_geo_point(p: &str) -> Result<Self, StoreError> {
+        scalar::GeoPoint::from_pg_point(p)
+            .map(|p| r::Value::String(p.to_string()))
+            .map_err(|e| StoreError::Unknown(anyhow!("failed to convert {} to GeoPoint: {}", p, e)))
+    }
+
     fn from_vec(v: Vec<Self>) -> Self {
         r::Value::List(v)
     }
@@ -388,6 +400,12 @@ impl FromColumnValue for graph::prelude::Value {
             })
     }
 
+    fn from_geo_point(p: &str) -> Result<Self, StoreError> {
+        scalar::GeoPoint::from_pg_point(p)
+            .map(graph::prelude::Value::GeoPoint)
+            .map_err(|e| StoreError::Unknown(anyhow!("failed to convert {} to GeoPoint: {}", p, e)))
+    }
+
     fn from_vec(v: Vec<Self>) -> Self {
         graph::prelude::Value::List(v)
     }
@@ -538,6 +556,8 @@ enum SqlValue<'a> {
     Int(i32),
     Int8(i64),
     Timestamp(scalar::Timestamp),
+    /// The text representation of a Postgres `point`
+    GeoPoint(String),
     Numeric(String),
     Numerics(Vec<String>),
     Bool(bool),
@@ -573,6 +593,7 @@ impl<'a> SqlValue<'a> {
                 S::Numeric(d.to_string())
             }
             Timestamp(ts) => S::Timestamp(ts.clone()),
+            GeoPoint(p) => S::GeoPoint(p.to_pg_point()),
             Bool(b) => S::Bool(*b),
             List(values) => {
                 match column_type {
@@ -585,6 +606,7 @@ impl<'a> SqlValue<'a> {
                     ColumnType::Int8|
                     ColumnType::String|
                     ColumnType::Timestamp|
+                    ColumnType::GeoPoint|
                     ColumnType::Enum(_)|
                     ColumnType::TSVector(_) => {
                         S::List(values)
@@ -613,6 +635,7 @@ impl std::fmt::Display for SqlValue<'_> {
             S::Int8(i) => write!(f, "{}", i),
             S::Numeric(s) => write!(f, "{}", s),
             S::Timestamp(ts) => write!(f, "{}", ts.as_microseconds_since_epoch().to_string()),
+            S::GeoPoint(p) => write!(f, "{}", p),
             S::Numerics(values) => write!(f, "{:?}", values),
             S::Bool(b) => write!(f, "{}", b),
             S::List(values) => write!(f, "{:?}", values),
@@ -673,12 +696,7 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
                     out.push_sql(enum_type.name.as_str());
                     Ok(())
                 }
-                ColumnType::TSVector(_) => {
-                    out.push_sql("to_tsquery(");
-                    out.push_bind_param::<Text, _>(s)?;
-                    out.push_sql(")");
-                    Ok(())
-                }
+                ColumnType::TSVector(config) => push_tsquery(s, config, out),
                 _ => unreachable!(
                     "only string, enum and tsvector columns have values of type string but not {column_type}"
                 ),
@@ -695,6 +713,11 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
             S::Int(i) => out.push_bind_param::<Integer, _>(i),
             S::Int8(i) => out.push_bind_param::<Int8, _>(i),
             S::Timestamp(i) => out.push_bind_param::<Timestamptz, _>(&i.0),
+            S::GeoPoint(p) => {
+                out.push_bind_param::<Text, _>(p)?;
+                out.push_sql("::point");
+                Ok(())
+            }
             S::Numeric(s) => {
                 out.push_bind_param::<Text, _>(s)?;
                 out.push_sql("::numeric");
@@ -708,6 +731,11 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
                     ColumnType::Int => out.push_bind_param::<Array<Integer>, _>(values),
                     ColumnType::Int8 => out.push_bind_param::<Array<Int8>, _>(values),
                     ColumnType::Timestamp => out.push_bind_param::<Array<Timestamptz>, _>(values),
+                    ColumnType::GeoPoint => {
+                        out.push_bind_param::<Array<Text>, _>(values)?;
+                        out.push_sql("::point[]");
+                        Ok(())
+                    }
                     ColumnType::String => out.push_bind_param::<Array<Text>, _>(values),
                     ColumnType::Enum(enum_type) => {
                         out.push_bind_param::<Array<Text>, _>(values)?;
@@ -718,7 +746,7 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
                     }
                     // TSVector will only be in a Value::List() for inserts so "to_tsvector" can always be used here
                     ColumnType::TSVector(config) => {
-                        process_vec_ast(values, &mut out, config.language.as_sql())?;
+                        process_vec_ast(values, &[], &mut out, config)?;
                         Ok(())
                     }
                     ColumnType::BigDecimal | ColumnType::BigInt => {
@@ -743,29 +771,77 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
     }
 }
 
+/// Generate the tsvector for `values` with each language in `config`. The
+/// value at index `i` gets the weight `weights[i]`; values past the end of
+/// `weights` have no weight
 fn process_vec_ast<'a, T: diesel::serialize::ToSql<Text, Pg>>(
-    values: &'a Vec<T>,
+    values: &'a [T],
+    weights: &[Option<FulltextWeight>],
     out: &mut AstPass<'_, 'a, Pg>,
-    sql_language: &str,
+    config: &FulltextConfig,
 ) -> Result<(), DieselError> {
     if values.is_empty() {
         out.push_sql("''::tsvector");
     } else {
         out.push_sql("(");
+        let mut first = true;
         for (i, value) in values.iter().enumerate() {
-            if i > 0 {
-                out.push_sql(") || ");
+            let weight = weights.get(i).copied().flatten();
+            for language in &config.languages {
+                if !first {
+                    out.push_sql(" || ");
+                }
+                first = false;
+                if weight.is_some() {
+                    out.push_sql("setweight(");
+                }
+                out.push_sql("to_tsvector(");
+                out.push_sql(language.as_sql());
+                out.push_sql(", ");
+                out.push_bind_param::<Text, _>(value)?;
+                out.push_sql(")");
+                if let Some(weight) = weight {
+                    out.push_sql(", ");
+                    out.push_sql(weight.as_sql());
+                    out.push_sql(")");
+                }
             }
-            out.push_sql("to_tsvector(");
-            out.push_sql(sql_language);
-            out.push_sql(", ");
-            out.push_bind_param::<Text, _>(value)?;
         }
-        out.push_sql("))");
+        out.push_sql(")");
     }
     Ok(())
 }
 
+/// Generate the tsquery for the fulltext search `query`. With more than
+/// one language, the query is parsed with each of them and matches if it
+/// matches with any of them
+fn push_tsquery<'a>(
+    query: &'a str,
+    config: &FulltextConfig,
+    out: &mut AstPass<'_, 'a, Pg>,
+) -> QueryResult<()> {
+    if config.languages.len() <= 1 {
+        out.push_sql("to_tsquery(");
+        out.push_bind_param::<Text, _>(query)?;
+        out.push_sql(")");
+        return Ok(());
+    }
+
+    out.push_sql("(");
+    for (i, language) in config.languages.iter().enumerate() {
+        if i > 0 {
+            out.push_sql(" || ");
+        }
+        out.push_sql("to_tsquery(");
+        out.push_sql(language.as_sql());
+        out.push_sql(", ");
+        out.push_bind_param::<Text, _>(query)?;
+        out.push_sql(")");
+    }
+    out.push_sql(")");
+    Ok(())
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Comparison {
     Less,
@@ -816,7 +892,7 @@ impl Comparison {
                 | Comparison::LessOrEqual
                 | Comparison::GreaterOrEqual
                 | Comparison::Greater,
-                Value::Bool(_) | Value::List(_) | Value::Null,
+                Value::Bool(_) | Value::List(_) | Value::GeoPoint(_) | Value::Null,
             )
             | (Comparison::Match, _) => {
                 return Err(StoreError::UnsupportedFilter(
@@ -1138,17 +1214,19 @@ impl<'a> QueryChild<'a> {
             entity_type,
             filter,
             derived,
+            key,
         } = child;
         let derived = *derived;
         let child_table = layout.table_for_entity(entity_type)?.dsl_table().child(0);
         let (parent_column, child_column) = if derived {
             // If the parent is derived, the child column is picked based on
-            // the provided attribute and the parent column is the primary
-            // key of the parent table
-            (
-                parent_table.primary_key(),
-                child_table.column_for_field(attr)?,
-            )
+            // the provided attribute and the parent column is the key or,
+            // without one, the primary key of the parent table
+            let parent_column = match key {
+                Some(key) => parent_table.column_for_field(key)?,
+                None => parent_table.primary_key(),
+            };
+            (parent_column, child_table.column_for_field(attr)?)
         } else {
             // If the parent is not derived, we do the opposite. The parent
             // column is picked based on the provided attribute and the
@@ -1281,6 +1359,22 @@ pub enum Filter<'a> {
     Child(Box<QueryChild<'a>>),
     /// The value is never null for fulltext queries
     Fulltext(dsl::Column<'a>, QueryValue<'a>),
+    /// Points in `column` that are at most `meters` away from `center`.
+    /// The box `sw`, `ne` contains all such points and is used to narrow
+    /// down candidates with the GiST index on the column
+    WithinDistance {
+        column: dsl::Column<'a>,
+        center: scalar::GeoPoint,
+        meters: f64,
+        /// The south-west and north-east corners of the bounding boxes
+        boxes: Vec<(String, String)>,
+        postgis: bool,
+    },
+    WithinBox {
+        column: dsl::Column<'a>,
+        sw: String,
+        ne: String,
+    },
 }
 
 impl<'a> Filter<'a> {
@@ -1311,6 +1405,21 @@ impl<'a> Filter<'a> {
             Ok((column, value))
         }
 
+        fn geo_column<'v>(
+            table: dsl::Table<'v>,
+            attr: &String,
+            filter: &str,
+        ) -> Result<dsl::Column<'v>, StoreError> {
+            let column = table.column_for_field(attr)?;
+            if column.column_type() != &ColumnType::GeoPoint || column.is_list() {
+                return Err(StoreError::UnsupportedFilter(
+                    filter.to_owned(),
+                    attr.to_owned(),
+                ));
+            }
+            Ok(column)
+        }
+
         fn starts_or_ends_with<'s>(
             table: dsl::Table<'s>,
             attr: &String,
@@ -1338,6 +1447,7 @@ impl<'a> Filter<'a> {
                 | Value::Bytes(_)
                 | Value::BigDecimal(_)
                 | Value::Timestamp(_)
+                | Value::GeoPoint(_)
                 | Value::Int(_)
                 | Value::Int8(_)
                 | Value::List(_)
@@ -1404,6 +1514,7 @@ impl<'a> Filter<'a> {
                 SqlValue::Int(_)
                 | SqlValue::Int8(_)
                 | SqlValue::Timestamp(_)
+                | SqlValue::GeoPoint(_)
                 | SqlValue::Numeric(_)
                 | SqlValue::Numerics(_)
                 | SqlValue::Bool(_)
@@ -1499,6 +1610,29 @@ impl<'a> Filter<'a> {
                 }
                 Ok(F::Fulltext(column, value))
             }
+            WithinDistance(attr, center, meters) => {
+                let column = geo_column(table, attr, "within_distance")?;
+                let boxes = center
+                    .bounding_boxes(*meters)
+                    .into_iter()
+                    .map(|(sw, ne)| (sw.to_pg_point(), ne.to_pg_point()))
+                    .collect();
+                Ok(F::WithinDistance {
+                    column,
+                    center: *center,
+                    meters: *meters,
+                    boxes,
+                    postgis: layout.catalog.has_postgis,
+                })
+            }
+            WithinBox(attr, sw, ne) => {
+                let column = geo_column(table, attr, "within_box")?;
+                Ok(F::WithinBox {
+                    column,
+                    sw: sw.to_pg_point(),
+                    ne: ne.to_pg_point(),
+                })
+            }
         }
     }
 
@@ -1606,6 +1740,7 @@ impl<'a> Filter<'a> {
             | SqlValue::Bool(_)
             | SqlValue::Numeric(_)
             | SqlValue::Timestamp(_)
+            | SqlValue::GeoPoint(_)
             | SqlValue::Int(_)
             | SqlValue::Int8(_) => {
                 let filter = match op.negated() {
@@ -1622,6 +1757,80 @@ impl<'a> Filter<'a> {
         Ok(())
     }
 
+    /// Generate `column <@ box(sw, ne)` which can use a GiST index
+    fn within_box<'b>(
+        column: &'b dsl::Column<'b>,
+        sw: &'b String,
+        ne: &'b String,
+        mut out: AstPass<'_, 'b, Pg>,
+    ) -> QueryResult<()> {
+        column.walk_ast(out.reborrow())?;
+        out.push_sql(" <@ box(");
+        out.push_bind_param::<Text, _>(sw)?;
+        out.push_sql("::point, ");
+        out.push_bind_param::<Text, _>(ne)?;
+        out.push_sql("::point)");
+        Ok(())
+    }
+
+    /// Generate a check that `column` is at most `meters` away from
+    /// `center`. With PostGIS, we use `ST_DWithin` on geographies, and
+    /// otherwise compute the haversine distance on a sphere, the same way
+    /// as `GeoPoint::distance` does. The x coordinate of a Postgres point
+    /// is the longitude and the y coordinate the latitude. Candidates are
+    /// narrowed down with the bounding `boxes` first
+    fn within_distance<'b>(
+        column: &'b dsl::Column<'b>,
+        center: &'b scalar::GeoPoint,
+        meters: &'b f64,
+        boxes: &'b [(String, String)],
+        postgis: bool,
+        mut out: AstPass<'_, 'b, Pg>,
+    ) -> QueryResult<()> {
+        out.push_sql("((");
+        for (i, (sw, ne)) in boxes.iter().enumerate() {
+            if i > 0 {
+                out.push_sql(" or ");
+            }
+            Self::within_box(column, sw, ne, out.reborrow())?;
+        }
+        out.push_sql(") and ");
+        if postgis {
+            out.push_sql("ST_DWithin(geography(ST_SetSRID(ST_MakePoint(");
+            column.walk_ast(out.reborrow())?;
+            out.push_sql("[0], ");
+            column.walk_ast(out.reborrow())?;
+            out.push_sql("[1]), 4326)), geography(ST_SetSRID(ST_MakePoint(");
+            out.push_sql(&center.longitude().to_string());
+            out.push_sql(", ");
+            out.push_sql(&center.latitude().to_string());
+            out.push_sql("), 4326)), ");
+            out.push_bind_param::<Double, _>(meters)?;
+            out.push_sql(")");
+        } else {
+            let lat = center.latitude().to_string();
+            let lng = center.longitude().to_string();
+            out.push_sql("2 * ");
+            out.push_sql(&scalar::EARTH_RADIUS_METERS.to_string());
+            out.push_sql(" * asin(least(1, sqrt(power(sin(radians(");
+            column.walk_ast(out.reborrow())?;
+            out.push_sql("[1] - ");
+            out.push_sql(&lat);
+            out.push_sql(") / 2), 2) + cos(radians(");
+            out.push_sql(&lat);
+            out.push_sql(")) * cos(radians(");
+            column.walk_ast(out.reborrow())?;
+            out.push_sql("[1])) * power(sin(radians(");
+            column.walk_ast(out.reborrow())?;
+            out.push_sql("[0] - ");
+            out.push_sql(&lng);
+            out.push_sql(") / 2), 2)))) <= ");
+            out.push_bind_param::<Double, _>(meters)?;
+        }
+        out.push_sql(")");
+        Ok(())
+    }
+
     fn in_array<'b>(
         column: &'b dsl::Column<'b>,
         values: &'b [QueryValue],
@@ -1763,6 +1972,13 @@ impl<'a> fmt::Display for Filter<'a> {
                 child.child_from,
                 child.child_filter
             ),
+            WithinDistance {
+                column,
+                center,
+                meters,
+                ..
+            } => write!(f, "{column} within {meters}m of ({center})"),
+            WithinBox { column, sw, ne } => write!(f, "{column} <@ box({sw}, {ne})"),
         }
     }
 }
@@ -1797,6 +2013,14 @@ impl<'a> QueryFragment<Pg> for Filter<'a> {
             }
             ChangeBlockGte(changed_since) => changed_since.walk_ast(out.reborrow())?,
             Child(child) => child.walk_ast(out)?,
+            WithinDistance {
+                column,
+                center,
+                meters,
+                boxes,
+                postgis,
+            } => Self::within_distance(column, center, meters, boxes, *postgis, out)?,
+            WithinBox { column, sw, ne } => Self::within_box(column, sw, ne, out)?,
         }
         Ok(())
     }
@@ -1912,6 +2136,131 @@ impl<'a> Query for FindPossibleDeletionsQuery<'a> {
 
 impl<'a, Conn> RunQueryDsl<Conn> for FindPossibleDeletionsQuery<'a> {}
 
+/// One version of an entity as found by `FindHistoryQuery`, together with
+/// the range of blocks for which it was current
+#[derive(QueryableByName, Debug)]
+pub struct EntityVersionData {
+    #[diesel(sql_type = Integer)]
+    pub block_from: BlockNumber,
+    #[diesel(sql_type = diesel::sql_types::Nullable<Integer>)]
+    pub block_to: Option<BlockNumber>,
+    #[diesel(embed)]
+    pub data: EntityData,
+}
+
+/// A query that finds the versions of entities in `table` that were
+/// written or removed in the blocks `from` to `to`, both inclusive. A
+/// version is removed at the block at which its block range ends; versions
+/// of immutable entities are never removed
+#[derive(Debug)]
+pub struct FindHistoryQuery<'a> {
+    table: &'a Table,
+    ids: Option<IdList>,
+    from: BlockNumber,
+    to: BlockNumber,
+    first: u32,
+    skip: u32,
+}
+
+impl<'a> FindHistoryQuery<'a> {
+    pub fn new(
+        table: &'a Table,
+        ids: Option<IdList>,
+        from: BlockNumber,
+        to: BlockNumber,
+        first: u32,
+        skip: u32,
+    ) -> Self {
+        Self {
+            table,
+            ids,
+            from,
+            to,
+            first,
+            skip,
+        }
+    }
+
+    fn between<'b>(&'b self, out: &mut AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.push_sql(" between ");
+        out.push_bind_param::<Integer, _>(&self.from)?;
+        out.push_sql(" and ");
+        out.push_bind_param::<Integer, _>(&self.to)
+    }
+}
+
+impl<'a> QueryFragment<Pg> for FindHistoryQuery<'a> {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.unsafe_to_cache_prepared();
+
+        // Generate
+        //    select $object as entity, to_jsonb(e.*) as data,
+        //           lower(e.block_range) as block_from,
+        //           upper(e.block_range) as block_to
+        //      from schema.<table> e
+        //     where {id.is_in($ids)}
+        //       and (lower(e.block_range) between $from and $to
+        //            or upper(e.block_range) between $from and $to)
+        //     order by block_from, e.vid
+        //     limit $first offset $skip
+        //
+        // For immutable tables, we use `block$` instead of the block range
+        out.push_sql("select ");
+        out.push_bind_param::<Text, _>(self.table.object.as_str())?;
+        out.push_sql(" as entity, to_jsonb(e.*) as data, ");
+        if self.table.immutable {
+            out.push_sql("e.");
+            out.push_identifier(BLOCK_COLUMN)?;
+            out.push_sql(" as block_from, null::int4 as block_to");
+        } else {
+            out.push_sql("lower(e.");
+            out.push_identifier(BLOCK_RANGE_COLUMN)?;
+            out.push_sql(") as block_from, upper(e.");
+            out.push_identifier(BLOCK_RANGE_COLUMN)?;
+            out.push_sql(") as block_to");
+        }
+        out.push_sql("\n  from ");
+        out.push_sql(self.table.qualified_name.as_str());
+        out.push_sql(" e\n where ");
+        if let Some(ids) = &self.ids {
+            id_is_in(ids, &mut out)?;
+            out.push_sql(" and ");
+        }
+        if self.table.immutable {
+            out.push_sql("e.");
+            out.push_identifier(BLOCK_COLUMN)?;
+            self.between(&mut out)?;
+        } else {
+            out.push_sql("(lower(e.");
+            out.push_identifier(BLOCK_RANGE_COLUMN)?;
+            out.push_sql(")");
+            self.between(&mut out)?;
+            out.push_sql(" or upper(e.");
+            out.push_identifier(BLOCK_RANGE_COLUMN)?;
+            out.push_sql(")");
+            self.between(&mut out)?;
+            out.push_sql(")");
+        }
+        out.push_sql("\n order by block_from, e.vid\n limit ");
+        out.push_sql(&self.first.to_string());
+        out.push_sql(" offset ");
+        out.push_sql(&self.skip.to_string());
+        Ok(())
+    }
+}
+
+impl<'a> QueryId for FindHistoryQuery<'a> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<'a> Query for FindHistoryQuery<'a> {
+    type SqlType = Untyped;
+}
+
+impl<'a, Conn> RunQueryDsl<Conn> for FindHistoryQuery<'a> {}
+
 #[derive(Debug)]
 pub struct FindManyQuery<'a> {
     pub(crate) tables: Vec<(&'a Table, CausalityRegion, BlockRangeColumn<'a>)>,
@@ -1993,24 +2342,60 @@ impl<'a, Conn> RunQueryDsl<Conn> for FindManyQuery<'a> {}
 pub struct FindDerivedQuery<'a> {
     table: &'a Table,
     derived_query: &'a DerivedEntityQuery,
+    filter: Option<Filter<'a>>,
+    order_by: Option<dsl::Column<'a>>,
+    id: dsl::Column<'a>,
     excluded_keys: IdList,
     br_column: BlockRangeColumn<'a>,
 }
 
 impl<'a> FindDerivedQuery<'a> {
+    /// Create the query; `filter` must be the `entity_filter()` of the
+    /// `derived_query.filter`
     pub fn new(
+        layout: &'a Layout,
         table: &'a Table,
         derived_query: &'a DerivedEntityQuery,
+        filter: Option<&'a EntityFilter>,
         block: BlockNumber,
         excluded_keys: IdList,
-    ) -> Self {
-        let br_column = BlockRangeColumn::new(table, "e.", block);
-        Self {
+    ) -> Result<Self, StoreError> {
+        let dsl_table = table.dsl_table();
+        let filter = filter
+            .map(|filter| Filter::main(layout, dsl_table, filter, block))
+            .transpose()?;
+        let order_by = derived_query
+            .filter
+            .order_by
+            .as_ref()
+            .map(|attr| dsl_table.column_for_field(attr))
+            .transpose()?;
+        let id = dsl_table.primary_key();
+        let br_column = BlockRangeColumn::new(table, "c.", block);
+        Ok(Self {
             table,
             derived_query,
+            filter,
+            order_by,
+            id,
             excluded_keys,
             br_column,
+        })
+    }
+
+    /// Order by `column`; strings are compared bytewise so that the order
+    /// does not depend on the collation of the database
+    fn order_by_column<'b>(
+        column: &'b dsl::Column<'a>,
+        direction: &'static str,
+        out: &mut AstPass<'_, 'b, Pg>,
+    ) -> QueryResult<()> {
+        column.walk_ast(out.reborrow())?;
+        if column.column_type() == &ColumnType::String {
+            out.push_sql(" collate \"C\"");
         }
+        out.push_sql(direction);
+        Ok(())
     }
 }
 
@@ -2023,17 +2408,20 @@ impl<'a> QueryFragment<Pg> for FindDerivedQuery<'a> {
             entity_field,
             value: entity_id,
             causality_region,
+            filter,
         } = self.derived_query;
 
         // Generate
-        //    select '..' as entity, to_jsonb(e.*) as data
-        //      from schema.table e where field = $1
+        //    select '..' as entity, to_jsonb(c.*) as data
+        //      from schema.table c where field = $1
+        //     order by c.attr, c.id limit $2
+        // where the filter, the order and the limit are optional
         out.push_sql("select ");
         out.push_bind_param::<Text, _>(self.table.object.as_str())?;
-        out.push_sql(" as entity, to_jsonb(e.*) as data\n");
+        out.push_sql(" as entity, to_jsonb(c.*) as data\n");
         out.push_sql("  from ");
         out.push_sql(self.table.qualified_name.as_str());
-        out.push_sql(" e\n where ");
+        out.push_sql(" c\n where ");
         // This clause with an empty array would filter out everything
         if self.excluded_keys.len() > 0 {
             out.push_identifier(&self.table.primary_key().name)?;
@@ -2053,7 +2441,25 @@ impl<'a> QueryFragment<Pg> for FindDerivedQuery<'a> {
             out.push_bind_param::<Integer, _>(causality_region)?;
             out.push_sql(" and ");
         }
-        self.br_column.contains(&mut out, false)
+        if let Some(filter) = &self.filter {
+            filter.walk_ast(out.reborrow())?;
+            out.push_sql(" and ");
+        }
+        self.br_column.contains(&mut out, false)?;
+
+        // The order only matters when we limit the number of entities
+        if let Some(first) = &filter.first {
+            let direction = if filter.descending { " desc" } else { " asc" };
+            out.push_sql("\n order by ");
+            if let Some(column) = &self.order_by {
+                Self::order_by_column(column, direction, &mut out)?;
+                out.push_sql(", ");
+            }
+            Self::order_by_column(&self.id, direction, &mut out)?;
+            out.push_sql("\n limit ");
+            out.push_sql(&first.to_string());
+        }
+        Ok(())
     }
 }
 
@@ -2073,15 +2479,19 @@ impl<'a, Conn> RunQueryDsl<Conn> for FindDerivedQuery<'a> {}
 #[derive(Debug)]
 enum InsertValue<'a> {
     Value(QueryValue<'a>),
-    Fulltext(Vec<&'a String>, &'a FulltextConfig),
+    Fulltext(
+        Vec<&'a String>,
+        Vec<Option<FulltextWeight>>,
+        &'a FulltextConfig,
+    ),
 }
 
 impl<'a> QueryFragment<Pg> for InsertValue<'a> {
     fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
         match self {
             InsertValue::Value(qv) => qv.walk_ast(out),
-            InsertValue::Fulltext(qvs, config) => {
-                process_vec_ast(qvs, &mut out, config.language.as_sql())?;
+            InsertValue::Fulltext(qvs, weights, config) => {
+                process_vec_ast(qvs, weights, &mut out, config)?;
                 Ok(())
             }
         }
@@ -2106,19 +2516,25 @@ impl<'a> InsertRow<'a> {
         let mut values = Vec::with_capacity(columns.len());
         for column in columns {
             let iv = if let Some(fields) = column.fulltext_fields.as_ref() {
-                let fulltext_field_values: Vec<_> = fields
-                    .iter()
-                    .filter_map(|field| row.entity.get(field))
-                    .map(|value| match value {
-                        Value::String(s) => Ok(s),
-                        _ => Err(constraint_violation!(
-                            "fulltext fields must be strings but got {:?}",
-                            value
-                        )),
-                    })
-                    .collect::<Result<_, _>>()?;
                 if let ColumnType::TSVector(config) = &column.column_type {
-                    InsertValue::Fulltext(fulltext_field_values, &config)
+                    let (fulltext_field_values, weights): (Vec<_>, Vec<_>) = fields
+                        .iter()
+                        .filter_map(|field| {
+                            row.entity
+                                .get(field)
+                                .map(|value| (value, config.weights.get(field).copied()))
+                        })
+                        .map(|(value, weight)| match value {
+                            Value::String(s) => Ok((s, weight)),
+                            _ => Err(constraint_violation!(
+                                "fulltext fields must be strings but got {:?}",
+                                value
+                            )),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?
+                        .into_iter()
+                        .unzip();
+                    InsertValue::Fulltext(fulltext_field_values, weights, config)
                 } else {
                     return Err(StoreError::FulltextColumnMissingConfig);
                 }
@@ -2204,8 +2620,12 @@ impl<'a> InsertQuery<'a> {
             // put values into bind variables
             if let Some(fields) = &column.fulltext_fields {
                 // Fulltext fields use one bind variable for each field that
-                // gets put into the index
-                count += fields.len()
+                // gets put into the index and each language of the index
+                let languages = match &column.column_type {
+                    ColumnType::TSVector(config) => config.languages.len(),
+                    _ => 1,
+                };
+                count += fields.len() * languages
             } else {
                 // All other values use one bind variable
                 count += 1
@@ -2364,6 +2784,8 @@ impl ParentIds {
 #[derive(Debug, Clone)]
 enum TableLink<'a> {
     Direct(dsl::Column<'a>, ChildMultiplicity),
+    /// The `IdList` holds the value of the parent's key for each parent
+    Key(dsl::Column<'a>, IdList, ChildMultiplicity),
     /// The `Table` is the parent table
     Parent(&'a Table, ParentIds),
 }
@@ -2379,6 +2801,10 @@ impl<'a> TableLink<'a> {
                 let column = child_table.column_for_field(attribute.name())?;
                 Ok(TableLink::Direct(column, multiplicity))
             }
+            EntityLink::Key(attribute, keys, multiplicity) => {
+                let column = child_table.column_for_field(attribute.name())?;
+                Ok(TableLink::Key(column, keys, multiplicity))
+            }
             EntityLink::Parent(parent_type, parent_link) => {
                 let parent_table = layout.table_for_entity(&parent_type)?;
                 Ok(TableLink::Parent(
@@ -2494,6 +2920,7 @@ impl<'a> FilterWindow<'a> {
     fn parent_type(&self) -> QueryResult<IdType> {
         match &self.link {
             TableLink::Direct(column, _) => column.column_type().id_type(),
+            TableLink::Key(_, _, _) => Ok(self.ids.id_type()),
             TableLink::Parent(parent_table, _) => parent_table.primary_key().column_type.id_type(),
         }
     }
@@ -2760,6 +3187,79 @@ impl<'a> FilterWindow<'a> {
         Ok(())
     }
 
+    fn children_type_e<'b>(
+        &'b self,
+        column: &'b dsl::Column<'b>,
+        keys: &'b IdList,
+        is_outer: bool,
+        limit: &'b ParentLimit<'_>,
+        out: &mut AstPass<'_, 'b, Pg>,
+    ) -> QueryResult<()> {
+        assert!(!column.is_list());
+
+        // Generate
+        //      from rows from (unnest({parent_ids}), unnest({keys})) as p(id, key)
+        //           cross join lateral
+        //           (select {column names}
+        //              from children c
+        //             where p.key = c.{parent_field}
+        //               and .. other conditions on c ..
+        //             order by c.{sort_key}
+        //             limit {first} offset {skip}) c
+        //     order by c.{sort_key}
+
+        out.push_sql("\n/* children_type_e */ from rows from (unnest(");
+        self.ids.push_bind_param(out)?;
+        out.push_sql("), unnest(");
+        keys.push_bind_param(out)?;
+        out.push_sql(")) as p(id, key) cross join lateral (select ");
+        write_column_names(&self.column_names, self.table, None, out)?;
+        out.push_sql(" from ");
+        self.from_table.walk_ast(out.reborrow())?;
+        out.push_sql(" where ");
+        self.at_block.walk_ast(out.reborrow())?;
+        limit.filter(is_outer, out);
+        out.push_sql(" and p.key = ");
+        column.walk_ast(out.reborrow())?;
+        self.and_filter(out)?;
+        limit.restrict(is_outer, out)?;
+        out.push_sql(") c");
+        Ok(())
+    }
+
+    fn child_type_e<'b>(
+        &'b self,
+        column: &'b dsl::Column<'b>,
+        keys: &'b IdList,
+        is_outer: bool,
+        limit: &'b ParentLimit<'_>,
+        out: &mut AstPass<'_, 'b, Pg>,
+    ) -> QueryResult<()> {
+        assert!(!column.is_list());
+
+        // Generate
+        //      from rows from (unnest({parent_ids}), unnest({keys})) as p(id, key),
+        //           children c
+        //     where c.{parent_field} = p.key
+        //       and .. other conditions on c ..
+        //     limit {parent_ids.len} + 1
+
+        out.push_sql("\n/* child_type_e */ from rows from (unnest(");
+        self.ids.push_bind_param(out)?;
+        out.push_sql("), unnest(");
+        keys.push_bind_param(out)?;
+        out.push_sql(")) as p(id, key), ");
+        self.from_table.walk_ast(out.reborrow())?;
+        out.push_sql(" where ");
+        self.at_block.walk_ast(out.reborrow())?;
+        limit.filter(is_outer, out);
+        out.push_sql(" and p.key = ");
+        column.walk_ast(out.reborrow())?;
+        self.and_filter(out)?;
+        limit.single_limit(is_outer, self.ids.len(), out);
+        Ok(())
+    }
+
     fn children<'b>(
         &'b self,
         is_outer: bool,
@@ -2781,6 +3281,10 @@ impl<'a> FilterWindow<'a> {
                     }
                 }
             }
+            TableLink::Key(column, keys, multiplicity) => match multiplicity {
+                ChildMultiplicity::Many => self.children_type_e(column, keys, is_outer, limit, out),
+                ChildMultiplicity::Single => self.child_type_e(column, keys, is_outer, limit, out),
+            },
             TableLink::Parent(_, ParentIds::List(child_ids)) => {
                 self.children_type_c(child_ids, is_outer, limit, out)
             }
@@ -2917,6 +3421,14 @@ impl<'a> fmt::Display for FilterCollection<'a> {
                     TableLink::Direct(col, Many) => {
                         write!(f, "many:{}={}", col.name(), ids.join(","))?
                     }
+                    TableLink::Key(col, keys, multiplicity) => {
+                        let kind = match multiplicity {
+                            Single => "uniq",
+                            Many => "many",
+                        };
+                        let keys = keys.iter().map(|key| key.to_string()).join(",");
+                        write!(f, "{}:{}={}", kind, col.name(), keys)?
+                    }
                     TableLink::Parent(_, ParentIds::List(css)) => {
                         let css = css
                             .iter()
@@ -3811,10 +4323,9 @@ impl<'a> SortKey<'a> {
                     column.walk_ast(out.reborrow())?;
                 }
 
-                out.push_sql(", to_tsquery(");
-
-                out.push_bind_param::<Text, _>(value.unwrap())?;
-                out.push_sql("))");
+                out.push_sql(", ");
+                push_tsquery(value.unwrap(), config, out)?;
+                out.push_sql(")");
             }
             _ => {
                 if use_sort_key_alias {
@@ -13,25 +13,29 @@ use std::{iter::FromIterator, time::Duration};
 
 use graph::futures03::future::join_all;
 use graph::{
+    blockchain::{block_stream::FirehoseCursor, IngestionMode},
     cheap_clone::CheapClone,
     components::{
         server::index_node::VersionInfo,
         store::{
-            self, BlockPtrForNumber, BlockStore, DeploymentLocator, EnsLookup as EnsLookupTrait,
-            PruneReporter, PruneRequest, SubgraphFork,
+            self, BlockPtrForNumber, BlockStore, DeploymentLocator,
+            DeploymentLogs as DeploymentLogsTrait, EnsLookup as EnsLookupTrait,
+            HttpCache as HttpCacheTrait, PruneReporter, PruneRequest, SubgraphFork,
         },
+        subgraph::{FetchPolicy, HandlerLimits, OffchainFailure, RetryPolicy},
     },
     constraint_violation,
     data::query::QueryTarget,
     data::subgraph::{schema::DeploymentCreate, status, DeploymentFeatures},
+    env::ENV_VARS,
     prelude::{
         anyhow, lazy_static, o, web3::types::Address, ApiVersion, BlockNumber, BlockPtr,
-        ChainStore, DeploymentHash, EntityOperation, Logger, MetricsRegistry, NodeId,
+        ChainStore, DeploymentHash, Entity, EntityOperation, Logger, MetricsRegistry, NodeId,
         PartialBlockPtr, StoreError, SubgraphDeploymentEntity, SubgraphName,
         SubgraphStore as SubgraphStoreTrait, SubgraphVersionSwitchingMode,
     },
     prelude::{CancelableError, StoreEvent},
-    schema::{ApiSchema, InputSchema},
+    schema::{ApiSchema, EntityKey, InputSchema},
     url::Url,
     util::timed_cache::TimedCache,
 };
@@ -39,6 +43,8 @@ use graph::{
 use crate::{
     connection_pool::ConnectionPool,
     deployment::{OnSync, SubgraphHealth},
+    deployment_logs::DeploymentLogStorage,
+    http_cache::HttpCacheStorage,
     primary::{self, DeploymentId, Mirror as PrimaryMirror, Site},
     relational::{
         index::{IndexList, Method},
@@ -125,6 +131,37 @@ impl ToSql<Text, Pg> for Shard {
 pub trait DeploymentPlacer {
     fn place(&self, name: &str, network: &str)
         -> Result<Option<(Vec<Shard>, Vec<NodeId>)>, String>;
+
+    /// The retry policy for non-deterministic errors that a new deployment
+    /// should use. Returns `None` if the deployment should use the
+    /// defaults
+    fn retry_policy(&self, _name: &str, _network: &str) -> Option<RetryPolicy> {
+        None
+    }
+
+    /// The limits for the mapping handlers of a new deployment. Returns
+    /// `None` if the deployment should use the defaults
+    fn handler_limits(&self, _name: &str, _network: &str) -> Option<HandlerLimits> {
+        None
+    }
+
+    /// The constants that mappings of a new deployment can read with
+    /// `dataSource.env`. Returns `None` if there are none
+    fn mapping_env(&self, _name: &str, _network: &str) -> Option<BTreeMap<String, String>> {
+        None
+    }
+
+    /// How a new deployment should get its blocks. Returns `None` if the
+    /// deployment should use the block stream of its chain
+    fn ingestion(&self, _name: &str, _network: &str) -> Option<IngestionMode> {
+        None
+    }
+
+    /// How the file data sources of a new deployment fetch their files.
+    /// Returns `None` if the deployment should use the defaults
+    fn fetch_policy(&self, _name: &str, _network: &str) -> Option<FetchPolicy> {
+        None
+    }
 }
 
 /// Tools for managing unused deployments
@@ -389,6 +426,37 @@ impl SubgraphStoreInner {
         Ok(site)
     }
 
+    /// Check that nothing uses `base` so that an in-place graft can take
+    /// over its data
+    pub(crate) fn check_in_place_graft_base(&self, base: &Site) -> Result<(), StoreError> {
+        let mut pconn = self.primary_conn()?;
+        if let Some(node) = pconn.assigned_node(base)? {
+            return Err(StoreError::Unknown(anyhow!(
+                "the graft base {} can not be grafted onto in place since it is \
+                 assigned to {}; unassign it first",
+                base.deployment,
+                node
+            )));
+        }
+        let names = pconn.subgraphs_with_current_or_pending(base)?;
+        if !names.is_empty() {
+            return Err(StoreError::Unknown(anyhow!(
+                "the graft base {} can not be grafted onto in place since it is \
+                 the current or pending version of {}",
+                base.deployment,
+                names.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Mark the base of an in-place graft as unusable after its data was
+    /// moved to the graft
+    pub(crate) fn retire_in_place_graft_base(&self, base: &Site) -> Result<(), StoreError> {
+        self.primary_conn()?.deactivate(base)?;
+        self.evict(&base.deployment)
+    }
+
     fn evict(&self, id: &DeploymentHash) -> Result<(), StoreError> {
         if let Some((site, _)) = self.sites.remove(id) {
             let store = self.stores.get(&site.shard).ok_or_else(|| {
@@ -530,7 +598,36 @@ impl SubgraphStoreInner {
         assert!(!replace);
 
         self.evict(schema.id())?;
+        let deployment = match self.placer.retry_policy(name.as_str(), &network_name) {
+            Some(policy) if deployment.retry_policy.is_none() => {
+                deployment.with_retry_policy(policy)
+            }
+            _ => deployment,
+        };
+        let deployment = match self.placer.handler_limits(name.as_str(), &network_name) {
+            Some(limits) if deployment.handler_limits.is_none() => {
+                deployment.with_handler_limits(limits)
+            }
+            _ => deployment,
+        };
+        let deployment = match self.placer.mapping_env(name.as_str(), &network_name) {
+            Some(env) if deployment.mapping_env.is_none() => deployment.with_mapping_env(env),
+            _ => deployment,
+        };
+        let deployment = match self.placer.ingestion(name.as_str(), &network_name) {
+            Some(ingestion) if deployment.ingestion.is_none() => {
+                deployment.with_ingestion(ingestion)
+            }
+            _ => deployment,
+        };
+        let deployment = match self.placer.fetch_policy(name.as_str(), &network_name) {
+            Some(policy) if deployment.fetch_policy.is_none() => {
+                deployment.with_fetch_policy(policy)
+            }
+            _ => deployment,
+        };
         let graft_base = deployment.graft_base.as_ref();
+        let graft_in_place = graft_base.is_some() && deployment.graft_in_place;
 
         let (site, exists, node_id) = {
             // We need to deal with two situations:
@@ -544,6 +641,16 @@ impl SubgraphStoreInner {
             //       assignment that we used last time to avoid creating
             //       the same deployment in another shard
             let (shard, node_id) = self.place(&name, &network_name, node_id)?;
+            // Moving the data of the graft base into the new deployment is
+            // only possible within the shard of the base
+            let shard = match graft_base {
+                Some(base) if graft_in_place => {
+                    let (_, base) = self.store(base)?;
+                    self.check_in_place_graft_base(&base)?;
+                    base.shard.clone()
+                }
+                _ => shard,
+            };
             let mut conn = self.primary_conn()?;
             let (site, site_was_created) =
                 conn.allocate_site(shard, schema.id(), network_name, graft_base)?;
@@ -563,8 +670,10 @@ impl SubgraphStoreInner {
                 .transpose()?;
 
             if let Some(graft_base) = &graft_base {
-                self.primary_conn()?
-                    .record_active_copy(graft_base.site.as_ref(), site.as_ref())?;
+                if !graft_in_place {
+                    self.primary_conn()?
+                        .record_active_copy(graft_base.site.as_ref(), site.as_ref())?;
+                }
             }
             graft_base
         } else {
@@ -669,8 +778,15 @@ impl SubgraphStoreInner {
             start_block: deployment.start_block.clone(),
             graft_base: Some(src.deployment.clone()),
             graft_block: Some(block),
+            graft_in_place: false,
             debug_fork: deployment.debug_fork,
             history_blocks_override: None,
+            retry_policy: src_store.retry_policy(&src)?,
+            handler_limits: src_store.handler_limits(&src)?,
+            mapping_env: src_store.mapping_env(&src)?,
+            substreams_params: src_store.substreams_params(&src)?,
+            ingestion: src_store.ingestion(&src)?,
+            fetch_policy: src_store.fetch_policy(&src)?,
         };
 
         let graft_base = self.layout(&src.deployment)?;
@@ -1210,6 +1326,22 @@ impl SubgraphStoreInner {
         store.prune(reporter, site, req).await
     }
 
+    /// Remove data for entity types with a `retention` that has fallen out
+    /// of their retention window as of `final_block`; see
+    /// `Layout::prune_by_retention`. This normally happens automatically
+    /// while the deployment is indexing
+    pub async fn prune_by_retention(
+        &self,
+        reporter: Box<dyn PruneReporter>,
+        deployment: &DeploymentLocator,
+        final_block: BlockNumber,
+    ) -> Result<Box<dyn PruneReporter>, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.prune_by_retention(reporter, site, final_block).await
+    }
+
     pub fn set_history_blocks(
         &self,
         deployment: &DeploymentLocator,
@@ -1222,6 +1354,65 @@ impl SubgraphStoreInner {
         store.set_history_blocks(&site, history_blocks, reorg_threshold)
     }
 
+    pub fn set_retry_policy(
+        &self,
+        deployment: &DeploymentLocator,
+        policy: Option<RetryPolicy>,
+    ) -> Result<(), StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.set_retry_policy(&site, policy.as_ref())
+    }
+
+    pub fn set_substreams_params(
+        &self,
+        deployment: &DeploymentLocator,
+        params: Option<BTreeMap<String, String>>,
+    ) -> Result<(), StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.set_substreams_params(&site, params.as_ref())
+    }
+
+    /// Forget that the deployment gave up on the offchain files from
+    /// `sources`, or on all of them if `sources` is empty, so that it
+    /// fetches them again when it is restarted
+    pub fn clear_offchain_failures(
+        &self,
+        deployment: &DeploymentLocator,
+        sources: &[String],
+    ) -> Result<usize, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.clear_offchain_failures(&site, sources)
+    }
+
+    /// The block pointer of the deployment together with the Firehose
+    /// cursor that block streams resume from
+    pub async fn head_and_cursor(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<(Option<BlockPtr>, FirehoseCursor), StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        let head = store.block_ptr(site.cheap_clone()).await?;
+        let cursor = store.block_cursor(site).await?;
+        Ok((head, cursor))
+    }
+
+    /// Remove the Firehose cursor of the deployment. This must only be
+    /// done while the deployment is not running
+    pub fn reset_firehose_cursor(&self, deployment: &DeploymentLocator) -> Result<(), StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.reset_block_cursor(&site)
+    }
+
     pub fn load_deployment(&self, site: Arc<Site>) -> Result<SubgraphDeploymentEntity, StoreError> {
         let src_store = self.for_site(&site)?;
         src_store.load_deployment(site)
@@ -1299,12 +1490,54 @@ impl EnsLookupTrait for EnsLookup {
     }
 }
 
+/// A read-only view of a deployment for the subgraph data sources that use
+/// it as their source
+struct SourceableStore {
+    site: Arc<Site>,
+    store: Arc<DeploymentStore>,
+    input_schema: InputSchema,
+}
+
+#[async_trait::async_trait]
+impl store::SourceableStore for SourceableStore {
+    fn get_changes(&self, block: BlockNumber) -> Result<Vec<EntityOperation>, StoreError> {
+        self.store.get_changes(self.site.cheap_clone(), block)
+    }
+
+    fn get(&self, key: &EntityKey, block: BlockNumber) -> Result<Option<Entity>, StoreError> {
+        self.store.get(self.site.cheap_clone(), key, block)
+    }
+
+    fn input_schema(&self) -> InputSchema {
+        self.input_schema.cheap_clone()
+    }
+
+    fn network(&self) -> &str {
+        &self.site.network
+    }
+
+    async fn block_ptr(&self) -> Result<Option<BlockPtr>, StoreError> {
+        self.store.block_ptr(self.site.cheap_clone()).await
+    }
+}
+
 #[async_trait::async_trait]
 impl SubgraphStoreTrait for SubgraphStore {
     fn ens_lookup(&self) -> Arc<dyn EnsLookupTrait> {
         Arc::new(EnsLookup::new(self.mirror.primary().clone()))
     }
 
+    fn http_cache(&self) -> Arc<dyn HttpCacheTrait> {
+        Arc::new(HttpCacheStorage::new(self.mirror.primary().clone()))
+    }
+
+    fn deployment_logs(&self) -> Arc<dyn DeploymentLogsTrait> {
+        Arc::new(DeploymentLogStorage::new(
+            self.mirror.primary().clone(),
+            ENV_VARS.mappings.deployment_log_retention,
+        ))
+    }
+
     // FIXME: This method should not get a node_id
     fn create_subgraph_deployment(
         &self,
@@ -1600,4 +1833,101 @@ impl SubgraphStoreTrait for SubgraphStore {
         let info = store.subgraph_info(site)?;
         Ok(info.instrument)
     }
+
+    fn retry_policy(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<RetryPolicy>, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.retry_policy(&site)
+    }
+
+    fn handler_limits(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<HandlerLimits>, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.handler_limits(&site)
+    }
+
+    fn mapping_env(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<BTreeMap<String, String>>, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.mapping_env(&site)
+    }
+
+    fn substreams_params(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<BTreeMap<String, String>>, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.substreams_params(&site)
+    }
+
+    fn ingestion(&self, deployment: &DeploymentLocator) -> Result<IngestionMode, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store
+            .ingestion(&site)
+            .map(|ingestion| ingestion.unwrap_or_default())
+    }
+
+    fn fetch_policy(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Option<FetchPolicy>, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.fetch_policy(&site)
+    }
+
+    fn offchain_failures(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Vec<OffchainFailure>, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.offchain_failures(&site)
+    }
+
+    fn record_offchain_failure(
+        &self,
+        deployment: &DeploymentLocator,
+        failure: &OffchainFailure,
+    ) -> Result<(), StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.record_offchain_failure(&site, failure)
+    }
+
+    fn sourceable(
+        &self,
+        id: &DeploymentHash,
+    ) -> Result<Arc<dyn store::SourceableStore>, StoreError> {
+        let (store, site) = self.store(id)?;
+        let input_schema = store
+            .find_layout(site.cheap_clone())?
+            .input_schema
+            .cheap_clone();
+
+        Ok(Arc::new(SourceableStore {
+            site,
+            store: store.cheap_clone(),
+            input_schema,
+        }))
+    }
 }
@@ -8,6 +8,7 @@ use std::{collections::BTreeMap, sync::Arc};
 use graph::blockchain::block_stream::FirehoseCursor;
 use graph::blockchain::BlockTime;
 use graph::components::store::{Batch, DeploymentCursorTracker, DerivedEntityQuery, ReadStore};
+use graph::components::tracing::{self, KeyValue};
 use graph::constraint_violation;
 use graph::data::store::IdList;
 use graph::data::subgraph::schema;
@@ -71,6 +72,14 @@ impl WritableSubgraphStore {
     fn load_indexes(&self, site: Arc<Site>) -> Result<IndexList, StoreError> {
         self.0.load_indexes(site)
     }
+
+    fn check_in_place_graft_base(&self, base: &Site) -> Result<(), StoreError> {
+        self.0.check_in_place_graft_base(base)
+    }
+
+    fn retire_in_place_graft_base(&self, base: &Site) -> Result<(), StoreError> {
+        self.0.retire_in_place_graft_base(base)
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -223,9 +232,16 @@ impl SyncStore {
 
     fn start_subgraph_deployment(&self, logger: &Logger) -> Result<(), StoreError> {
         retry::forever(&self.logger, "start_subgraph_deployment", || {
+            let mut in_place_base = None;
             let graft_base = match self.writable.graft_pending(&self.site.deployment)? {
                 Some((base_id, base_ptr)) => {
                     let src = self.store.layout(&base_id)?;
+                    // Something might have started to use the base since
+                    // the graft was deployed
+                    if self.writable.graft_in_place(&self.site.deployment)? {
+                        self.store.check_in_place_graft_base(&src.site)?;
+                        in_place_base = Some(src.site.cheap_clone());
+                    }
                     let deployment_entity = self.store.load_deployment(src.site.clone())?;
                     let indexes = self.store.load_indexes(src.site.clone())?;
                     Some((src, base_ptr, deployment_entity, indexes))
@@ -234,7 +250,13 @@ impl SyncStore {
             };
             self.writable
                 .start_subgraph(logger, self.site.clone(), graft_base)?;
-            self.store.primary_conn()?.copy_finished(self.site.as_ref())
+            self.store
+                .primary_conn()?
+                .copy_finished(self.site.as_ref())?;
+            if let Some(base) = in_place_base {
+                self.store.retire_in_place_graft_base(&base)?;
+            }
+            Ok(())
         })
     }
 
@@ -308,6 +330,17 @@ impl SyncStore {
         })
     }
 
+    fn get_at_block(
+        &self,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<Entity>, StoreError> {
+        retry::forever(&self.logger, "get_at_block", || {
+            self.writable
+                .get_at_block(self.site.cheap_clone(), key, block)
+        })
+    }
+
     fn transact_block_operations(
         &self,
         batch: &Batch,
@@ -384,6 +417,17 @@ impl SyncStore {
         })
     }
 
+    fn pause_subgraph(&self, site: &Site) -> Result<(), StoreError> {
+        retry::forever(&self.logger, "pause_subgraph", || {
+            let mut pconn = self.store.primary_conn()?;
+            pconn.transaction(|conn| -> Result<_, StoreError> {
+                let mut pconn = primary::Connection::new(conn);
+                let changes = pconn.pause_subgraph(site)?;
+                self.store.send_store_event(&StoreEvent::new(changes))
+            })
+        })
+    }
+
     async fn load_dynamic_data_sources(
         &self,
         block: BlockNumber,
@@ -714,9 +758,19 @@ impl Request {
                     // here will poison and shut down the queue
                     return Err(err.clone());
                 }
+                // A batch can contain many blocks, so its span is not part
+                // of the trace of any of them
+                let mut attributes =
+                    tracing::block_attributes(&store.site.deployment, &batch.block_ptr);
+                attributes.push(KeyValue::new("first_block", batch.first_block as i64));
+                attributes.push(KeyValue::new("entities", batch.entity_count() as i64));
+                let cx = tracing::root_span("store.write_batch", attributes);
                 let res = store
                     .transact_block_operations(batch.deref(), stopwatch)
                     .map(|()| ExecResult::Continue);
+                if let Err(e) = &res {
+                    tracing::record_error(&cx, e);
+                }
                 info!(store.logger, "Committed write batch";
                         "block_number" => batch.block_ptr.number,
                         "block_count" => batch.block_ptr.number - batch.first_block + 1,
@@ -730,9 +784,19 @@ impl Request {
                 block_ptr,
                 firehose_cursor,
                 processed: _,
-            } => store
-                .revert_block_operations(block_ptr.clone(), firehose_cursor)
-                .map(|()| ExecResult::Continue),
+            } => {
+                let cx = tracing::root_span(
+                    "store.revert",
+                    tracing::block_attributes(&store.site.deployment, block_ptr),
+                );
+                let res = store
+                    .revert_block_operations(block_ptr.clone(), firehose_cursor)
+                    .map(|()| ExecResult::Continue);
+                if let Err(e) = &res {
+                    tracing::record_error(&cx, e);
+                }
+                res
+            }
             Request::Stop => Ok(ExecResult::Stop),
         }
     }
@@ -1172,6 +1236,26 @@ impl Queue {
         }
     }
 
+    /// Get the entity for `key` as of `block` by looking at both the queue
+    /// and the store
+    fn get_at_block(
+        &self,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<Entity>, StoreError> {
+        let (op, query_block) = BlockTracker::find_map(&self.queue, |batch, at| {
+            batch.last_op(key, at.min(block)).map(|op| match op {
+                EntityOp::Write { key: _, entity } => Some(entity.clone()),
+                EntityOp::Remove { .. } => None,
+            })
+        });
+
+        match op {
+            Some(entity) => Ok(entity),
+            None => self.store.get_at_block(key, query_block.min(block)),
+        }
+    }
+
     /// Get many entities at once by looking at both the queue and the store
     fn get_many(
         &self,
@@ -1227,6 +1311,7 @@ impl Queue {
                 .get(&derived_query.entity_field)
                 .map(|v| &derived_query.value == v)
                 .unwrap_or(false)
+                && derived_query.filter.matches(entity)
         }
 
         fn effective_ops<'a>(
@@ -1240,7 +1325,9 @@ impl Queue {
                     EntityOp::Write { key, entity } if is_related(derived_query, entity) => {
                         Some((key.clone(), Some(entity.clone())))
                     }
-                    EntityOp::Write { .. } => None,
+                    // The entity might match in the database, but this
+                    // newer version does not
+                    EntityOp::Write { key, .. } => Some((key.clone(), None)),
                     EntityOp::Remove { key } => Some((key.clone(), None)),
                 })
         }
@@ -1277,6 +1364,18 @@ impl Queue {
             .collect();
         items_from_database.extend(items_from_queue);
 
+        // The database returned the first entities that are not in the
+        // queue; combined with the ones from the queue, we might now have
+        // too many
+        if let Some(first) = derived_query.filter.first {
+            if items_from_database.len() > first {
+                let mut entities: Vec<_> = items_from_database.into_iter().collect();
+                entities.sort_by(|(_, a), (_, b)| derived_query.filter.compare(a, b));
+                entities.truncate(first);
+                items_from_database = entities.into_iter().collect();
+            }
+        }
+
         Ok(items_from_database)
     }
 
@@ -1420,6 +1519,17 @@ impl Writer {
         }
     }
 
+    fn get_at_block(
+        &self,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<Entity>, StoreError> {
+        match self {
+            Writer::Sync(store) => store.get_at_block(key, block),
+            Writer::Async { queue, .. } => queue.get_at_block(key, block),
+        }
+    }
+
     fn get_many(
         &self,
         keys: BTreeSet<EntityKey>,
@@ -1551,6 +1661,14 @@ impl ReadStore for WritableStore {
         self.writer.get(key)
     }
 
+    fn get_at_block(
+        &self,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<Entity>, StoreError> {
+        self.writer.get_at_block(key, block)
+    }
+
     fn get_many(
         &self,
         keys: BTreeSet<EntityKey>,
@@ -1714,6 +1832,10 @@ impl WritableStoreTrait for WritableStore {
         self.store.unassign_subgraph(&self.store.site)
     }
 
+    fn pause_subgraph(&self) -> Result<(), StoreError> {
+        self.store.pause_subgraph(&self.store.site)
+    }
+
     async fn load_dynamic_data_sources(
         &self,
         manifest_idx_and_name: Vec<(u32, String)>,
@@ -116,6 +116,7 @@ impl FakeBlock {
         EthereumBlock {
             block: Arc::new(block),
             transaction_receipts: Vec::new(),
+            slim: false,
         }
     }
 
@@ -173,6 +173,8 @@ pub async fn create_subgraph(
         templates: vec![],
         chain: PhantomData,
         indexer_hints: None,
+        big_decimal: None,
+        handler_fuel: None,
     };
 
     create_subgraph_with_manifest(subgraph_id, schema, manifest, base).await
@@ -241,6 +243,8 @@ pub async fn create_test_subgraph_with_features(
         templates: vec![],
         chain: PhantomData,
         indexer_hints: None,
+        big_decimal: None,
+        handler_fuel: None,
     };
 
     let deployment_features = manifest.deployment_features();
@@ -559,6 +563,7 @@ async fn execute_subgraph_query_internal(
             QueryExecutionOptions {
                 resolver,
                 deadline,
+                partial_results: false,
                 max_first: std::u32::MAX,
                 max_skip: std::u32::MAX,
                 trace,
@@ -6,12 +6,13 @@ use std::time::Duration;
 
 use graph::blockchain::DataSource;
 use graph::components::store::BLOCK_NUMBER_MAX;
-use graph::data::store::scalar::Bytes;
+use graph::data::store::scalar::{BigDecimalPrecision, BigDecimalRounding, Bytes};
 use graph::data::store::Value;
 use graph::data::subgraph::schema::SubgraphError;
 use graph::data::subgraph::{
     Prune, LATEST_VERSION, SPEC_VERSION_0_0_4, SPEC_VERSION_0_0_7, SPEC_VERSION_0_0_8,
-    SPEC_VERSION_0_0_9, SPEC_VERSION_1_0_0, SPEC_VERSION_1_2_0,
+    SPEC_VERSION_0_0_9, SPEC_VERSION_1_0_0, SPEC_VERSION_1_2_0, SPEC_VERSION_1_3_0,
+    SPEC_VERSION_1_4_0, SPEC_VERSION_1_5_0,
 };
 use graph::data_source::offchain::OffchainDataSourceKind;
 use graph::data_source::DataSourceTemplate;
@@ -245,6 +246,69 @@ indexerHints:
     assert_eq!(manifest.history_blocks(), BLOCK_NUMBER_MAX);
 }
 
+#[tokio::test]
+async fn parse_big_decimal_precision() {
+    const YAML: &str = "
+dataSources: []
+schema:
+  file:
+    /: /ipfs/Qmschema
+specVersion: 1.3.0
+bigDecimal:
+  precision: 60
+  rounding: halfEven
+";
+
+    let manifest = resolve_manifest(YAML, SPEC_VERSION_1_3_0).await;
+    assert_eq!(
+        manifest.big_decimal_precision(),
+        BigDecimalPrecision {
+            precision: 60,
+            rounding: BigDecimalRounding::HalfEven
+        }
+    );
+
+    const NO_PRECISION: &str = "
+dataSources: []
+schema:
+  file:
+    /: /ipfs/Qmschema
+specVersion: 1.3.0
+";
+
+    let manifest = resolve_manifest(NO_PRECISION, SPEC_VERSION_1_3_0).await;
+    assert_eq!(
+        manifest.big_decimal_precision(),
+        BigDecimalPrecision::default()
+    );
+}
+
+#[tokio::test]
+async fn parse_handler_fuel() {
+    const YAML: &str = "
+dataSources: []
+schema:
+  file:
+    /: /ipfs/Qmschema
+specVersion: 1.5.0
+handlerFuel: 1000000
+";
+
+    let manifest = resolve_manifest(YAML, SPEC_VERSION_1_5_0).await;
+    assert_eq!(manifest.handler_fuel(), Some(1_000_000));
+
+    const NO_FUEL: &str = "
+dataSources: []
+schema:
+  file:
+    /: /ipfs/Qmschema
+specVersion: 1.5.0
+";
+
+    let manifest = resolve_manifest(NO_FUEL, SPEC_VERSION_1_5_0).await;
+    assert_eq!(manifest.handler_fuel(), None);
+}
+
 #[test]
 fn graft_failed_subgraph() {
     const YAML: &str = "
@@ -715,6 +779,61 @@ specVersion: 0.0.9
     assert_eq!(Some(9562481), end_block);
 }
 
+#[tokio::test]
+async fn parse_subgraph_data_source() {
+    const YAML: &str = "
+dataSources:
+  - kind: ethereum/contract
+    name: Factory
+    network: mainnet
+    source:
+      abi: Factory
+    mapping:
+      kind: ethereum/events
+      apiVersion: 0.0.4
+      language: wasm/assemblyscript
+      entities:
+        - TestEntity
+      file:
+        /: /ipfs/Qmmapping
+      abis:
+        - name: Factory
+          file:
+            /: /ipfs/Qmabi
+  - kind: subgraph
+    name: Things
+    network: mainnet
+    source:
+      address: QmSource
+      startBlock: 9562480
+    mapping:
+      apiVersion: 0.0.4
+      language: wasm/assemblyscript
+      entities:
+        - TestEntity
+      file:
+        /: /ipfs/Qmmapping
+      handlers:
+        - handler: handleThing
+          entity: Thing
+schema:
+  file:
+    /: /ipfs/Qmschema
+specVersion: 1.4.0
+";
+
+    let manifest = resolve_manifest(YAML, SPEC_VERSION_1_4_0).await;
+    let data_source = manifest.data_sources[1].as_subgraph().unwrap();
+
+    assert_eq!("QmSource", data_source.source.address.as_str());
+    assert_eq!(9562480, data_source.source.start_block);
+    assert_eq!(
+        vec!["Thing"],
+        data_source.entity_types().collect::<Vec<_>>()
+    );
+    assert_eq!(vec![0, 9562480], manifest.start_blocks());
+}
+
 #[tokio::test]
 async fn parse_block_handlers_with_both_polling_and_once_filter() {
     const YAML: &str = "
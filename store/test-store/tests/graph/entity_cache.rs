@@ -1,8 +1,8 @@
 use graph::blockchain::block_stream::FirehoseCursor;
 use graph::blockchain::BlockTime;
 use graph::components::store::{
-    DeploymentCursorTracker, DerivedEntityQuery, GetScope, LoadRelatedRequest, ReadStore,
-    StoredDynamicDataSource, WritableStore,
+    DeploymentCursorTracker, DerivedEntityFilter, DerivedEntityQuery, GetScope, LoadRelatedRequest,
+    ReadStore, StoredDynamicDataSource, WritableStore,
 };
 use graph::data::store::Id;
 use graph::data::subgraph::schema::{DeploymentCreate, SubgraphError, SubgraphHealth};
@@ -59,6 +59,14 @@ impl ReadStore for MockStore {
         Ok(self.get_many_res.get(key).cloned())
     }
 
+    fn get_at_block(
+        &self,
+        key: &EntityKey,
+        _block: BlockNumber,
+    ) -> Result<Option<Entity>, StoreError> {
+        Ok(self.get_many_res.get(key).cloned())
+    }
+
     fn get_many(
         &self,
         _keys: BTreeSet<EntityKey>,
@@ -149,6 +157,10 @@ impl WritableStore for MockStore {
         unimplemented!()
     }
 
+    fn pause_subgraph(&self) -> Result<(), StoreError> {
+        unimplemented!()
+    }
+
     async fn load_dynamic_data_sources(
         &self,
         _manifest_idx_and_name: Vec<(u32, String)>,
@@ -316,6 +328,12 @@ const ACCOUNT_GQL: &str = "
         email: String!
         age: Int!
         wallets: [Wallet!]! @derivedFrom(field: \"account\")
+        logins: [Login!]! @derivedFrom(field: \"email\", key: \"email\")
+    }
+
+    type Login @entity {
+        id: ID!
+        email: String!
     }
 
     interface Purse {
@@ -333,6 +351,7 @@ const ACCOUNT_GQL: &str = "
 const ACCOUNT: &str = "Account";
 const WALLET: &str = "Wallet";
 const PURSE: &str = "Purse";
+const LOGIN: &str = "Login";
 
 lazy_static! {
     static ref LOAD_RELATED_ID_STRING: String = String::from("loadrelatedsubgraph");
@@ -351,6 +370,7 @@ lazy_static! {
     static ref WALLET_TYPE: EntityType = LOAD_RELATED_SUBGRAPH.entity_type(WALLET).unwrap();
     static ref ACCOUNT_TYPE: EntityType = LOAD_RELATED_SUBGRAPH.entity_type(ACCOUNT).unwrap();
     static ref PURSE_TYPE: EntityType = LOAD_RELATED_SUBGRAPH.entity_type(PURSE).unwrap();
+    static ref LOGIN_TYPE: EntityType = LOAD_RELATED_SUBGRAPH.entity_type(LOGIN).unwrap();
 }
 
 fn remove_test_data(store: Arc<DieselSubgraphStore>) {
@@ -410,6 +430,8 @@ async fn insert_test_data(store: Arc<DieselSubgraphStore>) -> DeploymentLocator
         templates: vec![],
         chain: PhantomData,
         indexer_hints: None,
+        big_decimal: None,
+        handler_fuel: None,
     };
 
     // Create SubgraphDeploymentEntity
@@ -439,6 +461,9 @@ async fn insert_test_data(store: Arc<DieselSubgraphStore>) -> DeploymentLocator
     let wallet_entity_4 = create_wallet_operation("4", &id_two, 32_i32);
     // 1 account 0 wallets
     let test_entity_3 = create_account_entity("3", "Shaqueeena", "queensha@email.com", 28_i32);
+    // 2 logins for the email of account 1
+    let login_1 = create_login_operation("l1", "tonofjohn@email.com");
+    let login_2 = create_login_operation("l2", "tonofjohn@email.com");
     transact_entity_operations(
         &store,
         &deployment,
@@ -451,6 +476,8 @@ async fn insert_test_data(store: Arc<DieselSubgraphStore>) -> DeploymentLocator
             wallet_entity_2,
             wallet_entity_3,
             wallet_entity_4,
+            login_1,
+            login_2,
         ],
     )
     .await
@@ -480,6 +507,13 @@ fn create_wallet_operation(id: &str, account_id: &Id, balance: i32) -> EntityOpe
     }
 }
 
+fn create_login_operation(id: &str, email: &str) -> EntityOperation {
+    EntityOperation::Set {
+        key: LOGIN_TYPE.parse_key(id).unwrap(),
+        data: entity! { LOAD_RELATED_SUBGRAPH => id: id, email: email },
+    }
+}
+
 #[test]
 fn check_for_account_with_multiple_wallets() {
     run_store_test(|mut cache, _store, _deployment, _writable| async move {
@@ -572,6 +606,41 @@ fn check_for_non_existent_field() {
     });
 }
 
+#[test]
+fn check_for_related_entities_with_key() {
+    run_store_test(|mut cache, _store, _deployment, _writable| async move {
+        let request = |id: &str| LoadRelatedRequest {
+            entity_type: ACCOUNT_TYPE.clone(),
+            entity_field: "logins".into(),
+            entity_id: ACCOUNT_TYPE.parse_id(id).unwrap(),
+            causality_region: CausalityRegion::ONCHAIN,
+        };
+        let login =
+            |id: &str, email: &str| entity! { LOAD_RELATED_SUBGRAPH => id: id, email: email };
+
+        let result = cache.load_related(&request("1")).unwrap();
+        let expected = vec![
+            login("l1", "tonofjohn@email.com"),
+            login("l2", "tonofjohn@email.com"),
+        ];
+        assert_eq!(result, expected);
+
+        // Logins that were added in this block are found, too
+        cache
+            .set(
+                LOGIN_TYPE.parse_key("l3").unwrap(),
+                login("l3", "dinici@email.com"),
+            )
+            .unwrap();
+        let result = cache.load_related(&request("2")).unwrap();
+        assert_eq!(result, vec![login("l3", "dinici@email.com")]);
+
+        // An account that does not exist has no key and no logins
+        let result = cache.load_related(&request("4")).unwrap();
+        assert_eq!(result, vec![]);
+    });
+}
+
 #[test]
 fn check_for_insert_async_store() {
     run_store_test(|mut cache, store, deployment, _writable| async move {
@@ -703,6 +772,44 @@ fn check_for_delete_async_related() {
     });
 }
 
+#[test]
+fn check_for_filtered_related() {
+    run_store_test(|mut cache, _store, _deployment, _writable| async move {
+        let account_id = ACCOUNT_TYPE.parse_id("1").unwrap();
+        let request = LoadRelatedRequest {
+            entity_type: ACCOUNT_TYPE.clone(),
+            entity_field: "wallets".into(),
+            entity_id: account_id.clone(),
+            causality_region: CausalityRegion::ONCHAIN,
+        };
+
+        // Change a wallet in the cache so that the store's version of it
+        // would be among the first two, but the changed one is not
+        let wallet_3 = create_wallet_entity("3", &account_id, 10_i32);
+        cache
+            .set(WALLET_TYPE.parse_key("3").unwrap(), wallet_3)
+            .unwrap();
+
+        let filter = DerivedEntityFilter {
+            order_by: Some("balance".into()),
+            descending: true,
+            first: Some(2),
+            ..Default::default()
+        };
+        let result = cache.load_related_filtered(&request, &filter).unwrap();
+        let wallet_1 = create_wallet_entity("1", &account_id, 67_i32);
+        let wallet_2 = create_wallet_entity("2", &account_id, 92_i32);
+        assert_eq!(result, vec![wallet_2, wallet_1.clone()]);
+
+        let filter = DerivedEntityFilter {
+            values: vec![("balance".into(), Value::from(67_i32))],
+            ..Default::default()
+        };
+        let result = cache.load_related_filtered(&request, &filter).unwrap();
+        assert_eq!(result, vec![wallet_1]);
+    });
+}
+
 #[test]
 fn scoped_get() {
     run_store_test(|mut cache, _store, _deployment, _writable| async move {
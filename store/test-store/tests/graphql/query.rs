@@ -218,6 +218,8 @@ async fn setup(
             templates: vec![],
             chain: PhantomData,
             indexer_hints: None,
+            big_decimal: None,
+            handler_fuel: None,
         };
 
         insert_test_entities(store.subgraph_store().as_ref(), manifest, id_type).await
@@ -284,6 +286,13 @@ fn test_schema(id: DeploymentHash, id_type: IdType) -> InputSchema {
         media: [Media!]!
         release: Release! @derivedFrom(field: \"songs\")
         stats: [SongStat!]! @derivedFrom(field: \"id\")
+        lyrics: [Lyric!]! @derivedFrom(field: \"songSid\", key: \"sid\")
+    }
+
+    type Lyric @entity {
+        id: ID!
+        songSid: String!
+        text: String!
     }
 
     type SongStat @entity {
@@ -502,6 +511,14 @@ async fn insert_test_entities(
                 entity! { is => id: s[2], played: 15 },
             ],
         ),
+        (
+            "Lyric",
+            vec![
+                entity! { is => id: "l1", songSid: "s1", text: "Cheese, cheese" },
+                entity! { is => id: "l2", songSid: "s1", text: "More cheese" },
+                entity! { is => id: "l3", songSid: "s2", text: "We will rock" },
+            ],
+        ),
         (
             "BandReview",
             vec![
@@ -911,6 +928,134 @@ fn can_query_one_to_many_relationships_in_both_directions() {
     })
 }
 
+#[test]
+fn can_filter_and_order_derived_fields() {
+    const QUERY: &str = "
+    query {
+        musicians(first: 100, orderBy: id) {
+            name
+            latest: writtenSongs(first: 1, orderBy: title, orderDirection: desc) {
+                title
+            }
+            notPop: writtenSongs(where: { title_not: \"Pop Tune\" }, orderBy: title) {
+                title
+            }
+        }
+        bands(first: 100, orderBy: id) {
+            name
+            members(where: { name_not: \"John\" }, orderBy: name, orderDirection: desc) {
+                name
+            }
+        }
+    }";
+
+    run_query(QUERY, |result, _| {
+        fn titles(titles: Vec<&str>) -> Vec<r::Value> {
+            titles
+                .into_iter()
+                .map(|title| object! { title: title })
+                .collect()
+        }
+
+        fn names(names: Vec<&str>) -> Vec<r::Value> {
+            names
+                .into_iter()
+                .map(|name| object! { name: name })
+                .collect()
+        }
+
+        let exp = object! {
+            musicians: vec![
+                object! {
+                    name: "John",
+                    latest: titles(vec!["Pop Tune"]),
+                    notPop: titles(vec!["Cheesy Tune"])
+                },
+                object! {
+                    name: "Lisa",
+                    latest: titles(vec!["Rock Tune"]),
+                    notPop: titles(vec!["Rock Tune"])
+                },
+                object! {
+                    name: "Tom",
+                    latest: titles(vec!["Folk Tune"]),
+                    notPop: titles(vec!["Folk Tune"])
+                },
+                object! {
+                    name: "Valerie",
+                    latest: titles(vec![]),
+                    notPop: titles(vec![])
+                },
+            ],
+            bands: vec![
+                object! { name: "The Musicians", members: names(vec!["Tom", "Lisa"]) },
+                object! { name: "The Amateurs", members: names(vec!["Tom"]) },
+            ]
+        };
+
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    })
+}
+
+#[test]
+fn can_defer_and_stream_top_level_fields() {
+    const QUERY: &str = "
+    query {
+        musicians(first: 3, orderBy: id) @stream(initialCount: 1) {
+            name
+        }
+        ... @defer(label: \"bands\") {
+            bands(orderBy: id) {
+                name
+            }
+        }
+    }";
+
+    run_test_sequentially(|store| async move {
+        let deployment = setup_readonly(store.as_ref()).await;
+        let runner = Arc::new(GraphQlRunner::new(
+            &LOGGER,
+            STORE.clone(),
+            SUBSCRIPTION_MANAGER.clone(),
+            LOAD_MANAGER.clone(),
+            METRICS_REGISTRY.clone(),
+        ));
+        let query = Query::new(q::parse_query(QUERY).unwrap().into_static(), None, false);
+        let target = QueryTarget::Deployment(deployment.hash.clone(), Default::default());
+
+        let payloads: Vec<_> = runner
+            .run_incremental_query(query, target)
+            .await
+            .map(|payload| payload.to_json())
+            .collect()
+            .await;
+
+        let exp = vec![
+            serde_json::json!({
+                "data": { "musicians": [{ "name": "John" }] },
+                "hasNext": true
+            }),
+            serde_json::json!({
+                "incremental": [{
+                    "data": { "bands": [{ "name": "The Musicians" }, { "name": "The Amateurs" }] },
+                    "path": [],
+                    "label": "bands"
+                }],
+                "hasNext": true
+            }),
+            serde_json::json!({
+                "incremental": [{
+                    "items": [{ "name": "Lisa" }, { "name": "Tom" }],
+                    "path": ["musicians", 1]
+                }],
+                "hasNext": false
+            }),
+        ];
+        assert_eq!(exp, payloads);
+    })
+}
+
 #[test]
 fn can_query_many_to_many_relationship() {
     const QUERY: &str = "
@@ -1372,6 +1517,61 @@ fn can_query_with_child_filter_on_derived_list_type_field() {
     })
 }
 
+#[test]
+fn can_query_derived_field_with_key() {
+    const QUERY: &str = "
+    query {
+        songs(first: 100, orderBy: id) {
+            title
+            lyrics(orderBy: id) { id text }
+        }
+    }";
+
+    run_query(QUERY, |result, _| {
+        let exp = object! {
+            songs: vec![
+                object! {
+                    title: "Cheesy Tune",
+                    lyrics: vec![
+                        object! { id: "l1", text: "Cheese, cheese" },
+                        object! { id: "l2", text: "More cheese" },
+                    ]
+                },
+                object! {
+                    title: "Rock Tune",
+                    lyrics: vec![ object! { id: "l3", text: "We will rock" } ]
+                },
+                object! { title: "Pop Tune", lyrics: Vec::<r::Value>::new() },
+                object! { title: "Folk Tune", lyrics: Vec::<r::Value>::new() },
+            ]
+        };
+
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    })
+}
+
+#[test]
+fn can_query_with_child_filter_on_derived_field_with_key() {
+    const QUERY: &str = "
+    query {
+        songs(first: 100, orderBy: id, where: { lyrics_: { text_contains: \"rock\" } }) {
+            title
+        }
+    }";
+
+    run_query(QUERY, |result, _| {
+        let exp = object! {
+            songs: vec![
+                object! { title: "Rock Tune" },
+            ]
+        };
+
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    })
+}
+
 #[test]
 fn can_query_with_child_filter_on_named_type_field() {
     const QUERY: &str = "
@@ -4,6 +4,7 @@ pub mod postgres {
     pub mod graft;
     pub mod relational;
     pub mod relational_bytes;
+    pub mod retention;
     pub mod store;
     pub mod subgraph;
     pub mod writable;
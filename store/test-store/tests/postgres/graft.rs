@@ -146,6 +146,8 @@ async fn insert_test_data(store: Arc<DieselSubgraphStore>) -> DeploymentLocator
         templates: vec![],
         chain: PhantomData,
         indexer_hints: None,
+        big_decimal: None,
+        handler_fuel: None,
     };
 
     // Create SubgraphDeploymentEntity
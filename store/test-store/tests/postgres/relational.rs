@@ -2,7 +2,10 @@
 use diesel::connection::SimpleConnection as _;
 use diesel::pg::PgConnection;
 use graph::components::store::write::{EntityModification, RowGroup};
+use graph::data::query::EntityHistoryQuery;
 use graph::data::store::scalar;
+use graph::data::store::IdList;
+use graph::data_source::CausalityRegion;
 use graph::entity;
 use graph::prelude::{
     o, slog, tokio, web3::types::H256, DeploymentHash, Entity, EntityCollection, EntityFilter,
@@ -15,7 +18,7 @@ use graph_store_postgres::layout_for_tests::LayoutCache;
 use graph_store_postgres::layout_for_tests::SqlName;
 use hex_literal::hex;
 use lazy_static::lazy_static;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::panic;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -24,7 +27,9 @@ use std::time::Duration;
 
 use graph::{
     components::store::AttributeNames,
-    data::store::scalar::{BigDecimal, BigInt, Bytes, Timestamp},
+    data::store::scalar::{
+        BigDecimal, BigDecimalPrecision, BigDecimalRounding, BigInt, Bytes, Timestamp,
+    },
 };
 use graph_store_postgres::{
     layout_for_tests::make_dummy_site,
@@ -739,6 +744,91 @@ fn serialize_bigdecimal() {
     });
 }
 
+#[test]
+fn bigdecimal_keeps_precision() {
+    run_test(|conn, layout| {
+        // More significant digits than the default precision of 34
+        const DECIMAL: &str = "1234567890.1234567890123456789012345678901234567891";
+        let precision = BigDecimalPrecision {
+            precision: 60,
+            rounding: BigDecimalRounding::HalfEven,
+        };
+        let decimal = BigDecimal::from_str_with_precision(DECIMAL, &precision).unwrap();
+        assert_eq!(DECIMAL, decimal.to_string());
+
+        let mut entity = SCALAR_ENTITY.clone();
+        entity.set("bigDecimal", decimal.clone()).unwrap();
+        insert_entity(conn, layout, &*SCALAR_TYPE, vec![entity]);
+
+        let actual = layout
+            .find(
+                conn,
+                &SCALAR_TYPE.parse_key("one").unwrap(),
+                BLOCK_NUMBER_MAX,
+            )
+            .expect("Failed to read Scalar[one]")
+            .unwrap();
+        assert_eq!(
+            Some(&Value::BigDecimal(decimal.clone())),
+            actual.get("bigDecimal")
+        );
+
+        let ids = IdList::try_from_iter(
+            SCALAR_TYPE.id_type().unwrap(),
+            vec![SCALAR_TYPE.parse_id("one").unwrap()].into_iter(),
+        )
+        .unwrap();
+        let mut id_map = BTreeMap::default();
+        id_map.insert((SCALAR_TYPE.clone(), CausalityRegion::ONCHAIN), ids);
+        let entities = layout
+            .find_many(conn, &id_map, BLOCK_NUMBER_MAX)
+            .expect("Failed to read many Scalars");
+        let actual = entities
+            .get(&SCALAR_TYPE.parse_key("one").unwrap())
+            .expect("Scalar[one] was found");
+        assert_eq!(Some(&Value::BigDecimal(decimal)), actual.get("bigDecimal"));
+    });
+}
+
+#[test]
+fn entity_history() {
+    run_test(|conn, layout| {
+        // Scalar[one] is written at block 0 and updated at block 2,
+        // Scalar[two] is written at block 1
+        insert_entity(conn, layout, &*SCALAR_TYPE, vec![SCALAR_ENTITY.clone()]);
+        let mut two = SCALAR_ENTITY.clone();
+        two.set("id", "two").unwrap();
+        insert_entity_at(conn, layout, &*SCALAR_TYPE, vec![two], 1);
+        let mut one = SCALAR_ENTITY.clone();
+        one.set("string", "updated").unwrap();
+        update_entity_at(conn, layout, &*SCALAR_TYPE, vec![one], 2);
+
+        let mut history = |id: Option<&str>, to_block: BlockNumber| {
+            let query = EntityHistoryQuery {
+                entity: "Scalar".to_string(),
+                id: id.map(str::to_string),
+                from_block: 0,
+                to_block,
+                first: 100,
+                skip: 0,
+            };
+            layout
+                .entity_history(conn, &query)
+                .expect("Failed to read the history of Scalar")
+                .versions
+                .into_iter()
+                .map(|version| (version.block_from, version.block_to))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![(0, Some(2)), (1, None), (2, None)], history(None, 3));
+        assert_eq!(vec![(0, Some(2)), (2, None)], history(Some("one"), 3));
+        assert_eq!(vec![(1, None)], history(Some("two"), 3));
+        // The update at block 2 is outside of the range
+        assert_eq!(vec![(0, None), (1, None)], history(None, 1));
+    });
+}
+
 fn count_scalar_entities(conn: &mut PgConnection, layout: &Layout) -> usize {
     let filter = EntityFilter::Or(vec![
         EntityFilter::Equal("bool".into(), true.into()),
@@ -0,0 +1,283 @@
+use std::{future::Future, sync::Arc};
+
+use graph::{
+    blockchain::{block_stream::FirehoseCursor, BlockPtr, BlockTime},
+    components::{
+        metrics::stopwatch::StopwatchMetrics,
+        store::{
+            AttributeNames, BlockNumber, DeploymentLocator, EntityCache, EntityCollection,
+            EntityOperation, EntityQuery, PruneReporter, PruneRequest, ReadStore,
+            SubgraphStore as _, WritableStore,
+        },
+    },
+    data::{
+        store::{scalar::Bytes, Entity, Id, Value, ID},
+        subgraph::DeploymentHash,
+    },
+    entity,
+    prelude::lazy_static,
+};
+use graph_store_postgres::{Store as DieselStore, SubgraphStore};
+use test_store::{create_test_subgraph, run_test_sequentially, BLOCKS, LOGGER, METRICS_REGISTRY};
+
+const SCHEMA: &str = r#"
+type Price @entity(retention: "1h") {
+    id: ID!
+    price: Int!
+}
+
+type Transfer @entity(immutable: true, retention: "1h") {
+    id: ID!
+    amount: Int!
+}"#;
+
+fn minutes(n: u32) -> BlockTime {
+    BlockTime::since_epoch(n as i64 * 60, 0)
+}
+
+lazy_static! {
+    static ref TIMES: Vec<BlockTime> = vec![minutes(0), minutes(30), minutes(70), minutes(150)];
+}
+
+struct Progress;
+impl PruneReporter for Progress {}
+
+fn remove_test_data(store: Arc<SubgraphStore>) {
+    store
+        .delete_all_entities_for_test_use_only()
+        .expect("deleting test entities succeeds");
+}
+
+/// Write the prices `(id, price)` and a transfer with id `t<block number>`
+/// at `block_ptr`, together with the PoI entity that records the block
+/// time like the subgraph runner would
+async fn insert(
+    store: &Arc<dyn WritableStore>,
+    deployment: &DeploymentLocator,
+    block_ptr: BlockPtr,
+    block_time: BlockTime,
+    prices: Vec<(&str, i32)>,
+) {
+    let schema = ReadStore::input_schema(store);
+    let price_type = schema.entity_type("Price").unwrap();
+    let transfer_type = schema.entity_type("Transfer").unwrap();
+    let mut ops: Vec<_> = prices
+        .into_iter()
+        .map(|(id, price)| EntityOperation::Set {
+            key: price_type.parse_key(id).unwrap(),
+            data: entity! { schema => id: id, price: price },
+        })
+        .collect();
+    let transfer = format!("t{}", block_ptr.number);
+    ops.push(EntityOperation::Set {
+        key: transfer_type.parse_key(transfer.as_str()).unwrap(),
+        data: entity! { schema => id: transfer.as_str(), amount: block_ptr.number },
+    });
+    let poi = schema
+        .make_entity(vec![
+            (ID.clone(), Value::from("test")),
+            (schema.poi_digest(), Value::from(Bytes::from(vec![0u8]))),
+            (
+                schema.poi_block_time(),
+                Value::Int8(block_time.as_secs_since_epoch()),
+            ),
+        ])
+        .unwrap();
+    ops.push(EntityOperation::Set {
+        key: schema.poi_type().key(Id::String("test".into())),
+        data: poi,
+    });
+
+    let mut entity_cache = EntityCache::new(Arc::new(store.clone()));
+    entity_cache.append(ops);
+    let mods = entity_cache
+        .as_modifications(block_ptr.number)
+        .expect("failed to convert to modifications")
+        .modifications;
+    let stopwatch_metrics = StopwatchMetrics::new(
+        LOGGER.clone(),
+        deployment.hash.clone(),
+        "transact",
+        METRICS_REGISTRY.clone(),
+        store.shard().to_string(),
+    );
+    store
+        .transact_block_operations(
+            block_ptr,
+            block_time,
+            FirehoseCursor::None,
+            mods,
+            &stopwatch_metrics,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            false,
+            false,
+        )
+        .await
+        .unwrap();
+}
+
+struct TestEnv {
+    store: Arc<DieselStore>,
+    deployment: DeploymentLocator,
+}
+
+impl TestEnv {
+    /// The entities of type `entity_type` visible at `block`
+    #[track_caller]
+    fn find(&self, entity_type: &str, block: BlockNumber) -> Vec<Entity> {
+        let entity_type = self
+            .store
+            .subgraph_store()
+            .input_schema(&self.deployment.hash)
+            .unwrap()
+            .entity_type(entity_type)
+            .unwrap();
+        let query = EntityQuery::new(
+            self.deployment.hash.clone(),
+            block,
+            EntityCollection::All(vec![(entity_type, AttributeNames::All)]),
+        );
+        self.store
+            .subgraph_store()
+            .find(query)
+            .expect("query succeeds")
+    }
+
+    /// The prices visible at `block` as `(id, price)`
+    #[track_caller]
+    fn prices(&self, block: BlockNumber) -> Vec<(String, i32)> {
+        self.find("Price", block)
+            .into_iter()
+            .map(|entity| {
+                let price = match entity.get("price") {
+                    Some(Value::Int(price)) => *price,
+                    _ => panic!("price is an Int"),
+                };
+                (entity.id().to_string(), price)
+            })
+            .collect()
+    }
+
+    /// The ids of the transfers visible at `block`
+    #[track_caller]
+    fn transfers(&self, block: BlockNumber) -> Vec<String> {
+        self.find("Transfer", block)
+            .into_iter()
+            .map(|entity| entity.id().to_string())
+            .collect()
+    }
+
+    async fn prune_by_retention(&self, final_block: BlockNumber) {
+        self.store
+            .subgraph_store()
+            .prune_by_retention(Box::new(Progress), &self.deployment, final_block)
+            .await
+            .expect("pruning by retention works");
+    }
+}
+
+fn run_test<R, F>(test: F)
+where
+    F: FnOnce(TestEnv) -> R + Send + 'static,
+    R: Future<Output = ()> + Send + 'static,
+{
+    run_test_sequentially(|store| async move {
+        let subgraph_store = store.subgraph_store();
+        // Reset state before starting
+        remove_test_data(subgraph_store.clone());
+
+        let hash = DeploymentHash::new("retentionSubgraph").unwrap();
+        let loc = create_test_subgraph(&hash, SCHEMA).await;
+        let writable = store
+            .subgraph_store()
+            .writable(LOGGER.clone(), loc.id, Arc::new(Vec::new()))
+            .await
+            .expect("we can get a writable store");
+
+        // Price p1 changes in every block, p2 never changes. That creates
+        // versions [0,1), [1,2), [2,3), [3,) for p1 and [0,) for p2
+        insert(
+            &writable,
+            &loc,
+            BLOCKS[0].clone(),
+            TIMES[0],
+            vec![("p1", 0), ("p2", 10)],
+        )
+        .await;
+        for i in 1..4 {
+            insert(
+                &writable,
+                &loc,
+                BLOCKS[i].clone(),
+                TIMES[i],
+                vec![("p1", i as i32)],
+            )
+            .await;
+        }
+        writable.flush().await.unwrap();
+
+        let env = TestEnv {
+            store: store.clone(),
+            deployment: loc.clone(),
+        };
+        test(env).await;
+    });
+}
+
+fn prices(prices: &[(&str, i32)]) -> Vec<(String, i32)> {
+    prices
+        .iter()
+        .map(|(id, price)| (id.to_string(), *price))
+        .collect()
+}
+
+#[test]
+fn prune_by_retention() {
+    run_test(|env| async move {
+        // As of block 1, everything is within the retention of 1h
+        env.prune_by_retention(1).await;
+        assert_eq!(prices(&[("p1", 0), ("p2", 10)]), env.prices(0));
+
+        // As of block 2 at 70m, block 1 at 30m is the first block in the
+        // retention window, and the version of p1 that was closed at block
+        // 1 gets removed
+        env.prune_by_retention(2).await;
+        assert_eq!(prices(&[("p2", 10)]), env.prices(0));
+        assert_eq!(prices(&[("p1", 1), ("p2", 10)]), env.prices(1));
+
+        // As of block 3 at 150m, blocks 1 and 2 are outside the retention
+        // window, but current versions are never removed
+        env.prune_by_retention(3).await;
+        assert_eq!(prices(&[("p2", 10)]), env.prices(1));
+        assert_eq!(prices(&[("p2", 10)]), env.prices(2));
+        assert_eq!(prices(&[("p1", 3), ("p2", 10)]), env.prices(3));
+    })
+}
+
+#[test]
+fn prune_immutable_by_retention() {
+    run_test(|env| async move {
+        let all = ["t0", "t1", "t2", "t3"].map(String::from).to_vec();
+
+        // As of block 3 at 150m, blocks 0 to 2 are outside the retention
+        // window, but time-travel queries can still reach all of them
+        env.prune_by_retention(3).await;
+        assert_eq!(all, env.transfers(3));
+
+        // Prune to 2 blocks of history, which moves the earliest block to 1
+        let req = PruneRequest::new(&env.deployment, 2, 1, 0, 3).unwrap();
+        env.store
+            .subgraph_store()
+            .prune(Box::new(Progress), &env.deployment, req)
+            .await
+            .expect("pruning works");
+        assert_eq!(all, env.transfers(3));
+
+        // Now the transfer from block 0 is outside the retention window and
+        // before the earliest block
+        env.prune_by_retention(3).await;
+        assert_eq!(all[1..].to_vec(), env.transfers(3));
+    })
+}
@@ -174,6 +174,8 @@ async fn insert_test_data(store: Arc<DieselSubgraphStore>) -> DeploymentLocator
         templates: vec![],
         chain: PhantomData,
         indexer_hints: None,
+        big_decimal: None,
+        handler_fuel: None,
     };
 
     // Create SubgraphDeploymentEntity
@@ -1129,6 +1131,7 @@ fn mock_data_source() -> graph_chain_ethereum::DataSource {
             entities: vec![],
             abis: vec![],
             event_handlers: vec![],
+            fallback_event_handler: None,
             call_handlers: vec![],
             block_handlers: vec![],
             link: Link {
@@ -1266,6 +1269,8 @@ fn entity_changes_are_fired_and_forwarded_to_subscriptions() {
             templates: vec![],
             chain: PhantomData,
             indexer_hints: None,
+            big_decimal: None,
+            handler_fuel: None,
         };
 
         let deployment =
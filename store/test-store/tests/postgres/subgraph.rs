@@ -180,6 +180,8 @@ fn create_subgraph() {
             templates: vec![],
             chain: PhantomData,
             indexer_hints: None,
+            big_decimal: None,
+            handler_fuel: None,
         };
         let deployment = DeploymentCreate::new(String::new(), &manifest, None);
         let node_id = NodeId::new("left").unwrap();
@@ -8,7 +8,9 @@ use std::collections::BTreeSet;
 use std::marker::PhantomData;
 use test_store::*;
 
-use graph::components::store::{DeploymentLocator, DerivedEntityQuery, WritableStore};
+use graph::components::store::{
+    DeploymentLocator, DerivedEntityFilter, DerivedEntityQuery, WritableStore,
+};
 use graph::data::subgraph::*;
 use graph::semver::Version;
 use graph::{entity, prelude::*};
@@ -51,6 +53,8 @@ async fn insert_test_data(store: Arc<DieselSubgraphStore>) -> DeploymentLocator
         templates: vec![],
         chain: PhantomData,
         indexer_hints: None,
+        big_decimal: None,
+        handler_fuel: None,
     };
 
     // Create SubgraphDeploymentEntity
@@ -200,6 +204,7 @@ fn count_get_derived(writable: &dyn WritableStore) -> i32 {
         entity_field: Word::from("id"),
         value: key.entity_id.clone(),
         causality_region: CausalityRegion::ONCHAIN,
+        filter: DerivedEntityFilter::default(),
     };
     let map = writable.get_derived(&query).unwrap();
     let counter = map.get(&key).unwrap();
@@ -236,6 +241,55 @@ fn get_derived_nobatch() {
     get_with_pending(false, count_get_derived);
 }
 
+/// Get the count as of `block` using `WritableStore::get_at_block`
+fn count_at_block(writable: &dyn WritableStore, block: BlockNumber) -> Option<i32> {
+    writable
+        .get_at_block(&count_key("1"), block)
+        .unwrap()
+        .map(|counter| counter.get("count").unwrap().as_int().unwrap())
+}
+
+/// Test that looking up entities as of earlier blocks sees the changes
+/// that are still queued as well as the ones in the database
+fn get_at_block_with_pending(batch: bool) {
+    run_test(move |store, writable, deployment| async move {
+        let subgraph_store = store.subgraph_store();
+
+        if !batch {
+            writable.deployment_synced(block_pointer(0)).unwrap();
+        }
+
+        for count in 1..4 {
+            insert_count(&subgraph_store, &deployment, count).await;
+        }
+        pause_writer(&deployment).await;
+        for count in 4..7 {
+            insert_count(&subgraph_store, &deployment, count).await;
+        }
+
+        assert_eq!(None, count_at_block(writable.as_ref(), 0));
+        for block in 1..7 {
+            assert_eq!(Some(block), count_at_block(writable.as_ref(), block));
+        }
+
+        writable.flush().await.unwrap();
+        assert_eq!(None, count_at_block(writable.as_ref(), 0));
+        for block in 1..7 {
+            assert_eq!(Some(block), count_at_block(writable.as_ref(), block));
+        }
+    })
+}
+
+#[test]
+fn get_at_block_batch() {
+    get_at_block_with_pending(true);
+}
+
+#[test]
+fn get_at_block_nobatch() {
+    get_at_block_with_pending(false);
+}
+
 #[test]
 fn restart() {
     run_test(|store, writable, deployment| async move {
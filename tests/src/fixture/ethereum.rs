@@ -10,16 +10,16 @@ use graph::blockchain::client::ChainClient;
 use graph::blockchain::{BlockPtr, TriggersAdapterSelector};
 use graph::cheap_clone::CheapClone;
 use graph::prelude::ethabi::ethereum_types::H256;
-use graph::prelude::web3::types::{Address, Log, Transaction, H160};
+use graph::prelude::web3::types::{Address, Block, Log, Transaction, H160};
 use graph::prelude::{ethabi, tiny_keccak, LightEthereumBlock, ENV_VARS};
 use graph::{blockchain::block_stream::BlockWithTriggers, prelude::ethabi::ethereum_types::U64};
 use graph_chain_ethereum::network::EthereumNetworkAdapters;
 use graph_chain_ethereum::trigger::LogRef;
-use graph_chain_ethereum::Chain;
 use graph_chain_ethereum::{
     chain::BlockFinality,
     trigger::{EthereumBlockTriggerType, EthereumTrigger},
 };
+use graph_chain_ethereum::{Chain, HeadBlock};
 
 pub async fn chain(
     test_name: &str,
@@ -63,6 +63,7 @@ pub async fn chain(
         eth_adapters,
         ENV_VARS.reorg_threshold,
         ENV_VARS.ingestor_polling_interval,
+        HeadBlock::Latest,
         // We assume the tested chain is always ingestible for now
         true,
     );
@@ -76,11 +77,11 @@ pub async fn chain(
 pub fn genesis() -> BlockWithTriggers<graph_chain_ethereum::Chain> {
     let ptr = test_ptr(0);
     BlockWithTriggers::<graph_chain_ethereum::Chain> {
-        block: BlockFinality::Final(Arc::new(LightEthereumBlock {
+        block: BlockFinality::Final(Arc::new(LightEthereumBlock::from(Block {
             hash: Some(H256::from_slice(ptr.hash.as_slice())),
             number: Some(U64::from(ptr.number)),
             ..Default::default()
-        })),
+        }))),
         trigger_data: vec![EthereumTrigger::Block(ptr, EthereumBlockTriggerType::End)],
     }
 }
@@ -121,13 +122,13 @@ pub fn empty_block(parent_ptr: BlockPtr, ptr: BlockPtr) -> BlockWithTriggers<Cha
     }];
 
     BlockWithTriggers::<graph_chain_ethereum::Chain> {
-        block: BlockFinality::Final(Arc::new(LightEthereumBlock {
+        block: BlockFinality::Final(Arc::new(LightEthereumBlock::from(Block {
             hash: Some(H256::from_slice(ptr.hash.as_slice())),
             number: Some(U64::from(ptr.number)),
             parent_hash: H256::from_slice(parent_ptr.hash.as_slice()),
             transactions,
             ..Default::default()
-        })),
+        }))),
         trigger_data: vec![EthereumTrigger::Block(ptr, EthereumBlockTriggerType::End)],
     }
 }
@@ -45,7 +45,7 @@ use graph::schema::InputSchema;
 use graph_chain_ethereum::chain::RuntimeAdapterBuilder;
 use graph_chain_ethereum::network::EthereumNetworkAdapters;
 use graph_chain_ethereum::Chain;
-use graph_core::polling_monitor::{arweave_service, ipfs_service};
+use graph_core::polling_monitor::{arweave_service, http_service, ipfs_service};
 use graph_core::{
     SubgraphAssignmentProvider as IpfsSubgraphAssignmentProvider, SubgraphInstanceManager,
     SubgraphRegistrar as IpfsSubgraphRegistrar, SubgraphTriggerProcessor,
@@ -480,6 +480,8 @@ pub async fn setup<C: Blockchain>(
         env_vars.mappings.max_ipfs_file_bytes,
         env_vars.mappings.ipfs_timeout,
         env_vars.mappings.ipfs_request_limit,
+        None,
+        None,
     );
 
     let arweave_resolver = Arc::new(ArweaveClient::default());
@@ -490,6 +492,15 @@ pub async fn setup<C: Blockchain>(
             0 => FileSizeLimit::Unlimited,
             n => FileSizeLimit::MaxBytes(n as u64),
         },
+        env_vars.mappings.ipfs_timeout,
+        None,
+    );
+    let http_service = http_service(
+        env_vars.mappings.file_http_allowed_hosts.clone(),
+        env_vars.mappings.max_ipfs_file_bytes,
+        env_vars.mappings.ipfs_timeout,
+        env_vars.mappings.ipfs_request_limit,
+        None,
     );
     let sg_count = Arc::new(SubgraphCountMetric::new(mock_registry.cheap_clone()));
 
@@ -504,6 +515,7 @@ pub async fn setup<C: Blockchain>(
         link_resolver.cheap_clone(),
         ipfs_service,
         arweave_service,
+        http_service,
         static_filters,
     );
 